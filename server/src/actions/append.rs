@@ -0,0 +1,58 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `APPEND` queries
+//! This module provides functions to work with `APPEND` queries
+//!
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::limits::WriteError};
+
+action!(
+    /// Run an `APPEND` query. This atomically appends the bytes to the value (creating the
+    /// key if it doesn't exist) and returns the new length of the value
+    /// ## Syntax
+    /// `APPEND <key> <bytes>`
+    fn append(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            let ret = unsafe {
+                // UNSAFE(@ohsayan): This is completely safe as we've already checked
+                // that there are exactly 2 arguments
+                kve.append(SharedSlice::new(act.next_unchecked()), act.next_unchecked())
+            };
+            match ret {
+                Ok(len) => con.write_usize(len).await?,
+                Err(WriteError::Encoding) => return util::err(P::RCODE_ENCODING_ERROR),
+                Err(WriteError::TooLarge) => return util::err(P::RSTRING_TOO_LARGE),
+                Err(WriteError::Unreadable) => return util::err(P::RCODE_SERVER_ERR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
@@ -0,0 +1,118 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Bit actions
+//!
+//! Actions to read and modify single bits of values in key/value tables. Bit `0` is the most
+//! significant bit of the first byte of the value
+
+use crate::{
+    actions::ActionResult, corestore::SharedSlice, dbnet::prelude::*, kvengine::limits::WriteError,
+};
+
+/// The largest bit offset that can be set. This keeps a single `SETBIT` from growing a value
+/// past 512MB
+const MAX_BIT_OFFSET: usize = u32::MAX as usize;
+
+/// Parse a bit offset
+fn parse_bit_offset<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<usize> {
+    match String::from_utf8_lossy(raw).parse::<usize>() {
+        Ok(offset) if offset <= MAX_BIT_OFFSET => Ok(offset),
+        Ok(_) => util::err(P::RSTRING_OUT_OF_RANGE),
+        Err(_) => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+action! {
+    /// Run a `SETBIT` query. This sets (or clears) the bit at `offset`, zero-padding the value
+    /// if needed, and returns the previous value of the bit. The key is created if it doesn't
+    /// exist
+    /// ## Syntax
+    /// `SETBIT <key> <offset> <0 | 1>`
+    fn setbit(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let (key, offset, bit) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 3 arguments
+            (
+                act.next_unchecked_bytes(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+            )
+        };
+        let offset = parse_bit_offset::<P>(offset)?;
+        let bit = match bit {
+            b"0" => false,
+            b"1" => true,
+            _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            match kve.set_bit(key, offset, bit) {
+                Ok(previous) => con.write_usize(previous as usize).await?,
+                Err(WriteError::Encoding) => return util::err(P::RCODE_ENCODING_ERROR),
+                Err(WriteError::TooLarge) => return util::err(P::RSTRING_TOO_LARGE),
+                Err(WriteError::Unreadable) => return util::err(P::RCODE_SERVER_ERR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Run a `GETBIT` query. Bits past the end of the value are zero
+    /// ## Syntax
+    /// `GETBIT <key> <offset>`
+    fn getbit(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (key, offset) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let offset = match String::from_utf8_lossy(offset).parse::<usize>() {
+            Ok(offset) => offset,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        match translate_read_error::<P, _>(kve.get_bit(key, offset))? {
+            Some(bit) => con.write_usize(bit as usize).await?,
+            None => return util::err(P::RCODE_NIL),
+        }
+        Ok(())
+    }
+    /// Run a `BITCOUNT` query. This returns the number of set bits in the value
+    /// ## Syntax
+    /// `BITCOUNT <key>`
+    fn bitcount(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        match translate_read_error::<P, _>(kve.bit_count(unsafe { act.next_unchecked() }))? {
+            Some(count) => con.write_int64(count).await?,
+            None => return util::err(P::RCODE_NIL),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Bloom filter actions
+//!
+//! Actions for the bloom filter model (`keymap(str,bloom)` and friends). A filter answers if
+//! an item was *possibly* added to it (with a configurable false positive rate) or if it was
+//! definitely never added
+
+use crate::{
+    corestore::bloom::{BloomFilter, DEFAULT_CAPACITY},
+    dbnet::prelude::*,
+};
+
+action! {
+    /// Handle a `BFRESERVE` query. This creates an empty filter with the given false positive
+    /// rate (and optionally, the number of items it is sized for)
+    /// ## Syntax
+    /// `BFRESERVE <key> <error_rate> [<capacity>]`
+    fn bfreserve(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Between(2, 3))?;
+        let bloommap = handle.get_table_with::<P, KVEBloom>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        if !bloommap.key_fits(&key) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let error_rate = match String::from_utf8_lossy(unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked()
+        })
+        .parse::<f64>()
+        {
+            Ok(rate) => rate,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let capacity = match act.next() {
+            Some(capacity) => match String::from_utf8_lossy(capacity).parse::<u64>() {
+                Ok(capacity) => capacity,
+                Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+            },
+            None => DEFAULT_CAPACITY,
+        };
+        let filter = match BloomFilter::new(error_rate, capacity) {
+            Some(filter) => filter,
+            None => return util::err(P::RSTRING_OUT_OF_RANGE),
+        };
+        if registry::state_okay() {
+            match bloommap.bloom_reserve(key, filter) {
+                Ok(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Ok(false) => return util::err(P::RCODE_OVERWRITE_ERR),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `BFADD` query. The filter is created with the default settings if it doesn't
+    /// exist and the number of items that definitely weren't in the filter is returned
+    /// ## Syntax
+    /// `BFADD <key> <items ...>`
+    fn bfadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let bloommap = handle.get_table_with::<P, KVEBloom>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        // only the key counts against the size limits; the items are never stored
+        if !bloommap.key_fits(&key) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match bloommap.bloom_add(key, act) {
+                Ok(added) => con.write_usize(added).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `BFEXISTS` query. This returns 1 if the item was possibly added to the filter
+    /// and 0 if it definitely wasn't (or if the filter doesn't exist)
+    /// ## Syntax
+    /// `BFEXISTS <key> <item>`
+    fn bfexists(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let bloommap = handle.get_table_with::<P, KVEBloom>()?;
+        let (key, item) = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        match bloommap.bloom_exists(key, item) {
+            Ok(exists) => con.write_usize(exists as usize).await?,
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
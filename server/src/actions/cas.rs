@@ -0,0 +1,67 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `CAS` queries
+//! This module provides functions to work with `CAS` (compare-and-swap) queries
+//!
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*};
+
+action!(
+    /// Run a `CAS` query. The value of the key is replaced with `new` only if its current
+    /// value is `expected`
+    /// ## Syntax
+    /// `CAS <key> <expected> <new>`
+    fn cas(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        if registry::state_okay() {
+            let swapped = {
+                let writer = handle.get_table_with::<P, KVEBlob>()?;
+                let (key, expected, new) = unsafe {
+                    // UNSAFE(@ohsayan): This is completely safe as we've already checked
+                    // that there are exactly 3 arguments
+                    (
+                        act.next_unchecked(),
+                        act.next_unchecked(),
+                        act.next_unchecked(),
+                    )
+                };
+                if !writer.pair_fits(key, new) {
+                    return util::err(P::RSTRING_TOO_LARGE);
+                }
+                writer.compare_and_swap(key, expected, SharedSlice::new(new))
+            };
+            match translate_read_error::<P, _>(swapped)? {
+                Some(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Some(false) => return util::err(P::RSTRING_CAS_MISMATCH),
+                None => return util::err(P::RCODE_NIL),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
@@ -0,0 +1,103 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Counter actions
+//!
+//! Atomic increments and decrements for the counter model (`keymap(str,u64)`). A counter that
+//! doesn't exist is created with a value of zero before it is updated. Counters can be read
+//! with a regular `GET`
+
+use crate::dbnet::prelude::*;
+
+action! {
+    /// Handle an `INCR` query. This increments the counter by one and returns the new value
+    /// ## Syntax
+    /// `INCR <key>`
+    fn incr(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        self::update(handle, con, act, true).await
+    }
+    /// Handle a `DECR` query. This decrements the counter by one and returns the new value
+    /// ## Syntax
+    /// `DECR <key>`
+    fn decr(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        self::update(handle, con, act, false).await
+    }
+    /// Handle an `INCRBY` query. This increments the counter by `delta` and returns the new
+    /// value
+    /// ## Syntax
+    /// `INCRBY <key> <delta>`
+    fn incrby(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        self::update(handle, con, act, true).await
+    }
+    /// Handle a `DECRBY` query. This decrements the counter by `delta` and returns the new
+    /// value
+    /// ## Syntax
+    /// `DECRBY <key> <delta>`
+    fn decrby(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        self::update(handle, con, act, false).await
+    }
+    /// Update a counter by the provided delta (or by one, if there's no delta)
+    fn update(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        act: ActionIter<'a>,
+        incr: bool
+    ) {
+        let mut act = act;
+        let countermap = handle.get_table_with::<P, KVECounter>()?;
+        let key = unsafe { act.next_unchecked_bytes() };
+        if !countermap.key_fits(&key) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let delta = match act.next() {
+            Some(delta) => match String::from_utf8_lossy(delta).parse::<u64>() {
+                Ok(delta) => delta,
+                Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+            },
+            None => 1,
+        };
+        if registry::state_okay() {
+            let ret = if incr {
+                countermap.counter_incr_by(key, delta)
+            } else {
+                countermap.counter_decr_by(key, delta)
+            };
+            match ret {
+                Ok(Some(value)) => con.write_int64(value).await?,
+                Ok(None) if incr => return util::err(P::RSTRING_COUNTER_OVERFLOW),
+                Ok(None) => return util::err(P::RSTRING_COUNTER_UNDERFLOW),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+}
@@ -24,12 +24,12 @@
  *
 */
 
-use crate::dbnet::prelude::*;
+use crate::{blueql::util::split_qualified_key, dbnet::prelude::*};
 
 action!(
     /// Returns the number of keys in the database
     fn dbsize(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len < 2)?;
+        ensure_arity(act.len(), Arity::AtMost(1))?;
         if act.is_empty() {
             let len = get_tbl_ref!(handle, con).count();
             con.write_usize(len).await?;
@@ -41,4 +41,26 @@ action!(
         }
         Ok(())
     }
+    /// Returns the approximate number of bytes used by a key and its value. The key can be
+    /// qualified with a table (`<keyspace>:<table>:<key>`) if qualified keys are enabled
+    /// ## Syntax
+    /// `MEMUSAGE <key>`
+    fn memusage(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let key = unsafe { act.next_unchecked() };
+        let (table, key) = match split_qualified_key(key) {
+            Some(qualified) => {
+                let entity = qualified.entity();
+                let entity = handle_entity!(con, entity);
+                (get_tbl!(&entity, handle, con), qualified.key)
+            }
+            None => (get_tbl!(handle, con), key),
+        };
+        match table.key_memory_usage(key) {
+            Ok(Some(usage)) => con.write_usize(usage).await?,
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
 );
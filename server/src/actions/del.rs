@@ -38,7 +38,7 @@ action!(
     /// Do note that this function is blocking since it acquires a write lock.
     /// It will write an entire datagroup, for this `del` action
     fn del(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |size| size != 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
         let table = get_tbl_ref!(handle, con);
         macro_rules! remove {
             ($engine:expr) => {{
@@ -73,9 +73,53 @@ action!(
             DataModel::KVExtListmap(kvlmap) => {
                 remove!(kvlmap)
             }
+            DataModel::KVExtSetmap(kvsmap) => {
+                remove!(kvsmap)
+            }
+            DataModel::KVExtZsetmap(kvzmap) => {
+                remove!(kvzmap)
+            }
+            DataModel::KVExtHashmap(kvhmap) => {
+                remove!(kvhmap)
+            }
+            DataModel::KVExtCountermap(kvcmap) => {
+                remove!(kvcmap)
+            }
+            DataModel::KVExtBloommap(kvbmap) => {
+                remove!(kvbmap)
+            }
+            DataModel::KVExtHllmap(kvhllmap) => {
+                remove!(kvhllmap)
+            }
+            DataModel::KVExtGeomap(kvgmap) => {
+                remove!(kvgmap)
+            }
+            DataModel::KVExtTimeseriesmap(kvtmap) => {
+                remove!(kvtmap)
+            }
             #[allow(unreachable_patterns)]
             _ => return util::err(P::RSTRING_WRONG_MODEL),
         }
         Ok(())
     }
+    /// Run a `DELPREFIX` query. This removes all the keys that start with the (non-empty)
+    /// prefix, returning the number of removed keys
+    ///
+    /// Syntax: `DELPREFIX <prefix>`
+    fn delprefix(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let prefix = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there is exactly 1 argument
+            act.next_unchecked()
+        };
+        // use FLUSHDB to remove everything
+        ensure_boolean_or_aerr::<P>(!prefix.is_empty())?;
+        if registry::state_okay() {
+            let removed = get_tbl_ref!(handle, con).remove_prefix(prefix);
+            con.write_usize(removed).await?;
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
 );
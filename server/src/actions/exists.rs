@@ -28,23 +28,51 @@
 //! This module provides functions to work with `EXISTS` queries
 
 use crate::{
-    corestore::table::DataModel, dbnet::prelude::*,
+    actions::expire, corestore::table::DataModel, dbnet::prelude::*,
     kvengine::encoding::ENCODING_LUT_ITER, queryengine::ActionIter, util::compiler,
 };
 
 action!(
     /// Run an `EXISTS` query
-    fn exists(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len != 0)?;
+    ///
+    /// Syntax: `EXISTS <key> ... [WITHTTL]`
+    ///
+    /// This returns the number of keys that exist. With `WITHTTL`, this returns a flat array
+    /// with that number followed by each key that has an expiry and its remaining TTL (in
+    /// seconds)
+    fn exists(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        // there has to be atleast one key, so that `EXISTS WITHTTL` still looks for that key
+        let with_ttl = expire::take_ttl_flag(&mut act, 1);
         let mut how_many_of_them_exist = 0usize;
         macro_rules! exists {
             ($engine:expr) => {{
                 let encoding_is_okay = ENCODING_LUT_ITER[$engine.is_key_encoded()](act.as_ref());
                 if compiler::likely(encoding_is_okay) {
-                    act.for_each(|key| {
-                        how_many_of_them_exist += $engine.exists_unchecked(key) as usize;
-                    });
-                    con.write_usize(how_many_of_them_exist).await?;
+                    if with_ttl {
+                        let mut expiring = Vec::new();
+                        act.for_each(|key| {
+                            if let Ok(Some(ttl)) = $engine.remaining_ttl(key) {
+                                how_many_of_them_exist += 1;
+                                if let Some(millis) = ttl {
+                                    expiring.push((key, millis));
+                                }
+                            }
+                        });
+                        let tsymbol = $engine.get_key_tsymbol();
+                        con.write_flat_array_header(1 + expiring.len() * 2).await?;
+                        con.write_usize(how_many_of_them_exist).await?;
+                        for (key, millis) in expiring {
+                            con.write_mono_length_prefixed_with_tsymbol(key, tsymbol)
+                                .await?;
+                            con.write_int64(expire::ttl_secs(millis)).await?;
+                        }
+                    } else {
+                        act.for_each(|key| {
+                            how_many_of_them_exist += $engine.exists_unchecked(key) as usize;
+                        });
+                        con.write_usize(how_many_of_them_exist).await?;
+                    }
                 } else {
                     return util::err(P::RCODE_ENCODING_ERROR);
                 }
@@ -54,6 +82,14 @@ action!(
         match tbl.get_model_ref() {
             DataModel::KV(kve) => exists!(kve),
             DataModel::KVExtListmap(kve) => exists!(kve),
+            DataModel::KVExtSetmap(kve) => exists!(kve),
+            DataModel::KVExtZsetmap(kve) => exists!(kve),
+            DataModel::KVExtHashmap(kve) => exists!(kve),
+            DataModel::KVExtCountermap(kve) => exists!(kve),
+            DataModel::KVExtBloommap(kve) => exists!(kve),
+            DataModel::KVExtHllmap(kve) => exists!(kve),
+            DataModel::KVExtGeomap(kve) => exists!(kve),
+            DataModel::KVExtTimeseriesmap(kve) => exists!(kve),
             #[allow(unreachable_patterns)]
             _ => return util::err(P::RSTRING_WRONG_MODEL),
         }
@@ -0,0 +1,163 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `EXPIRE`, `TTL` and `PERSIST` queries
+//! This module provides functions to manage the expiry of keys
+
+use crate::{
+    actions::ActionResult, corestore::table::DataModel, dbnet::prelude::*, kvengine::expiry,
+    util::compiler,
+};
+
+/// Parse a TTL (in seconds) from the raw argument
+pub fn parse_ttl<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<u64> {
+    match String::from_utf8_lossy(raw).parse::<u64>() {
+        Ok(secs) => Ok(secs),
+        Err(_) => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// The flag that makes `EXISTS` and `LSKEYS` report the remaining TTL of the keys
+const WITH_TTL_FLAG: &[u8] = b"WITHTTL";
+
+/// Consume the trailing `WITHTTL` flag (if any), returning true if it was there. The flag is
+/// only looked for if there are more than `min_args` arguments
+pub fn take_ttl_flag(act: &mut ActionIter<'_>, min_args: usize) -> bool {
+    let with_ttl = act.len() > min_args
+        && act
+            .peek_back()
+            .map_or(false, |flag| flag.eq_ignore_ascii_case(WITH_TTL_FLAG));
+    if with_ttl {
+        let _ = act.next_back();
+    }
+    with_ttl
+}
+
+/// Returns the remaining TTL in seconds, rounding up so that a key with a few millis left
+/// doesn't report zero
+pub const fn ttl_secs(millis: u64) -> u64 {
+    millis.saturating_add(999) / 1000
+}
+
+action!(
+    /// Run an `EXPIRE` query
+    ///
+    /// Syntax: `EXPIRE <key> <seconds>`
+    fn expire(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (key, secs) = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let deadline = expiry::deadline_after_secs(parse_ttl::<P>(secs)?);
+        if registry::state_okay() {
+            let tbl = get_tbl_ref!(handle, con);
+            let did = match tbl.get_model_ref() {
+                DataModel::KV(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtListmap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtSetmap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtZsetmap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtHashmap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtCountermap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtBloommap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtHllmap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtGeomap(kve) => kve.set_expiry(key, deadline),
+                DataModel::KVExtTimeseriesmap(kve) => kve.set_expiry(key, deadline),
+            };
+            match did {
+                Ok(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Ok(false) => con._write_raw(P::RCODE_NIL).await?,
+                Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+            }
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+    /// Run a `TTL` query. This returns the number of seconds after which the key will expire
+    ///
+    /// Syntax: `TTL <key>`
+    fn ttl(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there is exactly 1 argument
+            act.next_unchecked()
+        };
+        let tbl = get_tbl_ref!(handle, con);
+        let remaining = match tbl.get_model_ref() {
+            DataModel::KV(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtListmap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtSetmap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtZsetmap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtHashmap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtCountermap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtBloommap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtHllmap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtGeomap(kve) => kve.remaining_ttl(key),
+            DataModel::KVExtTimeseriesmap(kve) => kve.remaining_ttl(key),
+        };
+        match remaining {
+            Ok(Some(Some(millis))) => con.write_int64(ttl_secs(millis)).await?,
+            Ok(Some(None)) => con._write_raw(P::RSTRING_NO_EXPIRY).await?,
+            Ok(None) => con._write_raw(P::RCODE_NIL).await?,
+            Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+        }
+        Ok(())
+    }
+    /// Run a `PERSIST` query. This removes the expiry of a key
+    ///
+    /// Syntax: `PERSIST <key>`
+    fn persist(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there is exactly 1 argument
+            act.next_unchecked()
+        };
+        if registry::state_okay() {
+            let tbl = get_tbl_ref!(handle, con);
+            let did = match tbl.get_model_ref() {
+                DataModel::KV(kve) => kve.persist(key),
+                DataModel::KVExtListmap(kve) => kve.persist(key),
+                DataModel::KVExtSetmap(kve) => kve.persist(key),
+                DataModel::KVExtZsetmap(kve) => kve.persist(key),
+                DataModel::KVExtHashmap(kve) => kve.persist(key),
+                DataModel::KVExtCountermap(kve) => kve.persist(key),
+                DataModel::KVExtBloommap(kve) => kve.persist(key),
+                DataModel::KVExtHllmap(kve) => kve.persist(key),
+                DataModel::KVExtGeomap(kve) => kve.persist(key),
+                DataModel::KVExtTimeseriesmap(kve) => kve.persist(key),
+            };
+            match did {
+                Ok(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Ok(false) => con._write_raw(P::RCODE_NIL).await?,
+                Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+            }
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+);
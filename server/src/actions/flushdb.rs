@@ -27,9 +27,13 @@
 use crate::{dbnet::prelude::*, queryengine::ActionIter};
 
 action!(
-    /// Delete all the keys in the database
+    /// Delete all the keys in the current table or in the provided entity. The table itself
+    /// (along with its model and volatility) is left untouched, so this is safe to run while
+    /// other connections are using the table. This is also available as `FLUSHTABLE`
+    ///
+    /// Syntax: `FLUSHDB [<entity>]` or `FLUSHTABLE [<entity>]`
     fn flushdb(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len < 2)?;
+        ensure_arity(act.len(), Arity::AtMost(1))?;
         if registry::state_okay() {
             if act.is_empty() {
                 // flush the current table
@@ -0,0 +1,137 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Geo actions
+//!
+//! Actions for the geo model (`keymap(str,geo)` and friends). Every member has a location,
+//! given as a longitude and a latitude (in degrees)
+
+use crate::{
+    actions::ActionResult,
+    corestore::{geo, SharedSlice},
+    dbnet::prelude::*,
+};
+
+/// Parse a coordinate. Coordinates are finite 64-bit floats
+fn parse_coordinate<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<f64> {
+    match String::from_utf8_lossy(raw).parse::<f64>() {
+        Ok(coordinate) if coordinate.is_finite() => Ok(coordinate),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Parse a location, making sure that it's within the supported bounds
+fn parse_location<P: ProtocolSpec>(lon: &[u8], lat: &[u8]) -> ActionResult<(f64, f64)> {
+    let (lon, lat) = (parse_coordinate::<P>(lon)?, parse_coordinate::<P>(lat)?);
+    if geo::is_valid_location(lon, lat) {
+        Ok((lon, lat))
+    } else {
+        util::err(P::RSTRING_OUT_OF_RANGE)
+    }
+}
+
+/// Returns the number of meters in the given unit of distance
+fn parse_unit<P: ProtocolSpec>(unit: &[u8]) -> ActionResult<f64> {
+    match unit.to_ascii_lowercase().as_slice() {
+        b"m" => Ok(1.0),
+        b"km" => Ok(1000.0),
+        b"mi" => Ok(1609.34),
+        b"ft" => Ok(0.3048),
+        _ => util::err(P::RCODE_ACTION_ERR),
+    }
+}
+
+action! {
+    /// Handle a `GEOADD` query for the geo model. The geo index is created if it doesn't
+    /// exist, and the location of a member that already exists is updated. This returns the
+    /// number of newly added members
+    /// ## Syntax
+    /// `GEOADD <key> <longitude> <latitude> <member> [<longitude> <latitude> <member> ...]`
+    fn geoadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Groups { head: 1, group: 3 })?;
+        let geomap = handle.get_table_with::<P, KVEGeo>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        let mut members = Vec::with_capacity(act.len() / 3);
+        while let (Some(lon), Some(lat), Some(member)) = (act.next(), act.next(), act.next()) {
+            let (lon, lat) = parse_location::<P>(lon, lat)?;
+            members.push((lon, lat, SharedSlice::new(member)));
+        }
+        if !geomap.fits(&key, members.iter().map(|(_, _, member)| &member[..])) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match geomap.geo_add(key, members) {
+                Ok(added) => con.write_usize(added).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `GEOSEARCH` query for the geo model. This returns the members within the
+    /// radius of the location, nearest first. The radius is in meters, unless a unit (`m`,
+    /// `km`, `mi` or `ft`) is provided
+    /// ## Syntax
+    /// `GEOSEARCH <key> <longitude> <latitude> <radius> [<unit>]`
+    fn geosearch(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Between(4, 5))?;
+        let geomap = handle.get_table_with::<P, KVEGeo>()?;
+        let (key, lon, lat, radius) = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            (
+                act.next_unchecked(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+            )
+        };
+        let (lon, lat) = parse_location::<P>(lon, lat)?;
+        let radius = parse_coordinate::<P>(radius)?;
+        if radius < 0.0 {
+            return util::err(P::RSTRING_OUT_OF_RANGE);
+        }
+        let radius = match act.next() {
+            Some(unit) => radius * parse_unit::<P>(unit)?,
+            None => radius,
+        };
+        match geomap.geo_search_radius(key, lon, lat, radius) {
+            Ok(Some(members)) => {
+                con.write_typed_non_null_array_header(members.len(), geomap.get_value_tsymbol())
+                    .await?;
+                for (member, _) in members {
+                    con.write_typed_non_null_array_element(&member).await?;
+                }
+            }
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
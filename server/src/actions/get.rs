@@ -36,17 +36,27 @@ action!(
         con: &mut Connection<C, P>,
         mut act: ActionIter<'a>,
     ) {
-        ensure_length::<P>(act.len(), |len| len == 1)?;
-        let kve = handle.get_table_with::<P, KVEBlob>()?;
-        unsafe {
-            match kve.get_cloned(act.next_unchecked()) {
-                Ok(Some(val)) => {
-                    con.write_mono_length_prefixed_with_tsymbol(&val, kve.get_value_tsymbol())
-                        .await?
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let key = unsafe { act.next_unchecked() };
+        let kve = match handle.get_table_with::<P, KVEBlob>() {
+            Ok(kve) => kve,
+            Err(e) => {
+                // counters can be read with a GET too
+                let kvc = handle.get_table_with::<P, KVECounter>().map_err(|_| e)?;
+                match kvc.counter_get(key) {
+                    Ok(Some(counter)) => con.write_int64(counter).await?,
+                    Err(_) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+                    Ok(None) => con._write_raw(P::RCODE_NIL).await?,
                 }
-                Err(_) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
-                Ok(_) => con._write_raw(P::RCODE_NIL).await?,
+                return Ok(());
             }
+        };
+        match translate_read_error::<P, _>(kve.get_cloned(key))? {
+            Some(val) => {
+                con.write_mono_length_prefixed_with_tsymbol(&val, kve.get_value_tsymbol())
+                    .await?
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
         }
         Ok(())
     }
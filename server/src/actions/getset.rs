@@ -0,0 +1,63 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `GETSET` queries
+//! This module provides functions to work with `GETSET` queries. (`GETDEL` is the same as
+//! `POP`)
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*};
+
+action!(
+    /// Run a `GETSET` query. The key is set to the new value (whether or not it exists) and
+    /// its old value is returned. Like `SET`, this drops the expiry of the key, if any
+    /// ## Syntax
+    /// `GETSET <key> <value>`
+    fn getset(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            let (key, value) = unsafe {
+                // UNSAFE(@ohsayan): This is completely safe as we've already checked
+                // that there are exactly 2 arguments
+                (act.next_unchecked(), act.next_unchecked())
+            };
+            if !kve.pair_fits(key, value) {
+                return util::err(P::RSTRING_TOO_LARGE);
+            }
+            let old = kve.get_and_set(SharedSlice::new(key), SharedSlice::new(value));
+            match translate_read_error::<P, _>(old)? {
+                Some(val) => {
+                    con.write_mono_length_prefixed_with_tsymbol(&val, kve.get_value_tsymbol())
+                        .await?
+                }
+                None => return util::err(P::RCODE_NIL),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
@@ -0,0 +1,126 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Hash actions
+//!
+//! Actions for the hash model (`keymap(str,map<str,str>)` and friends). This lets a single key
+//! hold named fields, so clients don't have to read, modify and write back entire records
+
+use crate::{
+    corestore::SharedSlice, dbnet::prelude::*, kvengine::encoding::ENCODING_LUT, util::compiler,
+};
+
+action! {
+    /// Handle an `HSET` query for the hash model. The hash is created if it doesn't exist and
+    /// existing fields are overwritten. This returns the number of newly added fields
+    /// ## Syntax
+    /// `HSET <myhash> <field> <value> [<field> <value> ...]`
+    fn hset(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Groups { head: 1, group: 2 })?;
+        let hashmap = handle.get_table_with::<P, KVEHash>()?;
+        let hashname = unsafe { act.next_unchecked_bytes() };
+        if !hashmap.fits(&hashname, act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let (fenc_ok, venc_ok) = (ENCODING_LUT[true], hashmap.get_val_encoder());
+        let mut fields = Vec::with_capacity(act.len() / 2);
+        while let (Some(field), Some(value)) = (act.next(), act.next()) {
+            if compiler::unlikely(!(fenc_ok(field) && venc_ok(value))) {
+                return util::err(P::RCODE_ENCODING_ERROR);
+            }
+            fields.push((SharedSlice::new(field), SharedSlice::new(value)));
+        }
+        if registry::state_okay() {
+            match hashmap.hash_set(hashname, fields) {
+                Ok(added) => con.write_usize(added).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle an `HGET` query for the hash model. This returns nil if either the hash or the
+    /// field doesn't exist
+    /// ## Syntax
+    /// `HGET <myhash> <field>`
+    fn hget(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let hashmap = handle.get_table_with::<P, KVEHash>()?;
+        let (hashname, field) = unsafe { (act.next_unchecked(), act.next_unchecked()) };
+        match hashmap.hash_get(hashname, field) {
+            Ok(Some(Some(value))) => {
+                con.write_mono_length_prefixed_with_tsymbol(&value, hashmap.get_value_tsymbol())
+                    .await?
+            }
+            Ok(Some(None)) | Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+    /// Handle an `HDEL` query for the hash model. This returns the number of fields that
+    /// were removed
+    /// ## Syntax
+    /// `HDEL <myhash> <fields ...>`
+    fn hdel(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let hashmap = handle.get_table_with::<P, KVEHash>()?;
+        let hashname = unsafe { act.next_unchecked() };
+        if registry::state_okay() {
+            match hashmap.hash_del(hashname, act) {
+                Ok(Some(removed)) => con.write_usize(removed).await?,
+                Ok(None) => return util::err(P::RCODE_NIL),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle an `HGETALL` query for the hash model. This returns a flat array of every field
+    /// (sorted by name) followed by its value: `[field1, value1, field2, value2, ...]`
+    /// ## Syntax
+    /// `HGETALL <myhash>`
+    fn hgetall(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let hashmap = handle.get_table_with::<P, KVEHash>()?;
+        let hashname = unsafe { act.next_unchecked() };
+        match hashmap.hash_get_all(hashname) {
+            Ok(Some(fields)) => {
+                // field names are always valid unicode, so they're good for a binary array too
+                con.write_typed_non_null_array_header(fields.len() * 2, hashmap.get_value_tsymbol())
+                    .await?;
+                for (field, value) in fields {
+                    con.write_typed_non_null_array_element(&field).await?;
+                    con.write_typed_non_null_array_element(&value).await?;
+                }
+            }
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
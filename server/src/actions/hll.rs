@@ -0,0 +1,97 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # HyperLogLog actions
+//!
+//! Actions for the HLL model (`keymap(str,hll)` and friends). An HLL estimates the number of
+//! distinct items added to it (with a standard error of about 0.81%) without storing them
+
+use crate::dbnet::prelude::*;
+
+action! {
+    /// Handle a `PFADD` query. The HLL is created if it doesn't exist. This returns 1 if the
+    /// HLL was created or if its estimate changed, and 0 otherwise
+    /// ## Syntax
+    /// `PFADD <key> [<items ...>]`
+    fn pfadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let hllmap = handle.get_table_with::<P, KVEHll>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        // only the key counts against the size limits; the items are never stored
+        if !hllmap.key_fits(&key) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match hllmap.hll_add(key, act) {
+                Ok(changed) => con.write_usize(changed as usize).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `PFCOUNT` query. This returns the estimated number of distinct items across
+    /// all the given HLLs (HLLs that don't exist are treated as empty)
+    /// ## Syntax
+    /// `PFCOUNT <keys ...>`
+    fn pfcount(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let hllmap = handle.get_table_with::<P, KVEHll>()?;
+        match hllmap.hll_count(act) {
+            Ok(count) => con.write_int64(count).await?,
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+    /// Handle a `PFMERGE` query. This merges the source HLLs into the destination HLL
+    /// (creating it if it doesn't exist)
+    /// ## Syntax
+    /// `PFMERGE <dst> <sources ...>`
+    fn pfmerge(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let hllmap = handle.get_table_with::<P, KVEHll>()?;
+        let dst = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        if !hllmap.key_fits(&dst) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match hllmap.hll_merge(dst, act) {
+                Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `JGET` queries
+//! This module provides functions to read parts of JSON values without having to transfer
+//! (and parse) the entire value on the client side
+//!
+
+use crate::{dbnet::prelude::*, kvengine::json};
+
+action!(
+    /// Run a `JGET` query. This returns the raw JSON text at `path` in the value of the key,
+    /// or nil if either the key or the path doesn't exist. The table must have JSON values
+    /// ## Syntax
+    /// `JGET <key> <path>`, where `path` looks like `$.users[0].name`
+    fn jget(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        if !kve.is_json() {
+            return util::err(P::RSTRING_WRONG_MODEL);
+        }
+        let (key, path) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let path = match json::parse_path(path) {
+            Some(path) => path,
+            None => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        match kve.json_get(key, &path) {
+            Ok(Some(Some(value))) => {
+                con.write_mono_length_prefixed_with_tsymbol(&value, kve.get_value_tsymbol())
+                    .await?
+            }
+            Ok(_) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+);
@@ -27,20 +27,17 @@
 use crate::dbnet::prelude::*;
 
 action!(
-    /// Run a `KEYLEN` query
+    /// Run a `KEYLEN` (or `STRLEN`) query. This returns the length of the value in bytes
     ///
     /// At this moment, `keylen` only supports a single key
     fn keylen(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 1)?;
+        ensure_arity(act.len(), Arity::Exactly(1))?;
         let res: Option<usize> = {
             let reader = handle.get_table_with::<P, KVEBlob>()?;
             unsafe {
                 // UNSAFE(@ohsayan): this is completely safe as we've already checked
                 // the number of arguments is one
-                match reader.get(act.next_unchecked()) {
-                    Ok(v) => v.map(|b| b.len()),
-                    Err(_) => None,
-                }
+                reader.value_len(act.next_unchecked()).unwrap_or(None)
             }
         };
         if let Some(value) = res {
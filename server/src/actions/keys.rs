@@ -0,0 +1,66 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `KEYS` queries
+//! This module provides functions to list the keys that match a pattern. See
+//! [`Pattern`] for the pattern syntax
+//!
+
+use crate::{corestore::table::DataModel, dbnet::prelude::*, kvengine::pattern::Pattern};
+
+action!(
+    /// Run a `KEYS` query
+    /// ## Syntax
+    /// `KEYS <pattern>`
+    fn keys(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let table = get_tbl!(handle, con);
+        let pattern = Pattern::compile(unsafe { act.next_unchecked() });
+        let tsymbol = match table.get_model_ref() {
+            DataModel::KV(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtListmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtSetmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtZsetmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHashmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtCountermap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtBloommap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHllmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtGeomap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtTimeseriesmap(kv) => kv.get_key_tsymbol(),
+        };
+        let items = table.get_keys_matching(&pattern);
+        con.write_typed_non_null_array_header(items.len(), tsymbol)
+            .await?;
+        for key in items {
+            con.write_typed_non_null_array_element(&key).await?;
+        }
+        Ok(())
+    }
+);
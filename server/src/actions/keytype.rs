@@ -0,0 +1,61 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `TYPE` queries
+//! This module provides functions to introspect the type of a key's value without fetching it
+//!
+
+use crate::dbnet::prelude::*;
+
+action!(
+    /// Run a `TYPE` query. If the key exists, this returns the model code of the table and
+    /// the table's description
+    /// ## Syntax
+    /// `TYPE <key>`
+    fn keytype(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let table = get_tbl!(handle, con);
+        match table.key_exists(unsafe { act.next_unchecked() }) {
+            Ok(true) => {
+                con.write_typed_non_null_array_header(2, P::TSYMBOL_STRING)
+                    .await?;
+                con.write_typed_non_null_array_element(
+                    table.get_model_code().to_string().as_bytes(),
+                )
+                .await?;
+                con.write_typed_non_null_array_element(table.describe_self().as_bytes())
+                    .await?;
+            }
+            Ok(false) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+);
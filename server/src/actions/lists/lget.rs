@@ -63,7 +63,7 @@ action! {
     /// - `LGET <mylist> LAST` will return the last item
     /// if it exists
     fn lget(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len != 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
         // get the list name
         let listname = unsafe { act.next_unchecked() };
@@ -89,7 +89,7 @@ action! {
             Some(subaction) => {
                 match subaction.as_ref() {
                     LEN => {
-                        ensure_length::<P>(act.len(), |len| len == 0)?;
+                        ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"LGET LEN")?;
                         match listmap.list_len(listname) {
                             Ok(Some(len)) => con.write_usize(len).await?,
                             Ok(None) => return Err(P::RCODE_NIL.into()),
@@ -97,7 +97,7 @@ action! {
                         }
                     }
                     LIMIT => {
-                        ensure_length::<P>(act.len(), |len| len == 1)?;
+                        ensure_subcommand_arity(act.len(), Arity::Exactly(1), b"LGET LIMIT")?;
                         let count = get_numeric_count!();
                         match listmap.list_cloned(listname, count) {
                             Ok(Some(items)) => writelist!(con, listmap, items),
@@ -106,7 +106,7 @@ action! {
                         }
                     }
                     VALUEAT => {
-                        ensure_length::<P>(act.len(), |len| len == 1)?;
+                        ensure_subcommand_arity(act.len(), Arity::Exactly(1), b"LGET VALUEAT")?;
                         let idx = get_numeric_count!();
                         let maybe_value = listmap.get(listname).map(|list| {
                             list.map(|lst| lst.read().get(idx).cloned())
@@ -131,7 +131,7 @@ action! {
                         }
                     }
                     LAST => {
-                        ensure_length::<P>(act.len(), |len| len == 0)?;
+                        ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"LGET LAST")?;
                         let maybe_value = listmap.get(listname).map(|list| {
                             list.map(|lst| lst.read().last().cloned())
                         });
@@ -149,7 +149,7 @@ action! {
                         }
                     }
                     FIRST => {
-                        ensure_length::<P>(act.len(), |len| len == 0)?;
+                        ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"LGET FIRST")?;
                         let maybe_value = listmap.get(listname).map(|list| {
                             list.map(|lst| lst.read().first().cloned())
                         });
@@ -24,7 +24,7 @@
  *
 */
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::notify::Event, util::compiler};
 
 const CLEAR: &[u8] = "CLEAR".as_bytes();
 const PUSH: &[u8] = "PUSH".as_bytes();
@@ -41,7 +41,7 @@ action! {
     /// - `LMOD <mylist> remove <index>`
     /// - `LMOD <mylist> clear`
     fn lmod(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len > 1)?;
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
         // get the list name
         let listname = unsafe { act.next_unchecked() };
@@ -56,13 +56,14 @@ action! {
         // now let us see what we need to do
         match unsafe { act.next_uppercase_unchecked() }.as_ref() {
             CLEAR => {
-                ensure_length::<P>(act.len(), |len| len == 0)?;
+                ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"LMOD CLEAR")?;
                 let list = match listmap.get_inner_ref().get(listname) {
                     Some(l) => l,
                     _ => return Err(P::RCODE_NIL.into()),
                 };
                 let okay = if registry::state_okay() {
                     list.write().clear();
+                    listmap.notify(Event::Update, listname);
                     P::RCODE_OKAY
                 } else {
                     P::RCODE_SERVER_ERR
@@ -70,15 +71,19 @@ action! {
                 con._write_raw(okay).await?
             }
             PUSH => {
-                ensure_boolean_or_aerr::<P>(!act.is_empty())?;
+                ensure_subcommand_arity(act.len(), Arity::AtLeast(1), b"LMOD PUSH")?;
                 let list = match listmap.get_inner_ref().get(listname) {
                     Some(l) => l,
                     _ => return Err(P::RCODE_NIL.into()),
                 };
+                if !listmap.fits(listname, act.as_ref()) {
+                    return Err(P::RSTRING_TOO_LARGE.into());
+                }
                 let venc_ok = listmap.get_val_encoder();
                 let ret = if compiler::likely(act.as_ref().all(venc_ok)) {
                     if registry::state_okay() {
                         list.write().extend(act.map(SharedSlice::new));
+                        listmap.notify(Event::Update, listname);
                         P::RCODE_OKAY
                     } else {
                         P::RCODE_SERVER_ERR
@@ -89,13 +94,14 @@ action! {
                 con._write_raw(ret).await?
             }
             REMOVE => {
-                ensure_length::<P>(act.len(), |len| len == 1)?;
+                ensure_subcommand_arity(act.len(), Arity::Exactly(1), b"LMOD REMOVE")?;
                 let idx_to_remove = get_numeric_count!();
                 if registry::state_okay() {
                     let maybe_value = listmap.get_inner_ref().get(listname).map(|list| {
                         let mut wlock = list.write();
                         if idx_to_remove < wlock.len() {
                             wlock.remove(idx_to_remove);
+                            listmap.notify(Event::Update, listname);
                             true
                         } else {
                             false
@@ -107,9 +113,12 @@ action! {
                 }
             }
             INSERT => {
-                ensure_length::<P>(act.len(), |len| len == 2)?;
+                ensure_subcommand_arity(act.len(), Arity::Exactly(2), b"LMOD INSERT")?;
                 let idx_to_insert_at = get_numeric_count!();
                 let bts = unsafe { act.next_unchecked() };
+                if !listmap.fits(listname, Some(bts)) {
+                    return Err(P::RSTRING_TOO_LARGE.into());
+                }
                 let ret = if compiler::likely(listmap.is_val_ok(bts)) {
                     if registry::state_okay() {
                         // okay state, good to insert
@@ -119,6 +128,7 @@ action! {
                                 if idx_to_insert_at < wlock.len() {
                                     // we can insert
                                     wlock.insert(idx_to_insert_at, SharedSlice::new(bts));
+                                    listmap.notify(Event::Update, listname);
                                     true
                                 } else {
                                     // oops, out of bounds
@@ -139,7 +149,7 @@ action! {
                 con._write_raw(ret).await?
             }
             POP => {
-                ensure_length::<P>(act.len(), |len| len < 2)?;
+                ensure_subcommand_arity(act.len(), Arity::AtMost(1), b"LMOD POP")?;
                 let idx = if act.len() == 1 {
                     // we have an idx
                     Some(get_numeric_count!())
@@ -166,6 +176,7 @@ action! {
                     };
                     match maybe_pop {
                         Some(Some(val)) => {
+                            listmap.notify(Event::Update, listname);
                             con.write_mono_length_prefixed_with_tsymbol(
                                 &val, listmap.get_value_tsymbol()
                             ).await?;
@@ -0,0 +1,143 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::{actions::expire::parse_ttl, dbnet::prelude::*, storage::v1::wal},
+    std::time::Duration,
+    tokio::time::{self, Instant},
+};
+
+action! {
+    /// Handle an `LPOP` query for the list model. This removes and returns the first element
+    /// ## Syntax
+    /// `LPOP <mylist>`
+    fn lpop(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::pop_from_list(handle, con, act, true).await
+    }
+    /// Handle an `RPOP` query for the list model. This removes and returns the last element
+    /// ## Syntax
+    /// `RPOP <mylist>`
+    fn rpop(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::pop_from_list(handle, con, act, false).await
+    }
+    /// Pop a value from the head or the tail of a list
+    fn pop_from_list(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        act: ActionIter<'a>,
+        from_head: bool
+    ) {
+        let mut act = act;
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let listmap = handle.get_table_with::<P, KVEList>()?;
+        let listname = unsafe { act.next_unchecked() };
+        if registry::state_okay() {
+            match listmap.list_pop(listname, from_head) {
+                Ok(Some(Some(value))) => {
+                    con.write_mono_length_prefixed_with_tsymbol(
+                        &value, listmap.get_value_tsymbol()
+                    ).await?
+                }
+                Ok(Some(None)) => return util::err(P::RSTRING_LISTMAP_LIST_IS_EMPTY),
+                Ok(None) => return util::err(P::RCODE_NIL),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `BLPOP` query for the list model. This is like `LPOP`, but if the list is
+    /// empty (or doesn't exist) it waits for an element to be pushed, for up to `timeout`
+    /// seconds (or forever, if the timeout is `0`). Returns nil if the timeout fires first
+    /// ## Syntax
+    /// `BLPOP <mylist> <timeout>`
+    fn blpop(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::blocking_pop_from_list(handle, con, act, true).await
+    }
+    /// Handle a `BRPOP` query for the list model. This is like `RPOP`, but it waits for an
+    /// element just like `BLPOP`
+    /// ## Syntax
+    /// `BRPOP <mylist> <timeout>`
+    fn brpop(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::blocking_pop_from_list(handle, con, act, false).await
+    }
+    /// Pop a value from the head or the tail of a list, waiting for one if needed
+    fn blocking_pop_from_list(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        act: ActionIter<'a>,
+        from_head: bool
+    ) {
+        let mut act = act;
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let listmap = handle.get_table_with::<P, KVEList>()?;
+        let (listname, timeout) = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there are exactly 2 arguments
+            (act.next_unchecked_bytes(), act.next_unchecked())
+        };
+        let timeout = parse_ttl::<P>(timeout)?;
+        let deadline = (timeout != 0).then(|| Instant::now() + Duration::from_secs(timeout));
+        loop {
+            if !registry::state_okay() {
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+            // start waiting before we look at the list, so that we can't miss a push
+            let mut waiter = listmap.notifier().waiters().register(listname.clone());
+            // the pop is logged like an `LPOP` (or an `RPOP`) once it happens. Our turn to write
+            // is given up before we wait, so that the pushes that we're waiting for can be logged
+            let scope = wal::Scope::current(handle);
+            let sequence = wal::sequence(wal::durability(handle), scope).await;
+            match listmap.list_pop(&listname, from_head) {
+                Ok(Some(Some(value))) => {
+                    let action: &[u8] = if from_head { b"LPOP" } else { b"RPOP" };
+                    sequence.log(handle.get_ids(), &[[action, listname.as_ref()]]).await;
+                    con.write_mono_length_prefixed_with_tsymbol(
+                        &value, listmap.get_value_tsymbol()
+                    ).await?;
+                    return Ok(());
+                }
+                // the list is empty or doesn't exist yet, so wait
+                Ok(_) => {}
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+            drop(sequence);
+            // don't hold back the responses to any queries that were pipelined before this one
+            con.flush().await?;
+            let changed = match deadline {
+                Some(deadline) => time::timeout_at(deadline, waiter.changed())
+                    .await
+                    .unwrap_or(None),
+                None => waiter.changed().await,
+            };
+            if changed.is_none() {
+                // timed out
+                return util::err(P::RCODE_NIL);
+            }
+            // someone else might have popped the element, so try again
+        }
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
+
+action! {
+    /// Handle an `LPUSH` query for the list model. The list is created if it doesn't exist
+    /// ## Syntax
+    /// `LPUSH <mylist> <values ...>`
+    fn lpush(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::push_to_list(handle, con, act, true).await
+    }
+    /// Handle an `RPUSH` query for the list model. The list is created if it doesn't exist
+    /// ## Syntax
+    /// `RPUSH <mylist> <values ...>`
+    fn rpush(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::push_to_list(handle, con, act, false).await
+    }
+    /// Push values to the head or the tail of a list, and return the new length
+    fn push_to_list(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        act: ActionIter<'a>,
+        at_head: bool
+    ) {
+        let mut act = act;
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let listmap = handle.get_table_with::<P, KVEList>()?;
+        let listname = unsafe { act.next_unchecked_bytes() };
+        let venc_ok = listmap.get_val_encoder();
+        if compiler::unlikely(!act.as_ref().all(venc_ok)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !listmap.fits(&listname, act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match listmap.list_push(listname, act.map(SharedSlice::new).collect(), at_head) {
+                Ok(len) => con.write_usize(len).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+}
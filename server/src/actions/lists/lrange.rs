@@ -0,0 +1,55 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::dbnet::prelude::*;
+
+action! {
+    /// Handle an `LRANGE` query for the list model. This returns the elements between `start`
+    /// and `stop` (both inclusive). Negative indices are counted from the end of the list, so
+    /// `LRANGE <mylist> 0 -1` returns the entire list
+    /// ## Syntax
+    /// `LRANGE <mylist> <start> <stop>`
+    fn lrange(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let listmap = handle.get_table_with::<P, KVEList>()?;
+        let listname = unsafe { act.next_unchecked() };
+        macro_rules! get_index {
+            () => {
+                match unsafe { String::from_utf8_lossy(act.next_unchecked()) }.parse::<i64>() {
+                    Ok(int) => int,
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                }
+            };
+        }
+        let (start, stop) = (get_index!(), get_index!());
+        match listmap.list_range(listname, start, stop) {
+            Ok(Some(items)) => writelist!(con, listmap, items),
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
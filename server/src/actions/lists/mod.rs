@@ -29,21 +29,32 @@ mod macros;
 // modules
 pub mod lget;
 pub mod lmod;
+pub mod lpop;
+pub mod lpush;
+pub mod lrange;
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::LockedVec};
+use crate::{
+    corestore::SharedSlice,
+    dbnet::prelude::*,
+    kvengine::{notify::Event, LockedVec},
+};
 
 action! {
     /// Handle an `LSET` query for the list model
     /// Syntax: `LSET <listname> <values ...>`
     fn lset(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len > 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
         let listname = unsafe { act.next_unchecked_bytes() };
+        if !listmap.fits(&listname, act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
         let list = listmap.get_inner_ref();
         if registry::state_okay() {
-            let did = if let Some(entry) = list.fresh_entry(listname) {
+            let did = if let Some(entry) = list.fresh_entry(listname.clone()) {
                 let v: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
                 entry.insert(LockedVec::new(v));
+                listmap.notify(Event::Set, &listname);
                 true
             } else {
                 false
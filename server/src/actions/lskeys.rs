@@ -25,6 +25,7 @@
 */
 
 use crate::{
+    actions::expire,
     corestore::{table::DataModel, SharedSlice},
     dbnet::prelude::*,
 };
@@ -33,8 +34,14 @@ const DEFAULT_COUNT: usize = 10;
 
 action!(
     /// Run an `LSKEYS` query
+    ///
+    /// Syntax: `LSKEYS [<entity>] [<count>] [WITHTTL]`
+    ///
+    /// With `WITHTTL`, this lists the keys that will expire the soonest (instead of any keys)
+    /// as a flat array of each key followed by its remaining TTL (in seconds)
     fn lskeys(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |size| size < 4)?;
+        ensure_arity(act.len(), Arity::AtMost(3))?;
+        let with_ttl = expire::take_ttl_flag(&mut act, 0);
         let (table, count) = if act.is_empty() {
             (get_tbl!(handle, con), DEFAULT_COUNT)
         } else if act.len() == 1 {
@@ -65,13 +72,54 @@ action!(
             };
             (get_tbl!(&entity, handle, con), count)
         };
+        if with_ttl {
+            let (tsymbol, expiring) = match table.get_model_ref() {
+                DataModel::KV(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtListmap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtSetmap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtZsetmap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtHashmap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtCountermap(kv) => {
+                    (kv.get_key_tsymbol(), kv.expiring_soonest(count))
+                }
+                DataModel::KVExtBloommap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtHllmap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtGeomap(kv) => (kv.get_key_tsymbol(), kv.expiring_soonest(count)),
+                DataModel::KVExtTimeseriesmap(kv) => {
+                    (kv.get_key_tsymbol(), kv.expiring_soonest(count))
+                }
+            };
+            con.write_flat_array_header(expiring.len() * 2).await?;
+            for (key, millis) in expiring {
+                con.write_mono_length_prefixed_with_tsymbol(&key, tsymbol)
+                    .await?;
+                con.write_int64(expire::ttl_secs(millis)).await?;
+            }
+            return Ok(());
+        }
         let tsymbol = match table.get_model_ref() {
             DataModel::KV(kv) => kv.get_value_tsymbol(),
             DataModel::KVExtListmap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtSetmap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtZsetmap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtHashmap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtCountermap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtBloommap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtHllmap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtGeomap(kv) => kv.get_value_tsymbol(),
+            DataModel::KVExtTimeseriesmap(kv) => kv.get_value_tsymbol(),
         };
         let items: Vec<SharedSlice> = match table.get_model_ref() {
             DataModel::KV(kv) => kv.get_inner_ref().get_keys(count),
             DataModel::KVExtListmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtSetmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtZsetmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtHashmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtCountermap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtBloommap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtHllmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtGeomap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KVExtTimeseriesmap(kv) => kv.get_inner_ref().get_keys(count),
         };
         con.write_typed_non_null_array_header(items.len(), tsymbol)
             .await?;
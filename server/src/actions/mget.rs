@@ -25,22 +25,38 @@
 */
 
 use crate::{
-    dbnet::prelude::*, kvengine::encoding::ENCODING_LUT_ITER, queryengine::ActionIter,
+    blueql::util::split_qualified_key,
+    corestore::table::DescribeTable,
+    dbnet::prelude::*,
+    kvengine::{encoding::ENCODING_LUT_ITER, storage::ReadError},
+    queryengine::ActionIter,
     util::compiler,
 };
 
 action!(
-    /// Run an `MGET` query
+    /// Run an `MGET` query. If qualified keys are enabled, keys can be qualified with a table
+    /// (`<keyspace>:<table>:<key>`), in which case they're read from that table instead of the
+    /// current table
     ///
     fn mget(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |size| size != 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        if compiler::unlikely(act.as_ref().any(|key| split_qualified_key(key).is_some())) {
+            return self::mget_qualified(handle, con, act).await;
+        }
         let kve = handle.get_table_with::<P, KVEBlob>()?;
         let encoding_is_okay = ENCODING_LUT_ITER[kve.is_key_encoded()](act.as_ref());
         if compiler::likely(encoding_is_okay) {
-            con.write_typed_array_header(act.len(), kve.get_value_tsymbol())
+            // read everything before writing the header so that an unreadable value
+            // fails the whole query instead of cutting the array short
+            let values = act
+                .map(|key| kve.get_cloned_unchecked(key))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(ReadError::from);
+            let values = translate_read_error::<P, _>(values)?;
+            con.write_typed_array_header(values.len(), kve.get_value_tsymbol())
                 .await?;
-            for key in act {
-                match kve.get_cloned_unchecked(key) {
+            for value in values {
+                match value {
                     Some(v) => con.write_typed_array_element(&v).await?,
                     None => con.write_typed_array_element_null().await?,
                 }
@@ -50,4 +66,78 @@ action!(
         }
         Ok(())
     }
+    /// Run an `MGET` query where the table is resolved separately for every key
+    fn mget_qualified(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        let mut tsymbol = None;
+        let mut values = Vec::with_capacity(act.len());
+        for key in act {
+            let (table, key) = match split_qualified_key(key) {
+                Some(qualified) => {
+                    let entity = qualified.entity();
+                    let entity = handle_entity!(con, entity);
+                    (get_tbl!(&entity, handle, con), qualified.key)
+                }
+                None => (get_tbl!(handle, con), key),
+            };
+            let kve = match <KVEBlob as DescribeTable>::try_get(&table) {
+                Some(kve) => kve,
+                None => return util::err(P::RSTRING_WRONG_MODEL),
+            };
+            if compiler::unlikely(!kve.is_key_ok(key)) {
+                return util::err(P::RCODE_ENCODING_ERROR);
+            }
+            // every string is also a valid binary string, so we fall back to binary strings
+            // if the tables don't agree on the value type
+            tsymbol = match tsymbol {
+                Some(tsymbol) if tsymbol != kve.get_value_tsymbol() => Some(P::TSYMBOL_BINARY),
+                Some(tsymbol) => Some(tsymbol),
+                None => Some(kve.get_value_tsymbol()),
+            };
+            let value = kve.get_cloned_unchecked(key).map_err(ReadError::from);
+            values.push(translate_read_error::<P, _>(value)?);
+        }
+        con.write_typed_array_header(values.len(), tsymbol.unwrap_or(P::TSYMBOL_BINARY))
+            .await?;
+        for value in values {
+            match value {
+                Some(v) => con.write_typed_array_element(&v).await?,
+                None => con.write_typed_array_element_null().await?,
+            }
+        }
+        Ok(())
+    }
+    /// Run a `GETMANY` query. This returns a single typed array with the keys and their values
+    /// interleaved (`[key1, value1, key2, value2, ...]`); the value is null if the key doesn't
+    /// exist
+    ///
+    /// Syntax: `GETMANY <key1> <key2> ...`
+    fn getmany(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        let encoding_is_okay = ENCODING_LUT_ITER[kve.is_key_encoded()](act.as_ref());
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        // keys and values share the array, so they also have to share the type
+        let tsymbol = if kve.get_key_tsymbol() == kve.get_value_tsymbol() {
+            kve.get_value_tsymbol()
+        } else {
+            P::TSYMBOL_BINARY
+        };
+        let pairs = act
+            .map(|key| kve.get_cloned_unchecked(key).map(|value| (key, value)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ReadError::from);
+        let pairs = translate_read_error::<P, _>(pairs)?;
+        con.write_typed_array_header(pairs.len() * 2, tsymbol)
+            .await?;
+        for (key, value) in pairs {
+            con.write_typed_array_element(key).await?;
+            match value {
+                Some(v) => con.write_typed_array_element(&v).await?,
+                None => con.write_typed_array_element_null().await?,
+            }
+        }
+        Ok(())
+    }
 );
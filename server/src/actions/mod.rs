@@ -32,26 +32,55 @@
 
 #[macro_use]
 mod macros;
+pub mod append;
+pub mod bits;
+pub mod bloom;
+pub mod cas;
+pub mod counters;
 pub mod dbsize;
 pub mod del;
 pub mod exists;
+pub mod expire;
 pub mod flushdb;
+pub mod geo;
 pub mod get;
+pub mod getset;
+pub mod hashes;
+pub mod hll;
+pub mod json;
 pub mod keylen;
+pub mod keys;
+pub mod keytype;
 pub mod lists;
 pub mod lskeys;
 pub mod mget;
 pub mod mpop;
 pub mod mset;
 pub mod mupdate;
+pub mod notify;
 pub mod pop;
+pub mod pubsub;
+pub mod randomkey;
+pub mod range;
+pub mod rename;
+pub mod scan;
 pub mod set;
+pub mod sets;
+pub mod snapshot;
 pub mod strong;
+pub mod timeseries;
+pub mod txn;
 pub mod update;
 pub mod uset;
+pub mod watch;
 pub mod whereami;
+pub mod zsets;
 use {
-    crate::{corestore::memstore::DdlError, protocol::interface::ProtocolSpec, util},
+    crate::{
+        cluster::Moved, corestore::memstore::DdlError, kvengine::storage::ReadError,
+        protocol::interface::ProtocolSpec, util,
+    },
+    core::fmt,
     std::io::Error as IoError,
 };
 
@@ -62,19 +91,119 @@ pub type ActionResult<T> = Result<T, ActionError>;
 #[derive(Debug)]
 pub enum ActionError {
     ActionError(&'static [u8]),
+    /// The action was run with the wrong number of arguments
+    ArityError(ArityError),
+    /// The action's keys are owned by another cluster node
+    Moved(Moved),
     IoError(std::io::Error),
 }
 
+impl ActionError {
+    /// Attribute an arity error to the given action (any other error is returned as-is)
+    pub fn in_action(self, action: &[u8]) -> Self {
+        match self {
+            Self::ArityError(mut e) if e.action.is_none() => {
+                e.action = Some(String::from_utf8_lossy(action).into());
+                Self::ArityError(e)
+            }
+            e => e,
+        }
+    }
+}
+
 impl PartialEq for ActionError {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::ActionError(a1), Self::ActionError(a2)) => a1 == a2,
+            (Self::ArityError(e1), Self::ArityError(e2)) => e1 == e2,
+            (Self::Moved(m1), Self::Moved(m2)) => m1 == m2,
             (Self::IoError(ioe1), Self::IoError(ioe2)) => ioe1.to_string() == ioe2.to_string(),
             _ => false,
         }
     }
 }
 
+/// The number of arguments that an action accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly `n` arguments
+    Exactly(usize),
+    /// `n` or more arguments
+    AtLeast(usize),
+    /// Up to `n` arguments
+    AtMost(usize),
+    /// Anywhere from `min` to `max` arguments
+    Between(usize, usize),
+    /// One of the two argument counts
+    Either(usize, usize),
+    /// `head` arguments followed by one or more groups of `group` arguments (like the key
+    /// and the field/value pairs of `HSET`)
+    Groups { head: usize, group: usize },
+}
+
+impl Arity {
+    /// Returns true if an action with this arity can be run with `len` arguments
+    pub const fn accepts(self, len: usize) -> bool {
+        match self {
+            Self::Exactly(n) => len == n,
+            Self::AtLeast(n) => len >= n,
+            Self::AtMost(n) => len <= n,
+            Self::Between(min, max) => len >= min && len <= max,
+            Self::Either(a, b) => len == a || len == b,
+            Self::Groups { head, group } => len > head && (len - head) % group == 0,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        match *self {
+            Self::Exactly(0) => f.write_str("no arguments"),
+            Self::Exactly(n) => write!(f, "{n} argument{}", plural(n)),
+            Self::AtLeast(n) => write!(f, "at least {n} argument{}", plural(n)),
+            Self::AtMost(n) => write!(f, "at most {n} argument{}", plural(n)),
+            Self::Between(min, max) => write!(f, "{min} to {max} arguments"),
+            Self::Either(a, b) => write!(f, "{a} or {b} arguments"),
+            Self::Groups { head, group } => write!(
+                f,
+                "{}, {}, {}, ... arguments",
+                head + group,
+                head + group * 2,
+                head + group * 3
+            ),
+        }
+    }
+}
+
+/// An action was run with an unacceptable number of arguments
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArityError {
+    /// the action, which is filled in by the dispatcher
+    action: Option<Box<str>>,
+    expected: Arity,
+    got: usize,
+}
+
+impl ArityError {
+    pub const fn new(expected: Arity, got: usize) -> Self {
+        Self {
+            action: None,
+            expected,
+            got,
+        }
+    }
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+    pub const fn expected(&self) -> Arity {
+        self.expected
+    }
+    pub const fn got(&self) -> usize {
+        self.got
+    }
+}
+
 impl From<&'static [u8]> for ActionError {
     fn from(e: &'static [u8]) -> Self {
         Self::ActionError(e)
@@ -112,14 +241,45 @@ pub fn translate_ddl_error<P: ProtocolSpec, T>(r: Result<T, DdlError>) -> Result
     }
 }
 
-pub fn ensure_length<P: ProtocolSpec>(len: usize, is_valid: fn(usize) -> bool) -> ActionResult<()> {
-    if util::compiler::likely(is_valid(len)) {
+#[cold]
+#[inline(never)]
+fn map_read_error_to_status<P: ProtocolSpec>(e: ReadError) -> ActionError {
+    let r = match e {
+        ReadError::Encoding => P::RCODE_ENCODING_ERROR,
+        // the storage engine has already logged why
+        ReadError::Unreadable => P::RCODE_SERVER_ERR,
+    };
+    ActionError::ActionError(r)
+}
+
+#[inline(always)]
+pub fn translate_read_error<P: ProtocolSpec, T>(r: Result<T, ReadError>) -> Result<T, ActionError> {
+    match r {
+        Ok(r) => Ok(r),
+        Err(e) => Err(map_read_error_to_status::<P>(e)),
+    }
+}
+
+/// Ensure that an action was run with an acceptable number of arguments. This returns an
+/// error that names the expected number of arguments
+pub fn ensure_arity(len: usize, arity: Arity) -> ActionResult<()> {
+    if util::compiler::likely(arity.accepts(len)) {
         Ok(())
     } else {
-        util::err(P::RCODE_ACTION_ERR)
+        Err(ActionError::ArityError(ArityError::new(arity, len)))
     }
 }
 
+/// Like [`ensure_arity`], but for the arguments that follow a subcommand. The error names
+/// the subcommand along with the action (for example, `LGET LEN`)
+pub fn ensure_subcommand_arity(
+    len: usize,
+    arity: Arity,
+    subcommand: &'static [u8],
+) -> ActionResult<()> {
+    ensure_arity(len, arity).map_err(|e| e.in_action(subcommand))
+}
+
 pub fn ensure_boolean_or_aerr<P: ProtocolSpec>(boolean: bool) -> ActionResult<()> {
     if util::compiler::likely(boolean) {
         Ok(())
@@ -134,7 +294,7 @@ pub mod heya {
     action!(
         /// Returns a `HEY!` `Response`
         fn heya(_handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-            ensure_length::<P>(act.len(), |len| len == 0 || len == 1)?;
+            ensure_arity(act.len(), Arity::Between(0, 1))?;
             if act.len() == 1 {
                 let raw_byte = unsafe { act.next_unchecked() };
                 con.write_mono_length_prefixed_with_tsymbol(raw_byte, b'+')
@@ -25,24 +25,36 @@
 */
 
 use crate::{
-    corestore, dbnet::prelude::*, kvengine::encoding::ENCODING_LUT_ITER,
-    queryengine::ActionIter, util::compiler,
+    corestore,
+    dbnet::prelude::*,
+    kvengine::{encoding::ENCODING_LUT_ITER, storage::StorageKind},
+    queryengine::ActionIter,
+    util::compiler,
 };
 
 action!(
     /// Run an MPOP action
     fn mpop(handle: &corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len != 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
         if registry::state_okay() {
             let kve = handle.get_table_with::<P, KVEBlob>()?;
             let encoding_is_okay = ENCODING_LUT_ITER[kve.is_key_encoded()](act.as_ref());
             if compiler::likely(encoding_is_okay) {
+                if kve.storage_kind() != StorageKind::Memory
+                    && act
+                        .as_ref()
+                        .any(|key| kve.get_cloned_unchecked(key).is_err())
+                {
+                    // don't pop anything if we already know that a value can't be read back
+                    return util::err(P::RCODE_SERVER_ERR);
+                }
                 con.write_typed_array_header(act.len(), kve.get_value_tsymbol())
                     .await?;
                 for key in act {
+                    // a value that became unreadable since the check is left in place
                     match kve.pop_unchecked(key) {
-                        Some(val) => con.write_typed_array_element(&val).await?,
-                        None => con.write_typed_array_element_null().await?,
+                        Ok(Some(val)) => con.write_typed_array_element(&val).await?,
+                        Ok(None) | Err(_) => con.write_typed_array_element_null().await?,
                     }
                 }
             } else {
@@ -25,17 +25,23 @@
 */
 
 use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, util::compiler,
+    actions::expire, corestore::SharedSlice, dbnet::prelude::*, kvengine::expiry, util::compiler,
 };
 
 action!(
     /// Run an `MSET` query
-    fn mset(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+    fn mset(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
         let howmany = act.len();
-        ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let encoding_is_okay = kve.get_pair_iter_encoder()(&act);
         if compiler::likely(encoding_is_okay) {
             let done_howmany: Option<usize> = if registry::state_okay() {
                 let mut didmany = 0;
@@ -57,4 +63,80 @@ action!(
         }
         Ok(())
     }
+    /// Run an `MSETEX` query. Unlike `MSET`, a bad pair doesn't fail the whole batch; instead
+    /// a typed array with the status for each pair (`0` if set, `2` if the key already exists
+    /// and `10` if the pair fails the encoding checks) is returned. A pair that breaks the size
+    /// limits of the table still fails the whole batch
+    ///
+    /// Syntax: `MSETEX <seconds> <key1> <value1> <key2> <value2> ...`
+    fn msetex(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        let howmany = act.len();
+        ensure_arity(howmany, Arity::Groups { head: 1, group: 2 })?;
+        let secs = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are atleast 3 arguments
+            act.next_unchecked()
+        };
+        let deadline = expiry::deadline_after_secs(expire::parse_ttl::<P>(secs)?);
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            let mut statuses = Vec::with_capacity(howmany / 2);
+            while let (Some(key), Some(val)) = (act.next(), act.next()) {
+                let did_we = if kve.is_key_ok(key) && kve.is_val_ok(val) {
+                    Some(kve.set_with_expiry_unchecked(
+                        SharedSlice::new(key),
+                        SharedSlice::new(val),
+                        deadline,
+                    ))
+                } else {
+                    None
+                };
+                statuses.push(P::BATCH_SET_NLUT[did_we]);
+            }
+            con.write_typed_non_null_array(statuses, P::TSYMBOL_STRING)
+                .await?;
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Run a `SETMANY` query. This is `MSET` with the status of every pair returned in a
+    /// typed non-null array (with the same codes as `MSETEX`) instead of a count
+    ///
+    /// Syntax: `SETMANY <key1> <value1> <key2> <value2> ...`
+    fn setmany(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        let howmany = act.len();
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            let mut statuses = Vec::with_capacity(howmany / 2);
+            while let (Some(key), Some(val)) = (act.next(), act.next()) {
+                let did_we = if kve.is_key_ok(key) && kve.is_val_ok(val) {
+                    Some(kve.set_unchecked(SharedSlice::new(key), SharedSlice::new(val)))
+                } else {
+                    None
+                };
+                statuses.push(P::BATCH_SET_NLUT[did_we]);
+            }
+            con.write_typed_non_null_array(statuses, P::TSYMBOL_STRING)
+                .await?;
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
 );
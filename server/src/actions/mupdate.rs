@@ -24,18 +24,18 @@
  *
 */
 
-use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, util::compiler,
-};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
 
 action!(
     /// Run an `MUPDATE` query
     fn mupdate(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         let howmany = act.len();
-        ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let encoding_is_okay = kve.get_pair_iter_encoder()(&act);
         let done_howmany: Option<usize>;
         if compiler::likely(encoding_is_okay) {
             if registry::state_okay() {
@@ -0,0 +1,70 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Keyspace notifications
+//!
+//! `NOTIFY` turns keyspace notifications on or off for the current table (see
+//! [`crate::kvengine::notify`] for what's published)
+
+use crate::{dbnet::prelude::*, kvengine::notify};
+
+const ON: &[u8] = b"on";
+const OFF: &[u8] = b"off";
+
+action!(
+    /// Run a `NOTIFY` query
+    /// ## Syntax
+    /// - `NOTIFY ON`: start publishing notifications for the current table. This returns the
+    /// notification channel
+    /// - `NOTIFY OFF`: stop publishing notifications for the current table
+    fn notify(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let notifier = get_tbl_ref!(handle, con).notifier();
+        match unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there is exactly 1 argument
+            act.next_lowercase_unchecked()
+        }
+        .as_ref()
+        {
+            ON => {
+                let channel = match handle.get_ids() {
+                    (Some(ks), Some(tbl)) => notify::channel_for(ks, tbl),
+                    _ => return util::err(P::RSTRING_DEFAULT_UNSET),
+                };
+                notifier.enable(handle.get_pubsub().clone(), channel.clone());
+                con.write_mono_length_prefixed_with_tsymbol(&channel, P::TSYMBOL_STRING)
+                    .await?;
+            }
+            OFF => {
+                notifier.disable();
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+);
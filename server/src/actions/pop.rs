@@ -28,19 +28,18 @@ use crate::dbnet::prelude::*;
 
 action! {
     fn pop(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 1)?;
+        ensure_arity(act.len(), Arity::Exactly(1))?;
         let key = unsafe {
             // SAFETY: We have checked for there to be one arg
             act.next_unchecked()
         };
         if registry::state_okay() {
             let kve = handle.get_table_with::<P, KVEBlob>()?;
-            match kve.pop(key) {
-                Ok(Some(val)) => con.write_mono_length_prefixed_with_tsymbol(
+            match translate_read_error::<P, _>(kve.pop(key))? {
+                Some(val) => con.write_mono_length_prefixed_with_tsymbol(
                     &val, kve.get_value_tsymbol()
                 ).await?,
-                Ok(None) => return util::err(P::RCODE_NIL),
-                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+                None => return util::err(P::RCODE_NIL),
             }
         } else {
             return util::err(P::RCODE_SERVER_ERR);
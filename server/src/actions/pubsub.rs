@@ -0,0 +1,80 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Pub/Sub
+//!
+//! Actions to subscribe to channels and publish messages to them. Messages published to a
+//! channel are pushed to all the connections that are subscribed to it (see
+//! [`crate::dbnet::pubsub`])
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*};
+
+action!(
+    /// Run a `SUBSCRIBE` query. This returns the number of channels that the connection is
+    /// subscribed to
+    /// ## Syntax
+    /// `SUBSCRIBE <channel1> <channel2> ...`
+    fn subscribe(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let mut subscribed = 0;
+        for channel in act {
+            subscribed = con.subscribe(handle.get_pubsub(), SharedSlice::new(channel));
+        }
+        con.write_usize(subscribed).await?;
+        Ok(())
+    }
+    /// Run an `UNSUBSCRIBE` query. This returns the number of channels that the connection is
+    /// still subscribed to
+    /// ## Syntax
+    /// - `UNSUBSCRIBE <channel1> <channel2> ...`: unsubscribe from the given channels
+    /// - `UNSUBSCRIBE`: unsubscribe from all channels
+    fn unsubscribe(_handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        let mut subscribed = 0;
+        if act.len() == 0 {
+            con.unsubscribe_all();
+        } else {
+            for channel in act {
+                subscribed = con.unsubscribe(channel);
+            }
+        }
+        con.write_usize(subscribed).await?;
+        Ok(())
+    }
+    /// Run a `PUBLISH` query. This returns the number of subscribers that received the message
+    /// ## Syntax
+    /// `PUBLISH <channel> <message>`
+    fn publish(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (channel, message) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let receivers = handle.get_pubsub().publish(channel, message);
+        con.write_usize(receivers).await?;
+        Ok(())
+    }
+);
@@ -0,0 +1,84 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `RANDOMKEY` and `SAMPLE` queries
+//! This module provides functions to pick random keys from a table, which comes in handy for
+//! debugging and for warming caches
+//!
+
+use crate::dbnet::prelude::*;
+
+action!(
+    /// Run a `RANDOMKEY` query. This returns a random key from the table
+    /// ## Syntax
+    /// `RANDOMKEY [<entity>]`
+    fn randomkey(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::AtMost(1))?;
+        let table = if act.is_empty() {
+            get_tbl!(handle, con)
+        } else {
+            let entity = handle_entity!(con, unsafe { act.next_unchecked() });
+            get_tbl!(&entity, handle, con)
+        };
+        match table.sample_keys(1).pop() {
+            Some(key) => {
+                con.write_mono_length_prefixed_with_tsymbol(&key, table.get_key_tsymbol())
+                    .await?
+            }
+            None => return util::err(P::RCODE_NIL),
+        }
+        Ok(())
+    }
+    /// Run a `SAMPLE` query. This returns at most `count` distinct keys picked at random
+    /// ## Syntax
+    /// `SAMPLE [<entity>] <count>`
+    fn sample(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Between(1, 2))?;
+        let table = if act.len() == 2 {
+            let entity = handle_entity!(con, unsafe { act.next_unchecked() });
+            get_tbl!(&entity, handle, con)
+        } else {
+            get_tbl!(handle, con)
+        };
+        let count = match String::from_utf8_lossy(unsafe { act.next_unchecked() }).parse::<usize>()
+        {
+            Ok(count) => count,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let keys = table.sample_keys(count);
+        con.write_typed_non_null_array(keys, table.get_key_tsymbol())
+            .await?;
+        Ok(())
+    }
+);
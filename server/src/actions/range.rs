@@ -0,0 +1,101 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `GETRANGE` and `SETRANGE` queries
+//! This module provides functions to read and overwrite byte ranges of values, without
+//! having to transfer the entire value
+//!
+
+use crate::{actions::ActionResult, dbnet::prelude::*, kvengine::limits::WriteError};
+
+/// Parse a byte offset
+fn parse_offset<P: ProtocolSpec>(offset: &[u8]) -> ActionResult<usize> {
+    match String::from_utf8_lossy(offset).parse::<usize>() {
+        Ok(offset) => Ok(offset),
+        Err(_) => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+action!(
+    /// Run a `GETRANGE` query. This returns the bytes of the value from `start` to `end`
+    /// (both inclusive), clamped to the length of the value
+    /// ## Syntax
+    /// `GETRANGE <key> <start> <end>`
+    fn getrange(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        let (key, start, end) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 3 arguments
+            (
+                act.next_unchecked(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+            )
+        };
+        let (start, end) = (parse_offset::<P>(start)?, parse_offset::<P>(end)?);
+        match translate_read_error::<P, _>(kve.get_range(key, start, end))? {
+            Some(range) => {
+                con.write_mono_length_prefixed_with_tsymbol(&range, kve.get_value_tsymbol())
+                    .await?
+            }
+            None => return util::err(P::RCODE_NIL),
+        }
+        Ok(())
+    }
+    /// Run a `SETRANGE` query. This overwrites the bytes of the value starting at `offset`
+    /// (growing the value if needed) and returns the new length of the value. The offset
+    /// can't be past the end of the value
+    /// ## Syntax
+    /// `SETRANGE <key> <offset> <bytes>`
+    fn setrange(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        let (key, offset, bytes) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 3 arguments
+            (
+                act.next_unchecked(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+            )
+        };
+        let offset = parse_offset::<P>(offset)?;
+        if registry::state_okay() {
+            match kve.set_range(key, offset, bytes) {
+                Ok(Some(Some(len))) => con.write_usize(len).await?,
+                Ok(Some(None)) => return util::err(P::RSTRING_OUT_OF_RANGE),
+                Ok(None) => return util::err(P::RCODE_NIL),
+                Err(WriteError::Encoding) => return util::err(P::RCODE_ENCODING_ERROR),
+                Err(WriteError::TooLarge) => return util::err(P::RSTRING_TOO_LARGE),
+                Err(WriteError::Unreadable) => return util::err(P::RCODE_SERVER_ERR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
@@ -0,0 +1,128 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `RENAME`, `COPY` and `MOVE` queries
+//! This module provides functions to rename, copy and move keys. These work with every model
+//!
+
+use crate::{blueql::util::split_qualified_key, dbnet::prelude::*};
+
+action!(
+    /// Run a `RENAME` query. The key is renamed only if the new key doesn't exist, and its
+    /// expiry (if any) is retained
+    /// ## Syntax
+    /// `RENAME <key> <newkey>`
+    fn rename(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (from, to) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked_bytes())
+        };
+        if registry::state_okay() {
+            let table = get_tbl!(handle, con);
+            match table.rename_key::<P>(from, to)? {
+                Some(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Some(false) => return util::err(P::RCODE_OVERWRITE_ERR),
+                None => return util::err(P::RCODE_NIL),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Run a `COPY` query. The value is copied only if the destination key doesn't exist. The
+    /// destination can be in another table (with the same model) if it is qualified
+    /// (`<keyspace>:<table>:<key>`, if qualified keys are enabled). The expiry of the source key isn't copied
+    /// ## Syntax
+    /// `COPY <key> <destination>`
+    fn copy(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (src, dst) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        if registry::state_okay() {
+            let table = get_tbl!(handle, con);
+            let ret = match split_qualified_key(dst) {
+                Some(qualified) => {
+                    let entity = qualified.entity();
+                    let entity = handle_entity!(con, entity);
+                    let target = get_tbl!(&entity, handle, con);
+                    table.copy_key::<P>(src, &target, qualified.key.into())?
+                }
+                None => table.copy_key::<P>(src, &table, dst.into())?,
+            };
+            match ret {
+                Some(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Some(false) => return util::err(P::RCODE_OVERWRITE_ERR),
+                None => return util::err(P::RCODE_NIL),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Run a `MOVE` query. The key is atomically removed from the current table and inserted
+    /// into the given table (which must have the same model), but only if it doesn't exist
+    /// there. The expiry of the key (if any) is retained
+    /// ## Syntax
+    /// `MOVE <key> <entity>`
+    fn move_key(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (key, entity) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are exactly 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        if registry::state_okay() {
+            let table = get_tbl!(handle, con);
+            let entity = handle_entity!(con, entity);
+            let target = get_tbl!(&entity, handle, con);
+            match table.move_key::<P>(key, &target)? {
+                Some(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Some(false) => return util::err(P::RCODE_OVERWRITE_ERR),
+                None => return util::err(P::RCODE_NIL),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
@@ -0,0 +1,84 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `SCAN` queries
+//! This module provides functions to iterate over the keys of a table in batches
+//!
+
+use crate::{corestore::table::DataModel, dbnet::prelude::*};
+
+const DEFAULT_COUNT: usize = 10;
+
+action!(
+    /// Run a `SCAN` query. This returns an array whose first element is the cursor to continue
+    /// with (which is `0` once the scan is complete), followed by the next `count` keys. A cursor
+    /// of `0` starts a new scan. The cursor is just a position in the table, so the server
+    /// keeps nothing around for a scan: a key that's in the table for the entire scan is
+    /// returned exactly once, while keys that are added or removed during the scan may or may
+    /// not be returned
+    /// ## Syntax
+    /// `SCAN <cursor> [<count>]`
+    fn scan(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
+        ensure_arity(act.len(), Arity::Between(1, 2))?;
+        let table = get_tbl!(handle, con);
+        let cursor = match String::from_utf8_lossy(unsafe { act.next_unchecked() }).parse::<u64>() {
+            Ok(cursor) => cursor,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let count = match act.next() {
+            Some(count) => match String::from_utf8_lossy(count).parse::<usize>() {
+                Ok(count) if count != 0 => count,
+                _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+            },
+            None => DEFAULT_COUNT,
+        };
+        let tsymbol = match table.get_model_ref() {
+            DataModel::KV(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtListmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtSetmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtZsetmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHashmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtCountermap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtBloommap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHllmap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtGeomap(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtTimeseriesmap(kv) => kv.get_key_tsymbol(),
+        };
+        let (next_cursor, keys) = table.scan(cursor, count);
+        con.write_typed_non_null_array_header(keys.len() + 1, tsymbol)
+            .await?;
+        con.write_typed_non_null_array_element(next_cursor.to_string().as_bytes())
+            .await?;
+        for key in keys {
+            con.write_typed_non_null_array_element(&key).await?;
+        }
+        Ok(())
+    }
+);
@@ -27,29 +27,71 @@
 //! # `SET` queries
 //! This module provides functions to work with `SET` queries
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*, queryengine::ActionIter};
+use crate::{
+    actions::expire, corestore::SharedSlice, dbnet::prelude::*, kvengine::expiry,
+    queryengine::ActionIter,
+};
+
+const EXPIRY_FLAG: &[u8] = b"EX";
+const ONLY_IF_ABSENT_FLAG: &[u8] = b"NX";
+const ONLY_IF_PRESENT_FLAG: &[u8] = b"XX";
 
 action!(
     /// Run a `SET` query
+    ///
+    /// Syntax: `SET <key> <value> [NX | XX] [EX <seconds>]`
+    ///
+    /// By default (or with `NX`), the key is only set if it doesn't exist. With `XX`, the key is
+    /// only set if it already exists, in which case its expiry is retained unless a new one is
+    /// provided
     fn set(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 2)?;
+        ensure_arity(act.len(), Arity::Between(2, 5))?;
+        let (key, value) = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there are atleast 2 arguments
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let mut deadline = None;
+        let mut only_if_present = None;
+        while let Some(flag) = act.next_uppercase() {
+            match flag.as_ref() {
+                EXPIRY_FLAG if deadline.is_none() => {
+                    let secs = act.next().unwrap_or_aerr::<P>()?;
+                    deadline = Some(expiry::deadline_after_secs(expire::parse_ttl::<P>(secs)?));
+                }
+                ONLY_IF_ABSENT_FLAG if only_if_present.is_none() => only_if_present = Some(false),
+                ONLY_IF_PRESENT_FLAG if only_if_present.is_none() => only_if_present = Some(true),
+                _ => return util::err(P::RCODE_ACTION_ERR),
+            }
+        }
+        let only_if_present = only_if_present.unwrap_or(false);
         if registry::state_okay() {
             let did_we = {
                 let writer = handle.get_table_with::<P, KVEBlob>()?;
-                match unsafe {
-                    // UNSAFE(@ohsayan): This is completely safe as we've already checked
-                    // that there are exactly 2 arguments
-                    writer.set(
-                        SharedSlice::new(act.next().unsafe_unwrap()),
-                        SharedSlice::new(act.next().unsafe_unwrap()),
-                    )
-                } {
-                    Ok(true) => Some(true),
-                    Ok(false) => Some(false),
-                    Err(()) => None,
+                if !writer.pair_fits(key, value) {
+                    return util::err(P::RSTRING_TOO_LARGE);
+                }
+                if writer.is_key_ok(key) && writer.is_val_ok(value) {
+                    let (key, value) = (SharedSlice::new(key), SharedSlice::new(value));
+                    Some(match (only_if_present, deadline) {
+                        (true, Some(deadline)) => {
+                            writer.update_with_expiry_unchecked(key, value, deadline)
+                        }
+                        (true, None) => writer.update_unchecked(key, value),
+                        (false, Some(deadline)) => {
+                            writer.set_with_expiry_unchecked(key, value, deadline)
+                        }
+                        (false, None) => writer.set_unchecked(key, value),
+                    })
+                } else {
+                    None
                 }
             };
-            con._write_raw(P::SET_NLUT[did_we]).await?;
+            if only_if_present {
+                con._write_raw(P::UPDATE_NLUT[did_we]).await?;
+            } else {
+                con._write_raw(P::SET_NLUT[did_we]).await?;
+            }
         } else {
             con._write_raw(P::RCODE_SERVER_ERR).await?;
         }
@@ -0,0 +1,147 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Set actions
+//!
+//! Actions for the set model (`keymap(str,set<str>)` and friends). Members are always returned
+//! in sorted order
+
+use crate::{
+    corestore::SharedSlice,
+    dbnet::prelude::*,
+    kvengine::{encoding::ENCODING_LUT_ITER, sets::SetAlgebra},
+    util::compiler,
+};
+
+/// Write out the members of a set as a typed array
+macro_rules! writeset {
+    ($con:expr, $setmap:expr, $members:expr) => {{
+        $con.write_typed_non_null_array_header($members.len(), $setmap.get_value_tsymbol())
+            .await?;
+        for member in $members {
+            $con.write_typed_non_null_array_element(&member).await?;
+        }
+    }};
+}
+
+action! {
+    /// Handle an `SADD` query for the set model. The set is created if it doesn't exist and
+    /// the number of newly added members is returned
+    /// ## Syntax
+    /// `SADD <myset> <members ...>`
+    fn sadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let setmap = handle.get_table_with::<P, KVESet>()?;
+        let setname = unsafe { act.next_unchecked_bytes() };
+        if compiler::unlikely(!ENCODING_LUT_ITER[setmap.is_val_encoded()](act.as_ref())) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !setmap.fits(&setname, act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match setmap.set_add(setname, act.map(SharedSlice::new).collect()) {
+                Ok(added) => con.write_usize(added).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle an `SREM` query for the set model. This returns the number of members that
+    /// were removed
+    /// ## Syntax
+    /// `SREM <myset> <members ...>`
+    fn srem(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(2))?;
+        let setmap = handle.get_table_with::<P, KVESet>()?;
+        let setname = unsafe { act.next_unchecked() };
+        if registry::state_okay() {
+            match setmap.set_remove(setname, act) {
+                Ok(Some(removed)) => con.write_usize(removed).await?,
+                Ok(None) => return util::err(P::RCODE_NIL),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle an `SMEMBERS` query for the set model
+    /// ## Syntax
+    /// `SMEMBERS <myset>`
+    fn smembers(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let setmap = handle.get_table_with::<P, KVESet>()?;
+        let setname = unsafe { act.next_unchecked() };
+        match setmap.set_members(setname) {
+            Ok(Some(members)) => writeset!(con, setmap, members),
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
+
+action! {
+    /// Handle an `SUNION` query for the set model. Sets that don't exist are treated as
+    /// empty sets
+    /// ## Syntax
+    /// `SUNION <set1> <set2> ...`
+    fn sunion(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::algebra(handle, con, act, SetAlgebra::Union).await
+    }
+    /// Handle an `SINTER` query for the set model. Sets that don't exist are treated as
+    /// empty sets
+    /// ## Syntax
+    /// `SINTER <set1> <set2> ...`
+    fn sinter(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::algebra(handle, con, act, SetAlgebra::Intersect).await
+    }
+    /// Handle an `SDIFF` query for the set model. This returns the members of the first set
+    /// that are not present in any of the other sets
+    /// ## Syntax
+    /// `SDIFF <set1> <set2> ...`
+    fn sdiff(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        self::algebra(handle, con, act, SetAlgebra::Diff).await
+    }
+    /// Run a set operation across the provided sets
+    fn algebra(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        act: ActionIter<'a>,
+        op: SetAlgebra
+    ) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let setmap = handle.get_table_with::<P, KVESet>()?;
+        match setmap.set_algebra(op, act) {
+            Ok(members) => writeset!(con, setmap, members),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
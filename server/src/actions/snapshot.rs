@@ -0,0 +1,98 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Point-in-time reads
+//!
+//! `SNAPSHOT` gives a connection a consistent view of a table as of a point in time, that it
+//! can keep reading from while writes to the table continue (see
+//! [`crate::kvengine::snapshot`])
+
+use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::storage::ReadError};
+
+const BEGIN: &[u8] = b"begin";
+const READ: &[u8] = b"read";
+const END: &[u8] = b"end";
+
+action!(
+    /// Run a `SNAPSHOT` query
+    /// ## Syntax
+    /// - `SNAPSHOT BEGIN`: take a snapshot of the current table. This replaces the current
+    /// snapshot of the connection (if any)
+    /// - `SNAPSHOT READ <key> [<key> ...]`: read the keys from the snapshot (even if the
+    /// connection has switched to another table since). This returns an array with a null
+    /// for every key that didn't exist when the snapshot was taken
+    /// - `SNAPSHOT END`: drop the snapshot
+    fn snapshot(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        match unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there is at least 1 argument
+            act.next_lowercase_unchecked()
+        }
+        .as_ref()
+        {
+            BEGIN => {
+                ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"SNAPSHOT BEGIN")?;
+                let snapshot = handle.get_table_with::<P, KVEBlob>()?.snapshot();
+                con.begin_snapshot(snapshot);
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            READ => {
+                ensure_subcommand_arity(act.len(), Arity::AtLeast(1), b"SNAPSHOT READ")?;
+                let snapshot = match con.snapshot() {
+                    Some(snapshot) => snapshot,
+                    None => return util::err(P::RSTRING_NO_SNAPSHOT),
+                };
+                if !act.as_ref().all(|key| snapshot.is_key_ok(key)) {
+                    return util::err(P::RCODE_ENCODING_ERROR);
+                }
+                let tsymbol = snapshot.get_value_tsymbol();
+                let values = act
+                    .map(|key| snapshot.get(key))
+                    .collect::<Result<Vec<Option<SharedSlice>>, _>>()
+                    .map_err(ReadError::from);
+                let values = translate_read_error::<P, _>(values)?;
+                con.write_typed_array_header(values.len(), tsymbol).await?;
+                for value in values {
+                    match value {
+                        Some(v) => con.write_typed_array_element(&v).await?,
+                        None => con.write_typed_array_element_null().await?,
+                    }
+                }
+            }
+            END => {
+                ensure_subcommand_arity(act.len(), Arity::Exactly(0), b"SNAPSHOT END")?;
+                if con.end_snapshot() {
+                    con._write_raw(P::RCODE_OKAY).await?;
+                } else {
+                    return util::err(P::RSTRING_NO_SNAPSHOT);
+                }
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+);
@@ -28,7 +28,7 @@ use {
     crate::{
         actions::strong::StrongActionResult,
         dbnet::prelude::*,
-        kvengine::{KVEStandard, SingleEncoder},
+        kvengine::{notify::Event, KVEStandard, SingleEncoder},
         protocol::iter::DerefUnsafeSlice,
         util::compiler,
     },
@@ -41,7 +41,7 @@ action! {
     /// This either returns `Okay` if all the keys were `del`eted, or it returns a
     /// `Nil`, which is code `1`
     fn sdel(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len != 0)?;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
         if registry::state_okay() {
             // guarantee one check: consistency
@@ -127,7 +127,9 @@ pub(super) fn snapshot_and_del<'a, T: 'a + DerefUnsafeSlice>(
                 // value after we snapshotted it. In that case, let this key
                 // be whatever the "newer" value is. Since our snapshot is a "happens-before"
                 // thing, this is absolutely fine
-                let _ = lowtable.remove_if(key, |_, val| val.eq(&snapshot));
+                if lowtable.true_remove_if(key, |_, val| val.eq(&snapshot)) {
+                    kve.notify(Event::Del, key);
+                }
             });
             StrongActionResult::Okay
         } else {
@@ -29,7 +29,7 @@ use {
         actions::strong::StrongActionResult,
         corestore::SharedSlice,
         dbnet::prelude::*,
-        kvengine::{DoubleEncoder, KVEStandard},
+        kvengine::{notify::Event, DoubleEncoder, KVEStandard},
         protocol::iter::DerefUnsafeSlice,
         util::compiler,
     },
@@ -43,8 +43,11 @@ action! {
     /// `Overwrite Error` or code `2`
     fn sset(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
         let howmany = act.len();
-        ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
         if registry::state_okay() {
             let encoder = kve.get_double_encoder();
             let outcome = unsafe {
@@ -103,15 +106,14 @@ pub(super) fn snapshot_and_insert<'a, T: 'a + DerefUnsafeSlice>(
     }
     if registry::state_okay() {
         if key_iter_stat_ok {
-            let _kve = kve;
             let lowtable = lowtable;
             // fine, the keys were non-existent when we looked at them
             while let (Some(key), Some(value)) = (act.next(), act.next()) {
                 unsafe {
-                    if let Some(fresh) =
-                        lowtable.fresh_entry(SharedSlice::new(key.deref_slice()))
-                    {
-                        fresh.insert(SharedSlice::new(value.deref_slice()));
+                    let key = key.deref_slice();
+                    if let Some(fresh) = lowtable.fresh_entry(SharedSlice::new(key)) {
+                        fresh.insert(kve.pack(SharedSlice::new(value.deref_slice())));
+                        kve.notify(Event::Set, key);
                     }
                     // we don't care if some other thread initialized the value we checked
                     // it. We expected a fresh entry, so that's what we'll check and use
@@ -29,7 +29,7 @@ use {
         actions::strong::StrongActionResult,
         corestore::SharedSlice,
         dbnet::prelude::*,
-        kvengine::{DoubleEncoder, KVEStandard},
+        kvengine::{notify::Event, DoubleEncoder, KVEStandard},
         protocol::iter::DerefUnsafeSlice,
         util::compiler,
     },
@@ -43,8 +43,11 @@ action! {
     /// or code `1`
     fn supdate(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
         let howmany = act.len();
-        ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
         if registry::state_okay() {
             let encoder = kve.get_double_encoder();
             let outcome = unsafe {
@@ -123,11 +126,12 @@ pub(super) fn snapshot_and_update<'a, T: 'a + DerefUnsafeSlice>(
                 unsafe {
                     // When we snapshotted, we looked at `snapshot`. If the value is still the
                     // same, then we'll update it. Otherwise, let it be
-                    if let Some(mut mutable) =
-                        lowtable.mut_entry(SharedSlice::new(key.deref_slice()))
-                    {
+                    let key = key.deref_slice();
+                    if let Some(mut mutable) = lowtable.mut_entry(SharedSlice::new(key)) {
                         if mutable.value().eq(&snapshot) {
-                            mutable.insert(SharedSlice::new(value.deref_slice()));
+                            mutable.insert(kve.pack(SharedSlice::new(value.deref_slice())));
+                            drop(mutable);
+                            kve.notify(Event::Update, key);
                         } else {
                             drop(mutable);
                         }
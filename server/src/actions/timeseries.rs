@@ -0,0 +1,158 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Time series actions
+//!
+//! Actions for the time series model (`keymap(str,timeseries)` and friends). Every series is
+//! a list of samples (a timestamp in milliseconds and a value) ordered by their timestamps,
+//! and samples older than the retention period of the series are dropped
+
+use crate::{
+    actions::ActionResult, corestore::timeseries::Sample, dbnet::prelude::*, kvengine::expiry,
+};
+
+/// Parse a timestamp. `*` is the current time
+fn parse_timestamp<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<u64> {
+    if raw == b"*" {
+        return Ok(expiry::now_millis());
+    }
+    match String::from_utf8_lossy(raw).parse::<u64>() {
+        Ok(timestamp) => Ok(timestamp),
+        Err(_) => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Parse a timestamp for a range bound, where `-` and `+` are the lowest and highest
+/// possible timestamps respectively
+fn parse_bound<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<u64> {
+    match raw {
+        b"-" => Ok(u64::MIN),
+        b"+" => Ok(u64::MAX),
+        raw => parse_timestamp::<P>(raw),
+    }
+}
+
+/// Parse a sample value. Values are finite 64-bit floats
+fn parse_value<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<f64> {
+    match String::from_utf8_lossy(raw).parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+action! {
+    /// Handle a `TS.ADD` query. The series is created if it doesn't exist and a sample with
+    /// the same timestamp as an existing one replaces it. If `RETENTION` is passed, it sets
+    /// the retention period of the series (in milliseconds; 0 keeps samples forever). This
+    /// returns the timestamp of the sample
+    /// ## Syntax
+    /// `TS.ADD <key> <timestamp | *> <value> [RETENTION <millis>]`
+    fn ts_add(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Either(3, 5))?;
+        let tsmap = handle.get_table_with::<P, KVETimeseries>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked_bytes()
+        };
+        if !tsmap.key_fits(&key) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let (timestamp, value) = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let sample = Sample::new(parse_timestamp::<P>(timestamp)?, parse_value::<P>(value)?);
+        let retention = match (act.next(), act.next()) {
+            (Some(flag), Some(millis)) if flag.eq_ignore_ascii_case(b"RETENTION") => {
+                match String::from_utf8_lossy(millis).parse::<u64>() {
+                    Ok(millis) => Some(millis),
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                }
+            }
+            (Some(_), Some(_)) => return util::err(P::RCODE_ACTION_ERR),
+            _ => None,
+        };
+        if registry::state_okay() {
+            match tsmap.ts_add(key, sample, retention) {
+                Ok(Some(_)) => con.write_int64(sample.timestamp).await?,
+                // the sample is older than the retention period
+                Ok(None) => return util::err(P::RSTRING_OUT_OF_RANGE),
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `TS.RANGE` query. This returns a flat array with the timestamp and value of
+    /// every sample in the (inclusive) range, oldest first
+    /// ## Syntax
+    /// `TS.RANGE <key> <from | -> <to | +>`
+    fn ts_range(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let tsmap = handle.get_table_with::<P, KVETimeseries>()?;
+        let (key, from, to) = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            (act.next_unchecked(), act.next_unchecked(), act.next_unchecked())
+        };
+        let (from, to) = (parse_bound::<P>(from)?, parse_bound::<P>(to)?);
+        match tsmap.ts_range(key, from, to) {
+            Ok(Some(samples)) => {
+                con.write_flat_array_header(samples.len() * 2).await?;
+                for sample in samples {
+                    con.write_int64(sample.timestamp).await?;
+                    con.write_string(&sample.value.to_string()).await?;
+                }
+            }
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+    /// Handle a `TS.LAST` query. This returns a flat array with the timestamp and value of
+    /// the newest sample in the series
+    /// ## Syntax
+    /// `TS.LAST <key>`
+    fn ts_last(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(1))?;
+        let tsmap = handle.get_table_with::<P, KVETimeseries>()?;
+        let key = unsafe {
+            // UNSAFE(@ohsayan): We have checked the length above
+            act.next_unchecked()
+        };
+        match tsmap.ts_last(key) {
+            Ok(Some(Some(sample))) => {
+                con.write_flat_array_header(2).await?;
+                con.write_int64(sample.timestamp).await?;
+                con.write_string(&sample.value.to_string()).await?;
+            }
+            // the series doesn't exist or has no samples
+            Ok(_) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Transactions
+//!
+//! `MULTI` starts a transaction on the connection. Every write action that follows is queued
+//! instead of being run, until `EXEC` applies all of them atomically on the current table
+//! (or `DISCARD` throws them away). Only `SET`, `UPDATE`, `USET` and `DEL` can be queued, and if
+//! any queued action fails on `EXEC`, all the changes are rolled back
+
+use crate::{dbnet::prelude::*, kvengine::txn::TxnOp, storage::v1::wal};
+
+action!(
+    /// Run a `MULTI` query
+    fn multi(_handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(0))?;
+        if con.begin_transaction() {
+            con._write_raw(P::RCODE_OKAY).await?;
+            Ok(())
+        } else {
+            util::err(P::RSTRING_TXN_ALREADY_ACTIVE)
+        }
+    }
+    /// Run an `EXEC` query
+    fn exec(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(0))?;
+        let ops = match con.take_transaction() {
+            Some(ops) => ops,
+            None => return util::err(P::RSTRING_TXN_NOT_ACTIVE),
+        };
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            let ops_fit = ops
+                .iter()
+                .all(|op| kve.fits(op.key(), op.value().map(|v| &v[..])));
+            if !ops_fit {
+                return util::err(P::RSTRING_TOO_LARGE);
+            }
+            let scope = wal::Scope::current(handle);
+            let sequence = wal::sequence(wal::durability(handle), scope).await;
+            match kve.apply_transaction(&ops) {
+                Ok(true) => {
+                    sequence.log_transaction(handle.get_ids(), &ops).await;
+                    con._write_raw(P::RCODE_OKAY).await?
+                }
+                Ok(false) => con._write_raw(P::RSTRING_TXN_ABORTED).await?,
+                Err(()) => con._write_raw(P::RCODE_ENCODING_ERROR).await?,
+            }
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+    /// Run a `DISCARD` query
+    fn discard(_handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(0))?;
+        if con.take_transaction().is_some() {
+            con._write_raw(P::RCODE_OKAY).await?;
+            Ok(())
+        } else {
+            util::err(P::RSTRING_TXN_NOT_ACTIVE)
+        }
+    }
+    /// Queue an action in the current transaction. This is used in place of the usual
+    /// dispatch when a transaction is in progress
+    fn queue(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let action = act
+            .next_uppercase()
+            .unwrap_or_custom_aerr(P::RCODE_PACKET_ERR)?;
+        // this bypasses the usual dispatch, so we attribute arity errors ourselves
+        let op = match action.as_ref() {
+            b"EXEC" => {
+                return exec(handle, con, act)
+                    .await
+                    .map_err(|e| e.in_action(&action))
+            }
+            b"DISCARD" => {
+                return discard(handle, con, act)
+                    .await
+                    .map_err(|e| e.in_action(&action))
+            }
+            b"MULTI" => {
+                return multi(handle, con, act)
+                    .await
+                    .map_err(|e| e.in_action(&action))
+            }
+            b"SET" | b"UPDATE" | b"USET" => {
+                ensure_arity(act.len(), Arity::Exactly(2)).map_err(|e| e.in_action(&action))?;
+                let (key, value) = unsafe {
+                    // UNSAFE(@ohsayan): We've already checked that there are exactly 2 arguments
+                    (act.next_unchecked_bytes(), act.next_unchecked_bytes())
+                };
+                match action.as_ref() {
+                    b"SET" => TxnOp::Set(key, value),
+                    b"UPDATE" => TxnOp::Update(key, value),
+                    _ => TxnOp::Upsert(key, value),
+                }
+            }
+            b"DEL" => {
+                ensure_arity(act.len(), Arity::Exactly(1)).map_err(|e| e.in_action(&action))?;
+                TxnOp::Del(unsafe {
+                    // UNSAFE(@ohsayan): We've already checked that there is exactly 1 argument
+                    act.next_unchecked_bytes()
+                })
+            }
+            _ => return util::err(P::RSTRING_TXN_UNSUPPORTED_ACTION),
+        };
+        con.queue_txn_op(op);
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+);
@@ -33,18 +33,19 @@ use crate::{corestore::SharedSlice, dbnet::prelude::*};
 action!(
     /// Run an `UPDATE` query
     fn update(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 2)?;
+        ensure_arity(act.len(), Arity::Exactly(2))?;
         if registry::state_okay() {
             let did_we = {
                 let writer = handle.get_table_with::<P, KVEBlob>()?;
-                match unsafe {
+                let (key, value) = unsafe {
                     // UNSAFE(@ohsayan): This is completely safe as we've already checked
                     // that there are exactly 2 arguments
-                    writer.update(
-                        SharedSlice::new(act.next_unchecked()),
-                        SharedSlice::new(act.next_unchecked()),
-                    )
-                } {
+                    (act.next_unchecked(), act.next_unchecked())
+                };
+                if !writer.pair_fits(key, value) {
+                    return util::err(P::RSTRING_TOO_LARGE);
+                }
+                match writer.update(SharedSlice::new(key), SharedSlice::new(value)) {
                     Ok(true) => Some(true),
                     Ok(false) => Some(false),
                     Err(()) => None,
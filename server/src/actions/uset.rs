@@ -24,10 +24,7 @@
  *
 */
 
-use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, queryengine::ActionIter, util::compiler,
-};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, queryengine::ActionIter, util::compiler};
 
 action!(
     /// Run an `USET` query
@@ -35,9 +32,12 @@ action!(
     /// This is like "INSERT or UPDATE"
     fn uset(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         let howmany = act.len();
-        ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
+        ensure_arity(howmany, Arity::Groups { head: 0, group: 2 })?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        if !kve.pairs_fit(act.as_ref()) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        let encoding_is_okay = kve.get_pair_iter_encoder()(&act);
         if compiler::likely(encoding_is_okay) {
             if registry::state_okay() {
                 while let (Some(key), Some(val)) = (act.next(), act.next()) {
@@ -0,0 +1,75 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `WATCH` queries
+//!
+//! `WATCH` parks the connection until a key in the current table changes, so that clients can
+//! wait for work without polling. Expiries are only seen once the key is actually evicted (see
+//! [`crate::kvengine::expiry`])
+
+use {
+    crate::{actions::expire::parse_ttl, dbnet::prelude::*},
+    std::time::Duration,
+    tokio::time,
+};
+
+action!(
+    /// Run a `WATCH` query. This returns the change (`set`, `update`, `del` or `expired`) once
+    /// the key changes, or nil if the timeout (in seconds) fires first. A timeout of `0` waits
+    /// forever
+    /// ## Syntax
+    /// `WATCH <key> <timeout>`
+    fn watch(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let (key, timeout) = unsafe {
+            // UNSAFE(@ohsayan): We've already checked that there are exactly 2 arguments
+            (act.next_unchecked_bytes(), act.next_unchecked())
+        };
+        let timeout = parse_ttl::<P>(timeout)?;
+        let tbl = get_tbl_ref!(handle, con);
+        if tbl.key_exists(&key).is_err() {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        let mut waiter = tbl.notifier().waiters().register(key);
+        // don't hold back the responses to any queries that were pipelined before this one
+        con.flush().await?;
+        let event = if timeout == 0 {
+            waiter.changed().await
+        } else {
+            time::timeout(Duration::from_secs(timeout), waiter.changed())
+                .await
+                .unwrap_or(None)
+        };
+        match event {
+            Some(event) => {
+                con.write_mono_length_prefixed_with_tsymbol(event.name(), P::TSYMBOL_STRING)
+                    .await?
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+);
@@ -28,7 +28,7 @@ use crate::dbnet::prelude::*;
 
 action! {
     fn whereami(store: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 0)?;
+        ensure_arity(act.len(), Arity::Exactly(0))?;
         match store.get_ids() {
             (Some(ks), Some(tbl)) =>  {
                 con.write_typed_non_null_array_header(2, b'+').await?;
@@ -0,0 +1,111 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Sorted set actions
+//!
+//! Actions for the sorted set model (`keymap(str,zset<str>)` and friends)
+
+use crate::{actions::ActionResult, corestore::SharedSlice, dbnet::prelude::*, util::compiler};
+
+/// Parse a score. Scores are 64-bit floats and can be `-inf` or `+inf`, but never `NaN`
+fn parse_score<P: ProtocolSpec>(raw: &[u8]) -> ActionResult<f64> {
+    match String::from_utf8_lossy(raw).parse::<f64>() {
+        Ok(score) if !score.is_nan() => Ok(score),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+action! {
+    /// Handle a `ZADD` query for the sorted set model. The sorted set is created if it doesn't
+    /// exist, and the score of a member that already exists is updated. This returns the number
+    /// of newly added members
+    /// ## Syntax
+    /// `ZADD <myzset> <score> <member> [<score> <member> ...]`
+    fn zadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Groups { head: 1, group: 2 })?;
+        let zsetmap = handle.get_table_with::<P, KVEZset>()?;
+        let zsetname = unsafe { act.next_unchecked_bytes() };
+        let venc_ok = zsetmap.get_val_encoder();
+        let mut members = Vec::with_capacity(act.len() / 2);
+        while let (Some(score), Some(member)) = (act.next(), act.next()) {
+            if compiler::unlikely(!venc_ok(member)) {
+                return util::err(P::RCODE_ENCODING_ERROR);
+            }
+            members.push((parse_score::<P>(score)?, SharedSlice::new(member)));
+        }
+        if !zsetmap.fits(&zsetname, members.iter().map(|(_, member)| &member[..])) {
+            return util::err(P::RSTRING_TOO_LARGE);
+        }
+        if registry::state_okay() {
+            match zsetmap.zset_add(zsetname, members) {
+                Ok(added) => con.write_usize(added).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+    /// Handle a `ZRANGEBYSCORE` query for the sorted set model. This returns the members with
+    /// a score between `min` and `max` (both inclusive) in ascending order of scores
+    /// ## Syntax
+    /// `ZRANGEBYSCORE <myzset> <min> <max>`
+    fn zrangebyscore(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(3))?;
+        let zsetmap = handle.get_table_with::<P, KVEZset>()?;
+        let (zsetname, min, max) = unsafe {
+            (act.next_unchecked(), act.next_unchecked(), act.next_unchecked())
+        };
+        let (min, max) = (parse_score::<P>(min)?, parse_score::<P>(max)?);
+        match zsetmap.zset_range_by_score(zsetname, min, max) {
+            Ok(Some(members)) => {
+                con.write_typed_non_null_array_header(members.len(), zsetmap.get_value_tsymbol())
+                    .await?;
+                for member in members {
+                    con.write_typed_non_null_array_element(&member).await?;
+                }
+            }
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+    /// Handle a `ZRANK` query for the sorted set model. This returns the zero-based position
+    /// of the member in ascending order of scores
+    /// ## Syntax
+    /// `ZRANK <myzset> <member>`
+    fn zrank(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::Exactly(2))?;
+        let zsetmap = handle.get_table_with::<P, KVEZset>()?;
+        let (zsetname, member) = unsafe { (act.next_unchecked(), act.next_unchecked()) };
+        match zsetmap.zset_rank(zsetname, member) {
+            Ok(Some(Some(rank))) => con.write_usize(rank).await?,
+            Ok(Some(None)) | Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
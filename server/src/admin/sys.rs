@@ -26,31 +26,184 @@
 
 use {
     crate::{
-        corestore::booltable::BoolTable, dbnet::prelude::*,
-        storage::v1::interface::DIR_ROOT,
+        actions::ActionResult,
+        blueql::{self, Entity},
+        cdc::{self, Consumer},
+        cluster::{
+            self,
+            backup::{self, BackupError},
+            migration::{self, MigrateError},
+            AddSlots,
+        },
+        corestore::{arena, booltable::BoolTable, memstore::ObjectID},
+        dbnet::{
+            self, admission,
+            clients::{self, ClientInfo},
+            monitor::Filter,
+            prelude::*,
+            BufferedSocketStream,
+        },
+        health, memory,
+        metrics::{self, Latency},
+        queryengine,
+        replication::{self, Primary},
+        services::{
+            bgsave, compaction,
+            confreload::{self, ReloadError},
+        },
+        kvengine::encoding,
+        storage::v1::{
+            export::{self, ExportError},
+            interface::DIR_ROOT,
+            quarantine,
+            sengine::SnapshotActionResult,
+            wal,
+        },
+        IoResult,
     },
+    core::str,
     libsky::VERSION,
+    std::{io::ErrorKind, path::PathBuf},
 };
 
 const INFO: &[u8] = b"info";
 const METRIC: &[u8] = b"metric";
+const STRICTUTF8: &[u8] = b"strictutf8";
+const MAXQUERYSIZE: &[u8] = b"maxquerysize";
+const MAXQUERYARGS: &[u8] = b"maxqueryargs";
+const RELOAD: &[u8] = b"reload";
+const READONLY: &[u8] = b"readonly";
+const RELOADCONF: &[u8] = b"reloadconf";
+const METRICS: &[u8] = b"metrics";
+const LATENCY: &[u8] = b"latency";
+const CLIENT: &[u8] = b"client";
+const REQUESTID: &[u8] = b"requestid";
+const HEALTH: &[u8] = b"health";
+const MONITOR: &[u8] = b"monitor";
+const SNAPSHOT: &[u8] = b"snapshot";
+const COMPACT: &[u8] = b"compact";
+const FLUSH: &[u8] = b"flush";
+const EXPORT: &[u8] = b"export";
+const IMPORT: &[u8] = b"import";
+const SYNC: &[u8] = b"sync";
+const REPLICAOF: &[u8] = b"replicaof";
+const REPLICATION: &[u8] = b"replication";
+const FAILOVER: &[u8] = b"failover";
+const CLUSTER: &[u8] = b"cluster";
+const CDC: &[u8] = b"cdc";
+const MEMUSAGE: &[u8] = b"memusage";
 const INFO_PROTOCOL: &[u8] = b"protocol";
 const INFO_PROTOVER: &[u8] = b"protover";
 const INFO_VERSION: &[u8] = b"version";
 const METRIC_HEALTH: &[u8] = b"health";
 const METRIC_STORAGE_USAGE: &[u8] = b"storage";
-const ERR_UNKNOWN_PROPERTY: &[u8] = b"!16\nunknown-property\n";
-const ERR_UNKNOWN_METRIC: &[u8] = b"!14\nunknown-metric\n";
+const METRIC_CONNECTIONS: &[u8] = b"connections";
+const METRIC_READONLY: &[u8] = b"readonly";
+const METRIC_MEMORY: &[u8] = b"memory";
+const METRIC_QUARANTINED: &[u8] = b"quarantined";
+const STRICTUTF8_ON: &[u8] = b"on";
+const STRICTUTF8_OFF: &[u8] = b"off";
+const RELOAD_TLS: &[u8] = b"tls";
+const READONLY_ON: &[u8] = b"on";
+const READONLY_OFF: &[u8] = b"off";
+const CLIENT_ID: &[u8] = b"id";
+const CLIENT_LIST: &[u8] = b"list";
+const CLIENT_KILL: &[u8] = b"kill";
+const MONITOR_ON: &[u8] = b"on";
+const MONITOR_OFF: &[u8] = b"off";
+const REQUESTID_ON: &[u8] = b"on";
+const REQUESTID_OFF: &[u8] = b"off";
+const SNAPSHOT_LIST: &[u8] = b"list";
+const SNAPSHOT_DELETE: &[u8] = b"delete";
+const SNAPSHOT_RESTORE: &[u8] = b"restore";
+const COMPACT_STATUS: &[u8] = b"status";
+const REPLICATION_INFO: &[u8] = b"info";
+const REPLICAOF_NO: &[u8] = b"no";
+const REPLICAOF_ONE: &[u8] = b"one";
+const CLUSTER_INFO: &[u8] = b"info";
+const CLUSTER_SLOTS: &[u8] = b"slots";
+const CLUSTER_KEYSLOT: &[u8] = b"keyslot";
+const CLUSTER_MEET: &[u8] = b"meet";
+const CLUSTER_ADDSLOTS: &[u8] = b"addslots";
+const CLUSTER_GOSSIP: &[u8] = b"gossip";
+const CLUSTER_MIGRATE: &[u8] = b"migrate";
+const CLUSTER_TAKEOVER: &[u8] = b"takeover";
+const CLUSTER_IMPORT: &[u8] = b"import";
+const CLUSTER_APPLY: &[u8] = b"apply";
+const CLUSTER_BACKUP: &[u8] = b"backup";
+const CLUSTER_FREEZE: &[u8] = b"freeze";
+const CLUSTER_SNAPSHOT: &[u8] = b"snapshot";
+const CLUSTER_THAW: &[u8] = b"thaw";
+const CDC_STREAM: &[u8] = b"stream";
+const CDC_STOP: &[u8] = b"stop";
+const CDC_INFO: &[u8] = b"info";
 
 const HEALTH_TABLE: BoolTable<&str> = BoolTable::new("good", "critical");
+const READONLY_TABLE: BoolTable<&str> = BoolTable::new("on", "off");
+const READY_TABLE: BoolTable<&str> = BoolTable::new("true", "false");
+const RELOADCONF_APPLIED: &str = "applied";
+const RELOADCONF_RESTART: &str = "restart";
+const SNAPSHOT_LOCAL: &str = "local";
+const SNAPSHOT_REMOTE: &str = "remote";
 
 action! {
-    fn sys(_handle: &Corestore, con: &mut Connection<C, P>, iter: ActionIter<'_>) {
+    fn sys(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: ActionIter<'_>
+    ) {
         let mut iter = iter;
-        ensure_boolean_or_aerr::<P>(iter.len() == 2)?;
-        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+        ensure_boolean_or_aerr::<P>((1..=6).contains(&iter.len()))?;
+        let subaction = unsafe { iter.next_lowercase_unchecked() };
+        match subaction.as_ref() {
+            // these don't take an argument
+            RELOADCONF | METRICS | HEALTH | SYNC | MEMUSAGE => {
+                ensure_boolean_or_aerr::<P>(iter.is_empty())?
+            }
+            // these take an optional argument
+            LATENCY | COMPACT | FLUSH => ensure_boolean_or_aerr::<P>(iter.len() <= 1)?,
+            // these check their arguments themselves
+            CLIENT | MONITOR | SNAPSHOT | CLUSTER | CDC => {
+                ensure_boolean_or_aerr::<P>(!iter.is_empty())?
+            }
+            // these take two arguments (the second one is optional for an import)
+            EXPORT => ensure_boolean_or_aerr::<P>(iter.len() == 2)?,
+            IMPORT => ensure_boolean_or_aerr::<P>(!iter.is_empty())?,
+            // a primary, with the login to use (if any), or `NO ONE` (with the ID of a
+            // handover, if any)
+            REPLICAOF => ensure_boolean_or_aerr::<P>((2..=4).contains(&iter.len()))?,
+            // a replica, with the login to use (if any)
+            FAILOVER => ensure_boolean_or_aerr::<P>(matches!(iter.len(), 2 | 4))?,
+            _ => ensure_boolean_or_aerr::<P>(iter.len() == 1)?,
+        }
+        match subaction.as_ref() {
             INFO => sys_info(con, &mut iter).await,
             METRIC => sys_metric(con, &mut iter).await,
+            STRICTUTF8 => sys_strictutf8(con, &mut iter).await,
+            MAXQUERYSIZE => sys_querylimit(con, &mut iter, false).await,
+            MAXQUERYARGS => sys_querylimit(con, &mut iter, true).await,
+            RELOAD => sys_reload(con, auth, &mut iter).await,
+            READONLY => sys_readonly(con, auth, &mut iter).await,
+            RELOADCONF => sys_reloadconf(con, auth).await,
+            METRICS => sys_metrics(con).await,
+            LATENCY => sys_latency(con, &mut iter).await,
+            CLIENT => sys_client(con, auth, &mut iter).await,
+            MONITOR => sys_monitor(con, auth, &mut iter).await,
+            REQUESTID => sys_requestid(con, &mut iter).await,
+            HEALTH => sys_health(con).await,
+            SNAPSHOT => sys_snapshot(handle, con, auth, &mut iter).await,
+            COMPACT => sys_compact(con, auth, &mut iter).await,
+            FLUSH => sys_flush(handle, con, auth, &mut iter).await,
+            EXPORT => sys_export(handle, con, auth, &mut iter).await,
+            IMPORT => sys_import(handle, con, auth, &mut iter).await,
+            SYNC => sys_sync(handle, con, auth).await,
+            REPLICAOF => sys_replicaof(handle, con, auth, &mut iter).await,
+            REPLICATION => sys_replication(con, &mut iter).await,
+            FAILOVER => sys_failover(handle, con, auth, &mut iter).await,
+            CLUSTER => sys_cluster(handle, con, auth, &mut iter).await,
+            CDC => sys_cdc(con, auth, &mut iter).await,
+            MEMUSAGE => sys_memusage(con).await,
             _ => util::err(P::RCODE_UNKNOWN_ACTION),
         }
     }
@@ -59,7 +212,7 @@ action! {
             INFO_PROTOCOL => con.write_string(P::PROTOCOL_VERSIONSTRING).await?,
             INFO_PROTOVER => con.write_float(P::PROTOCOL_VERSION).await?,
             INFO_VERSION => con.write_string(VERSION).await?,
-            _ => return util::err(ERR_UNKNOWN_PROPERTY),
+            _ => return util::err(P::RSTRING_UNKNOWN_PROPERTY),
         }
         Ok(())
     }
@@ -77,8 +230,1064 @@ action! {
                     },
                 }
             }
-            _ => return util::err(ERR_UNKNOWN_METRIC),
+            METRIC_CONNECTIONS => {
+                // the counters are written as pairs of names and values
+                let stats = admission::stats();
+                con.write_flat_array_header(8).await?;
+                con.write_string("active").await?;
+                con.write_int64(stats.active).await?;
+                con.write_string("accepted").await?;
+                con.write_int64(stats.accepted).await?;
+                con.write_string("rejected-per-ip").await?;
+                con.write_int64(stats.rejected_per_ip).await?;
+                con.write_string("rejected-rate").await?;
+                con.write_int64(stats.rejected_rate).await?;
+            }
+            METRIC_READONLY => {
+                con.write_string(READONLY_TABLE[registry::is_read_only()]).await?
+            }
+            METRIC_MEMORY => {
+                // the memory is reported in bytes, and the limit is zero if there's none
+                con.write_flat_array_header(6).await?;
+                con.write_string("used").await?;
+                con.write_usize(memory::used()).await?;
+                con.write_string("limit").await?;
+                con.write_usize(memory::limit().unwrap_or(0)).await?;
+                con.write_string("rejected-writes").await?;
+                con.write_int64(memory::rejected()).await?;
+            }
+            METRIC_QUARANTINED => {
+                // the tables that were loaded empty since their files were corrupted
+                let quarantined = quarantine::quarantined();
+                con.write_flat_array_header(quarantined.len()).await?;
+                for table in quarantined.iter() {
+                    con.write_string(table).await?;
+                }
+            }
+            _ => return util::err(P::RSTRING_UNKNOWN_METRIC),
+        }
+        Ok(())
+    }
+    /// Returns all the metrics (see [`metrics`]) in the Prometheus text format (`SYS METRICS`)
+    fn sys_metrics(con: &mut Connection<C, P>) {
+        con.write_string(&metrics::render()).await?;
+        Ok(())
+    }
+    /// Returns the memory used by the data and by the small object arena (see [`arena`]) as a
+    /// flat array of field/value pairs, in bytes:
+    /// - `used`: the keys and values, along with the part of `arena-reserved` that isn't in
+    /// `arena-used` (as in `SYS METRIC MEMORY`; this is what `memory.maxmemory` applies to)
+    /// - `arena-blocks`: the number of small keys and values kept in the arena
+    /// - `arena-used`: the bytes in those blocks
+    /// - `arena-reserved`: the bytes that the arena took from the allocator (the rest of
+    /// which is free for new small keys and values)
+    fn sys_memusage(con: &mut Connection<C, P>) {
+        let stats = arena::stats();
+        con.write_flat_array_header(8).await?;
+        con.write_string("used").await?;
+        con.write_usize(memory::used()).await?;
+        con.write_string("arena-blocks").await?;
+        con.write_usize(stats.blocks).await?;
+        con.write_string("arena-used").await?;
+        con.write_usize(stats.used).await?;
+        con.write_string("arena-reserved").await?;
+        con.write_usize(stats.reserved).await?;
+        Ok(())
+    }
+    /// Returns the readiness checks (see [`health`]) as a flat array of field/value pairs:
+    /// - `ready`: `true` if none of the checks is failing, else `false`
+    /// - `storage`: `okay` or `failing` (the last BGSAVE)
+    /// - `snapshots`: `okay`, `failing` or `disabled` (the last snapshot)
+    /// - `writes`: `accepting`, `read-only` or `stopped` (by a failure)
+    fn sys_health(con: &mut Connection<C, P>) {
+        let report = health::report();
+        con.write_flat_array_header(8).await?;
+        con.write_string("ready").await?;
+        con.write_string(READY_TABLE[report.is_ready()]).await?;
+        con.write_string("storage").await?;
+        con.write_string(report.storage.name()).await?;
+        con.write_string("snapshots").await?;
+        con.write_string(report.snapshots.name()).await?;
+        con.write_string("writes").await?;
+        con.write_string(report.writes.name()).await?;
+        Ok(())
+    }
+    /// Returns the latency percentiles of the most recent queries that ran every action
+    /// (`SYS LATENCY`) or the given action (`SYS LATENCY <action>`; see [`metrics`]). The
+    /// percentiles of an action are written as a flat array (see [`write_latency`]) and the
+    /// ones of every action as an array of those. This returns `nil` if the given action hasn't
+    /// been run yet
+    fn sys_latency(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match iter.next_uppercase() {
+            Some(action) => match metrics::latencies(Some(&*action)).first() {
+                Some((name, latency)) => write_latency(con, name, latency).await?,
+                None => return util::err(P::RCODE_NIL),
+            },
+            None => {
+                let latencies = metrics::latencies(None);
+                con.write_array_header(latencies.len()).await?;
+                for (name, latency) in latencies.iter() {
+                    write_latency(con, name, latency).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Inspect and close client connections (see [`clients`]):
+    /// - `SYS CLIENT ID` returns the ID of this connection (`nil` if it isn't registered, like
+    /// the ones run by the HTTP gateway)
+    /// - `SYS CLIENT LIST` returns an array with the description of every client (see
+    /// [`write_client`])
+    /// - `SYS CLIENT KILL <id>` closes the connection of a client (`nil` if there's no such
+    /// client)
+    ///
+    /// If auth is enabled, only root can list and kill clients
+    fn sys_client(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let subaction = unsafe { iter.next_lowercase_unchecked() };
+        match (subaction.as_ref(), iter.len()) {
+            (CLIENT_ID, 0) => match con.client_id() {
+                Some(id) => con.write_int64(id).await?,
+                None => return util::err(P::RCODE_NIL),
+            },
+            (CLIENT_LIST, 0) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let clients = clients::list();
+                con.write_array_header(clients.len()).await?;
+                for client in clients.iter() {
+                    write_client(con, client).await?;
+                }
+            }
+            (CLIENT_KILL, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let id = unsafe { iter.next_unchecked() };
+                let id = match String::from_utf8_lossy(id).parse::<u64>() {
+                    Ok(id) => id,
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                };
+                if !clients::kill(id) {
+                    return util::err(P::RCODE_NIL);
+                }
+                log::info!("Killed client {id}");
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CLIENT_ID | CLIENT_LIST | CLIENT_KILL, _) => return util::err(P::RCODE_ACTION_ERR),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Manage the snapshots on disk:
+    /// - `SYS SNAPSHOT LIST` returns the snapshots as a flat array of pairs of the name and
+    /// either `local` or `remote` (the local ones come first, oldest first)
+    /// - `SYS SNAPSHOT DELETE <name>` deletes a snapshot
+    /// - `SYS SNAPSHOT RESTORE <name>` replaces the data with the data in a snapshot while the
+    /// server is running, and flushes it to disk. The users (and their permissions) are kept
+    ///
+    /// Deleting and restoring return `nil` if there's no such snapshot. If auth is enabled, only
+    /// root can manage snapshots
+    fn sys_snapshot(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let subaction = unsafe { iter.next_lowercase_unchecked() };
+        let engine = handle.get_engine();
+        let result = match (subaction.as_ref(), iter.next()) {
+            (SNAPSHOT_LIST, None) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let snapshots = match engine.list() {
+                    Some(snapshots) => snapshots,
+                    None => return util::err(P::RSTRING_SNAPSHOT_BUSY),
+                };
+                con.write_flat_array_header((snapshots.local.len() + snapshots.remote.len()) * 2)
+                    .await?;
+                for name in snapshots.local.iter() {
+                    con.write_string(name).await?;
+                    con.write_string(SNAPSHOT_LOCAL).await?;
+                }
+                for name in snapshots.remote.iter() {
+                    con.write_string(name).await?;
+                    con.write_string(SNAPSHOT_REMOTE).await?;
+                }
+                return Ok(());
+            }
+            (SNAPSHOT_DELETE | SNAPSHOT_RESTORE, Some(name)) if iter.is_empty() => {
+                auth.provider().ensure_superuser::<P>()?;
+                if !encoding::is_utf8(name) {
+                    return util::err(P::RCODE_ENCODING_ERROR);
+                }
+                if subaction.as_ref() == SNAPSHOT_DELETE {
+                    engine.delete(name).await
+                } else {
+                    engine.restore(name, handle.clone_store()).await
+                }
+            }
+            (SNAPSHOT_LIST | SNAPSHOT_DELETE | SNAPSHOT_RESTORE, _) => {
+                return util::err(P::RCODE_ACTION_ERR)
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        };
+        match result {
+            SnapshotActionResult::Ok => con._write_raw(P::RCODE_OKAY).await?,
+            SnapshotActionResult::NotFound => return util::err(P::RCODE_NIL),
+            SnapshotActionResult::Busy => return util::err(P::RSTRING_SNAPSHOT_BUSY),
+            SnapshotActionResult::Failure => return util::err(P::RCODE_SERVER_ERR),
+            _ => unsafe { impossible!() },
+        }
+        Ok(())
+    }
+    /// Start compacting the tables in the background (`SYS COMPACT`; see
+    /// [`compaction`]), or report the progress of the running compaction, or of the last one
+    /// if none is running (`SYS COMPACT STATUS`). Only root can compact
+    fn sys_compact(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        auth.provider().ensure_superuser::<P>()?;
+        match iter.next_lowercase().as_deref() {
+            None => {
+                if !compaction::request() {
+                    return util::err(P::RSTRING_COMPACTION_BUSY);
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            Some(COMPACT_STATUS) => {
+                let progress = compaction::progress();
+                con.write_flat_array_header(14).await?;
+                con.write_string("running").await?;
+                con.write_string(READY_TABLE[progress.running]).await?;
+                con.write_string("tables").await?;
+                con.write_usize(progress.tables).await?;
+                con.write_string("total").await?;
+                con.write_usize(progress.total).await?;
+                con.write_string("expired").await?;
+                con.write_usize(progress.expired).await?;
+                con.write_string("freed").await?;
+                con.write_usize(progress.freed).await?;
+                con.write_string("reclaimed").await?;
+                con.write_int64(progress.reclaimed).await?;
+                con.write_string("finished").await?;
+                con.write_int64(progress.finished).await?;
+            }
+            Some(_) => return util::err(P::RCODE_UNKNOWN_ACTION),
         }
         Ok(())
     }
+    /// Flush the keyspaces that changed since they were last flushed right away (`SYS FLUSH`),
+    /// or just the given keyspace, whether or not it changed (`SYS FLUSH <keyspace>`; see
+    /// [`bgsave::flush_keyspace`]). Only root can flush
+    fn sys_flush(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        auth.provider().ensure_superuser::<P>()?;
+        let handle = handle.clone();
+        let okay = match iter.next() {
+            None => tokio::task::spawn_blocking(move || bgsave::flush_now(handle))
+                .await
+                .expect("Something caused the flush to panic"),
+            Some(ksid) => {
+                if ksid.len() > 64 {
+                    return util::err(P::RSTRING_BAD_CONTAINER_NAME);
+                }
+                let ksid = unsafe { ObjectID::from_slice(ksid) };
+                let flushed =
+                    tokio::task::spawn_blocking(move || bgsave::flush_keyspace(&handle, &ksid))
+                        .await
+                        .expect("Something caused the flush to panic");
+                match flushed {
+                    Some(okay) => okay,
+                    None => return util::err(P::RSTRING_CONTAINER_NOT_FOUND),
+                }
+            }
+        };
+        if okay {
+            con._write_raw(P::RCODE_OKAY).await?;
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+    /// Write a table to a portable archive in the export directory (`SYS EXPORT <entity>
+    /// <path>`; see [`export`]). A file that's already there is never overwritten. Only root
+    /// can export (see [`ensure_can_export`])
+    fn sys_export(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        self::ensure_can_export::<P>(auth)?;
+        let (entity, path) = unsafe { (iter.next_unchecked(), iter.next_unchecked()) };
+        let path = self::parse_export_path::<P>(path)?;
+        let entity = blueql::util::from_slice_action_result::<P>(entity)?;
+        let (keyspace, name) = match entity.as_ref() {
+            Entity::Full(ksid, tblid) => unsafe { (ksid.as_slice(), tblid.as_slice()) },
+            Entity::Current(tblid) => match handle.get_ids().0 {
+                Some(ksid) => (ksid.as_slice(), unsafe { tblid.as_slice() }),
+                None => return util::err(P::RSTRING_DEFAULT_UNSET),
+            },
+            // a shard can't be imported on its own
+            Entity::Shard(..) => return util::err(P::RSTRING_WRONG_MODEL),
+        };
+        let keyspace = String::from_utf8_lossy(keyspace).into_owned();
+        let name = String::from_utf8_lossy(name).into_owned();
+        let table = translate_ddl_error::<P, _>(handle.get_table(&entity))?;
+        let exported = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || export::export(&path, &keyspace, &name, &table)
+        })
+        .await
+        .expect("Something caused the export to panic");
+        match exported {
+            Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+            Err(ExportError::Io(e)) if e.kind() == ErrorKind::AlreadyExists => {
+                return util::err(P::RSTRING_ALREADY_EXISTS)
+            }
+            Err(e) => {
+                log::error!("Failed to export to `{}`: {e}", path.display());
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+        }
+        Ok(())
+    }
+    /// Load a table from an archive in the export directory that was written by `SYS EXPORT`
+    /// (`SYS IMPORT <path> [<entity>]`). The table is added under the name that it was exported
+    /// with, unless another one is given, and its keyspace is flushed right away. Only root can
+    /// import (see [`ensure_can_export`])
+    fn sys_import(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        self::ensure_can_export::<P>(auth)?;
+        if let Some(rstring) = queryengine::rejects_writes(con) {
+            return util::err(rstring);
+        }
+        let path = self::parse_export_path::<P>(unsafe { iter.next_unchecked() })?;
+        let imported = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || export::import(&path)
+        })
+        .await
+        .expect("Something caused the import to panic");
+        let (manifest, table) = match imported {
+            Ok(imported) => imported,
+            Err(ExportError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+                return util::err(P::RCODE_NIL)
+            }
+            Err(ExportError::BadArchive(e)) => {
+                log::warn!("Failed to import `{}`: {e}", path.display());
+                return util::err(P::RSTRING_BAD_ARCHIVE);
+            }
+            Err(e) => {
+                log::error!("Failed to import `{}`: {e}", path.display());
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+        };
+        let target = match iter.next() {
+            Some(entity) => entity.to_vec(),
+            None => format!("{}.{}", manifest.keyspace, manifest.table).into_bytes(),
+        };
+        let entity = blueql::util::from_slice_action_result::<P>(&target)?;
+        // the table isn't in the write-ahead log, so the replicas have to sync again to get it
+        let paused = wal::pause().await;
+        let ksid = translate_ddl_error::<P, _>(handle.add_table(&entity, table))?;
+        replication::resync();
+        drop(paused);
+        let handle = handle.clone();
+        let flushed = tokio::task::spawn_blocking(move || bgsave::flush_keyspace(&handle, &ksid))
+            .await
+            .expect("Something caused the flush to panic");
+        if flushed == Some(true) {
+            con._write_raw(P::RCODE_OKAY).await?;
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+    /// Send a full sync, and then feed every write to this connection (`SYS SYNC`; this is
+    /// what a replica runs on its primary, see [`replication`]). Only root can sync
+    fn sys_sync(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle
+    ) {
+        auth.provider().ensure_superuser::<P>()?;
+        replication::primary::sync(handle, con).await
+    }
+    /// Replicate the primary at the given host and port (`SYS REPLICAOF <host> <port> [<user>
+    /// <token>]`), logging in with the given user and token if they're there. The data is
+    /// replaced with the primary's in the background, and replaced again with the data of
+    /// another primary if this is run again. `SYS REPLICAOF NO ONE [<handover>]` stops
+    /// replicating instead, once the handover with the given ID arrives if there's one (see
+    /// [`replication::failover`]). Only root can change the primary
+    fn sys_replicaof(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        auth.provider().ensure_superuser::<P>()?;
+        let host = unsafe { iter.next_unchecked() };
+        let port = unsafe { iter.next_unchecked() };
+        if host.eq_ignore_ascii_case(REPLICAOF_NO) && port.eq_ignore_ascii_case(REPLICAOF_ONE) {
+            let handover = match (iter.next(), iter.next()) {
+                (None, _) => None,
+                (Some(handover), None) => match str::from_utf8(handover).map(str::parse) {
+                    Ok(Ok(handover)) => Some(handover),
+                    _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+                },
+                (Some(_), Some(_)) => return util::err(P::RCODE_ACTION_ERR),
+            };
+            if !replication::failover::promote(handover).await {
+                return util::err(P::RSTRING_FAILOVER_FAILED);
+            }
+        } else {
+            let primary = self::parse_primary::<P>(host, port, iter)?;
+            replication::replicate(handle, primary);
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Hand over to the replica at the given host and port, and then replicate it (`SYS
+    /// FAILOVER <host> <port> [<user> <token>]`, logging in on the replica with the given user
+    /// and token if they're there; see [`replication::failover`]). Only root can fail over
+    fn sys_failover(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        auth.provider().ensure_superuser::<P>()?;
+        let host = unsafe { iter.next_unchecked() };
+        let port = unsafe { iter.next_unchecked() };
+        let replica = self::parse_primary::<P>(host, port, iter)?;
+        let target = replica.to_string();
+        match replication::failover::run(handle, replica).await {
+            Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+            Err(e) => {
+                log::error!("Failed to hand over to the replica at {target}: {e}");
+                return util::err(P::RSTRING_FAILOVER_FAILED);
+            }
+        }
+        Ok(())
+    }
+    /// Returns how replication is doing (`SYS REPLICATION INFO`), as pairs of names and values
+    /// (see [`replication`])
+    fn sys_replication(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            REPLICATION_INFO => {
+                let info = replication::info();
+                let (role, primary, link, lag, last_contact) = match &info.replica {
+                    Some(replica) => (
+                        "replica",
+                        replica.primary.as_str(),
+                        replica.link.name(),
+                        replica.lag,
+                        replica.last_contact,
+                    ),
+                    None => ("primary", "", "none", 0, 0),
+                };
+                con.write_flat_array_header(14).await?;
+                con.write_string("role").await?;
+                con.write_string(role).await?;
+                con.write_string("primary").await?;
+                con.write_string(primary).await?;
+                con.write_string("link").await?;
+                con.write_string(link).await?;
+                con.write_string("lag-ms").await?;
+                con.write_int64(lag).await?;
+                con.write_string("last-contact-ms").await?;
+                con.write_int64(last_contact).await?;
+                con.write_string("read-only").await?;
+                con.write_string(READY_TABLE[info.read_only]).await?;
+                con.write_string("replicas").await?;
+                con.write_usize(info.replicas).await?;
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Manage cluster mode (see [`cluster`]):
+    /// - `SYS CLUSTER INFO` returns how the cluster is doing, as pairs of names and values
+    /// - `SYS CLUSTER SLOTS` returns the owners of the slots, as a flat array of triples of the
+    /// first slot, the last slot and the address of the node that owns them
+    /// - `SYS CLUSTER KEYSLOT <key>` returns the slot of a key
+    /// - `SYS CLUSTER MEET <host> <port> [<user> <token>]` adds a node, and makes the nodes log
+    /// in on each other with the given user and token if they're there
+    /// - `SYS CLUSTER ADDSLOTS <first> [<last>]` makes this node the owner of a range of slots
+    /// (`already-exists` if another node owns any of them)
+    /// - `SYS CLUSTER GOSSIP <message>` merges the gossip of another node and returns ours, as
+    /// a binary string (the nodes run this on each other)
+    /// - `SYS CLUSTER MIGRATE <first> <last> <node>` moves a range of slots that this node owns
+    /// to the node with the given address, along with their keys (see [`cluster::migration`]).
+    /// It returns once the keys have moved (`err-migration-failed` if they couldn't be)
+    /// - `SYS CLUSTER TAKEOVER <first> <last> <epoch>`, `SYS CLUSTER IMPORT <archive>
+    /// <deadlines>` and `SYS CLUSTER APPLY <records>` are what a node that's migrating slots
+    /// runs on the node that it's migrating them to
+    /// - `SYS CLUSTER BACKUP <name>` takes a remote snapshot named `<name>` on every node at the
+    /// same cut, and returns the manifest of the backup (see [`cluster::backup`]), as a TOML
+    /// string (`err-backup-failed` if it couldn't be taken)
+    /// - `SYS CLUSTER FREEZE <id>`, `SYS CLUSTER SNAPSHOT <id> <name>` and `SYS CLUSTER THAW
+    /// <id>` are what the node that takes a backup runs on the other nodes
+    ///
+    /// Everything but `INFO`, `KEYSLOT`, `IMPORT` and `APPLY` fails with `err-cluster-disabled`
+    /// if the server isn't running in cluster mode. If auth is enabled, only root can meet
+    /// nodes, add slots, gossip, migrate slots and take backups
+    fn sys_cluster(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let subaction = unsafe { iter.next_lowercase_unchecked() };
+        match (subaction.as_ref(), iter.len()) {
+            (CLUSTER_INFO, 0) => {
+                let info = cluster::info();
+                let (myself, epoch, nodes, reachable, assigned, owned) = match &info {
+                    Some(info) => (
+                        info.myself.as_str(),
+                        info.epoch,
+                        info.nodes,
+                        info.reachable,
+                        info.assigned,
+                        info.owned,
+                    ),
+                    None => ("", 0, 0, 0, 0, 0),
+                };
+                con.write_flat_array_header(14).await?;
+                con.write_string("enabled").await?;
+                con.write_string(READY_TABLE[info.is_some()]).await?;
+                con.write_string("myself").await?;
+                con.write_string(myself).await?;
+                con.write_string("epoch").await?;
+                con.write_int64(epoch).await?;
+                con.write_string("nodes").await?;
+                con.write_usize(nodes).await?;
+                con.write_string("reachable").await?;
+                con.write_usize(reachable).await?;
+                con.write_string("slots-assigned").await?;
+                con.write_usize(assigned).await?;
+                con.write_string("slots-owned").await?;
+                con.write_usize(owned).await?;
+            }
+            (CLUSTER_SLOTS, 0) => {
+                let slots = match cluster::slots() {
+                    Some(slots) => slots,
+                    None => return util::err(P::RSTRING_CLUSTER_DISABLED),
+                };
+                con.write_flat_array_header(slots.len() * 3).await?;
+                for (range, node) in slots {
+                    con.write_usize(*range.start() as usize).await?;
+                    con.write_usize(*range.end() as usize).await?;
+                    con.write_string(&node).await?;
+                }
+            }
+            (CLUSTER_KEYSLOT, 1) => {
+                let key = unsafe { iter.next_unchecked() };
+                con.write_usize(cluster::slot_of(key) as usize).await?;
+            }
+            (CLUSTER_MEET, 2 | 4) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let host = unsafe { iter.next_unchecked() };
+                let port = unsafe { iter.next_unchecked() };
+                let node = self::parse_primary::<P>(host, port, iter)?;
+                if !cluster::meet(&node.to_string(), node.login) {
+                    return util::err(P::RSTRING_CLUSTER_DISABLED);
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CLUSTER_ADDSLOTS, 1 | 2) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let first = self::parse_slot::<P>(unsafe { iter.next_unchecked() })?;
+                let last = match iter.next() {
+                    Some(last) => self::parse_slot::<P>(last)?,
+                    None => first,
+                };
+                if first > last {
+                    return util::err(P::RCODE_ACTION_ERR);
+                }
+                match cluster::add_slots(first..=last) {
+                    AddSlots::Added => con._write_raw(P::RCODE_OKAY).await?,
+                    AddSlots::Taken => return util::err(P::RSTRING_ALREADY_EXISTS),
+                    AddSlots::Disabled => return util::err(P::RSTRING_CLUSTER_DISABLED),
+                }
+            }
+            (CLUSTER_GOSSIP, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                if !cluster::is_enabled() {
+                    return util::err(P::RSTRING_CLUSTER_DISABLED);
+                }
+                let message = unsafe { iter.next_unchecked() };
+                match cluster::gossip::receive(message) {
+                    Some(reply) => con.write_binary(&reply).await?,
+                    None => return util::err(P::RCODE_ACTION_ERR),
+                }
+            }
+            (CLUSTER_MIGRATE, 3) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let first = self::parse_slot::<P>(unsafe { iter.next_unchecked() })?;
+                let last = self::parse_slot::<P>(unsafe { iter.next_unchecked() })?;
+                let node = match str::from_utf8(unsafe { iter.next_unchecked() }) {
+                    Ok(node) => node.to_owned(),
+                    Err(_) => return util::err(P::RCODE_ENCODING_ERROR),
+                };
+                if first > last {
+                    return util::err(P::RCODE_ACTION_ERR);
+                }
+                // the migration runs to the end even if this connection goes away
+                let db = handle.clone();
+                let migrated = tokio::spawn(async move {
+                    migration::migrate(&db, first..=last, &node).await
+                });
+                match migrated.await.expect("Something caused the migration to panic") {
+                    Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+                    Err(MigrateError::Disabled) => return util::err(P::RSTRING_CLUSTER_DISABLED),
+                    Err(MigrateError::UnknownNode) => return util::err(P::RCODE_NIL),
+                    Err(MigrateError::NotOwned) => return util::err(P::RCODE_ACTION_ERR),
+                    Err(MigrateError::Busy) => return util::err(P::RSTRING_MIGRATION_FAILED),
+                    Err(MigrateError::Failed(e)) => {
+                        log::error!("Failed to migrate the slots {first}-{last}: {e}");
+                        return util::err(P::RSTRING_MIGRATION_FAILED);
+                    }
+                }
+            }
+            (CLUSTER_TAKEOVER, 3) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let first = self::parse_slot::<P>(unsafe { iter.next_unchecked() })?;
+                let last = self::parse_slot::<P>(unsafe { iter.next_unchecked() })?;
+                let epoch = match str::from_utf8(unsafe { iter.next_unchecked() }).map(str::parse) {
+                    Ok(Ok(epoch)) => epoch,
+                    _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+                };
+                if first > last {
+                    return util::err(P::RCODE_ACTION_ERR);
+                }
+                if !migration::take_over(first..=last, epoch) {
+                    return util::err(P::RSTRING_CLUSTER_DISABLED);
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CLUSTER_IMPORT, 2) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let archive = unsafe { iter.next_unchecked() };
+                let deadlines = unsafe { iter.next_unchecked() };
+                migration::import::<P>(handle, archive, deadlines).await?;
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CLUSTER_APPLY, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let records = unsafe { iter.next_unchecked() };
+                if let Err(e) = wal::apply_records(handle, auth, records).await {
+                    log::error!("Failed to apply the writes of a migration: {e}");
+                    return util::err(P::RCODE_SERVER_ERR);
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CLUSTER_BACKUP, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let name = unsafe { iter.next_unchecked() };
+                if !backup::is_valid_name(name) {
+                    return util::err(P::RSTRING_SNAPSHOT_ILLEGAL_NAME);
+                }
+                // (the name is ASCII)
+                let name = String::from_utf8_lossy(name);
+                match backup::backup(handle, &name).await {
+                    Ok(manifest) => con.write_string(&manifest).await?,
+                    Err(BackupError::Disabled) => return util::err(P::RSTRING_CLUSTER_DISABLED),
+                    Err(BackupError::Busy) => return util::err(P::RSTRING_SNAPSHOT_BUSY),
+                    Err(BackupError::Failed(e)) => {
+                        log::error!("Failed to take the backup `{name}` of the cluster: {e}");
+                        return util::err(P::RSTRING_BACKUP_FAILED);
+                    }
+                }
+            }
+            (CLUSTER_FREEZE, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                if !cluster::is_enabled() {
+                    return util::err(P::RSTRING_CLUSTER_DISABLED);
+                }
+                let id = self::parse_u64::<P>(unsafe { iter.next_unchecked() })?;
+                match backup::freeze(id).await {
+                    Some(gossip) => con.write_binary(&gossip).await?,
+                    None => return util::err(P::RSTRING_SNAPSHOT_BUSY),
+                }
+            }
+            (CLUSTER_SNAPSHOT, 2) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let id = self::parse_u64::<P>(unsafe { iter.next_unchecked() })?;
+                let name = unsafe { iter.next_unchecked() };
+                if !backup::is_valid_name(name) {
+                    return util::err(P::RSTRING_SNAPSHOT_ILLEGAL_NAME);
+                }
+                match backup::snapshot(handle, id, &String::from_utf8_lossy(name)).await {
+                    Some(SnapshotActionResult::Ok) => con._write_raw(P::RCODE_OKAY).await?,
+                    Some(SnapshotActionResult::AlreadyExists) => {
+                        return util::err(P::RSTRING_SNAPSHOT_DUPLICATE)
+                    }
+                    Some(SnapshotActionResult::Busy) => return util::err(P::RSTRING_SNAPSHOT_BUSY),
+                    Some(_) => return util::err(P::RCODE_SERVER_ERR),
+                    // we aren't frozen for the backup (anymore)
+                    None => return util::err(P::RSTRING_BACKUP_FAILED),
+                }
+            }
+            (CLUSTER_THAW, 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let id = self::parse_u64::<P>(unsafe { iter.next_unchecked() })?;
+                backup::thaw(id);
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (
+                CLUSTER_INFO | CLUSTER_SLOTS | CLUSTER_KEYSLOT | CLUSTER_MEET | CLUSTER_ADDSLOTS
+                | CLUSTER_GOSSIP | CLUSTER_MIGRATE | CLUSTER_TAKEOVER | CLUSTER_IMPORT
+                | CLUSTER_APPLY | CLUSTER_BACKUP | CLUSTER_FREEZE | CLUSTER_SNAPSHOT
+                | CLUSTER_THAW,
+                _,
+            ) => return util::err(P::RCODE_ACTION_ERR),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Start receiving every query that's run on the server (`SYS MONITOR ON`), or just the
+    /// ones run on an entity (`SYS MONITOR ON <keyspace>[.<table>]`), as push frames (see
+    /// [`dbnet::monitor`]). `SYS MONITOR OFF` stops it. If auth is enabled, only root can
+    /// monitor
+    fn sys_monitor(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let switch = unsafe { iter.next_lowercase_unchecked() };
+        match (switch.as_ref(), iter.next()) {
+            (MONITOR_ON, filter) => {
+                auth.provider().ensure_superuser::<P>()?;
+                con.start_monitor(filter.map(Filter::parse));
+            }
+            (MONITOR_OFF, None) => {
+                con.stop_monitor();
+            }
+            (MONITOR_OFF, Some(_)) => return util::err(P::RCODE_ACTION_ERR),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Stream the committed writes (see [`cdc`]):
+    /// - `SYS CDC STREAM [<offset>]` streams the changes after the given offset (or the ones
+    /// captured from now on) to this connection as push frames, in place of the ones that it
+    /// streams already (if any)
+    /// - `SYS CDC STOP` stops streaming them
+    /// - `SYS CDC INFO` returns how capture is doing, as pairs of names and values:
+    /// `next-offset`, `oldest-offset` (of the changes that are kept), `kept` and `consumers`
+    ///
+    /// Streaming and `INFO` fail with `err-cdc-disabled` if capture isn't enabled. If auth is
+    /// enabled, only root can stream the changes
+    fn sys_cdc(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let subaction = unsafe { iter.next_lowercase_unchecked() };
+        match (subaction.as_ref(), iter.len()) {
+            (CDC_STREAM, 0 | 1) => {
+                auth.provider().ensure_superuser::<P>()?;
+                let after = match iter.next() {
+                    Some(offset) => Some(self::parse_u64::<P>(offset)?),
+                    None => None,
+                };
+                match Consumer::start(after) {
+                    Some(consumer) => con.start_cdc(consumer),
+                    None => return util::err(P::RSTRING_CDC_DISABLED),
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CDC_STOP, 0) => {
+                con.stop_cdc();
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            (CDC_INFO, 0) => {
+                let info = match cdc::info() {
+                    Some(info) => info,
+                    None => return util::err(P::RSTRING_CDC_DISABLED),
+                };
+                con.write_flat_array_header(8).await?;
+                con.write_string("next-offset").await?;
+                con.write_int64(info.next).await?;
+                con.write_string("oldest-offset").await?;
+                con.write_int64(info.oldest).await?;
+                con.write_string("kept").await?;
+                con.write_usize(info.kept).await?;
+                con.write_string("consumers").await?;
+                con.write_usize(info.consumers).await?;
+            }
+            (CDC_STREAM | CDC_STOP | CDC_INFO, _) => return util::err(P::RCODE_ACTION_ERR),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Turn strict UTF-8 mode on or off for this connection (`SYS STRICTUTF8 ON|OFF`)
+    fn sys_strictutf8(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            STRICTUTF8_ON => con.set_strict_utf8(true),
+            STRICTUTF8_OFF => con.set_strict_utf8(false),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Turn request ID frames on or off for this connection (`SYS REQUESTID ON|OFF`). While
+    /// they're on, every response is preceded by a push frame with the ID of its request (see
+    /// [`crate::logging`]), so that clients can find the server's records about a request
+    fn sys_requestid(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            REQUESTID_ON => con.set_request_id_frames(true),
+            REQUESTID_OFF => con.set_request_id_frames(false),
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Lower the maximum size of (`SYS MAXQUERYSIZE <bytes>`) or the maximum number of
+    /// elements in (`SYS MAXQUERYARGS <count>`) the queries on this connection. The limits
+    /// can't be raised above the ones that the server was configured with
+    fn sys_querylimit(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>, args: bool) {
+        let limit = unsafe { iter.next_unchecked() };
+        let limit = match String::from_utf8_lossy(limit).parse::<usize>() {
+            Ok(limit) => limit,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let mut limits = con.query_limits();
+        if args {
+            limits.max_args = limit;
+        } else {
+            limits.max_size = limit;
+        }
+        if !con.set_query_limits(limits) {
+            return util::err(P::RSTRING_OUT_OF_RANGE);
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Reload the TLS certificates and keys of the listeners from disk (`SYS RELOAD TLS`).
+    /// Connections that are already open aren't affected. If auth is enabled, only root can
+    /// do this
+    fn sys_reload(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            RELOAD_TLS => {
+                auth.provider().ensure_superuser::<P>()?;
+                match dbnet::reload_certificates() {
+                    Ok(0) => return util::err(P::RSTRING_TLS_DISABLED),
+                    Ok(count) => {
+                        log::info!("Reloaded the TLS certificates of {count} listener(s)")
+                    }
+                    Err(e) => {
+                        log::error!("Failed to reload the TLS certificates: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                }
+            }
+            _ => return util::err(P::RSTRING_UNKNOWN_PROPERTY),
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Make the server read-only (`SYS READONLY ON`) or writable again (`SYS READONLY OFF`).
+    /// Writes are rejected while the server is read-only, but reads (and snapshots) go on as
+    /// usual. If auth is enabled, only root can do this
+    fn sys_readonly(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>
+    ) {
+        let read_only = match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            READONLY_ON => true,
+            READONLY_OFF => false,
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        };
+        auth.provider().ensure_superuser::<P>()?;
+        if registry::set_read_only(read_only) != read_only {
+            if read_only {
+                log::info!("The server is now read-only");
+            } else {
+                log::info!("The server is now accepting writes");
+            }
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Reload the configuration file (`SYS RELOADCONF`; see [`confreload`]). This returns the
+    /// keys that were changed as pairs of the key and either `applied` or `restart` (if it only
+    /// takes effect after a restart). If auth is enabled, only root can do this
+    fn sys_reloadconf(con: &mut Connection<C, P>, auth: &mut AuthProviderHandle) {
+        auth.provider().ensure_superuser::<P>()?;
+        let report = match confreload::reload() {
+            Ok(report) => report,
+            Err(ReloadError::NoConfigFile) => return util::err(P::RSTRING_NO_CONFIG_FILE),
+            Err(e) => {
+                log::error!("Failed to reload the configuration: {e}");
+                return util::err(P::RSTRING_BAD_CONFIG);
+            }
+        };
+        con.write_flat_array_header((report.applied.len() + report.restart.len()) * 2)
+            .await?;
+        for key in report.applied {
+            con.write_string(key).await?;
+            con.write_string(RELOADCONF_APPLIED).await?;
+        }
+        for key in report.restart {
+            con.write_string(key).await?;
+            con.write_string(RELOADCONF_RESTART).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Ensure that the connection can export and import tables. These read and write files on the
+/// server, so only root can if auth is enabled, and nobody can if it's disabled (unless the
+/// configuration lets anyone; see [`crate::config::ExportConfig`])
+fn ensure_can_export<P: ProtocolSpec>(auth: &AuthProviderHandle) -> ActionResult<()> {
+    let auth = auth.provider();
+    if auth.is_enabled() || export::allows_noauth() {
+        auth.ensure_superuser::<P>()
+    } else {
+        util::err(P::AUTH_CODE_PERMS)
+    }
+}
+
+/// Parse a path in the export directory (see [`export::resolve`])
+fn parse_export_path<P: ProtocolSpec>(path: &[u8]) -> ActionResult<PathBuf> {
+    let path = match str::from_utf8(path) {
+        Ok(path) => path,
+        Err(_) => return util::err(P::RCODE_ENCODING_ERROR),
+    };
+    match export::resolve(path) {
+        Some(path) => Ok(path),
+        None => util::err(P::RSTRING_BAD_EXPORT_PATH),
+    }
+}
+
+/// Returns the server at the given host and port, with the login that follows them (if any)
+fn parse_primary<P: ProtocolSpec>(
+    host: &[u8],
+    port: &[u8],
+    iter: &mut ActionIter<'_>,
+) -> ActionResult<Primary> {
+    let host = match str::from_utf8(host) {
+        Ok(host) => host.to_owned(),
+        Err(_) => return util::err(P::RCODE_ENCODING_ERROR),
+    };
+    let port = match str::from_utf8(port).map(str::parse) {
+        Ok(Ok(port)) => port,
+        _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+    };
+    let login = match (iter.next(), iter.next()) {
+        (Some(user), Some(token)) => Some((user.to_vec(), token.to_vec())),
+        (None, None) => None,
+        // a user without a token
+        _ => return util::err(P::RCODE_ACTION_ERR),
+    };
+    Ok(Primary { host, port, login })
+}
+
+/// Parse a hash slot (see [`cluster::SLOTS`])
+fn parse_slot<P: ProtocolSpec>(slot: &[u8]) -> ActionResult<u16> {
+    match str::from_utf8(slot).map(str::parse::<u16>) {
+        Ok(Ok(slot)) if (slot as usize) < cluster::SLOTS => Ok(slot),
+        Ok(Ok(_)) => util::err(P::RCODE_ACTION_ERR),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Parse an unsigned integer (like the ID of a backup of the cluster or an offset of a change)
+fn parse_u64<P: ProtocolSpec>(id: &[u8]) -> ActionResult<u64> {
+    match str::from_utf8(id).map(str::parse) {
+        Ok(Ok(id)) => Ok(id),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Write the latency percentiles of an action as a flat array of field/value pairs. The fields
+/// are:
+/// - `action`: the name of the action
+/// - `samples`: the number of queries that the percentiles were taken over (int)
+/// - `p50`, `p90` and `p99`: the percentiles in microseconds (int)
+/// - `max`: the highest latency in microseconds (int)
+async fn write_latency<P, C>(
+    con: &mut Connection<C, P>,
+    action: &[u8],
+    latency: &Latency,
+) -> IoResult<()>
+where
+    P: ProtocolSpec,
+    C: BufferedSocketStream,
+{
+    con.write_flat_array_header(12).await?;
+    con.write_string("action").await?;
+    con.write_mono_length_prefixed_with_tsymbol(action, P::TSYMBOL_STRING)
+        .await?;
+    con.write_string("samples").await?;
+    con.write_usize(latency.samples).await?;
+    con.write_string("p50").await?;
+    con.write_int64(latency.p50).await?;
+    con.write_string("p90").await?;
+    con.write_int64(latency.p90).await?;
+    con.write_string("p99").await?;
+    con.write_int64(latency.p99).await?;
+    con.write_string("max").await?;
+    con.write_int64(latency.max).await
+}
+
+/// Write the description of a client as a flat array of field/value pairs. The fields are:
+/// - `id`: the ID of the client (int)
+/// - `addr`: the address of the client (empty if it isn't known)
+/// - `user`: the user that the client is logged in as (empty if it isn't logged in)
+/// - `entity`: the entity that the client is using (`keyspace` or `keyspace.table`)
+/// - `lastaction`: the last action that the client ran (empty if it hasn't run any)
+/// - `connected`: when the client connected, as a UNIX timestamp (int)
+/// - `idle`: the number of seconds since the client last ran a query (int)
+async fn write_client<P, C>(con: &mut Connection<C, P>, client: &ClientInfo) -> IoResult<()>
+where
+    P: ProtocolSpec,
+    C: BufferedSocketStream,
+{
+    con.write_flat_array_header(14).await?;
+    con.write_string("id").await?;
+    con.write_int64(client.id).await?;
+    con.write_string("addr").await?;
+    match client.peer {
+        Some(peer) => con.write_string(&peer.to_string()).await?,
+        None => con.write_string("").await?,
+    }
+    con.write_string("user").await?;
+    con.write_mono_length_prefixed_with_tsymbol(
+        client.user.as_deref().unwrap_or_default(),
+        P::TSYMBOL_STRING,
+    )
+    .await?;
+    con.write_string("entity").await?;
+    con.write_mono_length_prefixed_with_tsymbol(&client.entity, P::TSYMBOL_STRING)
+        .await?;
+    con.write_string("lastaction").await?;
+    con.write_mono_length_prefixed_with_tsymbol(
+        client.last_action.unwrap_or_default(),
+        P::TSYMBOL_STRING,
+    )
+    .await?;
+    con.write_string("connected").await?;
+    con.write_int64(client.connected).await?;
+    con.write_string("idle").await?;
+    con.write_int64(client.idle).await
 }
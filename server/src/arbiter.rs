@@ -27,12 +27,13 @@
 use {
     crate::{
         auth::AuthProvider,
-        config::{ConfigurationSet, SnapshotConfig, SnapshotPref},
+        cdc, cluster,
+        config::{ConfigurationSet, Restore, SnapshotConfig, SnapshotPref},
         corestore::Corestore,
         dbnet,
         diskstore::flock::FileLock,
-        services,
-        storage::v1::sengine::SnapshotEngine,
+        replication, services,
+        storage::v1::{encryption, export, sengine::SnapshotEngine, wal},
         util::{
             error::{Error, SkyResult},
             os::TerminationSignal,
@@ -52,36 +53,104 @@ use {
 const TERMSIG_THRESHOLD: usize = 3;
 
 /// Start the server waiting for incoming connections or a termsig
-pub async fn run(
-    ConfigurationSet {
+pub async fn run(cfg: ConfigurationSet, restore: Option<Restore>) -> SkyResult<Corestore> {
+    // the running configuration is kept around for reloads
+    let (bgsave_cfg, snapshot_cfg) = services::confreload::init(cfg.clone());
+    let ConfigurationSet {
         ports,
-        bgsave,
         snapshot,
+        snapshot_sink,
         maxcon,
+        shards,
         auth,
         protocol,
+        http,
+        limits,
+        ratelimit,
+        audit,
+        wal,
+        encryption,
+        compaction,
+        cluster,
+        cdc,
+        export,
+        qualified_keys,
         ..
-    }: ConfigurationSet,
-    restore_filepath: Option<String>,
-) -> SkyResult<Corestore> {
+    } = cfg;
+    // this has to come before any table is created, since the maps of an engine must all have
+    // the same number of shards
+    crate::corestore::map::set_shard_count(shards);
+    // and this before anything is replayed, since it changes what a key refers to
+    crate::blueql::util::set_qualified_keys(qualified_keys);
     // Intialize the broadcast channel
     let (signal, _) = broadcast::channel(1);
     let engine = match &snapshot {
         SnapshotConfig::Enabled(SnapshotPref { atmost, .. }) => SnapshotEngine::new(*atmost),
         SnapshotConfig::Disabled => SnapshotEngine::new_disabled(),
     };
+    let engine = match snapshot_sink {
+        Some(sink) => {
+            // the configuration was validated already
+            let sink = crate::storage::v1::sink::build(&sink).unwrap();
+            log::info!("Snapshots will be streamed to {}", sink.describe());
+            engine.with_sink(sink)
+        }
+        None => engine,
+    };
     let engine = Arc::new(engine);
+    // start the audit log before anything can be audited
+    crate::audit::init(&audit).map_err(|e| Error::ioerror_extra(e, "opening the audit log"))?;
+    // the key is needed to read (and write) the data
+    encryption::init(&encryption)
+        .map_err(|e| Error::ioerror_extra(e, "loading the encryption key"))?;
+    // tables are only exported to (and imported from) the export directory
+    export::init(&export);
     // restore data
-    services::restore_data(restore_filepath)
+    let (backup, recover_to) = match restore {
+        Some(Restore::Backup(backup)) => (Some(backup), None),
+        Some(Restore::PointInTime(time)) => (None, Some(time)),
+        None => (None, None),
+    };
+    let restored = backup.is_some();
+    services::restore_data(backup)
         .map_err(|e| Error::ioerror_extra(e, "restoring data from backup"))?;
+    // the tables on disk get new value files, so the old ones are no longer needed
+    crate::kvengine::disk::remove_leftovers()
+        .map_err(|e| Error::ioerror_extra(e, "removing the leftover value files"))?;
     // init the store
     let db = Corestore::init_with_snapcfg(engine.clone())?;
     // refresh the snapshotengine state
     engine.parse_dir()?;
+    if let Some(time) = recover_to {
+        wal::recover(&db, &engine, time)
+            .await
+            .map_err(|e| Error::ioerror_extra(e, "recovering the data to a point in time"))?;
+    }
+    // replay the writes made since the last flush (the log doesn't apply to restored data).
+    // The writes are archived for point-in-time recovery if there are snapshots to recover from
+    wal::init(&wal, &db, restored, engine.is_local_enabled())
+        .await
+        .map_err(|e| Error::ioerror_extra(e, "replaying the write-ahead log"))?;
+    // capture the writes committed from here on (the replayed ones were captured already)
+    let cdc_sink =
+        cdc::init(&cdc).map_err(|e| Error::ioerror_extra(e, "starting change data capture"))?;
     let auth_provider = match auth.origin_key {
         Some(key) => {
             let authref = db.get_store().setup_auth();
-            AuthProvider::new(authref, Some(key.into_inner()))
+            let aclref = db.get_store().setup_acl();
+            let rotationref = db.get_store().setup_rotation();
+            // the configuration was validated already
+            let external = auth
+                .external
+                .map(|external| crate::auth::external::build(&external).unwrap());
+            AuthProvider::new(
+                authref,
+                aclref,
+                rotationref,
+                Some(key.into_inner()),
+                ratelimit,
+                external,
+            )
         }
         None => AuthProvider::new_disabled(),
     };
@@ -89,15 +158,53 @@ pub async fn run(
     // initialize the background services
     let bgsave_handle = tokio::spawn(services::bgsave::bgsave_scheduler(
         db.clone(),
-        bgsave,
+        bgsave_cfg,
         signal.subscribe(),
     ));
     let snapshot_handle = tokio::spawn(services::snapshot::snapshot_service(
         engine,
         db.clone(),
-        snapshot,
+        snapshot_cfg,
+        signal.subscribe(),
+    ));
+    let sweeper_handle = tokio::spawn(services::sweeper::expiry_sweeper(
+        db.clone(),
+        signal.subscribe(),
+    ));
+    let memreport_handle = tokio::spawn(services::memreport::memory_reporter(
+        db.clone(),
         signal.subscribe(),
     ));
+    let compaction_handle = tokio::spawn(services::compaction::compaction_service(
+        db.clone(),
+        compaction,
+        signal.subscribe(),
+    ));
+    // the records that the writes queue are appended to the write-ahead log (and synced) in
+    // the background. The writer is stopped after the connections, since they may still be
+    // waiting for their writes to be synced
+    let (walstop, _) = broadcast::channel(1);
+    let walwriter_handle = wal::writer()
+        .map(|writer| tokio::spawn(services::walsync::wal_writer(writer, walstop.subscribe())));
+    // the captured changes are appended to the CDC file in the background
+    let cdc_handle = cdc_sink.map(|sink| tokio::spawn(cdc::sink::appender(sink)));
+    // in cluster mode, the nodes gossip with each other in the background
+    cluster::init(&cluster);
+    let gossip_handle =
+        cluster::is_enabled().then(|| tokio::spawn(cluster::gossip::gossiper(signal.subscribe())));
+    // SIGHUP reloads the configuration file
+    #[cfg(unix)]
+    let confreload_handle =
+        tokio::spawn(services::confreload::reload_on_hangup(signal.subscribe()));
+    // SIGHUP also reloads the TLS certificates (we leave it alone if we don't have any)
+    #[cfg(unix)]
+    let tlsreload_handle = if ports.insecure_only() {
+        None
+    } else {
+        Some(tokio::spawn(services::tlsreload::reload_on_hangup(
+            signal.subscribe(),
+        )))
+    };
 
     // bind to signals
     let termsig =
@@ -106,7 +213,9 @@ pub async fn run(
     let mut server = dbnet::connect(
         ports,
         protocol,
+        http,
         maxcon,
+        limits,
         db.clone(),
         auth_provider,
         signal.clone(),
@@ -122,10 +231,34 @@ pub async fn run(
     // drop the signal and let others exit
     drop(signal);
     server.finish_with_termsig().await;
+    // stop replicating the primary (if we are), so that it's not writing while we shut down
+    replication::stop().await;
+    // no more writes are committed, so the consumers can have the last of the changes
+    cdc::stop();
+    // and nothing else is queued for the write-ahead log
+    drop(walstop);
 
     // wait for the background services to terminate
     let _ = snapshot_handle.await;
     let _ = bgsave_handle.await;
+    let _ = sweeper_handle.await;
+    let _ = memreport_handle.await;
+    let _ = compaction_handle.await;
+    if let Some(walwriter_handle) = walwriter_handle {
+        let _ = walwriter_handle.await;
+    }
+    if let Some(gossip_handle) = gossip_handle {
+        let _ = gossip_handle.await;
+    }
+    if let Some(cdc_handle) = cdc_handle {
+        let _ = cdc_handle.await;
+    }
+    #[cfg(unix)]
+    let _ = confreload_handle.await;
+    #[cfg(unix)]
+    if let Some(tlsreload_handle) = tlsreload_handle {
+        let _ = tlsreload_handle.await;
+    }
     Ok(db)
 }
 
@@ -0,0 +1,610 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Audit log
+//!
+//! The audit log is an append-only record of security-relevant events: logins (and failed
+//! attempts), changes to users and their permissions, DDL statements and administrative
+//! actions (`SYS` and `MKSNAP`). Any query that is turned away for bad credentials or missing
+//! permissions is recorded as an auth failure, whatever the action.
+//!
+//! Every record is a single line of `key=value` pairs:
+//! ```text
+//! 2026-10-15T09:41:07.112Z event=admin outcome=ok addr=127.0.0.1:50432 user=root action="AUTH ADDUSER alice"
+//! ```
+//! The `user` is the user that the connection was logged in as when the action was run (so
+//! logins name the account that they log into in the `action`). Secrets (tokens and the
+//! origin key) are never recorded, and neither are the arguments of actions that aren't
+//! audited. Since clients of the HTTP gateway log in with every request, only their failed
+//! logins are recorded.
+//!
+//! Records are appended to a file (which is rotated once it grows past `maxsize`) or sent to
+//! the local syslog daemon (with the `authpriv` facility).
+
+use {
+    crate::{
+        actions::{ActionError, ActionResult},
+        config::{AuditConfig, AuditLog},
+        protocol::interface::ProtocolSpec,
+        IoResult,
+    },
+    chrono::Utc,
+    core::sync::atomic::{AtomicBool, Ordering},
+    parking_lot::Mutex,
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{ErrorKind, Write},
+        net::SocketAddr,
+    },
+};
+
+/// Set once a sink has been installed, so that queries can skip auditing cheaply
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static AUDITOR: Mutex<Option<Auditor>> = parking_lot::const_mutex(None);
+
+/// The syslog facility (`authpriv`), already shifted into place
+#[cfg(unix)]
+const SYSLOG_AUTHPRIV: u8 = 10 << 3;
+#[cfg(unix)]
+const SYSLOG_NOTICE: u8 = 5;
+#[cfg(unix)]
+const SYSLOG_INFO: u8 = 6;
+/// The sockets that syslog daemons commonly listen on (Linux, macOS and the BSDs)
+#[cfg(unix)]
+const SYSLOG_SOCKETS: [&str; 3] = ["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// An audited event
+pub enum Event {
+    /// A user logged in (or claimed root)
+    Login,
+    /// A user logged out
+    Logout,
+    /// A query was turned away for bad credentials or missing permissions
+    AuthFailure,
+    /// A user (or their permissions) was changed
+    Admin,
+    /// A DDL statement
+    Ddl,
+    /// An administrative action
+    Sys,
+}
+
+impl Event {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::Logout => "logout",
+            Self::AuthFailure => "auth-failure",
+            Self::Admin => "admin",
+            Self::Ddl => "ddl",
+            Self::Sys => "sys",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The outcome of an audited event
+pub enum Outcome {
+    Okay,
+    /// Bad credentials or missing permissions
+    Denied,
+    /// Anything else that went wrong (like a bad argument)
+    Error,
+}
+
+impl Outcome {
+    /// Returns the outcome of an action
+    pub fn of<P: ProtocolSpec>(ret: &ActionResult<()>) -> Self {
+        match ret {
+            Ok(()) => Self::Okay,
+            Err(ActionError::ActionError(e))
+                if *e == P::AUTH_CODE_BAD_CREDENTIALS || *e == P::AUTH_CODE_PERMS =>
+            {
+                Self::Denied
+            }
+            Err(_) => Self::Error,
+        }
+    }
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Okay => "ok",
+            Self::Denied => "denied",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Start auditing with the given configuration (this does nothing if auditing is disabled)
+pub fn init(cfg: &AuditConfig) -> IoResult<()> {
+    let sink = match &cfg.log {
+        None => return Ok(()),
+        Some(AuditLog::File(path)) => Sink::File(FileSink::open(path, cfg.maxsize, cfg.keep)?),
+        #[cfg(unix)]
+        Some(AuditLog::Syslog) => Sink::Syslog(SyslogSink::connect()?),
+        #[cfg(not(unix))]
+        Some(AuditLog::Syslog) => {
+            return Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "syslog isn't supported on this platform",
+            ))
+        }
+    };
+    *AUDITOR.lock() = Some(Auditor {
+        sink,
+        failing: false,
+    });
+    ENABLED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Returns true if events are being audited
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Record a query (with the given result) that was run by `user` from `peer`. Queries that
+/// aren't audited are only recorded if they were denied
+pub fn record_query<P: ProtocolSpec>(
+    args: &[&[u8]],
+    ret: &ActionResult<()>,
+    peer: Option<SocketAddr>,
+    user: Option<&[u8]>,
+) {
+    let (event, action) = self::classify(args);
+    let outcome = Outcome::of::<P>(ret);
+    let event = match (event, outcome) {
+        (_, Outcome::Denied) => Event::AuthFailure,
+        (Some(event), _) => event,
+        (None, _) => return,
+    };
+    self::record(event, outcome, peer, user, &action)
+}
+
+/// Record an event. Denied events are always recorded as auth failures
+pub fn record(
+    event: Event,
+    outcome: Outcome,
+    peer: Option<SocketAddr>,
+    user: Option<&[u8]>,
+    action: &[&[u8]],
+) {
+    let event = match outcome {
+        Outcome::Denied => Event::AuthFailure,
+        _ => event,
+    };
+    let record = self::format_record(event, outcome, peer, user, action);
+    if let Some(auditor) = AUDITOR.lock().as_mut() {
+        auditor.write(outcome, &record);
+    }
+}
+
+/// Work out how a query is audited. Returns the event (or `None` if the query is only recorded
+/// when it's denied) and the arguments that can be recorded
+fn classify<'a>(args: &[&'a [u8]]) -> (Option<Event>, Vec<&'a [u8]>) {
+    // an `@<entity>` prefix is recorded, but has nothing to do with the action
+    let (prefix, rest) = match args.split_first() {
+        Some((first, rest)) if first.starts_with(b"@") => (Some(*first), rest),
+        _ => (None, args),
+    };
+    let mut recorded: Vec<&[u8]> = prefix.into_iter().collect();
+    let action = match rest.first() {
+        Some(action) => *action,
+        None => return (None, recorded),
+    };
+    let is = |name: &[u8]| action.eq_ignore_ascii_case(name);
+    let event = if is(b"AUTH") {
+        let subaction = rest.get(1).copied().unwrap_or_default();
+        let is_sub = |name: &[u8]| subaction.eq_ignore_ascii_case(name);
+        if is_sub(b"LOGIN") {
+            // just the username (never the token)
+            recorded.extend(rest.iter().take(3));
+            Some(Event::Login)
+        } else if is_sub(b"CLAIM") {
+            // the only argument is the origin key
+            recorded.extend(rest.iter().take(2));
+            Some(Event::Login)
+        } else if is_sub(b"RESTORE") {
+            // just the username (the origin key may come before it)
+            recorded.extend(rest.iter().take(2));
+            recorded.extend(rest.iter().skip(2).last());
+            Some(Event::Admin)
+        } else if is_sub(b"LOGOUT") {
+            recorded.extend(rest);
+            Some(Event::Logout)
+        } else if [
+            &b"ADDUSER"[..],
+            b"DELUSER",
+            b"GRANT",
+            b"REVOKE",
+            b"UNRESTRICT",
+            b"ROTATE",
+            b"RENAMEUSER",
+        ]
+        .iter()
+        .any(|name| is_sub(name))
+        {
+            recorded.extend(rest);
+            Some(Event::Admin)
+        } else {
+            // everything else just reads
+            recorded.extend(rest.iter().take(2));
+            None
+        }
+    } else if is(b"SYS") || is(b"MKSNAP") {
+        recorded.extend(rest);
+        Some(Event::Sys)
+    } else if rest.len() == 1 && self::is_ddl_statement(action) {
+        recorded.extend(rest);
+        Some(Event::Ddl)
+    } else {
+        // the arguments could be anything, so we only record the action
+        recorded.push(action);
+        None
+    };
+    (event, recorded)
+}
+
+/// Returns true if a BlueQL statement changes the schema
+pub(crate) fn is_ddl_statement(statement: &[u8]) -> bool {
+    let mut words = statement
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty());
+    match (words.next(), words.next()) {
+        (Some(keyword), Some(_)) => [&b"CREATE"[..], b"DROP", b"ALTER", b"RENAME"]
+            .iter()
+            .any(|ddl| keyword.eq_ignore_ascii_case(ddl)),
+        _ => false,
+    }
+}
+
+/// Format a record (without the timestamp)
+fn format_record(
+    event: Event,
+    outcome: Outcome,
+    peer: Option<SocketAddr>,
+    user: Option<&[u8]>,
+    action: &[&[u8]],
+) -> Vec<u8> {
+    let mut record =
+        format!("event={} outcome={} addr=", event.name(), outcome.name()).into_bytes();
+    match peer {
+        Some(peer) => record.extend(peer.to_string().as_bytes()),
+        None => record.push(b'-'),
+    }
+    record.extend(b" user=");
+    match user {
+        Some(user) => self::write_value(&mut record, user),
+        None => record.push(b'-'),
+    }
+    record.extend(b" action=");
+    self::write_value(&mut record, &action.join(&b' '));
+    record
+}
+
+/// Write a value, quoting (and escaping) it if it has anything but printable characters
+fn write_value(buf: &mut Vec<u8>, value: &[u8]) {
+    let is_plain = !value.is_empty()
+        && value
+            .iter()
+            .all(|b| b.is_ascii_graphic() && !matches!(b, b'"' | b'\\' | b'='));
+    if is_plain {
+        buf.extend(value);
+        return;
+    }
+    buf.push(b'"');
+    for &byte in value {
+        match byte {
+            b'"' | b'\\' => buf.extend([b'\\', byte]),
+            b' '..=b'~' => buf.push(byte),
+            _ => buf.extend(format!("\\x{byte:02x}").as_bytes()),
+        }
+    }
+    buf.push(b'"');
+}
+
+/// Writes records to a sink, logging (once) if it stops working
+struct Auditor {
+    sink: Sink,
+    /// the last write failed
+    failing: bool,
+}
+
+impl Auditor {
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn write(&mut self, outcome: Outcome, record: &[u8]) {
+        let ret = match &mut self.sink {
+            Sink::File(file) => file.append(record),
+            #[cfg(unix)]
+            Sink::Syslog(syslog) => syslog.send(outcome, record),
+        };
+        match ret {
+            Ok(()) => self.failing = false,
+            Err(e) if !self.failing => {
+                self.failing = true;
+                log::error!("Failed to write to the audit log: {e}");
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+enum Sink {
+    File(FileSink),
+    #[cfg(unix)]
+    Syslog(SyslogSink),
+}
+
+/// An audit log file. Once it grows past `maxsize`, it is renamed to `<path>.1` (after the
+/// older ones are shifted to `<path>.2`, `<path>.3` and so on, dropping the oldest one) and a
+/// new file is started
+struct FileSink {
+    path: String,
+    file: File,
+    /// the size of the current file
+    size: u64,
+    maxsize: Option<u64>,
+    keep: usize,
+}
+
+impl FileSink {
+    fn open(path: &str, maxsize: Option<u64>, keep: usize) -> IoResult<Self> {
+        let file = Self::open_file(path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            size: file.metadata()?.len(),
+            file,
+            maxsize,
+            keep,
+        })
+    }
+    fn open_file(path: &str) -> IoResult<File> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            // the log names users and where they connect from
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)
+    }
+    fn rotated(&self, generation: usize) -> String {
+        format!("{}.{generation}", self.path)
+    }
+    fn append(&mut self, record: &[u8]) -> IoResult<()> {
+        let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        let mut line = Vec::with_capacity(timestamp.len() + record.len() + 2);
+        line.extend(timestamp.as_bytes());
+        line.push(b' ');
+        line.extend(record);
+        line.push(b'\n');
+        if let Some(maxsize) = self.maxsize {
+            if self.size != 0 && self.size + line.len() as u64 > maxsize {
+                self.rotate()?;
+            }
+        }
+        // a record is written in one go, so that it's never interleaved with another one
+        self.file.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+    fn rotate(&mut self) -> IoResult<()> {
+        match fs::remove_file(self.rotated(self.keep)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        for generation in (1..self.keep).rev() {
+            match fs::rename(self.rotated(generation), self.rotated(generation + 1)) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))?;
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+/// The local syslog daemon
+struct SyslogSink {
+    socket: std::os::unix::net::UnixDatagram,
+}
+
+#[cfg(unix)]
+impl SyslogSink {
+    fn connect() -> IoResult<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        let mut error = None;
+        for path in SYSLOG_SOCKETS {
+            match socket.connect(path) {
+                Ok(()) => return Ok(Self { socket }),
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap())
+    }
+    fn send(&mut self, outcome: Outcome, record: &[u8]) -> IoResult<()> {
+        let severity = match outcome {
+            Outcome::Denied => SYSLOG_NOTICE,
+            _ => SYSLOG_INFO,
+        };
+        // the daemon adds the timestamp and the hostname
+        let mut message = format!(
+            "<{}>skyd[{}]: ",
+            SYSLOG_AUTHPRIV | severity,
+            std::process::id()
+        )
+        .into_bytes();
+        message.extend(record);
+        if self.socket.send(&message).is_err() {
+            // the daemon may have been restarted, so we'll reconnect and try again
+            *self = Self::connect()?;
+            self.socket.send(&message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER: &str = "127.0.0.1:2048";
+
+    fn classify_str(query: &str) -> (Option<Event>, String) {
+        let args: Vec<&[u8]> = query.split(' ').map(str::as_bytes).collect();
+        let (event, recorded) = classify(&args);
+        (event, String::from_utf8(recorded.join(&b' ')).unwrap())
+    }
+
+    #[test]
+    fn classify_auth() {
+        assert_eq!(
+            classify_str("AUTH LOGIN alice s3cret"),
+            (Some(Event::Login), "AUTH LOGIN alice".to_owned())
+        );
+        assert_eq!(
+            classify_str("auth claim originkey"),
+            (Some(Event::Login), "auth claim".to_owned())
+        );
+        assert_eq!(
+            classify_str("AUTH RESTORE originkey alice"),
+            (Some(Event::Admin), "AUTH RESTORE alice".to_owned())
+        );
+        assert_eq!(
+            classify_str("AUTH RESTORE alice"),
+            (Some(Event::Admin), "AUTH RESTORE alice".to_owned())
+        );
+        assert_eq!(
+            classify_str("AUTH GRANT alice ks.tbl read"),
+            (
+                Some(Event::Admin),
+                "AUTH GRANT alice ks.tbl read".to_owned()
+            )
+        );
+        assert_eq!(
+            classify_str("AUTH LOGOUT"),
+            (Some(Event::Logout), "AUTH LOGOUT".to_owned())
+        );
+        assert_eq!(
+            classify_str("AUTH WHOAMI"),
+            (None, "AUTH WHOAMI".to_owned())
+        );
+    }
+
+    #[test]
+    fn classify_sys_and_ddl() {
+        assert_eq!(
+            classify_str("SYS RELOAD TLS"),
+            (Some(Event::Sys), "SYS RELOAD TLS".to_owned())
+        );
+        assert_eq!(
+            classify_str("@ks:tbl MKSNAP"),
+            (Some(Event::Sys), "@ks:tbl MKSNAP".to_owned())
+        );
+        let args: [&[u8]; 1] = [b"drop model  ks.tbl force"];
+        assert_eq!(classify(&args), (Some(Event::Ddl), args.to_vec()));
+        let args: [&[u8]; 1] = [b"inspect keyspaces"];
+        assert_eq!(classify(&args), (None, args.to_vec()));
+    }
+
+    #[test]
+    fn classify_other_actions() {
+        // the key and the value are never recorded
+        assert_eq!(classify_str("SET secret value"), (None, "SET".to_owned()));
+        assert_eq!(
+            classify_str("@ks:tbl RENAME old new"),
+            (None, "@ks:tbl RENAME".to_owned())
+        );
+        assert_eq!(classify(&[]), (None, vec![]));
+    }
+
+    #[test]
+    fn format_records() {
+        assert_eq!(
+            format_record(
+                Event::Admin,
+                Outcome::Okay,
+                Some(PEER.parse().unwrap()),
+                Some(&b"root"[..]),
+                &[&b"AUTH"[..], b"ADDUSER", b"alice"],
+            ),
+            b"event=admin outcome=ok addr=127.0.0.1:2048 user=root action=\"AUTH ADDUSER alice\""
+        );
+        assert_eq!(
+            format_record(
+                Event::AuthFailure,
+                Outcome::Denied,
+                None,
+                None,
+                &[&b"AUTH"[..], b"LOGIN", b"\"bob\"\n\xff"],
+            ),
+            &b"event=auth-failure outcome=denied addr=- user=- action=\"AUTH LOGIN \\\"bob\\\"\\x0a\\xff\""[..]
+        );
+    }
+
+    #[test]
+    fn file_rotation() {
+        const DIR: &str = "audit-rotation-test";
+        let path = format!("{DIR}/audit.log");
+        fs::create_dir_all(DIR).unwrap();
+        let record = format_record(
+            Event::Sys,
+            Outcome::Okay,
+            None,
+            None,
+            &[&b"SYS"[..], b"INFO"],
+        );
+        // every line is a little longer than the record
+        let mut sink = FileSink::open(&path, Some(record.len() as u64 * 2), 2).unwrap();
+        for _ in 0..8 {
+            sink.append(&record).unwrap();
+        }
+        let lines = |path: &str| fs::read_to_string(path).unwrap().lines().count();
+        assert_eq!(lines(&path), 1);
+        assert_eq!(lines(&format!("{path}.1")), 1);
+        assert_eq!(lines(&format!("{path}.2")), 1);
+        assert!(!std::path::Path::new(&format!("{path}.3")).exists());
+        fs::remove_dir_all(DIR).unwrap();
+    }
+
+    #[test]
+    fn outcome_of_result() {
+        use crate::protocol::Skyhash2;
+        assert_eq!(Outcome::of::<Skyhash2>(&Ok(())), Outcome::Okay);
+        assert_eq!(
+            Outcome::of::<Skyhash2>(&Err(ActionError::ActionError(Skyhash2::AUTH_CODE_PERMS))),
+            Outcome::Denied
+        );
+        assert_eq!(
+            Outcome::of::<Skyhash2>(&Err(ActionError::ActionError(Skyhash2::RCODE_ACTION_ERR))),
+            Outcome::Error
+        );
+    }
+}
@@ -0,0 +1,529 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Access control lists
+//!
+//! Root can restrict a standard user to a set of keyspaces and tables by granting it
+//! permissions on them. There are three permissions:
+//! - `read`: run actions that only read data
+//! - `write`: run actions that modify data (and anything else that isn't a read)
+//! - `ddl`: create, alter, rename and drop the keyspace or its tables
+//!
+//! A grant on a keyspace covers every table in it, while a grant on `<keyspace>.<table>` only
+//! covers that table (and its shards). Users without an ACL (and root) aren't restricted at
+//! all, so that existing deployments keep working; a user is restricted as soon as anything is
+//! granted to it.
+//!
+//! The notification channel of a table (see [`crate::kvengine::notify`]) is covered by the
+//! grants on the table: subscribing to it needs `read` and publishing to it needs `write`.
+//!
+//! The grants of a user are stored as a sequence of `[PERMS: 1B][SCOPE LEN: 1B][SCOPE]`
+//! records, which is how they're persisted in the system keyspace too
+
+use {
+    crate::{
+        actions::ActionResult,
+        blueql::Entity,
+        corestore::{htable::Coremap, memstore::SHARD_SEPARATOR, Corestore, SharedSlice},
+        kvengine::notify,
+        protocol::interface::ProtocolSpec,
+        util::err,
+    },
+    std::sync::Arc,
+};
+
+/// Permission to read data
+pub const PERM_READ: u8 = 1;
+/// Permission to write data
+pub const PERM_WRITE: u8 = 2;
+/// Permission to run DDL queries
+pub const PERM_DDL: u8 = 4;
+/// All the permissions
+pub const PERM_ALL: u8 = PERM_READ | PERM_WRITE | PERM_DDL;
+
+/// A map of users to their (encoded) grants
+pub type Aclmap = Arc<Coremap<SharedSlice, SharedSlice>>;
+
+/// Actions that don't touch any table
+const FREE_ACTIONS: [&[u8]; 11] = [
+    b"HEYA",
+    b"AUTH",
+    b"SYS",
+    b"WHEREAMI",
+    b"MULTI",
+    b"DISCARD",
+    b"SCRIPT",
+    b"SUBSCRIBE",
+    b"UNSUBSCRIBE",
+    b"PUBLISH",
+    b"MKSNAP",
+];
+
+/// Actions that only read data
+const READ_ACTIONS: [&[u8]; 36] = [
+    b"GET",
+    b"GETRANGE",
+    b"JGET",
+    b"GETBIT",
+    b"BITCOUNT",
+    b"EXISTS",
+    b"MGET",
+    b"GETMANY",
+    b"DBSIZE",
+    b"MEMUSAGE",
+    b"KEYLEN",
+    b"STRLEN",
+    b"TYPE",
+    b"LSKEYS",
+    b"SCAN",
+    b"KEYS",
+    b"RANDOMKEY",
+    b"SAMPLE",
+    b"LGET",
+    b"LRANGE",
+    b"SMEMBERS",
+    b"SUNION",
+    b"SINTER",
+    b"SDIFF",
+    b"ZRANGEBYSCORE",
+    b"ZRANK",
+    b"HGET",
+    b"HGETALL",
+    b"BFEXISTS",
+    b"PFCOUNT",
+    b"GEOSEARCH",
+    b"TS.RANGE",
+    b"TS.LAST",
+    b"TTL",
+    b"WATCH",
+    b"SNAPSHOT",
+];
+
+/// Returns the permission named `name` (`read`, `write`, `ddl` or `all`)
+pub fn parse_permission(name: &[u8]) -> Option<u8> {
+    let perm = match name.to_ascii_lowercase().as_slice() {
+        b"read" => PERM_READ,
+        b"write" => PERM_WRITE,
+        b"ddl" => PERM_DDL,
+        b"all" => PERM_ALL,
+        _ => return None,
+    };
+    Some(perm)
+}
+
+/// Returns the scope of a grant on the given entity: `<keyspace>` or `<keyspace>.<table>`.
+/// Grants can't be made on shards
+pub fn scope_of(entity: &Entity) -> Option<Vec<u8>> {
+    match entity {
+        Entity::Current(ks) => Some(unsafe { ks.as_slice() }.to_owned()),
+        Entity::Full(ks, tbl) => {
+            let (ks, tbl) = unsafe { (ks.as_slice(), tbl.as_slice()) };
+            let mut scope = Vec::with_capacity(ks.len() + 1 + tbl.len());
+            scope.extend_from_slice(ks);
+            scope.push(b'.');
+            scope.extend_from_slice(tbl);
+            Some(scope)
+        }
+        Entity::Shard(..) => None,
+    }
+}
+
+/// An iterator over the `(permissions, scope)` records in some encoded grants
+struct Records<'a> {
+    grants: &'a [u8],
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = (u8, &'a [u8]);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.grants {
+            [perms, len, rest @ ..] if rest.len() >= *len as usize => {
+                let (scope, rest) = rest.split_at(*len as usize);
+                self.grants = rest;
+                Some((*perms, scope))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn records(grants: &[u8]) -> Records<'_> {
+    Records { grants }
+}
+
+fn push_record(grants: &mut Vec<u8>, perms: u8, scope: &[u8]) {
+    grants.push(perms);
+    grants.push(scope.len() as u8);
+    grants.extend_from_slice(scope);
+}
+
+/// Returns the grants with `perms` added to `scope` (or removed from it, if `grant` is false).
+/// A scope that is left without any permissions is dropped
+pub fn update(grants: &[u8], scope: &[u8], perms: u8, grant: bool) -> Vec<u8> {
+    let mut updated = Vec::with_capacity(grants.len() + 2 + scope.len());
+    let mut found = false;
+    for (have, this_scope) in records(grants) {
+        let have = if this_scope == scope {
+            found = true;
+            if grant {
+                have | perms
+            } else {
+                have & !perms
+            }
+        } else {
+            have
+        };
+        if have != 0 {
+            self::push_record(&mut updated, have, this_scope);
+        }
+    }
+    if grant && !found && perms != 0 {
+        self::push_record(&mut updated, perms, scope);
+    }
+    updated
+}
+
+fn is_table_scope(scope: &[u8], ks: &[u8], tbl: &[u8]) -> bool {
+    scope.len() == ks.len() + 1 + tbl.len()
+        && scope.starts_with(ks)
+        && scope[ks.len()] == b'.'
+        && scope.ends_with(tbl)
+}
+
+/// Returns true if the grants allow all of `perms` on the given table of the keyspace (or on
+/// the keyspace itself, if `tbl` is `None`). The shard of a table is covered by the grants on
+/// the table
+pub fn allows(grants: &[u8], ks: &[u8], tbl: Option<&[u8]>, perms: u8) -> bool {
+    let tbl = tbl.map(|tbl| {
+        tbl.split(|b| *b == SHARD_SEPARATOR)
+            .next()
+            .unwrap_or_default()
+    });
+    let have = records(grants)
+        .filter(|(_, scope)| {
+            *scope == ks || tbl.map_or(false, |tbl| self::is_table_scope(scope, ks, tbl))
+        })
+        .fold(0, |have, (perms, _)| have | perms);
+    have & perms == perms
+}
+
+/// Returns true if the grants allow anything at all on the given table of the keyspace (or
+/// anywhere in the keyspace, if `tbl` is `None`)
+pub fn allows_any(grants: &[u8], ks: &[u8], tbl: Option<&[u8]>) -> bool {
+    records(grants).any(|(_, scope)| match tbl {
+        Some(tbl) => scope == ks || self::is_table_scope(scope, ks, tbl),
+        None => scope == ks || matches!(scope.strip_prefix(ks), Some([b'.', ..])),
+    })
+}
+
+/// Resolve an entity to the keyspace and the table that it names. A lone name is a table in
+/// the current keyspace (if there is one)
+///
+/// ## Safety
+/// The entity must still be valid, that is, the query that it was parsed from must be alive
+pub unsafe fn resolve<'a>(db: &'a Corestore, entity: &'a Entity) -> Option<(&'a [u8], &'a [u8])> {
+    match entity {
+        Entity::Current(tbl) => {
+            let ks = db.get_ids().0?;
+            Some((&ks[..], tbl.as_slice()))
+        }
+        Entity::Full(ks, tbl) | Entity::Shard(ks, tbl, _) => Some((ks.as_slice(), tbl.as_slice())),
+    }
+}
+
+/// Describe the grants: one `<scope> <permission>[,<permission>...]` string per scope
+pub fn describe(grants: &[u8]) -> Vec<String> {
+    records(grants)
+        .map(|(perms, scope)| {
+            let names: Vec<&str> = [
+                (PERM_READ, "read"),
+                (PERM_WRITE, "write"),
+                (PERM_DDL, "ddl"),
+            ]
+            .into_iter()
+            .filter(|(perm, _)| perms & perm != 0)
+            .map(|(_, name)| name)
+            .collect();
+            format!("{} {}", String::from_utf8_lossy(scope), names.join(","))
+        })
+        .collect()
+}
+
+/// Returns the permissions that the (uppercased) action needs on the tables that it touches
+fn required_by(action: &[u8]) -> u8 {
+    if FREE_ACTIONS.contains(&action) {
+        0
+    } else if READ_ACTIONS.contains(&action) {
+        PERM_READ
+    } else {
+        PERM_WRITE
+    }
+}
+
+/// Returns true if the (uppercased) action writes. These are the actions that are rejected
+/// while the server is read-only
+pub fn writes(action: &[u8]) -> bool {
+    self::required_by(action) == PERM_WRITE
+}
+
+/// Returns true if the (uppercased) action can write to tables other than the current one:
+/// the one named by its argument, or by its last argument for `MOVE`, and the tables of any
+/// qualified keys
+pub fn writes_elsewhere<'a>(action: &[u8], mut args: impl Iterator<Item = &'a [u8]>) -> bool {
+    match action {
+        b"MOVE" => true,
+        b"FLUSHDB" | b"FLUSHTABLE" => args.next().is_some(),
+        _ => args.any(|arg| crate::blueql::util::split_qualified_key(arg).is_some()),
+    }
+}
+
+/// Returns the index of the argument that names the table that the action is run on instead
+/// of the current table, if any
+fn entity_argument(action: &[u8], args: &[&[u8]]) -> Option<usize> {
+    match (action, args) {
+        (b"FLUSHDB" | b"FLUSHTABLE" | b"DBSIZE" | b"RANDOMKEY", [_]) => Some(0),
+        (b"SAMPLE" | b"LSKEYS", [_, _]) => Some(0),
+        (b"LSKEYS", [arg]) if !arg.first().map_or(false, u8::is_ascii_digit) => Some(0),
+        _ => None,
+    }
+}
+
+/// Check that the grants allow `perms` on the table of the given entity. Entities that can't be
+/// resolved are left for the action to report
+fn check_entity<P: ProtocolSpec>(
+    grants: &[u8],
+    db: &Corestore,
+    entity: &[u8],
+    perms: u8,
+) -> ActionResult<()> {
+    let entity = match Entity::from_slice(entity) {
+        Ok(entity) => entity,
+        Err(_) => return Ok(()),
+    };
+    let allowed = match unsafe {
+        // UNSAFE(@ohsayan): The entity borrows from the query, which outlives this call
+        self::resolve(db, &entity)
+    } {
+        Some((ks, tbl)) => self::allows(grants, ks, Some(tbl), perms),
+        None => true,
+    };
+    if allowed {
+        Ok(())
+    } else {
+        err(P::AUTH_CODE_PERMS)
+    }
+}
+
+/// Check that the grants allow `perms` on the table whose notification channel is `channel`.
+/// Other channels aren't restricted
+fn check_channel<P: ProtocolSpec>(grants: &[u8], channel: &[u8], perms: u8) -> ActionResult<()> {
+    let rest = match channel.strip_prefix(notify::CHANNEL_PREFIX) {
+        Some(rest) => rest,
+        None => return Ok(()),
+    };
+    // `<keyspace>:<table>`; without a table, the whole keyspace is needed
+    let mut parts = rest.splitn(2, |b| *b == b':');
+    let ks = parts.next().unwrap_or_default();
+    if self::allows(grants, ks, parts.next(), perms) {
+        Ok(())
+    } else {
+        err(P::AUTH_CODE_PERMS)
+    }
+}
+
+/// The permission check hook for actions: this checks that the grants allow the (uppercased)
+/// action on every table that it will touch. That is the current table, or the entity given
+/// as an argument, and the tables of any qualified keys (`<keyspace>:<table>:<key>`). Pub/sub
+/// actions are checked against the tables of the notification channels that they name
+pub fn check_action<'a, P: ProtocolSpec>(
+    grants: &[u8],
+    db: &Corestore,
+    action: &[u8],
+    mut args: impl Iterator<Item = &'a [u8]>,
+) -> ActionResult<()> {
+    match action {
+        b"SUBSCRIBE" => {
+            return args
+                .try_for_each(|channel| self::check_channel::<P>(grants, channel, PERM_READ))
+        }
+        b"PUBLISH" => {
+            return match args.next() {
+                Some(channel) => self::check_channel::<P>(grants, channel, PERM_WRITE),
+                None => Ok(()),
+            }
+        }
+        _ => {}
+    }
+    let perms = self::required_by(action);
+    if perms == 0 {
+        return Ok(());
+    }
+    let args: Vec<&[u8]> = args.collect();
+    match self::entity_argument(action, &args) {
+        Some(idx) => self::check_entity::<P>(grants, db, args[idx], perms)?,
+        None => {
+            let allowed = match db.get_ids() {
+                (Some(ks), Some(tbl)) => self::allows(grants, ks, Some(&tbl[..]), perms),
+                // no table to run on, so let the action report it
+                _ => true,
+            };
+            if !allowed {
+                return err(P::AUTH_CODE_PERMS);
+            }
+        }
+    }
+    if action == b"MOVE" {
+        // the key is moved into the table named by the last argument
+        if let Some(entity) = args.last() {
+            self::check_entity::<P>(grants, db, entity, PERM_WRITE)?;
+        }
+    }
+    for arg in args {
+        if let Some(qualified) = crate::blueql::util::split_qualified_key(arg) {
+            if !self::allows(grants, qualified.keyspace, Some(qualified.table), perms) {
+                return err(P::AUTH_CODE_PERMS);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grant_and_revoke() {
+        let grants = update(&[], b"ks", PERM_READ, true);
+        let grants = update(&grants, b"ks.tbl", PERM_WRITE, true);
+        let grants = update(&grants, b"ks", PERM_DDL, true);
+        assert_eq!(grants, b"\x05\x02ks\x02\x06ks.tbl");
+        let grants = update(&grants, b"ks", PERM_ALL, false);
+        assert_eq!(grants, b"\x02\x06ks.tbl");
+        // revoking what was never granted changes nothing
+        assert_eq!(update(&grants, b"other", PERM_READ, false), grants);
+    }
+
+    #[test]
+    fn keyspace_grant_covers_tables() {
+        let grants = update(&[], b"ks", PERM_READ, true);
+        assert!(allows(&grants, b"ks", None, PERM_READ));
+        assert!(allows(&grants, b"ks", Some(b"tbl"), PERM_READ));
+        assert!(!allows(&grants, b"ks", Some(b"tbl"), PERM_WRITE));
+        assert!(!allows(&grants, b"ksx", Some(b"tbl"), PERM_READ));
+    }
+
+    #[test]
+    fn table_grant() {
+        let grants = update(&[], b"ks", PERM_READ, true);
+        let grants = update(&grants, b"ks.tbl", PERM_WRITE, true);
+        // the permissions on the keyspace and the table add up
+        assert!(allows(&grants, b"ks", Some(b"tbl"), PERM_READ | PERM_WRITE));
+        // and shards are covered by the table
+        assert!(allows(&grants, b"ks", Some(b"tbl.shard1"), PERM_WRITE));
+        assert!(!allows(&grants, b"ks", Some(b"tbl2"), PERM_WRITE));
+        assert!(!allows(&grants, b"ks", None, PERM_WRITE));
+    }
+
+    #[test]
+    fn any_in_keyspace() {
+        let grants = update(&[], b"ks.tbl", PERM_READ, true);
+        assert!(allows_any(&grants, b"ks", None));
+        assert!(allows_any(&grants, b"ks", Some(b"tbl")));
+        assert!(!allows_any(&grants, b"ks", Some(b"tbl2")));
+        assert!(!allows_any(&grants, b"k", None));
+    }
+
+    #[test]
+    fn describe_grants() {
+        let grants = update(&[], b"ks", PERM_READ | PERM_DDL, true);
+        let grants = update(&grants, b"ks.tbl", PERM_ALL, true);
+        assert_eq!(
+            describe(&grants),
+            vec!["ks read,ddl".to_owned(), "ks.tbl read,write,ddl".to_owned()]
+        );
+    }
+
+    #[test]
+    fn parse_permissions() {
+        assert_eq!(parse_permission(b"READ"), Some(PERM_READ));
+        assert_eq!(parse_permission(b"all"), Some(PERM_ALL));
+        assert_eq!(parse_permission(b"admin"), None);
+    }
+
+    #[test]
+    fn write_actions() {
+        assert!(writes(b"SET"));
+        assert!(writes(b"FLUSHDB"));
+        assert!(writes(b"EXEC"));
+        assert!(!writes(b"GET"));
+        assert!(!writes(b"SYS"));
+        assert!(!writes(b"DISCARD"));
+    }
+
+    #[test]
+    fn writes_to_other_tables() {
+        fn args<'a>(args: &'a [&'a [u8]]) -> impl Iterator<Item = &'a [u8]> {
+            args.iter().copied()
+        }
+        assert!(!writes_elsewhere(b"SET", args(&[b"x".as_slice(), b"100"])));
+        // qualified keys are only read as such if they're enabled
+        assert!(!writes_elsewhere(
+            b"SET",
+            args(&[b"ks:tbl:x".as_slice(), b"100"])
+        ));
+        assert!(writes_elsewhere(
+            b"MOVE",
+            args(&[b"x".as_slice(), b"ks.tbl"])
+        ));
+        assert!(writes_elsewhere(b"FLUSHDB", args(&[b"ks.tbl".as_slice()])));
+        assert!(!writes_elsewhere(b"FLUSHDB", args(&[])));
+    }
+
+    #[test]
+    fn entity_arguments() {
+        assert_eq!(entity_argument(b"DBSIZE", &[b"ks.tbl".as_slice()]), Some(0));
+        assert_eq!(entity_argument(b"DBSIZE", &[]), None);
+        assert_eq!(entity_argument(b"LSKEYS", &[b"10".as_slice()]), None);
+        assert_eq!(entity_argument(b"LSKEYS", &[b"ks.tbl".as_slice()]), Some(0));
+        assert_eq!(entity_argument(b"SAMPLE", &[b"5".as_slice()]), None);
+    }
+
+    #[test]
+    fn notification_channels() {
+        use crate::protocol::Skyhash2;
+        let grants = update(&[], b"ks.tbl", PERM_READ, true);
+        let check = |channel: &[u8], perms| check_channel::<Skyhash2>(&grants, channel, perms);
+        assert!(check(b"__notify__:ks:tbl", PERM_READ).is_ok());
+        assert!(check(b"__notify__:ks:tbl", PERM_WRITE).is_err());
+        assert!(check(b"__notify__:ks:tbl2", PERM_READ).is_err());
+        assert!(check(b"__notify__:other:tbl", PERM_READ).is_err());
+        // a grant on a table doesn't cover the whole keyspace
+        assert!(check(b"__notify__:ks", PERM_READ).is_err());
+        // other channels aren't restricted
+        assert!(check(b"news", PERM_WRITE).is_ok());
+    }
+}
@@ -33,17 +33,24 @@
  * accounts. On claiming the root account, this key is issued
  *
  * When the root account is claimed, it can be used to create "standard users". standard
- * users have access to everything but the ability to create/revoke other users
+ * users have access to everything but the ability to create/revoke other users, unless
+ * root restricts them to specific keyspaces and tables with an ACL (see `acl`)
 */
 
+pub mod acl;
+pub mod external;
 mod keys;
 pub mod provider;
-pub use provider::{AuthProvider, Authmap};
+pub mod ratelimit;
+pub use {
+    acl::Aclmap,
+    provider::{AuthProvider, Authmap, Rotationmap},
+};
 
 #[cfg(test)]
 mod tests;
 
-use crate::dbnet::prelude::*;
+use crate::{blueql, dbnet::prelude::*};
 
 const AUTH_CLAIM: &[u8] = b"claim";
 const AUTH_LOGIN: &[u8] = b"login";
@@ -53,6 +60,14 @@ const AUTH_DELUSER: &[u8] = b"deluser";
 const AUTH_RESTORE: &[u8] = b"restore";
 const AUTH_LISTUSER: &[u8] = b"listuser";
 const AUTH_WHOAMI: &[u8] = b"whoami";
+const AUTH_GRANT: &[u8] = b"grant";
+const AUTH_REVOKE: &[u8] = b"revoke";
+const AUTH_GRANTS: &[u8] = b"grants";
+const AUTH_UNRESTRICT: &[u8] = b"unrestrict";
+const AUTH_ROTATE: &[u8] = b"rotate";
+const AUTH_RENAMEUSER: &[u8] = b"renameuser";
+/// How long a rotated token keeps working if no grace period is given (in seconds)
+const DEFAULT_ROTATION_GRACE: u64 = 300;
 
 action! {
     /// Handle auth. Should have passed the `auth` token
@@ -88,6 +103,23 @@ action! {
             AUTH_RESTORE => self::auth_restore(con, auth, &mut iter).await,
             AUTH_LISTUSER => self::auth_listuser(con, auth, &mut iter).await,
             AUTH_WHOAMI => self::auth_whoami(con, auth, &mut iter).await,
+            AUTH_GRANT => self::auth_grant(con, auth, &mut iter, true).await,
+            AUTH_REVOKE => self::auth_grant(con, auth, &mut iter, false).await,
+            AUTH_GRANTS => self::auth_grants(con, auth, &mut iter).await,
+            AUTH_ROTATE => self::auth_rotate(con, auth, &mut iter).await,
+            AUTH_RENAMEUSER => {
+                ensure_boolean_or_aerr::<P>(iter.len() == 2)?; // the username and the new name
+                let (user, to) = unsafe { (iter.next_unchecked(), iter.next_unchecked()) };
+                auth.provider().rename_user::<P>(user, to)?;
+                con._write_raw(P::RCODE_OKAY).await?;
+                Ok(())
+            }
+            AUTH_UNRESTRICT => {
+                ensure_boolean_or_aerr::<P>(iter.len() == 1)?; // just the username
+                auth.provider().unrestrict::<P>(unsafe { iter.next_unchecked() })?;
+                con._write_raw(P::RCODE_OKAY).await?;
+                Ok(())
+            }
             _ => util::err(P::RCODE_UNKNOWN_ACTION),
         }
     }
@@ -105,6 +137,64 @@ action! {
         }
         Ok(())
     }
+    /// Grant (or revoke) permissions on a keyspace or table to a user
+    /// ## Syntax
+    /// - `AUTH GRANT <user> <entity> <permission> [<permission> ...]`
+    /// - `AUTH REVOKE <user> <entity> <permission> [<permission> ...]`
+    ///
+    /// where the entity is `<keyspace>` or `<keyspace>.<table>` and the permissions are
+    /// `read`, `write`, `ddl` or `all`
+    fn auth_grant(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        iter: &mut ActionIter<'_>,
+        grant: bool
+    ) {
+        ensure_boolean_or_aerr::<P>(iter.len() > 2)?; // the user, the entity and the perms
+        let (user, entity) = unsafe { (iter.next_unchecked(), iter.next_unchecked()) };
+        let entity = blueql::util::from_slice_action_result::<P>(entity)?;
+        let scope = acl::scope_of(&entity).unwrap_or_aerr::<P>()?;
+        let mut perms = 0;
+        for perm in iter {
+            perms |= acl::parse_permission(perm).unwrap_or_aerr::<P>()?;
+        }
+        auth.provider().update_grants::<P>(user, &scope, perms, grant)?;
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Returns the grants of a user as an array of `<entity> <permission>[,<permission>...]`
+    /// strings, or nil if the user isn't restricted
+    fn auth_grants(con: &mut Connection<C, P>, auth: &mut AuthProviderHandle, iter: &mut ActionIter<'_>) {
+        ensure_boolean_or_aerr::<P>(iter.len() == 1)?; // just the username
+        match auth.provider().collect_grants::<P>(unsafe { iter.next_unchecked() })? {
+            Some(grants) => {
+                con.write_typed_non_null_array_header(grants.len(), b'+').await?;
+                for grant in grants {
+                    con.write_typed_non_null_array_element(grant.as_bytes()).await?;
+                }
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+    /// Rotate the token of a user, returning the new token. The old token keeps working for
+    /// the grace period (five minutes, by default)
+    /// ## Syntax
+    /// `AUTH ROTATE <user> [<grace period in seconds>]`
+    fn auth_rotate(con: &mut Connection<C, P>, auth: &mut AuthProviderHandle, iter: &mut ActionIter<'_>) {
+        ensure_boolean_or_aerr::<P>(iter.len() == 1 || iter.len() == 2)?;
+        let user = unsafe { iter.next_unchecked() };
+        let grace = match iter.next() {
+            Some(grace) => match String::from_utf8_lossy(grace).parse::<u64>() {
+                Ok(grace) => grace,
+                Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+            },
+            None => DEFAULT_ROTATION_GRACE,
+        };
+        let newkey = auth.provider().rotate::<P>(user, grace)?;
+        con.write_string(&newkey).await?;
+        Ok(())
+    }
     fn auth_restore(con: &mut Connection<C, P>, auth: &mut AuthProviderHandle, iter: &mut ActionIter<'_>) {
         let newkey = match iter.len() {
             1 => {
@@ -151,7 +241,7 @@ action! {
         // sweet, where's our username and password
         ensure_boolean_or_aerr::<P>(iter.len() == 2)?; // just the uname and pass
         let (username, password) = unsafe { (iter.next_unchecked(), iter.next_unchecked()) };
-        auth.provider_mut().login::<P>(username, password)?;
+        auth.provider_mut().login_with_external::<P>(username, password).await?;
         auth.set_auth();
         con._write_raw(P::RCODE_OKAY).await?;
         Ok(())
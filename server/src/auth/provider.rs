@@ -25,14 +25,22 @@
 */
 
 use {
-    super::keys,
+    super::{
+        acl::{self, Aclmap},
+        external::Authenticator,
+        keys,
+        ratelimit::RateLimiter,
+    },
     crate::{
         actions::{ActionError, ActionResult},
-        corestore::{array::Array, htable::Coremap},
+        config::RateLimitConfig,
+        corestore::{array::Array, htable::Coremap, SharedSlice},
+        kvengine::expiry,
         protocol::interface::ProtocolSpec,
         util::err,
     },
     std::sync::Arc,
+    tokio::task,
 };
 
 // constants
@@ -62,6 +70,9 @@ type AuthID = Array<u8, AUTHID_SIZE>;
 pub type Authkey = [u8; AUTHKEY_SIZE];
 /// Authmap
 pub type Authmap = Arc<Coremap<AuthID, Authkey>>;
+/// A map of users to their previous token (while it's still in its grace period). Every value
+/// is the hash of the token followed by the deadline (a UNIX timestamp in milliseconds, 8B LE)
+pub type Rotationmap = Arc<Coremap<SharedSlice, SharedSlice>>;
 
 /// The authn/authz provider
 ///
@@ -71,32 +82,90 @@ pub struct AuthProvider {
     whoami: Option<AuthID>,
     /// a map of users
     authmap: Authmap,
+    /// a map of users to their grants
+    aclmap: Aclmap,
+    /// a map of users to their rotated tokens
+    rotationmap: Rotationmap,
+    /// the query budgets of the users
+    ratelimit: Arc<RateLimiter>,
+    /// the provider that token checks are delegated to (see [`super::external`])
+    external: Option<Arc<dyn Authenticator>>,
 }
 
 impl AuthProvider {
-    fn _new(authmap: Authmap, whoami: Option<AuthID>, origin: Option<Authkey>) -> Self {
+    fn _new(
+        authmap: Authmap,
+        aclmap: Aclmap,
+        rotationmap: Rotationmap,
+        whoami: Option<AuthID>,
+        origin: Option<Authkey>,
+        ratelimit: RateLimiter,
+        external: Option<Arc<dyn Authenticator>>,
+    ) -> Self {
         Self {
             authmap,
+            aclmap,
+            rotationmap,
             whoami,
             origin,
+            ratelimit: Arc::new(ratelimit),
+            external,
         }
     }
     /// New provider with no origin-key
     pub fn new_disabled() -> Self {
-        Self::_new(Default::default(), None, None)
+        Self::_new(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            None,
+            RateLimiter::new_disabled(),
+            None,
+        )
     }
     /// New provider with zero users
     #[cfg(test)]
     pub fn new_blank(origin: Option<Authkey>) -> Self {
-        Self::_new(Default::default(), None, origin)
+        Self::_new(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+            origin,
+            RateLimiter::new_disabled(),
+            None,
+        )
     }
-    /// New provider with users from the provided map
+    /// Delegate token checks to the given provider
+    #[cfg(test)]
+    pub fn with_external(mut self, external: Arc<dyn Authenticator>) -> Self {
+        self.external = Some(external);
+        self
+    }
+    /// New provider with users (and their grants and rotated tokens) from the provided maps,
+    /// optionally delegating token checks to an external provider
     ///
     /// ## Test suite
     /// The testsuite creates users `root` and `testuser`; this **does not** apply to
     /// release mode
-    pub fn new(authmap: Arc<Coremap<AuthID, Authkey>>, origin: Option<Authkey>) -> Self {
-        let slf = Self::_new(authmap, None, origin);
+    pub fn new(
+        authmap: Arc<Coremap<AuthID, Authkey>>,
+        aclmap: Aclmap,
+        rotationmap: Rotationmap,
+        origin: Option<Authkey>,
+        ratelimit: RateLimitConfig,
+        external: Option<Arc<dyn Authenticator>>,
+    ) -> Self {
+        let slf = Self::_new(
+            authmap,
+            aclmap,
+            rotationmap,
+            None,
+            origin,
+            RateLimiter::new(ratelimit),
+            external,
+        );
         #[cfg(debug_assertions)]
         {
             // 'root' user in test mode
@@ -123,6 +192,14 @@ impl AuthProvider {
     pub const fn is_enabled(&self) -> bool {
         matches!(self.origin, Some(_))
     }
+    /// Returns true if a user is logged in
+    pub const fn is_logged_in(&self) -> bool {
+        self.whoami.is_some()
+    }
+    /// Returns the user that is logged in (if any)
+    pub fn current_user(&self) -> Option<&[u8]> {
+        self.whoami.as_deref()
+    }
     pub fn claim_root<P: ProtocolSpec>(&mut self, origin_key: &[u8]) -> ActionResult<String> {
         self.verify_origin::<P>(origin_key)?;
         // the origin key was good, let's try claiming root
@@ -159,22 +236,81 @@ impl AuthProvider {
     }
     pub fn login<P: ProtocolSpec>(&mut self, account: &[u8], token: &[u8]) -> ActionResult<()> {
         self.ensure_enabled::<P>()?;
-        match self
+        let verified = self
             .authmap
             .get(account)
-            .map(|token_hash| keys::verify_key(token, token_hash.as_slice()))
-        {
+            .map(|token_hash| keys::verify_key(token, token_hash.as_slice()));
+        match verified {
             Some(Some(true)) => {
                 // great, authenticated
                 self.whoami = Some(Self::try_auth_id::<P>(account)?);
                 Ok(())
             }
+            Some(_) if self.verify_rotated(account, token) => {
+                // the token was rotated, but it's still in its grace period
+                self.whoami = Some(Self::try_auth_id::<P>(account)?);
+                Ok(())
+            }
             _ => {
                 // either the password was wrong, or the username was wrong
                 err(P::AUTH_CODE_BAD_CREDENTIALS)
             }
         }
     }
+    /// Log in like [`Self::login`], but if the token isn't good, let the external provider (if
+    /// there's one) check it instead. The provider is never asked about root, or about users
+    /// that don't exist
+    pub async fn login_with_external<P: ProtocolSpec>(
+        &mut self,
+        account: &[u8],
+        token: &[u8],
+    ) -> ActionResult<()> {
+        let error = match self.login::<P>(account, token) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        let external = match self.external.as_ref() {
+            Some(external)
+                if self.is_enabled()
+                    && account.ne(&USER_ROOT)
+                    && self.authmap.contains_key(account) =>
+            {
+                external.clone()
+            }
+            _ => return Err(error),
+        };
+        let (user, secret) = (account.to_owned(), token.to_owned());
+        let name = external.name();
+        match task::spawn_blocking(move || external.authenticate(&user, &secret)).await {
+            // the user might have been deleted while the provider was busy
+            Ok(Ok(true)) if self.authmap.contains_key(account) => {
+                self.whoami = Some(Self::try_auth_id::<P>(account)?);
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                log::warn!("Failed to authenticate with the {name} provider: {e}");
+                Err(error)
+            }
+            _ => Err(error),
+        }
+    }
+    /// Log in as the user named by a (verified) client certificate. Returns false if auth is
+    /// disabled or if there's no such user
+    pub fn login_with_certificate(&mut self, identity: &[u8]) -> bool {
+        if self.is_enabled()
+            && identity.is_ascii()
+            && identity.len() <= AUTHID_SIZE
+            && self.authmap.contains_key(identity)
+        {
+            self.whoami = Some(unsafe {
+                // We just verified the length
+                AuthID::from_slice(identity)
+            });
+            true
+        } else {
+            false
+        }
+    }
     pub fn regenerate_using_origin<P: ProtocolSpec>(
         &self,
         origin: &[u8],
@@ -192,11 +328,60 @@ impl AuthProvider {
         let id = Self::try_auth_id::<P>(account)?;
         let (key, store) = keys::generate_full();
         if self.authmap.true_if_update(id, store) {
+            // the old token might have been lost, so a rotated token shouldn't outlive it
+            self.rotationmap.remove(account);
             Ok(key)
         } else {
             err(P::AUTH_CODE_BAD_CREDENTIALS)
         }
     }
+    /// Rotate the token of the given user. This returns a new token, while the old one keeps
+    /// working until `grace` seconds have passed (only the last rotated token is kept). Users
+    /// can rotate their own tokens, and root can rotate anyone's
+    pub fn rotate<P: ProtocolSpec>(&self, account: &[u8], grace: u64) -> ActionResult<String> {
+        self.ensure_enabled::<P>()?;
+        if !self.whoami.as_ref().map_or(false, |me| me.eq(account)) {
+            self.ensure_root::<P>()?;
+        }
+        let id = Self::try_auth_id::<P>(account)?;
+        let (key, store) = keys::generate_full();
+        match self.authmap.mut_entry(id) {
+            Some(mut entry) => {
+                // the old token is saved before the new one is set, so that there's never a
+                // moment when neither of them works
+                if grace == 0 {
+                    self.rotationmap.remove(account);
+                } else {
+                    let mut rotated = Vec::with_capacity(AUTHKEY_SIZE + 8);
+                    rotated.extend_from_slice(entry.value());
+                    rotated.extend_from_slice(&expiry::deadline_after_secs(grace).to_le_bytes());
+                    self.rotationmap
+                        .upsert(SharedSlice::new(account), rotated.into());
+                }
+                entry.insert(store);
+                Ok(key)
+            }
+            None => err(P::AUTH_CODE_BAD_CREDENTIALS),
+        }
+    }
+    /// Returns true if the token is the rotated token of the user and its grace period hasn't
+    /// ended yet. Rotated tokens are dropped once they're found to have expired
+    fn verify_rotated(&self, account: &[u8], token: &[u8]) -> bool {
+        let rotated = match self.rotationmap.get_cloned(account) {
+            Some(rotated) if rotated.len() == AUTHKEY_SIZE + 8 => rotated,
+            _ => return false,
+        };
+        let (hash, deadline) = rotated.split_at(AUTHKEY_SIZE);
+        let deadline = u64::from_le_bytes(deadline.try_into().unwrap());
+        if expiry::now_millis() < deadline {
+            keys::verify_key(token, hash) == Some(true)
+        } else {
+            // don't remove it if the token was rotated again in the meantime
+            self.rotationmap
+                .true_remove_if(account, |_, current| current.eq(&rotated));
+            false
+        }
+    }
     fn try_auth_id<P: ProtocolSpec>(authid: &[u8]) -> ActionResult<AuthID> {
         if authid.is_ascii() && authid.len() <= AUTHID_SIZE {
             Ok(unsafe {
@@ -240,17 +425,66 @@ impl AuthProvider {
             err(P::AUTH_CODE_PERMS)
         }
     }
+    /// Ensure that the connection is allowed to change server-wide state: this is anyone
+    /// if auth is disabled and only root otherwise
+    pub fn ensure_superuser<P: ProtocolSpec>(&self) -> ActionResult<()> {
+        if self.is_enabled() {
+            self.ensure_root::<P>()
+        } else {
+            Ok(())
+        }
+    }
     pub fn delete_user<P: ProtocolSpec>(&self, user: &[u8]) -> ActionResult<()> {
         self.ensure_root::<P>()?;
         if user.eq(&USER_ROOT) {
             // can't delete root!
             err(P::AUTH_ERROR_FAILED_TO_DELETE_USER)
         } else if self.authmap.true_if_removed(user) {
+            // a new user with the same name shouldn't inherit the grants (or the old token)
+            self.aclmap.remove(user);
+            self.rotationmap.remove(user);
             Ok(())
         } else {
             err(P::AUTH_CODE_BAD_CREDENTIALS)
         }
     }
+    /// Rename a user. The grants of the user (and its rotated token) are moved too
+    pub fn rename_user<P: ProtocolSpec>(&self, user: &[u8], to: &[u8]) -> ActionResult<()> {
+        self.ensure_root::<P>()?;
+        let to_id = Self::try_auth_id::<P>(to)?;
+        if user.eq(&USER_ROOT) {
+            // root is always root
+            return err(P::RCODE_ACTION_ERR);
+        }
+        if self.authmap.contains_key(to) {
+            return err(P::AUTH_ERROR_ALREADYCLAIMED);
+        }
+        // the grants are copied before the rename, so that the user is never without them
+        let grants = self.aclmap.get_cloned(user);
+        if let Some(ref grants) = grants {
+            self.aclmap.upsert(SharedSlice::new(to), grants.clone());
+        }
+        match self.authmap.rename(user, to_id) {
+            Some(true) => {
+                self.aclmap.remove(user);
+                if let Some((_, rotated)) = self.rotationmap.remove(user) {
+                    self.rotationmap.upsert(SharedSlice::new(to), rotated);
+                }
+                Ok(())
+            }
+            ret => {
+                if grants.is_some() {
+                    self.aclmap.remove(to);
+                }
+                if ret.is_some() {
+                    // someone claimed the name in the meantime
+                    err(P::AUTH_ERROR_ALREADYCLAIMED)
+                } else {
+                    err(P::AUTH_CODE_BAD_CREDENTIALS)
+                }
+            }
+        }
+    }
     /// List all the users
     pub fn collect_usernames<P: ProtocolSpec>(&self) -> ActionResult<Vec<String>> {
         self.ensure_root::<P>()?;
@@ -268,14 +502,100 @@ impl AuthProvider {
             .map(|v| String::from_utf8_lossy(v).to_string())
             .ok_or(ActionError::ActionError(P::AUTH_CODE_PERMS))
     }
+    /// Grant `perms` on `scope` to the given user, or revoke them if `grant` is false (see
+    /// [`super::acl`]). Revoking doesn't do anything for a user without an ACL
+    pub fn update_grants<P: ProtocolSpec>(
+        &self,
+        user: &[u8],
+        scope: &[u8],
+        perms: u8,
+        grant: bool,
+    ) -> ActionResult<()> {
+        self.ensure_restrictable::<P>(user)?;
+        let user = SharedSlice::new(user);
+        loop {
+            if let Some(mut entry) = self.aclmap.mut_entry(user.clone()) {
+                let updated = acl::update(entry.value().as_slice(), scope, perms, grant);
+                entry.insert(updated.into());
+                break;
+            }
+            if !grant {
+                break;
+            }
+            if let Some(fresh) = self.aclmap.fresh_entry(user.clone()) {
+                fresh.insert(acl::update(&[], scope, perms, true).into());
+                break;
+            }
+            // someone else created the ACL in the meantime, so update theirs
+        }
+        Ok(())
+    }
+    /// Remove the ACL of the given user, which lifts all its restrictions
+    pub fn unrestrict<P: ProtocolSpec>(&self, user: &[u8]) -> ActionResult<()> {
+        self.ensure_restrictable::<P>(user)?;
+        self.aclmap.remove(user);
+        Ok(())
+    }
+    /// Describe the grants of the given user. This returns `None` if the user doesn't have an ACL
+    pub fn collect_grants<P: ProtocolSpec>(
+        &self,
+        user: &[u8],
+    ) -> ActionResult<Option<Vec<String>>> {
+        self.ensure_restrictable::<P>(user)?;
+        Ok(self
+            .aclmap
+            .get(user)
+            .map(|grants| acl::describe(grants.value().as_slice())))
+    }
+    /// Only root can manage ACLs, and root itself can't be restricted
+    fn ensure_restrictable<P: ProtocolSpec>(&self, user: &[u8]) -> ActionResult<()> {
+        self.ensure_root::<P>()?;
+        if user.eq(&USER_ROOT) {
+            err(P::RCODE_ACTION_ERR)
+        } else if self.authmap.contains_key(user) {
+            Ok(())
+        } else {
+            err(P::AUTH_CODE_BAD_CREDENTIALS)
+        }
+    }
+    /// Returns the grants of the current user, or `None` if the user isn't restricted (which is
+    /// always the case for root and anonymous users)
+    pub fn grants(&self) -> Option<SharedSlice> {
+        match self.whoami.as_ref() {
+            Some(user) if !self.aclmap.is_empty() && user.ne(&USER_ROOT) => {
+                match self.aclmap.get(user.as_slice()) {
+                    Some(grants) => Some(grants.value().clone()),
+                    // the user was deleted (or renamed) while logged in, so it can't access
+                    // anything anymore
+                    None if !self.authmap.contains_key(user.as_slice()) => {
+                        Some(SharedSlice::new(&[]))
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        }
+    }
+    /// Returns true if the current user can run `count` queries right now (see
+    /// [`super::ratelimit`]). Anonymous users are never limited
+    pub fn try_run_queries(&self, count: usize) -> bool {
+        match self.whoami.as_ref() {
+            Some(user) => self.ratelimit.try_run(user, count),
+            None => true,
+        }
+    }
 }
 
 impl Clone for AuthProvider {
     fn clone(&self) -> Self {
         Self {
             authmap: self.authmap.clone(),
+            aclmap: self.aclmap.clone(),
+            rotationmap: self.rotationmap.clone(),
             whoami: None,
             origin: self.origin,
+            ratelimit: self.ratelimit.clone(),
+            external: self.external.clone(),
         }
     }
 }
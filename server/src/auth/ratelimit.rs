@@ -0,0 +1,193 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Query rate limiting
+//!
+//! Every authenticated user that has a budget (in queries per second) gets a token bucket that
+//! is refilled at the rate of their budget and holds at most a second's worth of queries. Each
+//! query takes a token from the bucket (a pipeline takes one for each of its queries) and once
+//! the bucket runs dry, queries are rejected with `703 throttled` until it has been refilled.
+//!
+//! A user's budget is the one set for them in `ratelimit.users` or, failing that, the one set
+//! in `ratelimit.queries`. Anonymous connections (when auth is disabled) are never limited
+
+use {
+    crate::{config::RateLimitConfig, corestore::htable::Coremap},
+    parking_lot::Mutex,
+    std::{collections::HashMap, time::Instant},
+};
+
+/// The token bucket of a user
+struct Bucket {
+    /// the number of queries that can be run right away
+    tokens: f64,
+    /// when the bucket was last refilled
+    last: Instant,
+}
+
+impl Bucket {
+    /// A full bucket
+    fn new(budget: u64, now: Instant) -> Self {
+        Self {
+            tokens: budget as f64,
+            last: now,
+        }
+    }
+    /// Refill the bucket for the time that has elapsed since the last refill and then attempt
+    /// to take `cost` tokens from it. A cost higher than the budget is capped at the budget,
+    /// so that a large pipeline can still run once the bucket is full
+    fn try_take(&mut self, budget: u64, cost: usize, now: Instant) -> bool {
+        let budget = budget as f64;
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * budget).min(budget);
+        self.last = now;
+        let cost = (cost as f64).min(budget);
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The query rate limiter shared by all the connections
+pub struct RateLimiter {
+    /// the budget of users that don't have one of their own
+    default: Option<u64>,
+    /// the budgets of specific users
+    budgets: HashMap<Box<[u8]>, u64>,
+    /// the buckets of the users that have run queries
+    buckets: Coremap<Box<[u8]>, Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(RateLimitConfig { queries, users }: RateLimitConfig) -> Self {
+        Self {
+            default: queries,
+            budgets: users
+                .0
+                .into_iter()
+                .map(|(user, budget)| (user.into_bytes().into_boxed_slice(), budget))
+                .collect(),
+            buckets: Coremap::new(),
+        }
+    }
+    /// A limiter that doesn't limit anyone
+    pub fn new_disabled() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+    /// Returns the budget of the given user (`None` if they aren't limited)
+    fn budget_of(&self, user: &[u8]) -> Option<u64> {
+        self.budgets.get(user).copied().or(self.default)
+    }
+    /// Returns true if `user` can run `count` queries now (taking them out of their budget)
+    pub fn try_run(&self, user: &[u8], count: usize) -> bool {
+        self.try_run_at(user, count, Instant::now())
+    }
+    fn try_run_at(&self, user: &[u8], count: usize, now: Instant) -> bool {
+        let budget = match self.budget_of(user) {
+            Some(budget) => budget,
+            None => return true,
+        };
+        if let Some(bucket) = self.buckets.get(user) {
+            return bucket.lock().try_take(budget, count, now);
+        }
+        // first query from this user; if somebody else beat us to creating the bucket, we'll
+        // just use theirs
+        let mut bucket = Bucket::new(budget, now);
+        let okay = bucket.try_take(budget, count, now);
+        if self
+            .buckets
+            .true_if_insert(user.to_vec().into_boxed_slice(), Mutex::new(bucket))
+        {
+            okay
+        } else {
+            self.try_run_at(user, count, now)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::RateLimiter,
+        crate::config::{RateLimitConfig, UserBudgets},
+        std::time::{Duration, Instant},
+    };
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig::new(
+            Some(10),
+            UserBudgets::new(vec![("root".to_owned(), 2)]),
+        ))
+    }
+
+    #[test]
+    fn budget_is_spent_and_refilled() {
+        let limiter = limiter();
+        let start = Instant::now();
+        assert!(limiter.try_run_at(b"root", 1, start));
+        assert!(limiter.try_run_at(b"root", 1, start));
+        assert!(!limiter.try_run_at(b"root", 1, start));
+        // half a second gets root another query
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_run_at(b"root", 1, later));
+        assert!(!limiter.try_run_at(b"root", 1, later));
+        // but the bucket never holds more than a second's worth
+        let much_later = later + Duration::from_secs(60);
+        assert!(limiter.try_run_at(b"root", 2, much_later));
+        assert!(!limiter.try_run_at(b"root", 1, much_later));
+    }
+
+    #[test]
+    fn users_have_their_own_buckets() {
+        let limiter = limiter();
+        let now = Instant::now();
+        // everyone else gets the default budget
+        assert!(limiter.try_run_at(b"sayan", 10, now));
+        assert!(!limiter.try_run_at(b"sayan", 1, now));
+        assert!(limiter.try_run_at(b"other", 1, now));
+        assert!(limiter.try_run_at(b"root", 2, now));
+    }
+
+    #[test]
+    fn large_pipelines_take_the_full_bucket() {
+        let limiter = limiter();
+        let now = Instant::now();
+        assert!(limiter.try_run_at(b"root", 100, now));
+        assert!(!limiter.try_run_at(b"root", 1, now));
+    }
+
+    #[test]
+    fn disabled_limiter() {
+        let limiter = RateLimiter::new_disabled();
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.try_run_at(b"root", 1, now));
+        }
+    }
+}
@@ -35,10 +35,13 @@ mod keys {
 }
 
 mod authn {
-    use crate::{
-        actions::ActionError,
-        auth::AuthProvider,
-        protocol::{interface::ProtocolSpec, Skyhash2},
+    use {
+        crate::{
+            actions::ActionError,
+            auth::{acl, external::Authenticator, AuthProvider},
+            protocol::{interface::ProtocolSpec, Skyhash2},
+        },
+        std::{io::Result as IoResult, sync::Arc},
     };
 
     const ORIG: &[u8; 40] = b"c4299d190fb9a00626797fcc138c56eae9971664";
@@ -121,4 +124,192 @@ mod authn {
             ActionError::ActionError(Skyhash2::AUTH_CODE_PERMS)
         );
     }
+    #[test]
+    fn login_with_certificate() {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG));
+        let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let _ = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        provider.logout::<Skyhash2>().unwrap();
+        // the certificate has to name an existing user
+        assert!(!provider.login_with_certificate(b"nobody"));
+        assert!(!provider.is_logged_in());
+        assert!(provider.login_with_certificate(b"sayan"));
+        assert_eq!(provider.whoami::<Skyhash2>().unwrap(), "sayan");
+    }
+    #[test]
+    fn login_with_certificate_disabled() {
+        let mut provider = AuthProvider::new_disabled();
+        assert!(!provider.login_with_certificate(b"root"));
+        assert!(!provider.is_logged_in());
+    }
+    #[test]
+    fn grants_restrict_user() {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG));
+        let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let userkey = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        // root can't be restricted
+        assert_eq!(
+            provider
+                .update_grants::<Skyhash2>(b"root", b"ks", acl::PERM_READ, true)
+                .unwrap_err(),
+            ActionError::ActionError(Skyhash2::RCODE_ACTION_ERR)
+        );
+        // revoking doesn't restrict a user without an ACL
+        provider
+            .update_grants::<Skyhash2>(b"sayan", b"ks", acl::PERM_READ, false)
+            .unwrap();
+        assert_eq!(provider.collect_grants::<Skyhash2>(b"sayan").unwrap(), None);
+        provider
+            .update_grants::<Skyhash2>(b"sayan", b"ks", acl::PERM_READ, true)
+            .unwrap();
+        assert_eq!(
+            provider.collect_grants::<Skyhash2>(b"sayan").unwrap(),
+            Some(vec!["ks read".to_owned()])
+        );
+        // root is never restricted
+        assert!(provider.grants().is_none());
+        provider
+            .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .unwrap();
+        assert_eq!(provider.grants().unwrap().as_slice(), b"\x01\x02ks");
+    }
+    #[test]
+    fn rotate_token_with_grace() {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG));
+        let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let oldkey = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        let newkey = provider.rotate::<Skyhash2>(b"sayan", 60).unwrap();
+        // both the tokens work in the grace period
+        provider
+            .login::<Skyhash2>(b"sayan", oldkey.as_bytes())
+            .unwrap();
+        provider
+            .login::<Skyhash2>(b"sayan", newkey.as_bytes())
+            .unwrap();
+        // users can rotate their own tokens, and without a grace period the old one stops
+        // working right away
+        let lastkey = provider.rotate::<Skyhash2>(b"sayan", 0).unwrap();
+        for key in [oldkey, newkey] {
+            assert_eq!(
+                provider
+                    .login::<Skyhash2>(b"sayan", key.as_bytes())
+                    .unwrap_err(),
+                ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+            );
+        }
+        provider
+            .login::<Skyhash2>(b"sayan", lastkey.as_bytes())
+            .unwrap();
+        // but they can't rotate anyone else's
+        assert_eq!(
+            provider.rotate::<Skyhash2>(b"root", 0).unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_PERMS)
+        );
+    }
+    #[test]
+    fn rename_user_keeps_grants() {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG));
+        let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let userkey = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        let _ = provider.claim_user::<Skyhash2>(b"other").unwrap();
+        provider
+            .update_grants::<Skyhash2>(b"sayan", b"ks", acl::PERM_READ, true)
+            .unwrap();
+        assert_eq!(
+            provider
+                .rename_user::<Skyhash2>(b"sayan", b"other")
+                .unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_ERROR_ALREADYCLAIMED)
+        );
+        assert_eq!(
+            provider
+                .rename_user::<Skyhash2>(b"nobody", b"someone")
+                .unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+        );
+        provider
+            .rename_user::<Skyhash2>(b"sayan", b"nandan")
+            .unwrap();
+        assert_eq!(
+            provider.collect_grants::<Skyhash2>(b"nandan").unwrap(),
+            Some(vec!["ks read".to_owned()])
+        );
+        assert_eq!(
+            provider
+                .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+                .unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+        );
+        provider
+            .login::<Skyhash2>(b"nandan", userkey.as_bytes())
+            .unwrap();
+    }
+    #[test]
+    fn delete_user_removes_grants() {
+        let provider = {
+            let mut provider = AuthProvider::new_blank(Some(*ORIG));
+            let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+            provider
+        };
+        let _ = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        provider
+            .update_grants::<Skyhash2>(b"sayan", b"ks", acl::PERM_ALL, true)
+            .unwrap();
+        provider.delete_user::<Skyhash2>(b"sayan").unwrap();
+        let _ = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        assert_eq!(provider.collect_grants::<Skyhash2>(b"sayan").unwrap(), None);
+    }
+
+    /// A provider that only knows a single secret
+    struct Directory;
+
+    impl Authenticator for Directory {
+        fn authenticate(&self, _user: &[u8], secret: &[u8]) -> IoResult<bool> {
+            Ok(secret == b"hunter2")
+        }
+        fn name(&self) -> &'static str {
+            "directory"
+        }
+    }
+
+    #[tokio::test]
+    async fn login_with_external_provider() {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG)).with_external(Arc::new(Directory));
+        let _ = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let userkey = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        // the local token still works
+        provider
+            .login_with_external::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .await
+            .unwrap();
+        provider.logout::<Skyhash2>().unwrap();
+        // and so does the secret that the provider knows
+        provider
+            .login_with_external::<Skyhash2>(b"sayan", b"hunter2")
+            .await
+            .unwrap();
+        assert_eq!(provider.whoami::<Skyhash2>().unwrap(), "sayan");
+        provider.logout::<Skyhash2>().unwrap();
+        assert_eq!(
+            provider
+                .login_with_external::<Skyhash2>(b"sayan", b"hunter3")
+                .await
+                .unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+        );
+        // root and unknown users never go through the provider
+        for user in [&b"root"[..], b"nobody"] {
+            assert_eq!(
+                provider
+                    .login_with_external::<Skyhash2>(user, b"hunter2")
+                    .await
+                    .unwrap_err(),
+                ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+            );
+        }
+        assert_eq!(
+            provider.whoami::<Skyhash2>().unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_PERMS)
+        );
+    }
 }
@@ -30,7 +30,13 @@ use {
         lexer::{Keyword, Lexer, Token, Type, TypeExpression},
         RawSlice,
     },
-    crate::util::{compiler, Life},
+    crate::{
+        config::WalFsync,
+        corestore::table::{COMPRESSED_MODEL_CODE_OFFSET, DISK_MODEL_CODE_OFFSET},
+        kvengine::eviction::EvictionPolicy,
+        storage::v1::wal::Durability,
+        util::{compiler, Life},
+    },
     core::{marker::PhantomData, mem::transmute, ptr},
 };
 
@@ -39,35 +45,85 @@ use {
 #[repr(u8)]
 /// A statement that can be executed
 pub enum Statement {
-    /// Create a new space with the provided ID
-    CreateSpace(RawSlice),
-    /// Create a new model with the provided configuration
+    /// Create a new space with the provided ID and the defaults for the models created in it
+    CreateSpace {
+        entity: RawSlice,
+        model: FieldConfig,
+        volatile: bool,
+    },
+    /// Create a new model with the provided configuration (an empty field configuration means
+    /// that the defaults of the space are used). If `volatile` isn't declared, the default of
+    /// the space is used. If `compressed` is set, the values are stored compressed, while if
+    /// `disk` is set, they're kept on disk
     CreateModel {
         entity: Entity,
         model: FieldConfig,
-        volatile: bool,
+        volatile: Option<bool>,
+        compressed: bool,
+        disk: bool,
+        limits: LimitsDecl,
     },
     /// Drop the given model
     DropModel { entity: Entity, force: bool },
     /// Drop the given space
     DropSpace { entity: RawSlice, force: bool },
+    /// Change the properties of the given model. Properties that are `None` are left as is
+    AlterModel {
+        entity: Entity,
+        volatile: Option<bool>,
+        limits: LimitsDecl,
+    },
+    /// Rename the given space
+    RenameSpace { entity: RawSlice, to: RawSlice },
+    /// Rename the given model (within its space)
+    RenameModel { entity: Entity, to: RawSlice },
     /// Inspect the given space
     InspectSpace(Option<RawSlice>),
     /// Inspect the given model
     InspectModel(Option<Entity>),
     /// Inspect all the spaces in the database
     InspectSpaces,
+    /// Inspect all the shards of the given model
+    InspectShards(Entity),
     /// Switch to the given entity
     Use(Entity),
 }
 
 pub type StatementLT<'a> = Life<'a, Statement>;
 
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+/// The limits declared for a model: the size limits (`max_key_size` and `max_value_size`,
+/// in bytes), the memory cap (`max_memory`, in bytes), the eviction policy (`eviction`) and
+/// the durability (`durability`). `None` leaves a limit as is, while zero removes it
+pub struct LimitsDecl {
+    pub max_key_size: Option<u64>,
+    pub max_value_size: Option<u64>,
+    pub max_memory: Option<u64>,
+    pub eviction: Option<EvictionPolicy>,
+    pub durability: Option<Durability>,
+}
+
+impl LimitsDecl {
+    /// Returns true if no limit was declared
+    pub const fn is_empty(&self) -> bool {
+        self.max_key_size.is_none()
+            && self.max_value_size.is_none()
+            && self.max_memory.is_none()
+            && self.eviction.is_none()
+            && self.durability.is_none()
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub enum Entity {
+    /// `<model>` in the current space
     Current(RawSlice),
+    /// `<space>.<model>`
     Full(RawSlice, RawSlice),
+    /// `<space>.<model>.<shard>`
+    Shard(RawSlice, RawSlice, RawSlice),
 }
 
 impl Entity {
@@ -75,6 +131,10 @@ impl Entity {
     pub fn from_slice(slice: &[u8]) -> LangResult<Self> {
         Compiler::new(&Lexer::lex(slice)?).parse_entity_name()
     }
+    /// Returns true if the shard ID (`<model>.<shard>`) fits in an object ID
+    fn is_valid_shard(model: &RawSlice, shard: &RawSlice) -> bool {
+        model.len() + 1 + shard.len() < Self::MAX_LENGTH_EX
+    }
 }
 
 #[derive(Debug)]
@@ -95,6 +155,30 @@ impl FieldConfig {
             names: Vec::new(),
         }
     }
+    /// Same as [`Self::get_model_code`], but returns `None` if no fields were declared
+    pub fn get_model_code_if_declared(&self) -> LangResult<Option<u8>> {
+        if self.types.is_empty() && self.names.is_empty() {
+            Ok(None)
+        } else {
+            self.get_model_code().map(Some)
+        }
+    }
+    /// Same as [`Self::get_model_code`], but for a model that stores its values compressed.
+    /// Only models with binary or string values can be compressed
+    pub fn get_compressed_model_code(&self) -> LangResult<u8> {
+        match self.get_model_code()? {
+            code if code < 4 => Ok(code + COMPRESSED_MODEL_CODE_OFFSET),
+            _ => Err(LangError::UnsupportedModelDeclaration),
+        }
+    }
+    /// Same as [`Self::get_model_code`], but for a model that keeps its values on disk. Only
+    /// models with binary or string values can be kept on disk
+    pub fn get_disk_model_code(&self) -> LangResult<u8> {
+        match self.get_model_code()? {
+            code if code < 4 => Ok(code + DISK_MODEL_CODE_OFFSET),
+            _ => Err(LangError::UnsupportedModelDeclaration),
+        }
+    }
     // TODO(@ohsayan): Completely deprecate the model-code based API
     pub fn get_model_code(&self) -> LangResult<u8> {
         let Self { types, names } = self;
@@ -104,17 +188,35 @@ impl FieldConfig {
             || types.len() != 2
             // the key type cannot be compound
             || types[0].0.len() != 1
-            // the key type cannot be a list
-            || types[0].0[0] == Type::List
-            // the value cannot have a depth more than two
-            || types[1].0.len() > 2
-            // if the value is a string or binary, it cannot have a depth more than 1
-            || ((types[1].0[0] == Type::Binary || types[1].0[0] == Type::String) && types[1].0.len() != 1)
-            // if the value is a list, it must have a depth of two
-            || (types[1].0[0] == Type::List && types[1].0.len() != 2)
-            // if the value is a list, the type argument cannot be a list (it's stupid, I know; that's exactly
-            // why I'll be ditching this API in the next two PRs)
-            || (types[1].0[0] == Type::List && types[1].0[1] == Type::List)
+            // the key type cannot be a list, set, zset or map
+            || types[0].0[0].is_compound()
+            // the key type cannot be an integer, JSON, a bloom filter, an HLL, a geo index or a
+            // time series
+            || matches!(
+                types[0].0[0],
+                Type::Uint64 | Type::Json | Type::Bloom | Type::Hll | Type::Geo | Type::Timeseries
+            )
+            // the value cannot have a depth more than two (three for a map)
+            || types[1].0.len() > 2 + (types[1].0[0] == Type::Map) as usize
+            // if the value is a string, binary or an integer, it cannot have a depth more than 1
+            || (!types[1].0[0].is_compound() && types[1].0.len() != 1)
+            // integers, JSON, bloom filters, HLLs, geo indexes and time series can only be used as
+            // values (and not as type arguments)
+            || types[1].0[1..].contains(&Type::Uint64)
+            || types[1].0[1..].contains(&Type::Json)
+            || types[1].0[1..].contains(&Type::Bloom)
+            || types[1].0[1..].contains(&Type::Hll)
+            || types[1].0[1..].contains(&Type::Geo)
+            || types[1].0[1..].contains(&Type::Timeseries)
+            // if the value is a list, set or zset, it must have a depth of two
+            || (types[1].0[0].is_compound() && types[1].0[0] != Type::Map && types[1].0.len() != 2)
+            // if the value is a map, it must be `map<string, string>` or `map<string, binary>` (the field
+            // names are always strings)
+            || (types[1].0[0] == Type::Map
+                && (types[1].0.len() != 3 || types[1].0[1] != Type::String || types[1].0[2].is_compound()))
+            // if the value is a list, set or zset, the type argument cannot be compound (it's stupid, I
+            // know; that's exactly why I'll be ditching this API in the next two PRs)
+            || (types[1].0[0].is_compound() && types[1].0[1].is_compound())
         };
         if compiler::unlikely(invalid_expr) {
             // the value type cannot have a depth more than 2
@@ -126,6 +228,36 @@ impl FieldConfig {
             let k_enc = key_expr[0] == Type::String;
             let v_enc = value_expr[1] == Type::String;
             Ok(((k_enc as u8) << 1) + (v_enc as u8) + 4)
+        } else if value_expr[0] == Type::Set {
+            let k_enc = key_expr[0] == Type::String;
+            let v_enc = value_expr[1] == Type::String;
+            Ok(((k_enc as u8) << 1) + (v_enc as u8) + 8)
+        } else if value_expr[0] == Type::Zset {
+            let k_enc = key_expr[0] == Type::String;
+            let v_enc = value_expr[1] == Type::String;
+            Ok(((k_enc as u8) << 1) + (v_enc as u8) + 12)
+        } else if value_expr[0] == Type::Map {
+            let k_enc = key_expr[0] == Type::String;
+            let v_enc = value_expr[2] == Type::String;
+            Ok(((k_enc as u8) << 1) + (v_enc as u8) + 16)
+        } else if value_expr[0] == Type::Uint64 {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 20)
+        } else if value_expr[0] == Type::Json {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 22)
+        } else if value_expr[0] == Type::Bloom {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 28)
+        } else if value_expr[0] == Type::Hll {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 30)
+        } else if value_expr[0] == Type::Geo {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 32)
+        } else if value_expr[0] == Type::Timeseries {
+            let k_enc = key_expr[0] == Type::String;
+            Ok(k_enc as u8 + 34)
         } else {
             let k_enc = key_expr[0] == Type::String;
             let v_enc = value_expr[0] == Type::String;
@@ -275,6 +407,8 @@ impl<'a> Compiler<'a> {
             Some(tok) => match tok {
                 Token::Keyword(Keyword::Create) => self.parse_create0(),
                 Token::Keyword(Keyword::Drop) => self.parse_drop0(),
+                Token::Keyword(Keyword::Alter) => self.parse_alter0(),
+                Token::Keyword(Keyword::Rename) => self.parse_rename0(),
                 Token::Keyword(Keyword::Inspect) => self.parse_inspect0(),
                 Token::Keyword(Keyword::Use) => self.parse_use0(),
                 _ => Err(LangError::ExpectedStatement),
@@ -306,21 +440,42 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
-    /// Parse `inspect model <model>`
+    /// Parse `inspect model <model>`. Wildcards can be used to list all the models in a space
+    /// (`<space>.*`) or all the shards of a model (`<space>.<model>.*`)
     fn parse_inspect_model0(&mut self) -> LangResult<Statement> {
-        match self.next() {
-            Some(Token::Identifier(ident)) => Ok(Statement::InspectModel(Some(
-                self.parse_entity_name_with_start(ident)?,
-            ))),
-            Some(_) => Err(LangError::InvalidSyntax),
-            None => Ok(Statement::InspectModel(None)),
+        let start = match self.next() {
+            Some(Token::Identifier(ident)) => ident,
+            Some(_) => return Err(LangError::InvalidSyntax),
+            None => return Ok(Statement::InspectModel(None)),
+        };
+        if !self.next_eq(&Token::Period) {
+            return Ok(Statement::InspectModel(Some(Entity::Current(start))));
+        }
+        if self.next_eq(&Token::Asterisk) {
+            return Ok(Statement::InspectSpace(Some(start)));
+        }
+        let model = self.next_ident()?;
+        if !self.next_eq(&Token::Period) {
+            return Ok(Statement::InspectModel(Some(Entity::Full(start, model))));
+        }
+        if self.next_eq(&Token::Asterisk) {
+            return Ok(Statement::InspectShards(Entity::Full(start, model)));
+        }
+        let shard = self.next_ident()?;
+        if compiler::likely(Entity::is_valid_shard(&model, &shard)) {
+            Ok(Statement::InspectModel(Some(Entity::Shard(
+                start, model, shard,
+            ))))
+        } else {
+            Err(LangError::InvalidSyntax)
         }
     }
     #[inline(always)]
-    /// Parse `inspect space <space>`
+    /// Parse `inspect space <space>`. `inspect space *` lists all the spaces
     fn parse_inspect_space0(&mut self) -> LangResult<Statement> {
         match self.next() {
             Some(Token::Identifier(ident)) => Ok(Statement::InspectSpace(Some(ident))),
+            Some(Token::Asterisk) => Ok(Statement::InspectSpaces),
             Some(_) => Err(LangError::InvalidSyntax),
             None => Ok(Statement::InspectSpace(None)),
         }
@@ -346,6 +501,78 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
+    /// Parse an alter statement
+    fn parse_alter0(&mut self) -> LangResult<Statement> {
+        match self.next_result()? {
+            Token::Keyword(Keyword::Model) => self.parse_alter_model0(),
+            _ => Err(LangError::InvalidSyntax),
+        }
+    }
+    #[inline(always)]
+    /// Parse `alter model <model> with <option> = <value>, ...`, where the options are
+    /// `volatile = <true|false>` and the limits (see [`Self::parse_limit`])
+    fn parse_alter_model0(&mut self) -> LangResult<Statement> {
+        let entity = self.parse_entity_name()?;
+        let mut is_good_expr = self.next_eq(&Token::Keyword(Keyword::With));
+        let mut volatile = None;
+        let mut limits = LimitsDecl::default();
+        loop {
+            if self.next_eq(&Token::Keyword(Keyword::Volatile)) {
+                is_good_expr &= self.next_eq(&Token::Equal);
+                volatile = Some(self.parse_boolean()?);
+            } else {
+                let option = self.next_ident().map_err(|_| LangError::BadExpression)?;
+                is_good_expr &= self.next_eq(&Token::Equal);
+                if !self.parse_limit(&option, &mut limits)? {
+                    return Err(LangError::BadExpression);
+                }
+            }
+            if !self.next_eq(&Token::Comma) {
+                break;
+            }
+        }
+        if compiler::likely(is_good_expr) {
+            Ok(Statement::AlterModel {
+                entity,
+                volatile,
+                limits,
+            })
+        } else {
+            Err(LangError::BadExpression)
+        }
+    }
+    #[inline(always)]
+    /// Parse `rename space <space> <newname>` or `rename model <model> <newname>`
+    fn parse_rename0(&mut self) -> LangResult<Statement> {
+        match self.next_result()? {
+            Token::Keyword(Keyword::Model) => {
+                let entity = self.parse_entity_name()?;
+                let to = self.next_ident()?;
+                let is_valid_name = match &entity {
+                    // a shard is renamed within its model
+                    Entity::Shard(_, model, _) => Entity::is_valid_shard(model, &to),
+                    _ => to.len() < Entity::MAX_LENGTH_EX,
+                };
+                if compiler::likely(is_valid_name) {
+                    Ok(Statement::RenameModel { entity, to })
+                } else {
+                    Err(LangError::BadExpression)
+                }
+            }
+            Token::Keyword(Keyword::Space) => {
+                let (entity, to) = (self.next_ident()?, self.next_ident()?);
+                if compiler::likely(
+                    entity.len() < Entity::MAX_LENGTH_EX && to.len() < Entity::MAX_LENGTH_EX,
+                ) {
+                    Ok(Statement::RenameSpace { entity, to })
+                } else {
+                    Err(LangError::BadExpression)
+                }
+            }
+            _ => Err(LangError::InvalidSyntax),
+        }
+    }
+    #[inline(always)]
     /// Parse a create statement
     fn parse_create0(&mut self) -> LangResult<Statement> {
         match self.next() {
@@ -362,8 +589,127 @@ impl<'a> Compiler<'a> {
         self.parse_create_model1(entity)
     }
     #[inline(always)]
-    /// Parse a field expression and return a `Statement::CreateModel`
+    /// Parse a field expression (if any), the volatility and the options (if any) and return a
+    /// `Statement::CreateModel`
     pub(super) fn parse_create_model1(&mut self, entity: Entity) -> LangResult<Statement> {
+        let model = if self.peek_eq(&Token::OpenParen) {
+            self.parse_field_config()?
+        } else {
+            // no fields; we'll use the defaults of the space
+            FieldConfig::new()
+        };
+        let mut volatile = self
+            .next_eq(&Token::Keyword(Keyword::Volatile))
+            .then_some(true);
+        let mut compressed = false;
+        let mut disk = false;
+        let mut limits = LimitsDecl::default();
+        if self.next_eq(&Token::Keyword(Keyword::With)) {
+            // options: `volatile = <true|false>`, `compression = <lz4|none>`,
+            // `storage = <memory|disk>` and the limits
+            loop {
+                if volatile.is_none() && self.next_eq(&Token::Keyword(Keyword::Volatile)) {
+                    if compiler::unlikely(!self.next_eq(&Token::Equal)) {
+                        return Err(LangError::BadExpression);
+                    }
+                    volatile = Some(self.parse_boolean()?);
+                    if !self.next_eq(&Token::Comma) {
+                        break;
+                    }
+                    continue;
+                }
+                let option = self.next_ident()?;
+                if compiler::unlikely(!self.next_eq(&Token::Equal)) {
+                    return Err(LangError::BadExpression);
+                }
+                if unsafe { option.as_slice() }.eq_ignore_ascii_case(b"compression") {
+                    compressed = self.parse_compression()?;
+                } else if unsafe { option.as_slice() }.eq_ignore_ascii_case(b"storage") {
+                    disk = self.parse_storage()?;
+                } else if !self.parse_limit(&option, &mut limits)? {
+                    return Err(LangError::BadExpression);
+                }
+                if !self.next_eq(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+        if compressed && disk {
+            // the values on disk aren't compressed
+            return Err(LangError::UnsupportedModelDeclaration);
+        }
+        Ok(Statement::CreateModel {
+            entity,
+            model,
+            volatile,
+            compressed,
+            disk,
+            limits,
+        })
+    }
+    #[inline(always)]
+    /// Parse the algorithm of a compression option (`compression = <lz4|none>`), returning true
+    /// if the values are to be compressed
+    fn parse_compression(&mut self) -> LangResult<bool> {
+        let algorithm = self.next_ident()?;
+        match unsafe { algorithm.as_slice() } {
+            algorithm if algorithm.eq_ignore_ascii_case(b"lz4") => Ok(true),
+            algorithm if algorithm.eq_ignore_ascii_case(b"none") => Ok(false),
+            _ => Err(LangError::BadExpression),
+        }
+    }
+    #[inline(always)]
+    /// Parse the engine of a storage option (`storage = <memory|disk>`), returning true if the
+    /// values are to be kept on disk
+    fn parse_storage(&mut self) -> LangResult<bool> {
+        let engine = self.next_ident()?;
+        match unsafe { engine.as_slice() } {
+            engine if engine.eq_ignore_ascii_case(b"disk") => Ok(true),
+            engine if engine.eq_ignore_ascii_case(b"memory") => Ok(false),
+            _ => Err(LangError::BadExpression),
+        }
+    }
+    /// Parse the value of a limit option (`max_key_size = <bytes>`, `max_value_size = <bytes>`,
+    /// `max_memory = <bytes>`, `eviction = <lru|lfu|random|none>` or `durability =
+    /// <default|none|always|everysec|millis>`) into `limits`. Returns false if `option` isn't a
+    /// limit
+    fn parse_limit(&mut self, option: &RawSlice, limits: &mut LimitsDecl) -> LangResult<bool> {
+        let limit = match unsafe { option.as_slice() } {
+            option if option.eq_ignore_ascii_case(b"max_key_size") => &mut limits.max_key_size,
+            option if option.eq_ignore_ascii_case(b"max_value_size") => &mut limits.max_value_size,
+            option if option.eq_ignore_ascii_case(b"max_memory") => &mut limits.max_memory,
+            option if option.eq_ignore_ascii_case(b"eviction") => {
+                let policy = self.next_ident().map_err(|_| LangError::BadExpression)?;
+                limits.eviction = Some(
+                    EvictionPolicy::from_name(unsafe { policy.as_slice() })
+                        .ok_or(LangError::BadExpression)?,
+                );
+                return Ok(true);
+            }
+            option if option.eq_ignore_ascii_case(b"durability") => {
+                limits.durability = Some(match self.next() {
+                    Some(Token::Identifier(durability)) => {
+                        Durability::from_name(unsafe { durability.as_slice() })
+                            .ok_or(LangError::BadExpression)?
+                    }
+                    Some(Token::Number(ms)) if ms != 0 => Durability::Fsync(WalFsync::Every(ms)),
+                    _ => return Err(LangError::BadExpression),
+                });
+                return Ok(true);
+            }
+            _ => return Ok(false),
+        };
+        match self.next() {
+            Some(Token::Number(bytes)) => {
+                *limit = Some(bytes);
+                Ok(true)
+            }
+            _ => Err(LangError::BadExpression),
+        }
+    }
+    #[inline(always)]
+    /// Parse a parenthesized field expression and return a `FieldConfig`
+    fn parse_field_config(&mut self) -> LangResult<FieldConfig> {
         let mut fc = FieldConfig::new();
         let mut is_good_expr = self.next_eq(&Token::OpenParen);
         while is_good_expr && self.peek_neq(&Token::CloseParen) {
@@ -394,18 +740,28 @@ impl<'a> Compiler<'a> {
         // without introducing some funky naming conventions ($<field_number> if you don't have the
         // right name sounds like an outrageous idea)
         is_good_expr &= fc.names.is_empty() || fc.names.len() == fc.types.len();
-        let volatile = self.next_eq(&Token::Keyword(Keyword::Volatile));
         if compiler::likely(is_good_expr) {
-            Ok(Statement::CreateModel {
-                entity,
-                model: fc,
-                volatile,
-            })
+            Ok(fc)
         } else {
             Err(LangError::BadExpression)
         }
     }
     #[inline(always)]
+    /// Parse a boolean literal (`true` or `false`)
+    fn parse_boolean(&mut self) -> LangResult<bool> {
+        match self.next() {
+            Some(Token::Identifier(v)) if unsafe { v.as_slice() }.eq_ignore_ascii_case(b"true") => {
+                Ok(true)
+            }
+            Some(Token::Identifier(v))
+                if unsafe { v.as_slice() }.eq_ignore_ascii_case(b"false") =>
+            {
+                Ok(false)
+            }
+            _ => Err(LangError::BadExpression),
+        }
+    }
+    #[inline(always)]
     /// Parse a type expression return a `TypeExpression`
     fn parse_type_expression(&mut self, first_type: Type) -> LangResult<TypeExpression> {
         let mut expr = Vec::with_capacity(2);
@@ -451,19 +807,50 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
-    /// Parse a `create space` statement
+    /// Parse a `create space` statement, optionally followed by the defaults for the models
+    /// in the space: `with model = (<fields>), volatile = <true|false>`
     fn parse_create_space0(&mut self) -> LangResult<Statement> {
-        match self.next() {
-            Some(Token::Identifier(model_name)) => Ok(Statement::CreateSpace(model_name)),
-            Some(_) => Err(LangError::InvalidSyntax),
-            None => Err(LangError::UnexpectedEOF),
+        let entity = match self.next() {
+            Some(Token::Identifier(space_name)) => space_name,
+            Some(_) => return Err(LangError::InvalidSyntax),
+            None => return Err(LangError::UnexpectedEOF),
+        };
+        let mut model = FieldConfig::new();
+        let mut volatile = None;
+        if self.next_eq(&Token::Keyword(Keyword::With)) {
+            let mut is_good_expr = true;
+            while is_good_expr {
+                match self.next() {
+                    Some(Token::Keyword(Keyword::Model)) if model.types.is_empty() => {
+                        is_good_expr &= self.next_eq(&Token::Equal);
+                        model = self.parse_field_config()?;
+                    }
+                    Some(Token::Keyword(Keyword::Volatile)) if volatile.is_none() => {
+                        is_good_expr &= self.next_eq(&Token::Equal);
+                        volatile = Some(self.parse_boolean()?);
+                    }
+                    _ => is_good_expr = false,
+                }
+                if !self.next_eq(&Token::Comma) {
+                    break;
+                }
+            }
+            if compiler::unlikely(!is_good_expr) {
+                return Err(LangError::BadExpression);
+            }
         }
+        Ok(Statement::CreateSpace {
+            entity,
+            model,
+            volatile: volatile.unwrap_or(false),
+        })
     }
     #[inline(always)]
     fn parse_entity_name_with_start(&mut self, start: RawSlice) -> LangResult<Entity> {
         if self.peek_eq(&Token::Period) {
             unsafe { self.incr_cursor() };
-            Ok(Entity::Full(start, self.next_ident()?))
+            let model = self.next_ident()?;
+            self.parse_shard_name(start, model)
         } else {
             Ok(Entity::Current(start))
         }
@@ -476,10 +863,24 @@ impl<'a> Compiler<'a> {
                 && compiler::likely(id.len() < Entity::MAX_LENGTH_EX) =>
             {
                 unsafe { self.incr_cursor() };
-                Ok(Entity::Full(id, self.next_ident()?))
+                let model = self.next_ident()?;
+                self.parse_shard_name(id, model)
             }
             id if compiler::likely(id.len() < Entity::MAX_LENGTH_EX) => Ok(Entity::Current(id)),
             _ => Err(LangError::InvalidSyntax),
         }
     }
+    #[inline(always)]
+    /// Parse the (optional) shard that follows `<space>.<model>`
+    fn parse_shard_name(&mut self, space: RawSlice, model: RawSlice) -> LangResult<Entity> {
+        if !self.next_eq(&Token::Period) {
+            return Ok(Entity::Full(space, model));
+        }
+        let shard = self.next_ident()?;
+        if compiler::likely(Entity::is_valid_shard(&model, &shard)) {
+            Ok(Entity::Shard(space, model, shard))
+        } else {
+            Err(LangError::InvalidSyntax)
+        }
+    }
 }
@@ -28,14 +28,19 @@ use crate::dbnet::BufferedSocketStream;
 
 use {
     super::{
-        ast::{Statement, StatementLT},
+        ast::{Entity, Statement, StatementLT},
         error,
     },
     crate::{
         actions::{self, ActionError, ActionResult},
+        auth::acl,
         blueql,
-        corestore::memstore::ObjectID,
+        corestore::{
+            memstore::{KeyspaceDefaults, ObjectID},
+            table::TableDescription,
+        },
         dbnet::prelude::*,
+        queryengine, IoResult,
     },
 };
 
@@ -44,6 +49,7 @@ pub async fn execute<'a, P, C>(
     con: &mut Connection<C, P>,
     maybe_statement: &[u8],
     extra: usize,
+    grants: Option<&[u8]>,
 ) -> ActionResult<()>
 where
     P: ProtocolSpec,
@@ -51,12 +57,34 @@ where
 {
     let statement =
         error::map_ql_err_to_resp::<StatementLT, P>(blueql::compile(maybe_statement, extra))?;
+    if let Some(grants) = grants {
+        self::check_statement::<P>(handle, grants, statement.as_ref())?;
+    }
+    if let Some(rstring) = queryengine::rejects_writes(con) {
+        if !self::is_read_statement(statement.as_ref()) {
+            // the schema can't be changed while the server (or the replica) is read-only
+            return util::err(rstring);
+        }
+    }
     let system_health_okay = registry::state_okay();
     let result = match statement.as_ref() {
         Statement::Use(entity) => handle.swap_entity(entity),
-        Statement::CreateSpace(space_name) if system_health_okay => {
-            // ret okay
-            handle.create_keyspace(unsafe { ObjectID::from_slice(space_name.as_slice()) })
+        Statement::CreateSpace {
+            entity,
+            model,
+            volatile,
+        } if system_health_okay => {
+            match model.get_model_code_if_declared() {
+                // ret okay
+                Ok(model_code) => handle.create_keyspace(
+                    unsafe { ObjectID::from_slice(entity.as_slice()) },
+                    KeyspaceDefaults {
+                        model_code,
+                        volatile: *volatile,
+                    },
+                ),
+                Err(e) => return Err(ActionError::ActionError(error::cold_err::<P>(e))),
+            }
         }
         Statement::DropSpace { entity, force } if system_health_okay => {
             // ret okay
@@ -71,14 +99,49 @@ where
             // ret okay
             handle.drop_table(entity, *force)
         }
+        Statement::AlterModel {
+            entity,
+            volatile,
+            limits,
+        } if system_health_okay => {
+            // ret okay
+            handle.alter_table(entity, *volatile, limits)
+        }
+        Statement::RenameSpace { entity, to } if system_health_okay => {
+            // ret okay
+            handle.rename_keyspace(
+                unsafe { &ObjectID::from_slice(entity.as_slice()) },
+                unsafe { ObjectID::from_slice(to.as_slice()) },
+            )
+        }
+        Statement::RenameModel { entity, to } if system_health_okay => {
+            // ret okay
+            handle.rename_table(entity, unsafe { ObjectID::from_slice(to.as_slice()) })
+        }
         Statement::CreateModel {
             entity,
             model,
             volatile,
+            compressed,
+            disk,
+            limits,
         } if system_health_okay => {
-            match model.get_model_code() {
+            let code = if *compressed {
+                model.get_compressed_model_code().map(Some)
+            } else if *disk {
+                model.get_disk_model_code().map(Some)
+            } else {
+                model.get_model_code_if_declared()
+            };
+            match code {
                 // ret okay
-                Ok(code) => handle.create_table(entity, code, *volatile),
+                Ok(code) => handle.create_table(entity, code, *volatile).and_then(|_| {
+                    if limits.is_empty() {
+                        Ok(())
+                    } else {
+                        handle.alter_table(entity, None, limits)
+                    }
+                }),
                 Err(e) => return Err(ActionError::ActionError(error::cold_err::<P>(e))),
             }
         }
@@ -89,18 +152,28 @@ where
             return Ok(());
         }
         Statement::InspectSpace(space) => {
-            // ret directly
-            con.write_typed_non_null_array(
-                handle.list_tables::<P>(space.as_ref().map(|v| unsafe { v.as_slice() }))?,
-                b'+',
-            )
-            .await?;
+            // ret directly: an array with the description of every model
+            let models =
+                handle.describe_tables::<P>(space.as_ref().map(|v| unsafe { v.as_slice() }))?;
+            con.write_array_header(models.len()).await?;
+            for (name, description) in models.iter() {
+                write_model_description(con, Some(name.as_ref()), description).await?;
+            }
+            return Ok(());
+        }
+        Statement::InspectShards(model) => {
+            // ret directly: an array with the description of every shard
+            let shards = handle.describe_shards::<P>(model)?;
+            con.write_array_header(shards.len()).await?;
+            for (name, description) in shards.iter() {
+                write_model_description(con, Some(name.as_ref()), description).await?;
+            }
             return Ok(());
         }
         Statement::InspectModel(model) => {
             // ret directly
-            con.write_string(&handle.describe_table::<P>(model)?)
-                .await?;
+            let description = handle.describe_table::<P>(model)?;
+            write_model_description(con, None, &description).await?;
             return Ok(());
         }
         _ => {
@@ -113,3 +186,129 @@ where
     con._write_raw(P::RCODE_OKAY).await?;
     Ok(())
 }
+
+/// Returns true if the statement leaves the schema alone (and hence can be run while the
+/// server is read-only)
+fn is_read_statement(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Use(_)
+            | Statement::InspectSpaces
+            | Statement::InspectSpace(_)
+            | Statement::InspectShards(_)
+            | Statement::InspectModel(_)
+    )
+}
+
+/// Check that the grants of a restricted user allow the statement (see [`acl`]). A user can
+/// switch to any keyspace or table that it was granted something on, and needs the DDL
+/// permission on a keyspace or table to create, alter, rename or drop it. Inspecting is
+/// always allowed
+fn check_statement<P: ProtocolSpec>(
+    handle: &Corestore,
+    grants: &[u8],
+    statement: &Statement,
+) -> ActionResult<()> {
+    let allowed = unsafe {
+        // UNSAFE(@ohsayan): The statement borrows from the query, which outlives this check
+        match statement {
+            Statement::Use(Entity::Current(ks)) => acl::allows_any(grants, ks.as_slice(), None),
+            Statement::Use(Entity::Full(ks, tbl) | Entity::Shard(ks, tbl, _)) => {
+                acl::allows_any(grants, ks.as_slice(), Some(tbl.as_slice()))
+            }
+            Statement::CreateSpace { entity, .. }
+            | Statement::DropSpace { entity, .. }
+            | Statement::RenameSpace { entity, .. } => {
+                acl::allows(grants, entity.as_slice(), None, acl::PERM_DDL)
+            }
+            Statement::CreateModel { entity, .. }
+            | Statement::DropModel { entity, .. }
+            | Statement::AlterModel { entity, .. }
+            | Statement::RenameModel { entity, .. } => match acl::resolve(handle, entity) {
+                Some((ks, tbl)) => acl::allows(grants, ks, Some(tbl), acl::PERM_DDL),
+                // no space to resolve the model in, so let the statement report it
+                None => true,
+            },
+            _ => true,
+        }
+    };
+    if allowed {
+        Ok(())
+    } else {
+        util::err(P::AUTH_CODE_PERMS)
+    }
+}
+
+/// Write the description of a model as a flat array of field/value pairs. The fields are:
+/// - `name` (only if provided): the name of the model
+/// - `model`: the data model, which is always `keymap`
+/// - `data`: the data declaration, for example `(str,str)`
+/// - `volatile`: `true` or `false`
+/// - `entries`: the number of entries (int)
+/// - `memory`: the approximate memory usage in bytes (int)
+/// - `created`: the creation time as a UNIX timestamp in milliseconds (int)
+/// - `compression`: `lz4` or `none`
+/// - `max_key_size`: the maximum size of a key in bytes, zero if there's no limit (int)
+/// - `max_value_size`: the maximum size of a value in bytes, zero if there's no limit (int)
+/// - `max_memory`: the memory cap in bytes, zero if there's no cap (int)
+/// - `eviction`: the eviction policy, which is `lru`, `lfu`, `random` or `none`
+/// - `evicted`: the number of keys that were evicted (int)
+/// - `durability`: how the writes are logged, which is `default`, `none`, `always`, `<N>ms` or
+/// `no` (see [`crate::storage::v1::wal`])
+/// - `storage`: where the values are kept, which is `memory` or `disk`
+async fn write_model_description<P, C>(
+    con: &mut Connection<C, P>,
+    name: Option<&[u8]>,
+    description: &TableDescription,
+) -> IoResult<()>
+where
+    P: ProtocolSpec,
+    C: BufferedSocketStream,
+{
+    con.write_flat_array_header(if name.is_some() { 30 } else { 28 })
+        .await?;
+    if let Some(name) = name {
+        con.write_string("name").await?;
+        con.write_mono_length_prefixed_with_tsymbol(name, P::TSYMBOL_STRING)
+            .await?;
+    }
+    con.write_string("model").await?;
+    con.write_string("keymap").await?;
+    con.write_string("data").await?;
+    con.write_string(description.data).await?;
+    con.write_string("volatile").await?;
+    con.write_string(if description.volatile {
+        "true"
+    } else {
+        "false"
+    })
+    .await?;
+    con.write_string("entries").await?;
+    con.write_usize(description.entries).await?;
+    con.write_string("memory").await?;
+    con.write_usize(description.memory).await?;
+    con.write_string("created").await?;
+    con.write_int64(description.created).await?;
+    con.write_string("compression").await?;
+    con.write_string(if description.compressed {
+        "lz4"
+    } else {
+        "none"
+    })
+    .await?;
+    con.write_string("max_key_size").await?;
+    con.write_usize(description.max_key_size).await?;
+    con.write_string("max_value_size").await?;
+    con.write_usize(description.max_value_size).await?;
+    con.write_string("max_memory").await?;
+    con.write_usize(description.max_memory).await?;
+    con.write_string("eviction").await?;
+    con.write_string(description.eviction.name()).await?;
+    con.write_string("evicted").await?;
+    con.write_int64(description.evicted).await?;
+    con.write_string("durability").await?;
+    con.write_string(&description.durability.to_string())
+        .await?;
+    con.write_string("storage").await?;
+    con.write_string(description.storage).await
+}
@@ -44,6 +44,8 @@ pub enum Token {
     Comma,        // ,
     Colon,        // :
     Period,       // .
+    Equal,        // =
+    Asterisk,     // *
     QuotedString(String),
     Identifier(RawSlice),
     Number(u64),
@@ -82,6 +84,9 @@ pub enum Keyword {
     Create,
     Use,
     Drop,
+    Alter,
+    With,
+    Rename,
     Inspect,
     Model,
     Space,
@@ -97,6 +102,23 @@ pub enum Type {
     String,
     Binary,
     List,
+    Set,
+    Zset,
+    Map,
+    Uint64,
+    Json,
+    Bloom,
+    Hll,
+    Geo,
+    Timeseries,
+}
+
+impl Type {
+    /// Returns true if this type takes type arguments (`list<...>`, `set<...>`, `zset<...>`,
+    /// `map<..., ...>`)
+    pub const fn is_compound(&self) -> bool {
+        matches!(self, Type::List | Type::Set | Type::Zset | Type::Map)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -110,6 +132,9 @@ impl Keyword {
         let r = match slice.to_ascii_lowercase().as_slice() {
             b"create" => Keyword::Create,
             b"drop" => Keyword::Drop,
+            b"alter" => Keyword::Alter,
+            b"with" => Keyword::With,
+            b"rename" => Keyword::Rename,
             b"inspect" => Keyword::Inspect,
             b"model" => Keyword::Model,
             b"space" => Keyword::Space,
@@ -117,6 +142,15 @@ impl Keyword {
             b"string" => Keyword::Type(Type::String),
             b"binary" => Keyword::Type(Type::Binary),
             b"list" => Keyword::Type(Type::List),
+            b"set" => Keyword::Type(Type::Set),
+            b"zset" => Keyword::Type(Type::Zset),
+            b"map" => Keyword::Type(Type::Map),
+            b"u64" => Keyword::Type(Type::Uint64),
+            b"json" => Keyword::Type(Type::Json),
+            b"bloom" => Keyword::Type(Type::Bloom),
+            b"hll" => Keyword::Type(Type::Hll),
+            b"geo" => Keyword::Type(Type::Geo),
+            b"timeseries" => Keyword::Type(Type::Timeseries),
             b"force" => Keyword::Force,
             b"use" => Keyword::Use,
             _ => return None,
@@ -351,6 +385,8 @@ impl<'a> Lexer<'a> {
             b',' => Token::Comma,
             b':' => Token::Colon,
             b'.' => Token::Period,
+            b'=' => Token::Equal,
+            b'*' => Token::Asterisk,
             _ => {
                 self.last_error = Some(LangError::UnexpectedChar);
                 return;
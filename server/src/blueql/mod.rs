@@ -37,7 +37,10 @@ use {
     self::{ast::Statement, error::LangResult},
     crate::util::Life,
 };
-pub use {ast::Compiler, ast::Entity, executor::execute};
+pub use {
+    ast::{Compiler, Entity, LimitsDecl},
+    executor::execute,
+};
 
 #[cfg(test)]
 use core::fmt;
@@ -24,10 +24,13 @@
  *
 */
 
-use super::{
-    ast::{Compiler, Entity, FieldConfig, Statement},
-    error::LangError,
-    lexer::{Keyword, Lexer, Token, Type, TypeExpression},
+use {
+    super::{
+        ast::{Compiler, Entity, FieldConfig, LimitsDecl, Statement},
+        error::LangError,
+        lexer::{Keyword, Lexer, Token, Type, TypeExpression},
+    },
+    crate::{config::WalFsync, kvengine::eviction::EvictionPolicy, storage::v1::wal::Durability},
 };
 
 macro_rules! src {
@@ -126,11 +129,19 @@ mod lexer {
         )
     }
 
+    #[test]
+    fn lex_wildcard() {
+        assert_eq!(
+            Lexer::lex(b"twitter.*").unwrap(),
+            vec![Token::from("twitter"), Token::Period, Token::Asterisk]
+        )
+    }
+
     #[test]
     fn lex_fail_unknown_chars() {
         const SOURCES: &[&[u8]] = &[
-            b"!", b"@", b"#", b"$", b"%", b"^", b"&", b"*", b"[", b"]", b"{", b"}", b"|", b"\\",
-            b"/", b"~", b"`", b";", b"hello?",
+            b"!", b"@", b"#", b"$", b"%", b"^", b"&", b"[", b"]", b"{", b"}", b"|", b"\\", b"/",
+            b"~", b"`", b";", b"hello?",
         ];
         for source in SOURCES {
             assert_eq!(Lexer::lex(source).unwrap_err(), LangError::UnexpectedChar);
@@ -195,6 +206,27 @@ mod ast {
                 .unwrap(),
             Entity::Full("hello".into(), "world".into())
         );
+        assert_eq!(
+            Compiler::new(&Lexer::lex(b"hello.world.eu").unwrap())
+                .parse_entity_name()
+                .unwrap(),
+            Entity::Shard("hello".into(), "world".into(), "eu".into())
+        );
+    }
+    #[test]
+    fn parse_entity_name_shard_too_long() {
+        // `<model>.<shard>` has to fit in 64 bytes
+        let src = format!("hello.{}.{}", "a".repeat(32), "b".repeat(32));
+        assert_eq!(
+            Compiler::new(&Lexer::lex(src.as_bytes()).unwrap())
+                .parse_entity_name()
+                .unwrap_err(),
+            LangError::InvalidSyntax
+        );
+        let src = format!("hello.{}.{}", "a".repeat(32), "b".repeat(31));
+        assert!(Compiler::new(&Lexer::lex(src.as_bytes()).unwrap())
+            .parse_entity_name()
+            .is_ok());
     }
 
     use super::*;
@@ -213,7 +245,10 @@ mod ast {
                 ],
                 names: vec!["username".into(), "password".into(), "posts".into()],
             },
-            volatile: true,
+            volatile: Some(true),
+            compressed: false,
+            disk: false,
+            limits: LimitsDecl::default(),
         };
         (src, stmt)
     }
@@ -238,11 +273,391 @@ mod ast {
                     TypeExpression(vec![Type::Binary]),
                 ],
             },
-            volatile: false,
+            volatile: None,
+            compressed: false,
+            disk: false,
+            limits: LimitsDecl::default(),
         };
         assert_eq!(Compiler::compile(&src).unwrap(), expected);
     }
     #[test]
+    fn stmt_create_model_without_fields() {
+        assert_eq!(
+            Compiler::compile(b"create model twitter.tweet volatile").unwrap(),
+            Statement::CreateModel {
+                entity: Entity::Full("twitter".into(), "tweet".into()),
+                model: FieldConfig::new(),
+                volatile: Some(true),
+                compressed: false,
+                disk: false,
+                limits: LimitsDecl::default(),
+            }
+        );
+    }
+    #[test]
+    fn stmt_create_model_compressed() {
+        assert_eq!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with compression = lz4"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                entity: Entity::Full("twitter".into(), "tweets".into()),
+                model: FieldConfig {
+                    names: vec![],
+                    types: vec![
+                        TypeExpression(vec![Type::String]),
+                        TypeExpression(vec![Type::String]),
+                    ],
+                },
+                volatile: None,
+                compressed: true,
+                disk: false,
+                limits: LimitsDecl::default(),
+            }
+        );
+        assert!(matches!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) volatile with compression = none"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                volatile: Some(true),
+                compressed: false,
+                ..
+            }
+        ));
+        assert_eq!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with compression = zip"
+            )
+            .unwrap_err(),
+            LangError::BadExpression
+        );
+        assert_eq!(
+            Compiler::compile(b"create model twitter.tweets(string, string) with compression")
+                .unwrap_err(),
+            LangError::BadExpression
+        );
+    }
+    #[test]
+    fn stmt_create_model_explicit_volatility() {
+        assert!(matches!(
+            Compiler::compile(b"create model twitter.tweets(string, string) with volatile = false")
+                .unwrap(),
+            Statement::CreateModel {
+                volatile: Some(false),
+                ..
+            }
+        ));
+        assert!(matches!(
+            Compiler::compile(
+                b"create model twitter.tweets with compression = lz4, volatile = true"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                volatile: Some(true),
+                compressed: true,
+                ..
+            }
+        ));
+        for bad in [
+            "create model twitter.tweets volatile with volatile = false",
+            "create model twitter.tweets with volatile = false, volatile = true",
+            "create model twitter.tweets with volatile false",
+        ] {
+            assert!(Compiler::compile(bad.as_bytes()).is_err(), "{bad}");
+        }
+    }
+    #[test]
+    fn stmt_create_model_disk() {
+        assert_eq!(
+            Compiler::compile(b"create model twitter.tweets(string, string) with storage = disk")
+                .unwrap(),
+            Statement::CreateModel {
+                entity: Entity::Full("twitter".into(), "tweets".into()),
+                model: FieldConfig {
+                    names: vec![],
+                    types: vec![
+                        TypeExpression(vec![Type::String]),
+                        TypeExpression(vec![Type::String]),
+                    ],
+                },
+                volatile: None,
+                compressed: false,
+                disk: true,
+                limits: LimitsDecl::default(),
+            }
+        );
+        assert!(matches!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with storage = memory, max_value_size = 10"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                disk: false,
+                limits: LimitsDecl {
+                    max_value_size: Some(10),
+                    ..
+                },
+                ..
+            }
+        ));
+        assert_eq!(
+            Compiler::compile(b"create model twitter.tweets(string, string) with storage = tape")
+                .unwrap_err(),
+            LangError::BadExpression
+        );
+        assert_eq!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with storage = disk, compression = lz4"
+            )
+            .unwrap_err(),
+            LangError::UnsupportedModelDeclaration
+        );
+    }
+    #[test]
+    fn stmt_create_model_with_limits() {
+        assert_eq!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with max_key_size = 64, max_value_size = 1024"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                entity: Entity::Full("twitter".into(), "tweets".into()),
+                model: FieldConfig {
+                    names: vec![],
+                    types: vec![
+                        TypeExpression(vec![Type::String]),
+                        TypeExpression(vec![Type::String]),
+                    ],
+                },
+                volatile: None,
+                compressed: false,
+                disk: false,
+                limits: LimitsDecl {
+                    max_key_size: Some(64),
+                    max_value_size: Some(1024),
+                    max_memory: None,
+                    eviction: None,
+                    durability: None,
+                },
+            }
+        );
+        assert!(matches!(
+            Compiler::compile(
+                b"create model twitter.tweets(string, string) with compression = lz4, max_value_size = 10"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                compressed: true,
+                limits: LimitsDecl {
+                    max_key_size: None,
+                    max_value_size: Some(10),
+                    ..
+                },
+                ..
+            }
+        ));
+        src!(
+            SOURCES,
+            "create model twitter.tweets(string, string) with max_value_size = big",
+            "create model twitter.tweets(string, string) with max_value_size 10",
+            "create model twitter.tweets(string, string) with max_size = 10",
+            "create model twitter.tweets(string, string) with max_key_size = 10,",
+        );
+        for src in SOURCES {
+            assert!(Compiler::compile(src).is_err());
+        }
+    }
+    #[test]
+    fn stmt_create_model_with_durability() {
+        for (durability, expected) in [
+            ("default", Durability::Default),
+            ("NONE", Durability::None),
+            ("always", Durability::Fsync(WalFsync::Always)),
+            ("everysec", Durability::Fsync(WalFsync::Every(1000))),
+            ("250", Durability::Fsync(WalFsync::Every(250))),
+        ] {
+            let src = format!(
+                "create model twitter.tweets(string, string) with durability = {durability}"
+            );
+            assert!(matches!(
+                Compiler::compile(src.as_bytes()).unwrap(),
+                Statement::CreateModel {
+                    limits: LimitsDecl { durability: Some(parsed), .. },
+                    ..
+                } if parsed == expected
+            ));
+        }
+        src!(
+            SOURCES,
+            "create model twitter.tweets(string, string) with durability = sometimes",
+            "create model twitter.tweets(string, string) with durability = 0",
+            "create model twitter.tweets(string, string) with durability",
+        );
+        for src in SOURCES {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::BadExpression
+            );
+        }
+    }
+    #[test]
+    fn stmt_create_model_with_eviction() {
+        assert_eq!(
+            Compiler::compile(
+                b"create model cache.pages(string, binary) volatile with max_memory = 1048576, eviction = LRU"
+            )
+            .unwrap(),
+            Statement::CreateModel {
+                entity: Entity::Full("cache".into(), "pages".into()),
+                model: FieldConfig {
+                    names: vec![],
+                    types: vec![
+                        TypeExpression(vec![Type::String]),
+                        TypeExpression(vec![Type::Binary]),
+                    ],
+                },
+                volatile: Some(true),
+                compressed: false,
+                disk: false,
+                limits: LimitsDecl {
+                    max_key_size: None,
+                    max_value_size: None,
+                    max_memory: Some(1048576),
+                    eviction: Some(EvictionPolicy::Lru),
+                    durability: None,
+                },
+            }
+        );
+        for (policy, expected) in [
+            ("lfu", EvictionPolicy::Lfu),
+            ("random", EvictionPolicy::Random),
+            ("none", EvictionPolicy::None),
+        ] {
+            let src = format!(
+                "create model cache.pages(string, binary) volatile with eviction = {policy}"
+            );
+            assert!(matches!(
+                Compiler::compile(src.as_bytes()).unwrap(),
+                Statement::CreateModel {
+                    limits: LimitsDecl { eviction: Some(parsed), .. },
+                    ..
+                } if parsed == expected
+            ));
+        }
+        assert_eq!(
+            Compiler::compile(
+                b"create model cache.pages(string, binary) volatile with eviction = fifo"
+            )
+            .unwrap_err(),
+            LangError::BadExpression
+        );
+    }
+    #[test]
+    fn compressed_model_code() {
+        let get_code = |src: &[u8]| match Compiler::compile(src).unwrap() {
+            Statement::CreateModel { model, .. } => model.get_compressed_model_code(),
+            x => panic!("Expected model found {:?}", x),
+        };
+        assert_eq!(get_code(b"create model a(binary, binary)"), Ok(24));
+        assert_eq!(get_code(b"create model a(binary, string)"), Ok(25));
+        assert_eq!(get_code(b"create model a(string, string)"), Ok(26));
+        assert_eq!(get_code(b"create model a(string, binary)"), Ok(27));
+        assert_eq!(
+            get_code(b"create model a(string, list<string>)"),
+            Err(LangError::UnsupportedModelDeclaration)
+        );
+        assert_eq!(
+            get_code(b"create model a"),
+            Err(LangError::UnsupportedModelDeclaration)
+        );
+    }
+    #[test]
+    fn disk_model_code() {
+        let get_code = |src: &[u8]| match Compiler::compile(src).unwrap() {
+            Statement::CreateModel { model, .. } => model.get_disk_model_code(),
+            x => panic!("Expected model found {:?}", x),
+        };
+        assert_eq!(get_code(b"create model a(binary, binary)"), Ok(36));
+        assert_eq!(get_code(b"create model a(binary, string)"), Ok(37));
+        assert_eq!(get_code(b"create model a(string, string)"), Ok(38));
+        assert_eq!(get_code(b"create model a(string, binary)"), Ok(39));
+        assert_eq!(
+            get_code(b"create model a(string, list<string>)"),
+            Err(LangError::UnsupportedModelDeclaration)
+        );
+    }
+    #[test]
+    fn stmt_create_space() {
+        assert_eq!(
+            Compiler::compile(b"create space twitter").unwrap(),
+            Statement::CreateSpace {
+                entity: "twitter".into(),
+                model: FieldConfig::new(),
+                volatile: false,
+            }
+        );
+    }
+    #[test]
+    fn stmt_create_space_with_defaults() {
+        let expected = Statement::CreateSpace {
+            entity: "twitter".into(),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::List, Type::String]),
+                ],
+            },
+            volatile: true,
+        };
+        assert_eq!(
+            Compiler::compile(
+                b"create space twitter with model = (string, list<string>), volatile = true"
+            )
+            .unwrap(),
+            expected
+        );
+        // order doesn't matter
+        assert_eq!(
+            Compiler::compile(
+                b"create space twitter with volatile = true, model = (string, list<string>)"
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            Compiler::compile(b"create space twitter with volatile = true").unwrap(),
+            Statement::CreateSpace {
+                entity: "twitter".into(),
+                model: FieldConfig::new(),
+                volatile: true,
+            }
+        );
+    }
+    #[test]
+    fn stmt_create_space_bad_defaults() {
+        src!(
+            SOURCES,
+            "create space twitter with",
+            "create space twitter with model (string, string)",
+            "create space twitter with model = string",
+            "create space twitter with volatile = yes",
+            "create space twitter with volatile = true, volatile = false",
+            "create space twitter with force = true",
+        );
+        for src in SOURCES {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::BadExpression,
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    #[test]
     fn stmt_drop_space() {
         assert_eq!(
             Compiler::compile(b"drop space twitter force").unwrap(),
@@ -263,6 +678,121 @@ mod ast {
         );
     }
     #[test]
+    fn stmt_alter_model() {
+        assert_eq!(
+            Compiler::compile(b"alter model twitter.tweet with volatile = true").unwrap(),
+            Statement::AlterModel {
+                entity: Entity::Full("twitter".into(), "tweet".into()),
+                volatile: Some(true),
+                limits: LimitsDecl::default(),
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"ALTER MODEL tweet WITH VOLATILE=FALSE").unwrap(),
+            Statement::AlterModel {
+                entity: Entity::Current("tweet".into()),
+                volatile: Some(false),
+                limits: LimitsDecl::default(),
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"alter model tweet with max_key_size = 0, volatile = true").unwrap(),
+            Statement::AlterModel {
+                entity: Entity::Current("tweet".into()),
+                volatile: Some(true),
+                limits: LimitsDecl {
+                    max_key_size: Some(0),
+                    max_value_size: None,
+                    max_memory: None,
+                    eviction: None,
+                    durability: None,
+                },
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"alter model tweet with max_memory = 0, eviction = none").unwrap(),
+            Statement::AlterModel {
+                entity: Entity::Current("tweet".into()),
+                volatile: None,
+                limits: LimitsDecl {
+                    max_memory: Some(0),
+                    eviction: Some(EvictionPolicy::None),
+                    ..LimitsDecl::default()
+                },
+            }
+        );
+    }
+    #[test]
+    fn stmt_alter_model_bad_expression() {
+        src!(
+            SOURCES,
+            "alter model tweet with volatile = 1",
+            "alter model tweet with volatile true",
+            "alter model tweet volatile = true",
+            "alter model tweet with volatile =",
+            "alter model tweet with",
+            "alter model tweet with max_value_size = true",
+            "alter model tweet with max_size = 10",
+            "alter model tweet max_key_size = 10",
+            "alter model tweet with eviction = fifo",
+            "alter model tweet with eviction = 10",
+            "alter model tweet with max_memory = lru",
+        );
+        for src in SOURCES {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::BadExpression
+            );
+        }
+        assert_eq!(
+            Compiler::compile(b"alter space twitter with volatile = true").unwrap_err(),
+            LangError::InvalidSyntax
+        );
+    }
+    #[test]
+    fn stmt_rename_space() {
+        assert_eq!(
+            Compiler::compile(b"rename space twitter x").unwrap(),
+            Statement::RenameSpace {
+                entity: "twitter".into(),
+                to: "x".into()
+            }
+        );
+    }
+    #[test]
+    fn stmt_rename_model() {
+        assert_eq!(
+            Compiler::compile(b"rename model twitter.tweet post").unwrap(),
+            Statement::RenameModel {
+                entity: Entity::Full("twitter".into(), "tweet".into()),
+                to: "post".into()
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"rename model tweet post").unwrap(),
+            Statement::RenameModel {
+                entity: Entity::Current("tweet".into()),
+                to: "post".into()
+            }
+        );
+    }
+    #[test]
+    fn stmt_rename_bad() {
+        assert_eq!(
+            Compiler::compile(b"rename space twitter").unwrap_err(),
+            LangError::UnexpectedEOF
+        );
+        assert_eq!(
+            Compiler::compile(b"rename model tweet post extra").unwrap_err(),
+            LangError::InvalidSyntax
+        );
+        let long_name = format!("rename space twitter {}", "x".repeat(65));
+        assert_eq!(
+            Compiler::compile(long_name.as_bytes()).unwrap_err(),
+            LangError::BadExpression
+        );
+    }
+    #[test]
     fn stmt_inspect_space() {
         assert_eq!(
             Compiler::compile(b"inspect space twitter").unwrap(),
@@ -275,6 +805,57 @@ mod ast {
             Compiler::compile(b"inspect model twitter.tweet").unwrap(),
             Statement::InspectModel(Some(Entity::Full("twitter".into(), "tweet".into())))
         );
+        assert_eq!(
+            Compiler::compile(b"inspect model twitter.tweet.eu").unwrap(),
+            Statement::InspectModel(Some(Entity::Shard(
+                "twitter".into(),
+                "tweet".into(),
+                "eu".into()
+            )))
+        );
+    }
+    #[test]
+    fn stmt_inspect_wildcards() {
+        assert_eq!(
+            Compiler::compile(b"inspect space *").unwrap(),
+            Statement::InspectSpaces
+        );
+        assert_eq!(
+            Compiler::compile(b"inspect model twitter.*").unwrap(),
+            Statement::InspectSpace(Some("twitter".into()))
+        );
+        assert_eq!(
+            Compiler::compile(b"inspect model twitter.tweet.*").unwrap(),
+            Statement::InspectShards(Entity::Full("twitter".into(), "tweet".into()))
+        );
+        src!(
+            SOURCES,
+            "inspect model *",
+            "inspect model twitter.tweet.eu.*",
+            "inspect model twitter.*.*"
+        );
+        for src in SOURCES {
+            assert!(Compiler::compile(src).is_err());
+        }
+    }
+    #[test]
+    fn stmt_rename_shard() {
+        assert_eq!(
+            Compiler::compile(b"rename model twitter.tweet.eu europe").unwrap(),
+            Statement::RenameModel {
+                entity: Entity::Shard("twitter".into(), "tweet".into(), "eu".into()),
+                to: "europe".into()
+            }
+        );
+        let src = format!(
+            "rename model twitter.{}.eu {}",
+            "a".repeat(32),
+            "b".repeat(32)
+        );
+        assert_eq!(
+            Compiler::compile(src.as_bytes()).unwrap_err(),
+            LangError::BadExpression
+        );
     }
     #[test]
     fn compile_full() {
@@ -309,7 +890,53 @@ mod ast {
             // rule: fields can't be named
             "(id: string, posts: list<string>)",
             // rule: nested lists are disallowed
-            "(string, list<list<string>>)"
+            "(string, list<list<string>>)",
+            // rule: first cannot be a set
+            "(set<string>, string)",
+            // rule: sets must have a type argument and cannot be nested
+            "(string, set)",
+            "(string, set<list<string>>)",
+            "(string, list<set<string>>)",
+            // rule: first cannot be a zset and zsets cannot be nested
+            "(zset<string>, string)",
+            "(string, zset<set<string>>)",
+            // rule: maps need string field names and a non-compound value type
+            "(map<string, string>, string)",
+            "(string, map)",
+            "(string, map<string>)",
+            "(string, map<binary, string>)",
+            "(string, map<string, list<string>>)",
+            "(string, list<map<string, string>>)",
+            // rule: integers can only be used as (non-compound) values
+            "(u64, string)",
+            "(string, u64<string>)",
+            "(string, list<u64>)",
+            "(string, map<string, u64>)",
+            // rule: JSON can only be used as a (non-compound) value
+            "(json, string)",
+            "(string, json<string>)",
+            "(string, list<json>)",
+            "(string, map<string, json>)",
+            // rule: bloom filters can only be used as a (non-compound) value
+            "(bloom, string)",
+            "(string, bloom<string>)",
+            "(string, list<bloom>)",
+            "(string, map<string, bloom>)",
+            // rule: HLLs can only be used as a (non-compound) value
+            "(hll, string)",
+            "(string, hll<string>)",
+            "(string, set<hll>)",
+            "(string, map<string, hll>)",
+            // rule: geo indexes can only be used as a (non-compound) value
+            "(geo, string)",
+            "(string, geo<string>)",
+            "(string, list<geo>)",
+            "(string, map<string, geo>)",
+            // rule: time series can only be used as a (non-compound) value
+            "(timeseries, string)",
+            "(string, timeseries<string>)",
+            "(string, list<timeseries>)",
+            "(string, map<string, timeseries>)"
         );
         for src in SRC {
             assert_eq!(
@@ -320,4 +947,190 @@ mod ast {
             );
         }
     }
+    #[test]
+    fn set_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, set<binary>)"), 8);
+        assert_eq!(get_model_code(b"(binary, set<string>)"), 9);
+        assert_eq!(get_model_code(b"(string, set<binary>)"), 10);
+        assert_eq!(get_model_code(b"(string, set<string>)"), 11);
+    }
+    #[test]
+    fn zset_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, zset<binary>)"), 12);
+        assert_eq!(get_model_code(b"(binary, zset<string>)"), 13);
+        assert_eq!(get_model_code(b"(string, zset<binary>)"), 14);
+        assert_eq!(get_model_code(b"(string, zset<string>)"), 15);
+    }
+    #[test]
+    fn map_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, map<string, binary>)"), 16);
+        assert_eq!(get_model_code(b"(binary, map<string, string>)"), 17);
+        assert_eq!(get_model_code(b"(string, map<string, binary>)"), 18);
+        assert_eq!(get_model_code(b"(string, map<string, string>)"), 19);
+    }
+    #[test]
+    fn counter_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, u64)"), 20);
+        assert_eq!(get_model_code(b"(string, u64)"), 21);
+    }
+    #[test]
+    fn bloom_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, bloom)"), 28);
+        assert_eq!(get_model_code(b"(string, bloom)"), 29);
+    }
+    #[test]
+    fn hll_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, hll)"), 30);
+        assert_eq!(get_model_code(b"(string, hll)"), 31);
+    }
+    #[test]
+    fn geo_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, geo)"), 32);
+        assert_eq!(get_model_code(b"(string, geo)"), 33);
+    }
+    #[test]
+    fn timeseries_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, timeseries)"), 34);
+        assert_eq!(get_model_code(b"(string, timeseries)"), 35);
+    }
+    #[test]
+    fn json_model_code() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(get_model_code(b"(binary, json)"), 22);
+        assert_eq!(get_model_code(b"(string, json)"), 23);
+    }
+}
+
+mod qualified_keys {
+    use super::super::util::{parse_qualified_key, split_qualified_key, QualifiedKey};
+
+    fn qualified<'a>(keyspace: &'a [u8], table: &'a [u8], key: &'a [u8]) -> QualifiedKey<'a> {
+        QualifiedKey {
+            keyspace,
+            table,
+            key,
+        }
+    }
+
+    #[test]
+    fn qualified_key() {
+        assert_eq!(
+            parse_qualified_key(b"ks:tbl:key").unwrap(),
+            qualified(b"ks", b"tbl", b"key")
+        );
+        // the key can have colons in it
+        assert_eq!(
+            parse_qualified_key(b"ks:tbl:user:1").unwrap(),
+            qualified(b"ks", b"tbl", b"user:1")
+        );
+        assert_eq!(
+            parse_qualified_key(b"ks:tbl:").unwrap(),
+            qualified(b"ks", b"tbl", b"")
+        );
+        assert_eq!(qualified(b"ks", b"tbl", b"key").entity(), b"ks.tbl");
+    }
+
+    #[test]
+    fn unqualified_key() {
+        assert!(parse_qualified_key(b"tbl:key").is_none());
+        assert!(parse_qualified_key(b":tbl:key").is_none());
+        assert!(parse_qualified_key(b"ks::key").is_none());
+        assert!(parse_qualified_key(b"key").is_none());
+        assert!(parse_qualified_key(b"").is_none());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        // keys with colons in them mean what they always did unless it's enabled
+        assert!(split_qualified_key(b"ks:tbl:key").is_none());
+    }
 }
@@ -31,6 +31,7 @@ use {
         protocol::interface::ProtocolSpec,
         util::Life,
     },
+    core::sync::atomic::{AtomicBool, Ordering},
 };
 
 pub fn from_slice_action_result<P: ProtocolSpec>(slice: &[u8]) -> ActionResult<Life<'_, Entity>> {
@@ -39,3 +40,62 @@ pub fn from_slice_action_result<P: ProtocolSpec>(slice: &[u8]) -> ActionResult<L
         Err(e) => Err(ActionError::ActionError(error::cold_err::<P>(e))),
     }
 }
+
+/// Set if keys can be qualified with the table that they're in
+static QUALIFIED_KEYS: AtomicBool = AtomicBool::new(false);
+
+/// Allow (or disallow) qualified keys. They're disallowed unless `server.qualified_keys` is
+/// set, since a key with two colons in it would otherwise be read as a qualified key
+pub fn set_qualified_keys(enabled: bool) {
+    QUALIFIED_KEYS.store(enabled, Ordering::Release);
+}
+
+/// Returns true if keys can be qualified with the table that they're in
+pub fn qualified_keys_enabled() -> bool {
+    QUALIFIED_KEYS.load(Ordering::Acquire)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// A key qualified with the table that it's in (`<keyspace>:<table>:<key>`)
+pub struct QualifiedKey<'a> {
+    pub keyspace: &'a [u8],
+    pub table: &'a [u8],
+    pub key: &'a [u8],
+}
+
+impl QualifiedKey<'_> {
+    /// Returns the entity (`<keyspace>.<table>`) that the key is in
+    pub fn entity(&self) -> Vec<u8> {
+        let mut entity = Vec::with_capacity(self.keyspace.len() + self.table.len() + 1);
+        entity.extend_from_slice(self.keyspace);
+        entity.push(b'.');
+        entity.extend_from_slice(self.table);
+        entity
+    }
+}
+
+/// Split a qualified key of the form `<keyspace>:<table>:<key>` into the keyspace, the table
+/// and the key. The key itself may contain colons. Returns `None` if the key isn't qualified,
+/// or if qualified keys aren't enabled
+pub fn split_qualified_key(slice: &[u8]) -> Option<QualifiedKey<'_>> {
+    if qualified_keys_enabled() {
+        self::parse_qualified_key(slice)
+    } else {
+        None
+    }
+}
+
+/// Parse a qualified key, whether or not qualified keys are enabled
+pub(super) fn parse_qualified_key(slice: &[u8]) -> Option<QualifiedKey<'_>> {
+    let mut parts = slice.splitn(3, |b| *b == b':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(keyspace), Some(table), Some(key)) if !keyspace.is_empty() && !table.is_empty() => {
+            Some(QualifiedKey {
+                keyspace,
+                table,
+                key,
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,455 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Change data capture
+//!
+//! With `cdc.enabled`, every write that's committed is captured as a change, so that downstream
+//! systems can mirror the data. Writes are captured once they're applied (and logged, if the
+//! write-ahead log is on), in the order that they were applied. A change has:
+//! - `offset`: where the change is in the stream. Offsets only ever grow, but they can skip
+//! numbers (after a crash, for example)
+//! - `timestamp`: when the write was applied, as a UNIX timestamp in milliseconds
+//! - `entity`: the table that the write was made to (`<keyspace>.<table>`), or the one that a
+//! qualified key names
+//! - `op`: the action (uppercased, like `SET` or `DEL`), or `DDL` for a BlueQL statement
+//! - `key`: the key. This is empty for the writes that don't name one (`FLUSHDB`,
+//! `FLUSHTABLE`, `DELPREFIX` and DDL)
+//! - `values`: the rest of the arguments that go with the key (like the value of a `SET`, or
+//! nothing for a `DEL`), or all the arguments of a write that doesn't name a key (the
+//! statement, for DDL)
+//!
+//! Writes on many keys (like `MSET` and `DEL`) are split into a change for every key, and the
+//! writes in a transaction are captured one by one, so that a consumer can apply the changes
+//! one at a time. The changes of a write have the same timestamp, and `MSETEX` has the TTL
+//! after the value.
+//!
+//! ## Consuming the changes
+//! The latest `cdc.backlog` changes are kept in memory. `SYS CDC STREAM [<offset>]` makes a
+//! connection receive the changes after the given offset (or just the new ones, without an
+//! offset) as push frames, like pub/sub messages:
+//! `["cdc", offset, timestamp, entity, op, key, values...]`. So a consumer that remembers the
+//! offset of the last change that it applied can connect again and pick up from there. If the
+//! changes that it asks for aren't kept anymore (or it falls behind by more than the backlog),
+//! it receives `["cdc-lost", first, next]` instead: the changes from `first` up to `next`
+//! (not included) are lost, so it has to copy the data again, and the stream goes on from
+//! `next`. `SYS CDC STOP` stops the stream, and `SYS CDC INFO` returns the next offset, the
+//! oldest offset that's kept, the number of changes kept and the number of consumers. If auth
+//! is enabled, only root can stream the changes.
+//!
+//! With `cdc.file`, the changes are also appended to a file (see [`sink`]), which can be shipped
+//! to Kafka (or anywhere else) by a connector that tails it.
+//!
+//! ## Offsets
+//! The next offset is saved (in `data/cdcoffset`) when the server shuts down, and every
+//! [`OFFSET_RESERVATION`] changes in between, so that the offsets keep growing across restarts.
+//! The backlog isn't saved: consumers that were behind when the server stopped are told that
+//! they lost the changes that they didn't receive.
+//!
+//! ## Caveats
+//! - While capture is on, writes take turns like they do for the write-ahead log (see
+//! [`crate::storage::v1::wal::sequence`])
+//! - Only the writes run as queries are captured. Keys removed by the expiry sweeper or evicted
+//! from volatile tables aren't, and neither is data that's replaced as a whole (by a snapshot
+//! restore, an import or a full sync of a replica). The writes that a primary feeds its
+//! replicas are captured on the replicas
+//! - Relative TTLs (like the ones set by `EXPIRE`) are captured as they were given
+//! - Slots migrated to another cluster node show up as `DEL`s on the node that they leave, and
+//! not at all on the one that they move to
+
+use {
+    crate::{
+        blueql::util::split_qualified_key, config::CdcConfig,
+        storage::v1::interface::FILE_CDCOFFSET, IoResult,
+    },
+    core::sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        collections::VecDeque,
+        fs,
+        io::{Error as IoError, ErrorKind},
+        sync::Arc,
+    },
+    tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender},
+};
+
+pub mod sink;
+#[cfg(test)]
+mod tests;
+
+/// The number of changes that a consumer can fall behind by before it has to catch up from the
+/// backlog
+const FEED_CAPACITY: usize = 1024;
+/// The number of offsets that are handed out before the next offset is saved again
+const OFFSET_RESERVATION: u64 = 4096;
+/// Set while the writes are captured, so that writes can skip capturing cheaply
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The number of consumers (connections and the file)
+static CONSUMERS: AtomicUsize = AtomicUsize::new(0);
+static STATE: Mutex<Option<State>> = const_mutex(None);
+
+#[derive(Debug, PartialEq, Eq)]
+/// A change made by a committed write
+pub struct Change {
+    pub offset: u64,
+    /// when the write was applied, as a UNIX timestamp in milliseconds
+    pub timestamp: u64,
+    /// the table that the write was made to (`<keyspace>.<table>`)
+    pub entity: Vec<u8>,
+    /// the (uppercased) action, or `DDL`
+    pub op: Vec<u8>,
+    /// the key (empty if the write doesn't name one)
+    pub key: Vec<u8>,
+    /// the arguments that go with the key
+    pub values: Vec<Vec<u8>>,
+}
+
+impl Change {
+    /// A change (without its offset) to the key on the given entity, or on the entity that
+    /// the key names
+    fn new(timestamp: u64, entity: &[u8], op: &[u8], key: &[u8], values: Vec<Vec<u8>>) -> Self {
+        let (entity, key) = match split_qualified_key(key) {
+            Some(qualified) => (qualified.entity(), qualified.key),
+            None => (entity.to_vec(), key),
+        };
+        Self {
+            offset: 0,
+            timestamp,
+            entity,
+            op: op.to_vec(),
+            key: key.to_vec(),
+            values,
+        }
+    }
+}
+
+/// The changes captured while the server is running
+struct State {
+    /// the offset of the next change
+    next: u64,
+    /// the offset that's saved as the next one (so the offsets below it are taken)
+    reserved: u64,
+    /// the latest changes, oldest first
+    backlog: VecDeque<Arc<Change>>,
+    /// the number of changes kept in the backlog
+    capacity: usize,
+    feed: Sender<Arc<Change>>,
+}
+
+impl State {
+    /// Returns the oldest offset that a consumer can still receive
+    fn floor(&self) -> u64 {
+        self.backlog
+            .front()
+            .map_or(self.next, |change| change.offset)
+    }
+    /// Give the change the next offset, keep it and feed it to the consumers
+    fn push(&mut self, mut change: Change) {
+        change.offset = self.next;
+        self.next += 1;
+        let change = Arc::new(change);
+        if self.backlog.len() == self.capacity {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(change.clone());
+        // there's no one to receive this if there are no consumers
+        let _ = self.feed.send(change);
+    }
+}
+
+/// Start capturing the committed writes. This has to be called once the write-ahead log has
+/// been replayed (so that the replayed writes aren't captured again). Returns the file that the
+/// changes are appended to, if there's one (see [`sink::appender`])
+pub fn init(cfg: &CdcConfig) -> IoResult<Option<sink::Sink>> {
+    if !cfg.enabled {
+        return Ok(None);
+    }
+    let mut next = self::load_offset()?;
+    let file = match &cfg.file {
+        Some(path) => {
+            let (file, last) = sink::open(path)?;
+            // (the file outlives the saved offset if `data` was replaced)
+            if let Some(last) = last {
+                next = next.max(last + 1);
+            }
+            Some((file, last))
+        }
+        None => None,
+    };
+    let reserved = next + OFFSET_RESERVATION;
+    self::save_offset(reserved)?;
+    *STATE.lock() = Some(State {
+        next,
+        reserved,
+        backlog: VecDeque::with_capacity(cfg.backlog.min(FEED_CAPACITY)),
+        capacity: cfg.backlog,
+        feed: broadcast::channel(FEED_CAPACITY).0,
+    });
+    ENABLED.store(true, Ordering::Release);
+    log::info!("Capturing changes from offset {next}");
+    // (the file picks up after the last change in it)
+    Ok(file.and_then(|(file, last)| {
+        Consumer::start(last).map(|consumer| sink::Sink::new(file, consumer))
+    }))
+}
+
+/// Stop capturing and save the next offset, so that the offsets pick up from there when the
+/// server starts again. This has to be called once the server has stopped taking writes. The
+/// consumers receive the changes that are left, and then they're done
+pub fn stop() {
+    if let Some(state) = STATE.lock().take() {
+        ENABLED.store(false, Ordering::Release);
+        if let Err(e) = self::save_offset(state.next) {
+            log::error!("Failed to save the next CDC offset: {e}");
+        }
+    }
+}
+
+/// Returns true if the committed writes are captured
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Capture the queries (in this order) of a write that was committed on the given entity. This
+/// has to be called in the write's turn (see [`crate::storage::v1::wal::sequence`]), so that
+/// the changes to a table are captured in the order that they were applied
+pub fn capture<Q: AsRef<[T]>, T: AsRef<[u8]>>(timestamp: u64, entity: &[u8], queries: &[Q]) {
+    let mut state = STATE.lock();
+    let state = match state.as_mut() {
+        Some(state) => state,
+        None => return,
+    };
+    for query in queries {
+        for change in self::decode(timestamp, entity, query.as_ref()) {
+            state.push(change);
+        }
+    }
+    if state.next >= state.reserved {
+        // (an offset is never handed out twice, even if we crash before the next one is saved)
+        let reserved = state.next + OFFSET_RESERVATION;
+        match self::save_offset(reserved) {
+            Ok(()) => state.reserved = reserved,
+            Err(e) => log::error!("Failed to save the next CDC offset: {e}"),
+        }
+    }
+}
+
+/// Split a query that was committed on the given entity into its changes
+fn decode<T: AsRef<[u8]>>(timestamp: u64, entity: &[u8], query: &[T]) -> Vec<Change> {
+    let (first, args) = match query.split_first() {
+        Some((first, args)) => (first.as_ref(), args),
+        None => return Vec::new(),
+    };
+    let change = |op: &[u8], key: &[u8], values| Change::new(timestamp, entity, op, key, values);
+    if first.iter().any(u8::is_ascii_whitespace) {
+        // a BlueQL statement (only the ones that change the schema are committed)
+        return vec![change(b"DDL", b"", vec![first.to_vec()])];
+    }
+    let op = first.to_ascii_uppercase();
+    let args: Vec<&[u8]> = args.iter().map(AsRef::as_ref).collect();
+    match &op[..] {
+        // the bounds of a transaction, and the writes made by the server itself (like the keys
+        // imported by a migration of slots)
+        b"MULTI" | b"EXEC" | b"SYS" => Vec::new(),
+        // these don't name a key
+        b"FLUSHDB" | b"FLUSHTABLE" | b"DELPREFIX" => {
+            vec![change(
+                &op,
+                b"",
+                args.iter().map(|arg| arg.to_vec()).collect(),
+            )]
+        }
+        // keys and values
+        b"MSET" | b"SETMANY" | b"USET" | b"MUPDATE" | b"SSET" | b"SUPDATE" => args
+            .chunks_exact(2)
+            .map(|pair| change(&op, pair[0], vec![pair[1].to_vec()]))
+            .collect(),
+        b"MSETEX" => match args.split_first() {
+            Some((ttl, pairs)) => pairs
+                .chunks_exact(2)
+                .map(|pair| change(&op, pair[0], vec![pair[1].to_vec(), ttl.to_vec()]))
+                .collect(),
+            None => Vec::new(),
+        },
+        // every argument is a key
+        b"DEL" | b"SDEL" | b"MPOP" => args
+            .iter()
+            .map(|&key| change(&op, key, Vec::new()))
+            .collect(),
+        // the rest are run on the key that comes first
+        _ => match args.split_first() {
+            Some((&key, values)) => vec![change(
+                &op,
+                key,
+                values.iter().map(|value| value.to_vec()).collect(),
+            )],
+            None => vec![change(&op, b"", Vec::new())],
+        },
+    }
+}
+
+/// Returns the offset that was saved as the next one (zero if there's none)
+fn load_offset() -> IoResult<u64> {
+    match fs::read_to_string(FILE_CDCOFFSET) {
+        Ok(offset) => offset.trim().parse().map_err(|_| {
+            IoError::new(
+                ErrorKind::InvalidData,
+                format!("`{FILE_CDCOFFSET}` doesn't have an offset"),
+            )
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+/// Save the next offset (in a new file that replaces the old one, so that a crash leaves one of
+/// them behind)
+fn save_offset(offset: u64) -> IoResult<()> {
+    let tmp = format!("{FILE_CDCOFFSET}.tmp");
+    fs::write(&tmp, offset.to_string())?;
+    fs::rename(&tmp, FILE_CDCOFFSET)
+}
+
+/// How capture is doing (see [`info`])
+pub struct Info {
+    /// the offset that the next change gets
+    pub next: u64,
+    /// the oldest offset that's kept (this is `next` if nothing is kept)
+    pub oldest: u64,
+    /// the number of changes kept
+    pub kept: usize,
+    /// the number of consumers
+    pub consumers: usize,
+}
+
+/// Returns how capture is doing (or `None` if it isn't enabled)
+pub fn info() -> Option<Info> {
+    let state = STATE.lock();
+    let state = state.as_ref()?;
+    Some(Info {
+        next: state.next,
+        oldest: state.floor(),
+        kept: state.backlog.len(),
+        consumers: CONSUMERS.load(Ordering::Relaxed),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// What a consumer receives
+pub enum Delivery {
+    /// the next change
+    Change(Arc<Change>),
+    /// the changes from `first` up to `next` (not included) were lost
+    Lost { first: u64, next: u64 },
+}
+
+#[derive(Debug)]
+/// A consumer of the changes. It stops consuming when this is dropped
+pub struct Consumer {
+    rx: Receiver<Arc<Change>>,
+    /// the offset of the next change to deliver
+    next: u64,
+    /// the changes from the backlog that are delivered before the ones in the feed
+    pending: VecDeque<Arc<Change>>,
+    /// the changes that were lost (to be reported before anything else)
+    lost: Option<(u64, u64)>,
+}
+
+impl Consumer {
+    /// Start consuming the changes after the given offset (or the ones captured from now on,
+    /// without one). Returns `None` if capture isn't enabled
+    pub fn start(after: Option<u64>) -> Option<Self> {
+        let state = STATE.lock();
+        let state = state.as_ref()?;
+        // (nothing is captured while we hold the state, so nothing can slip in between the
+        // backlog and the feed)
+        let mut consumer = Self {
+            rx: state.feed.subscribe(),
+            next: state.next,
+            pending: VecDeque::new(),
+            lost: None,
+        };
+        if let Some(after) = after {
+            consumer.next = after.saturating_add(1);
+            consumer.catch_up(state);
+        }
+        CONSUMERS.fetch_add(1, Ordering::Relaxed);
+        Some(consumer)
+    }
+    /// Queue the kept changes from the next offset on, and take note of the ones that aren't
+    /// kept anymore
+    fn catch_up(&mut self, state: &State) {
+        let floor = state.floor();
+        if self.next < floor {
+            self.lost = Some((self.next, floor));
+            self.next = floor;
+        }
+        let start = state
+            .backlog
+            .partition_point(|change| change.offset < self.next);
+        self.pending.extend(state.backlog.range(start..).cloned());
+    }
+    /// Wait for the next change (or for news of the changes that were lost). Returns `None`
+    /// once capture has stopped and every change has been delivered
+    pub async fn recv(&mut self) -> Option<Delivery> {
+        loop {
+            if let Some((first, next)) = self.lost.take() {
+                return Some(Delivery::Lost { first, next });
+            }
+            if let Some(change) = self.pending.pop_front() {
+                self.next = change.offset + 1;
+                return Some(Delivery::Change(change));
+            }
+            match self.rx.recv().await {
+                Ok(change) if change.offset == self.next => {
+                    self.next += 1;
+                    return Some(Delivery::Change(change));
+                }
+                // the offsets don't skip numbers while the server is running, so the ones in
+                // between were missed (this only happens if capture stopped while we lagged)
+                Ok(change) if change.offset > self.next => {
+                    self.lost = Some((self.next, change.offset));
+                    self.pending.push_back(change);
+                }
+                // delivered from the backlog already
+                Ok(_) => {}
+                Err(RecvError::Lagged(_)) => {
+                    // the feed only has the latest changes now, so pick up the rest from the
+                    // backlog (if it's still there)
+                    if let Some(state) = STATE.lock().as_ref() {
+                        self.catch_up(state);
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Consumer {
+    fn drop(&mut self) {
+        CONSUMERS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,211 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # The CDC file
+//!
+//! With `cdc.file`, the changes are appended to a file, a change on every line, as JSON:
+//! ```text
+//! {"offset":42,"timestamp":1760520067112,"entity":"default.default","op":"SET","key":"x","values":["100"]}
+//! ```
+//! The key and the values are strings, or `{"base64": <string>}` if they aren't valid UTF-8
+//! (like the responses of the HTTP gateway). The file is flushed every second, and when the
+//! server starts again it picks up after the last change in the file (a line that was torn by
+//! a crash is cut off). Changes that the file missed (the ones that weren't flushed before a
+//! crash, for example) are logged.
+//!
+//! The file isn't rotated. A connector that tails it (like the file source of Kafka Connect)
+//! can ship the changes to Kafka, and it can be truncated once they've been shipped.
+
+use {
+    super::{Change, Consumer, Delivery},
+    crate::{
+        dbnet,
+        kvengine::json::{self, PathSegment},
+        IoResult,
+    },
+    core::str,
+    std::{
+        fs::{File, OpenOptions},
+        io::{Error as IoError, ErrorKind, Read, Seek, SeekFrom},
+    },
+    tokio::{
+        io::{AsyncWriteExt, BufWriter},
+        time::{self, Duration},
+    },
+};
+
+/// How often the file is flushed
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// The number of bytes that are read at a time while looking for the last line
+const TAIL_CHUNK: u64 = 8192;
+
+/// The file that the changes are appended to (see [`appender`])
+pub struct Sink {
+    file: File,
+    consumer: Consumer,
+}
+
+impl Sink {
+    pub(super) fn new(file: File, consumer: Consumer) -> Self {
+        Self { file, consumer }
+    }
+}
+
+/// Open the file (creating it if it doesn't exist) and cut off a line that was torn by a
+/// crash. Returns the file and the offset of the last change in it (if any)
+pub(super) fn open(path: &str) -> IoResult<(File, Option<u64>)> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let len = file.metadata()?.len();
+    let (complete, last) = self::last_line(&mut file, len)?;
+    if complete != len {
+        log::warn!("Cutting off the torn change at the end of `{path}`");
+        file.set_len(complete)?;
+    }
+    if last.is_empty() {
+        return Ok((file, None));
+    }
+    let offset = match json::is_json(&last) {
+        true => json::lookup(&last, &[PathSegment::Key(b"offset")]),
+        false => None,
+    };
+    match offset.map(str::from_utf8) {
+        Some(Ok(offset)) => match offset.parse() {
+            Ok(offset) => Ok((file, Some(offset))),
+            Err(_) => Err(self::not_a_change(path)),
+        },
+        _ => Err(self::not_a_change(path)),
+    }
+}
+
+fn not_a_change(path: &str) -> IoError {
+    IoError::new(
+        ErrorKind::InvalidData,
+        format!("the last line of `{path}` isn't a change"),
+    )
+}
+
+/// Returns the length of the complete lines at the start of the file and the last one of them
+/// (without the newline; this is empty if there are none)
+pub(super) fn last_line(file: &mut (impl Read + Seek), len: u64) -> IoResult<(u64, Vec<u8>)> {
+    // the bytes from `start` to the end of the file
+    let mut tail = Vec::new();
+    let mut start = len;
+    loop {
+        // the complete lines end with the last newline, and the last of them starts after the
+        // newline before it (or at the start of the file)
+        match tail.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => match tail[..end].iter().rposition(|byte| *byte == b'\n') {
+                Some(begin) => return Ok((start + end as u64 + 1, tail[begin + 1..end].to_vec())),
+                None if start == 0 => return Ok((end as u64 + 1, tail[..end].to_vec())),
+                None => {}
+            },
+            None if start == 0 => return Ok((0, Vec::new())),
+            None => {}
+        }
+        let read = TAIL_CHUNK.min(start);
+        start -= read;
+        let mut chunk = vec![0; read as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+}
+
+/// Encode a change as a line of the file
+pub(super) fn encode_line(change: &Change) -> Vec<u8> {
+    let mut line = Vec::with_capacity(
+        96 + change.entity.len()
+            + change.key.len()
+            + change.values.iter().map(Vec::len).sum::<usize>(),
+    );
+    line.extend_from_slice(b"{\"offset\":");
+    line.extend_from_slice(change.offset.to_string().as_bytes());
+    line.extend_from_slice(b",\"timestamp\":");
+    line.extend_from_slice(change.timestamp.to_string().as_bytes());
+    line.extend_from_slice(b",\"entity\":");
+    dbnet::write_json_blob(&mut line, &change.entity);
+    line.extend_from_slice(b",\"op\":");
+    dbnet::write_json_blob(&mut line, &change.op);
+    line.extend_from_slice(b",\"key\":");
+    dbnet::write_json_blob(&mut line, &change.key);
+    line.extend_from_slice(b",\"values\":[");
+    for (i, value) in change.values.iter().enumerate() {
+        if i != 0 {
+            line.push(b',');
+        }
+        dbnet::write_json_blob(&mut line, value);
+    }
+    line.extend_from_slice(b"]}\n");
+    line
+}
+
+/// Append the changes to the file till capture stops
+pub async fn appender(sink: Sink) {
+    let Sink { file, mut consumer } = sink;
+    let mut file = BufWriter::new(tokio::fs::File::from_std(file));
+    let mut flush = time::interval(FLUSH_INTERVAL);
+    let mut unflushed = false;
+    loop {
+        tokio::select! {
+            delivery = consumer.recv() => match delivery {
+                Some(Delivery::Change(change)) => {
+                    if let Err(e) = file.write_all(&self::encode_line(&change)).await {
+                        let offset = change.offset;
+                        log::error!("Failed to append change {offset} to the CDC file: {e}");
+                    }
+                    unflushed = true;
+                }
+                Some(Delivery::Lost { first, next }) => {
+                    log::warn!("The CDC file missed the changes from offset {first} up to {next}");
+                }
+                None => break,
+            },
+            _ = flush.tick() => {
+                if unflushed {
+                    self::flush(&mut file).await;
+                    unflushed = false;
+                }
+            }
+        }
+    }
+    self::flush(&mut file).await;
+    log::info!("CDC appender has exited");
+}
+
+async fn flush(file: &mut BufWriter<tokio::fs::File>) {
+    let ret = match file.flush().await {
+        Ok(()) => file.get_ref().sync_data().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = ret {
+        log::error!("Failed to flush the CDC file: {e}");
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{sink, Change, Consumer, Delivery, State},
+    std::{collections::VecDeque, io::Cursor},
+    tokio::sync::broadcast,
+};
+
+fn change(offset: u64, op: &[u8], key: &[u8], values: &[&[u8]]) -> Change {
+    Change {
+        offset,
+        timestamp: 1000,
+        entity: b"default.default".to_vec(),
+        op: op.to_vec(),
+        key: key.to_vec(),
+        values: values.iter().map(|value| value.to_vec()).collect(),
+    }
+}
+
+fn decode(query: &[&[u8]]) -> Vec<Change> {
+    super::decode(1000, b"default.default", query)
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(
+        decode(&[b"set", b"x", b"100"]),
+        [change(0, b"SET", b"x", &[b"100"])]
+    );
+    // a change for every key
+    assert_eq!(
+        decode(&[b"MSET", b"x", b"1", b"y", b"2"]),
+        [
+            change(0, b"MSET", b"x", &[b"1"]),
+            change(0, b"MSET", b"y", &[b"2"])
+        ]
+    );
+    assert_eq!(
+        decode(&[b"MSETEX", b"60", b"x", b"1"]),
+        [change(0, b"MSETEX", b"x", &[b"1", b"60"])]
+    );
+    assert_eq!(
+        decode(&[b"DEL", b"x", b"y"]),
+        [change(0, b"DEL", b"x", &[]), change(0, b"DEL", b"y", &[])]
+    );
+    // no key
+    assert_eq!(
+        decode(&[b"FLUSHTABLE"]),
+        [change(0, b"FLUSHTABLE", b"", &[])]
+    );
+    assert_eq!(
+        decode(&[b"create model mymodel(string, string)"]),
+        [change(
+            0,
+            b"DDL",
+            b"",
+            &[b"create model mymodel(string, string)"]
+        )]
+    );
+    // not changes
+    assert!(decode(&[b"MULTI"]).is_empty());
+    assert!(decode(&[b"EXEC"]).is_empty());
+    assert!(decode(&[b"SYS", b"CLUSTER", b"IMPORT", b"archive", b"deadlines"]).is_empty());
+    // the key names its own entity
+    let decoded = decode(&[b"SET", b"@myks.mytbl:x", b"100"]);
+    assert_eq!(decoded[0].entity, b"myks.mytbl");
+    assert_eq!(decoded[0].key, b"x");
+}
+
+/// A state (that isn't the global one) with the given backlog
+fn state(capacity: usize) -> State {
+    State {
+        next: 0,
+        reserved: u64::MAX,
+        backlog: VecDeque::new(),
+        capacity,
+        feed: broadcast::channel(16).0,
+    }
+}
+
+fn consumer(state: &State, next: u64) -> Consumer {
+    let mut consumer = Consumer {
+        rx: state.feed.subscribe(),
+        next,
+        pending: VecDeque::new(),
+        lost: None,
+    };
+    consumer.catch_up(state);
+    consumer
+}
+
+async fn recv_offset(consumer: &mut Consumer) -> u64 {
+    match consumer.recv().await {
+        Some(Delivery::Change(change)) => change.offset,
+        delivery => panic!("expected a change, got {delivery:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_consumer_resume() {
+    let mut state = self::state(3);
+    for _ in 0..5 {
+        state.push(change(0, b"DEL", b"x", &[]));
+    }
+    // only the latest three are kept
+    assert_eq!(state.floor(), 2);
+    assert_eq!(state.next, 5);
+    // resume after offset 2
+    let mut resumed = self::consumer(&state, 3);
+    assert_eq!(recv_offset(&mut resumed).await, 3);
+    assert_eq!(recv_offset(&mut resumed).await, 4);
+    state.push(change(0, b"DEL", b"x", &[]));
+    assert_eq!(recv_offset(&mut resumed).await, 5);
+    // resume after offset 0, but 1 isn't kept anymore
+    let mut behind = self::consumer(&state, 1);
+    assert_eq!(
+        behind.recv().await,
+        Some(Delivery::Lost { first: 1, next: 3 })
+    );
+    assert_eq!(recv_offset(&mut behind).await, 3);
+    // the changes that were fed after the ones in the backlog aren't delivered twice
+    let mut fed = Consumer {
+        rx: state.feed.subscribe(),
+        next: 4,
+        pending: VecDeque::new(),
+        lost: None,
+    };
+    state.push(change(0, b"DEL", b"x", &[]));
+    fed.catch_up(&state);
+    for offset in 4..=6 {
+        assert_eq!(recv_offset(&mut fed).await, offset);
+    }
+    state.push(change(0, b"DEL", b"x", &[]));
+    assert_eq!(recv_offset(&mut fed).await, 7);
+    // nothing is delivered once capture stops
+    drop(state);
+    assert_eq!(fed.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_consumer_gap() {
+    let mut state = self::state(8);
+    let mut consumer = self::consumer(&state, 0);
+    state.push(change(0, b"DEL", b"x", &[]));
+    // (a change that the consumer never saw)
+    state.next += 1;
+    state.push(change(0, b"DEL", b"x", &[]));
+    assert_eq!(recv_offset(&mut consumer).await, 0);
+    assert_eq!(
+        consumer.recv().await,
+        Some(Delivery::Lost { first: 1, next: 2 })
+    );
+    assert_eq!(recv_offset(&mut consumer).await, 2);
+}
+
+#[test]
+fn test_encode_line() {
+    let mut change = change(42, b"SET", b"x", &[b"100"]);
+    assert_eq!(
+        sink::encode_line(&change),
+        b"{\"offset\":42,\"timestamp\":1000,\"entity\":\"default.default\",\"op\":\"SET\",\
+        \"key\":\"x\",\"values\":[\"100\"]}\n"
+    );
+    // binary values are base64 encoded
+    change.values = vec![vec![0xff, 0x00]];
+    change.key = b"say \"hi\"".to_vec();
+    assert_eq!(
+        sink::encode_line(&change),
+        b"{\"offset\":42,\"timestamp\":1000,\"entity\":\"default.default\",\"op\":\"SET\",\
+        \"key\":\"say \\\"hi\\\"\",\"values\":[{\"base64\":\"/wA=\"}]}\n"
+    );
+}
+
+#[test]
+fn test_last_line() {
+    let last_line =
+        |file: &[u8]| sink::last_line(&mut Cursor::new(file.to_vec()), file.len() as u64).unwrap();
+    assert_eq!(last_line(b""), (0, b"".to_vec()));
+    assert_eq!(last_line(b"first\n"), (6, b"first".to_vec()));
+    assert_eq!(last_line(b"first\nsecond\n"), (13, b"second".to_vec()));
+    // a torn line is cut off
+    assert_eq!(last_line(b"first\nsecond\nthi"), (13, b"second".to_vec()));
+    assert_eq!(last_line(b"torn"), (0, b"".to_vec()));
+    // lines longer than what's read at a time
+    let mut file = vec![b'a'; 20000];
+    file.push(b'\n');
+    file.extend(vec![b'b'; 20000]);
+    file.push(b'\n');
+    assert_eq!(last_line(&file), (40002, vec![b'b'; 20000]));
+}
@@ -0,0 +1,343 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Cluster backups
+//!
+//! `SYS CLUSTER BACKUP <name>` takes a backup of the whole cluster that can be restored: a
+//! remote snapshot named `<name>` on every node (see `MKSNAP`), all of them taken at the same
+//! cut, and a manifest that says which node's snapshot has which slots. The node that it's run
+//! on (the coordinator) does this in two phases:
+//! 1. Every node (the coordinator first) is frozen with `SYS CLUSTER FREEZE <id>`: it holds
+//! off writes (see [`wal::pause`]) and returns what it knows about the cluster (see
+//! [`super::gossip`]). A node that's migrating slots, or that's frozen for another backup,
+//! refuses. The nodes have to agree on the owners of the slots (so that no claim is on its
+//! way), or the backup fails. Once every node is frozen, no node has a write that another
+//! node's write (which it doesn't have) could have led to, and that's the cut
+//! 2. Every node takes its snapshot with `SYS CLUSTER SNAPSHOT <id> <name>`, and goes on with
+//! the writes once it's done
+//!
+//! The coordinator then writes the manifest (see [`Manifest`]) to
+//! `data/backups/cluster-<name>.toml`, and returns it. If the backup fails, the nodes are
+//! thawed right away, and a node that doesn't hear back from the coordinator thaws on its own
+//! after [`FREEZE_TIMEOUT`].
+//!
+//! ## Restoring
+//! Every node in the manifest restores its snapshot with `SYS SNAPSHOT RESTORE <name>`, and
+//! claims its slots with `SYS CLUSTER ADDSLOTS` (since the cluster state isn't kept, see
+//! [`super`]).
+//!
+//! ## Caveats
+//! - Every node holds off writes until it has taken its snapshot, so the backup is as quick as
+//! the slowest snapshot
+//! - Every node that the coordinator knows of has to take part, so a backup can't be taken
+//! while a node is down
+
+use {
+    super::{gossip::Gossip, migration, CLUSTER, MYSELF},
+    crate::{
+        corestore::Corestore,
+        dbnet::peer::{self, Peer},
+        kvengine::expiry,
+        storage::v1::{
+            interface::{DIR_BACKUPS, DIR_RSNAPROOT},
+            sengine::SnapshotActionResult,
+            wal::{self, Paused},
+        },
+    },
+    chrono::Utc,
+    core::ops::RangeInclusive,
+    parking_lot::{const_mutex, Mutex},
+    serde::{Deserialize, Serialize},
+    std::fs,
+    tokio::time::{self, Duration},
+};
+
+/// The longest that a node stays frozen for a backup
+const FREEZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The backup that we're frozen for (if any)
+static FROZEN: Mutex<Option<Frozen>> = const_mutex(None);
+
+/// Writes are held off for a backup till this is dropped
+struct Frozen {
+    /// the ID of the backup
+    id: u64,
+    _paused: Paused,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The manifest of a backup of the cluster
+pub struct Manifest {
+    /// the name of the remote snapshots that make up the backup
+    pub name: String,
+    /// when the backup was taken (UNIX millis)
+    pub taken: u64,
+    /// the highest epoch that the nodes had seen
+    pub epoch: u64,
+    pub nodes: Vec<NodeManifest>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The part of a backup that a node took
+pub struct NodeManifest {
+    /// the address of the node
+    pub node: String,
+    /// where the node keeps its snapshot (relative to its working directory)
+    pub snapshot: String,
+    /// the slots that the node owned, as `<first>-<last>` ranges
+    pub slots: Vec<String>,
+}
+
+#[derive(Debug)]
+/// Why a backup failed
+pub enum BackupError {
+    /// we aren't running in cluster mode
+    Disabled,
+    /// we're migrating slots, or we're frozen for another backup
+    Busy,
+    /// a node failed to take part (or the nodes don't agree on the owners of the slots)
+    Failed(String),
+}
+
+/// Returns true if the name can be used for a backup (it names a directory)
+pub fn is_valid_name(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+}
+
+/// Hold off writes for the backup with the given ID, and return what we know about the
+/// cluster (as a gossip message). Returns `None` if we're migrating slots, or if we're frozen
+/// for another backup
+pub async fn freeze(id: u64) -> Option<Vec<u8>> {
+    if FROZEN.lock().is_some() {
+        return None;
+    }
+    let paused = wal::pause().await;
+    // (a migration only starts while writes are paused, so it can't start after this)
+    if migration::is_active() {
+        return None;
+    }
+    let gossip = CLUSTER.read().as_ref()?.gossip().encode();
+    {
+        let mut frozen = FROZEN.lock();
+        if frozen.is_some() {
+            return None;
+        }
+        *frozen = Some(Frozen {
+            id,
+            _paused: paused,
+        });
+    }
+    tokio::spawn(async move {
+        time::sleep(FREEZE_TIMEOUT).await;
+        if self::thaw(id) {
+            log::warn!("Thawed since the backup {id} didn't go on in time");
+        }
+    });
+    Some(gossip)
+}
+
+/// Go on with the writes if we're frozen for the backup with the given ID. Returns false if
+/// we weren't
+pub fn thaw(id: u64) -> bool {
+    let mut frozen = FROZEN.lock();
+    let thawed = frozen.as_ref().map_or(false, |backup| backup.id == id);
+    if thawed {
+        *frozen = None;
+    }
+    thawed
+}
+
+/// Take our snapshot for the backup with the given ID, and then go on with the writes. Returns
+/// `None` if we aren't frozen for the backup
+pub async fn snapshot(db: &Corestore, id: u64, name: &str) -> Option<SnapshotActionResult> {
+    let frozen = {
+        let mut frozen = FROZEN.lock();
+        if frozen.as_ref().map_or(true, |backup| backup.id != id) {
+            return None;
+        }
+        frozen.take()
+    };
+    let taken = db
+        .get_engine()
+        .mkrsnap(name.as_bytes(), db.clone_store())
+        .await;
+    drop(frozen);
+    Some(taken)
+}
+
+/// Take a backup of the cluster (see the [module docs](self)), and return its manifest (as
+/// TOML)
+pub async fn backup(db: &Corestore, name: &str) -> Result<String, BackupError> {
+    let (myself, others, login) = {
+        let cluster = CLUSTER.read();
+        let cluster = cluster.as_ref().ok_or(BackupError::Disabled)?;
+        let others: Vec<String> = cluster.nodes[MYSELF + 1..]
+            .iter()
+            .map(|node| node.addr.clone())
+            .collect();
+        let myself = cluster.nodes[MYSELF].addr.clone();
+        (myself, others, cluster.login.clone())
+    };
+    let mut links = Vec::with_capacity(others.len());
+    for node in others {
+        // (the addresses of the nodes were checked when we learnt of them)
+        let (host, port) = super::split_addr(&node).unwrap_or_default();
+        match peer::connect(host, port, login.as_ref()).await {
+            Ok(link) => links.push((node, link)),
+            Err(e) => return Err(BackupError::Failed(format!("{node} is unreachable: {e}"))),
+        }
+    }
+    let id = Utc::now().timestamp_nanos() as u64;
+    let (epoch, owners) = match self::freeze_all(id, &mut links).await {
+        Ok(owners) => owners,
+        Err(e) => {
+            // (the nodes would thaw on their own, but there's no point in waiting)
+            self::thaw(id);
+            let id = id.to_string();
+            for (_, link) in links.iter_mut() {
+                let _ = peer::query(link, &[b"SYS", b"CLUSTER", b"THAW", id.as_bytes()]).await;
+            }
+            return Err(e);
+        }
+    };
+    log::info!("Froze every node for the backup `{name}`, taking the snapshots");
+    let mut nodes = vec![myself];
+    nodes.extend(links.iter().map(|(node, _)| node.clone()));
+    self::snapshot_all(db, id, name, links).await?;
+    let manifest = Manifest {
+        name: name.to_owned(),
+        taken: expiry::now_millis(),
+        epoch,
+        nodes: nodes
+            .into_iter()
+            .map(|node| NodeManifest {
+                slots: owners
+                    .iter()
+                    .filter(|(_, owner)| *owner == node)
+                    .map(|(range, _)| format!("{}-{}", range.start(), range.end()))
+                    .collect(),
+                node,
+                snapshot: format!("{DIR_RSNAPROOT}/{name}"),
+            })
+            .collect(),
+    };
+    let encoded = self::write_manifest(&manifest).map_err(BackupError::Failed)?;
+    log::info!("Took the backup `{name}` of the cluster");
+    Ok(encoded)
+}
+
+/// The owners of the slots, as ranges, and the highest epoch that the nodes had seen
+type Owners = (u64, Vec<(RangeInclusive<u16>, String)>);
+
+/// Freeze every node, and check that they agree on the owners of the slots
+async fn freeze_all(id: u64, links: &mut [(String, Peer)]) -> Result<Owners, BackupError> {
+    let mut views = vec![self::freeze(id).await.ok_or(BackupError::Busy)?];
+    let id = id.to_string();
+    for (node, link) in links.iter_mut() {
+        let query: [&[u8]; 4] = [b"SYS", b"CLUSTER", b"FREEZE", id.as_bytes()];
+        match peer::query_binary(link, &query).await {
+            Ok(view) => views.push(view),
+            Err(e) => return Err(BackupError::Failed(format!("{node} didn't freeze: {e}"))),
+        }
+    }
+    let mut epoch = 0;
+    let mut agreed: Option<Vec<(RangeInclusive<u16>, String)>> = None;
+    for view in views {
+        let gossip = Gossip::decode(&view)
+            .ok_or_else(|| BackupError::Failed("got a bad view of the cluster".to_owned()))?;
+        epoch = epoch.max(gossip.epoch);
+        let owners = gossip.owners();
+        match &agreed {
+            Some(agreed) if *agreed != owners => {
+                return Err(BackupError::Failed(format!(
+                    "{} doesn't agree on the owners of the slots",
+                    gossip.sender
+                )))
+            }
+            Some(_) => {}
+            None => agreed = Some(owners),
+        }
+    }
+    Ok((epoch, agreed.unwrap_or_default()))
+}
+
+/// Have every node take its snapshot (all at once, since they're all holding off writes)
+async fn snapshot_all(
+    db: &Corestore,
+    id: u64,
+    name: &str,
+    links: Vec<(String, Peer)>,
+) -> Result<(), BackupError> {
+    let remote: Vec<_> = links
+        .into_iter()
+        .map(|(node, mut link)| {
+            let (id, name) = (id.to_string(), name.to_owned());
+            tokio::spawn(async move {
+                let query: [&[u8]; 5] = [
+                    b"SYS",
+                    b"CLUSTER",
+                    b"SNAPSHOT",
+                    id.as_bytes(),
+                    name.as_bytes(),
+                ];
+                let taken = peer::query(&mut link, &query).await;
+                (node, taken)
+            })
+        })
+        .collect();
+    let ours = self::snapshot(db, id, name).await;
+    let mut failed = match ours {
+        Some(SnapshotActionResult::Ok) => None,
+        Some(SnapshotActionResult::AlreadyExists) => {
+            Some(format!("a snapshot named `{name}` exists already"))
+        }
+        _ => Some("failed to take our snapshot".to_owned()),
+    };
+    for taken in remote {
+        let (node, taken) = taken.await.expect("Something caused a snapshot to panic");
+        if let Err(e) = taken {
+            failed.get_or_insert(format!("{node} failed to take its snapshot: {e}"));
+        }
+    }
+    match failed {
+        Some(e) => Err(BackupError::Failed(e)),
+        None => Ok(()),
+    }
+}
+
+/// Write the manifest to the backups directory, and return it (as TOML)
+fn write_manifest(manifest: &Manifest) -> Result<String, String> {
+    let encoded = toml::to_string(manifest).map_err(|e| e.to_string())?;
+    let path = format!("{DIR_BACKUPS}/cluster-{}.toml", manifest.name);
+    fs::create_dir_all(DIR_BACKUPS)
+        .and_then(|_| fs::write(&path, &encoded))
+        .map_err(|e| format!("failed to write `{path}`: {e}"))?;
+    Ok(encoded)
+}
@@ -0,0 +1,322 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Gossip
+//!
+//! Every node sends what it knows to every other node that it knows of every
+//! [`GOSSIP_INTERVAL`] (with `SYS CLUSTER GOSSIP <message>`), and the other node merges it and
+//! returns what it knows in return (as a binary string). A message is (in little endian):
+//! - `[u16 length][address]`: the node that sent it
+//! - `[u64 epoch]`: the highest epoch that the sender has seen
+//! - `[u32 count]` nodes, each `[u16 length][address]`: every node that the sender knows of
+//! - `[u32 count]` claims, each `[u16 first][u16 last][u32 node][u64 epoch]`: a range of slots
+//! claimed by the node with the given index (among the nodes above) in the given epoch
+
+use {
+    super::{Claim, Cluster, CLUSTER, MYSELF, SLOTS},
+    crate::{
+        dbnet::peer::{self, Peer},
+        IoResult,
+    },
+    core::ops::RangeInclusive,
+    std::{
+        collections::{hash_map::Entry, HashMap},
+        io::{Error as IoError, ErrorKind},
+        time::Instant,
+    },
+    tokio::{
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The interval between two rounds of gossip
+const GOSSIP_INTERVAL: Duration = Duration::from_secs(1);
+/// The longest that we wait for another node to gossip back
+const EXCHANGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, PartialEq, Eq)]
+/// What a node knows about the cluster
+pub(super) struct Gossip {
+    pub(super) sender: String,
+    pub(super) epoch: u64,
+    pub(super) nodes: Vec<String>,
+    pub(super) claims: Vec<ClaimedRange>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// A range of slots claimed by a node
+pub(super) struct ClaimedRange {
+    pub(super) first: u16,
+    pub(super) last: u16,
+    /// the index of the node in [`Gossip::nodes`]
+    pub(super) node: u32,
+    pub(super) epoch: u64,
+}
+
+impl Gossip {
+    pub(super) fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self::encode_addr(&mut buf, &self.sender);
+        buf.extend(self.epoch.to_le_bytes());
+        buf.extend((self.nodes.len() as u32).to_le_bytes());
+        for node in &self.nodes {
+            self::encode_addr(&mut buf, node);
+        }
+        buf.extend((self.claims.len() as u32).to_le_bytes());
+        for claim in &self.claims {
+            buf.extend(claim.first.to_le_bytes());
+            buf.extend(claim.last.to_le_bytes());
+            buf.extend(claim.node.to_le_bytes());
+            buf.extend(claim.epoch.to_le_bytes());
+        }
+        buf
+    }
+    /// Decode a message, returning `None` if it's malformed
+    pub(super) fn decode(mut buf: &[u8]) -> Option<Self> {
+        let sender = self::decode_addr(&mut buf)?;
+        let epoch = u64::from_le_bytes(self::take(&mut buf)?);
+        let count = u32::from_le_bytes(self::take(&mut buf)?);
+        // the counts aren't trusted with an allocation either
+        let mut nodes = Vec::new();
+        for _ in 0..count {
+            nodes.push(self::decode_addr(&mut buf)?);
+        }
+        let count = u32::from_le_bytes(self::take(&mut buf)?);
+        let mut claims = Vec::new();
+        for _ in 0..count {
+            let claim = ClaimedRange {
+                first: u16::from_le_bytes(self::take(&mut buf)?),
+                last: u16::from_le_bytes(self::take(&mut buf)?),
+                node: u32::from_le_bytes(self::take(&mut buf)?),
+                epoch: u64::from_le_bytes(self::take(&mut buf)?),
+            };
+            let okay = claim.first <= claim.last
+                && (claim.last as usize) < SLOTS
+                && (claim.node as usize) < nodes.len();
+            if !okay {
+                return None;
+            }
+            claims.push(claim);
+        }
+        buf.is_empty().then_some(Self {
+            sender,
+            epoch,
+            nodes,
+            claims,
+        })
+    }
+    /// Returns the owners of the slots, as ranges of slots with the same owner
+    pub(super) fn owners(&self) -> Vec<(RangeInclusive<u16>, String)> {
+        let ranges = self.claims.iter().map(|claim| {
+            (
+                claim.first..=claim.last,
+                self.nodes[claim.node as usize].as_str(),
+            )
+        });
+        super::owners(ranges)
+    }
+}
+
+fn encode_addr(buf: &mut Vec<u8>, addr: &str) {
+    buf.extend((addr.len() as u16).to_le_bytes());
+    buf.extend(addr.as_bytes());
+}
+
+fn decode_addr(buf: &mut &[u8]) -> Option<String> {
+    let len = u16::from_le_bytes(self::take(buf)?) as usize;
+    if buf.len() < len {
+        return None;
+    }
+    let (addr, rest) = buf.split_at(len);
+    *buf = rest;
+    let addr = String::from_utf8(addr.to_vec()).ok()?;
+    super::split_addr(&addr)?;
+    Some(addr)
+}
+
+/// Split `N` bytes off the front of `buf`
+fn take<const N: usize>(buf: &mut &[u8]) -> Option<[u8; N]> {
+    if buf.len() < N {
+        return None;
+    }
+    let (taken, rest) = buf.split_at(N);
+    *buf = rest;
+    taken.try_into().ok()
+}
+
+impl Cluster {
+    /// Returns what we know about the cluster
+    pub(super) fn gossip(&self) -> Gossip {
+        let claims = self
+            .ranges()
+            .into_iter()
+            .map(|(range, claim)| ClaimedRange {
+                first: *range.start(),
+                last: *range.end(),
+                node: claim.node as u32,
+                epoch: claim.epoch,
+            })
+            .collect();
+        Gossip {
+            sender: self.nodes[MYSELF].addr.clone(),
+            epoch: self.epoch,
+            nodes: self.nodes.iter().map(|node| node.addr.clone()).collect(),
+            claims,
+        }
+    }
+    /// Learn what another node knows about the cluster
+    pub(super) fn merge(&mut self, gossip: &Gossip) {
+        let sender = self.node(&gossip.sender);
+        self.nodes[sender].last_seen = Some(Instant::now());
+        let ids: Vec<usize> = gossip.nodes.iter().map(|addr| self.node(addr)).collect();
+        for range in &gossip.claims {
+            let claim = Claim {
+                node: ids[range.node as usize],
+                epoch: range.epoch,
+            };
+            for slot in &mut self.slots[range.first as usize..=range.last as usize] {
+                let wins = match slot {
+                    Some(current) => Self::beats(&self.nodes, claim, *current),
+                    None => true,
+                };
+                if wins {
+                    *slot = Some(claim);
+                }
+            }
+        }
+        self.epoch = self.epoch.max(gossip.epoch);
+    }
+    /// A node that we met at one address goes by the address that it announces (unless we
+    /// know of another node with that address)
+    pub(super) fn rename(&mut self, addr: &str, announced: &str) {
+        if addr == announced || self.nodes.iter().any(|node| node.addr == announced) {
+            return;
+        }
+        if let Some(node) = self.nodes[1..].iter_mut().find(|node| node.addr == addr) {
+            log::info!("The cluster node at {addr} goes by {announced}");
+            node.addr = announced.to_owned();
+        }
+    }
+}
+
+/// Merge the gossip that another node sent us, and return what we know in return. Returns
+/// `None` if the message is malformed (or if we aren't running in cluster mode)
+pub fn receive(message: &[u8]) -> Option<Vec<u8>> {
+    let gossip = Gossip::decode(message)?;
+    let mut cluster = CLUSTER.write();
+    let cluster = cluster.as_mut()?;
+    cluster.merge(&gossip);
+    Some(cluster.gossip().encode())
+}
+
+/// Gossip with the other nodes till we're told to stop
+pub async fn gossiper(mut terminator: Receiver<()>) {
+    // a connection to every node that we gossip with
+    let mut links = HashMap::new();
+    loop {
+        tokio::select! {
+            _ = self::round(&mut links) => {}
+            _ = terminator.recv() => break,
+        }
+        tokio::select! {
+            _ = time::sleep(GOSSIP_INTERVAL) => {}
+            _ = terminator.recv() => break,
+        }
+    }
+    log::info!("Cluster gossip has exited");
+}
+
+/// Gossip with every other node once
+async fn round(links: &mut HashMap<String, Peer>) {
+    let (message, others, login) = match CLUSTER.read().as_ref() {
+        Some(cluster) => (
+            cluster.gossip().encode(),
+            cluster.nodes[1..]
+                .iter()
+                .map(|node| node.addr.clone())
+                .collect::<Vec<_>>(),
+            cluster.login.clone(),
+        ),
+        None => return,
+    };
+    for addr in others {
+        let connected = links.contains_key(&addr);
+        let exchange = self::exchange(links, &addr, login.as_ref(), &message);
+        let reply = match time::timeout(EXCHANGE_TIMEOUT, exchange).await {
+            Ok(Ok(reply)) => reply,
+            failed => {
+                links.remove(&addr);
+                let e = match failed {
+                    Ok(Err(e)) => e,
+                    _ => IoError::new(ErrorKind::TimedOut, "the node didn't gossip back in time"),
+                };
+                // (a node that's down is only reported when we lose it)
+                if connected {
+                    log::warn!("Lost the cluster node at {addr}: {e}");
+                } else {
+                    log::debug!("Failed to gossip with the cluster node at {addr}: {e}");
+                }
+                continue;
+            }
+        };
+        let gossip = match Gossip::decode(&reply) {
+            Some(gossip) => gossip,
+            None => {
+                log::warn!("The cluster node at {addr} sent malformed gossip");
+                links.remove(&addr);
+                continue;
+            }
+        };
+        if let Some(cluster) = CLUSTER.write().as_mut() {
+            cluster.rename(&addr, &gossip.sender);
+            cluster.merge(&gossip);
+        }
+        if gossip.sender != addr {
+            if let Some(link) = links.remove(&addr) {
+                links.insert(gossip.sender, link);
+            }
+        }
+    }
+}
+
+/// Send our gossip to a node (connecting to it if we have to), and return its gossip
+async fn exchange(
+    links: &mut HashMap<String, Peer>,
+    addr: &str,
+    login: Option<&(Vec<u8>, Vec<u8>)>,
+    message: &[u8],
+) -> IoResult<Vec<u8>> {
+    let link = match links.entry(addr.to_owned()) {
+        Entry::Occupied(link) => link.into_mut(),
+        Entry::Vacant(vacant) => {
+            let (host, port) = super::split_addr(addr)
+                .ok_or_else(|| IoError::new(ErrorKind::InvalidInput, "bad address"))?;
+            vacant.insert(peer::connect(host, port, login).await?)
+        }
+    };
+    peer::query_binary(link, &[b"SYS", b"CLUSTER", b"GOSSIP", message]).await
+}
@@ -0,0 +1,548 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Migrating slots
+//!
+//! `SYS CLUSTER MIGRATE <first> <last> <node>` moves a range of slots (that we own) to another
+//! node, along with their keys, while the keys are still being read and written:
+//! 1. Every write on a key in the slots that's applied from then on is forwarded to the other
+//! node as well (with `SYS CLUSTER APPLY <records>`), in the order that it was applied in
+//! 2. The keys in the slots are copied to the other node in batches (with `SYS CLUSTER IMPORT
+//! <archive> <deadlines>`), each batch in a [`pause`](wal::pause) of the writes. A batch
+//! replaces the keys that the other node has, and is queued with the forwarded writes, so the
+//! other node sees a write that's applied after a key was copied after the copy, and the copy
+//! has every write that was applied before it
+//! 3. Once every key has been copied, the writes are paused for as long as it takes for the
+//! other node to catch up (at most [`CUTOVER_TIMEOUT`]), and the other node claims the slots
+//! in a new epoch (with `SYS CLUSTER TAKEOVER <first> <last> <epoch>`). The writes that were
+//! held off then see that the slots have moved (and fail with `moved`), and so do the other
+//! nodes once they've heard from either of us
+//! 4. The keys in the slots are removed here (and the removals are logged and fed to the
+//! replicas like any other `DEL`)
+//!
+//! While the slots are being migrated, a query that names keys both in and outside of the
+//! slots fails with `cross-slot`, and only one migration can run at a time. If the migration
+//! fails before the other node has taken over, the slots stay with us and it fails with
+//! `err-migration-failed` (the copies that the other node has are left behind, and replaced if
+//! the migration is run again).
+//!
+//! ## Caveats
+//! - Writes that don't name keys (like `FLUSHTABLE` and `DELPREFIX`) aren't forwarded, so they
+//! should be held off during a migration
+//! - Keys that are evicted here aren't removed on the other node
+//! - A batch has to fit in the largest query that the other node accepts
+
+use {
+    super::{Claim, Cluster, CLUSTER, MYSELF},
+    crate::{
+        actions::ActionResult,
+        corestore::{
+            memstore::{Keyspace, ObjectID, SYSTEM},
+            table::Table,
+            Corestore, SharedSlice,
+        },
+        dbnet::peer::{self, Peer},
+        protocol::{interface::ProtocolSpec, Skyhash2},
+        storage::v1::{
+            export::{self, ExportError},
+            wal::{self, Durability},
+        },
+        util::err,
+        IoResult,
+    },
+    core::{
+        ops::RangeInclusive,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        io::{Error as IoError, ErrorKind},
+        sync::Arc,
+    },
+    tokio::{
+        sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+        time::{self, Duration},
+    },
+};
+
+/// The most keys that are copied in a batch
+const BATCH_KEYS: usize = 256;
+/// Forwarded writes are sent once they add up to this many bytes
+const BATCH_BYTES: usize = 4 * 1024 * 1024;
+/// The longest that the writes are held off for the other node to catch up
+const CUTOVER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set while slots are being migrated
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// The migration that's running (if any)
+static MIGRATING: Mutex<Option<Migrating>> = const_mutex(None);
+
+/// A migration that's running
+struct Migrating {
+    /// the slots that are being migrated
+    slots: RangeInclusive<u16>,
+    /// where the writes on the keys in the slots are forwarded to
+    forward: UnboundedSender<Outgoing>,
+}
+
+/// What's sent to the other node, in order
+enum Outgoing {
+    /// a write (as an encoded record of the write-ahead log)
+    Write(Vec<u8>),
+    /// a batch of keys
+    Keys(Batch),
+}
+
+/// A batch of keys from a table
+struct Batch {
+    keyspace: String,
+    table: String,
+    /// a table with just the keys in the batch
+    keys: Table,
+    /// the expiry deadlines of the keys in the batch that have one
+    deadlines: Vec<(SharedSlice, u64)>,
+}
+
+/// Set for as long as a migration runs. It ends once this is dropped
+struct Active;
+
+impl Active {
+    /// Returns `None` if another migration is running
+    fn start(slots: RangeInclusive<u16>, forward: UnboundedSender<Outgoing>) -> Option<Self> {
+        let mut migrating = MIGRATING.lock();
+        if migrating.is_some() {
+            return None;
+        }
+        *migrating = Some(Migrating { slots, forward });
+        ACTIVE.store(true, Ordering::Release);
+        Some(Self)
+    }
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::Release);
+        *MIGRATING.lock() = None;
+    }
+}
+
+/// Returns true if slots are being migrated
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+/// Returns the slots that are being migrated (if any)
+pub(super) fn migrating() -> Option<RangeInclusive<u16>> {
+    MIGRATING
+        .lock()
+        .as_ref()
+        .map(|migrating| migrating.slots.clone())
+}
+
+/// Forward a write (the queries that were run, and the record that they were logged as) to the
+/// node that the slots are being migrated to, if it names a key in them
+pub fn forward<Q: AsRef<[T]>, T: AsRef<[u8]>>(queries: &[Q], record: &[u8]) {
+    let migrating = MIGRATING.lock();
+    if let Some(migrating) = migrating.as_ref() {
+        if self::touches(&migrating.slots, queries) {
+            // (if the migration failed, it's ending anyway)
+            let _ = migrating.forward.send(Outgoing::Write(record.to_vec()));
+        }
+    }
+}
+
+/// Returns true if any of the queries names a key in the given slots
+pub(super) fn touches<Q: AsRef<[T]>, T: AsRef<[u8]>>(
+    slots: &RangeInclusive<u16>,
+    queries: &[Q],
+) -> bool {
+    queries.iter().any(|query| {
+        let mut args = query.as_ref().iter().map(AsRef::as_ref);
+        let action = match args.next() {
+            Some(action) => action.to_ascii_uppercase(),
+            None => return false,
+        };
+        super::keys_of(&action, args)
+            .into_iter()
+            .any(|key| slots.contains(&super::slot_of(key)))
+    })
+}
+
+#[derive(Debug)]
+/// Why a migration didn't run, or failed
+pub enum MigrateError {
+    /// we aren't running in cluster mode
+    Disabled,
+    /// the node isn't one that we know of (or it's our own)
+    UnknownNode,
+    /// we don't own all of the slots
+    NotOwned,
+    /// another migration is running
+    Busy,
+    /// the migration failed before the other node took over (so the slots are still ours)
+    Failed(IoError),
+}
+
+/// Migrate the given slots (that we own) to the node with the given address, along with their
+/// keys (see the [module docs](self))
+pub async fn migrate(
+    db: &Corestore,
+    slots: RangeInclusive<u16>,
+    node: &str,
+) -> Result<(), MigrateError> {
+    let (target, login) = {
+        let cluster = CLUSTER.read();
+        let cluster = cluster.as_ref().ok_or(MigrateError::Disabled)?;
+        let target = match cluster.nodes.iter().position(|known| known.addr == node) {
+            Some(target) if target != MYSELF => target,
+            _ => return Err(MigrateError::UnknownNode),
+        };
+        let owned = cluster.slots[*slots.start() as usize..=*slots.end() as usize]
+            .iter()
+            .all(|claim| matches!(claim, Some(claim) if claim.node == MYSELF));
+        if !owned {
+            return Err(MigrateError::NotOwned);
+        }
+        (target, cluster.login.clone())
+    };
+    if self::is_active() {
+        return Err(MigrateError::Busy);
+    }
+    // (the addresses of the nodes were checked when we learnt of them)
+    let (host, port) = super::split_addr(node).ok_or(MigrateError::UnknownNode)?;
+    let mut link = peer::connect(host, port, login.as_ref())
+        .await
+        .map_err(MigrateError::Failed)?;
+    let (forward, mut outgoing) = mpsc::unbounded_channel();
+    let active = {
+        // every write from here on takes its turn, so none of them can miss being forwarded
+        let _paused = wal::pause().await;
+        Active::start(slots.clone(), forward.clone()).ok_or(MigrateError::Busy)?
+    };
+    log::info!(
+        "Migrating the slots {}-{} to {node}",
+        slots.start(),
+        slots.end()
+    );
+    self::copy(db, &slots, &forward, &mut link, &mut outgoing)
+        .await
+        .map_err(MigrateError::Failed)?;
+    self::cut_over(&slots, target, &mut link, &mut outgoing, active)
+        .await
+        .map_err(MigrateError::Failed)?;
+    log::info!(
+        "Handed the slots {}-{} over to {node}",
+        slots.start(),
+        slots.end()
+    );
+    self::remove_keys(db, &slots).await;
+    Ok(())
+}
+
+/// Returns the keyspaces (other than the system keyspace)
+fn keyspaces(db: &Corestore) -> Vec<(ObjectID, Arc<Keyspace>)> {
+    db.get_store()
+        .keyspaces
+        .iter()
+        .filter(|ks| ks.key() != &SYSTEM)
+        .map(|ks| (ks.key().clone(), ks.value().clone()))
+        .collect()
+}
+
+/// Returns the keys of the table that are in the given slots
+fn keys_in(table: &Table, slots: &RangeInclusive<u16>) -> Vec<SharedSlice> {
+    table
+        .keys()
+        .into_iter()
+        .filter(|key| slots.contains(&super::slot_of(key)))
+        .collect()
+}
+
+/// Returns the durability that the writes to the table are logged with
+fn durability(table: &Table) -> Durability {
+    if table.is_volatile() {
+        Durability::None
+    } else {
+        table.durability()
+    }
+}
+
+/// Copy the keys in the slots to the other node, in batches
+async fn copy(
+    db: &Corestore,
+    slots: &RangeInclusive<u16>,
+    forward: &UnboundedSender<Outgoing>,
+    link: &mut Peer,
+    outgoing: &mut UnboundedReceiver<Outgoing>,
+) -> IoResult<()> {
+    for (ksid, keyspace) in self::keyspaces(db) {
+        let tables: Vec<(ObjectID, Arc<Table>)> = keyspace
+            .tables
+            .iter()
+            .map(|tbl| (tbl.key().clone(), tbl.value().clone()))
+            .collect();
+        for (tblid, table) in tables {
+            // the batches are kept in memory, whatever the table does with its values
+            let model = match Table::model_code_for(table.description().data, false, false) {
+                Some(model) => model,
+                None => continue,
+            };
+            for keys in self::keys_in(&table, slots).chunks(BATCH_KEYS) {
+                let paused = wal::pause().await;
+                let batch = Table::from_model_code(model, table.is_volatile())
+                    .expect("the model code was looked up");
+                let mut deadlines = Vec::new();
+                for key in keys {
+                    // (the key is skipped if it was removed since)
+                    if let Ok(Some(true)) = table.copy_key::<Skyhash2>(key, &batch, key.clone()) {
+                        if let Some(deadline) = table.deadline_of(key) {
+                            deadlines.push((key.clone(), deadline));
+                        }
+                    }
+                }
+                let _ = forward.send(Outgoing::Keys(Batch {
+                    keyspace: String::from_utf8_lossy(&ksid[..]).into_owned(),
+                    table: String::from_utf8_lossy(&tblid[..]).into_owned(),
+                    keys: batch,
+                    deadlines,
+                }));
+                drop(paused);
+                self::drain(link, outgoing).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send everything that's queued for the other node, in order
+async fn drain(link: &mut Peer, outgoing: &mut UnboundedReceiver<Outgoing>) -> IoResult<()> {
+    let mut writes = Vec::new();
+    while let Ok(next) = outgoing.try_recv() {
+        match next {
+            Outgoing::Write(record) => {
+                writes.extend(record);
+                if writes.len() >= BATCH_BYTES {
+                    self::send_writes(link, &mut writes).await?;
+                }
+            }
+            Outgoing::Keys(batch) => {
+                // the writes that were queued before the batch go first
+                self::send_writes(link, &mut writes).await?;
+                self::send_keys(link, batch).await?;
+            }
+        }
+    }
+    self::send_writes(link, &mut writes).await
+}
+
+async fn send_writes(link: &mut Peer, writes: &mut Vec<u8>) -> IoResult<()> {
+    if !writes.is_empty() {
+        peer::query(link, &[b"SYS", b"CLUSTER", b"APPLY", writes.as_slice()]).await?;
+        writes.clear();
+    }
+    Ok(())
+}
+
+async fn send_keys(link: &mut Peer, batch: Batch) -> IoResult<()> {
+    let Batch {
+        keyspace,
+        table,
+        keys,
+        deadlines,
+    } = batch;
+    let archive = tokio::task::spawn_blocking(move || export::encode(&keyspace, &table, &keys))
+        .await
+        .expect("Something caused the encoding to panic")
+        .map_err(|e| match e {
+            ExportError::Io(e) => e,
+            e => IoError::new(ErrorKind::Other, e.to_string()),
+        })?;
+    let deadlines = self::encode_deadlines(&deadlines);
+    peer::query(link, &[b"SYS", b"CLUSTER", b"IMPORT", &archive, &deadlines]).await
+}
+
+/// Hold off the writes till the other node has caught up, and hand the slots over to it. The
+/// migration ends here (with the writes still held off)
+async fn cut_over(
+    slots: &RangeInclusive<u16>,
+    target: usize,
+    link: &mut Peer,
+    outgoing: &mut UnboundedReceiver<Outgoing>,
+    active: Active,
+) -> IoResult<()> {
+    // catch up first, so that the writes are held off for as short as possible
+    self::drain(link, outgoing).await?;
+    let _paused = wal::pause().await;
+    let epoch = CLUSTER.read().as_ref().map_or(0, |cluster| cluster.epoch) + 1;
+    let (first, last) = (slots.start().to_string(), slots.end().to_string());
+    let epoch_name = epoch.to_string();
+    let handover = async {
+        self::drain(link, outgoing).await?;
+        let takeover: [&[u8]; 6] = [
+            b"SYS",
+            b"CLUSTER",
+            b"TAKEOVER",
+            first.as_bytes(),
+            last.as_bytes(),
+            epoch_name.as_bytes(),
+        ];
+        peer::query(link, &takeover).await
+    };
+    time::timeout(CUTOVER_TIMEOUT, handover)
+        .await
+        .map_err(|_| IoError::new(ErrorKind::TimedOut, "the node didn't catch up in time"))??;
+    if let Some(cluster) = CLUSTER.write().as_mut() {
+        cluster.assign(slots.clone(), target, epoch);
+    }
+    drop(active);
+    Ok(())
+}
+
+/// Remove the keys in the slots (once they've been handed over), in batches
+async fn remove_keys(db: &Corestore, slots: &RangeInclusive<u16>) {
+    for (ksid, keyspace) in self::keyspaces(db) {
+        let tables: Vec<(ObjectID, Arc<Table>)> = keyspace
+            .tables
+            .iter()
+            .map(|tbl| (tbl.key().clone(), tbl.value().clone()))
+            .collect();
+        for (tblid, table) in tables {
+            for keys in self::keys_in(&table, slots).chunks(BATCH_KEYS) {
+                let scope = wal::Scope::Table(&ksid, &tblid);
+                let turn = wal::sequence(self::durability(&table), scope).await;
+                let mut query: Vec<&[u8]> = vec![&b"DEL"[..]];
+                query.extend(
+                    keys.iter()
+                        .filter(|key| table.remove_key(key))
+                        .map(|key| &key[..]),
+                );
+                if query.len() > 1 {
+                    turn.log((Some(&ksid), Some(&tblid)), &[query]).await;
+                }
+            }
+        }
+        keyspace.mark_dirty();
+    }
+}
+
+/// Take a batch of keys that another node is migrating to us in (see `SYS CLUSTER IMPORT`).
+/// The keys replace the ones that we have, and the import is logged and fed to the replicas
+pub async fn import<P: ProtocolSpec>(
+    db: &Corestore,
+    archive: &[u8],
+    raw_deadlines: &[u8],
+) -> ActionResult<()> {
+    let (manifest, batch) = match export::decode(archive) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            log::warn!("Got a bad batch of keys: {e}");
+            return err(P::RSTRING_BAD_ARCHIVE);
+        }
+    };
+    let deadlines = match self::decode_deadlines(raw_deadlines) {
+        Some(deadlines) => deadlines,
+        None => return err(P::RSTRING_BAD_ARCHIVE),
+    };
+    let keyspace = db
+        .get_store()
+        .get_keyspace_atomic_ref(manifest.keyspace.as_bytes());
+    let table = keyspace
+        .as_ref()
+        .and_then(|ks| ks.get_table_atomic_ref(manifest.table.as_bytes()));
+    let (keyspace, table) = match (keyspace, table) {
+        (Some(keyspace), Some(table)) => (keyspace, table),
+        _ => return err(P::RSTRING_CONTAINER_NOT_FOUND),
+    };
+    if table.description().data != manifest.data {
+        return err(P::RSTRING_WRONG_MODEL);
+    }
+    let scope = wal::Scope::Table(manifest.keyspace.as_bytes(), manifest.table.as_bytes());
+    let turn = wal::sequence(self::durability(&table), scope).await;
+    for key in batch.keys() {
+        table.remove_key(&key);
+        batch.move_key::<P>(&key, &table)?;
+    }
+    for (key, deadline) in deadlines {
+        table.set_deadline(key, deadline);
+    }
+    keyspace.mark_dirty();
+    let query: [&[u8]; 5] = [b"SYS", b"CLUSTER", b"IMPORT", archive, raw_deadlines];
+    turn.log((None, None), &[query]).await;
+    Ok(())
+}
+
+/// Claim the given slots (that another node is migrating to us) in the given epoch. Returns
+/// false if we aren't running in cluster mode
+pub fn take_over(slots: RangeInclusive<u16>, epoch: u64) -> bool {
+    let mut cluster = CLUSTER.write();
+    match cluster.as_mut() {
+        Some(cluster) => {
+            cluster.assign(slots.clone(), MYSELF, epoch);
+            log::info!(
+                "Took over the slots {}-{} in epoch {epoch}",
+                slots.start(),
+                slots.end()
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+impl Cluster {
+    /// Give the slots to a node, with a claim made in the given epoch
+    pub(super) fn assign(&mut self, slots: RangeInclusive<u16>, node: usize, epoch: u64) {
+        let claim = Claim { node, epoch };
+        self.slots[*slots.start() as usize..=*slots.end() as usize].fill(Some(claim));
+        self.epoch = self.epoch.max(epoch);
+    }
+}
+
+/// Encode the deadlines of the keys in a batch, each as `[u32 length][key][u64 deadline]` (in
+/// little endian)
+pub(super) fn encode_deadlines(deadlines: &[(SharedSlice, u64)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (key, deadline) in deadlines {
+        buf.extend((key.len() as u32).to_le_bytes());
+        buf.extend_from_slice(key);
+        buf.extend(deadline.to_le_bytes());
+    }
+    buf
+}
+
+/// Decode the deadlines of the keys in a batch, returning `None` if they're malformed
+pub(super) fn decode_deadlines(mut buf: &[u8]) -> Option<Vec<(&[u8], u64)>> {
+    let mut deadlines = Vec::new();
+    while !buf.is_empty() {
+        let len = u32::from_le_bytes(buf.get(..4)?.try_into().ok()?) as usize;
+        buf = &buf[4..];
+        let key = buf.get(..len)?;
+        let deadline = u64::from_le_bytes(buf.get(len..len + 8)?.try_into().ok()?);
+        buf = &buf[len + 8..];
+        deadlines.push((key, deadline));
+    }
+    Some(deadlines)
+}
@@ -0,0 +1,458 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Cluster mode
+//!
+//! In cluster mode, the keys are split across a number of servers (the nodes). Every key
+//! belongs to one of [`SLOTS`] hash slots, and every slot is owned by one node, which is the only
+//! one that keeps its keys. The slot of a key is the CRC16 (XMODEM) of the key, modulo the
+//! number of slots. If the key has a hash tag (a non-empty part between the first `{` and the
+//! `}` after it), only the tag is hashed, so that related keys (like `{user1}.name` and
+//! `{user1}.email`) end up in the same slot. The key of a qualified key
+//! (`<keyspace>:<table>:<key>`) is hashed without the keyspace and the table.
+//!
+//! A server runs in cluster mode if it has an address to announce to the other nodes (and to
+//! clients) with `cluster.announce`. The address is also the name of the node.
+//!
+//! ## Redirections
+//! A query that names keys is run only if all of them are owned by this node. Otherwise it
+//! fails with:
+//! - `115 moved <slot> <host>:<port>` if they're all owned by another node, which the client
+//! should send the query to (and the queries for that slot from then on)
+//! - `116 cross-slot` if they're owned by more than one node
+//! - `117 cluster-down` if a slot isn't owned by any node
+//!
+//! Queries that don't name keys (like `DBSIZE`, `LSKEYS`, `SCAN` and the `SYS` actions) run on
+//! the node that they're sent to, and only see its keys. BlueQL statements (which change the
+//! schema) aren't redirected either, so keyspaces and tables have to be created on every node.
+//! `SYS CLUSTER KEYSLOT <key>` returns the slot of a key and `SYS CLUSTER SLOTS` returns the
+//! owners of the slots, so that clients can route their queries to the right node to begin
+//! with.
+//!
+//! ## Membership and slots
+//! - `SYS CLUSTER MEET <host> <port> [<user> <token>]` adds a node. The nodes log in on each
+//! other as the user given to the last `MEET` (if auth is enabled, it has to exist on every
+//! node)
+//! - `SYS CLUSTER ADDSLOTS <first> [<last>]` makes this node the owner of a range of slots that
+//! isn't owned by another node
+//! - `SYS CLUSTER INFO` returns how the cluster is doing, as pairs of names and values
+//! - `SYS CLUSTER MIGRATE <first> <last> <node>` moves a range of slots that this node owns
+//! to another node, along with their keys, without holding off the queries on them for more
+//! than a moment (see [`migration`])
+//!
+//! ## Gossip
+//! Every second, every node sends what it knows to every other node that it knows of (with
+//! `SYS CLUSTER GOSSIP`), and gets what that node knows in return (see [`gossip`]): the nodes,
+//! and the owners of the slots. So a node that's met by one node is soon known to all of them.
+//! Every claim on a slot carries an epoch (which goes up with every claim made in the cluster),
+//! and the claim with the highest epoch wins (or the one of the node with the greater name,
+//! for claims made in the same epoch).
+//!
+//! ## Caveats
+//! - The cluster state isn't kept across restarts. A node that restarts learns it again from
+//! the other nodes (including the slots that it owns); if every node restarts, the slots have
+//! to be added again
+//! - Nodes aren't removed, and the slots of a node that's gone aren't moved elsewhere (slots
+//! can only be migrated by the node that owns them)
+//! - The nodes connect to each other over plain TCP, so they need a port without TLS
+
+use {
+    crate::{
+        actions::{ActionError, ActionResult},
+        blueql::util::split_qualified_key,
+        config::ClusterConfig,
+        protocol::interface::ProtocolSpec,
+        util::err,
+    },
+    core::{
+        ops::RangeInclusive,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+    parking_lot::{const_rwlock, RwLock},
+    std::time::{Duration, Instant},
+};
+
+pub mod backup;
+pub mod gossip;
+pub mod migration;
+#[cfg(test)]
+mod tests;
+
+/// The number of hash slots
+pub const SLOTS: usize = 16384;
+/// The index of our own node
+const MYSELF: usize = 0;
+/// A node that we haven't heard from for this long is unreachable
+const NODE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set if we're running in cluster mode
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// What we know about the cluster (if we're running in cluster mode)
+static CLUSTER: RwLock<Option<Cluster>> = const_rwlock(None);
+
+/// Start cluster mode if it's configured
+pub fn init(config: &ClusterConfig) {
+    if let Some(announce) = &config.announce {
+        log::info!("Running in cluster mode as {announce}");
+        *CLUSTER.write() = Some(Cluster::new(announce.clone()));
+        ENABLED.store(true, Ordering::Release);
+    }
+}
+
+/// Returns true if we're running in cluster mode
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Returns the hash slot of a key
+pub fn slot_of(key: &[u8]) -> u16 {
+    let key = match split_qualified_key(key) {
+        Some(qualified) => qualified.key,
+        None => key,
+    };
+    let hashed = match key.iter().position(|b| *b == b'{') {
+        Some(open) => match key[open + 1..].iter().position(|b| *b == b'}') {
+            Some(len) if len != 0 => &key[open + 1..open + 1 + len],
+            _ => key,
+        },
+        None => key,
+    };
+    self::crc16(hashed) % SLOTS as u16
+}
+
+/// CRC16 (XMODEM)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Returns the keys that the (uppercased) action is run on, among its arguments
+fn keys_of<'a>(action: &[u8], args: impl Iterator<Item = &'a [u8]>) -> Vec<&'a [u8]> {
+    match action {
+        // these don't name keys (or they run on all of them)
+        b"HEYA" | b"AUTH" | b"SYS" | b"WHEREAMI" | b"MULTI" | b"EXEC" | b"DISCARD" | b"SCRIPT"
+        | b"EVAL" | b"SUBSCRIBE" | b"UNSUBSCRIBE" | b"PUBLISH" | b"NOTIFY" | b"MKSNAP"
+        | b"SNAPSHOT" | b"DBSIZE" | b"MEMUSAGE" | b"LSKEYS" | b"SCAN" | b"KEYS" | b"RANDOMKEY"
+        | b"SAMPLE" | b"FLUSHDB" | b"FLUSHTABLE" | b"DELPREFIX" => Vec::new(),
+        // every argument is a key
+        b"DEL" | b"MGET" | b"GETMANY" | b"SDEL" | b"MPOP" | b"SUNION" | b"SINTER" | b"SDIFF"
+        | b"PFCOUNT" | b"PFMERGE" => args.collect(),
+        b"EXISTS" => {
+            let mut keys: Vec<&[u8]> = args.collect();
+            if keys.len() > 1 && keys[keys.len() - 1].eq_ignore_ascii_case(b"WITHTTL") {
+                keys.pop();
+            }
+            keys
+        }
+        // keys and values
+        b"MSET" | b"SETMANY" | b"USET" | b"MUPDATE" | b"SSET" | b"SUPDATE" => {
+            args.step_by(2).collect()
+        }
+        b"MSETEX" => args.skip(1).step_by(2).collect(),
+        // a key and its new name
+        b"RENAME" | b"COPY" => args.take(2).collect(),
+        // the rest are run on the key that comes first
+        _ => args.take(1).collect(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The keys of a query are owned by another node
+pub struct Moved {
+    slot: u16,
+    node: Box<str>,
+}
+
+impl Moved {
+    pub const fn slot(&self) -> u16 {
+        self.slot
+    }
+    /// The address of the node that owns the slot
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+}
+
+/// The cluster hook: check that the keys of the (uppercased) action are owned by this node
+pub fn check_route<'a, P: ProtocolSpec>(
+    action: &[u8],
+    args: impl Iterator<Item = &'a [u8]>,
+) -> ActionResult<()> {
+    let cluster = CLUSTER.read();
+    let cluster = match cluster.as_ref() {
+        Some(cluster) => cluster,
+        None => return Ok(()),
+    };
+    // the keys have to be either all in or all outside of the slots that are being migrated
+    let migrating = migration::is_active().then(migration::migrating).flatten();
+    let mut owner = None;
+    let mut moving = None;
+    for key in self::keys_of(action, args) {
+        let slot = self::slot_of(key);
+        let node = match cluster.slots[slot as usize] {
+            Some(claim) => claim.node,
+            None => return err(P::RSTRING_CLUSTER_DOWN),
+        };
+        match owner {
+            None => owner = Some((slot, node)),
+            Some((_, owner)) if owner != node => return err(P::RSTRING_CROSS_SLOT),
+            Some(_) => {}
+        }
+        if let Some(migrating) = &migrating {
+            let inside = migrating.contains(&slot);
+            match moving {
+                None => moving = Some(inside),
+                Some(moving) if moving != inside => return err(P::RSTRING_CROSS_SLOT),
+                Some(_) => {}
+            }
+        }
+    }
+    match owner {
+        Some((slot, node)) if node != MYSELF => Err(ActionError::Moved(Moved {
+            slot,
+            node: cluster.nodes[node].addr.as_str().into(),
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the host and the port of an address (`<host>:<port>`)
+pub fn split_addr(addr: &str) -> Option<(&str, u16)> {
+    let (host, port) = addr.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    (!host.is_empty()).then_some((host, port))
+}
+
+/// A node that we know of
+struct Node {
+    /// the address of the node (which is also its name)
+    addr: String,
+    /// when we last heard from it
+    last_seen: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The claim of a node on a slot
+struct Claim {
+    /// the index of the node
+    node: usize,
+    /// the epoch that the claim was made in
+    epoch: u64,
+}
+
+/// What we know about the cluster
+struct Cluster {
+    /// the nodes that we know of (our own node comes first)
+    nodes: Vec<Node>,
+    /// the claim on every slot (if there's one)
+    slots: Box<[Option<Claim>]>,
+    /// the highest epoch that we've seen
+    epoch: u64,
+    /// the user and the token that we log in on the other nodes with (if any)
+    login: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Cluster {
+    fn new(myself: String) -> Self {
+        Self {
+            nodes: vec![Node {
+                addr: myself,
+                last_seen: None,
+            }],
+            slots: vec![None; SLOTS].into_boxed_slice(),
+            epoch: 0,
+            login: None,
+        }
+    }
+    /// Returns the index of the node with the given address, adding it if we didn't know of it
+    fn node(&mut self, addr: &str) -> usize {
+        match self.nodes.iter().position(|node| node.addr == addr) {
+            Some(idx) => idx,
+            None => {
+                log::info!("Met the cluster node at {addr}");
+                self.nodes.push(Node {
+                    addr: addr.to_owned(),
+                    last_seen: None,
+                });
+                self.nodes.len() - 1
+            }
+        }
+    }
+    /// Claim the given slots for our own node, in a new epoch. Nothing is claimed (and false
+    /// is returned) if any of them is owned by another node
+    fn claim(&mut self, slots: RangeInclusive<u16>) -> bool {
+        let taken = self.slots[*slots.start() as usize..=*slots.end() as usize]
+            .iter()
+            .flatten()
+            .any(|claim| claim.node != MYSELF);
+        if taken {
+            return false;
+        }
+        self.assign(slots, MYSELF, self.epoch + 1);
+        true
+    }
+    /// Returns true if the claim `new` wins over the claim `old`
+    fn beats(nodes: &[Node], new: Claim, old: Claim) -> bool {
+        (new.epoch, &nodes[new.node].addr) > (old.epoch, &nodes[old.node].addr)
+    }
+    /// Returns the slots in ranges that have the same claim
+    fn ranges(&self) -> Vec<(RangeInclusive<u16>, Claim)> {
+        let mut ranges: Vec<(RangeInclusive<u16>, Claim)> = Vec::new();
+        for (slot, claim) in self.slots.iter().enumerate() {
+            let claim = match claim {
+                Some(claim) => *claim,
+                None => continue,
+            };
+            let slot = slot as u16;
+            match ranges.last_mut() {
+                Some((range, last)) if *last == claim && *range.end() + 1 == slot => {
+                    *range = *range.start()..=slot;
+                }
+                _ => ranges.push((slot..=slot, claim)),
+            }
+        }
+        ranges
+    }
+}
+
+/// Add the node at the given address, and log in on the nodes with the given user and token
+/// from then on (if they're there). Returns false if we aren't running in cluster mode
+pub fn meet(addr: &str, login: Option<(Vec<u8>, Vec<u8>)>) -> bool {
+    let mut cluster = CLUSTER.write();
+    let cluster = match cluster.as_mut() {
+        Some(cluster) => cluster,
+        None => return false,
+    };
+    cluster.node(addr);
+    if login.is_some() {
+        cluster.login = login;
+    }
+    true
+}
+
+/// The result of claiming slots
+pub enum AddSlots {
+    Added,
+    /// another node owns some of the slots
+    Taken,
+    /// we aren't running in cluster mode
+    Disabled,
+}
+
+/// Make our own node the owner of the given slots (see [`Cluster::claim`])
+pub fn add_slots(slots: RangeInclusive<u16>) -> AddSlots {
+    let mut cluster = CLUSTER.write();
+    match cluster.as_mut() {
+        Some(cluster) if cluster.claim(slots.clone()) => {
+            log::info!(
+                "Claimed the slots {}-{} in epoch {}",
+                slots.start(),
+                slots.end(),
+                cluster.epoch
+            );
+            AddSlots::Added
+        }
+        Some(_) => AddSlots::Taken,
+        None => AddSlots::Disabled,
+    }
+}
+
+/// Returns the owners of the slots, as ranges of slots with the same owner (if we're running
+/// in cluster mode)
+pub fn slots() -> Option<Vec<(RangeInclusive<u16>, String)>> {
+    let cluster = CLUSTER.read();
+    let cluster = cluster.as_ref()?;
+    let ranges = cluster.ranges().into_iter();
+    Some(self::owners(ranges.map(|(range, claim)| {
+        (range, cluster.nodes[claim.node].addr.as_str())
+    })))
+}
+
+/// Merge the adjacent ranges of slots (in order) that have the same owner
+fn owners<'a>(
+    ranges: impl Iterator<Item = (RangeInclusive<u16>, &'a str)>,
+) -> Vec<(RangeInclusive<u16>, String)> {
+    let mut slots: Vec<(RangeInclusive<u16>, String)> = Vec::new();
+    for (range, node) in ranges {
+        match slots.last_mut() {
+            // the claims of a node that were made in different epochs are merged
+            Some((last, owner)) if owner == node && *last.end() + 1 == *range.start() => {
+                *last = *last.start()..=*range.end();
+            }
+            _ => slots.push((range, node.to_owned())),
+        }
+    }
+    slots
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// How the cluster is doing (see `SYS CLUSTER INFO`)
+pub struct Info {
+    /// the address of our own node
+    pub myself: String,
+    /// the highest epoch that we've seen
+    pub epoch: u64,
+    /// the number of nodes that we know of (including our own)
+    pub nodes: usize,
+    /// the number of nodes that we heard from recently (including our own)
+    pub reachable: usize,
+    /// the number of slots that have an owner
+    pub assigned: usize,
+    /// the number of slots that we own
+    pub owned: usize,
+}
+
+/// Returns how the cluster is doing (if we're running in cluster mode)
+pub fn info() -> Option<Info> {
+    let cluster = CLUSTER.read();
+    let cluster = cluster.as_ref()?;
+    let reachable = cluster.nodes[1..]
+        .iter()
+        .filter(|node| {
+            node.last_seen
+                .map_or(false, |seen| seen.elapsed() < NODE_TIMEOUT)
+        })
+        .count();
+    let claims = cluster.slots.iter().flatten();
+    Some(Info {
+        myself: cluster.nodes[MYSELF].addr.clone(),
+        epoch: cluster.epoch,
+        nodes: cluster.nodes.len(),
+        reachable: reachable + 1,
+        assigned: claims.clone().count(),
+        owned: claims.filter(|claim| claim.node == MYSELF).count(),
+    })
+}
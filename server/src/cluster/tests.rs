@@ -0,0 +1,283 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{
+        backup,
+        gossip::{ClaimedRange, Gossip},
+        migration, Claim, Cluster, Moved, MYSELF,
+    },
+    crate::{
+        corestore::SharedSlice,
+        protocol::{responses, Skyhash2},
+    },
+};
+
+#[test]
+fn test_crc16() {
+    assert_eq!(super::crc16(b"123456789"), 0x31C3);
+}
+
+#[test]
+fn test_slot_of() {
+    assert_eq!(super::slot_of(b"foo"), 12182);
+    assert_eq!(super::slot_of(b"bar"), 5061);
+    // only the hash tag is hashed
+    assert_eq!(super::slot_of(b"{user1}.name"), super::slot_of(b"user1"));
+    assert_eq!(
+        super::slot_of(b"{user1}.name"),
+        super::slot_of(b"{user1}.email")
+    );
+    // an empty tag isn't a tag
+    assert_eq!(super::slot_of(b"{}foo"), super::crc16(b"{}foo") % 16384);
+    // the entity isn't hashed
+    assert_eq!(super::slot_of(b"@myks.mytbl:foo"), 12182);
+}
+
+#[test]
+fn test_keys_of() {
+    let args: [&[u8]; 4] = [b"a", b"1", b"b", b"2"];
+    assert_eq!(super::keys_of(b"GET", args.into_iter()), vec![&b"a"[..]]);
+    assert_eq!(
+        super::keys_of(b"MSET", args.into_iter()),
+        vec![&b"a"[..], b"b"]
+    );
+    assert_eq!(super::keys_of(b"DEL", args.into_iter()).len(), 4);
+    assert!(super::keys_of(b"DBSIZE", args.into_iter()).is_empty());
+    let args: [&[u8]; 3] = [b"a", b"b", b"withttl"];
+    assert_eq!(
+        super::keys_of(b"EXISTS", args.into_iter()),
+        vec![&b"a"[..], b"b"]
+    );
+}
+
+#[test]
+fn test_claim() {
+    let mut cluster = Cluster::new("127.0.0.1:2003".to_owned());
+    assert!(cluster.claim(0..=99));
+    assert_eq!(cluster.epoch, 1);
+    // claiming our own slots again is fine
+    assert!(cluster.claim(50..=149));
+    let other = cluster.node("127.0.0.1:2004");
+    cluster.slots[200] = Some(Claim {
+        node: other,
+        epoch: 3,
+    });
+    assert!(!cluster.claim(150..=200));
+    assert!(cluster.slots[150].is_none());
+    let ranges = cluster.ranges();
+    assert_eq!(ranges.len(), 3);
+    assert_eq!(ranges[0].0, 0..=49);
+    assert_eq!(ranges[1].0, 50..=149);
+    assert_eq!(ranges[2].0, 200..=200);
+}
+
+#[test]
+fn test_gossip_roundtrip() {
+    let mut cluster = Cluster::new("127.0.0.1:2003".to_owned());
+    cluster.node("127.0.0.1:2004");
+    cluster.claim(0..=8191);
+    let gossip = cluster.gossip();
+    assert_eq!(gossip.nodes.len(), 2);
+    assert_eq!(
+        gossip.claims,
+        vec![ClaimedRange {
+            first: 0,
+            last: 8191,
+            node: 0,
+            epoch: 1,
+        }]
+    );
+    let encoded = gossip.encode();
+    assert_eq!(Gossip::decode(&encoded), Some(gossip));
+    // truncated or with junk at the end
+    assert!(Gossip::decode(&encoded[..encoded.len() - 1]).is_none());
+    let mut junk = encoded;
+    junk.push(0);
+    assert!(Gossip::decode(&junk).is_none());
+}
+
+#[test]
+fn test_gossip_bad_claim() {
+    let gossip = Gossip {
+        sender: "127.0.0.1:2004".to_owned(),
+        epoch: 1,
+        nodes: vec!["127.0.0.1:2004".to_owned()],
+        claims: vec![ClaimedRange {
+            first: 0,
+            last: 10,
+            node: 1,
+            epoch: 1,
+        }],
+    };
+    assert!(Gossip::decode(&gossip.encode()).is_none());
+}
+
+#[test]
+fn test_merge() {
+    let mut cluster = Cluster::new("127.0.0.1:2003".to_owned());
+    cluster.claim(0..=99);
+    let gossip = Gossip {
+        sender: "127.0.0.1:2004".to_owned(),
+        epoch: 5,
+        nodes: vec!["127.0.0.1:2004".to_owned(), "127.0.0.1:2005".to_owned()],
+        claims: vec![
+            // beats our claim, since it was made in a later epoch
+            ClaimedRange {
+                first: 50,
+                last: 149,
+                node: 0,
+                epoch: 2,
+            },
+            // and so does this, since it was made in the same epoch by a node with a greater name
+            ClaimedRange {
+                first: 0,
+                last: 9,
+                node: 0,
+                epoch: 1,
+            },
+            ClaimedRange {
+                first: 150,
+                last: 159,
+                node: 1,
+                epoch: 5,
+            },
+        ],
+    };
+    cluster.merge(&gossip);
+    assert_eq!(cluster.nodes.len(), 3);
+    assert!(cluster.nodes[1].last_seen.is_some());
+    assert!(cluster.nodes[2].last_seen.is_none());
+    assert_eq!(cluster.epoch, 5);
+    assert_eq!(cluster.slots[0].unwrap().node, 1);
+    assert_eq!(cluster.slots[10].unwrap().node, MYSELF);
+    assert_eq!(cluster.slots[49].unwrap().node, MYSELF);
+    assert_eq!(cluster.slots[50].unwrap().node, 1);
+    assert_eq!(cluster.slots[150].unwrap().node, 2);
+    // a node that we met at another address
+    cluster.node("localhost:2006");
+    cluster.rename("localhost:2006", "127.0.0.1:2006");
+    assert_eq!(cluster.nodes[3].addr, "127.0.0.1:2006");
+}
+
+#[test]
+fn test_moved_response() {
+    let moved = Moved {
+        slot: 3999,
+        node: "10.0.0.2:2003".into(),
+    };
+    assert_eq!(
+        responses::moved::<Skyhash2>(&moved),
+        responses::structured_error::<Skyhash2>(
+            responses::ERRCODE_MOVED,
+            "moved 3999 10.0.0.2:2003"
+        )
+    );
+}
+
+#[test]
+fn test_migration_touches() {
+    // "foo" is in slot 12182 and "bar" in slot 5061
+    let slots = 12000..=12999;
+    let write: [&[u8]; 3] = [b"set", b"foo", b"1"];
+    assert!(migration::touches(&slots, &[write]));
+    let write: [&[u8]; 3] = [b"set", b"bar", b"1"];
+    assert!(!migration::touches(&slots, &[write]));
+    // any of the queries in a transaction
+    let txn: [Vec<&[u8]>; 4] = [
+        vec![&b"MULTI"[..]],
+        vec![&b"DEL"[..], b"bar"],
+        vec![&b"INCR"[..], b"foo"],
+        vec![&b"EXEC"[..]],
+    ];
+    assert!(migration::touches(&slots, &txn));
+    // the values aren't keys
+    let write: [&[u8]; 5] = [b"MSET", b"bar", b"foo", b"baz", b"foo"];
+    assert!(!migration::touches(&slots, &[write]));
+}
+
+#[test]
+fn test_migration_deadlines() {
+    let deadlines = vec![
+        (SharedSlice::from("foo"), 1_700_000_000_000),
+        (SharedSlice::from(""), 42),
+    ];
+    let encoded = migration::encode_deadlines(&deadlines);
+    assert_eq!(
+        migration::decode_deadlines(&encoded).unwrap(),
+        vec![(&b"foo"[..], 1_700_000_000_000), (&b""[..], 42)]
+    );
+    assert!(migration::decode_deadlines(&[]).unwrap().is_empty());
+    // truncated
+    assert!(migration::decode_deadlines(&encoded[..encoded.len() - 1]).is_none());
+    assert!(migration::decode_deadlines(&[3, 0, 0]).is_none());
+}
+
+#[test]
+fn test_gossip_owners() {
+    let gossip = Gossip {
+        sender: "127.0.0.1:2003".to_owned(),
+        epoch: 3,
+        nodes: vec!["127.0.0.1:2003".to_owned(), "127.0.0.1:2004".to_owned()],
+        claims: vec![
+            ClaimedRange {
+                first: 0,
+                last: 9,
+                node: 0,
+                epoch: 1,
+            },
+            // claimed by the same node in another epoch
+            ClaimedRange {
+                first: 10,
+                last: 19,
+                node: 0,
+                epoch: 3,
+            },
+            ClaimedRange {
+                first: 20,
+                last: 29,
+                node: 1,
+                epoch: 2,
+            },
+        ],
+    };
+    assert_eq!(
+        gossip.owners(),
+        vec![
+            (0..=19, "127.0.0.1:2003".to_owned()),
+            (20..=29, "127.0.0.1:2004".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn test_backup_name() {
+    assert!(backup::is_valid_name(b"nightly-2026_10_15"));
+    assert!(!backup::is_valid_name(b""));
+    assert!(!backup::is_valid_name(b"../nightly"));
+    assert!(!backup::is_valid_name(b"night ly"));
+    assert!(!backup::is_valid_name(&[b'a'; 65]));
+}
@@ -95,6 +95,18 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
     );
     fcli!(server_mode, matches.value_of("mode"), "--mode");
     fcli!(server_maxcon, matches.value_of("maxcon"), "--maxcon");
+    fcli!(server_shards, matches.value_of("shards"), "--shards");
+    fcli!(server_loglevel, matches.value_of("loglevel"), "--loglevel");
+    fcli!(
+        server_logformat,
+        matches.value_of("logformat"),
+        "--logformat"
+    );
+    fcli!(
+        server_qualified_keys,
+        Flag::<true>::new(matches.is_present("qualified-keys")),
+        "--qualified-keys"
+    );
     // bgsave settings
     fcli!(
         bgsave_settings,
@@ -125,7 +137,9 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         Flag::<true>::new(matches.is_present("sslonly")),
         "--sslonly",
         matches.value_of("tlspass"),
-        "--tlspassin"
+        "--tlspassin",
+        matches.value_of("sslclientca"),
+        "--sslclientca"
     );
     // auth settings
     fcli!(
@@ -133,5 +147,112 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         matches.value_of("authkey"),
         "--auth-origin-key"
     );
+    // HTTP gateway settings
+    fcli!(
+        http_settings,
+        matches.value_of("httpport"),
+        "--http-port",
+        Flag::<true>::new(matches.is_present("httpsecure")),
+        "--http-secure"
+    );
+    // connection and query limits
+    fcli!(
+        limits_settings,
+        matches.value_of("maxquerysize"),
+        "--max-query-size",
+        matches.value_of("maxqueryargs"),
+        "--max-query-args",
+        Flag::<true>::new(matches.is_present("closeonviolation")),
+        "--close-on-violation",
+        matches.value_of("idletimeout"),
+        "--idle-timeout",
+        matches.value_of("keepalive"),
+        "--keepalive"
+    );
+    fcli!(
+        admission_settings,
+        matches.value_of("maxconperip"),
+        "--max-con-per-ip",
+        matches.value_of("maxconrate"),
+        "--max-con-rate"
+    );
+    // query rate limits
+    fcli!(
+        ratelimit_settings,
+        matches.value_of("ratelimitqueries"),
+        "--ratelimit-queries",
+        matches.value_of("ratelimitusers"),
+        "--ratelimit-users"
+    );
+    // audit log
+    fcli!(
+        audit_settings,
+        matches.value_of("auditlog"),
+        "--audit-log",
+        matches.value_of("auditmaxsize"),
+        "--audit-maxsize",
+        matches.value_of("auditkeep"),
+        "--audit-keep"
+    );
+    // memory limit
+    fcli!(
+        memory_settings,
+        matches.value_of("maxmemory"),
+        "--maxmemory",
+        matches.value_of("maxmemorypolicy"),
+        "--maxmemory-policy"
+    );
+    // write-ahead log
+    fcli!(
+        wal_settings,
+        Flag::<true>::new(matches.is_present("wal")),
+        "--wal",
+        matches.value_of("walfsync"),
+        "--wal-fsync"
+    );
+    // encryption at rest
+    fcli!(
+        encryption_settings,
+        matches.value_of("encryptionkeyfile"),
+        "--encryption-keyfile",
+        matches.value_of("encryptionkeycommand"),
+        "--encryption-keycommand"
+    );
+    // compaction
+    fcli!(
+        compaction_settings,
+        matches.value_of("compactionevery"),
+        "--compaction-every"
+    );
+    // replication
+    fcli!(
+        replication_settings,
+        matches.value_of("replicareadonly"),
+        "--replica-read-only"
+    );
+    // cluster
+    fcli!(
+        cluster_settings,
+        matches.value_of("clusterannounce"),
+        "--cluster-announce"
+    );
+    // change data capture
+    fcli!(
+        cdc_settings,
+        Flag::<true>::new(matches.is_present("cdc")),
+        "--cdc",
+        matches.value_of("cdcbacklog"),
+        "--cdc-backlog",
+        matches.value_of("cdcfile"),
+        "--cdc-file"
+    );
+    // table exports
+    fcli!(
+        export_settings,
+        matches.value_of("exportdir"),
+        "--export-dir",
+        Flag::<true>::new(matches.is_present("exportallownoauth")),
+        "--export-allow-noauth"
+    );
     defset
 }
@@ -50,7 +50,11 @@ pub(super) fn parse_env_config() -> Configset {
     fenv!(server_tcp, SKY_SYSTEM_HOST, SKY_SYSTEM_PORT);
     fenv!(server_noart, SKY_SYSTEM_NOART);
     fenv!(server_maxcon, SKY_SYSTEM_MAXCON);
+    fenv!(server_shards, SKY_SYSTEM_SHARDS);
     fenv!(server_mode, SKY_DEPLOY_MODE);
+    fenv!(server_loglevel, SKY_SYSTEM_LOGLEVEL);
+    fenv!(server_logformat, SKY_SYSTEM_LOGFORMAT);
+    fenv!(server_qualified_keys, SKY_SYSTEM_QUALIFIED_KEYS);
     // bgsave settings
     fenv!(bgsave_settings, SKY_BGSAVE_ENABLED, SKY_BGSAVE_DURATION);
     // snapshot settings
@@ -67,8 +71,58 @@ pub(super) fn parse_env_config() -> Configset {
         SKY_TLS_CERT,
         SKY_TLS_PORT,
         SKY_TLS_ONLY,
-        SKY_TLS_PASSIN
+        SKY_TLS_PASSIN,
+        SKY_TLS_CLIENT_CA
     );
     fenv!(auth_settings, SKY_AUTH_ORIGIN_KEY);
+    // HTTP gateway settings
+    fenv!(http_settings, SKY_HTTP_PORT, SKY_HTTP_SECURE);
+    // connection and query limits
+    fenv!(
+        limits_settings,
+        SKY_LIMITS_MAX_QUERY_SIZE,
+        SKY_LIMITS_MAX_QUERY_ARGS,
+        SKY_LIMITS_CLOSE_ON_VIOLATION,
+        SKY_LIMITS_IDLE_TIMEOUT,
+        SKY_LIMITS_KEEPALIVE
+    );
+    fenv!(
+        admission_settings,
+        SKY_LIMITS_MAX_CON_PER_IP,
+        SKY_LIMITS_MAX_CON_RATE
+    );
+    // query rate limits
+    fenv!(
+        ratelimit_settings,
+        SKY_RATELIMIT_QUERIES,
+        SKY_RATELIMIT_USERS
+    );
+    // audit log
+    fenv!(
+        audit_settings,
+        SKY_AUDIT_LOG,
+        SKY_AUDIT_MAXSIZE,
+        SKY_AUDIT_KEEP
+    );
+    // memory limit
+    fenv!(memory_settings, SKY_MEMORY_MAXMEMORY, SKY_MEMORY_POLICY);
+    // write-ahead log
+    fenv!(wal_settings, SKY_WAL_ENABLED, SKY_WAL_FSYNC);
+    // encryption at rest
+    fenv!(
+        encryption_settings,
+        SKY_ENCRYPTION_KEYFILE,
+        SKY_ENCRYPTION_KEYCOMMAND
+    );
+    // compaction
+    fenv!(compaction_settings, SKY_COMPACTION_EVERY);
+    // replication
+    fenv!(replication_settings, SKY_REPLICA_READ_ONLY);
+    // cluster
+    fenv!(cluster_settings, SKY_CLUSTER_ANNOUNCE);
+    // change data capture
+    fenv!(cdc_settings, SKY_CDC_ENABLED, SKY_CDC_BACKLOG, SKY_CDC_FILE);
+    // table exports
+    fenv!(export_settings, SKY_EXPORT_DIR, SKY_EXPORT_ALLOW_NOAUTH);
     defset
 }
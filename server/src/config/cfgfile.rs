@@ -27,10 +27,10 @@
 use {
     super::{
         AuthSettings, ConfigSourceParseResult, Configset, Modeset, OptString, ProtocolVersion,
-        TryFromConfigSource,
+        SnapshotSinkConfig, TryFromConfigSource, UserBudgets,
     },
     serde::Deserialize,
-    std::net::IpAddr,
+    std::{collections::BTreeMap, net::IpAddr},
 };
 
 /// This struct is an _object representation_ used for parsing the TOML file
@@ -46,6 +46,30 @@ pub struct Config {
     pub(super) ssl: Option<KeySslOpts>,
     /// auth settings
     pub(super) auth: Option<AuthSettings>,
+    /// HTTP gateway settings
+    pub(super) http: Option<ConfigKeyHttp>,
+    /// Query limits
+    pub(super) limits: Option<ConfigKeyLimits>,
+    /// Query rate limits
+    pub(super) ratelimit: Option<ConfigKeyRateLimit>,
+    /// Audit log
+    pub(super) audit: Option<ConfigKeyAudit>,
+    /// Memory limit
+    pub(super) memory: Option<ConfigKeyMemory>,
+    /// Write-ahead log
+    pub(super) wal: Option<ConfigKeyWal>,
+    /// Encryption at rest
+    pub(super) encryption: Option<ConfigKeyEncryption>,
+    /// Compaction
+    pub(super) compaction: Option<ConfigKeyCompaction>,
+    /// Replication
+    pub(super) replication: Option<ConfigKeyReplication>,
+    /// Cluster mode
+    pub(super) cluster: Option<ConfigKeyCluster>,
+    /// Change data capture
+    pub(super) cdc: Option<ConfigKeyCdc>,
+    /// Table exports
+    pub(super) export: Option<ConfigKeyExport>,
 }
 
 /// This struct represents the `server` key in the TOML file
@@ -60,9 +84,17 @@ pub struct ConfigKeyServer {
     pub(super) noart: Option<bool>,
     /// The maximum number of clients
     pub(super) maxclient: Option<usize>,
+    /// The number of shards that every table is split into
+    pub(super) shards: Option<usize>,
     /// The deployment mode
     pub(super) mode: Option<Modeset>,
     pub(super) protocol: Option<ProtocolVersion>,
+    /// The most verbose level that is logged
+    pub(super) loglevel: Option<String>,
+    /// The format that log records are written in
+    pub(super) logformat: Option<String>,
+    /// Whether keys can be qualified with a table
+    pub(super) qualified_keys: Option<bool>,
 }
 
 /// The BGSAVE section in the config file
@@ -90,6 +122,8 @@ pub struct ConfigKeySnapshot {
     pub(super) atmost: usize,
     /// Prevent writes to the database if snapshotting fails
     pub(super) failsafe: Option<bool>,
+    /// Stream the snapshots to an object store
+    pub(super) sink: Option<SnapshotSinkConfig>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
@@ -99,6 +133,123 @@ pub struct KeySslOpts {
     pub(super) port: u16,
     pub(super) only: Option<bool>,
     pub(super) passin: Option<String>,
+    pub(super) clientca: Option<String>,
+}
+
+/// The HTTP gateway section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyHttp {
+    /// The port that the gateway listens on
+    pub(super) port: u16,
+    /// Serve the gateway over TLS (with the certificate of the server)
+    pub(super) secure: Option<bool>,
+}
+
+/// The limits section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyLimits {
+    /// The maximum size of a query, in bytes
+    pub(super) maxquerysize: Option<usize>,
+    /// The maximum number of elements in a query
+    pub(super) maxqueryargs: Option<usize>,
+    /// Close the connection if a query exceeds the limits
+    pub(super) closeonviolation: Option<bool>,
+    /// Close connections that have been idle for these many seconds
+    pub(super) idletimeout: Option<u64>,
+    /// Turn on TCP keepalive (after these many seconds of inactivity)
+    pub(super) keepalive: Option<u64>,
+    /// The maximum number of concurrent connections from a single IP address
+    pub(super) maxconperip: Option<usize>,
+    /// The maximum number of new connections per second from a single IP address
+    pub(super) maxconrate: Option<u64>,
+}
+
+/// The rate limit section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyRateLimit {
+    /// The budget (in queries per second) of the users that don't have one of their own
+    pub(super) queries: Option<u64>,
+    /// The budgets of specific users
+    pub(super) users: Option<BTreeMap<String, u64>>,
+}
+
+/// The audit section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyAudit {
+    /// A path or `syslog`
+    pub(super) log: Option<String>,
+    /// Rotate the log file once it grows past these many bytes
+    pub(super) maxsize: Option<u64>,
+    /// The number of rotated log files to keep
+    pub(super) keep: Option<usize>,
+}
+
+/// The memory section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyMemory {
+    /// The most bytes that the data can use
+    pub(super) maxmemory: Option<usize>,
+    /// `reject` or `warn`
+    pub(super) policy: Option<String>,
+}
+
+/// The WAL section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyWal {
+    /// Whether writes are logged before they're acknowledged
+    pub(super) enabled: Option<bool>,
+    /// `always`, `everysec`, `<N>ms` or `no`
+    pub(super) fsync: Option<String>,
+}
+
+/// The encryption section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyEncryption {
+    /// The file with the key
+    pub(super) keyfile: Option<String>,
+    /// The command that prints the key
+    pub(super) keycommand: Option<String>,
+}
+
+/// The compaction section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyCompaction {
+    /// Compact the tables every these many seconds
+    pub(super) every: Option<u64>,
+}
+
+/// The replication section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyReplication {
+    /// Whether a replica turns away the writes of its clients
+    pub(super) read_only: Option<bool>,
+}
+
+/// The cluster section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyCluster {
+    /// The address that this node is known by
+    pub(super) announce: Option<String>,
+}
+
+/// The change data capture section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyCdc {
+    /// Whether the committed writes are captured
+    pub(super) enabled: Option<bool>,
+    /// The number of changes that are kept in memory
+    pub(super) backlog: Option<usize>,
+    /// The file that the changes are appended to
+    pub(super) file: Option<String>,
+}
+
+/// The export section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyExport {
+    /// The directory that the archives are kept in
+    pub(super) dir: Option<String>,
+    /// Whether anyone can export and import tables while auth is disabled
+    pub(super) allow_noauth: Option<bool>,
 }
 
 /// A custom non-null type for config files
@@ -171,6 +322,18 @@ pub fn from_file(file: ConfigFile) -> Configset {
         snapshot,
         ssl,
         auth,
+        http,
+        limits,
+        ratelimit,
+        audit,
+        memory,
+        wal,
+        encryption,
+        compaction,
+        replication,
+        cluster,
+        cdc,
+        export,
     } = file;
     // server settings
     set.server_tcp(
@@ -181,8 +344,15 @@ pub fn from_file(file: ConfigFile) -> Configset {
     );
     set.protocol_settings(server.protocol, "server.protocol");
     set.server_maxcon(Optional::from(server.maxclient), "server.maxcon");
+    set.server_shards(Optional::from(server.shards), "server.shards");
     set.server_noart(Optional::from(server.noart), "server.noart");
     set.server_mode(Optional::from(server.mode), "server.mode");
+    set.server_loglevel(server.loglevel.as_deref(), "server.loglevel");
+    set.server_logformat(server.logformat.as_deref(), "server.logformat");
+    set.server_qualified_keys(
+        Optional::from(server.qualified_keys),
+        "server.qualified_keys",
+    );
     // bgsave settings
     if let Some(bgsave) = bgsave {
         let ConfigKeyBGSAVE { enabled, every } = bgsave;
@@ -199,6 +369,7 @@ pub fn from_file(file: ConfigFile) -> Configset {
             every,
             atmost,
             failsafe,
+            sink,
         } = snapshot;
         set.snapshot_settings(
             NonNull::from(every),
@@ -208,6 +379,7 @@ pub fn from_file(file: ConfigFile) -> Configset {
             Optional::from(failsafe),
             "snapshot.failsafe",
         );
+        set.snapshot_sink_settings(sink, "snapshot.sink");
     }
     // TLS settings
     if let Some(tls) = ssl {
@@ -217,6 +389,7 @@ pub fn from_file(file: ConfigFile) -> Configset {
             port,
             only,
             passin,
+            clientca,
         } = tls;
         set.tls_settings(
             NonNull::from(key),
@@ -229,11 +402,153 @@ pub fn from_file(file: ConfigFile) -> Configset {
             "ssl.only",
             OptString::from(passin),
             "ssl.passin",
+            OptString::from(clientca),
+            "ssl.clientca",
         );
     }
     if let Some(auth) = auth {
-        let AuthSettings { origin_key } = auth;
-        set.auth_settings(Optional::from(origin_key), "auth.origin")
+        let AuthSettings {
+            origin_key,
+            external,
+        } = auth;
+        set.auth_settings(Optional::from(origin_key), "auth.origin");
+        set.external_auth_settings(external, "auth.external");
+    }
+    // HTTP gateway settings
+    if let Some(http) = http {
+        let ConfigKeyHttp { port, secure } = http;
+        set.http_settings(
+            NonNull::from(port),
+            "http.port",
+            Optional::from(secure),
+            "http.secure",
+        );
+    }
+    // connection and query limits
+    if let Some(limits) = limits {
+        let ConfigKeyLimits {
+            maxquerysize,
+            maxqueryargs,
+            closeonviolation,
+            idletimeout,
+            keepalive,
+            maxconperip,
+            maxconrate,
+        } = limits;
+        set.limits_settings(
+            Optional::from(maxquerysize),
+            "limits.maxquerysize",
+            Optional::from(maxqueryargs),
+            "limits.maxqueryargs",
+            Optional::from(closeonviolation),
+            "limits.closeonviolation",
+            Optional::from(idletimeout),
+            "limits.idletimeout",
+            Optional::from(keepalive),
+            "limits.keepalive",
+        );
+        set.admission_settings(
+            Optional::from(maxconperip),
+            "limits.maxconperip",
+            Optional::from(maxconrate),
+            "limits.maxconrate",
+        );
+    }
+    // query rate limits
+    if let Some(ratelimit) = ratelimit {
+        let ConfigKeyRateLimit { queries, users } = ratelimit;
+        set.ratelimit_settings(
+            Optional::from(queries),
+            "ratelimit.queries",
+            Optional::from(users.map(|users| UserBudgets::new(users.into_iter().collect()))),
+            "ratelimit.users",
+        );
+    }
+    // audit log
+    if let Some(audit) = audit {
+        let ConfigKeyAudit { log, maxsize, keep } = audit;
+        set.audit_settings(
+            log.as_deref(),
+            "audit.log",
+            Optional::from(maxsize),
+            "audit.maxsize",
+            Optional::from(keep),
+            "audit.keep",
+        );
+    }
+    // memory limit
+    if let Some(memory) = memory {
+        let ConfigKeyMemory { maxmemory, policy } = memory;
+        set.memory_settings(
+            Optional::from(maxmemory),
+            "memory.maxmemory",
+            policy.as_deref(),
+            "memory.policy",
+        );
+    }
+    // write-ahead log
+    if let Some(wal) = wal {
+        let ConfigKeyWal { enabled, fsync } = wal;
+        set.wal_settings(
+            Optional::from(enabled),
+            "wal.enabled",
+            fsync.as_deref(),
+            "wal.fsync",
+        );
+    }
+    // encryption at rest
+    if let Some(encryption) = encryption {
+        let ConfigKeyEncryption {
+            keyfile,
+            keycommand,
+        } = encryption;
+        set.encryption_settings(
+            keyfile.as_deref(),
+            "encryption.keyfile",
+            keycommand.as_deref(),
+            "encryption.keycommand",
+        );
+    }
+    // compaction
+    if let Some(compaction) = compaction {
+        let ConfigKeyCompaction { every } = compaction;
+        set.compaction_settings(Optional::from(every), "compaction.every");
+    }
+    // replication
+    if let Some(replication) = replication {
+        let ConfigKeyReplication { read_only } = replication;
+        set.replication_settings(Optional::from(read_only), "replication.read_only");
+    }
+    // cluster
+    if let Some(cluster) = cluster {
+        let ConfigKeyCluster { announce } = cluster;
+        set.cluster_settings(announce.as_deref(), "cluster.announce");
+    }
+    // change data capture
+    if let Some(cdc) = cdc {
+        let ConfigKeyCdc {
+            enabled,
+            backlog,
+            file,
+        } = cdc;
+        set.cdc_settings(
+            Optional::from(enabled),
+            "cdc.enabled",
+            Optional::from(backlog),
+            "cdc.backlog",
+            file.as_deref(),
+            "cdc.file",
+        );
+    }
+    // table exports
+    if let Some(export) = export {
+        let ConfigKeyExport { dir, allow_noauth } = export;
+        set.export_settings(
+            dir.as_deref(),
+            "export.dir",
+            Optional::from(allow_noauth),
+            "export.allow_noauth",
+        );
     }
     set
 }
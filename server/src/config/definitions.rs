@@ -25,9 +25,13 @@
 */
 
 use {
-    super::{feedback::WarningStack, DEFAULT_IPV4, DEFAULT_PORT},
-    crate::{config::AuthkeyWrapper, dbnet::MAXIMUM_CONNECTION_LIMIT},
+    super::{
+        feedback::WarningStack, DEFAULT_AUDIT_KEEP, DEFAULT_CDC_BACKLOG, DEFAULT_EXPORT_DIR,
+        DEFAULT_IPV4, DEFAULT_PORT,
+    },
+    crate::{config::AuthkeyWrapper, dbnet::MAXIMUM_CONNECTION_LIMIT, protocol::QueryLimits},
     core::{fmt, str::FromStr},
+    log::LevelFilter,
     serde::{
         de::{self, Deserializer, Visitor},
         Deserialize,
@@ -39,7 +43,7 @@ use {
 ///
 /// If BGSAVE is enabled, then the duration (corresponding to `every`) is wrapped in the `Enabled`
 /// variant. Otherwise, the `Disabled` variant is to be used
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum BGSave {
     Enabled(u64),
     Disabled,
@@ -68,8 +72,406 @@ impl BGSave {
     }
 }
 
+/// The HTTP gateway configuration
+///
+/// If the gateway is enabled, the port that it listens on (the host is shared with the Skyhash
+/// listeners) and whether it's served over TLS are held by the `Enabled` variant. Otherwise, the
+/// `Disabled` variant is to be used
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HttpConfig {
+    Enabled { port: u16, secure: bool },
+    Disabled,
+}
+
+impl HttpConfig {
+    /// The HTTP gateway is disabled by default
+    pub const fn default() -> Self {
+        HttpConfig::Disabled
+    }
+}
+
+/// The limits on client connections and the queries sent on them
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct LimitsConfig {
+    /// The limits enforced by the protocol decoder
+    pub query: QueryLimits,
+    /// Close the connection (after sending the error) if a query exceeds the limits
+    pub close_on_violation: bool,
+    /// Close connections that haven't sent anything for these many seconds
+    pub idle_timeout: Option<u64>,
+    /// Turn on TCP keepalive, probing connections that have been quiet for these many seconds
+    pub keepalive: Option<u64>,
+    /// The limits on the connections from a single IP address
+    pub admission: AdmissionConfig,
+}
+
+impl LimitsConfig {
+    pub const fn new(
+        query: QueryLimits,
+        close_on_violation: bool,
+        idle_timeout: Option<u64>,
+        keepalive: Option<u64>,
+        admission: AdmissionConfig,
+    ) -> Self {
+        Self {
+            query,
+            close_on_violation,
+            idle_timeout,
+            keepalive,
+            admission,
+        }
+    }
+    /// The default limits
+    ///
+    /// Defaults:
+    /// - `maxquerysize`: 128 MiB
+    /// - `maxqueryargs`: 1048576
+    /// - `closeonviolation`: false
+    /// - `idletimeout`: none
+    /// - `keepalive`: disabled
+    /// - `maxconperip` and `maxconrate`: no limit
+    pub const fn default() -> Self {
+        Self::new(
+            QueryLimits::new(QueryLimits::DEFAULT_MAX_SIZE, QueryLimits::DEFAULT_MAX_ARGS),
+            false,
+            None,
+            None,
+            AdmissionConfig::default(),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The limits on the connections from a single IP address, enforced when connections are
+/// accepted
+pub struct AdmissionConfig {
+    /// The maximum number of concurrent connections
+    pub max_per_ip: Option<usize>,
+    /// The maximum number of new connections per second
+    pub max_rate: Option<u64>,
+}
+
+impl AdmissionConfig {
+    pub const fn new(max_per_ip: Option<usize>, max_rate: Option<u64>) -> Self {
+        Self {
+            max_per_ip,
+            max_rate,
+        }
+    }
+    pub const fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The query budgets (in queries per second) of authenticated users. Queries that aren't run
+/// by an authenticated user aren't rate limited
+pub struct RateLimitConfig {
+    /// The budget of every user that doesn't have one of their own (`None` if they aren't
+    /// limited)
+    pub queries: Option<u64>,
+    /// The budgets of specific users
+    pub users: UserBudgets,
+}
+
+impl RateLimitConfig {
+    pub const fn new(queries: Option<u64>, users: UserBudgets) -> Self {
+        Self { queries, users }
+    }
+    pub const fn default() -> Self {
+        Self::new(None, UserBudgets::new(Vec::new()))
+    }
+    /// Returns true if any user is rate limited
+    pub fn is_enabled(&self) -> bool {
+        self.queries.is_some() || !self.users.0.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The budgets of specific users. In the environment and on the command line, these are
+/// written as `<username>:<budget>` pairs separated by commas (like `alice:100,bob:50`)
+pub struct UserBudgets(pub Vec<(String, u64)>);
+
+impl UserBudgets {
+    pub const fn new(budgets: Vec<(String, u64)>) -> Self {
+        Self(budgets)
+    }
+}
+
+impl FromStr for UserBudgets {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        st.split(',')
+            .map(|budget| {
+                let (user, queries) = budget.trim().split_once(':').ok_or(())?;
+                match (user.trim(), queries.trim().parse()) {
+                    (user, Ok(queries)) if !user.is_empty() => Ok((user.to_owned(), queries)),
+                    _ => Err(()),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The audit log configuration
+pub struct AuditConfig {
+    /// Where the audit records are written to (`None` if auditing is disabled)
+    pub log: Option<AuditLog>,
+    /// Rotate the log file once it grows past these many bytes (`None` if it is never
+    /// rotated)
+    pub maxsize: Option<u64>,
+    /// The number of rotated log files to keep around
+    pub keep: usize,
+}
+
+impl AuditConfig {
+    pub const fn new(log: Option<AuditLog>, maxsize: Option<u64>, keep: usize) -> Self {
+        Self { log, maxsize, keep }
+    }
+    pub const fn default() -> Self {
+        Self::new(None, None, DEFAULT_AUDIT_KEEP)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The destination of the audit log. In the environment, on the command line and in the
+/// configuration file, `syslog` picks the system logger and anything else is a path
+pub enum AuditLog {
+    /// Append to a file
+    File(String),
+    /// Send the records to the local syslog daemon
+    Syslog,
+}
+
+impl FromStr for AuditLog {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        match st.trim() {
+            "" => Err(()),
+            "syslog" => Ok(Self::Syslog),
+            _ => Ok(Self::File(st.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The memory limit (see [`crate::memory`])
+pub struct MemoryConfig {
+    /// The most bytes that the data can use (`None` if there's no limit)
+    pub maxmemory: Option<usize>,
+    /// What happens to writes once the limit is exceeded
+    pub policy: MemoryPolicy,
+}
+
+impl MemoryConfig {
+    pub const fn new(maxmemory: Option<usize>, policy: MemoryPolicy) -> Self {
+        Self { maxmemory, policy }
+    }
+    pub const fn default() -> Self {
+        Self::new(None, MemoryPolicy::Reject)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// What happens to writes once the memory limit is exceeded
+pub enum MemoryPolicy {
+    /// writes that can grow the data are rejected
+    Reject,
+    /// writes go through, but a warning is logged
+    Warn,
+}
+
+impl FromStr for MemoryPolicy {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        match st {
+            "reject" => Ok(Self::Reject),
+            "warn" => Ok(Self::Warn),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The write-ahead log (see [`crate::storage::v1::wal`])
+pub struct WalConfig {
+    /// Whether writes are logged before they're acknowledged
+    pub enabled: bool,
+    /// When the log is synced to disk
+    pub fsync: WalFsync,
+}
+
+impl WalConfig {
+    pub const fn new(enabled: bool, fsync: WalFsync) -> Self {
+        Self { enabled, fsync }
+    }
+    pub const fn default() -> Self {
+        Self::new(false, WalFsync::EVERYSEC)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// When the write-ahead log is synced to disk
+pub enum WalFsync {
+    /// before every write is acknowledged
+    Always,
+    /// at most the given number of milliseconds after a write, so a power loss can lose (at
+    /// most) the writes made in that window
+    Every(u64),
+    /// whenever the OS decides to
+    No,
+}
+
+impl WalFsync {
+    /// `everysec`
+    pub const EVERYSEC: Self = Self::Every(1000);
+}
+
+impl FromStr for WalFsync {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        match st {
+            "always" => Ok(Self::Always),
+            "everysec" => Ok(Self::EVERYSEC),
+            "no" => Ok(Self::No),
+            st => match st.strip_suffix("ms").map(str::parse) {
+                Some(Ok(ms)) if ms != 0 => Ok(Self::Every(ms)),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for WalFsync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Always => f.write_str("always"),
+            Self::Every(ms) => write!(f, "{ms}ms"),
+            Self::No => f.write_str("no"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Encryption at rest (see [`crate::storage::v1::encryption`])
+pub enum EncryptionConfig {
+    /// the data isn't encrypted
+    Disabled,
+    /// the key is read from this file
+    KeyFile(String),
+    /// the key is printed by this command (like a KMS client that decrypts the key)
+    KeyCommand(String),
+}
+
+impl EncryptionConfig {
+    pub const fn default() -> Self {
+        Self::Disabled
+    }
+    pub const fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Compaction (see [`crate::services::compaction`])
+pub struct CompactionConfig {
+    /// Compact the tables every these many seconds (`None` if they're only compacted with
+    /// `SYS COMPACT`)
+    pub every: Option<u64>,
+}
+
+impl CompactionConfig {
+    pub const fn new(every: Option<u64>) -> Self {
+        Self { every }
+    }
+    pub const fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Replication (see [`crate::replication`])
+pub struct ReplicationConfig {
+    /// Whether a replica turns away the writes of its clients
+    pub read_only: bool,
+}
+
+impl ReplicationConfig {
+    pub const fn new(read_only: bool) -> Self {
+        Self { read_only }
+    }
+    pub const fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Cluster mode (see [`crate::cluster`])
+pub struct ClusterConfig {
+    /// The address (`<host>:<port>`) that this node is known by, if it runs in cluster mode
+    pub announce: Option<String>,
+}
+
+impl ClusterConfig {
+    pub const fn new(announce: Option<String>) -> Self {
+        Self { announce }
+    }
+    pub const fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Change data capture (see [`crate::cdc`])
+pub struct CdcConfig {
+    /// Whether the committed writes are captured
+    pub enabled: bool,
+    /// The number of changes that are kept in memory for consumers to resume from
+    pub backlog: usize,
+    /// The file that the changes are appended to (if any)
+    pub file: Option<String>,
+}
+
+impl CdcConfig {
+    pub const fn new(enabled: bool, backlog: usize, file: Option<String>) -> Self {
+        Self {
+            enabled,
+            backlog,
+            file,
+        }
+    }
+    pub const fn default() -> Self {
+        Self::new(false, DEFAULT_CDC_BACKLOG, None)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Table exports (see [`crate::storage::v1::export`])
+pub struct ExportConfig {
+    /// The directory that `SYS EXPORT` writes to and `SYS IMPORT` reads from (`None` picks
+    /// [`DEFAULT_EXPORT_DIR`])
+    pub dir: Option<String>,
+    /// Whether anyone can export and import tables while auth is disabled
+    pub allow_noauth: bool,
+}
+
+impl ExportConfig {
+    pub const fn new(dir: Option<String>, allow_noauth: bool) -> Self {
+        Self { dir, allow_noauth }
+    }
+    pub const fn default() -> Self {
+        Self::new(None, false)
+    }
+    /// Returns the directory that the archives are kept in
+    pub fn dir(&self) -> &str {
+        self.dir.as_deref().unwrap_or(DEFAULT_EXPORT_DIR)
+    }
+}
+
 #[repr(u8)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum ProtocolVersion {
     V1,
     V2,
@@ -119,7 +521,7 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
 /// A `ConfigurationSet` which can be used by main::check_args_or_connect() to bind
 /// to a `TcpListener` and show the corresponding terminal output for the given
 /// configuration
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ConfigurationSet {
     /// If `noart` is set to true, no terminal artwork should be displayed
     pub noart: bool,
@@ -127,16 +529,50 @@ pub struct ConfigurationSet {
     pub bgsave: BGSave,
     /// The snapshot configuration
     pub snapshot: SnapshotConfig,
+    /// The object store that snapshots are streamed to (if any)
+    pub snapshot_sink: Option<SnapshotSinkConfig>,
     /// Port configuration
     pub ports: PortConfig,
     /// The maximum number of connections
     pub maxcon: usize,
+    /// The number of shards that every table is split into (zero picks it from the parallelism)
+    pub shards: usize,
     /// The deployment mode
     pub mode: Modeset,
     /// The auth settings
     pub auth: AuthSettings,
     /// The protocol version
     pub protocol: ProtocolVersion,
+    /// The HTTP gateway configuration
+    pub http: HttpConfig,
+    /// The connection and query limits
+    pub limits: LimitsConfig,
+    /// The query budgets of authenticated users
+    pub ratelimit: RateLimitConfig,
+    /// The audit log configuration
+    pub audit: AuditConfig,
+    /// The memory limit
+    pub memory: MemoryConfig,
+    /// The write-ahead log configuration
+    pub wal: WalConfig,
+    /// Where the encryption key comes from (if the data is encrypted)
+    pub encryption: EncryptionConfig,
+    /// The compaction schedule
+    pub compaction: CompactionConfig,
+    /// How the server behaves as a replica
+    pub replication: ReplicationConfig,
+    /// Cluster mode
+    pub cluster: ClusterConfig,
+    /// Change data capture
+    pub cdc: CdcConfig,
+    /// Table exports
+    pub export: ExportConfig,
+    /// The most verbose level that is logged (`None` leaves it to the `SKY_LOG` filters)
+    pub loglevel: Option<LevelFilter>,
+    /// The format that log records are written in
+    pub logformat: LogFormat,
+    /// Whether keys can be qualified with a table (`<keyspace>:<table>:<key>`)
+    pub qualified_keys: bool,
 }
 
 impl ConfigurationSet {
@@ -145,21 +581,55 @@ impl ConfigurationSet {
         noart: bool,
         bgsave: BGSave,
         snapshot: SnapshotConfig,
+        snapshot_sink: Option<SnapshotSinkConfig>,
         ports: PortConfig,
         maxcon: usize,
+        shards: usize,
         mode: Modeset,
         auth: AuthSettings,
         protocol: ProtocolVersion,
+        http: HttpConfig,
+        limits: LimitsConfig,
+        ratelimit: RateLimitConfig,
+        audit: AuditConfig,
+        memory: MemoryConfig,
+        wal: WalConfig,
+        encryption: EncryptionConfig,
+        compaction: CompactionConfig,
+        replication: ReplicationConfig,
+        cluster: ClusterConfig,
+        cdc: CdcConfig,
+        export: ExportConfig,
+        loglevel: Option<LevelFilter>,
+        logformat: LogFormat,
+        qualified_keys: bool,
     ) -> Self {
         Self {
             noart,
             bgsave,
             snapshot,
+            snapshot_sink,
             ports,
             maxcon,
+            shards,
             mode,
             auth,
             protocol,
+            http,
+            limits,
+            ratelimit,
+            audit,
+            memory,
+            wal,
+            encryption,
+            compaction,
+            replication,
+            cluster,
+            cdc,
+            export,
+            loglevel,
+            logformat,
+            qualified_keys,
         }
     }
     /// Create a default `ConfigurationSet` with the following setup defaults:
@@ -169,16 +639,49 @@ impl ConfigurationSet {
     /// - `bgsave_enabled` : true
     /// - `bgsave_duration` : 120
     /// - `ssl` : disabled
+    /// - `shards` : picked from the parallelism
+    /// - `http` : disabled
+    /// - `limits` : see [`LimitsConfig::default`]
+    /// - `ratelimit` : disabled
+    /// - `audit` : disabled
+    /// - `memory` : no limit
+    /// - `wal` : disabled
+    /// - `encryption` : disabled
+    /// - `compaction` : only with `SYS COMPACT`
+    /// - `replication` : replicas are read-only
+    /// - `cluster` : disabled
+    /// - `cdc` : disabled
+    /// - `export` : to `exports`, and only with auth
+    /// - `loglevel` : unset
+    /// - `logformat` : text
+    /// - `qualified_keys` : false
     pub const fn default() -> Self {
         Self::new(
             false,
             BGSave::default(),
             SnapshotConfig::default(),
+            None,
             PortConfig::new_insecure_only(DEFAULT_IPV4, 2003),
             MAXIMUM_CONNECTION_LIMIT,
+            0,
             Modeset::Dev,
             AuthSettings::default(),
             ProtocolVersion::V2,
+            HttpConfig::default(),
+            LimitsConfig::default(),
+            RateLimitConfig::default(),
+            AuditConfig::default(),
+            MemoryConfig::default(),
+            WalConfig::default(),
+            EncryptionConfig::default(),
+            CompactionConfig::default(),
+            ReplicationConfig::default(),
+            ClusterConfig::default(),
+            CdcConfig::default(),
+            ExportConfig::default(),
+            None,
+            LogFormat::Text,
+            false,
         )
     }
     /// Returns `false` if `noart` is enabled. Otherwise it returns `true`
@@ -196,7 +699,7 @@ impl ConfigurationSet {
 /// and will not even activate the non-SSL socket
 /// - `InsecureOnly` : This indicates that the server would only accept non-SSL connections
 /// and will not even activate the SSL socket
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum PortConfig {
     SecureOnly {
         host: IpAddr,
@@ -273,21 +776,31 @@ impl PortConfig {
     }
 }
 
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct SslOpts {
     pub key: String,
     pub chain: String,
     pub port: u16,
     pub passfile: Option<String>,
+    /// The CA bundle that client certificates are verified against. If this is set, clients
+    /// have to present a certificate signed by one of these CAs
+    pub clientca: Option<String>,
 }
 
 impl SslOpts {
-    pub const fn new(key: String, chain: String, port: u16, passfile: Option<String>) -> Self {
+    pub const fn new(
+        key: String,
+        chain: String,
+        port: u16,
+        passfile: Option<String>,
+        clientca: Option<String>,
+    ) -> Self {
         SslOpts {
             key,
             chain,
             port,
             passfile,
+            clientca,
         }
     }
     pub const fn get_port(&self) -> u16 {
@@ -295,7 +808,7 @@ impl SslOpts {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 /// The snapshot configuration
 ///
 pub struct SnapshotPref {
@@ -322,7 +835,7 @@ impl SnapshotPref {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 /// Snapshotting configuration
 ///
 /// The variant `Enabled` directly carries a `ConfigKeySnapshot` object that
@@ -344,7 +857,48 @@ impl SnapshotConfig {
     }
 }
 
-type RestoreFile = Option<String>;
+/// An object store that snapshots are streamed to instead of the snapshot directory (see
+/// [`crate::storage::v1::sink`]). This can only be set in the configuration file
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+pub struct SnapshotSinkConfig {
+    /// The kind of object store
+    pub provider: SinkProvider,
+    /// The bucket that the snapshots are put in
+    pub bucket: String,
+    /// The credentials (an HMAC key for GCS)
+    pub access_key: String,
+    pub secret_key: String,
+    /// The region of the bucket (the default is `us-east-1` for S3 and `auto` for GCS)
+    pub region: Option<String>,
+    /// The URL of the object store, for anything other than AWS and Google (like MinIO)
+    pub endpoint: Option<String>,
+    /// The prefix of the names of the snapshot objects (like `skyd/`)
+    pub prefix: Option<String>,
+    /// The size of the parts of the multipart upload, in MiB
+    pub part_size: Option<usize>,
+    /// The number of times that a failed request is retried
+    pub retries: Option<u32>,
+}
+
+/// An object store that speaks the S3 API
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkProvider {
+    S3,
+    Gcs,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// How the data is restored when the server starts up
+pub enum Restore {
+    /// copy it over from a backup directory (`--restore`)
+    Backup(String),
+    /// recover it to the given point in time (in milliseconds since the epoch) from the
+    /// snapshots and the write-ahead log (`--recover-to`; see [`crate::storage::v1::wal`])
+    PointInTime(u64),
+}
+
+type RestoreFile = Option<Restore>;
 
 #[derive(Debug, PartialEq, Eq)]
 /// The type of configuration:
@@ -376,7 +930,7 @@ impl ConfigType {
             warnings.print_warnings()
         }
     }
-    pub fn finish(self) -> (ConfigurationSet, Option<String>) {
+    pub fn finish(self) -> (ConfigurationSet, RestoreFile) {
         (self.config, self.restore)
     }
     pub fn is_custom(&self) -> bool {
@@ -413,7 +967,27 @@ impl ConfigType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The format that log records are written in (see [`crate::logging`])
+pub enum LogFormat {
+    /// text lines
+    Text,
+    /// JSON objects, one per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+    fn from_str(st: &str) -> Result<LogFormat, Self::Err> {
+        match st {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Modeset {
     Dev,
     Prod,
@@ -458,23 +1032,44 @@ impl<'de> Deserialize<'de> for Modeset {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
 pub struct AuthSettings {
     pub origin_key: Option<AuthkeyWrapper>,
+    /// The provider that token checks are delegated to (see [`crate::auth::external`])
+    pub external: Option<ExternalAuthConfig>,
 }
 
 impl AuthSettings {
     pub const fn default() -> Self {
-        Self { origin_key: None }
+        Self {
+            origin_key: None,
+            external: None,
+        }
     }
     #[cfg(test)]
-    pub fn new(origin: AuthkeyWrapper) -> Self {
+    pub fn new(origin: AuthkeyWrapper, external: Option<ExternalAuthConfig>) -> Self {
         Self {
             origin_key: Some(origin),
+            external,
         }
     }
 }
 
+/// An external authentication provider. This can only be set in the configuration file
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum ExternalAuthConfig {
+    /// An LDAP server that users bind to with their DN (the `{user}` in `bind_dn` is replaced
+    /// with the username)
+    Ldap { url: String, bind_dn: String },
+    /// An OIDC (or any OAuth 2.0) provider's token introspection endpoint
+    Oidc {
+        url: String,
+        client_id: String,
+        client_secret: String,
+    },
+}
+
 struct AuthSettingsVisitor;
 
 impl<'de> Visitor<'de> for AuthSettingsVisitor {
@@ -26,8 +26,11 @@
 
 use {
     crate::auth::provider::Authkey,
+    chrono::{DateTime, NaiveDateTime},
     clap::{load_yaml, App},
     core::str::FromStr,
+    log::LevelFilter,
+    parking_lot::{const_mutex, Mutex},
     std::{
         env::VarError,
         fs,
@@ -48,6 +51,7 @@ mod tests;
 use self::cfgfile::Config as ConfigFile;
 pub use self::definitions::*;
 use self::feedback::{ConfigError, ErrorStack, WarningStack};
+use crate::corestore::map;
 use crate::dbnet::MAXIMUM_CONNECTION_LIMIT;
 
 // server defaults
@@ -59,10 +63,19 @@ const DEFAULT_BGSAVE_DURATION: u64 = 120;
 const DEFAULT_SNAPSHOT_FAILSAFE: bool = true;
 // TLS defaults
 const DEFAULT_SSL_PORT: u16 = 2004;
+// audit defaults
+const DEFAULT_AUDIT_KEEP: usize = 4;
+// change data capture defaults
+const DEFAULT_CDC_BACKLOG: usize = 65536;
+// export defaults
+const DEFAULT_EXPORT_DIR: &str = "exports";
 
 type StaticStr = &'static str;
 
-#[derive(Debug, PartialEq, Eq)]
+/// The configuration file that the server was started with (if any)
+static CONFIG_FILE: Mutex<Option<String>> = const_mutex(None);
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct AuthkeyWrapper(pub Authkey);
 
 impl AuthkeyWrapper {
@@ -383,7 +396,7 @@ impl Configset {
         }
     }
     /// Turns self into a Result that can be used by config::get_config()
-    pub fn into_result(self, restore_file: Option<String>) -> Result<ConfigType, ConfigError> {
+    pub fn into_result(self, restore_file: Option<Restore>) -> Result<ConfigType, ConfigError> {
         let mut target = if self.is_okay() {
             // no errors, sweet
             if self.is_mutated() {
@@ -464,6 +477,21 @@ impl Configset {
         );
         self.cfg.maxcon = maxcon;
     }
+    pub fn server_shards(
+        &mut self,
+        nshards: impl TryFromConfigSource<usize>,
+        nshards_key: StaticStr,
+    ) {
+        let mut shards = 0;
+        self.try_mutate_with_condcheck(
+            nshards,
+            &mut shards,
+            nshards_key,
+            "zero or a power of two between 2 and 65536",
+            |shards| *shards == 0 || map::is_valid_shard_count(*shards),
+        );
+        self.cfg.shards = shards;
+    }
     pub fn server_mode(&mut self, nmode: impl TryFromConfigSource<Modeset>, nmode_key: StaticStr) {
         let mut modeset = Modeset::Dev;
         self.try_mutate(
@@ -474,6 +502,45 @@ impl Configset {
         );
         self.cfg.mode = modeset;
     }
+    pub fn server_loglevel(
+        &mut self,
+        nlevel: impl TryFromConfigSource<LevelFilter>,
+        nlevel_key: StaticStr,
+    ) {
+        if nlevel.is_present() {
+            let mut level = LevelFilter::Info;
+            self.try_mutate(
+                nlevel,
+                &mut level,
+                nlevel_key,
+                "one of 'off', 'error', 'warn', 'info', 'debug' or 'trace'",
+            );
+            self.cfg.loglevel = Some(level);
+        }
+    }
+    pub fn server_logformat(
+        &mut self,
+        nformat: impl TryFromConfigSource<LogFormat>,
+        nformat_key: StaticStr,
+    ) {
+        let mut format = LogFormat::Text;
+        self.try_mutate(nformat, &mut format, nformat_key, "'text' or 'json'");
+        self.cfg.logformat = format;
+    }
+    pub fn server_qualified_keys(
+        &mut self,
+        nqualified: impl TryFromConfigSource<bool>,
+        nqualified_key: StaticStr,
+    ) {
+        let mut qualified_keys = false;
+        self.try_mutate(
+            nqualified,
+            &mut qualified_keys,
+            nqualified_key,
+            "true/false",
+        );
+        self.cfg.qualified_keys = qualified_keys;
+    }
 }
 
 // bgsave settings
@@ -564,6 +631,18 @@ impl Configset {
             }
         }
     }
+    pub fn snapshot_sink_settings(
+        &mut self,
+        nsink: Option<SnapshotSinkConfig>,
+        nsink_key: StaticStr,
+    ) {
+        if let Some(sink) = nsink {
+            if let Err(expected) = crate::storage::v1::sink::build(&sink) {
+                self.epush(nsink_key, expected);
+            }
+            self.cfg.snapshot_sink = Some(sink);
+        }
+    }
 }
 
 // TLS settings
@@ -581,6 +660,8 @@ impl Configset {
         nonly_key: StaticStr,
         npass: impl TryFromConfigSource<OptString>,
         npass_key: StaticStr,
+        nclientca: impl TryFromConfigSource<OptString>,
+        nclientca_key: StaticStr,
     ) {
         match (nkey.is_present(), ncert.is_present()) {
             (true, true) => {
@@ -607,7 +688,16 @@ impl Configset {
                     "path to TLS cert passphrase",
                 );
 
-                let sslopts = SslOpts::new(key, cert, port, tls_pass.base);
+                // check if client certificates are required
+                let mut client_ca = OptString::new_null();
+                self.try_mutate(
+                    nclientca,
+                    &mut client_ca,
+                    nclientca_key,
+                    "path to the CA bundle for client certificates",
+                );
+
+                let sslopts = SslOpts::new(key, cert, port, tls_pass.base, client_ca.base);
                 // now check if TLS only
                 if tls_only {
                     let host = self.cfg.ports.get_host();
@@ -642,6 +732,12 @@ impl Configset {
                         "Specifying `{npass_key}` is pointless when TLS is disabled"
                     ));
                 }
+                if nclientca.is_present() {
+                    self.mutated();
+                    self.wstack.push(format!(
+                        "Specifying `{nclientca_key}` is pointless when TLS is disabled"
+                    ));
+                }
             }
         }
     }
@@ -657,10 +753,491 @@ impl Configset {
         let mut def = AuthkeyWrapper::empty();
         self.try_mutate(nauth, &mut def, nauth_key, "A 40-byte long ASCII string");
         if def != AuthkeyWrapper::empty() {
-            self.cfg.auth = AuthSettings {
-                origin_key: Some(def),
-            };
+            self.cfg.auth.origin_key = Some(def);
+        }
+    }
+    pub fn external_auth_settings(
+        &mut self,
+        nexternal: Option<ExternalAuthConfig>,
+        nexternal_key: StaticStr,
+    ) {
+        if let Some(external) = nexternal {
+            if let Err(expected) = crate::auth::external::build(&external) {
+                self.epush(nexternal_key, expected);
+            }
+            if self.cfg.auth.origin_key.is_none() {
+                self.wstack.push(format!(
+                    "Specifying `{nexternal_key}` is pointless when auth is disabled"
+                ));
+            }
+            self.cfg.auth.external = Some(external);
+        }
+    }
+}
+
+// HTTP gateway settings
+impl Configset {
+    pub fn http_settings(
+        &mut self,
+        nport: impl TryFromConfigSource<u16>,
+        nport_key: StaticStr,
+        nsecure: impl TryFromConfigSource<bool>,
+        nsecure_key: StaticStr,
+    ) {
+        if nport.is_present() {
+            let mut port = 0;
+            self.try_mutate_with_condcheck(
+                nport,
+                &mut port,
+                nport_key,
+                "a positive 16-bit integer",
+                |port| *port > 0,
+            );
+            let mut secure = false;
+            self.try_mutate(nsecure, &mut secure, nsecure_key, "true/false");
+            if secure && self.cfg.ports.insecure_only() {
+                // the gateway uses the TLS certificate of the server
+                self.estack.push(format!(
+                    "To use `{nsecure_key}`, TLS has to be enabled for the server"
+                ));
+            }
+            self.cfg.http = HttpConfig::Enabled { port, secure };
+        } else if nsecure.is_present() {
+            self.mutated();
+            self.wstack.push(format!(
+                "Specifying `{nsecure_key}` is pointless when the HTTP gateway is disabled"
+            ));
+        }
+    }
+}
+
+// connection and query limits
+#[allow(clippy::too_many_arguments)]
+impl Configset {
+    pub fn limits_settings(
+        &mut self,
+        nsize: impl TryFromConfigSource<usize>,
+        nsize_key: StaticStr,
+        nargs: impl TryFromConfigSource<usize>,
+        nargs_key: StaticStr,
+        nclose: impl TryFromConfigSource<bool>,
+        nclose_key: StaticStr,
+        nidle: impl TryFromConfigSource<u64>,
+        nidle_key: StaticStr,
+        nkeepalive: impl TryFromConfigSource<u64>,
+        nkeepalive_key: StaticStr,
+    ) {
+        let mut limits = LimitsConfig::default();
+        self.try_mutate_with_condcheck(
+            nsize,
+            &mut limits.query.max_size,
+            nsize_key,
+            "a positive integer greater than zero",
+            |size| *size > 0,
+        );
+        self.try_mutate_with_condcheck(
+            nargs,
+            &mut limits.query.max_args,
+            nargs_key,
+            "a positive integer greater than zero",
+            |args| *args > 0,
+        );
+        self.try_mutate(
+            nclose,
+            &mut limits.close_on_violation,
+            nclose_key,
+            "true/false",
+        );
+        if nidle.is_present() {
+            let mut idle = 0;
+            self.try_mutate_with_condcheck(
+                nidle,
+                &mut idle,
+                nidle_key,
+                "a positive integer greater than zero",
+                |idle| *idle > 0,
+            );
+            limits.idle_timeout = Some(idle);
+        }
+        if nkeepalive.is_present() {
+            let mut keepalive = 0;
+            self.try_mutate_with_condcheck(
+                nkeepalive,
+                &mut keepalive,
+                nkeepalive_key,
+                "a positive integer greater than zero",
+                |keepalive| *keepalive > 0,
+            );
+            if cfg!(unix) {
+                limits.keepalive = Some(keepalive);
+            } else {
+                self.wstack.push(format!(
+                    "`{nkeepalive_key}` is ignored because TCP keepalive isn't supported on this platform"
+                ));
+            }
+        }
+        self.cfg.limits = limits;
+    }
+    /// Set the per-IP connection limits. This has to be called after
+    /// [`Configset::limits_settings`] since that resets all the limits
+    pub fn admission_settings(
+        &mut self,
+        nmaxperip: impl TryFromConfigSource<usize>,
+        nmaxperip_key: StaticStr,
+        nmaxrate: impl TryFromConfigSource<u64>,
+        nmaxrate_key: StaticStr,
+    ) {
+        let mut admission = AdmissionConfig::default();
+        if nmaxperip.is_present() {
+            let mut maxperip = 0;
+            self.try_mutate_with_condcheck(
+                nmaxperip,
+                &mut maxperip,
+                nmaxperip_key,
+                "a positive integer greater than zero",
+                |maxperip| *maxperip > 0,
+            );
+            admission.max_per_ip = Some(maxperip);
+        }
+        if nmaxrate.is_present() {
+            let mut maxrate = 0;
+            self.try_mutate_with_condcheck(
+                nmaxrate,
+                &mut maxrate,
+                nmaxrate_key,
+                "a positive integer greater than zero",
+                |maxrate| *maxrate > 0,
+            );
+            admission.max_rate = Some(maxrate);
+        }
+        self.cfg.limits.admission = admission;
+    }
+}
+
+// query rate limits
+impl Configset {
+    pub fn ratelimit_settings(
+        &mut self,
+        nqueries: impl TryFromConfigSource<u64>,
+        nqueries_key: StaticStr,
+        nusers: impl TryFromConfigSource<UserBudgets>,
+        nusers_key: StaticStr,
+    ) {
+        let mut ratelimit = RateLimitConfig::default();
+        if nqueries.is_present() {
+            let mut queries = 0;
+            self.try_mutate_with_condcheck(
+                nqueries,
+                &mut queries,
+                nqueries_key,
+                "a positive integer greater than zero",
+                |queries| *queries > 0,
+            );
+            ratelimit.queries = Some(queries);
+        }
+        self.try_mutate_with_condcheck(
+            nusers,
+            &mut ratelimit.users,
+            nusers_key,
+            "`<username>:<budget>` pairs with budgets greater than zero",
+            |users| users.0.iter().all(|(_, queries)| *queries > 0),
+        );
+        if ratelimit.is_enabled() && self.cfg.auth.origin_key.is_none() {
+            // only the queries of authenticated users are rate limited
+            self.wstack.push(format!(
+                "Specifying `{nqueries_key}` or `{nusers_key}` is pointless when auth is disabled"
+            ));
+        }
+        self.cfg.ratelimit = ratelimit;
+    }
+}
+
+// audit log
+impl Configset {
+    pub fn audit_settings(
+        &mut self,
+        nlog: impl TryFromConfigSource<AuditLog>,
+        nlog_key: StaticStr,
+        nmaxsize: impl TryFromConfigSource<u64>,
+        nmaxsize_key: StaticStr,
+        nkeep: impl TryFromConfigSource<usize>,
+        nkeep_key: StaticStr,
+    ) {
+        let mut audit = AuditConfig::default();
+        let (has_log, has_maxsize, has_keep) =
+            (nlog.is_present(), nmaxsize.is_present(), nkeep.is_present());
+        if has_log {
+            let mut log = AuditLog::Syslog;
+            self.try_mutate_with_condcheck(
+                nlog,
+                &mut log,
+                nlog_key,
+                "a path or `syslog` (only on unix)",
+                |log| cfg!(unix) || !matches!(log, AuditLog::Syslog),
+            );
+            audit.log = Some(log);
+        }
+        if has_maxsize {
+            let mut maxsize = 0;
+            self.try_mutate(
+                nmaxsize,
+                &mut maxsize,
+                nmaxsize_key,
+                "a positive integer (zero disables rotation)",
+            );
+            audit.maxsize = Some(maxsize).filter(|maxsize| *maxsize != 0);
+        }
+        self.try_mutate_with_condcheck(
+            nkeep,
+            &mut audit.keep,
+            nkeep_key,
+            "a positive integer greater than zero",
+            |keep| *keep > 0,
+        );
+        match audit.log {
+            None if has_maxsize || has_keep => self.wstack.push(format!(
+                "Specifying `{nmaxsize_key}` or `{nkeep_key}` is pointless without `{nlog_key}`"
+            )),
+            Some(AuditLog::Syslog) if has_maxsize || has_keep => self.wstack.push(format!(
+                "`{nmaxsize_key}` and `{nkeep_key}` are ignored because the system logger rotates its own logs"
+            )),
+            _ => {}
+        }
+        self.cfg.audit = audit;
+    }
+}
+
+// memory limit
+impl Configset {
+    pub fn memory_settings(
+        &mut self,
+        nmaxmemory: impl TryFromConfigSource<usize>,
+        nmaxmemory_key: StaticStr,
+        npolicy: impl TryFromConfigSource<MemoryPolicy>,
+        npolicy_key: StaticStr,
+    ) {
+        let mut memory = MemoryConfig::default();
+        let (has_maxmemory, has_policy) = (nmaxmemory.is_present(), npolicy.is_present());
+        if has_maxmemory {
+            let mut maxmemory = 0;
+            self.try_mutate(
+                nmaxmemory,
+                &mut maxmemory,
+                nmaxmemory_key,
+                "a positive integer (zero disables the limit)",
+            );
+            memory.maxmemory = Some(maxmemory).filter(|maxmemory| *maxmemory != 0);
+        }
+        self.try_mutate(
+            npolicy,
+            &mut memory.policy,
+            npolicy_key,
+            "'reject' or 'warn'",
+        );
+        if memory.maxmemory.is_none() && has_policy {
+            self.wstack.push(format!(
+                "Specifying `{npolicy_key}` is pointless without `{nmaxmemory_key}`"
+            ));
+        }
+        self.cfg.memory = memory;
+    }
+}
+
+// write-ahead log
+impl Configset {
+    pub fn wal_settings(
+        &mut self,
+        nenabled: impl TryFromConfigSource<bool>,
+        nenabled_key: StaticStr,
+        nfsync: impl TryFromConfigSource<WalFsync>,
+        nfsync_key: StaticStr,
+    ) {
+        let mut wal = WalConfig::default();
+        let has_fsync = nfsync.is_present();
+        self.try_mutate(nenabled, &mut wal.enabled, nenabled_key, "true/false");
+        self.try_mutate(
+            nfsync,
+            &mut wal.fsync,
+            nfsync_key,
+            "'always', 'everysec', '<N>ms' or 'no'",
+        );
+        if !wal.enabled && has_fsync {
+            self.wstack.push(format!(
+                "Specifying `{nfsync_key}` is pointless without `{nenabled_key}`"
+            ));
+        }
+        self.cfg.wal = wal;
+    }
+}
+
+// encryption at rest
+impl Configset {
+    pub fn encryption_settings(
+        &mut self,
+        nkeyfile: impl TryFromConfigSource<String>,
+        nkeyfile_key: StaticStr,
+        nkeycommand: impl TryFromConfigSource<String>,
+        nkeycommand_key: StaticStr,
+    ) {
+        let (mut keyfile, mut keycommand) = (String::new(), String::new());
+        let (has_keyfile, has_keycommand) = (nkeyfile.is_present(), nkeycommand.is_present());
+        self.try_mutate_with_condcheck(
+            nkeyfile,
+            &mut keyfile,
+            nkeyfile_key,
+            "a path to a file with the key",
+            |keyfile| !keyfile.trim().is_empty(),
+        );
+        self.try_mutate_with_condcheck(
+            nkeycommand,
+            &mut keycommand,
+            nkeycommand_key,
+            "a command that prints the key",
+            |keycommand| !keycommand.trim().is_empty(),
+        );
+        self.cfg.encryption = match (has_keyfile, has_keycommand) {
+            (true, true) => {
+                self.estack.push(format!(
+                    "Only one of `{nkeyfile_key}` and `{nkeycommand_key}` can be set"
+                ));
+                EncryptionConfig::Disabled
+            }
+            (true, false) => EncryptionConfig::KeyFile(keyfile),
+            (false, true) => EncryptionConfig::KeyCommand(keycommand),
+            (false, false) => EncryptionConfig::Disabled,
+        };
+    }
+}
+
+// compaction
+impl Configset {
+    pub fn compaction_settings(
+        &mut self,
+        nevery: impl TryFromConfigSource<u64>,
+        nevery_key: StaticStr,
+    ) {
+        let mut compaction = CompactionConfig::default();
+        if nevery.is_present() {
+            let mut every = 0;
+            self.try_mutate(
+                nevery,
+                &mut every,
+                nevery_key,
+                "a positive integer (zero disables scheduled compaction)",
+            );
+            compaction.every = Some(every).filter(|every| *every != 0);
         }
+        self.cfg.compaction = compaction;
+    }
+}
+
+// replication
+impl Configset {
+    pub fn replication_settings(
+        &mut self,
+        nread_only: impl TryFromConfigSource<bool>,
+        nread_only_key: StaticStr,
+    ) {
+        let mut replication = ReplicationConfig::default();
+        self.try_mutate(
+            nread_only,
+            &mut replication.read_only,
+            nread_only_key,
+            "true/false",
+        );
+        self.cfg.replication = replication;
+    }
+}
+
+// cluster
+impl Configset {
+    pub fn cluster_settings(
+        &mut self,
+        nannounce: impl TryFromConfigSource<String>,
+        nannounce_key: StaticStr,
+    ) {
+        let mut announce = String::new();
+        let has_announce = nannounce.is_present();
+        self.try_mutate_with_condcheck(
+            nannounce,
+            &mut announce,
+            nannounce_key,
+            "an address like `<host>:<port>`",
+            |announce| crate::cluster::split_addr(announce).is_some(),
+        );
+        self.cfg.cluster = ClusterConfig::new(has_announce.then_some(announce));
+    }
+}
+
+// change data capture
+impl Configset {
+    pub fn cdc_settings(
+        &mut self,
+        nenabled: impl TryFromConfigSource<bool>,
+        nenabled_key: StaticStr,
+        nbacklog: impl TryFromConfigSource<usize>,
+        nbacklog_key: StaticStr,
+        nfile: impl TryFromConfigSource<String>,
+        nfile_key: StaticStr,
+    ) {
+        let mut cdc = CdcConfig::default();
+        let (has_backlog, has_file) = (nbacklog.is_present(), nfile.is_present());
+        self.try_mutate(nenabled, &mut cdc.enabled, nenabled_key, "true/false");
+        self.try_mutate_with_condcheck(
+            nbacklog,
+            &mut cdc.backlog,
+            nbacklog_key,
+            "a positive integer greater than zero",
+            |backlog| *backlog > 0,
+        );
+        if has_file {
+            let mut file = String::new();
+            self.try_mutate_with_condcheck(
+                nfile,
+                &mut file,
+                nfile_key,
+                "a path to a file",
+                |file| !file.trim().is_empty(),
+            );
+            cdc.file = Some(file);
+        }
+        if !cdc.enabled && (has_backlog || has_file) {
+            self.wstack.push(format!(
+                "Specifying `{nbacklog_key}` or `{nfile_key}` is pointless without `{nenabled_key}`"
+            ));
+        }
+        self.cfg.cdc = cdc;
+    }
+}
+
+// table exports
+impl Configset {
+    pub fn export_settings(
+        &mut self,
+        ndir: impl TryFromConfigSource<String>,
+        ndir_key: StaticStr,
+        nallow_noauth: impl TryFromConfigSource<bool>,
+        nallow_noauth_key: StaticStr,
+    ) {
+        let mut export = ExportConfig::default();
+        if ndir.is_present() {
+            let mut dir = String::new();
+            self.try_mutate_with_condcheck(
+                ndir,
+                &mut dir,
+                ndir_key,
+                "a path to a directory",
+                |dir| !dir.trim().is_empty(),
+            );
+            export.dir = Some(dir);
+        }
+        self.try_mutate(
+            nallow_noauth,
+            &mut export.allow_noauth,
+            nallow_noauth_key,
+            "true/false",
+        );
+        self.cfg.export = export;
     }
 }
 
@@ -668,13 +1245,26 @@ pub fn get_config() -> Result<ConfigType, ConfigError> {
     // initialize clap because that will let us check for CLI/file configs
     let cfg_layout = load_yaml!("../cli.yml");
     let matches = App::from_yaml(cfg_layout).get_matches();
-    let restore_file = matches.value_of("restore").map(|v| v.to_string());
+    let restore_file = match (matches.value_of("restore"), matches.value_of("recover-to")) {
+        (Some(backup), _) => Some(Restore::Backup(backup.to_owned())),
+        (None, Some(time)) => match self::parse_recovery_time(time) {
+            Some(time) => Some(Restore::PointInTime(time)),
+            None => {
+                let mut estack = ErrorStack::new(Configset::EMSG_CLI);
+                estack.push(
+                    "Bad value for `--recover-to`. Expected a UTC time like `2022-07-05 14:30:00` or an RFC 3339 timestamp",
+                );
+                return Err(ConfigError::CfgError(estack));
+            }
+        },
+        (None, None) => None,
+    };
 
     // get config from file
     let cfg_from_file = if let Some(file) = matches.value_of("config") {
-        let file = fs::read(file)?;
-        let cfg_file: ConfigFile = toml::from_slice(&file)?;
-        Some(cfgfile::from_file(cfg_file))
+        let cfg_from_file = self::read_file(file)?;
+        *CONFIG_FILE.lock() = Some(file.to_owned());
+        Some(cfg_from_file)
     } else {
         None
     };
@@ -701,3 +1291,29 @@ pub fn get_config() -> Result<ConfigType, ConfigError> {
             .into_result(restore_file)
     }
 }
+
+/// Parse the time that `--recover-to` recovers the data to, into milliseconds since the epoch.
+/// This is either a UTC time (`2022-07-05 14:30:00`, with optional fractional seconds) or an
+/// RFC 3339 timestamp (`2022-07-05T20:00:00+05:30`)
+pub fn parse_recovery_time(time: &str) -> Option<u64> {
+    let time = match DateTime::parse_from_rfc3339(time) {
+        Ok(time) => time.naive_utc(),
+        Err(_) => NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.f").ok()?,
+    };
+    u64::try_from(time.timestamp_millis()).ok()
+}
+
+/// Read and parse the configuration file at `path`
+fn read_file(path: &str) -> Result<Configset, ConfigError> {
+    let file = fs::read(path)?;
+    let cfg_file: ConfigFile = toml::from_slice(&file)?;
+    Ok(cfgfile::from_file(cfg_file))
+}
+
+/// Read the configuration file that the server was started with again, validating it just like
+/// on startup (see [`crate::services::confreload`]). Returns `None` if the server wasn't
+/// started with a configuration file
+pub fn reload_file() -> Option<Result<ConfigType, ConfigError>> {
+    let path = CONFIG_FILE.lock().clone()?;
+    Some(self::read_file(&path).and_then(|cfg| cfg.into_result(None)))
+}
@@ -0,0 +1,250 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # The small object arena
+//!
+//! Small keys and values aren't allocated on their own. Instead, a [`SharedSlice`] and its
+//! shared state go in a single block that's carved out of a large chunk. Blocks come in size
+//! classes (multiples of [`BLOCK_ALIGN`] bytes, up to [`MAX_BLOCK`]) and a freed block is kept on
+//! the free list of its class for the next slice of that class, so that hundreds of millions of
+//! tiny entries don't cost twice as many allocations (and the fragmentation that comes with
+//! them)
+//!
+//! Every class is split into stripes so that threads don't contend on a single lock: a thread
+//! allocates from and frees to its own stripe. Chunks are never given back to the allocator, so
+//! the memory freed by deleting small entries is only reused by new small entries ([`stats`]
+//! shows how much of it is in use). The memory accounting counts all of it as used, including
+//! the free blocks (see [`crate::memory`])
+//!
+//! [`SharedSlice`]: super::SharedSlice
+
+use {
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        alloc::{alloc, handle_alloc_error, Layout},
+        ptr::{self, NonNull},
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// The alignment of a block (and the granularity of the size classes)
+pub const BLOCK_ALIGN: usize = 16;
+/// The size of the largest block
+pub const MAX_BLOCK: usize = 128;
+/// The number of size classes
+const CLASSES: usize = MAX_BLOCK / BLOCK_ALIGN;
+/// The size of the chunks that blocks are carved out of
+const CHUNK_SIZE: usize = 64 * 1024;
+/// The number of stripes of every class
+const STRIPES: usize = 16;
+
+static ARENA: [[Mutex<SizeClass>; CLASSES]; STRIPES] =
+    [const { [const { const_mutex(SizeClass::new()) }; CLASSES] }; STRIPES];
+/// The stripe that's handed to the next thread
+static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static STRIPE: usize = NEXT_STRIPE.fetch_add(1, Ordering::Relaxed) % STRIPES;
+}
+
+/// Returns true if a block of `size` bytes comes from the arena
+pub const fn fits(size: usize) -> bool {
+    size <= MAX_BLOCK
+}
+
+const fn class_of(size: usize) -> usize {
+    if size == 0 {
+        0
+    } else {
+        size.div_ceil(BLOCK_ALIGN) - 1
+    }
+}
+
+const fn block_size(class: usize) -> usize {
+    (class + 1) * BLOCK_ALIGN
+}
+
+fn stripe() -> &'static [Mutex<SizeClass>; CLASSES] {
+    // a thread that's going away still frees its slices
+    &ARENA[STRIPE.try_with(|stripe| *stripe).unwrap_or(0)]
+}
+
+/// Returns a block (aligned to [`BLOCK_ALIGN`]) that can hold `size` bytes, which must
+/// [fit](fits) in the arena
+pub fn alloc_block(size: usize) -> NonNull<u8> {
+    debug_assert!(fits(size));
+    let class = class_of(size);
+    unsafe {
+        // UNSAFE(@ohsayan): the class never hands out a null block
+        NonNull::new_unchecked(stripe()[class].lock().alloc(block_size(class)))
+    }
+}
+
+/// Puts a block back in the arena
+///
+/// # Safety
+/// The block must have been returned by [`alloc_block`] for the same `size`, and must not be
+/// used after this
+pub unsafe fn free_block(block: NonNull<u8>, size: usize) {
+    stripe()[class_of(size)].lock().free(block.as_ptr())
+}
+
+/// The memory that the arena holds
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of blocks in use
+    pub blocks: usize,
+    /// The bytes in the blocks in use
+    pub used: usize,
+    /// The bytes that were taken from the allocator
+    pub reserved: usize,
+}
+
+/// Returns the memory that the arena holds
+pub fn stats() -> Stats {
+    let (mut blocks, mut used, mut reserved) = (0, 0, 0);
+    for class in 0..CLASSES {
+        // a block can be freed in a stripe other than the one it came from
+        let live: isize = ARENA.iter().map(|stripe| stripe[class].lock().live).sum();
+        let live = live.max(0) as usize;
+        blocks += live;
+        used += live * block_size(class);
+    }
+    for stripe in ARENA.iter() {
+        reserved += stripe.iter().map(|sc| sc.lock().chunks).sum::<usize>() * CHUNK_SIZE;
+    }
+    Stats {
+        blocks,
+        used,
+        reserved,
+    }
+}
+
+/// The blocks of one size class in one stripe
+struct SizeClass {
+    /// the freed blocks, linked through their first word
+    free: *mut u8,
+    /// the start of the unused part of the newest chunk
+    next: *mut u8,
+    /// the end of the newest chunk
+    end: *mut u8,
+    /// the blocks allocated here less the ones freed here
+    live: isize,
+    /// the chunks allocated here
+    chunks: usize,
+}
+
+// UNSAFE(@ohsayan): the blocks aren't tied to a thread, and the pointers are only touched with
+// the lock held
+unsafe impl Send for SizeClass {}
+
+impl SizeClass {
+    const fn new() -> Self {
+        Self {
+            free: ptr::null_mut(),
+            next: ptr::null_mut(),
+            end: ptr::null_mut(),
+            live: 0,
+            chunks: 0,
+        }
+    }
+    /// Returns a block of `size` bytes, from the free list if possible
+    unsafe fn alloc(&mut self, size: usize) -> *mut u8 {
+        self.live += 1;
+        if !self.free.is_null() {
+            let block = self.free;
+            self.free = *(block as *mut *mut u8);
+            return block;
+        }
+        if (self.end as usize) - (self.next as usize) < size {
+            // the rest of the chunk (if any) is too small, so start a new one
+            let layout = Layout::from_size_align_unchecked(CHUNK_SIZE, BLOCK_ALIGN);
+            let chunk = alloc(layout);
+            if chunk.is_null() {
+                handle_alloc_error(layout)
+            }
+            self.next = chunk;
+            self.end = chunk.add(CHUNK_SIZE);
+            self.chunks += 1;
+        }
+        let block = self.next;
+        self.next = block.add(size);
+        block
+    }
+    /// Puts a block on the free list
+    unsafe fn free(&mut self, block: *mut u8) {
+        *(block as *mut *mut u8) = self.free;
+        self.free = block;
+        self.live -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_size, class_of, SizeClass, BLOCK_ALIGN, CHUNK_SIZE};
+
+    #[test]
+    fn test_size_classes() {
+        assert_eq!(class_of(0), 0);
+        assert_eq!(class_of(16), 0);
+        assert_eq!(class_of(17), 1);
+        assert_eq!(class_of(128), 7);
+        for size in 0..=128 {
+            let block = block_size(class_of(size));
+            assert!(block >= size && block < size.max(1) + BLOCK_ALIGN);
+        }
+    }
+
+    #[test]
+    fn test_reuse() {
+        let mut class = SizeClass::new();
+        unsafe {
+            let a = class.alloc(32);
+            let b = class.alloc(32);
+            assert_eq!(b as usize - a as usize, 32);
+            assert_eq!(a as usize % BLOCK_ALIGN, 0);
+            class.free(a);
+            // the freed block is handed out before the chunk is touched again
+            assert_eq!(class.alloc(32), a);
+            assert_eq!(class.live, 2);
+            assert_eq!(class.chunks, 1);
+        }
+    }
+
+    #[test]
+    fn test_new_chunk() {
+        let mut class = SizeClass::new();
+        unsafe {
+            for _ in 0..CHUNK_SIZE / 48 {
+                class.alloc(48);
+            }
+            assert_eq!(class.chunks, 1);
+            // the 16 bytes left in the chunk aren't enough
+            class.alloc(48);
+            assert_eq!(class.chunks, 2);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Bloom filters
+//!
+//! A [`BloomFilter`] tells whether an item was *possibly* added to it (with a configurable
+//! false positive rate) or was *definitely not* added to it, without storing the items
+//! themselves. A filter is sized for an expected number of items when it's created and adding
+//! more items than that raises the false positive rate above the configured rate
+//!
+//! Items are hashed with [`hash64`] which, unlike the hashers in `std`, is stable across
+//! releases and platforms so that a filter restored from disk still finds its items
+
+use core::f64::consts::LN_2;
+
+/// The false positive rate for filters created implicitly (by adding to a missing filter)
+pub const DEFAULT_ERROR_RATE: f64 = 0.01;
+/// The capacity of filters created implicitly (by adding to a missing filter)
+pub const DEFAULT_CAPACITY: u64 = 1000;
+/// The largest filter that can be created, in bits (512MB)
+pub const MAX_BITS: u64 = 1 << 32;
+/// The size of the header of a serialized filter
+const HEADER_SIZE: usize = 36;
+
+/// A 64-bit hash (FNV-1a followed by the murmur3 finalizer) that doesn't change across
+/// releases or platforms
+pub fn hash64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    fmix64(hash)
+}
+
+const fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51afd7ed558ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ceb9fe1a85ec53);
+    k ^ (k >> 33)
+}
+
+/// Returns the bit positions for an item (using double hashing)
+fn positions(item: &[u8], hashes: u32, nbits: u64) -> impl Iterator<Item = u64> {
+    let h1 = hash64(item);
+    // the step must be odd so that it never degenerates to zero
+    let h2 = fmix64(h1 ^ 0x9e3779b97f4a7c15) | 1;
+    (0..hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % nbits)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BloomFilter {
+    /// the configured false positive rate
+    error_rate: f64,
+    /// the number of items the filter was sized for
+    capacity: u64,
+    /// the number of items added (items that were possibly present aren't counted)
+    items: u64,
+    /// the number of bits set per item
+    hashes: u32,
+    /// the size of the filter in bits
+    nbits: u64,
+    bits: Vec<u64>,
+}
+
+impl Default for BloomFilter {
+    fn default() -> Self {
+        // the defaults are always valid
+        Self::new(DEFAULT_ERROR_RATE, DEFAULT_CAPACITY).unwrap()
+    }
+}
+
+impl BloomFilter {
+    /// Create an empty filter for `capacity` items with the given false positive rate.
+    /// Returns `None` if the rate isn't in `(0, 1)`, if the capacity is zero or if the filter
+    /// would be larger than [`MAX_BITS`]
+    pub fn new(error_rate: f64, capacity: u64) -> Option<Self> {
+        if !(error_rate > 0.0 && error_rate < 1.0) || capacity == 0 {
+            return None;
+        }
+        // m = -n * ln(p) / ln(2)^2 and k = (m / n) * ln(2)
+        let nbits = (-(capacity as f64) * error_rate.ln() / (LN_2 * LN_2)).ceil();
+        if nbits > MAX_BITS as f64 {
+            return None;
+        }
+        let nbits = (nbits as u64).max(64);
+        let hashes = ((nbits as f64 / capacity as f64) * LN_2).round().max(1.0) as u32;
+        Some(Self {
+            error_rate,
+            capacity,
+            items: 0,
+            hashes,
+            nbits,
+            bits: vec![0; Self::words_for(nbits)],
+        })
+    }
+    const fn words_for(nbits: u64) -> usize {
+        ((nbits + 63) / 64) as usize
+    }
+    /// Add an item, returning true if it definitely wasn't present before
+    pub fn add(&mut self, item: &[u8]) -> bool {
+        let mut added = false;
+        for pos in positions(item, self.hashes, self.nbits) {
+            let (word, mask) = ((pos / 64) as usize, 1u64 << (pos % 64));
+            added |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        self.items += added as u64;
+        added
+    }
+    /// Returns true if the item was possibly added and false if it definitely wasn't
+    pub fn contains(&self, item: &[u8]) -> bool {
+        positions(item, self.hashes, self.nbits)
+            .all(|pos| self.bits[(pos / 64) as usize] & (1u64 << (pos % 64)) != 0)
+    }
+    pub const fn error_rate(&self) -> f64 {
+        self.error_rate
+    }
+    pub const fn capacity(&self) -> u64 {
+        self.capacity
+    }
+    /// Returns the number of (distinct) items added to the filter
+    pub const fn len(&self) -> u64 {
+        self.items
+    }
+    pub const fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+    /// Returns the size of the bitmap in bytes
+    pub fn size(&self) -> usize {
+        self.bits.len() * 8
+    }
+    /// Returns the serialized form of the filter:
+    /// `[8B: error rate][8B: capacity][8B: items][4B: hashes][8B: bits]([8B: word])*`, where
+    /// everything is little endian (and the error rate is stored as the bits of an `f64`)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.size());
+        bytes.extend_from_slice(&self.error_rate.to_bits().to_le_bytes());
+        bytes.extend_from_slice(&self.capacity.to_le_bytes());
+        bytes.extend_from_slice(&self.items.to_le_bytes());
+        bytes.extend_from_slice(&self.hashes.to_le_bytes());
+        bytes.extend_from_slice(&self.nbits.to_le_bytes());
+        for word in self.bits.iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+    /// Restore a filter from the form returned by [`Self::to_bytes`]. Returns `None` if the
+    /// bytes are corrupted
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_SIZE {
+            return None;
+        }
+        let (header, words) = bytes.split_at(HEADER_SIZE);
+        let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        let error_rate = f64::from_bits(u64_at(0));
+        let (capacity, items) = (u64_at(8), u64_at(16));
+        let hashes = u32::from_le_bytes(header[24..28].try_into().unwrap());
+        let nbits = u64_at(28);
+        let is_sane = error_rate > 0.0
+            && error_rate < 1.0
+            && capacity != 0
+            && hashes != 0
+            && nbits != 0
+            && nbits <= MAX_BITS
+            && words.len() == Self::words_for(nbits) * 8;
+        if !is_sane {
+            return None;
+        }
+        Some(Self {
+            error_rate,
+            capacity,
+            items,
+            hashes,
+            nbits,
+            bits: words
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect(),
+        })
+    }
+}
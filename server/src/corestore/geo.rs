@@ -0,0 +1,279 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Geo indexes
+//!
+//! A [`GeoIndex`] holds unique members, each of which has a location (a longitude and a
+//! latitude). Members are ordered by the 52-bit geohash of their location, so all the members
+//! in a geohash cell are next to each other and a radius search only has to scan the few
+//! cells that cover the radius (instead of every member)
+
+use {
+    super::SharedSlice,
+    std::{
+        cmp::Ordering,
+        collections::{BTreeSet, HashMap},
+    },
+};
+
+/// The smallest longitude
+pub const LON_MIN: f64 = -180.0;
+/// The largest longitude
+pub const LON_MAX: f64 = 180.0;
+/// The smallest latitude (like everyone else, we use the bounds of the Web Mercator projection)
+pub const LAT_MIN: f64 = -85.05112878;
+/// The largest latitude
+pub const LAT_MAX: f64 = 85.05112878;
+/// The number of bits used for each axis of a geohash
+const STEP_MAX: u32 = 26;
+/// The radius of the earth in meters
+const EARTH_RADIUS: f64 = 6372797.560856;
+
+/// Returns true if the location is within the supported bounds
+pub fn is_valid_location(lon: f64, lat: f64) -> bool {
+    (LON_MIN..=LON_MAX).contains(&lon) && (LAT_MIN..=LAT_MAX).contains(&lat)
+}
+
+/// Returns the great-circle distance between two locations in meters (using the haversine
+/// formula)
+pub fn distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((lon2 - lon1).to_radians() / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+}
+
+/// Returns the index of the cell (at the given step) that the value falls in
+fn cell_of(value: f64, min: f64, max: f64, step: u32) -> u64 {
+    let cells = 1u64 << step;
+    let offset = (value - min) / (max - min) * cells as f64;
+    // the largest value belongs to the last cell
+    (offset as u64).min(cells - 1)
+}
+
+/// Interleave the bits of the latitude and longitude cells (the longitude takes the odd bits)
+fn interleave(lat: u64, lon: u64) -> u64 {
+    (0..STEP_MAX).fold(0, |hash, bit| {
+        hash | ((lat >> bit) & 1) << (2 * bit) | ((lon >> bit) & 1) << (2 * bit + 1)
+    })
+}
+
+/// Returns the 52-bit geohash of a location
+pub fn geohash(lon: f64, lat: f64) -> u64 {
+    interleave(
+        cell_of(lat, LAT_MIN, LAT_MAX, STEP_MAX),
+        cell_of(lon, LON_MIN, LON_MAX, STEP_MAX),
+    )
+}
+
+/// Returns the (half-open) geohash ranges of the cells that cover every location within
+/// `radius` meters of the given location. The cells are picked so that there are only a few
+/// of them, and the ranges never overlap
+fn cell_ranges(lon: f64, lat: f64, radius: f64) -> Vec<(u64, u64)> {
+    // the bounding box of the circle (see "Finding Points Within a Distance of a
+    // Latitude/Longitude Using Bounding Coordinates" by Jan Matuschek); the tiny margin
+    // makes up for rounding errors
+    let angle = radius / EARTH_RADIUS;
+    let dlat = angle.to_degrees() + 1e-9;
+    let lon_ranges = if lat + dlat >= 90.0 || lat - dlat <= -90.0 {
+        // the circle covers a pole, so it spans every longitude
+        vec![(LON_MIN, LON_MAX)]
+    } else {
+        let dlon = (angle.sin() / lat.to_radians().cos())
+            .min(1.0)
+            .asin()
+            .to_degrees()
+            + 1e-9;
+        let (lo, hi) = (lon - dlon, lon + dlon);
+        if hi - lo >= LON_MAX - LON_MIN {
+            vec![(LON_MIN, LON_MAX)]
+        } else if lo < LON_MIN {
+            // wrap around the antimeridian
+            vec![(lo + 360.0, LON_MAX), (LON_MIN, hi)]
+        } else if hi > LON_MAX {
+            vec![(lo, LON_MAX), (LON_MIN, hi - 360.0)]
+        } else {
+            vec![(lo, hi)]
+        }
+    };
+    let (lat_lo, lat_hi) = ((lat - dlat).max(LAT_MIN), (lat + dlat).min(LAT_MAX));
+    let lon_span = lon_ranges
+        .iter()
+        .map(|(lo, hi)| hi - lo)
+        .fold(0.0, f64::max);
+    // the smallest cells that are at least as large as the box, so that the box only spans
+    // two cells along each axis
+    let step = (0..=STEP_MAX)
+        .rev()
+        .find(|step| {
+            let cells = (1u64 << step) as f64;
+            (LAT_MAX - LAT_MIN) / cells >= lat_hi - lat_lo
+                && (LON_MAX - LON_MIN) / cells >= lon_span
+        })
+        .unwrap_or(0);
+    let shift = 2 * (STEP_MAX - step);
+    let lat_cells =
+        cell_of(lat_lo, LAT_MIN, LAT_MAX, step)..=cell_of(lat_hi, LAT_MIN, LAT_MAX, step);
+    let mut ranges = Vec::new();
+    for (lo, hi) in lon_ranges {
+        for lat_cell in lat_cells.clone() {
+            let lon_cells =
+                cell_of(lo, LON_MIN, LON_MAX, step)..=cell_of(hi, LON_MIN, LON_MAX, step);
+            for lon_cell in lon_cells {
+                let start = interleave(lat_cell, lon_cell) << shift;
+                ranges.push((start, start + (1 << shift)));
+            }
+        }
+    }
+    // the wrapped ranges might share cells
+    ranges.sort_unstable();
+    ranges.dedup();
+    ranges
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A location
+pub struct Point {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GeoIndex {
+    /// member -> location
+    points: HashMap<SharedSlice, Point>,
+    /// (geohash, member) in ascending order
+    ordered: BTreeSet<(u64, SharedSlice)>,
+}
+
+impl PartialEq for GeoIndex {
+    fn eq(&self, other: &Self) -> bool {
+        // the index is derived from the points
+        self.points == other.points
+    }
+}
+
+impl GeoIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of members
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+    /// Add a member or update its location. Returns true if the member was newly added. The
+    /// caller must make sure that the location is valid
+    pub fn insert(&mut self, member: SharedSlice, lon: f64, lat: f64) -> bool {
+        let hash = geohash(lon, lat);
+        match self.points.insert(member.clone(), Point { lon, lat }) {
+            Some(old) => {
+                let old_hash = geohash(old.lon, old.lat);
+                if old_hash != hash {
+                    self.ordered.remove(&(old_hash, member.clone()));
+                    self.ordered.insert((hash, member));
+                }
+                false
+            }
+            None => {
+                self.ordered.insert((hash, member));
+                true
+            }
+        }
+    }
+    /// Returns the location of a member
+    pub fn position(&self, member: &[u8]) -> Option<Point> {
+        self.points.get(member).copied()
+    }
+    /// Returns the members within `radius` meters of the location along with their distance
+    /// (in meters), nearest first
+    pub fn search_radius(&self, lon: f64, lat: f64, radius: f64) -> Vec<(SharedSlice, f64)> {
+        let mut found = Vec::new();
+        for (start, end) in cell_ranges(lon, lat, radius) {
+            let cell = self
+                .ordered
+                .range((start, SharedSlice::new(&[]))..)
+                .take_while(|(hash, _)| *hash < end);
+            for (_, member) in cell {
+                let point = self.points[member];
+                let dist = distance(lon, lat, point.lon, point.lat);
+                if dist <= radius {
+                    found.push((member.clone(), dist));
+                }
+            }
+        }
+        found.sort_by(|(m1, d1), (m2, d2)| match d1.total_cmp(d2) {
+            Ordering::Equal => m1.cmp(m2),
+            ord => ord,
+        });
+        found
+    }
+    /// Returns an iterator over the members and their locations (in geohash order)
+    pub fn iter(&self) -> impl Iterator<Item = (&SharedSlice, Point)> {
+        self.ordered
+            .iter()
+            .map(|(_, member)| (member, self.points[member]))
+    }
+}
+
+#[test]
+fn geo_index_search_radius() {
+    let mut geo = GeoIndex::new();
+    assert!(geo.insert("palermo".into(), 13.361389, 38.115556));
+    assert!(geo.insert("catania".into(), 15.087269, 37.502669));
+    assert!(geo.insert("dateline-east".into(), 179.9, 0.0));
+    assert!(geo.insert("dateline-west".into(), -179.9, 0.0));
+    let dist = distance(13.361389, 38.115556, 15.087269, 37.502669);
+    assert!((dist - 166274.15).abs() < 1.0, "{dist}");
+    let members = |found: Vec<(SharedSlice, f64)>| -> Vec<SharedSlice> {
+        found.into_iter().map(|(member, _)| member).collect()
+    };
+    assert_eq!(
+        members(geo.search_radius(15.0, 37.0, 200_000.0)),
+        vec![SharedSlice::from("catania"), SharedSlice::from("palermo")]
+    );
+    assert_eq!(
+        members(geo.search_radius(15.0, 37.0, 100_000.0)),
+        vec![SharedSlice::from("catania")]
+    );
+    assert!(geo.search_radius(0.0, 0.0, 1000.0).is_empty());
+    // the search wraps around the antimeridian
+    assert_eq!(geo.search_radius(180.0, 0.0, 20_000.0).len(), 2);
+    // move a member
+    assert!(!geo.insert("catania".into(), 0.0, 0.0));
+    assert_eq!(geo.len(), 4);
+    assert_eq!(
+        members(geo.search_radius(0.0, 0.0, 1000.0)),
+        vec![SharedSlice::from("catania")]
+    );
+    assert_eq!(
+        members(geo.search_radius(15.0, 37.0, 200_000.0)),
+        vec![SharedSlice::from("palermo")]
+    );
+}
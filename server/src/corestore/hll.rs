@@ -0,0 +1,190 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # HyperLogLog
+//!
+//! A [`HyperLogLog`] estimates the number of distinct items added to it using a fixed amount
+//! of memory (at most [`REGISTERS`] bytes), with a standard error of about 0.81%. Estimates
+//! from several HLLs can be combined by merging them, which is the same as adding all their
+//! items to a single HLL
+//!
+//! Items are hashed with [`hash64`] (the same hash that bloom filters use) so that an HLL
+//! restored from disk keeps counting items it has already seen as duplicates
+
+use super::bloom::hash64;
+
+/// The number of bits of the hash that select a register
+const PRECISION: u32 = 14;
+/// The number of registers
+pub const REGISTERS: usize = 1 << PRECISION;
+/// The largest value a register can hold (the position of the guard bit)
+const MAX_RANK: u8 = (64 - PRECISION + 1) as u8;
+/// Tag for the sparse serialized form: `[4B: count]([2B: index][1B: rank])*`
+const TAG_SPARSE: u8 = 0;
+/// Tag for the dense serialized form: `[REGISTERS B: ranks]`
+const TAG_DENSE: u8 = 1;
+/// The size of one entry in the sparse serialized form
+const SPARSE_ENTRY_SIZE: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HyperLogLog {
+    /// the registers (lazily allocated; an empty vector means that every register is zero)
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Create an empty HLL
+    pub const fn new() -> Self {
+        Self {
+            registers: Vec::new(),
+        }
+    }
+    /// Returns true if nothing was added to this HLL
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|rank| *rank == 0)
+    }
+    /// Returns the number of bytes used by the registers
+    pub fn size(&self) -> usize {
+        self.registers.len()
+    }
+    fn registers_mut(&mut self) -> &mut [u8] {
+        if self.registers.is_empty() {
+            self.registers = vec![0; REGISTERS];
+        }
+        &mut self.registers
+    }
+    /// Add an item. Returns true if the estimate (possibly) changed
+    pub fn add(&mut self, item: &[u8]) -> bool {
+        let hash = hash64(item);
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // the guard bit caps the rank if the remaining bits are all zero
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        let register = &mut self.registers_mut()[index];
+        if rank > *register {
+            *register = rank;
+            true
+        } else {
+            false
+        }
+    }
+    /// Merge another HLL into this one. Returns true if any register changed
+    pub fn merge(&mut self, other: &Self) -> bool {
+        if other.registers.is_empty() {
+            return false;
+        }
+        let mut changed = false;
+        for (register, rank) in self.registers_mut().iter_mut().zip(other.registers.iter()) {
+            if rank > register {
+                *register = *rank;
+                changed = true;
+            }
+        }
+        changed
+    }
+    /// Returns the estimated number of distinct items
+    pub fn count(&self) -> u64 {
+        if self.registers.is_empty() {
+            return 0;
+        }
+        let m = REGISTERS as f64;
+        let mut sum = 0.0;
+        let mut zeros = 0usize;
+        for rank in self.registers.iter() {
+            sum += 1.0 / (1u64 << rank) as f64;
+            zeros += (*rank == 0) as usize;
+        }
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let estimate = alpha * m * m / sum;
+        if estimate <= 2.5 * m && zeros != 0 {
+            // small range correction (linear counting)
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            // with 64-bit hashes, there's no need for a large range correction
+            estimate.round() as u64
+        }
+    }
+    /// Returns the serialized form of this HLL. Sparse HLLs only store their non-zero
+    /// registers, while the rest store every register
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let nonzero = self.registers.iter().filter(|rank| **rank != 0).count();
+        let sparse_size = 4 + nonzero * SPARSE_ENTRY_SIZE;
+        if sparse_size < REGISTERS {
+            let mut bytes = Vec::with_capacity(1 + sparse_size);
+            bytes.push(TAG_SPARSE);
+            bytes.extend_from_slice(&(nonzero as u32).to_le_bytes());
+            for (index, rank) in self.registers.iter().enumerate() {
+                if *rank != 0 {
+                    bytes.extend_from_slice(&(index as u16).to_le_bytes());
+                    bytes.push(*rank);
+                }
+            }
+            bytes
+        } else {
+            let mut bytes = Vec::with_capacity(1 + REGISTERS);
+            bytes.push(TAG_DENSE);
+            bytes.extend_from_slice(&self.registers);
+            bytes
+        }
+    }
+    /// Restore an HLL from the form returned by [`Self::to_bytes`]. Returns `None` if the
+    /// bytes are corrupted
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (tag, payload) = bytes.split_first()?;
+        let registers = match *tag {
+            TAG_SPARSE => {
+                if payload.len() < 4 {
+                    return None;
+                }
+                let (count, entries) = payload.split_at(4);
+                let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+                if entries.len() != count.checked_mul(SPARSE_ENTRY_SIZE)? {
+                    return None;
+                }
+                let mut registers = Vec::new();
+                if count != 0 {
+                    registers = vec![0; REGISTERS];
+                }
+                for entry in entries.chunks_exact(SPARSE_ENTRY_SIZE) {
+                    let index = u16::from_le_bytes([entry[0], entry[1]]) as usize;
+                    let rank = entry[2];
+                    if index >= REGISTERS || rank == 0 || rank > MAX_RANK {
+                        return None;
+                    }
+                    registers[index] = rank;
+                }
+                registers
+            }
+            TAG_DENSE => {
+                if payload.len() != REGISTERS || payload.iter().any(|rank| *rank > MAX_RANK) {
+                    return None;
+                }
+                payload.to_vec()
+            }
+            _ => return None,
+        };
+        Some(Self { registers })
+    }
+}
@@ -28,12 +28,15 @@
 
 use {
     crate::corestore::map::{
-        bref::{Entry, OccupiedEntry, Ref, VacantEntry},
+        bref::{Entry, OccupiedEntry, Ref, RefMut, VacantEntry},
+        capture::Capture,
         iter::{BorrowedIter, OwnedIter},
         Skymap,
     },
     ahash::RandomState,
-    std::{borrow::Borrow, hash::Hash, iter::FromIterator, ops::Deref},
+    std::{
+        borrow::Borrow, collections::HashMap, hash::Hash, iter::FromIterator, ops::Deref, sync::Arc,
+    },
 };
 
 type HashTable<K, V> = Skymap<K, V, RandomState>;
@@ -74,12 +77,32 @@ impl<K: Eq + Hash, V> Coremap<K, V> {
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+    /// Returns true if the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
     /// Clears the inner table!
     pub fn clear(&self) {
         self.inner.clear()
     }
 }
 
+impl<K: Eq + Hash, V> Coremap<K, V> {
+    /// Returns the number of shards (see [`Self::shrink_shard`])
+    pub fn shard_count(&self) -> usize {
+        self.inner.shard_count()
+    }
+    /// Shrink the capacity of a shard to fit its entries, returning the number of bytes that
+    /// were freed (not counting what the entries point to)
+    pub fn shrink_shard(&self, idx: usize) -> usize {
+        self.inner.shrink_shard(idx) * std::mem::size_of::<(K, V)>()
+    }
+    /// Call `f` on every value in a shard (see [`Skymap::update_shard`])
+    pub fn update_shard(&self, idx: usize, f: impl FnMut(&mut V)) {
+        self.inner.update_shard(idx, f)
+    }
+}
+
 impl<K, V> Coremap<K, V>
 where
     K: Eq + Hash,
@@ -120,6 +143,14 @@ where
     {
         self.inner.get(key)
     }
+    /// Get a mutable reference to the value of a key, if it exists
+    pub fn get_mut<Q>(&self, key: &Q) -> Option<RefMut<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.get_mut(key)
+    }
     /// Returns true if the non-existent key was assigned to a value
     pub fn true_if_insert(&self, k: K, v: V) -> bool {
         if let Entry::Vacant(ve) = self.inner.entry(k) {
@@ -147,6 +178,28 @@ where
     pub fn upsert(&self, k: K, v: V) {
         let _ = self.inner.insert(k, v);
     }
+    /// Update or insert, returning the old value (if any)
+    pub fn swap(&self, k: K, v: V) -> Option<V> {
+        self.inner.insert(k, v)
+    }
+    /// Move the value of `from` to the new key `to`, if `to` doesn't exist. See
+    /// [`Skymap::rename`]
+    pub fn rename<Q>(&self, from: &Q, to: K) -> Option<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.rename(from, to)
+    }
+    /// Same as [`Self::rename`], but only if the value satisfies the given condition. See
+    /// [`Skymap::rename_if`]
+    pub fn rename_if<Q>(&self, from: &Q, to: K, f: impl FnOnce(&V) -> bool) -> Option<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.rename_if(from, to, f)
+    }
     /// Returns true if the value was updated
     pub fn true_if_update(&self, k: K, v: V) -> bool {
         if let Entry::Occupied(mut oe) = self.inner.entry(k) {
@@ -182,7 +235,24 @@ impl<K: Eq + Hash, V: Clone> Coremap<K, V> {
     }
 }
 
+impl<K: Eq + Hash + Clone, V: Clone> Coremap<K, V> {
+    /// Start a capture of the key/value pairs (see [`Skymap::start_capture`])
+    pub fn start_capture(&self) -> Arc<Capture<K, V>> {
+        self.inner.start_capture()
+    }
+    /// Returns a copy of all the key/value pairs as of the time that the capture started (see
+    /// [`Skymap::cloned_entries`])
+    pub fn cloned_entries(&self, capture: Arc<Capture<K, V>>) -> HashMap<K, V> {
+        self.inner.cloned_entries(capture)
+    }
+}
+
 impl<K: Eq + Hash + Clone, V> Coremap<K, V> {
+    /// Returns the next batch of keys of a scan, along with the cursor to continue from (see
+    /// [`Skymap::scan`])
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<K>) {
+        self.inner.scan(cursor, count)
+    }
     /// Returns atleast `count` number of keys from the hashtable
     pub fn get_keys(&self, count: usize) -> Vec<K> {
         let mut v = Vec::with_capacity(count);
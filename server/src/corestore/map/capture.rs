@@ -0,0 +1,125 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Captures
+//!
+//! A capture records the value that every key had when it started, before the key is first
+//! changed (or `None` if the key didn't exist). The writes to a [`super::Skymap`] record into
+//! its captures while they hold the lock on the key's shard, so a copy of the map that's taken
+//! shard by shard while the writes continue can be turned into a copy as of the time the
+//! capture started, by putting the recorded values back (see [`Capture::take_preimages`])
+
+use {
+    core::{
+        hash::Hash,
+        mem,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+    parking_lot::{const_mutex, Mutex},
+    std::{collections::HashMap, sync::Arc},
+};
+
+/// The values that the keys had when a capture started
+pub struct Capture<K, V> {
+    preimages: Mutex<HashMap<K, Option<V>>>,
+    clone_key: fn(&K) -> K,
+    clone_value: fn(&V) -> V,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Capture<K, V> {
+    fn new() -> Self {
+        Self {
+            preimages: Mutex::new(HashMap::new()),
+            clone_key: K::clone,
+            clone_value: V::clone,
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Capture<K, V> {
+    /// Record the value of the key, unless it was recorded already
+    fn record(&self, key: &K, value: Option<&V>) {
+        let mut preimages = self.preimages.lock();
+        if !preimages.contains_key(key) {
+            preimages.insert((self.clone_key)(key), value.map(self.clone_value));
+        }
+    }
+    /// Returns the values that the changed keys had when the capture started
+    pub fn take_preimages(&self) -> HashMap<K, Option<V>> {
+        mem::take(&mut *self.preimages.lock())
+    }
+}
+
+/// The captures that are running on a map
+pub struct Captures<K, V> {
+    /// this is only set while there are captures, so that writes don't have to lock anything
+    /// otherwise
+    active: AtomicBool,
+    running: Mutex<Vec<Arc<Capture<K, V>>>>,
+}
+
+impl<K, V> Captures<K, V> {
+    pub const fn new() -> Self {
+        Self {
+            active: AtomicBool::new(false),
+            running: const_mutex(Vec::new()),
+        }
+    }
+    /// Returns true if the writes have to record into the captures
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+    /// Stop the capture
+    pub fn stop(&self, capture: &Arc<Capture<K, V>>) {
+        let mut running = self.running.lock();
+        running.retain(|running| !Arc::ptr_eq(running, capture));
+        self.active.store(!running.is_empty(), Ordering::Release);
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Captures<K, V> {
+    /// Start a new capture
+    pub fn start(&self) -> Arc<Capture<K, V>> {
+        let capture = Arc::new(Capture::new());
+        let mut running = self.running.lock();
+        running.push(capture.clone());
+        self.active.store(true, Ordering::Release);
+        capture
+    }
+}
+
+impl<K: Eq + Hash, V> Captures<K, V> {
+    /// Record the value of the key into every capture. The caller must hold the write lock on
+    /// the key's shard
+    pub fn record(&self, key: &K, value: Option<&V>) {
+        if self.is_active() {
+            self.running
+                .lock()
+                .iter()
+                .for_each(|capture| capture.record(key, value));
+        }
+    }
+}
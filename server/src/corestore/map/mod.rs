@@ -30,9 +30,10 @@
 use {
     self::{
         bref::{Entry, OccupiedEntry, Ref, RefMut, VacantEntry},
+        capture::{Capture, Captures},
         iter::{BorrowedIter, OwnedIter},
     },
-    crate::util::compiler,
+    crate::util::{compiler, Unwrappable},
     core::{
         borrow::Borrow,
         fmt,
@@ -40,12 +41,18 @@ use {
         iter::FromIterator,
         mem,
         num::NonZeroUsize,
+        sync::atomic::{AtomicUsize, Ordering},
     },
     parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard},
-    std::{collections::hash_map::RandomState, thread::available_parallelism},
+    std::{
+        collections::{hash_map::RandomState, HashMap},
+        sync::Arc,
+        thread::available_parallelism,
+    },
 };
 
 pub mod bref;
+pub mod capture;
 pub mod iter;
 
 type LowMap<K, V> = hashbrown::raw::RawTable<(K, V)>;
@@ -54,6 +61,11 @@ type SRlock<'a, K, V> = RwLockReadGuard<'a, hashbrown::raw::RawTable<(K, V)>>;
 type SWlock<'a, K, V> = RwLockWriteGuard<'a, hashbrown::raw::RawTable<(K, V)>>;
 const BITS_IN_USIZE: usize = mem::size_of::<usize>() * 8;
 const DEFAULT_CAP: usize = 128;
+/// The most shards that a map can be split into
+pub const MAX_SHARDS: usize = 65536;
+
+/// The number of shards that new maps are split into (zero picks it from the parallelism)
+static SHARD_COUNT: AtomicUsize = AtomicUsize::new(0);
 
 fn make_hash<K, Q, S>(hash_builder: &S, val: &Q) -> u64
 where
@@ -93,8 +105,25 @@ where
     move |x| k.eq(x.0.borrow())
 }
 
+/// Set the number of shards that the maps created from here on are split into. This has to be
+/// zero (to pick it from the parallelism) or a power of two in `2..=MAX_SHARDS`; maps that
+/// already exist keep their shards
+pub fn set_shard_count(count: usize) {
+    debug_assert!(count == 0 || is_valid_shard_count(count));
+    SHARD_COUNT.store(count, Ordering::Relaxed);
+}
+
+/// Returns true if a map can be split into `count` shards (a single shard would shift the
+/// hash out entirely)
+pub const fn is_valid_shard_count(count: usize) -> bool {
+    count.is_power_of_two() && count > 1 && count <= MAX_SHARDS
+}
+
 fn get_shard_count() -> usize {
-    (available_parallelism().map_or(1, usize::from) * 16).next_power_of_two()
+    match SHARD_COUNT.load(Ordering::Relaxed) {
+        0 => (available_parallelism().map_or(1, usize::from) * 16).next_power_of_two(),
+        count => count,
+    }
 }
 
 const fn cttz(amount: usize) -> usize {
@@ -106,6 +135,8 @@ pub struct Skymap<K, V, S = RandomState> {
     shards: Box<ShardSlice<K, V>>,
     hasher: S,
     shift: usize,
+    /// the writes record the values that they replace into these (see [`capture`])
+    captures: Captures<K, V>,
 }
 
 impl<K, V> Default for Skymap<K, V, RandomState> {
@@ -176,6 +207,7 @@ where
                 .collect(),
             hasher,
             shift,
+            captures: Captures::new(),
         }
     }
     /// Create a new Skymap with the provided hasher
@@ -236,8 +268,10 @@ where
             // begin critical section
             let mut lowtable = self.get_wshard_unchecked(idx);
             if let Some((_, item)) = lowtable.get_mut(hash, ceq(&k)) {
+                self.captures.record(&k, Some(&*item));
                 Some(mem::replace(item, v))
             } else {
+                self.captures.record(&k, None);
                 lowtable.insert(hash, (k, v), make_hasher::<K, _, V, S>(self.h()));
                 None
             }
@@ -255,13 +289,72 @@ where
         unsafe {
             // begin critical section
             let mut lowtable = self.get_wshard_unchecked(idx);
-            match lowtable.remove_entry(hash, ceq(k)) {
-                Some(kv) => Some(kv),
+            match lowtable.find(hash, ceq(k)) {
+                Some(bucket) => {
+                    let (kptr, vptr) = bucket.as_ref();
+                    self.captures.record(kptr, Some(vptr));
+                    Some(lowtable.remove(bucket))
+                }
                 None => None,
             }
             // end critical section
         }
     }
+    /// Atomically move the value of `from` to the new key `to`, but only if `to` doesn't
+    /// exist. Returns `None` if `from` doesn't exist and `Some(false)` if `to` already exists
+    pub fn rename<Q>(&self, from: &Q, to: K) -> Option<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.rename_if(from, to, |_| true)
+    }
+    /// Same as [`Self::rename`], but the value is only moved if it satisfies the given condition.
+    /// `Some(false)` is also returned if the condition isn't satisfied
+    pub fn rename_if<Q>(&self, from: &Q, to: K, f: impl FnOnce(&V) -> bool) -> Option<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let from_hash = make_hash::<K, Q, S>(self.h(), from);
+        let to_hash = make_insert_hash::<K, S>(self.h(), &to);
+        let from_idx = self.determine_shard(from_hash as usize);
+        let to_idx = self.determine_shard(to_hash as usize);
+        unsafe {
+            // begin critical section
+            // the shards are always locked in the same order so that two renames can't deadlock
+            let (mut from_table, mut to_table) = if from_idx == to_idx {
+                (self.get_wshard_unchecked(from_idx), None)
+            } else if from_idx < to_idx {
+                let from_table = self.get_wshard_unchecked(from_idx);
+                (from_table, Some(self.get_wshard_unchecked(to_idx)))
+            } else {
+                let to_table = self.get_wshard_unchecked(to_idx);
+                (self.get_wshard_unchecked(from_idx), Some(to_table))
+            };
+            let from_bucket = from_table.find(from_hash, ceq(from))?;
+            let to_exists = match to_table {
+                Some(ref to_table) => to_table.find(to_hash, ceq(&to)).is_some(),
+                None => from_table.find(to_hash, ceq(&to)).is_some(),
+            };
+            if to_exists || !f(&from_bucket.as_ref().1) {
+                return Some(false);
+            }
+            let (kptr, vptr) = from_bucket.as_ref();
+            self.captures.record(kptr, Some(vptr));
+            self.captures.record(&to, None);
+            let (_, value) = from_table
+                .remove_entry(from_hash, ceq(from))
+                .unsafe_unwrap();
+            to_table.as_mut().unwrap_or(&mut from_table).insert(
+                to_hash,
+                (to, value),
+                make_hasher::<K, _, V, S>(self.h()),
+            );
+            Some(true)
+            // end critical section
+        }
+    }
     /// Remove a key/value from the Skymap if it satisfies a certain condition
     pub fn remove_if<Q>(&self, k: &Q, f: impl FnOnce(&K, &V) -> bool) -> Option<(K, V)>
     where
@@ -277,6 +370,7 @@ where
                 Some(bucket) => {
                     let (kptr, vptr) = bucket.as_ref();
                     if f(kptr, vptr) {
+                        self.captures.record(kptr, Some(vptr));
                         Some(lowtable.remove(bucket))
                     } else {
                         None
@@ -287,6 +381,92 @@ where
             // end critical section
         }
     }
+    /// Returns the number of shards
+    pub fn shard_count(&self) -> usize {
+        self.shards().len()
+    }
+    /// Shrink the capacity of a shard to fit its entries, returning the number of slots that
+    /// were freed. This only locks that one shard
+    pub fn shrink_shard(&self, idx: usize) -> usize {
+        let mut lowtable = match self.shards().get(idx) {
+            Some(shard) => shard.write(),
+            None => return 0,
+        };
+        let capacity = lowtable.capacity();
+        lowtable.shrink_to(0, make_hasher::<K, _, V, S>(self.h()));
+        capacity - lowtable.capacity()
+    }
+    /// Call `f` on every value in a shard, while holding the write lock on it. The values
+    /// must mean the same after `f` is done with them, since the captures don't record them
+    pub fn update_shard(&self, idx: usize, mut f: impl FnMut(&mut V)) {
+        let lowtable = match self.shards().get(idx) {
+            Some(shard) => shard.write(),
+            None => return,
+        };
+        unsafe {
+            // UNSAFE(@ohsayan): we hold the write lock, so every bucket is valid
+            lowtable.iter().for_each(|bucket| f(&mut bucket.as_mut().1));
+        }
+    }
+}
+
+// scan impls
+impl<K: Hash + Clone, V, S: BuildHasher> Skymap<K, V, S> {
+    /// Returns the position of the key in a scan (see [`Self::scan`]). The top bits of the
+    /// position are the key's shard (see [`Self::determine_shard`]), so the keys in a shard
+    /// have contiguous positions
+    fn scan_position(&self, key: &K) -> usize {
+        (make_insert_hash::<K, S>(self.h(), key) as usize).rotate_left(7)
+    }
+    /// Returns `count` (which must not be zero) keys, starting from the position `cursor`,
+    /// along with the position to continue from (which is `0` once the scan is complete, in
+    /// which case there can be fewer keys). A key's position only depends on its hash, so growing or shrinking the map
+    /// doesn't move it: a scan that starts from `0` returns every key that's in the map for the
+    /// entire scan exactly once. Keys that share a position are returned together, which is
+    /// why a batch can have more than `count` keys. Only one shard is locked at a time, and
+    /// nothing is held in between calls
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<K>) {
+        debug_assert!(count != 0);
+        let mut cursor = match usize::try_from(cursor) {
+            Ok(cursor) => cursor,
+            // this isn't a position we could've handed out
+            Err(_) => return (0, Vec::new()),
+        };
+        let mut keys = Vec::with_capacity(count);
+        let mut shard = cursor >> self.shift;
+        while shard < self.shards().len() {
+            if keys.len() == count {
+                return ((shard << self.shift) as u64, keys);
+            }
+            let lowtable = unsafe {
+                // UNSAFE(@ohsayan): we just checked the shard index
+                self.get_rshard_unchecked(shard)
+            };
+            let mut found: Vec<(usize, &K)> = unsafe {
+                // UNSAFE(@ohsayan): we hold the read lock, so every bucket is valid
+                lowtable
+                    .iter()
+                    .map(|bucket| &bucket.as_ref().0)
+                    .map(|key| (self.scan_position(key), key))
+                    .filter(|(position, _)| *position >= cursor)
+                    .collect()
+            };
+            found.sort_unstable_by_key(|(position, _)| *position);
+            let room = count - keys.len();
+            if found.len() > room {
+                // the keys that share a position with the last key that fits come along
+                let last = found[room - 1].0;
+                let end = found.partition_point(|(position, _)| *position <= last);
+                keys.extend(found[..end].iter().map(|(_, key)| (*key).clone()));
+                let next = last.checked_add(1).map_or(0, |next| next as u64);
+                return (next, keys);
+            }
+            keys.extend(found.into_iter().map(|(_, key)| key.clone()));
+            shard += 1;
+            cursor = 0;
+        }
+        (0, keys)
+    }
 }
 
 // lt impls
@@ -327,6 +507,8 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: BuildHasher + Clone> Skymap<K, V, S> {
             let mut lowtable = self.get_wshard_unchecked(idx);
             match lowtable.get_mut(hash, ceq(k)) {
                 Some(&mut (ref kptr, ref mut vptr)) => {
+                    // the value may be changed through the reference
+                    self.captures.record(kptr, Some(&*vptr));
                     let kptr = compiler::extend_lifetime(kptr);
                     let vptr = compiler::extend_lifetime_mut(vptr);
                     Some(RefMut::new(lowtable, kptr, vptr))
@@ -345,6 +527,8 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: BuildHasher + Clone> Skymap<K, V, S> {
             let lowtable = self.get_wshard_unchecked(idx);
             if let Some(elem) = lowtable.find(hash, ceq(&key)) {
                 let (kptr, vptr) = elem.as_mut();
+                // the entry may be changed (or removed)
+                self.captures.record(kptr, Some(&*vptr));
                 let kptr = compiler::extend_lifetime(kptr);
                 let vptr = compiler::extend_lifetime_mut(vptr);
                 Entry::Occupied(OccupiedEntry::new(
@@ -354,6 +538,7 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: BuildHasher + Clone> Skymap<K, V, S> {
                     self.hasher.clone(),
                 ))
             } else {
+                self.captures.record(&key, None);
                 Entry::Vacant(VacantEntry::new(lowtable, key, self.hasher.clone()))
             }
             // end critical section
@@ -369,7 +554,19 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: BuildHasher + Clone> Skymap<K, V, S> {
     }
     /// Clear out all the entries in the Skymap
     pub fn clear(&self) {
-        self.shards().iter().for_each(|shard| shard.write().clear())
+        self.shards().iter().for_each(|shard| {
+            let mut lowtable = shard.write();
+            if self.captures.is_active() {
+                unsafe {
+                    // UNSAFE(@ohsayan): we hold the write lock, so every bucket is valid
+                    lowtable.iter().for_each(|bucket| {
+                        let (kptr, vptr) = bucket.as_ref();
+                        self.captures.record(kptr, Some(vptr));
+                    });
+                }
+            }
+            lowtable.clear()
+        })
     }
 }
 
@@ -394,6 +591,35 @@ impl<'a, K, V: Clone, S: BuildHasher> Skymap<K, V, S> {
     }
 }
 
+impl<K: Eq + Hash + Clone, V: Clone, S> Skymap<K, V, S> {
+    /// Start recording the values that the writes replace (see [`capture`]). No write can be
+    /// in progress when the capture starts
+    pub fn start_capture(&self) -> Arc<Capture<K, V>> {
+        self.captures.start()
+    }
+    /// Returns a copy of all the entries as of the time that the capture started, and stops
+    /// the capture. The shards are copied one at a time while the writes continue, and the
+    /// values that the writes replaced in the meantime are then put back
+    pub fn cloned_entries(&self, capture: Arc<Capture<K, V>>) -> HashMap<K, V> {
+        let mut entries = HashMap::with_capacity(self.len());
+        for shard in self.shards().iter() {
+            let lowtable = shard.read();
+            unsafe {
+                // UNSAFE(@ohsayan): we hold the read lock, so every bucket is valid
+                entries.extend(lowtable.iter().map(|bucket| bucket.as_ref().clone()));
+            }
+        }
+        self.captures.stop(&capture);
+        for (key, value) in capture.take_preimages() {
+            match value {
+                Some(value) => entries.insert(key, value),
+                None => entries.remove(&key),
+            };
+        }
+        entries
+    }
+}
+
 // inner impls
 impl<'a, K: 'a, V: 'a, S> Skymap<K, V, S> {
     /// Get a rlock to a certain stripe
@@ -437,3 +663,115 @@ fn test_entry() {
     assert!(map.entry("hello").is_occupied());
     assert!(map.entry("world").is_vacant());
 }
+
+#[test]
+fn test_rename() {
+    let map = Skymap::default();
+    assert_eq!(map.rename("hello", "world".to_owned()), None);
+    // enough keys to make sure that we rename within and across shards
+    for i in 0..100 {
+        map.insert(format!("old{}", i), i);
+    }
+    for i in 0..100 {
+        assert_eq!(
+            map.rename(&format!("old{}", i), format!("new{}", i)),
+            Some(true)
+        );
+    }
+    for i in 0..100 {
+        assert!(map.get(&format!("old{}", i)).is_none());
+        assert_eq!(*map.get(&format!("new{}", i)).unwrap(), i);
+    }
+    // the new key can't exist
+    assert_eq!(map.rename("new0", "new1".to_owned()), Some(false));
+    assert_eq!(*map.get("new0").unwrap(), 0);
+    assert_eq!(*map.get("new1").unwrap(), 1);
+}
+
+#[test]
+fn test_rename_if() {
+    let map = Skymap::default();
+    map.insert("hello".to_owned(), 1);
+    assert_eq!(
+        map.rename_if("hello", "world".to_owned(), |v| *v == 2),
+        Some(false)
+    );
+    assert_eq!(*map.get("hello").unwrap(), 1);
+    assert!(map.get("world").is_none());
+    assert_eq!(
+        map.rename_if("hello", "world".to_owned(), |v| *v == 1),
+        Some(true)
+    );
+    assert_eq!(*map.get("world").unwrap(), 1);
+}
+
+#[test]
+fn test_cloned_entries() {
+    let map = Skymap::default();
+    for i in 0..100 {
+        map.insert(format!("key{}", i), i);
+    }
+    let capture = map.start_capture();
+    // the writes made after the capture started aren't in the copy
+    map.insert("key0".to_owned(), 1000);
+    map.insert("key100".to_owned(), 100);
+    map.remove("key1");
+    assert_eq!(map.rename("key2", "key101".to_owned()), Some(true));
+    let entries = map.cloned_entries(capture);
+    assert_eq!(entries.len(), 100);
+    assert!((0..100).all(|i| entries.get(&format!("key{}", i)) == Some(&i)));
+    // the copy is independent of the map
+    map.clear();
+    assert_eq!(entries.len(), 100);
+    // and nothing is recorded once the capture is over
+    assert!(!map.captures.is_active());
+}
+
+#[test]
+fn test_shrink_shard() {
+    let map = Skymap::default();
+    for i in 0..10_000 {
+        map.insert(i, i);
+    }
+    for i in 10..10_000 {
+        map.remove(&i);
+    }
+    let capacity = map.capacity();
+    let freed: usize = (0..map.shard_count())
+        .map(|shard| map.shrink_shard(shard))
+        .sum();
+    assert!(freed > 0);
+    assert_eq!(map.capacity(), capacity - freed);
+    assert_eq!(map.shrink_shard(map.shard_count()), 0);
+    for i in 0..10 {
+        assert_eq!(*map.get(&i).unwrap(), i);
+    }
+}
+
+#[test]
+fn test_scan() {
+    let map = Skymap::default();
+    for i in 0..1000 {
+        map.insert(i, i);
+    }
+    let mut seen = Vec::new();
+    let (mut cursor, mut batches) = (0, 0);
+    loop {
+        let (next, keys) = map.scan(cursor, 64);
+        // keys added or removed mid-scan don't disturb the other keys
+        map.insert(1000 + batches, 0);
+        map.remove(&(1000 + batches));
+        assert!(keys.len() >= 64 || next == 0);
+        seen.extend(keys);
+        batches += 1;
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+    assert!(batches >= 1000 / 64);
+    // an empty map completes right away
+    assert_eq!(Skymap::<u8, u8>::default().scan(0, 10), (0, vec![]));
+}
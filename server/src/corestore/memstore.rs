@@ -57,23 +57,29 @@
 use {
     super::KeyspaceResult,
     crate::{
-        auth::Authmap,
+        auth::{Aclmap, Authmap, Rotationmap},
         corestore::{
             array::Array,
             htable::Coremap,
             table::{SystemDataModel, SystemTable, Table},
         },
+        memory::TableUsage,
         registry,
         util::Wrapper,
     },
     core::{borrow::Borrow, hash::Hash},
-    std::sync::Arc,
+    std::sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 uninit_array! {
     const DEFAULT_ARRAY: [u8; 64] = [b'd', b'e', b'f', b'a', b'u', b'l', b't'];
     const SYSTEM_ARRAY: [u8; 64] = [b's', b'y', b's', b't', b'e', b'm'];
     const SYSTEM_AUTH_ARRAY: [u8; 64] = [b'a', b'u', b't', b'h'];
+    const SYSTEM_ACL_ARRAY: [u8; 64] = [b'a', b'c', b'l'];
+    const SYSTEM_ROTATION_ARRAY: [u8; 64] = [b'r', b'o', b't', b'a', b't', b'i', b'o', b'n'];
 }
 
 /// typedef for the keyspace/table IDs. We don't need too much fancy here,
@@ -93,6 +99,45 @@ pub const AUTH: ObjectID = unsafe {
     // SAFETY: known init len
     Array::from_const(SYSTEM_AUTH_ARRAY, 4)
 };
+pub const ACL: ObjectID = unsafe {
+    // SAFETY: known init len
+    Array::from_const(SYSTEM_ACL_ARRAY, 3)
+};
+pub const ROTATION: ObjectID = unsafe {
+    // SAFETY: known init len
+    Array::from_const(SYSTEM_ROTATION_ARRAY, 8)
+};
+
+/// The separator between the name of a table and the name of one of its shards. A shard is
+/// held in the table's keyspace just like any other table, with the ID `<table>.<shard>`.
+/// Since identifiers can't have periods, a shard can never collide with a table
+pub const SHARD_SEPARATOR: u8 = b'.';
+
+/// Returns the ID for the shard of the given table
+///
+/// ## Safety
+/// The caller must ensure that the combined length (including the separator) doesn't
+/// exceed the capacity of an [`ObjectID`]
+pub unsafe fn shard_id(tblid: &[u8], shard: &[u8]) -> ObjectID {
+    let mut id = Vec::with_capacity(tblid.len() + 1 + shard.len());
+    id.extend_from_slice(tblid);
+    id.push(SHARD_SEPARATOR);
+    id.extend_from_slice(shard);
+    ObjectID::from_slice(id)
+}
+
+/// Returns the name of the shard if the ID belongs to a shard of the given table
+fn shard_name<'a>(tblid: &[u8], id: &'a [u8]) -> Option<&'a [u8]> {
+    match id.strip_prefix(tblid) {
+        Some([SHARD_SEPARATOR, shard @ ..]) => Some(shard),
+        _ => None,
+    }
+}
+
+/// Returns true if the ID belongs to a shard
+pub fn is_shard_id(id: &[u8]) -> bool {
+    id.contains(&SHARD_SEPARATOR)
+}
 
 #[test]
 fn test_def_macro_sanity() {
@@ -169,6 +214,8 @@ pub struct Memstore {
     pub keyspaces: Coremap<ObjectID, Arc<Keyspace>>,
     /// the system keyspace with the system tables
     pub system: SystemKeyspace,
+    /// the number of times the keyspaces were replaced (see [`Memstore::replace_keyspaces`])
+    generation: AtomicU64,
 }
 
 impl Memstore {
@@ -178,13 +225,18 @@ impl Memstore {
         Self {
             keyspaces: Coremap::new(),
             system: SystemKeyspace::new(Coremap::new()),
+            generation: AtomicU64::new(0),
         }
     }
     pub fn init_with_all(
         keyspaces: Coremap<ObjectID, Arc<Keyspace>>,
         system: SystemKeyspace,
     ) -> Self {
-        Self { keyspaces, system }
+        Self {
+            keyspaces,
+            system,
+            generation: AtomicU64::new(0),
+        }
     }
     /// Create a new in-memory table with the default keyspace and the default
     /// tables. So, whenever you're calling this, this is what you get:
@@ -214,6 +266,7 @@ impl Memstore {
                 n
             },
             system: SystemKeyspace::new(Coremap::new()),
+            generation: AtomicU64::new(0),
         }
     }
     pub fn setup_auth(&self) -> Authmap {
@@ -226,7 +279,34 @@ impl Memstore {
             }
             None => match self.system.tables.get(&AUTH).unwrap().data {
                 SystemDataModel::Auth(ref am) => am.clone(),
-                #[allow(unreachable_patterns)]
+                _ => unsafe { impossible!() },
+            },
+        }
+    }
+    pub fn setup_acl(&self) -> Aclmap {
+        match self.system.tables.fresh_entry(ACL) {
+            Some(fresh) => {
+                // created afresh, fine
+                let r = Aclmap::default();
+                fresh.insert(Wrapper::new(SystemTable::new_acl(r.clone())));
+                r
+            }
+            None => match self.system.tables.get(&ACL).unwrap().data {
+                SystemDataModel::Acl(ref am) => am.clone(),
+                _ => unsafe { impossible!() },
+            },
+        }
+    }
+    pub fn setup_rotation(&self) -> Rotationmap {
+        match self.system.tables.fresh_entry(ROTATION) {
+            Some(fresh) => {
+                // created afresh, fine
+                let r = Rotationmap::default();
+                fresh.insert(Wrapper::new(SystemTable::new_rotation(r.clone())));
+                r
+            }
+            None => match self.system.tables.get(&ROTATION).unwrap().data {
+                SystemDataModel::Rotation(ref rm) => rm.clone(),
                 _ => unsafe { impossible!() },
             },
         }
@@ -241,8 +321,18 @@ impl Memstore {
     }
     /// Returns true if a new keyspace was created
     pub fn create_keyspace(&self, keyspace_identifier: ObjectID) -> bool {
-        self.keyspaces
-            .true_if_insert(keyspace_identifier, Arc::new(Keyspace::empty()))
+        self.create_keyspace_with_defaults(keyspace_identifier, KeyspaceDefaults::default())
+    }
+    /// Returns true if a new keyspace with the provided table defaults was created
+    pub fn create_keyspace_with_defaults(
+        &self,
+        keyspace_identifier: ObjectID,
+        defaults: KeyspaceDefaults,
+    ) -> bool {
+        self.keyspaces.true_if_insert(
+            keyspace_identifier,
+            Arc::new(Keyspace::empty_with_defaults(defaults)),
+        )
     }
     /// Drop a keyspace only if it is empty and has no clients connected to it
     ///
@@ -330,9 +420,120 @@ impl Memstore {
             }
         }
     }
+    /// Rename a keyspace. Just like `drop space force`, this requires that neither the keyspace
+    /// nor any of its tables are referenced to, since otherwise other connections would keep
+    /// using a keyspace that no longer exists under their name
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn rename_keyspace(&self, from: &ObjectID, to: ObjectID) -> KeyspaceResult<()> {
+        if from.eq(&SYSTEM) || from.eq(&DEFAULT) || to.eq(&SYSTEM) {
+            return Err(DdlError::ProtectedObject);
+        }
+        let mut in_use = false;
+        let did_rename = self.keyspaces.rename_if(from, to, |keyspace| {
+            in_use = Arc::strong_count(keyspace) != 1
+                || keyspace
+                    .tables
+                    .iter()
+                    .any(|table| Arc::strong_count(table.value()) != 1);
+            !in_use
+        });
+        match did_rename {
+            Some(true) => {
+                // the tree has changed: the new keyspace needs to be created and the old one
+                // needs to be removed
+                registry::get_preload_tripswitch().trip();
+                registry::get_cleanup_tripswitch().trip();
+                Ok(())
+            }
+            Some(false) if in_use => Err(DdlError::StillInUse),
+            Some(false) => Err(DdlError::AlreadyExists),
+            None => Err(DdlError::ObjectNotFound),
+        }
+    }
     pub fn list_keyspaces(&self) -> Vec<ObjectID> {
         self.keyspaces.iter().map(|kv| kv.key().clone()).collect()
     }
+    /// Evict expired keys from all the tables in all the keyspaces, returning the total
+    /// number of evicted keys
+    pub fn sweep_expired(&self) -> usize {
+        self.keyspaces
+            .iter()
+            .map(|ks| {
+                ks.value()
+                    .tables
+                    .iter()
+                    .map(|tbl| tbl.value().sweep_expired())
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+    /// Mark every keyspace as dirty (see [`Keyspace::mark_dirty`]), for the writes that may
+    /// have changed keyspaces other than the current one
+    pub fn mark_all_dirty(&self) {
+        self.keyspaces.iter().for_each(|ks| ks.value().mark_dirty())
+    }
+    /// Returns the number of times the keyspaces were replaced. Connections compare this with
+    /// the generation they last saw to find out if their keyspace and table are stale
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    /// Replace all the keyspaces (and hence all the data) with the ones of `restored`, for
+    /// example with the ones read from a snapshot. The system keyspace (with the users and
+    /// their permissions) is left as is. Keyspaces are swapped one at a time, so queries that
+    /// run while this is going on may see a mix of both; connections move over to the new
+    /// keyspaces and tables with their next query (see [`Memstore::generation`])
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn replace_keyspaces(&self, restored: Memstore) {
+        let flush_lock = registry::lock_flush_state();
+        let stale: Vec<ObjectID> = self
+            .keyspaces
+            .iter()
+            .filter(|ks| !restored.keyspaces.contains_key(ks.key()))
+            .map(|ks| ks.key().clone())
+            .collect();
+        for (ksid, ks) in restored.keyspaces {
+            self.keyspaces.upsert(ksid, ks);
+        }
+        for ksid in stale {
+            self.keyspaces.true_if_removed(&ksid);
+        }
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        // the tree has changed, so it has to be recreated on the next flush
+        registry::get_preload_tripswitch().trip();
+        drop(flush_lock);
+    }
+    /// Evict keys from the volatile tables that are over their memory cap, in all the
+    /// keyspaces. Returns the number of evicted keys
+    pub fn evict(&self) -> usize {
+        self.keyspaces
+            .iter()
+            .map(|ks| {
+                ks.value()
+                    .tables
+                    .iter()
+                    .map(|tbl| tbl.value().evict())
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+    /// Returns the approximate number of bytes used by the data in every table in all the
+    /// keyspaces
+    pub fn memory_usage(&self) -> Vec<TableUsage> {
+        let mut usage = Vec::new();
+        for ks in self.keyspaces.iter() {
+            for tbl in ks.value().tables.iter() {
+                usage.push(TableUsage::new(
+                    ks.key().clone(),
+                    tbl.key().clone(),
+                    tbl.value().memory_usage(),
+                    tbl.value().eviction().evicted(),
+                ));
+            }
+        }
+        usage
+    }
 }
 
 /// System keyspace
@@ -347,14 +548,28 @@ impl SystemKeyspace {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// The defaults for the tables that are created in a keyspace. These are persisted along with
+/// the `PARTMAP` of the keyspace
+pub struct KeyspaceDefaults {
+    /// the model code used if a table is created without declaring its fields
+    pub model_code: Option<u8>,
+    /// tables are volatile unless said otherwise
+    pub volatile: bool,
+}
+
 #[derive(Debug)]
 /// A keyspace houses all the other tables
 pub struct Keyspace {
     /// the tables
     pub tables: Coremap<ObjectID, Arc<Table>>,
+    /// the defaults for new tables
+    defaults: KeyspaceDefaults,
     /// the replication strategy for this keyspace
     #[allow(dead_code)] // TODO: Remove this once we're ready with replication
     replication_strategy: cluster::ReplicationStrategy,
+    /// the keyspace may have changed since it was last flushed (see [`Keyspace::mark_dirty`])
+    dirty: AtomicBool,
 }
 
 #[cfg(test)]
@@ -374,25 +589,85 @@ impl Keyspace {
                 ht.true_if_insert(DEFAULT, Arc::new(Table::new_default_kve()));
                 ht
             },
+            defaults: KeyspaceDefaults::default(),
             replication_strategy: cluster::ReplicationStrategy::default(),
+            dirty: AtomicBool::new(true),
         }
     }
-    pub fn init_with_all_def_strategy(tables: Coremap<ObjectID, Arc<Table>>) -> Self {
+    pub fn init_with_all_def_strategy(
+        tables: Coremap<ObjectID, Arc<Table>>,
+        defaults: KeyspaceDefaults,
+    ) -> Self {
         Self {
             tables,
+            defaults,
             replication_strategy: cluster::ReplicationStrategy::default(),
+            dirty: AtomicBool::new(true),
         }
     }
     /// Create a new empty keyspace with zero tables
     pub fn empty() -> Self {
+        Self::empty_with_defaults(KeyspaceDefaults::default())
+    }
+    /// Create a new empty keyspace with zero tables and the provided defaults for new tables
+    pub fn empty_with_defaults(defaults: KeyspaceDefaults) -> Self {
         Self {
             tables: Coremap::new(),
+            defaults,
             replication_strategy: cluster::ReplicationStrategy::default(),
+            dirty: AtomicBool::new(true),
         }
     }
+    /// Returns the defaults for the tables created in this keyspace
+    pub const fn defaults(&self) -> &KeyspaceDefaults {
+        &self.defaults
+    }
     pub fn table_count(&self) -> usize {
         self.tables.len()
     }
+    /// Record that the keyspace may have changed, so that the next BGSAVE flushes it (see
+    /// [`crate::storage::v1::flush::flush_dirty`]). This has to be called once the write is
+    /// done, since a flush that runs in between clears it.
+    ///
+    /// Keyspaces start out dirty, so that the first flush after the data is loaded rewrites
+    /// it with the current format and encryption settings
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release)
+    }
+    /// Returns true if the keyspace may have changed since it was last flushed, and clears the
+    /// flag. Call this right before flushing the keyspace
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+    /// Returns true if the keyspace may have changed since it was last flushed
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+    /// Returns true if any of the tables that are flushed has keys with a TTL. These keys go
+    /// away without a write, so a keyspace with them can't be trusted to stay clean
+    pub fn has_expiring_keys(&self) -> bool {
+        self.tables
+            .iter()
+            .any(|tbl| !tbl.value().is_volatile() && tbl.value().expiry_count() != 0)
+    }
+    /// Returns the shards of the given table along with their names
+    pub fn get_shards(&self, tblid: &[u8]) -> Vec<(ObjectID, Arc<Table>)> {
+        self.tables
+            .iter()
+            .filter_map(|kv| {
+                shard_name(tblid, kv.key()).map(|shard| {
+                    // UNSAFE(@ohsayan): the shard's name is shorter than its ID
+                    (unsafe { ObjectID::from_slice(shard) }, kv.value().clone())
+                })
+            })
+            .collect()
+    }
+    /// Returns true if the given table has any shards
+    pub fn has_shards(&self, tblid: &[u8]) -> bool {
+        self.tables
+            .iter()
+            .any(|kv| shard_name(tblid, kv.key()).is_some())
+    }
     /// Get an atomic reference to a table in this keyspace if it exists
     pub fn get_table_atomic_ref<Q>(&self, table_identifier: &Q) -> Option<Arc<Table>>
     where
@@ -441,6 +716,30 @@ impl Keyspace {
             }
         }
     }
+    /// Rename a table if it isn't the default table and if no one references it
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn rename_table(&self, from: &ObjectID, to: ObjectID) -> KeyspaceResult<()> {
+        if from.eq(&DEFAULT) {
+            return Err(DdlError::ProtectedObject);
+        }
+        let mut in_use = false;
+        let did_rename = self.tables.rename_if(from, to, |table| {
+            in_use = Arc::strong_count(table) != 1;
+            !in_use
+        });
+        match did_rename {
+            Some(true) => {
+                // the table has to be written under its new name and the older one removed
+                registry::get_preload_tripswitch().trip();
+                registry::get_cleanup_tripswitch().trip();
+                Ok(())
+            }
+            Some(false) if in_use => Err(DdlError::StillInUse),
+            Some(false) => Err(DdlError::AlreadyExists),
+            None => Err(DdlError::ObjectNotFound),
+        }
+    }
     pub fn drop_table<Q>(&self, tblid: &Q, force: bool) -> KeyspaceResult<()>
     where
         ObjectID: Borrow<Q>,
@@ -490,3 +789,144 @@ fn test_keyspace_try_delete_protected_table() {
         DdlError::ProtectedObject
     );
 }
+
+#[test]
+fn test_keyspace_rename_table() {
+    let our_keyspace = Keyspace::empty_default();
+    assert!(our_keyspace.create_table(
+        unsafe_objectid_from_slice!("apps"),
+        Table::new_default_kve()
+    ));
+    assert!(our_keyspace.create_table(
+        unsafe_objectid_from_slice!("users"),
+        Table::new_default_kve()
+    ));
+    assert_eq!(
+        our_keyspace
+            .rename_table(
+                &unsafe_objectid_from_slice!("apps"),
+                unsafe_objectid_from_slice!("users")
+            )
+            .unwrap_err(),
+        DdlError::AlreadyExists
+    );
+    assert_eq!(
+        our_keyspace
+            .rename_table(
+                &unsafe_objectid_from_slice!("default"),
+                unsafe_objectid_from_slice!("mydefault")
+            )
+            .unwrap_err(),
+        DdlError::ProtectedObject
+    );
+    {
+        let _atomic_tbl_ref = our_keyspace
+            .get_table_atomic_ref(&unsafe_objectid_from_slice!("apps"))
+            .unwrap();
+        assert_eq!(
+            our_keyspace
+                .rename_table(
+                    &unsafe_objectid_from_slice!("apps"),
+                    unsafe_objectid_from_slice!("myapps")
+                )
+                .unwrap_err(),
+            DdlError::StillInUse
+        );
+    }
+    assert!(our_keyspace
+        .rename_table(
+            &unsafe_objectid_from_slice!("apps"),
+            unsafe_objectid_from_slice!("myapps")
+        )
+        .is_ok());
+    assert!(our_keyspace
+        .get_table_atomic_ref(&unsafe_objectid_from_slice!("apps"))
+        .is_none());
+    assert!(our_keyspace
+        .get_table_atomic_ref(&unsafe_objectid_from_slice!("myapps"))
+        .is_some());
+}
+
+#[test]
+fn test_rename_keyspace() {
+    let store = Memstore::new_default();
+    assert!(store.create_keyspace(unsafe_objectid_from_slice!("apps")));
+    assert_eq!(
+        store
+            .rename_keyspace(&DEFAULT, unsafe_objectid_from_slice!("mydefault"))
+            .unwrap_err(),
+        DdlError::ProtectedObject
+    );
+    assert_eq!(
+        store
+            .rename_keyspace(&unsafe_objectid_from_slice!("apps"), SYSTEM)
+            .unwrap_err(),
+        DdlError::ProtectedObject
+    );
+    assert_eq!(
+        store
+            .rename_keyspace(&unsafe_objectid_from_slice!("apps"), DEFAULT)
+            .unwrap_err(),
+        DdlError::AlreadyExists
+    );
+    assert_eq!(
+        store
+            .rename_keyspace(
+                &unsafe_objectid_from_slice!("nope"),
+                unsafe_objectid_from_slice!("yep")
+            )
+            .unwrap_err(),
+        DdlError::ObjectNotFound
+    );
+    {
+        let _atomic_ks_ref = store
+            .get_keyspace_atomic_ref(&unsafe_objectid_from_slice!("apps"))
+            .unwrap();
+        assert_eq!(
+            store
+                .rename_keyspace(
+                    &unsafe_objectid_from_slice!("apps"),
+                    unsafe_objectid_from_slice!("myapps")
+                )
+                .unwrap_err(),
+            DdlError::StillInUse
+        );
+    }
+    assert!(store
+        .rename_keyspace(
+            &unsafe_objectid_from_slice!("apps"),
+            unsafe_objectid_from_slice!("myapps")
+        )
+        .is_ok());
+    assert!(store
+        .get_keyspace_atomic_ref(&unsafe_objectid_from_slice!("myapps"))
+        .is_some());
+}
+
+#[test]
+fn test_keyspace_shards() {
+    let ks = Keyspace::empty();
+    assert!(ks.create_table(
+        unsafe_objectid_from_slice!("users"),
+        Table::new_default_kve()
+    ));
+    assert!(ks.create_table(
+        unsafe_objectid_from_slice!("users2"),
+        Table::new_default_kve()
+    ));
+    assert!(!ks.has_shards(b"users"));
+    assert!(ks.create_table(
+        unsafe { shard_id(b"users", b"eu") },
+        Table::new_default_kve()
+    ));
+    assert!(ks.create_table(
+        unsafe { shard_id(b"users2", b"us") },
+        Table::new_default_kve()
+    ));
+    assert!(ks.has_shards(b"users"));
+    let shards = ks.get_shards(b"users");
+    assert_eq!(shards.len(), 1);
+    assert_eq!(shards[0].0, unsafe_objectid_from_slice!("eu"));
+    assert!(is_shard_id(&unsafe { shard_id(b"users", b"eu") }));
+    assert!(!is_shard_id(b"users"));
+}
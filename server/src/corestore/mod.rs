@@ -27,28 +27,34 @@
 use {
     crate::{
         actions::{translate_ddl_error, ActionResult},
-        blueql::Entity,
+        blueql::{Entity, LimitsDecl, RawSlice},
         corestore::{
-            memstore::{DdlError, Keyspace, Memstore, ObjectID, DEFAULT},
-            table::{DescribeTable, Table},
+            memstore::{DdlError, Keyspace, KeyspaceDefaults, Memstore, ObjectID, DEFAULT},
+            table::{DescribeTable, Table, TableDescription},
         },
+        dbnet::pubsub::PubSub,
         protocol::interface::ProtocolSpec,
+        queryengine::script::ScriptCache,
         registry,
         storage::{
             self,
             v1::{error::StorageEngineResult, sengine::SnapshotEngine},
         },
-        util::{self, Unwrappable},
+        util::{self, compiler, Unwrappable},
     },
     core::{borrow::Borrow, hash::Hash},
     std::sync::Arc,
 };
 
+pub mod arena;
 pub mod array;
 pub mod backoff;
+pub mod bloom;
 pub mod booltable;
 pub mod buffers;
+pub mod geo;
 pub mod heap_array;
+pub mod hll;
 pub mod htable;
 pub mod iarray;
 pub mod lazy;
@@ -57,6 +63,10 @@ pub mod map;
 pub mod memstore;
 pub mod rc;
 pub mod table;
+pub mod timeseries;
+pub mod waiters;
+pub mod zset;
+
 #[cfg(test)]
 mod tests;
 
@@ -70,6 +80,8 @@ struct ConnectionEntityState {
     table: Option<(ObjectID, Arc<Table>)>,
     /// the current keyspace for a connection
     ks: Option<(ObjectID, Arc<Keyspace>)>,
+    /// the generation of the store that the keyspace and table were taken from
+    generation: u64,
 }
 
 impl ConnectionEntityState {
@@ -77,8 +89,25 @@ impl ConnectionEntityState {
         Self {
             table: Some((DEFAULT, tbl)),
             ks: Some((DEFAULT, ks)),
+            generation: 0,
         }
     }
+    /// Take the keyspace and table with the same names from the given generation of the store,
+    /// unsetting them if they don't exist there
+    fn refresh(&mut self, store: &Memstore, generation: u64) {
+        let ks = self
+            .ks
+            .take()
+            .and_then(|(ksid, _)| store.get_keyspace_atomic_ref(&ksid).map(|ks| (ksid, ks)));
+        self.table = match (&ks, self.table.take()) {
+            (Some((_, ks)), Some((tblid, _))) => {
+                ks.get_table_atomic_ref(&tblid).map(|tbl| (tblid, tbl))
+            }
+            _ => None,
+        };
+        self.ks = ks;
+        self.generation = generation;
+    }
     fn set_ks(&mut self, ks: Arc<Keyspace>, ksid: ObjectID) {
         self.ks = Some((ksid, ks));
         self.table = None;
@@ -106,6 +135,10 @@ pub struct Corestore {
     store: Arc<Memstore>,
     /// the snapshot engine
     sengine: Arc<SnapshotEngine>,
+    /// the cached scripts
+    scripts: Arc<ScriptCache>,
+    /// the pub/sub hub
+    pubsub: Arc<PubSub>,
 }
 
 impl Corestore {
@@ -125,14 +158,32 @@ impl Corestore {
             estate: ConnectionEntityState::default(cks, ctable),
             store: Arc::new(store),
             sengine,
+            scripts: Arc::new(ScriptCache::new()),
+            pubsub: Arc::new(PubSub::new()),
         }
     }
     pub fn get_engine(&self) -> &SnapshotEngine {
         &self.sengine
     }
+    pub fn get_scripts(&self) -> &ScriptCache {
+        &self.scripts
+    }
+    pub fn get_pubsub(&self) -> &Arc<PubSub> {
+        &self.pubsub
+    }
     pub fn get_store(&self) -> &Memstore {
         &self.store
     }
+    /// Move over to the keyspace and table with the same names if the keyspaces were replaced
+    /// since this handle last looked (see [`Memstore::replace_keyspaces`]). This is done
+    /// before every query
+    pub fn refresh_entity(&mut self) {
+        let generation = self.store.generation();
+        if compiler::likely(self.estate.generation == generation) {
+            return;
+        }
+        self.estate.refresh(&self.store, generation);
+    }
     /// Swap out the current table with a different one
     ///
     /// If the table is non-existent or the default keyspace was unset, then
@@ -165,6 +216,22 @@ impl Corestore {
                     None => return Err(DdlError::ObjectNotFound),
                 }
             }
+            // Switch to the provided shard of a table in the given keyspace
+            Entity::Shard(ks, tbl, shard) => {
+                let shardid = unsafe { memstore::shard_id(tbl.as_slice(), shard.as_slice()) };
+                match self.store.get_keyspace_atomic_ref(unsafe { ks.as_slice() }) {
+                    Some(kspace) => match kspace.get_table_atomic_ref(&shardid) {
+                        Some(tblref) => self.estate.set_table(
+                            kspace,
+                            unsafe { ObjectID::from_slice(ks.as_slice()) },
+                            tblref,
+                            shardid,
+                        ),
+                        None => return Err(DdlError::ObjectNotFound),
+                    },
+                    None => return Err(DdlError::ObjectNotFound),
+                }
+            }
         }
         Ok(())
     }
@@ -211,8 +278,25 @@ impl Corestore {
                 },
                 None => Err(DdlError::DefaultNotFound),
             },
+            Entity::Shard(ksid, table, shard) => {
+                let shardid = unsafe { memstore::shard_id(table.as_slice(), shard.as_slice()) };
+                match self
+                    .store
+                    .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
+                {
+                    Some(ks) => match ks.get_table_atomic_ref(&shardid) {
+                        Some(tbl) => Ok(tbl),
+                        None => Err(DdlError::ObjectNotFound),
+                    },
+                    None => Err(DdlError::ObjectNotFound),
+                }
+            }
         }
     }
+    /// Returns an atomic reference to the current keyspace, if set
+    pub fn get_cks_atomic_ref(&self) -> Option<Arc<Keyspace>> {
+        self.estate.ks.as_ref().map(|(_, ks)| ks.clone())
+    }
     pub fn get_ctable(&self) -> Option<Arc<Table>> {
         self.estate.table.as_ref().map(|(_, tbl)| tbl.clone())
     }
@@ -234,56 +318,33 @@ impl Corestore {
     pub fn create_table(
         &self,
         entity: &Entity,
-        modelcode: u8,
-        volatile: bool,
+        modelcode: Option<u8>,
+        volatile: Option<bool>,
     ) -> KeyspaceResult<()> {
         // first lock the global flush state
         let flush_lock = registry::lock_flush_state();
         let ret = match entity {
             // Important: create table <tblname> is only ks
-            Entity::Current(tblid) => {
-                match &self.estate.ks {
-                    Some((_, ks)) => {
-                        let tbl = Table::from_model_code(modelcode, volatile);
-                        if let Some(tbl) = tbl {
-                            if ks.create_table(
-                                unsafe { ObjectID::from_slice(tblid.as_slice()) },
-                                tbl,
-                            ) {
-                                // we need to re-init tree; so trip
-                                registry::get_preload_tripswitch().trip();
-                                Ok(())
-                            } else {
-                                Err(DdlError::AlreadyExists)
-                            }
-                        } else {
-                            Err(DdlError::WrongModel)
-                        }
-                    }
-                    None => Err(DdlError::DefaultNotFound),
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((_, ks)) => Self::create_table_in(ks, tblid, modelcode, volatile),
+                None => Err(DdlError::DefaultNotFound),
+            },
+            Entity::Full(ksid, tblid) => {
+                match self
+                    .store
+                    .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
+                {
+                    Some(kspace) => Self::create_table_in(&kspace, tblid, modelcode, volatile),
+                    None => Err(DdlError::ObjectNotFound),
                 }
             }
-            Entity::Full(ksid, tblid) => {
+            Entity::Shard(ksid, tblid, shard) => {
                 match self
                     .store
                     .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
                 {
                     Some(kspace) => {
-                        let tbl = Table::from_model_code(modelcode, volatile);
-                        if let Some(tbl) = tbl {
-                            if kspace.create_table(
-                                unsafe { ObjectID::from_slice(tblid.as_slice()) },
-                                tbl,
-                            ) {
-                                // trip the preload switch
-                                registry::get_preload_tripswitch().trip();
-                                Ok(())
-                            } else {
-                                Err(DdlError::AlreadyExists)
-                            }
-                        } else {
-                            Err(DdlError::WrongModel)
-                        }
+                        Self::create_shard_in(&kspace, tblid, shard, modelcode, volatile)
                     }
                     None => Err(DdlError::ObjectNotFound),
                 }
@@ -294,11 +355,156 @@ impl Corestore {
         ret
     }
 
-    /// Drop a table
+    /// Add a table that was built elsewhere (like one that was loaded from an export archive).
+    /// Returns the ID of the keyspace that it was added to. A shard always has the model of its
+    /// table, so one can't be added on its own
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn add_table(&self, entity: &Entity, table: Table) -> KeyspaceResult<ObjectID> {
+        let (ksid, ks, tblid) = match entity {
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((ksid, ks)) => (ksid.clone(), ks.clone(), tblid),
+                None => return Err(DdlError::DefaultNotFound),
+            },
+            Entity::Full(ksid, tblid) => {
+                let ksid = unsafe { ksid.as_slice() };
+                match self.store.get_keyspace_atomic_ref(ksid) {
+                    Some(ks) => (unsafe { ObjectID::from_slice(ksid) }, ks, tblid),
+                    None => return Err(DdlError::ObjectNotFound),
+                }
+            }
+            Entity::Shard(..) => return Err(DdlError::WrongModel),
+        };
+        let _flush_lock = registry::lock_flush_state();
+        if ks.create_table(unsafe { ObjectID::from_slice(tblid.as_slice()) }, table) {
+            // we need to re-init tree; so trip
+            registry::get_preload_tripswitch().trip();
+            ks.mark_dirty();
+            Ok(ksid)
+        } else {
+            Err(DdlError::AlreadyExists)
+        }
+    }
+
+    /// Create a table in the given keyspace. If the model code isn't provided, the keyspace's
+    /// default model is used (if the keyspace has none, this fails with
+    /// [`DdlError::WrongModel`]). Likewise, the table is only volatile by default if the
+    /// keyspace defaults to volatile tables, but an explicit `volatile` always wins
+    fn create_table_in(
+        ks: &Keyspace,
+        tblid: &RawSlice,
+        modelcode: Option<u8>,
+        volatile: Option<bool>,
+    ) -> KeyspaceResult<()> {
+        let defaults = ks.defaults();
+        let volatile = volatile.unwrap_or(defaults.volatile);
+        let tbl = modelcode
+            .or(defaults.model_code)
+            .and_then(|code| Table::from_model_code(code, volatile));
+        match tbl {
+            Some(tbl) => {
+                if ks.create_table(unsafe { ObjectID::from_slice(tblid.as_slice()) }, tbl) {
+                    // we need to re-init tree; so trip
+                    registry::get_preload_tripswitch().trip();
+                    Ok(())
+                } else {
+                    Err(DdlError::AlreadyExists)
+                }
+            }
+            None => Err(DdlError::WrongModel),
+        }
+    }
+
+    /// Create a shard of a table in the given keyspace. A shard always has the same model as
+    /// its table (so if a model code is provided, it has to match). Unless `volatile` says
+    /// otherwise, a shard is volatile if its table is
+    fn create_shard_in(
+        ks: &Keyspace,
+        tblid: &RawSlice,
+        shard: &RawSlice,
+        modelcode: Option<u8>,
+        volatile: Option<bool>,
+    ) -> KeyspaceResult<()> {
+        let table = match ks.get_table_atomic_ref(unsafe { tblid.as_slice() }) {
+            Some(table) => table,
+            None => return Err(DdlError::ObjectNotFound),
+        };
+        let code = table.get_model_code();
+        if modelcode.map_or(false, |modelcode| modelcode != code) {
+            return Err(DdlError::WrongModel);
+        }
+        let volatile = volatile.unwrap_or(table.is_volatile());
+        let shard_tbl = match Table::from_model_code(code, volatile) {
+            Some(tbl) => tbl,
+            None => return Err(DdlError::WrongModel),
+        };
+        let shardid = unsafe { memstore::shard_id(tblid.as_slice(), shard.as_slice()) };
+        if ks.create_table(shardid, shard_tbl) {
+            // we need to re-init tree; so trip
+            registry::get_preload_tripswitch().trip();
+            Ok(())
+        } else {
+            Err(DdlError::AlreadyExists)
+        }
+    }
+
+    /// Change the volatility and/or the limits of a table. The data in the table is left
+    /// untouched; if the table was made persistent it is written to disk on the next flush
+    /// cycle, while if it was made volatile, it is no longer flushed. Lowering a size limit
+    /// doesn't affect the keys and values that are already in the table, while lowering the
+    /// memory cap of a volatile table evicts keys on the next memory report. A new durability
+    /// is used for the writes made after it's changed
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn alter_table(
+        &self,
+        entity: &Entity,
+        volatile: Option<bool>,
+        limits: &LimitsDecl,
+    ) -> KeyspaceResult<()> {
+        let table = self.get_table(entity)?;
+        if let Some(volatile) = volatile {
+            // lock the global flush state so that the flush routine doesn't see a PARTMAP
+            // that disagrees with the table
+            let flush_lock = registry::lock_flush_state();
+            if table.set_volatile(volatile) != volatile {
+                // the storage type in the PARTMAP has changed; so trip
+                registry::get_preload_tripswitch().trip();
+            }
+            drop(flush_lock);
+        }
+        let table_limits = table.limits();
+        if let Some(max) = limits.max_key_size {
+            table_limits.set_max_key_size(max as usize);
+        }
+        if let Some(max) = limits.max_value_size {
+            table_limits.set_max_value_size(max as usize);
+        }
+        let eviction = table.eviction();
+        if let Some(max) = limits.max_memory {
+            eviction.set_max_memory(max as usize);
+        }
+        if let Some(policy) = limits.eviction {
+            eviction.set_policy(policy);
+        }
+        if let Some(durability) = limits.durability {
+            table.set_durability(durability);
+        }
+        Ok(())
+    }
+
+    /// Drop a table. A table can't be dropped while it still has shards
     pub fn drop_table(&self, entity: &Entity, force: bool) -> KeyspaceResult<()> {
+        fn drop_table_in(ks: &Keyspace, tblid: &[u8], force: bool) -> KeyspaceResult<()> {
+            if ks.has_shards(tblid) {
+                Err(DdlError::StillInUse)
+            } else {
+                ks.drop_table(tblid, force)
+            }
+        }
         match entity {
             Entity::Current(tblid) => match &self.estate.ks {
-                Some((_, ks)) => ks.drop_table(unsafe { tblid.as_slice() }, force),
+                Some((_, ks)) => drop_table_in(ks, unsafe { tblid.as_slice() }, force),
                 None => Err(DdlError::DefaultNotFound),
             },
             Entity::Full(ksid, tblid) => {
@@ -306,7 +512,19 @@ impl Corestore {
                     .store
                     .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
                 {
-                    Some(ks) => ks.drop_table(unsafe { tblid.as_slice() }, force),
+                    Some(ks) => drop_table_in(&ks, unsafe { tblid.as_slice() }, force),
+                    None => Err(DdlError::ObjectNotFound),
+                }
+            }
+            Entity::Shard(ksid, tblid, shard) => {
+                match self
+                    .store
+                    .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
+                {
+                    Some(ks) => ks.drop_table(
+                        &unsafe { memstore::shard_id(tblid.as_slice(), shard.as_slice()) },
+                        force,
+                    ),
                     None => Err(DdlError::ObjectNotFound),
                 }
             }
@@ -316,10 +534,14 @@ impl Corestore {
     /// Create a keyspace **without any transactional guarantees**
     ///
     /// **Trip switch handled:** Yes
-    pub fn create_keyspace(&self, ksid: ObjectID) -> KeyspaceResult<()> {
+    pub fn create_keyspace(
+        &self,
+        ksid: ObjectID,
+        defaults: KeyspaceDefaults,
+    ) -> KeyspaceResult<()> {
         // lock the global flush lock (see comment in create_table to know why)
         let flush_lock = registry::lock_flush_state();
-        let ret = if self.store.create_keyspace(ksid) {
+        let ret = if self.store.create_keyspace_with_defaults(ksid, defaults) {
             // woo, created
             // trip the preload switch
             registry::get_preload_tripswitch().trip();
@@ -332,6 +554,65 @@ impl Corestore {
         ret
     }
 
+    /// Rename a keyspace
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn rename_keyspace(&self, from: &ObjectID, to: ObjectID) -> KeyspaceResult<()> {
+        // lock the global flush lock so that a flush cycle never sees half the tree renamed
+        let flush_lock = registry::lock_flush_state();
+        let ret = self.store.rename_keyspace(from, to);
+        drop(flush_lock);
+        ret
+    }
+
+    /// Rename a table in its keyspace (or a shard within its table). A table can't be renamed
+    /// while it still has shards
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn rename_table(&self, entity: &Entity, to: ObjectID) -> KeyspaceResult<()> {
+        let (ks, from, to) = match entity {
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((_, ks)) => (
+                    ks.clone(),
+                    unsafe { ObjectID::from_slice(tblid.as_slice()) },
+                    to,
+                ),
+                None => return Err(DdlError::DefaultNotFound),
+            },
+            Entity::Full(ksid, tblid) => {
+                match self
+                    .store
+                    .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
+                {
+                    Some(ks) => (ks, unsafe { ObjectID::from_slice(tblid.as_slice()) }, to),
+                    None => return Err(DdlError::ObjectNotFound),
+                }
+            }
+            Entity::Shard(ksid, tblid, shard) => {
+                match self
+                    .store
+                    .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
+                {
+                    Some(ks) => unsafe {
+                        (
+                            ks,
+                            memstore::shard_id(tblid.as_slice(), shard.as_slice()),
+                            memstore::shard_id(tblid.as_slice(), &to),
+                        )
+                    },
+                    None => return Err(DdlError::ObjectNotFound),
+                }
+            }
+        };
+        if !memstore::is_shard_id(&from) && ks.has_shards(&from) {
+            return Err(DdlError::StillInUse);
+        }
+        let flush_lock = registry::lock_flush_state();
+        let ret = ks.rename_table(&from, to);
+        drop(flush_lock);
+        ret
+    }
+
     /// Drop a keyspace
     pub fn drop_keyspace(&self, ksid: ObjectID) -> KeyspaceResult<()> {
         // trip switch is handled by memstore here
@@ -349,7 +630,19 @@ impl Corestore {
     pub fn get_ids(&self) -> (Option<&ObjectID>, Option<&ObjectID>) {
         self.estate.get_id_pack()
     }
-    pub fn list_tables<P: ProtocolSpec>(&self, ksid: Option<&[u8]>) -> ActionResult<Vec<ObjectID>> {
+    /// Returns the description of every table in the keyspace (or the current keyspace)
+    pub fn describe_tables<P: ProtocolSpec>(
+        &self,
+        ksid: Option<&[u8]>,
+    ) -> ActionResult<Vec<(ObjectID, TableDescription)>> {
+        fn describe(ks: &Keyspace) -> Vec<(ObjectID, TableDescription)> {
+            // shards are listed with their tables
+            ks.tables
+                .iter()
+                .filter(|kv| !memstore::is_shard_id(kv.key()))
+                .map(|kv| (kv.key().clone(), kv.value().description()))
+                .collect()
+        }
         Ok(match ksid {
             Some(keyspace_name) => {
                 // inspect the provided keyspace
@@ -362,20 +655,52 @@ impl Corestore {
                     Some(kspace) => kspace,
                     None => return util::err(P::RSTRING_CONTAINER_NOT_FOUND),
                 };
-                ks.tables.iter().map(|kv| kv.key().clone()).collect()
+                describe(&ks)
             }
             None => {
                 // inspect the current keyspace
-                let cks = translate_ddl_error::<P, &Keyspace>(self.get_cks())?;
-                cks.tables.iter().map(|kv| kv.key().clone()).collect()
+                describe(translate_ddl_error::<P, &Keyspace>(self.get_cks())?)
             }
         })
     }
-    pub fn describe_table<P: ProtocolSpec>(&self, table: &Option<Entity>) -> ActionResult<String> {
+    /// Returns the description of every shard of the table
+    pub fn describe_shards<P: ProtocolSpec>(
+        &self,
+        table: &Entity,
+    ) -> ActionResult<Vec<(ObjectID, TableDescription)>> {
+        fn describe(ks: &Keyspace, tblid: &[u8]) -> Option<Vec<(ObjectID, TableDescription)>> {
+            ks.get_table_atomic_ref(tblid)?;
+            Some(
+                ks.get_shards(tblid)
+                    .into_iter()
+                    .map(|(shard, tbl)| (shard, tbl.description()))
+                    .collect(),
+            )
+        }
+        let shards = match table {
+            Entity::Current(tblid) => describe(
+                translate_ddl_error::<P, &Keyspace>(self.get_cks())?,
+                unsafe { tblid.as_slice() },
+            ),
+            Entity::Full(ksid, tblid) => self
+                .get_keyspace(unsafe { ksid.as_slice() })
+                .and_then(|ks| describe(&ks, unsafe { tblid.as_slice() })),
+            // shards can't have shards of their own
+            Entity::Shard(..) => None,
+        };
+        match shards {
+            Some(shards) => Ok(shards),
+            None => util::err(P::RSTRING_CONTAINER_NOT_FOUND),
+        }
+    }
+    pub fn describe_table<P: ProtocolSpec>(
+        &self,
+        table: &Option<Entity>,
+    ) -> ActionResult<TableDescription> {
         let r = match table {
-            Some(tbl) => translate_ddl_error::<P, Arc<Table>>(self.get_table(tbl))?.describe_self(),
-            None => translate_ddl_error::<P, &Table>(self.get_ctable_result())?.describe_self(),
+            Some(tbl) => translate_ddl_error::<P, Arc<Table>>(self.get_table(tbl))?.description(),
+            None => translate_ddl_error::<P, &Table>(self.get_ctable_result())?.description(),
         };
-        Ok(r.to_owned())
+        Ok(r)
     }
 }
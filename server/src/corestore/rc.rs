@@ -24,17 +24,26 @@
  *
 */
 
-use std::{
-    alloc::{alloc, dealloc, Layout},
-    borrow::Borrow,
-    fmt::Debug,
-    hash::Hash,
-    ops::Deref,
-    ptr::{self, NonNull},
-    slice,
-    sync::atomic::{self, AtomicUsize, Ordering},
+use {
+    super::arena,
+    std::{
+        alloc::{alloc, dealloc, Layout},
+        borrow::Borrow,
+        fmt::Debug,
+        hash::Hash,
+        mem,
+        ops::Deref,
+        ptr::{self, NonNull},
+        slice,
+        sync::atomic::{self, AtomicUsize, Ordering},
+    },
 };
 
+/// The size of the shared state
+const STATE_SIZE: usize = mem::size_of::<SharedSliceInner>();
+/// Slices with at most these many bytes share a block from the [`arena`] with their state
+const ARENA_MAX_LEN: usize = arena::MAX_BLOCK - STATE_SIZE;
+
 /// A [`SharedSlice`] is a dynamically sized, heap allocated slice that can be safely shared across threads. This
 /// type can be cheaply cloned and the only major cost is initialization that does a memcpy from the source into
 /// a new heap allocation. Once init is complete, cloning only increments an atomic counter and when no more owners
@@ -42,6 +51,8 @@ use std::{
 /// Do note that two heap allocations are made:
 /// - One for the actual data
 /// - One for the shared state
+///
+/// unless the slice is small, in which case both go in a single block from the [`arena`]
 pub struct SharedSlice {
     inner: NonNull<SharedSliceInner>,
 }
@@ -62,12 +73,37 @@ impl SharedSlice {
     #[inline(always)]
     /// Create a new [`SharedSlice`] using the given local slice
     pub fn new(slice: &[u8]) -> Self {
+        if slice.len() <= ARENA_MAX_LEN {
+            return Self::new_in_arena(slice);
+        }
         Self {
             inner: unsafe {
                 NonNull::new_unchecked(Box::leak(Box::new(SharedSliceInner::new(slice))))
             },
         }
     }
+    /// Create a new [`SharedSlice`] with its state and data in a single block from the arena
+    fn new_in_arena(slice: &[u8]) -> Self {
+        let block = arena::alloc_block(STATE_SIZE + slice.len());
+        unsafe {
+            // UNSAFE(@ohsayan): The block is aligned and large enough for the state followed by
+            // the data
+            let inner = block.as_ptr() as *mut SharedSliceInner;
+            let data = block.as_ptr().add(STATE_SIZE);
+            ptr::copy_nonoverlapping(slice.as_ptr(), data, slice.len());
+            ptr::write(
+                inner,
+                SharedSliceInner {
+                    data,
+                    len: slice.len(),
+                    rc: AtomicUsize::new(1),
+                },
+            );
+            Self {
+                inner: NonNull::new_unchecked(inner),
+            }
+        }
+    }
     #[inline(always)]
     /// Returns a reference to te inner heap allocation for shared state
     fn inner(&self) -> &SharedSliceInner {
@@ -76,7 +112,13 @@ impl SharedSlice {
     #[inline(never)]
     /// A slow-path to deallocating all the heap allocations
     unsafe fn slow_drop(&self) {
-        if self.len() != 0 {
+        let len = self.len();
+        if len <= ARENA_MAX_LEN {
+            // the state and the data share a block
+            arena::free_block(self.inner.cast(), STATE_SIZE + len);
+            return;
+        }
+        if len != 0 {
             // IMPORTANT: Do not use the aligned pointer as a sentinel
             let inner = self.inner();
             // heap array dtor
@@ -205,6 +247,20 @@ impl From<Vec<u8>> for SharedSlice {
 
 impl Eq for SharedSlice {}
 
+impl PartialOrd for SharedSlice {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SharedSlice {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
 /// The shared state structure
 struct SharedSliceInner {
     /// data ptr
@@ -252,6 +308,25 @@ fn basic_cloned() {
     assert_eq!(slice_a_clone, b"hello");
 }
 
+#[test]
+fn arena_boundary() {
+    // the slices on either side of the limit come from the arena and the allocator
+    for len in [
+        0,
+        1,
+        ARENA_MAX_LEN - 1,
+        ARENA_MAX_LEN,
+        ARENA_MAX_LEN + 1,
+        4096,
+    ] {
+        let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let slice = SharedSlice::new(&data);
+        let clone = slice.clone();
+        drop(slice);
+        assert_eq!(clone, data);
+    }
+}
+
 #[test]
 fn basic_cloned_across_threads() {
     use std::thread;
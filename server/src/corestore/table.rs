@@ -27,14 +27,28 @@
 #[cfg(test)]
 use crate::corestore::{memstore::DdlError, KeyspaceResult};
 use crate::{
-    actions::ActionResult,
-    auth::Authmap,
+    actions::{translate_read_error, ActionResult},
+    auth::{Aclmap, Authmap, Rotationmap},
     corestore::{htable::Coremap, SharedSlice},
     dbnet::prelude::Corestore,
-    kvengine::{KVEListmap, KVEStandard, LockedVec},
+    kvengine::{
+        compaction::ValueCompaction,
+        disk::Loaded,
+        eviction::{Eviction, EvictionPolicy},
+        expiry,
+        limits::SizeLimits,
+        notify::Notifier,
+        pattern::Pattern,
+        storage::StorageKind,
+        KVEBloommap, KVECountermap, KVEGeomap, KVEHashmap, KVEHllmap, KVEListmap, KVESetmap,
+        KVEStandard, KVETimeseriesmap, KVEZsetmap, LockedBloom, LockedGeo, LockedHll, LockedMap,
+        LockedSet, LockedTimeseries, LockedVec, LockedZset,
+    },
     protocol::interface::ProtocolSpec,
+    storage::v1::wal::{AtomicDurability, Durability},
     util,
 };
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 pub trait DescribeTable {
     type Table;
@@ -79,9 +93,115 @@ impl DescribeTable for KVEList {
     }
 }
 
+pub struct KVESet;
+
+impl DescribeTable for KVESet {
+    type Table = KVESetmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtSetmap(ref kvs) = table.model_store {
+            Some(kvs)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEZset;
+
+impl DescribeTable for KVEZset {
+    type Table = KVEZsetmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtZsetmap(ref kvz) = table.model_store {
+            Some(kvz)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEHash;
+
+impl DescribeTable for KVEHash {
+    type Table = KVEHashmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtHashmap(ref kvh) = table.model_store {
+            Some(kvh)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVECounter;
+
+impl DescribeTable for KVECounter {
+    type Table = KVECountermap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtCountermap(ref kvc) = table.model_store {
+            Some(kvc)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEBloom;
+
+impl DescribeTable for KVEBloom {
+    type Table = KVEBloommap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtBloommap(ref kvb) = table.model_store {
+            Some(kvb)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEHll;
+
+impl DescribeTable for KVEHll {
+    type Table = KVEHllmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtHllmap(ref kvh) = table.model_store {
+            Some(kvh)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEGeo;
+
+impl DescribeTable for KVEGeo {
+    type Table = KVEGeomap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtGeomap(ref kvg) = table.model_store {
+            Some(kvg)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVETimeseries;
+
+impl DescribeTable for KVETimeseries {
+    type Table = KVETimeseriesmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVExtTimeseriesmap(ref kvt) = table.model_store {
+            Some(kvt)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SystemDataModel {
     Auth(Authmap),
+    Acl(Aclmap),
+    Rotation(Rotationmap),
 }
 
 #[derive(Debug)]
@@ -100,12 +220,26 @@ impl SystemTable {
     pub fn new_auth(authmap: Authmap) -> Self {
         Self::new(SystemDataModel::Auth(authmap))
     }
+    pub fn new_acl(aclmap: Aclmap) -> Self {
+        Self::new(SystemDataModel::Acl(aclmap))
+    }
+    pub fn new_rotation(rotationmap: Rotationmap) -> Self {
+        Self::new(SystemDataModel::Rotation(rotationmap))
+    }
 }
 
 #[derive(Debug)]
 pub enum DataModel {
     KV(KVEStandard),
     KVExtListmap(KVEListmap),
+    KVExtSetmap(KVESetmap),
+    KVExtZsetmap(KVEZsetmap),
+    KVExtHashmap(KVEHashmap),
+    KVExtCountermap(KVECountermap),
+    KVExtBloommap(KVEBloommap),
+    KVExtHllmap(KVEHllmap),
+    KVExtGeomap(KVEGeomap),
+    KVExtTimeseriesmap(KVETimeseriesmap),
 }
 
 // same 8 byte ptrs; any chance of optimizations?
@@ -115,23 +249,185 @@ pub enum DataModel {
 pub struct Table {
     /// a key/value store
     model_store: DataModel,
-    /// is the table volatile
-    volatile: bool,
+    /// is the table volatile (this can be changed at runtime with `ALTER MODEL`)
+    volatile: AtomicBool,
+    /// when the table was created (UNIX millis). Tables restored from disk carry the time
+    /// they were loaded at since the `PARTMAP` doesn't record this
+    created: u64,
+    /// how the writes to the table are logged (see [`crate::storage::v1::wal`])
+    durability: AtomicDurability,
+}
+
+/// The model codes of the tables that store their values compressed are the codes of the
+/// corresponding (uncompressed) KV tables plus this offset
+pub const COMPRESSED_MODEL_CODE_OFFSET: u8 = 24;
+/// The model codes of the tables that keep their values on disk are the codes of the
+/// corresponding (in-memory) KV tables plus this offset
+pub const DISK_MODEL_CODE_OFFSET: u8 = 36;
+
+/// The data declaration for each model code (see [`Table::get_model_code`])
+const MODEL_DATA_DECL: [&str; 40] = [
+    "(binstr,binstr)",
+    "(binstr,str)",
+    "(str,str)",
+    "(str,binstr)",
+    "(binstr,list<binstr>)",
+    "(binstr,list<str>)",
+    "(str,list<binstr>)",
+    "(str,list<str>)",
+    "(binstr,set<binstr>)",
+    "(binstr,set<str>)",
+    "(str,set<binstr>)",
+    "(str,set<str>)",
+    "(binstr,zset<binstr>)",
+    "(binstr,zset<str>)",
+    "(str,zset<binstr>)",
+    "(str,zset<str>)",
+    "(binstr,map<str,binstr>)",
+    "(binstr,map<str,str>)",
+    "(str,map<str,binstr>)",
+    "(str,map<str,str>)",
+    "(binstr,u64)",
+    "(str,u64)",
+    "(binstr,json)",
+    "(str,json)",
+    "(binstr,binstr)",
+    "(binstr,str)",
+    "(str,str)",
+    "(str,binstr)",
+    "(binstr,bloom)",
+    "(str,bloom)",
+    "(binstr,hll)",
+    "(str,hll)",
+    "(binstr,geo)",
+    "(str,geo)",
+    "(binstr,timeseries)",
+    "(str,timeseries)",
+    "(binstr,binstr)",
+    "(binstr,str)",
+    "(str,str)",
+    "(str,binstr)",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+/// A point-in-time description of a table, as returned by `INSPECT`
+pub struct TableDescription {
+    /// the data declaration, for example `(str,str)`
+    pub data: &'static str,
+    pub volatile: bool,
+    /// the values are stored compressed
+    pub compressed: bool,
+    /// the number of entries
+    pub entries: usize,
+    /// the approximate memory usage in bytes
+    pub memory: usize,
+    /// the creation time (UNIX millis)
+    pub created: u64,
+    /// the maximum size of a key in bytes (zero if there's no limit)
+    pub max_key_size: usize,
+    /// the maximum size of a value in bytes (zero if there's no limit)
+    pub max_value_size: usize,
+    /// the memory cap in bytes (zero if there's no cap)
+    pub max_memory: usize,
+    /// how keys are evicted once the table is over its memory cap
+    pub eviction: EvictionPolicy,
+    /// the number of keys that were evicted
+    pub evicted: u64,
+    /// how the writes to the table are logged
+    pub durability: Durability,
+    /// where the values are kept (`memory` or `disk`)
+    pub storage: &'static str,
 }
 
 impl Table {
     #[cfg(test)]
-    pub const fn from_kve(kve: KVEStandard, volatile: bool) -> Self {
+    pub fn from_kve(kve: KVEStandard, volatile: bool) -> Self {
         Self {
             model_store: DataModel::KV(kve),
-            volatile,
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
         }
     }
     #[cfg(test)]
-    pub const fn from_kve_listmap(kve: KVEListmap, volatile: bool) -> Self {
+    pub fn from_kve_listmap(kve: KVEListmap, volatile: bool) -> Self {
         Self {
             model_store: DataModel::KVExtListmap(kve),
-            volatile,
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_setmap(kve: KVESetmap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtSetmap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_zsetmap(kve: KVEZsetmap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtZsetmap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_hashmap(kve: KVEHashmap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtHashmap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_countermap(kve: KVECountermap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtCountermap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_bloommap(kve: KVEBloommap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtBloommap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_hllmap(kve: KVEHllmap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtHllmap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_geomap(kve: KVEGeomap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtGeomap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    #[cfg(test)]
+    pub fn from_kve_timeseriesmap(kve: KVETimeseriesmap, volatile: bool) -> Self {
+        Self {
+            model_store: DataModel::KVExtTimeseriesmap(kve),
+            volatile: AtomicBool::new(volatile),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
         }
     }
     /// Get the key/value store if the table is a key/value store
@@ -148,6 +444,14 @@ impl Table {
         match &self.model_store {
             DataModel::KV(kv) => kv.len(),
             DataModel::KVExtListmap(kv) => kv.len(),
+            DataModel::KVExtSetmap(kv) => kv.len(),
+            DataModel::KVExtZsetmap(kv) => kv.len(),
+            DataModel::KVExtHashmap(kv) => kv.len(),
+            DataModel::KVExtCountermap(kv) => kv.len(),
+            DataModel::KVExtBloommap(kv) => kv.len(),
+            DataModel::KVExtHllmap(kv) => kv.len(),
+            DataModel::KVExtGeomap(kv) => kv.len(),
+            DataModel::KVExtTimeseriesmap(kv) => kv.len(),
         }
     }
     /// Returns this table's _description_
@@ -171,25 +475,599 @@ impl Table {
             6 if !self.is_volatile() => "Keymap { data:(str,list<binstr>), volatile:false }",
             7 if self.is_volatile() => "Keymap { data:(str,list<str>), volatile:true }",
             7 if !self.is_volatile() => "Keymap { data:(str,list<str>), volatile:false }",
+            // KVext => set
+            8 if self.is_volatile() => "Keymap { data:(binstr,set<binstr>), volatile:true }",
+            8 if !self.is_volatile() => "Keymap { data:(binstr,set<binstr>), volatile:false }",
+            9 if self.is_volatile() => "Keymap { data:(binstr,set<str>), volatile:true }",
+            9 if !self.is_volatile() => "Keymap { data:(binstr,set<str>), volatile:false }",
+            10 if self.is_volatile() => "Keymap { data:(str,set<binstr>), volatile:true }",
+            10 if !self.is_volatile() => "Keymap { data:(str,set<binstr>), volatile:false }",
+            11 if self.is_volatile() => "Keymap { data:(str,set<str>), volatile:true }",
+            11 if !self.is_volatile() => "Keymap { data:(str,set<str>), volatile:false }",
+            // KVext => zset
+            12 if self.is_volatile() => "Keymap { data:(binstr,zset<binstr>), volatile:true }",
+            12 if !self.is_volatile() => "Keymap { data:(binstr,zset<binstr>), volatile:false }",
+            13 if self.is_volatile() => "Keymap { data:(binstr,zset<str>), volatile:true }",
+            13 if !self.is_volatile() => "Keymap { data:(binstr,zset<str>), volatile:false }",
+            14 if self.is_volatile() => "Keymap { data:(str,zset<binstr>), volatile:true }",
+            14 if !self.is_volatile() => "Keymap { data:(str,zset<binstr>), volatile:false }",
+            15 if self.is_volatile() => "Keymap { data:(str,zset<str>), volatile:true }",
+            15 if !self.is_volatile() => "Keymap { data:(str,zset<str>), volatile:false }",
+            // KVext => map
+            16 if self.is_volatile() => "Keymap { data:(binstr,map<str,binstr>), volatile:true }",
+            16 if !self.is_volatile() => "Keymap { data:(binstr,map<str,binstr>), volatile:false }",
+            17 if self.is_volatile() => "Keymap { data:(binstr,map<str,str>), volatile:true }",
+            17 if !self.is_volatile() => "Keymap { data:(binstr,map<str,str>), volatile:false }",
+            18 if self.is_volatile() => "Keymap { data:(str,map<str,binstr>), volatile:true }",
+            18 if !self.is_volatile() => "Keymap { data:(str,map<str,binstr>), volatile:false }",
+            19 if self.is_volatile() => "Keymap { data:(str,map<str,str>), volatile:true }",
+            19 if !self.is_volatile() => "Keymap { data:(str,map<str,str>), volatile:false }",
+            // KVext => u64
+            20 if self.is_volatile() => "Keymap { data:(binstr,u64), volatile:true }",
+            20 if !self.is_volatile() => "Keymap { data:(binstr,u64), volatile:false }",
+            21 if self.is_volatile() => "Keymap { data:(str,u64), volatile:true }",
+            21 if !self.is_volatile() => "Keymap { data:(str,u64), volatile:false }",
+            // KV => json
+            22 if self.is_volatile() => "Keymap { data:(binstr,json), volatile:true }",
+            22 if !self.is_volatile() => "Keymap { data:(binstr,json), volatile:false }",
+            23 if self.is_volatile() => "Keymap { data:(str,json), volatile:true }",
+            23 if !self.is_volatile() => "Keymap { data:(str,json), volatile:false }",
+            // KV => compressed
+            24 if self.is_volatile() => {
+                "Keymap { data:(binstr,binstr), volatile:true, compression:lz4 }"
+            }
+            24 if !self.is_volatile() => {
+                "Keymap { data:(binstr,binstr), volatile:false, compression:lz4 }"
+            }
+            25 if self.is_volatile() => {
+                "Keymap { data:(binstr,str), volatile:true, compression:lz4 }"
+            }
+            25 if !self.is_volatile() => {
+                "Keymap { data:(binstr,str), volatile:false, compression:lz4 }"
+            }
+            26 if self.is_volatile() => "Keymap { data:(str,str), volatile:true, compression:lz4 }",
+            26 if !self.is_volatile() => {
+                "Keymap { data:(str,str), volatile:false, compression:lz4 }"
+            }
+            27 if self.is_volatile() => {
+                "Keymap { data:(str,binstr), volatile:true, compression:lz4 }"
+            }
+            27 if !self.is_volatile() => {
+                "Keymap { data:(str,binstr), volatile:false, compression:lz4 }"
+            }
+            // KVext => bloom
+            28 if self.is_volatile() => "Keymap { data:(binstr,bloom), volatile:true }",
+            28 if !self.is_volatile() => "Keymap { data:(binstr,bloom), volatile:false }",
+            29 if self.is_volatile() => "Keymap { data:(str,bloom), volatile:true }",
+            29 if !self.is_volatile() => "Keymap { data:(str,bloom), volatile:false }",
+            // KVext => hll
+            30 if self.is_volatile() => "Keymap { data:(binstr,hll), volatile:true }",
+            30 if !self.is_volatile() => "Keymap { data:(binstr,hll), volatile:false }",
+            31 if self.is_volatile() => "Keymap { data:(str,hll), volatile:true }",
+            31 if !self.is_volatile() => "Keymap { data:(str,hll), volatile:false }",
+            // KVext => geo
+            32 if self.is_volatile() => "Keymap { data:(binstr,geo), volatile:true }",
+            32 if !self.is_volatile() => "Keymap { data:(binstr,geo), volatile:false }",
+            33 if self.is_volatile() => "Keymap { data:(str,geo), volatile:true }",
+            33 if !self.is_volatile() => "Keymap { data:(str,geo), volatile:false }",
+            // KVext => timeseries
+            34 if self.is_volatile() => "Keymap { data:(binstr,timeseries), volatile:true }",
+            34 if !self.is_volatile() => "Keymap { data:(binstr,timeseries), volatile:false }",
+            35 if self.is_volatile() => "Keymap { data:(str,timeseries), volatile:true }",
+            35 if !self.is_volatile() => "Keymap { data:(str,timeseries), volatile:false }",
+            // KV => disk
+            36 if self.is_volatile() => {
+                "Keymap { data:(binstr,binstr), volatile:true, storage:disk }"
+            }
+            36 if !self.is_volatile() => {
+                "Keymap { data:(binstr,binstr), volatile:false, storage:disk }"
+            }
+            37 if self.is_volatile() => "Keymap { data:(binstr,str), volatile:true, storage:disk }",
+            37 if !self.is_volatile() => {
+                "Keymap { data:(binstr,str), volatile:false, storage:disk }"
+            }
+            38 if self.is_volatile() => "Keymap { data:(str,str), volatile:true, storage:disk }",
+            38 if !self.is_volatile() => "Keymap { data:(str,str), volatile:false, storage:disk }",
+            39 if self.is_volatile() => "Keymap { data:(str,binstr), volatile:true, storage:disk }",
+            39 if !self.is_volatile() => {
+                "Keymap { data:(str,binstr), volatile:false, storage:disk }"
+            }
             _ => unsafe { impossible!() },
         }
     }
+    /// Returns a structured description of this table
+    pub fn description(&self) -> TableDescription {
+        TableDescription {
+            data: MODEL_DATA_DECL[self.get_model_code() as usize],
+            volatile: self.is_volatile(),
+            compressed: self.is_compressed(),
+            entries: self.count(),
+            memory: self.memory_usage(),
+            created: self.created,
+            max_key_size: self.limits().max_key_size(),
+            max_value_size: self.limits().max_value_size(),
+            max_memory: self.eviction().max_memory(),
+            eviction: self.eviction().policy(),
+            evicted: self.eviction().evicted(),
+            durability: self.durability(),
+            storage: if self.is_on_disk() { "disk" } else { "memory" },
+        }
+    }
+    /// Returns the model code of the tables with the given data declaration (as in
+    /// [`TableDescription::data`]) that store their values compressed, or on disk, or neither.
+    /// Returns `None` if there's no such model
+    pub fn model_code_for(data: &str, compressed: bool, on_disk: bool) -> Option<u8> {
+        let compressed_codes = COMPRESSED_MODEL_CODE_OFFSET..COMPRESSED_MODEL_CODE_OFFSET + 4;
+        let disk_codes = DISK_MODEL_CODE_OFFSET..DISK_MODEL_CODE_OFFSET + 4;
+        (0..MODEL_DATA_DECL.len() as u8).find(|code| {
+            MODEL_DATA_DECL[*code as usize] == data
+                && compressed_codes.contains(code) == compressed
+                && disk_codes.contains(code) == on_disk
+        })
+    }
+    /// Returns the size limits of this table
+    pub fn limits(&self) -> &SizeLimits {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.limits(),
+            DataModel::KVExtListmap(ref kv) => kv.limits(),
+            DataModel::KVExtSetmap(ref kv) => kv.limits(),
+            DataModel::KVExtZsetmap(ref kv) => kv.limits(),
+            DataModel::KVExtHashmap(ref kv) => kv.limits(),
+            DataModel::KVExtCountermap(ref kv) => kv.limits(),
+            DataModel::KVExtBloommap(ref kv) => kv.limits(),
+            DataModel::KVExtHllmap(ref kv) => kv.limits(),
+            DataModel::KVExtGeomap(ref kv) => kv.limits(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.limits(),
+        }
+    }
+    /// Returns the memory cap and the eviction policy of this table
+    pub fn eviction(&self) -> &Eviction {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.eviction(),
+            DataModel::KVExtListmap(ref kv) => kv.eviction(),
+            DataModel::KVExtSetmap(ref kv) => kv.eviction(),
+            DataModel::KVExtZsetmap(ref kv) => kv.eviction(),
+            DataModel::KVExtHashmap(ref kv) => kv.eviction(),
+            DataModel::KVExtCountermap(ref kv) => kv.eviction(),
+            DataModel::KVExtBloommap(ref kv) => kv.eviction(),
+            DataModel::KVExtHllmap(ref kv) => kv.eviction(),
+            DataModel::KVExtGeomap(ref kv) => kv.eviction(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.eviction(),
+        }
+    }
+    /// Evict keys until the table fits in its memory cap again (if it's volatile), returning
+    /// the number of evicted keys
+    pub fn evict(&self) -> usize {
+        let volatile = self.is_volatile();
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.evict(volatile),
+            DataModel::KVExtListmap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtSetmap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtZsetmap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtHashmap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtCountermap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtBloommap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtHllmap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtGeomap(ref kv) => kv.evict(volatile),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.evict(volatile),
+        }
+    }
+    /// Returns the approximate number of bytes used by the data in this table
+    pub fn memory_usage(&self) -> usize {
+        match &self.model_store {
+            DataModel::KV(kv) => kv.memory_usage(),
+            DataModel::KVExtListmap(kv) => kv.memory_usage(),
+            DataModel::KVExtSetmap(kv) => kv.memory_usage(),
+            DataModel::KVExtZsetmap(kv) => kv.memory_usage(),
+            DataModel::KVExtHashmap(kv) => kv.memory_usage(),
+            DataModel::KVExtCountermap(kv) => kv.memory_usage(),
+            DataModel::KVExtBloommap(kv) => kv.memory_usage(),
+            DataModel::KVExtHllmap(kv) => kv.memory_usage(),
+            DataModel::KVExtGeomap(kv) => kv.memory_usage(),
+            DataModel::KVExtTimeseriesmap(kv) => kv.memory_usage(),
+        }
+    }
     pub fn truncate_table(&self) {
         match self.model_store {
             DataModel::KV(ref kv) => kv.truncate_table(),
             DataModel::KVExtListmap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtSetmap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtZsetmap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtHashmap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtCountermap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtBloommap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtHllmap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtGeomap(ref kv) => kv.truncate_table(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.truncate_table(),
+        }
+    }
+    /// Returns the next batch of keys for the scan cursor along with the cursor to continue
+    /// with (which is `0` once the scan is complete). A cursor of `0` starts a new scan
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<SharedSlice>) {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtListmap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtSetmap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtZsetmap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtHashmap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtCountermap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtBloommap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtHllmap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtGeomap(ref kv) => kv.scan_keys(cursor, count),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.scan_keys(cursor, count),
+        }
+    }
+    /// Check if the key exists, regardless of the model. Returns an error if the key's
+    /// encoding is invalid
+    pub fn key_exists(&self, key: &[u8]) -> Result<bool, ()> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.exists(key),
+            DataModel::KVExtListmap(ref kv) => kv.exists(key),
+            DataModel::KVExtSetmap(ref kv) => kv.exists(key),
+            DataModel::KVExtZsetmap(ref kv) => kv.exists(key),
+            DataModel::KVExtHashmap(ref kv) => kv.exists(key),
+            DataModel::KVExtCountermap(ref kv) => kv.exists(key),
+            DataModel::KVExtBloommap(ref kv) => kv.exists(key),
+            DataModel::KVExtHllmap(ref kv) => kv.exists(key),
+            DataModel::KVExtGeomap(ref kv) => kv.exists(key),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.exists(key),
+        }
+    }
+    /// Returns the approximate number of bytes used by the key and its value, or `None` if
+    /// the key doesn't exist
+    pub fn key_memory_usage(&self, key: &[u8]) -> Result<Option<usize>, ()> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtListmap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtSetmap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtZsetmap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtHashmap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtCountermap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtBloommap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtHllmap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtGeomap(ref kv) => kv.key_memory_usage(key),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.key_memory_usage(key),
+        }
+    }
+    /// Remove all the keys that start with `prefix`, returning the number of removed keys
+    pub fn remove_prefix(&self, prefix: &[u8]) -> usize {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtListmap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtSetmap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtZsetmap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtHashmap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtCountermap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtBloommap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtHllmap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtGeomap(ref kv) => kv.remove_prefix(prefix),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.remove_prefix(prefix),
+        }
+    }
+    /// Rename the key `from` to `to`, but only if `to` doesn't exist. Returns `None` if `from`
+    /// doesn't exist and `Some(false)` if `to` already exists
+    pub fn rename_key<P: ProtocolSpec>(
+        &self,
+        from: &[u8],
+        to: SharedSlice,
+    ) -> ActionResult<Option<bool>> {
+        let ret = match self.model_store {
+            DataModel::KV(ref kv) => kv.rename(from, to),
+            DataModel::KVExtListmap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtSetmap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtZsetmap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtHashmap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtCountermap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtBloommap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtHllmap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtGeomap(ref kv) => kv.rename(from, to),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.rename(from, to),
+        };
+        ret.or_else(|_| util::err(P::RCODE_ENCODING_ERROR))
+    }
+    /// Copy the value of `src` to the key `dst` in `target` (which can be this table), but
+    /// only if `dst` doesn't exist. Both the tables need to have the same model, although
+    /// their encodings can differ. Returns `None` if `src` doesn't exist and `Some(false)` if
+    /// `dst` already exists
+    pub fn copy_key<P: ProtocolSpec>(
+        &self,
+        src: &[u8],
+        target: &Table,
+        dst: SharedSlice,
+    ) -> ActionResult<Option<bool>> {
+        let ret = match (&self.model_store, &target.model_store) {
+            (DataModel::KV(kv), DataModel::KV(tkv)) => kv.copy_to(src, tkv, dst),
+            (DataModel::KVExtListmap(kv), DataModel::KVExtListmap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            (DataModel::KVExtSetmap(kv), DataModel::KVExtSetmap(tkv)) => kv.copy_to(src, tkv, dst),
+            (DataModel::KVExtZsetmap(kv), DataModel::KVExtZsetmap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            (DataModel::KVExtHashmap(kv), DataModel::KVExtHashmap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            (DataModel::KVExtCountermap(kv), DataModel::KVExtCountermap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            (DataModel::KVExtBloommap(kv), DataModel::KVExtBloommap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            (DataModel::KVExtHllmap(kv), DataModel::KVExtHllmap(tkv)) => kv.copy_to(src, tkv, dst),
+            (DataModel::KVExtGeomap(kv), DataModel::KVExtGeomap(tkv)) => kv.copy_to(src, tkv, dst),
+            (DataModel::KVExtTimeseriesmap(kv), DataModel::KVExtTimeseriesmap(tkv)) => {
+                kv.copy_to(src, tkv, dst)
+            }
+            _ => return util::err(P::RSTRING_WRONG_MODEL),
+        };
+        translate_read_error::<P, _>(ret)
+    }
+    /// Move `key` to `target`, but only if it doesn't exist there. Both tables must use the
+    /// same model. See [`crate::kvengine::KVEngine::move_to`]
+    pub fn move_key<P: ProtocolSpec>(
+        &self,
+        key: &[u8],
+        target: &Table,
+    ) -> ActionResult<Option<bool>> {
+        let ret = match (&self.model_store, &target.model_store) {
+            (DataModel::KV(kv), DataModel::KV(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtListmap(kv), DataModel::KVExtListmap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtSetmap(kv), DataModel::KVExtSetmap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtZsetmap(kv), DataModel::KVExtZsetmap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtHashmap(kv), DataModel::KVExtHashmap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtCountermap(kv), DataModel::KVExtCountermap(tkv)) => {
+                kv.move_to(key, tkv)
+            }
+            (DataModel::KVExtBloommap(kv), DataModel::KVExtBloommap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtHllmap(kv), DataModel::KVExtHllmap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtGeomap(kv), DataModel::KVExtGeomap(tkv)) => kv.move_to(key, tkv),
+            (DataModel::KVExtTimeseriesmap(kv), DataModel::KVExtTimeseriesmap(tkv)) => {
+                kv.move_to(key, tkv)
+            }
+            _ => return util::err(P::RSTRING_WRONG_MODEL),
+        };
+        translate_read_error::<P, _>(ret)
+    }
+    /// Returns all the keys that match the pattern
+    pub fn get_keys_matching(&self, pattern: &Pattern) -> Vec<SharedSlice> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtListmap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtSetmap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtZsetmap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtHashmap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtCountermap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtBloommap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtHllmap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtGeomap(ref kv) => kv.get_keys_matching(pattern),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.get_keys_matching(pattern),
+        }
+    }
+    /// Returns at most `count` distinct keys picked at random
+    pub fn sample_keys(&self, count: usize) -> Vec<SharedSlice> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtListmap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtSetmap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtZsetmap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtHashmap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtCountermap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtBloommap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtHllmap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtGeomap(ref kv) => kv.sample_keys(count),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.sample_keys(count),
+        }
+    }
+    /// Returns the tsymbol for the keys in this table
+    pub fn get_key_tsymbol(&self) -> u8 {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtListmap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtSetmap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtZsetmap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHashmap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtCountermap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtBloommap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtHllmap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtGeomap(ref kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.get_key_tsymbol(),
+        }
+    }
+    /// Returns the keyspace notification state of this table
+    pub fn notifier(&self) -> &Notifier {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.notifier(),
+            DataModel::KVExtListmap(ref kv) => kv.notifier(),
+            DataModel::KVExtSetmap(ref kv) => kv.notifier(),
+            DataModel::KVExtZsetmap(ref kv) => kv.notifier(),
+            DataModel::KVExtHashmap(ref kv) => kv.notifier(),
+            DataModel::KVExtCountermap(ref kv) => kv.notifier(),
+            DataModel::KVExtBloommap(ref kv) => kv.notifier(),
+            DataModel::KVExtHllmap(ref kv) => kv.notifier(),
+            DataModel::KVExtGeomap(ref kv) => kv.notifier(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.notifier(),
+        }
+    }
+    /// Returns all the keys in the table (after evicting the expired ones)
+    pub fn keys(&self) -> Vec<SharedSlice> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtListmap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtSetmap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtZsetmap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtHashmap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtCountermap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtBloommap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtHllmap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtGeomap(ref kv) => kv.get_all_keys(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.get_all_keys(),
+        }
+    }
+    /// Remove a key (without checking its encoding), along with its expiry. Returns false if
+    /// the key doesn't exist
+    pub fn remove_key(&self, key: &[u8]) -> bool {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtListmap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtSetmap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtZsetmap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtHashmap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtCountermap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtBloommap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtHllmap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtGeomap(ref kv) => kv.remove_unchecked(key),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.remove_unchecked(key),
+        }
+    }
+    /// Returns the expiry deadline of a key (without checking its encoding), if it has one
+    pub fn deadline_of(&self, key: &[u8]) -> Option<u64> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtListmap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtSetmap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtZsetmap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtHashmap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtCountermap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtBloommap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtHllmap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtGeomap(ref kv) => kv.deadline_unchecked(key),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.deadline_unchecked(key),
+        }
+    }
+    /// Returns all the keys that have an expiry set, along with their deadlines
+    pub fn deadlines(&self) -> Vec<(SharedSlice, u64)> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.deadlines(),
+            DataModel::KVExtListmap(ref kv) => kv.deadlines(),
+            DataModel::KVExtSetmap(ref kv) => kv.deadlines(),
+            DataModel::KVExtZsetmap(ref kv) => kv.deadlines(),
+            DataModel::KVExtHashmap(ref kv) => kv.deadlines(),
+            DataModel::KVExtCountermap(ref kv) => kv.deadlines(),
+            DataModel::KVExtBloommap(ref kv) => kv.deadlines(),
+            DataModel::KVExtHllmap(ref kv) => kv.deadlines(),
+            DataModel::KVExtGeomap(ref kv) => kv.deadlines(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.deadlines(),
+        }
+    }
+    /// Set the expiry deadline of an existing key (without checking its encoding). Returns
+    /// false if the key doesn't exist
+    pub fn set_deadline(&self, key: &[u8], deadline: u64) -> bool {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtListmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtSetmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtZsetmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtHashmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtCountermap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtBloommap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtHllmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtGeomap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.set_expiry_unchecked(key, deadline),
+        }
+    }
+    /// Returns the number of keys that have an expiry set
+    pub fn expiry_count(&self) -> usize {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.expiry_count(),
+            DataModel::KVExtListmap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtSetmap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtZsetmap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtHashmap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtCountermap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtBloommap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtHllmap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtGeomap(ref kv) => kv.expiry_count(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.expiry_count(),
+        }
+    }
+    /// Evict all expired keys, returning the number of evicted keys
+    pub fn sweep_expired(&self) -> usize {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtListmap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtSetmap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtZsetmap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtHashmap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtCountermap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtBloommap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtHllmap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtGeomap(ref kv) => kv.sweep_expired(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.sweep_expired(),
+        }
+    }
+    /// Returns the number of steps that compacting the table takes (see
+    /// [`crate::services::compaction`])
+    pub fn compaction_steps(&self) -> usize {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtListmap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtSetmap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtZsetmap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtHashmap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtCountermap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtBloommap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtHllmap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtGeomap(ref kv) => kv.compaction_steps(),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.compaction_steps(),
+        }
+    }
+    /// Run a step of the compaction, returning the number of bytes freed
+    pub fn compact_step(&self, step: usize) -> usize {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.compact_step(step),
+            DataModel::KVExtListmap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtSetmap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtZsetmap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtHashmap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtCountermap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtBloommap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtHllmap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtGeomap(ref kv) => kv.compact_step(step),
+            DataModel::KVExtTimeseriesmap(ref kv) => kv.compact_step(step),
+        }
+    }
+    /// Start moving the values of a table that keeps them on disk to a new file, if it's
+    /// worth it (see [`KVEStandard::compact_values`])
+    pub fn compact_values(&self) -> Option<ValueCompaction<'_>> {
+        match self.model_store {
+            DataModel::KV(ref kv) => kv.compact_values(),
+            _ => None,
         }
     }
     pub fn is_empty(&self) -> bool {
         self.count() == 0
     }
+    /// Returns true if the values in the table are stored compressed
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.model_store, DataModel::KV(ref kvs) if kvs.is_compressed())
+    }
+    /// Returns true if the values in the table are kept on disk
+    pub fn is_on_disk(&self) -> bool {
+        matches!(
+            self.model_store,
+            DataModel::KV(ref kvs) if kvs.storage_kind() == StorageKind::Disk
+        )
+    }
     /// Returns the storage type as an 8-bit uint
-    pub const fn storage_type(&self) -> u8 {
-        self.volatile as u8
+    pub fn storage_type(&self) -> u8 {
+        self.is_volatile() as u8
     }
     /// Returns the volatility of the table
-    pub const fn is_volatile(&self) -> bool {
-        self.volatile
+    pub fn is_volatile(&self) -> bool {
+        self.volatile.load(Ordering::Acquire)
+    }
+    /// Returns how the writes to the table are logged
+    pub fn durability(&self) -> Durability {
+        self.durability.load()
+    }
+    /// Change how the writes to the table are logged
+    pub fn set_durability(&self, durability: Durability) {
+        self.durability.store(durability)
+    }
+    /// Change the volatility of the table, returning the older volatility. The caller is
+    /// responsible for tripping the preload switch so that the `PARTMAP` is rewritten
+    pub fn set_volatile(&self, volatile: bool) -> bool {
+        self.volatile.swap(volatile, Ordering::AcqRel)
     }
     /// Create a new KVEBlob Table with the provided settings
     pub fn new_pure_kve_with_data(
@@ -199,8 +1077,46 @@ impl Table {
         v_enc: bool,
     ) -> Self {
         Self {
-            volatile,
+            volatile: AtomicBool::new(volatile),
             model_store: DataModel::KV(KVEStandard::new(k_enc, v_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    /// Create a new KVEBlob Table whose values must be valid JSON
+    pub fn new_kve_jsonmap_with_data(
+        data: Coremap<SharedSlice, SharedSlice>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KV(KVEStandard::new_json(k_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    /// Create a new KVEBlob Table that stores its values compressed
+    pub fn new_kve_compressed_with_data(
+        data: Coremap<SharedSlice, SharedSlice>,
+        volatile: bool,
+        k_enc: bool,
+        v_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KV(KVEStandard::new_compressed(k_enc, v_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    /// Create a new KVEBlob Table that keeps its values on disk
+    pub fn new_kve_disk_with_data(data: Loaded, volatile: bool, k_enc: bool, v_enc: bool) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KV(KVEStandard::from_loaded(k_enc, v_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
         }
     }
     pub fn new_kve_listmap_with_data(
@@ -210,8 +1126,114 @@ impl Table {
         payload_enc: bool,
     ) -> Self {
         Self {
-            volatile,
+            volatile: AtomicBool::new(volatile),
             model_store: DataModel::KVExtListmap(KVEListmap::new(k_enc, payload_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_setmap_with_data(
+        data: Coremap<SharedSlice, LockedSet>,
+        volatile: bool,
+        k_enc: bool,
+        payload_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KVExtSetmap(KVESetmap::new(k_enc, payload_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_zsetmap_with_data(
+        data: Coremap<SharedSlice, LockedZset>,
+        volatile: bool,
+        k_enc: bool,
+        payload_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KVExtZsetmap(KVEZsetmap::new(k_enc, payload_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_hashmap_with_data(
+        data: Coremap<SharedSlice, LockedMap>,
+        volatile: bool,
+        k_enc: bool,
+        payload_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            model_store: DataModel::KVExtHashmap(KVEHashmap::new(k_enc, payload_enc, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_countermap_with_data(
+        data: Coremap<SharedSlice, AtomicU64>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            // integers don't have an encoding
+            model_store: DataModel::KVExtCountermap(KVECountermap::new(k_enc, false, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_bloommap_with_data(
+        data: Coremap<SharedSlice, LockedBloom>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            // the items aren't stored, so they don't have an encoding
+            model_store: DataModel::KVExtBloommap(KVEBloommap::new(k_enc, false, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_hllmap_with_data(
+        data: Coremap<SharedSlice, LockedHll>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            // the items aren't stored, so they don't have an encoding
+            model_store: DataModel::KVExtHllmap(KVEHllmap::new(k_enc, false, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_geomap_with_data(
+        data: Coremap<SharedSlice, LockedGeo>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            // members are always binary
+            model_store: DataModel::KVExtGeomap(KVEGeomap::new(k_enc, false, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
+        }
+    }
+    pub fn new_kve_timeseriesmap_with_data(
+        data: Coremap<SharedSlice, LockedTimeseries>,
+        volatile: bool,
+        k_enc: bool,
+    ) -> Self {
+        Self {
+            volatile: AtomicBool::new(volatile),
+            // timestamps and values are numbers
+            model_store: DataModel::KVExtTimeseriesmap(KVETimeseriesmap::new(k_enc, false, data)),
+            created: expiry::now_millis(),
+            durability: AtomicDurability::new(Durability::Default),
         }
     }
     pub fn from_model_code(code: u8, volatile: bool) -> Option<Self> {
@@ -220,11 +1242,36 @@ impl Table {
                 Self::new_pure_kve_with_data(Coremap::new(), volatile, $kenc, $venc)
             };
         }
+        macro_rules! compressed {
+            ($kenc:expr, $venc:expr) => {
+                Self::new_kve_compressed_with_data(Coremap::new(), volatile, $kenc, $venc)
+            };
+        }
+        macro_rules! disk {
+            ($kenc:expr, $venc:expr) => {
+                Self::new_kve_disk_with_data(Loaded::new(), volatile, $kenc, $venc)
+            };
+        }
         macro_rules! listmap {
             ($kenc:expr, $penc:expr) => {
                 Self::new_kve_listmap_with_data(Coremap::new(), volatile, $kenc, $penc)
             };
         }
+        macro_rules! setmap {
+            ($kenc:expr, $penc:expr) => {
+                Self::new_kve_setmap_with_data(Coremap::new(), volatile, $kenc, $penc)
+            };
+        }
+        macro_rules! zsetmap {
+            ($kenc:expr, $penc:expr) => {
+                Self::new_kve_zsetmap_with_data(Coremap::new(), volatile, $kenc, $penc)
+            };
+        }
+        macro_rules! hashmap {
+            ($kenc:expr, $penc:expr) => {
+                Self::new_kve_hashmap_with_data(Coremap::new(), volatile, $kenc, $penc)
+            };
+        }
         let ret = match code {
             // pure kve
             0 => pkve!(false, false),
@@ -236,6 +1283,49 @@ impl Table {
             5 => listmap!(false, true),
             6 => listmap!(true, false),
             7 => listmap!(true, true),
+            // kvext: setmap
+            8 => setmap!(false, false),
+            9 => setmap!(false, true),
+            10 => setmap!(true, false),
+            11 => setmap!(true, true),
+            // kvext: zsetmap
+            12 => zsetmap!(false, false),
+            13 => zsetmap!(false, true),
+            14 => zsetmap!(true, false),
+            15 => zsetmap!(true, true),
+            // kvext: hashmap
+            16 => hashmap!(false, false),
+            17 => hashmap!(false, true),
+            18 => hashmap!(true, false),
+            19 => hashmap!(true, true),
+            // kvext: countermap
+            20 => Self::new_kve_countermap_with_data(Coremap::new(), volatile, false),
+            21 => Self::new_kve_countermap_with_data(Coremap::new(), volatile, true),
+            // kv: jsonmap
+            22 => Self::new_kve_jsonmap_with_data(Coremap::new(), volatile, false),
+            23 => Self::new_kve_jsonmap_with_data(Coremap::new(), volatile, true),
+            // kv: compressed
+            24 => compressed!(false, false),
+            25 => compressed!(false, true),
+            26 => compressed!(true, true),
+            27 => compressed!(true, false),
+            // kvext: bloommap
+            28 => Self::new_kve_bloommap_with_data(Coremap::new(), volatile, false),
+            29 => Self::new_kve_bloommap_with_data(Coremap::new(), volatile, true),
+            // kvext: hllmap
+            30 => Self::new_kve_hllmap_with_data(Coremap::new(), volatile, false),
+            31 => Self::new_kve_hllmap_with_data(Coremap::new(), volatile, true),
+            // kvext: geomap
+            32 => Self::new_kve_geomap_with_data(Coremap::new(), volatile, false),
+            33 => Self::new_kve_geomap_with_data(Coremap::new(), volatile, true),
+            // kvext: timeseriesmap
+            34 => Self::new_kve_timeseriesmap_with_data(Coremap::new(), volatile, false),
+            35 => Self::new_kve_timeseriesmap_with_data(Coremap::new(), volatile, true),
+            // kv: disk
+            36 => disk!(false, false),
+            37 => disk!(false, true),
+            38 => disk!(true, true),
+            39 => disk!(true, false),
             _ => return None,
         };
         Some(ret)
@@ -255,17 +1345,31 @@ impl Table {
     /// Returns the model code. See [`bytemarks`] for more info
     pub fn get_model_code(&self) -> u8 {
         match self.model_store {
+            DataModel::KV(ref kvs) if kvs.is_json() => {
+                /*
+                bin,json => 22,
+                str,json => 23
+                */
+                kvs.is_key_encoded() as u8 + 22
+            }
             DataModel::KV(ref kvs) => {
                 /*
                 bin,bin => 0
                 bin,str => 1
                 str,str => 2
                 str,bin => 3
+                (the same, compressed => 24-27)
+                (the same, on disk => 36-39)
                 */
                 let (kenc, venc) = kvs.get_encoding_tuple();
                 let ret = kenc as u8 + venc as u8;
                 // a little bitmagic goes a long way
-                (ret & 1) + ((kenc as u8) << 1)
+                let code = (ret & 1) + ((kenc as u8) << 1);
+                match kvs.storage_kind() {
+                    StorageKind::Memory => code,
+                    StorageKind::Compressed => code + COMPRESSED_MODEL_CODE_OFFSET,
+                    StorageKind::Disk => code + DISK_MODEL_CODE_OFFSET,
+                }
             }
             DataModel::KVExtListmap(ref kvlistmap) => {
                 /*
@@ -277,6 +1381,71 @@ impl Table {
                 let (kenc, venc) = kvlistmap.get_encoding_tuple();
                 ((kenc as u8) << 1) + (venc as u8) + 4
             }
+            DataModel::KVExtSetmap(ref kvsetmap) => {
+                /*
+                bin,set<bin> => 8,
+                bin,set<str> => 9,
+                str,set<bin> => 10,
+                str,set<str> => 11
+                */
+                let (kenc, venc) = kvsetmap.get_encoding_tuple();
+                ((kenc as u8) << 1) + (venc as u8) + 8
+            }
+            DataModel::KVExtZsetmap(ref kvzsetmap) => {
+                /*
+                bin,zset<bin> => 12,
+                bin,zset<str> => 13,
+                str,zset<bin> => 14,
+                str,zset<str> => 15
+                */
+                let (kenc, venc) = kvzsetmap.get_encoding_tuple();
+                ((kenc as u8) << 1) + (venc as u8) + 12
+            }
+            DataModel::KVExtHashmap(ref kvhashmap) => {
+                /*
+                bin,map<str,bin> => 16,
+                bin,map<str,str> => 17,
+                str,map<str,bin> => 18,
+                str,map<str,str> => 19
+                */
+                let (kenc, venc) = kvhashmap.get_encoding_tuple();
+                ((kenc as u8) << 1) + (venc as u8) + 16
+            }
+            DataModel::KVExtCountermap(ref kvcountermap) => {
+                /*
+                bin,u64 => 20,
+                str,u64 => 21
+                */
+                kvcountermap.is_key_encoded() as u8 + 20
+            }
+            DataModel::KVExtBloommap(ref kvbloommap) => {
+                /*
+                bin,bloom => 28,
+                str,bloom => 29
+                */
+                kvbloommap.is_key_encoded() as u8 + 28
+            }
+            DataModel::KVExtHllmap(ref kvhllmap) => {
+                /*
+                bin,hll => 30,
+                str,hll => 31
+                */
+                kvhllmap.is_key_encoded() as u8 + 30
+            }
+            DataModel::KVExtGeomap(ref kvgeomap) => {
+                /*
+                bin,geo => 32,
+                str,geo => 33
+                */
+                kvgeomap.is_key_encoded() as u8 + 32
+            }
+            DataModel::KVExtTimeseriesmap(ref kvtimeseriesmap) => {
+                /*
+                bin,timeseries => 34,
+                str,timeseries => 35
+                */
+                kvtimeseriesmap.is_key_encoded() as u8 + 34
+            }
         }
     }
     /// Returns the inner data model
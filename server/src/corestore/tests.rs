@@ -44,6 +44,21 @@ mod memstore_keyspace_tests {
         assert_eq!(ms.drop_keyspace(obj).unwrap_err(), DdlError::StillInUse);
     }
 
+    #[test]
+    fn test_keyspace_dirty() {
+        let ms = Memstore::new_empty();
+        let obj = unsafe { ObjectID::from_slice("myks") };
+        ms.create_keyspace(obj.clone());
+        let ks = ms.get_keyspace_atomic_ref(&obj).unwrap();
+        // keyspaces start out dirty
+        assert!(ks.take_dirty());
+        assert!(!ks.is_dirty());
+        ks.mark_dirty();
+        assert!(ks.take_dirty());
+        ms.mark_all_dirty();
+        assert!(ks.is_dirty());
+    }
+
     #[test]
     fn test_drop_keyspace_not_empty() {
         let ms = Memstore::new_empty();
@@ -117,12 +132,30 @@ mod memstore_keyspace_tests {
         // should succeed because the keyspace is non-empty, but no table is referenced to
         assert!(ms.force_drop_keyspace(obj).is_ok());
     }
+
+    #[test]
+    fn test_replace_keyspaces() {
+        let ms = Memstore::new_empty();
+        let stale = unsafe { ObjectID::from_slice("myks") };
+        let restored_ks = unsafe { ObjectID::from_slice("restoredks") };
+        ms.create_keyspace(stale.clone());
+        let restored = Memstore::new_empty();
+        restored.create_keyspace(restored_ks.clone());
+        let generation = ms.generation();
+        ms.replace_keyspaces(restored);
+        assert_eq!(ms.generation(), generation + 1);
+        assert!(ms.get_keyspace_atomic_ref(&stale).is_none());
+        assert!(ms.get_keyspace_atomic_ref(&restored_ks).is_some());
+    }
 }
 
 mod modelcode_tests {
     use {
         super::super::table::Table,
-        crate::kvengine::{KVEListmap, KVEngine},
+        crate::kvengine::{
+            KVEBloommap, KVECountermap, KVEGeomap, KVEHashmap, KVEHllmap, KVEListmap, KVESetmap,
+            KVETimeseriesmap, KVEZsetmap, KVEngine,
+        },
     };
 
     #[test]
@@ -167,4 +200,91 @@ mod modelcode_tests {
         let tbl4 = Table::from_kve_listmap(l4, false);
         assert_eq!(tbl4.get_model_code(), 7);
     }
+    #[test]
+    fn test_model_code_kvext() {
+        // every constructor takes the key encoding first, then the value encoding
+        let set = |k, v| Table::from_kve_setmap(KVESetmap::init(k, v), false);
+        let zset = |k, v| Table::from_kve_zsetmap(KVEZsetmap::init(k, v), false);
+        let hash = |k, v| Table::from_kve_hashmap(KVEHashmap::init(k, v), false);
+        let counter = |k| Table::from_kve_countermap(KVECountermap::init(k, false), false);
+        let bloom = |k| Table::from_kve_bloommap(KVEBloommap::init(k, false), false);
+        let hll = |k| Table::from_kve_hllmap(KVEHllmap::init(k, false), false);
+        let geo = |k| Table::from_kve_geomap(KVEGeomap::init(k, false), false);
+        let ts = |k| Table::from_kve_timeseriesmap(KVETimeseriesmap::init(k, false), false);
+        let tables = [
+            (8, set(false, false)),
+            (9, set(false, true)),
+            (10, set(true, false)),
+            (11, set(true, true)),
+            (12, zset(false, false)),
+            (13, zset(false, true)),
+            (14, zset(true, false)),
+            (15, zset(true, true)),
+            (16, hash(false, false)),
+            (17, hash(false, true)),
+            (18, hash(true, false)),
+            (19, hash(true, true)),
+            (20, counter(false)),
+            (21, counter(true)),
+            (28, bloom(false)),
+            (29, bloom(true)),
+            (30, hll(false)),
+            (31, hll(true)),
+            (32, geo(false)),
+            (33, geo(true)),
+            (34, ts(false)),
+            (35, ts(true)),
+        ];
+        for (code, tbl) in tables {
+            assert_eq!(tbl.get_model_code(), code);
+            // and the code maps back to the same model
+            let restored = Table::from_model_code(code, false).unwrap();
+            assert_eq!(restored.get_model_code(), code);
+            assert_eq!(restored.description().data, tbl.description().data);
+        }
+    }
+    #[test]
+    fn test_model_code_compressed_kv() {
+        for code in 24..28 {
+            let tbl = Table::from_model_code(code, false).unwrap();
+            assert!(tbl.is_compressed());
+            assert_eq!(tbl.get_model_code(), code);
+            // same encodings as the uncompressed tables
+            let uncompressed = Table::from_model_code(code - 24, false).unwrap();
+            assert_eq!(
+                tbl.get_kvstore().unwrap().get_encoding_tuple(),
+                uncompressed.get_kvstore().unwrap().get_encoding_tuple()
+            );
+        }
+    }
+    #[test]
+    fn test_model_code_disk_kv() {
+        for code in 36..40 {
+            let tbl = Table::from_model_code(code, false).unwrap();
+            assert!(tbl.is_on_disk());
+            assert!(!tbl.is_compressed());
+            assert_eq!(tbl.get_model_code(), code);
+            // same encodings as the tables in memory
+            let memory = Table::from_model_code(code - 36, false).unwrap();
+            assert_eq!(
+                tbl.get_kvstore().unwrap().get_encoding_tuple(),
+                memory.get_kvstore().unwrap().get_encoding_tuple()
+            );
+        }
+    }
+    #[test]
+    fn test_model_code_for() {
+        // every model can be found from its description
+        for code in 0..40 {
+            let tbl = Table::from_model_code(code, false).unwrap();
+            let description = tbl.description();
+            assert_eq!(
+                Table::model_code_for(description.data, tbl.is_compressed(), tbl.is_on_disk()),
+                Some(code)
+            );
+        }
+        assert_eq!(Table::model_code_for("(str,list<str>)", true, false), None);
+        assert_eq!(Table::model_code_for("(str,str)", true, true), None);
+        assert_eq!(Table::model_code_for("(str,nope)", false, false), None);
+    }
 }
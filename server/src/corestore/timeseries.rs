@@ -0,0 +1,193 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Time series
+//!
+//! A [`TimeSeries`] holds samples (a value at a timestamp, in milliseconds) in ascending order
+//! of timestamps. Samples almost always arrive in order, so appending a sample is a push to
+//! the back of a deque, while the (rare) out of order sample goes through a binary search.
+//! If the series has a retention period, samples that are older than the newest sample by
+//! more than the retention period are dropped from the front
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A value at a point in time
+pub struct Sample {
+    pub timestamp: u64,
+    pub value: f64,
+}
+
+impl Sample {
+    pub const fn new(timestamp: u64, value: f64) -> Self {
+        Self { timestamp, value }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TimeSeries {
+    /// the samples, in ascending order of timestamps
+    samples: VecDeque<Sample>,
+    /// the retention period in milliseconds (zero means that samples are kept forever)
+    retention: u64,
+}
+
+impl TimeSeries {
+    pub fn new(retention: u64) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            retention,
+        }
+    }
+    /// Restore a time series from its samples. Returns `None` if the samples aren't in strictly
+    /// ascending order of timestamps
+    pub fn from_samples(retention: u64, samples: VecDeque<Sample>) -> Option<Self> {
+        let is_sorted = samples
+            .iter()
+            .zip(samples.iter().skip(1))
+            .all(|(a, b)| a.timestamp < b.timestamp);
+        if is_sorted {
+            Some(Self { samples, retention })
+        } else {
+            None
+        }
+    }
+    /// Returns the number of samples
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    pub const fn retention(&self) -> u64 {
+        self.retention
+    }
+    /// Set the retention period, dropping any samples that fall outside it
+    pub fn set_retention(&mut self, retention: u64) {
+        self.retention = retention;
+        self.trim();
+    }
+    /// Returns the oldest timestamp that is still within the retention period
+    fn cutoff(&self) -> u64 {
+        match self.samples.back() {
+            Some(newest) if self.retention != 0 => newest.timestamp.saturating_sub(self.retention),
+            _ => 0,
+        }
+    }
+    /// Drop the samples that are outside the retention period
+    fn trim(&mut self) {
+        let cutoff = self.cutoff();
+        while matches!(self.samples.front(), Some(oldest) if oldest.timestamp < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+    /// Add a sample, replacing the value of an existing sample with the same timestamp.
+    /// Returns `Some(true)` if the sample was added, `Some(false)` if it replaced an existing
+    /// sample and `None` if it's too old for the retention period
+    pub fn add(&mut self, timestamp: u64, value: f64) -> Option<bool> {
+        let sample = Sample::new(timestamp, value);
+        match self.samples.back() {
+            Some(newest) if newest.timestamp >= timestamp => {
+                if timestamp < self.cutoff() {
+                    return None;
+                }
+                match self
+                    .samples
+                    .binary_search_by_key(&timestamp, |sample| sample.timestamp)
+                {
+                    Ok(pos) => {
+                        self.samples[pos] = sample;
+                        Some(false)
+                    }
+                    Err(pos) => {
+                        self.samples.insert(pos, sample);
+                        Some(true)
+                    }
+                }
+            }
+            _ => {
+                // the fast path: the newest sample
+                self.samples.push_back(sample);
+                self.trim();
+                Some(true)
+            }
+        }
+    }
+    /// Returns the samples with a timestamp in the inclusive range `from..=to`
+    pub fn range(&self, from: u64, to: u64) -> Vec<Sample> {
+        let start = self
+            .samples
+            .partition_point(|sample| sample.timestamp < from);
+        self.samples
+            .range(start..)
+            .take_while(|sample| sample.timestamp <= to)
+            .copied()
+            .collect()
+    }
+    /// Returns the newest sample
+    pub fn last(&self) -> Option<Sample> {
+        self.samples.back().copied()
+    }
+    /// Returns an iterator over the samples, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Sample> {
+        self.samples.iter()
+    }
+}
+
+#[test]
+fn time_series_add_range_and_retention() {
+    let mut series = TimeSeries::new(0);
+    assert_eq!(series.last(), None);
+    assert_eq!(series.add(10, 1.0), Some(true));
+    assert_eq!(series.add(30, 3.0), Some(true));
+    // out of order
+    assert_eq!(series.add(20, 2.0), Some(true));
+    // replace
+    assert_eq!(series.add(30, 3.5), Some(false));
+    assert_eq!(series.len(), 3);
+    assert_eq!(series.last(), Some(Sample::new(30, 3.5)));
+    assert_eq!(
+        series.range(15, 30),
+        vec![Sample::new(20, 2.0), Sample::new(30, 3.5)]
+    );
+    assert!(series.range(31, u64::MAX).is_empty());
+    // keep the last 15ms
+    series.set_retention(15);
+    assert_eq!(series.range(0, u64::MAX).len(), 2);
+    // too old
+    assert_eq!(series.add(5, 0.5), None);
+    assert_eq!(series.add(100, 10.0), Some(true));
+    assert_eq!(series.range(0, u64::MAX), vec![Sample::new(100, 10.0)]);
+    assert_eq!(
+        TimeSeries::from_samples(15, series.iter().copied().collect()),
+        Some(series.clone())
+    );
+    let unsorted = [Sample::new(2, 0.0), Sample::new(1, 0.0)];
+    assert_eq!(
+        TimeSeries::from_samples(0, unsorted.into_iter().collect()),
+        None
+    );
+}
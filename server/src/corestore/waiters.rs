@@ -0,0 +1,157 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Waiters
+//!
+//! Connections can park until a key changes (with `WATCH`, for example). Every table keeps a
+//! registry of the keys that have waiters, and every change to one of those keys wakes up all
+//! of its waiters with the change (see [`Event`]). Tables without any waiters only pay for an
+//! atomic load on every write
+
+use {
+    crate::{corestore::SharedSlice, kvengine::notify::Event, util::compiler},
+    parking_lot::Mutex,
+    std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+    tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender},
+};
+
+/// The number of events that are buffered for a waiter that hasn't been woken up yet
+const WAITER_CAPACITY: usize = 16;
+
+#[derive(Debug, Default)]
+/// The waiter registry of a table
+pub struct Waiters {
+    /// the number of keys that have waiters
+    len: AtomicUsize,
+    keys: Mutex<HashMap<SharedSlice, Sender<Event>>>,
+}
+
+impl Waiters {
+    /// Start waiting for changes to the key. No change made after this returns is missed, so
+    /// callers can check the key after registering without racing with writers
+    pub fn register(&self, key: SharedSlice) -> Waiter<'_> {
+        let mut keys = self.keys.lock();
+        let rx = match keys.get(&key) {
+            Some(tx) => tx.subscribe(),
+            None => {
+                let (tx, rx) = broadcast::channel(WAITER_CAPACITY);
+                keys.insert(key.clone(), tx);
+                self.len.store(keys.len(), Ordering::Release);
+                rx
+            }
+        };
+        Waiter {
+            waiters: self,
+            key,
+            rx,
+        }
+    }
+    /// Returns true if no key has waiters
+    pub fn is_empty(&self) -> bool {
+        self.len.load(Ordering::Acquire) == 0
+    }
+    /// Wake up all the waiters for the key (if any)
+    pub fn wake(&self, event: Event, key: &[u8]) {
+        if compiler::likely(self.is_empty()) {
+            return;
+        }
+        if let Some(tx) = self.keys.lock().get(key) {
+            // there's always a receiver while the key is registered
+            let _ = tx.send(event);
+        }
+    }
+    fn unregister(&self, key: &[u8]) {
+        let mut keys = self.keys.lock();
+        // the receiver of the waiter that's going away is still alive here
+        if matches!(keys.get(key), Some(tx) if tx.receiver_count() <= 1) {
+            keys.remove(key);
+            self.len.store(keys.len(), Ordering::Release);
+        }
+    }
+}
+
+/// A waiter for a key. Dropping it stops waiting
+pub struct Waiter<'a> {
+    waiters: &'a Waiters,
+    key: SharedSlice,
+    rx: Receiver<Event>,
+}
+
+impl Waiter<'_> {
+    /// Wait for the next change to the key. A waiter that falls behind will skip the older
+    /// changes. This returns `None` only if the waiter has been disconnected from the registry
+    /// (which never happens since the registry holds the sender while we're alive)
+    pub async fn changed(&mut self) -> Option<Event> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.waiters.unregister(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Waiters};
+
+    #[tokio::test]
+    async fn wake_waiters() {
+        let waiters = Waiters::default();
+        let mut first = waiters.register("a".into());
+        let mut second = waiters.register("a".into());
+        let mut other = waiters.register("b".into());
+        waiters.wake(Event::Update, b"a");
+        waiters.wake(Event::Del, b"b");
+        waiters.wake(Event::Set, b"c");
+        assert_eq!(first.changed().await, Some(Event::Update));
+        assert_eq!(second.changed().await, Some(Event::Update));
+        assert_eq!(other.changed().await, Some(Event::Del));
+    }
+
+    #[test]
+    fn unregister_on_drop() {
+        let waiters = Waiters::default();
+        let first = waiters.register("a".into());
+        let second = waiters.register("a".into());
+        assert_eq!(waiters.keys.lock().len(), 1);
+        drop(first);
+        assert_eq!(waiters.keys.lock().len(), 1);
+        drop(second);
+        assert!(waiters.keys.lock().is_empty());
+        assert_eq!(waiters.len.into_inner(), 0);
+    }
+}
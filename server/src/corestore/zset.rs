@@ -0,0 +1,182 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Sorted sets
+//!
+//! A [`SortedSet`] holds unique members, each of which has a score. The members are ordered
+//! by their scores and members with the same score are ordered lexicographically. Lookups
+//! by member go through a hashtable, while ordered retrieval goes through a B-tree
+
+use {
+    super::SharedSlice,
+    std::{
+        cmp::Ordering,
+        collections::{BTreeSet, HashMap},
+        ops::Bound,
+    },
+};
+
+#[derive(Debug, Clone, Copy)]
+/// A score with a total ordering. `-0.0` is normalized to `0.0`
+pub struct Score(f64);
+
+impl Score {
+    pub fn new(score: f64) -> Self {
+        // adding a positive zero turns a negative zero into a positive zero
+        Self(score + 0.0)
+    }
+    pub const fn get(&self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SortedSet {
+    /// member -> score
+    scores: HashMap<SharedSlice, Score>,
+    /// (score, member) in ascending order
+    ordered: BTreeSet<(Score, SharedSlice)>,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of members
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+    /// Add a member or update its score. Returns true if the member was newly added
+    pub fn insert(&mut self, member: SharedSlice, score: f64) -> bool {
+        let score = Score::new(score);
+        match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                if old != score {
+                    self.ordered.remove(&(old, member.clone()));
+                    self.ordered.insert((score, member));
+                }
+                false
+            }
+            None => {
+                self.ordered.insert((score, member));
+                true
+            }
+        }
+    }
+    /// Returns the score of a member
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).map(Score::get)
+    }
+    /// Returns the zero-based position of a member in ascending order of scores
+    pub fn rank(&self, member: &[u8]) -> Option<usize> {
+        let (member, score) = self.scores.get_key_value(member)?;
+        let upper = (*score, member.clone());
+        Some(
+            self.ordered
+                .range((Bound::Unbounded, Bound::Excluded(upper)))
+                .count(),
+        )
+    }
+    /// Returns the members with a score in the inclusive range `min..=max`, in ascending order
+    pub fn range_by_score(&self, min: f64, max: f64) -> Vec<SharedSlice> {
+        let (min, max) = (Score::new(min), Score::new(max));
+        // the empty member sorts before every other member with the same score, so we start
+        // right at the first member with a score of `min`
+        let lower = (min, SharedSlice::new(&[]));
+        self.ordered
+            .range((Bound::Included(lower), Bound::Unbounded))
+            .take_while(|(score, _)| *score <= max)
+            .map(|(_, member)| member.clone())
+            .collect()
+    }
+    /// Returns an iterator over the members and their scores, in ascending order of scores
+    pub fn iter(&self) -> impl Iterator<Item = (&SharedSlice, f64)> {
+        self.ordered
+            .iter()
+            .map(|(score, member)| (member, score.get()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SharedSlice, SortedSet};
+
+    #[test]
+    fn sorted_set_order_and_rank() {
+        let mut zset = SortedSet::new();
+        assert!(zset.insert("carol".into(), 30.0));
+        assert!(zset.insert("alice".into(), 10.0));
+        assert!(zset.insert("bob".into(), 20.0));
+        assert!(zset.insert("bobby".into(), 20.0));
+        assert_eq!(zset.rank(b"alice"), Some(0));
+        assert_eq!(zset.rank(b"bobby"), Some(2));
+        assert_eq!(zset.rank(b"carol"), Some(3));
+        assert_eq!(zset.rank(b"dave"), None);
+        // update a score
+        assert!(!zset.insert("alice".into(), 40.0));
+        assert_eq!(zset.len(), 4);
+        assert_eq!(zset.score(b"alice"), Some(40.0));
+        assert_eq!(zset.rank(b"alice"), Some(3));
+        assert_eq!(
+            zset.range_by_score(15.0, 30.0),
+            vec![
+                SharedSlice::from("bob"),
+                SharedSlice::from("bobby"),
+                SharedSlice::from("carol")
+            ]
+        );
+        assert!(zset.range_by_score(f64::NEG_INFINITY, 5.0).is_empty());
+        // both ends are inclusive, and an inverted range is empty
+        assert_eq!(
+            zset.range_by_score(20.0, 20.0),
+            vec![SharedSlice::from("bob"), SharedSlice::from("bobby")]
+        );
+        assert!(zset.range_by_score(30.0, 15.0).is_empty());
+    }
+}
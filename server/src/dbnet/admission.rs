@@ -0,0 +1,324 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Connection admission
+//!
+//! Every connection is admitted (or turned away) as soon as it's accepted, before the TLS
+//! handshake and the protocol negotiation, so that a client that's opening connections in a
+//! loop can't starve the others out. The limits apply to each IP address on its own:
+//! - the number of concurrent connections (`maxconperip`)
+//! - the number of new connections per second (`maxconrate`)
+//!
+//! Connections that are turned away are simply closed. The counters are shared by all the
+//! listeners and can be read with `SYS METRIC CONNECTIONS`
+
+use {
+    crate::config::AdmissionConfig,
+    parking_lot::{Mutex, RwLock},
+    std::{
+        collections::HashMap,
+        net::IpAddr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// The window over which the connection rate is measured
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// The number of peers that we'll track before looking for ones that we can forget
+const PRUNE_THRESHOLD: usize = 1024;
+
+static ACTIVE: AtomicU64 = AtomicU64::new(0);
+static ACCEPTED: AtomicU64 = AtomicU64::new(0);
+static REJECTED_PER_IP: AtomicU64 = AtomicU64::new(0);
+static REJECTED_RATE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// A snapshot of the admission counters
+pub struct AdmissionStats {
+    /// the number of connections that are currently open
+    pub active: u64,
+    /// the number of connections admitted since startup
+    pub accepted: u64,
+    /// the number of connections turned away because their IP had too many connections open
+    pub rejected_per_ip: u64,
+    /// the number of connections turned away because their IP was connecting too fast
+    pub rejected_rate: u64,
+}
+
+/// Returns the admission counters
+pub fn stats() -> AdmissionStats {
+    AdmissionStats {
+        active: ACTIVE.load(Ordering::Relaxed),
+        accepted: ACCEPTED.load(Ordering::Relaxed),
+        rejected_per_ip: REJECTED_PER_IP.load(Ordering::Relaxed),
+        rejected_rate: REJECTED_RATE.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The reason why a connection was turned away
+pub enum Rejection {
+    /// The IP already has the maximum number of connections open
+    TooManyConnections,
+    /// The IP has opened the maximum number of connections in the current window
+    TooFast,
+}
+
+#[derive(Debug)]
+/// What we know about a peer
+struct Peer {
+    /// the number of connections that are open
+    active: usize,
+    /// the start of the current rate window
+    window_start: Instant,
+    /// the number of connections admitted in the current window
+    window_count: u64,
+}
+
+impl Peer {
+    fn new(now: Instant) -> Self {
+        Self {
+            active: 0,
+            window_start: now,
+            window_count: 0,
+        }
+    }
+    /// Returns true if we don't need to remember this peer anymore
+    fn is_stale(&self, now: Instant) -> bool {
+        self.active == 0 && now.duration_since(self.window_start) >= RATE_WINDOW
+    }
+}
+
+#[derive(Debug)]
+/// The peers that we're tracking
+struct Peers {
+    peers: HashMap<IpAddr, Peer>,
+    /// we'll look for peers to forget once we're tracking these many (this grows with the
+    /// number of peers that have connections open so that we don't keep doing it in vain)
+    prune_at: usize,
+}
+
+#[derive(Debug)]
+/// The admission control shared by all the listeners
+pub struct Admission {
+    config: RwLock<AdmissionConfig>,
+    peers: Mutex<Peers>,
+}
+
+impl Admission {
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            peers: Mutex::new(Peers {
+                peers: HashMap::new(),
+                prune_at: PRUNE_THRESHOLD,
+            }),
+        }
+    }
+    /// Change the limits. Connections that were admitted without any limits in place aren't
+    /// counted against their IP
+    pub fn set_config(&self, config: AdmissionConfig) {
+        *self.config.write() = config;
+    }
+    /// Returns true if there's a limit to enforce (otherwise, we don't track peers at all)
+    fn is_enabled(&self) -> bool {
+        let config = self.config.read();
+        config.max_per_ip.is_some() || config.max_rate.is_some()
+    }
+    /// Admit a connection from `ip`. The returned guard has to be held for as long as the
+    /// connection is open
+    pub fn admit(self: &Arc<Self>, ip: IpAddr) -> Result<AdmissionGuard, Rejection> {
+        let tracked = self.is_enabled();
+        if tracked {
+            if let Err(rejection) = self.admit_at(ip, Instant::now()) {
+                let counter = match rejection {
+                    Rejection::TooManyConnections => &REJECTED_PER_IP,
+                    Rejection::TooFast => &REJECTED_RATE,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+                return Err(rejection);
+            }
+        }
+        ACCEPTED.fetch_add(1, Ordering::Relaxed);
+        ACTIVE.fetch_add(1, Ordering::Relaxed);
+        Ok(AdmissionGuard {
+            admission: self.clone(),
+            ip,
+            tracked,
+        })
+    }
+    fn admit_at(&self, ip: IpAddr, now: Instant) -> Result<(), Rejection> {
+        let mut guard = self.peers.lock();
+        let Peers { peers, prune_at } = &mut *guard;
+        if peers.len() >= *prune_at {
+            peers.retain(|_, peer| !peer.is_stale(now));
+            *prune_at = PRUNE_THRESHOLD.max(peers.len() * 2);
+        }
+        let peer = peers.entry(ip).or_insert_with(|| Peer::new(now));
+        let config = *self.config.read();
+        if let Some(max) = config.max_per_ip {
+            if peer.active >= max {
+                return Err(Rejection::TooManyConnections);
+            }
+        }
+        if now.duration_since(peer.window_start) >= RATE_WINDOW {
+            peer.window_start = now;
+            peer.window_count = 0;
+        }
+        if let Some(max) = config.max_rate {
+            if peer.window_count >= max {
+                return Err(Rejection::TooFast);
+            }
+        }
+        peer.active += 1;
+        peer.window_count += 1;
+        Ok(())
+    }
+    fn release_at(&self, ip: IpAddr, now: Instant) {
+        let mut peers = self.peers.lock();
+        let peers = &mut peers.peers;
+        if let Some(peer) = peers.get_mut(&ip) {
+            peer.active -= 1;
+            if peer.is_stale(now) {
+                peers.remove(&ip);
+            }
+        }
+    }
+    #[cfg(test)]
+    fn tracked_peers(&self) -> usize {
+        self.peers.lock().peers.len()
+    }
+}
+
+/// A connection that was admitted. The connection is counted against its IP till this is
+/// dropped
+#[derive(Debug)]
+pub struct AdmissionGuard {
+    admission: Arc<Admission>,
+    ip: IpAddr,
+    tracked: bool,
+}
+
+impl Drop for AdmissionGuard {
+    fn drop(&mut self) {
+        ACTIVE.fetch_sub(1, Ordering::Relaxed);
+        if self.tracked {
+            self.admission.release_at(self.ip, Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Admission, AdmissionConfig, Rejection, RATE_WINDOW},
+        std::{
+            net::{IpAddr, Ipv4Addr},
+            sync::Arc,
+            time::Instant,
+        },
+    };
+
+    const PEER_A: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const PEER_B: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[test]
+    fn test_max_per_ip() {
+        let admission = Arc::new(Admission::new(AdmissionConfig::new(Some(2), None)));
+        let first = admission.admit(PEER_A).unwrap();
+        let _second = admission.admit(PEER_A).unwrap();
+        assert_eq!(
+            admission.admit(PEER_A).unwrap_err(),
+            Rejection::TooManyConnections
+        );
+        // other peers have limits of their own
+        let _other = admission.admit(PEER_B).unwrap();
+        // and closing a connection makes room for another one
+        drop(first);
+        let _third = admission.admit(PEER_A).unwrap();
+    }
+
+    #[test]
+    fn test_max_rate() {
+        let admission = Admission::new(AdmissionConfig::new(None, Some(2)));
+        let now = Instant::now();
+        assert!(admission.admit_at(PEER_A, now).is_ok());
+        assert!(admission.admit_at(PEER_A, now).is_ok());
+        assert_eq!(
+            admission.admit_at(PEER_A, now).unwrap_err(),
+            Rejection::TooFast
+        );
+        // closing connections doesn't reset the window
+        admission.release_at(PEER_A, now);
+        assert_eq!(
+            admission.admit_at(PEER_A, now).unwrap_err(),
+            Rejection::TooFast
+        );
+        // but the next window does
+        assert!(admission.admit_at(PEER_A, now + RATE_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_forget_peers() {
+        let admission = Admission::new(AdmissionConfig::new(Some(8), Some(8)));
+        let now = Instant::now();
+        admission.admit_at(PEER_A, now).unwrap();
+        // the peer is remembered till its rate window is over
+        admission.release_at(PEER_A, now);
+        assert_eq!(admission.tracked_peers(), 1);
+        admission.admit_at(PEER_A, now).unwrap();
+        admission.release_at(PEER_A, now + RATE_WINDOW);
+        assert_eq!(admission.tracked_peers(), 0);
+    }
+
+    #[test]
+    fn test_no_limits() {
+        let admission = Arc::new(Admission::new(AdmissionConfig::default()));
+        let guards: Vec<_> = (0..16).map(|_| admission.admit(PEER_A).unwrap()).collect();
+        assert_eq!(guards.len(), 16);
+        assert_eq!(admission.tracked_peers(), 0);
+    }
+
+    #[test]
+    fn test_set_config() {
+        let admission = Arc::new(Admission::new(AdmissionConfig::default()));
+        let _untracked = admission.admit(PEER_A).unwrap();
+        admission.set_config(AdmissionConfig::new(Some(1), None));
+        // connections that were admitted without limits don't count
+        let _tracked = admission.admit(PEER_A).unwrap();
+        assert_eq!(
+            admission.admit(PEER_A).unwrap_err(),
+            Rejection::TooManyConnections
+        );
+        // lifting the limits lets everyone in again
+        admission.set_config(AdmissionConfig::default());
+        let _another = admission.admit(PEER_A).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Client registry
+//!
+//! Every Skyhash connection (including the ones tunnelled over WebSocket by the HTTP gateway)
+//! is registered here for as long as it's open, along with what we know about it: the address
+//! of the client, the user that it's logged in as, the entity that it's using and the last
+//! action that it ran. The registry is what `SYS CLIENT LIST` reads, and `SYS CLIENT KILL`
+//! closes a connection through it
+
+use {
+    crate::corestore::memstore::ObjectID,
+    chrono::Utc,
+    core::sync::atomic::{AtomicU64, Ordering},
+    parking_lot::{const_mutex, Mutex},
+    std::{collections::BTreeMap, net::SocketAddr, sync::Arc, time::Instant},
+    tokio::sync::Notify,
+};
+
+/// The ID of the next client (IDs are never reused)
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CLIENTS: Mutex<BTreeMap<u64, Arc<Shared>>> = const_mutex(BTreeMap::new());
+
+#[derive(Debug)]
+/// What the connection updates as it runs queries
+struct Details {
+    user: Option<Vec<u8>>,
+    keyspace: Option<ObjectID>,
+    table: Option<ObjectID>,
+    last_action: Option<&'static [u8]>,
+    last_active: Instant,
+}
+
+#[derive(Debug)]
+/// The part of a client that's shared with the registry
+struct Shared {
+    peer: Option<SocketAddr>,
+    /// when the client connected, as a UNIX timestamp
+    connected: u64,
+    details: Mutex<Details>,
+    /// notified when the connection has to be closed
+    kill: Notify,
+}
+
+#[derive(Debug)]
+/// A registered client. The client stays in the registry till this is dropped
+pub struct Client {
+    id: u64,
+    shared: Arc<Shared>,
+}
+
+impl Client {
+    /// Register a new client
+    pub fn register(peer: Option<SocketAddr>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let shared = Arc::new(Shared {
+            peer,
+            connected: Utc::now().timestamp() as u64,
+            details: Mutex::new(Details {
+                user: None,
+                keyspace: None,
+                table: None,
+                last_action: None,
+                last_active: Instant::now(),
+            }),
+            kill: Notify::new(),
+        });
+        CLIENTS.lock().insert(id, shared.clone());
+        Self { id, shared }
+    }
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    /// Update what the client is doing, after it ran a query
+    pub fn touch(
+        &self,
+        user: Option<&[u8]>,
+        (keyspace, table): (Option<&ObjectID>, Option<&ObjectID>),
+        last_action: Option<&'static [u8]>,
+    ) {
+        let mut details = self.shared.details.lock();
+        if details.user.as_deref() != user {
+            details.user = user.map(<[u8]>::to_vec);
+        }
+        if details.keyspace.as_ref() != keyspace {
+            details.keyspace = keyspace.cloned();
+        }
+        if details.table.as_ref() != table {
+            details.table = table.cloned();
+        }
+        if last_action.is_some() {
+            details.last_action = last_action;
+        }
+        details.last_active = Instant::now();
+    }
+    /// Wait till the client is killed (see [`kill`])
+    pub async fn killed(&self) {
+        self.shared.kill.notified().await
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        CLIENTS.lock().remove(&self.id);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A description of a client
+pub struct ClientInfo {
+    pub id: u64,
+    /// the address of the client (if it's known)
+    pub peer: Option<SocketAddr>,
+    /// the user that the client is logged in as (if any)
+    pub user: Option<Vec<u8>>,
+    /// the entity that the client is using, as `keyspace` or `keyspace.table`
+    pub entity: Vec<u8>,
+    /// the last action that the client ran (if it ran any)
+    pub last_action: Option<&'static [u8]>,
+    /// when the client connected, as a UNIX timestamp
+    pub connected: u64,
+    /// the number of seconds since the client last ran a query (or connected)
+    pub idle: u64,
+}
+
+/// Returns the descriptions of all the clients, ordered by their IDs
+pub fn list() -> Vec<ClientInfo> {
+    let clients: Vec<(u64, Arc<Shared>)> = CLIENTS
+        .lock()
+        .iter()
+        .map(|(id, shared)| (*id, shared.clone()))
+        .collect();
+    clients
+        .into_iter()
+        .map(|(id, shared)| {
+            let details = shared.details.lock();
+            let mut entity = Vec::new();
+            if let Some(keyspace) = &details.keyspace {
+                entity.extend_from_slice(keyspace);
+                if let Some(table) = &details.table {
+                    entity.push(b'.');
+                    entity.extend_from_slice(table);
+                }
+            }
+            ClientInfo {
+                id,
+                peer: shared.peer,
+                user: details.user.clone(),
+                entity,
+                last_action: details.last_action,
+                connected: shared.connected,
+                idle: details.last_active.elapsed().as_secs(),
+            }
+        })
+        .collect()
+}
+
+/// Close the connection of the given client (as soon as it's done with the query that it's
+/// running, if any). Returns false if there's no such client
+pub fn kill(id: u64) -> bool {
+    match CLIENTS.lock().get(&id) {
+        Some(shared) => {
+            // this is remembered if the connection isn't waiting right now
+            shared.kill.notify_one();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{kill, list, Client},
+        crate::corestore::memstore::ObjectID,
+    };
+
+    #[test]
+    fn register_and_list() {
+        let client = Client::register(Some("127.0.0.1:2003".parse().unwrap()));
+        let keyspace = ObjectID::try_from_slice(b"default").unwrap();
+        client.touch(Some(b"root"), (Some(&keyspace), None), Some(b"HEYA"));
+        let info = list()
+            .into_iter()
+            .find(|info| info.id == client.id())
+            .unwrap();
+        assert_eq!(info.user.as_deref(), Some(&b"root"[..]));
+        assert_eq!(info.entity, b"default");
+        assert_eq!(info.last_action, Some(&b"HEYA"[..]));
+        // a query that isn't an action leaves the last action alone
+        client.touch(None, (Some(&keyspace), Some(&keyspace)), None);
+        let info = list()
+            .into_iter()
+            .find(|info| info.id == client.id())
+            .unwrap();
+        assert_eq!(info.user, None);
+        assert_eq!(info.entity, b"default.default");
+        assert_eq!(info.last_action, Some(&b"HEYA"[..]));
+        let id = client.id();
+        drop(client);
+        assert!(list().iter().all(|info| info.id != id));
+        assert!(!kill(id));
+    }
+
+    #[tokio::test]
+    async fn kill_client() {
+        let client = Client::register(None);
+        // the kill is remembered till the connection waits for it
+        assert!(kill(client.id()));
+        client.killed().await;
+    }
+}
@@ -25,22 +25,85 @@
 */
 
 use {
-    super::{BufferedSocketStream, QueryResult},
+    super::{
+        monitor::{Event, Filter, Monitor},
+        pubsub::{Message, PubSub, Subscriber},
+        BufferedSocketStream, QueryResult,
+    },
     crate::{
-        corestore::buffers::Integer64,
-        protocol::{interface::ProtocolSpec, ParseError},
+        cdc::{Consumer, Delivery},
+        config::LimitsConfig,
+        corestore::{buffers::Integer64, SharedSlice},
+        kvengine::{snapshot::Snapshot, txn::TxnOp},
+        protocol::{interface::ProtocolSpec, ParseError, QueryLimits},
         IoResult,
     },
     bytes::BytesMut,
     std::{
-        io::{Error as IoError, ErrorKind},
+        borrow::Cow,
+        io::{Error as IoError, ErrorKind, IoSlice},
         marker::PhantomData,
+        net::SocketAddr,
+        sync::Arc,
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+        time::{self, Duration, Instant},
     },
-    tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter},
 };
 
 const BUF_WRITE_CAP: usize = 8192;
-const BUF_READ_CAP: usize = 8192;
+/// Bodies at least this large are written straight from where they're kept instead of being
+/// copied into the write buffer (see [`Connection::write_length_prefixed`])
+const ZERO_COPY_THRESHOLD: usize = BUF_WRITE_CAP;
+pub(super) const BUF_READ_CAP: usize = 8192;
+/// Responses are held back for at most this long while the rest of a query is on its way (see
+/// [`Connection::hold_deadline`])
+const COALESCE_DELAY: Duration = Duration::from_micros(200);
+
+/// Wait until a connection has been idle for `timeout` seconds (forever, if there's no timeout)
+pub(super) async fn idle_timeout(timeout: Option<u64>) {
+    match timeout {
+        Some(secs) => time::sleep(Duration::from_secs(secs)).await,
+        None => core::future::pending().await,
+    }
+}
+
+/// Wait until the given deadline (forever, if there's none)
+async fn hold_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => core::future::pending().await,
+    }
+}
+
+/// Wait for the next pub/sub message (forever, if the connection isn't subscribed to anything)
+async fn next_message(subscriber: &mut Option<Subscriber>) -> Option<Message> {
+    match subscriber {
+        Some(subscriber) => subscriber.recv().await,
+        None => core::future::pending().await,
+    }
+}
+
+/// Wait for the next query fed to the monitor (forever, if the connection isn't monitoring)
+async fn next_event(monitor: &mut Option<Monitor>) -> Arc<Event> {
+    match monitor {
+        Some(monitor) => monitor.recv().await,
+        None => core::future::pending().await,
+    }
+}
+
+/// Wait for the next change to stream (forever, if the connection isn't streaming them, or
+/// once capture has stopped)
+async fn next_change(consumer: &mut Option<Consumer>) -> Delivery {
+    match consumer {
+        Some(consumer) => match consumer.recv().await {
+            Some(delivery) => delivery,
+            None => core::future::pending().await,
+        },
+        None => core::future::pending().await,
+    }
+}
 
 /// A generic connection type
 ///
@@ -50,25 +113,349 @@ const BUF_READ_CAP: usize = 8192;
 pub struct Connection<T, P> {
     pub(super) stream: BufWriter<T>,
     pub(super) buffer: BytesMut,
+    /// the operations queued in the current transaction (if any)
+    txn: Option<Vec<TxnOp>>,
+    /// the pub/sub subscriptions of this connection (if any)
+    subscriber: Option<Subscriber>,
+    /// this connection's end of the monitor feed (if it's monitoring)
+    monitor: Option<Monitor>,
+    /// the consumer of the changes that this connection streams (if it streams them)
+    cdc: Option<Consumer>,
+    /// the snapshot that this connection reads from (if any)
+    snapshot: Option<Snapshot>,
+    /// if set, binary frames are sent as string frames (see [`Connection::set_strict_utf8`])
+    strict_utf8: bool,
+    /// the limits that the server was configured with
+    limits: LimitsConfig,
+    /// the query limits of this connection (see [`Connection::set_query_limits`])
+    query_limits: QueryLimits,
+    /// the address of the client (if it's known)
+    peer: Option<SocketAddr>,
+    /// the ID of this connection in the client registry (if it's registered)
+    client_id: Option<u64>,
+    /// the action that the last query ran (till the client registry is told about it)
+    last_action: Option<&'static [u8]>,
+    /// the ID of the request that's running (if it has one)
+    request_id: Option<u64>,
+    /// if set, every response is preceded by a frame with the ID of its request
+    request_id_frames: bool,
+    /// if set, the connection runs writes that were already accepted (see
+    /// [`Connection::set_internal`])
+    internal: bool,
+    /// when the buffered responses started being held back (see [`Connection::hold_deadline`])
+    held_since: Option<Instant>,
     _marker: PhantomData<P>,
 }
 
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
-    pub fn new(stream: T) -> Self {
+    /// Create a connection. The buffer holds any data that has already been read from the
+    /// stream (during the handshake, for example)
+    pub fn new(stream: T, buffer: BytesMut, limits: LimitsConfig) -> Self {
         Connection {
             stream: BufWriter::with_capacity(BUF_WRITE_CAP, stream),
-            buffer: BytesMut::with_capacity(BUF_READ_CAP),
+            buffer,
+            txn: None,
+            subscriber: None,
+            monitor: None,
+            cdc: None,
+            snapshot: None,
+            strict_utf8: false,
+            limits,
+            query_limits: limits.query,
+            peer: None,
+            client_id: None,
+            last_action: None,
+            request_id: None,
+            request_id_frames: false,
+            internal: false,
+            held_since: None,
             _marker: PhantomData,
         }
     }
 }
 
+// client address
+impl<T, P> Connection<T, P> {
+    /// Set the address of the client
+    pub fn set_peer(&mut self, peer: Option<SocketAddr>) {
+        self.peer = peer;
+    }
+    /// Returns the address of the client (if it's known)
+    pub fn peer(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+}
+
+// internal connections
+impl<T, P> Connection<T, P> {
+    /// Mark this connection as one that runs writes that were already accepted: the ones
+    /// replayed from the write-ahead log, or fed to a replica by its primary. They're never
+    /// turned away for being writes (on a read-only server or replica)
+    pub fn set_internal(&mut self) {
+        self.internal = true;
+    }
+    /// Returns true if this connection runs writes that were already accepted
+    pub fn is_internal(&self) -> bool {
+        self.internal
+    }
+}
+
+// client registry
+impl<T, P> Connection<T, P> {
+    /// Set the ID of this connection in the client registry
+    pub fn set_client_id(&mut self, id: u64) {
+        self.client_id = Some(id);
+    }
+    /// Returns the ID of this connection in the client registry (if it's registered)
+    pub fn client_id(&self) -> Option<u64> {
+        self.client_id
+    }
+    /// Remember the action that the current query runs
+    pub fn set_last_action(&mut self, action: &'static [u8]) {
+        self.last_action = Some(action);
+    }
+    /// Returns the action that the last query ran, if we haven't been asked already
+    pub fn take_last_action(&mut self) -> Option<&'static [u8]> {
+        self.last_action.take()
+    }
+}
+
+// request IDs
+impl<T, P> Connection<T, P> {
+    /// Set the ID of the request that's about to run
+    pub fn set_request_id(&mut self, id: u64) {
+        self.request_id = Some(id);
+    }
+    /// Returns the ID of the request that's running (`None` for the queries run by the HTTP
+    /// gateway, which aren't requests of a connection)
+    pub fn request_id(&self) -> Option<u64> {
+        self.request_id
+    }
+    /// Turn request ID frames on or off (see [`Connection::write_request_id_frame`])
+    pub fn set_request_id_frames(&mut self, on: bool) {
+        self.request_id_frames = on;
+    }
+    /// Returns true if responses are preceded by request ID frames
+    pub fn sends_request_id_frames(&self) -> bool {
+        self.request_id_frames
+    }
+}
+
+// transaction state
+impl<T, P> Connection<T, P> {
+    /// Returns true if a transaction has been started on this connection
+    pub fn in_transaction(&self) -> bool {
+        self.txn.is_some()
+    }
+    /// Start a transaction. Returns false if a transaction is already in progress
+    pub fn begin_transaction(&mut self) -> bool {
+        if self.txn.is_some() {
+            false
+        } else {
+            self.txn = Some(Vec::new());
+            true
+        }
+    }
+    /// Queue an operation in the current transaction. Returns false if no transaction
+    /// is in progress
+    pub fn queue_txn_op(&mut self, op: TxnOp) -> bool {
+        match self.txn {
+            Some(ref mut ops) => {
+                ops.push(op);
+                true
+            }
+            None => false,
+        }
+    }
+    /// End the current transaction, returning the queued operations
+    pub fn take_transaction(&mut self) -> Option<Vec<TxnOp>> {
+        self.txn.take()
+    }
+}
+
+// pub/sub state
+impl<T, P> Connection<T, P> {
+    /// Subscribe to a channel, returning the number of channels that this connection is
+    /// subscribed to
+    pub fn subscribe(&mut self, hub: &Arc<PubSub>, channel: SharedSlice) -> usize {
+        let subscriber = self
+            .subscriber
+            .get_or_insert_with(|| Subscriber::new(hub.clone()));
+        subscriber.subscribe(channel);
+        subscriber.channel_count()
+    }
+    /// Unsubscribe from a channel, returning the number of channels that this connection is
+    /// still subscribed to
+    pub fn unsubscribe(&mut self, channel: &[u8]) -> usize {
+        match self.subscriber {
+            Some(ref mut subscriber) => {
+                subscriber.unsubscribe(channel);
+                subscriber.channel_count()
+            }
+            None => 0,
+        }
+    }
+    /// Unsubscribe from all channels
+    pub fn unsubscribe_all(&mut self) {
+        // dropping the subscriber removes all its subscriptions
+        self.subscriber = None;
+    }
+}
+
+// monitor state
+impl<T, P> Connection<T, P> {
+    /// Start monitoring the queries run on the server, replacing the current filter (if any)
+    pub fn start_monitor(&mut self, filter: Option<Filter>) {
+        self.monitor = Some(Monitor::start(filter));
+    }
+    /// Stop monitoring. Returns false if this connection wasn't monitoring
+    pub fn stop_monitor(&mut self) -> bool {
+        self.monitor.take().is_some()
+    }
+}
+
+// change data capture state
+impl<T, P> Connection<T, P> {
+    /// Start streaming the changes with the given consumer, in place of the current one (if
+    /// any)
+    pub fn start_cdc(&mut self, consumer: Consumer) {
+        self.cdc = Some(consumer);
+    }
+    /// Stop streaming the changes. Returns false if this connection wasn't streaming them
+    pub fn stop_cdc(&mut self) -> bool {
+        self.cdc.take().is_some()
+    }
+}
+
+// snapshot state
+impl<T, P> Connection<T, P> {
+    /// Start reading from the given snapshot, replacing the current one (if any)
+    pub fn begin_snapshot(&mut self, snapshot: Snapshot) {
+        self.snapshot = Some(snapshot);
+    }
+    /// Returns the snapshot that this connection reads from (if any)
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+    /// Drop the current snapshot. Returns false if there was no snapshot
+    pub fn end_snapshot(&mut self) -> bool {
+        self.snapshot.take().is_some()
+    }
+}
+
+// strict UTF-8 mode
+impl<T, P: ProtocolSpec> Connection<T, P> {
+    /// Turn strict UTF-8 mode on or off. In strict mode, queries must be valid UTF-8 and
+    /// all binary responses are sent as strings (with any invalid sequences replaced)
+    pub fn set_strict_utf8(&mut self, strict: bool) {
+        self.strict_utf8 = strict;
+    }
+    /// Returns true if this connection is in strict UTF-8 mode
+    pub fn is_strict_utf8(&self) -> bool {
+        self.strict_utf8
+    }
+    /// Returns the tsymbol to use for a response
+    fn response_tsymbol(&self, tsymbol: u8) -> u8 {
+        if self.strict_utf8 && tsymbol == P::TSYMBOL_BINARY {
+            P::TSYMBOL_STRING
+        } else {
+            tsymbol
+        }
+    }
+    /// Returns the body to use for a (possibly binary) response element
+    fn response_body<'b>(&self, data: &'b [u8]) -> Cow<'b, [u8]> {
+        if !self.strict_utf8 {
+            return Cow::Borrowed(data);
+        }
+        match String::from_utf8_lossy(data) {
+            Cow::Borrowed(string) => Cow::Borrowed(string.as_bytes()),
+            Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+        }
+    }
+}
+
+// query limits
+impl<T, P> Connection<T, P> {
+    /// Returns the query limits of this connection
+    pub fn query_limits(&self) -> QueryLimits {
+        self.query_limits
+    }
+    /// Set the query limits of this connection. A connection can only tighten the limits that
+    /// the server was configured with, so this returns false (and changes nothing) if any of
+    /// the limits is zero or larger than the configured one
+    pub fn set_query_limits(&mut self, limits: QueryLimits) -> bool {
+        let configured = self.limits.query;
+        let okay = (1..=configured.max_size).contains(&limits.max_size)
+            && (1..=configured.max_args).contains(&limits.max_args);
+        if okay {
+            self.query_limits = limits;
+        }
+        okay
+    }
+}
+
 // protocol read
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Attempt to read a query
+    ///
+    /// Clients can send several queries without waiting for the responses, so we'll first see
+    /// if a complete query is already buffered. Responses are only flushed once we have to
+    /// wait for more data, so that the responses to a batch of queries are sent together (and
+    /// a burst of queries that comes in over several reads is answered in as few writes as
+    /// possible; see [`Self::hold_deadline`])
     pub(super) async fn read_query(&mut self) -> IoResult<QueryResult> {
         loop {
-            match self.stream.read_buf(&mut self.buffer).await {
+            if !self.buffer.is_empty() {
+                match P::decode_packet(self.buffer.as_ref(), self.query_limits) {
+                    Ok(query_with_advance) => return Ok(QueryResult::Q(query_with_advance)),
+                    Err(ParseError::NotEnough) => {}
+                    Err(e) => {
+                        // we can't tell where the next query starts, so drop everything
+                        self.buffer.clear();
+                        let close =
+                            e == ParseError::QueryTooLarge && self.limits.close_on_violation;
+                        self.write_error(P::SKYHASH_PARSE_ERROR_LUT[e as usize - 1])
+                            .await?;
+                        if close {
+                            // the rest of the query is probably still on its way, so don't
+                            // bother with it
+                            self.stream.flush().await?;
+                            return Ok(QueryResult::Close);
+                        }
+                        return Ok(QueryResult::NextLoop);
+                    }
+                }
+            }
+            // we need more data, so send out whatever we have before we wait (unless the rest
+            // of a query is on its way)
+            let hold = self.hold_deadline();
+            if hold.is_none() {
+                self.stream.flush().await?;
+            }
+            // subscribers, monitors and CDC consumers are exempt from the idle timeout since they
+            // usually just wait for messages
+            let timeout =
+                if self.subscriber.is_some() || self.monitor.is_some() || self.cdc.is_some() {
+                    None
+                } else {
+                    self.limits.idle_timeout
+                };
+            let read = tokio::select! {
+                read = self.stream.read_buf(&mut self.buffer) => read,
+                Some(message) = self::next_message(&mut self.subscriber) => {
+                    return Ok(QueryResult::Push(message));
+                }
+                event = self::next_event(&mut self.monitor) => {
+                    return Ok(QueryResult::Monitor(event));
+                }
+                delivery = self::next_change(&mut self.cdc) => {
+                    return Ok(QueryResult::Cdc(delivery));
+                }
+                // the rest didn't come in time, so the responses are flushed on the next pass
+                _ = self::hold_until(hold) => continue,
+                _ = self::idle_timeout(timeout) => return Ok(QueryResult::Close),
+            };
+            match read {
                 Ok(0) => {
                     if self.buffer.is_empty() {
                         // buffer is empty, and the remote pulled off (simple disconnection)
@@ -81,16 +468,32 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
                 Ok(_) => {}
                 Err(e) => return Err(e),
             }
-            // see if we have buffered enough data to run anything
-            match P::decode_packet(self.buffer.as_ref()) {
-                Ok(query_with_advance) => return Ok(QueryResult::Q(query_with_advance)),
-                Err(ParseError::NotEnough) => {}
-                Err(e) => {
-                    self.write_error(P::SKYHASH_PARSE_ERROR_LUT[e as usize - 1])
-                        .await?;
-                    return Ok(QueryResult::NextLoop);
-                }
-            }
+        }
+    }
+}
+
+// write coalescing
+impl<T: BufferedSocketStream, P> Connection<T, P> {
+    /// Returns the time till which the buffered responses can be held back, or `None` if they
+    /// have to be sent out now
+    ///
+    /// A partial query in the read buffer means that the client is in the middle of sending
+    /// queries (a pipelined burst, most likely) and that the rest is right behind. In that case
+    /// the responses so far are held back for up to [`COALESCE_DELAY`] to go out with the ones
+    /// that follow, so that a burst that comes in over several reads isn't answered with a
+    /// write for every read. The delay counts from when the responses started being held back,
+    /// so it bounds the latency that this adds
+    fn hold_deadline(&mut self) -> Option<Instant> {
+        if self.buffer.is_empty() || self.stream.buffer().is_empty() {
+            self.held_since = None;
+            return None;
+        }
+        let deadline = *self.held_since.get_or_insert_with(Instant::now) + COALESCE_DELAY;
+        if Instant::now() < deadline {
+            Some(deadline)
+        } else {
+            self.held_since = None;
+            None
         }
     }
 }
@@ -111,19 +514,139 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
         // write the LF
         self.stream.write_u8(P::LF).await
     }
+
+    /// Write a push frame for a pub/sub message and flush it
+    pub(super) async fn write_push_frame(&mut self, message: Message) -> IoResult<()> {
+        self.stream.write_all(P::PUSH_FRAME_HEADER).await?;
+        self.write_flat_array_header(3).await?;
+        self.write_string("message").await?;
+        self.write_binary(&message.channel).await?;
+        self.write_binary(&message.payload).await?;
+        self.stream.flush().await
+    }
+
+    /// Write a push frame for a query fed to the monitor and flush it. The frame is a flat
+    /// array of `monitor`, the timestamp, the client ID (zero if it isn't registered), the
+    /// request ID (zero if it doesn't have one), the address of the client (empty if it isn't
+    /// known), the entity and then the arguments
+    pub(super) async fn write_monitor_frame(&mut self, event: &Event) -> IoResult<()> {
+        self.stream.write_all(P::PUSH_FRAME_HEADER).await?;
+        self.write_flat_array_header(6 + event.args.len()).await?;
+        self.write_string("monitor").await?;
+        self.write_int64(event.timestamp).await?;
+        self.write_int64(event.client.unwrap_or(0)).await?;
+        self.write_int64(event.request.unwrap_or(0)).await?;
+        let peer = event.peer.map(|peer| peer.to_string()).unwrap_or_default();
+        self.write_string(&peer).await?;
+        self.write_binary(&event.entity()).await?;
+        for arg in event.args.iter() {
+            self.write_binary(arg).await?;
+        }
+        self.stream.flush().await
+    }
+
+    /// Write a push frame for a captured change and flush it. The frame is a flat array of
+    /// `cdc`, the offset, the timestamp, the entity, the op, the key and then the values. For
+    /// changes that were lost, it's `cdc-lost`, the first offset that was lost and the next
+    /// offset that wasn't
+    pub(super) async fn write_cdc_frame(&mut self, delivery: &Delivery) -> IoResult<()> {
+        self.stream.write_all(P::PUSH_FRAME_HEADER).await?;
+        match delivery {
+            Delivery::Change(change) => {
+                self.write_flat_array_header(6 + change.values.len())
+                    .await?;
+                self.write_string("cdc").await?;
+                self.write_int64(change.offset).await?;
+                self.write_int64(change.timestamp).await?;
+                self.write_binary(&change.entity).await?;
+                self.write_binary(&change.op).await?;
+                self.write_binary(&change.key).await?;
+                for value in change.values.iter() {
+                    self.write_binary(value).await?;
+                }
+            }
+            Delivery::Lost { first, next } => {
+                self.write_flat_array_header(3).await?;
+                self.write_string("cdc-lost").await?;
+                self.write_int64(*first).await?;
+                self.write_int64(*next).await?;
+            }
+        }
+        self.stream.flush().await
+    }
+}
+
+// protocol write (request IDs)
+impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
+    /// Write a push frame with the ID of the running request (a flat array of `request` and
+    /// the ID), so that clients can tell which request the response that follows belongs to
+    pub(super) async fn write_request_id_frame(&mut self) -> IoResult<()> {
+        self.stream.write_all(P::PUSH_FRAME_HEADER).await?;
+        self.write_flat_array_header(2).await?;
+        self.write_string("request").await?;
+        self.write_int64(self.request_id.unwrap_or(0)).await
+    }
 }
 
 // protocol write (helpers)
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Write an error to the stream (just used to differentiate between "normal" and "errored" writes)
     pub(super) async fn write_error(&mut self, error: &[u8]) -> IoResult<()> {
-        self.stream.write_all(error).await?;
+        self.stream.write_all(error).await
+    }
+    /// Flush the responses written so far. This is only needed before waiting on something
+    /// other than the client, since responses are otherwise flushed before reading the next
+    /// query
+    pub async fn flush(&mut self) -> IoResult<()> {
         self.stream.flush().await
     }
     /// Write something "raw" to the stream (intentional underscore to avoid misuse)
     pub async fn _write_raw(&mut self, raw: &[u8]) -> IoResult<()> {
         self.stream.write_all(raw).await
     }
+    /// Write `head`, the length of `body`, an LF and `body` (followed by an LF if the protocol
+    /// needs one). A large body on a socket that supports vectored writes isn't copied into the
+    /// write buffer; instead the whole element goes out in a single vectored write
+    async fn write_length_prefixed(&mut self, head: &[u8], body: &[u8]) -> IoResult<()> {
+        let len = Integer64::from(body.len());
+        if body.len() >= ZERO_COPY_THRESHOLD && self.stream.get_ref().is_write_vectored() {
+            let lf = [P::LF];
+            let tail: &[u8] = if P::NEEDS_TERMINAL_LF { &lf } else { &[] };
+            let mut parts = [
+                IoSlice::new(head),
+                IoSlice::new(&len),
+                IoSlice::new(&lf),
+                IoSlice::new(body),
+                IoSlice::new(tail),
+            ];
+            return self.write_unbuffered(&mut parts).await;
+        }
+        self.stream.write_all(head).await?;
+        self.stream.write_all(&len).await?;
+        self.stream.write_u8(P::LF).await?;
+        self.stream.write_all(body).await?;
+        if P::NEEDS_TERMINAL_LF {
+            self.stream.write_u8(P::LF).await
+        } else {
+            Ok(())
+        }
+    }
+    /// Write `parts` to the socket with vectored writes, bypassing the write buffer. Anything
+    /// that's buffered is flushed first so that the responses stay in order
+    async fn write_unbuffered(&mut self, mut parts: &mut [IoSlice<'_>]) -> IoResult<()> {
+        if !self.stream.buffer().is_empty() {
+            self.stream.flush().await?;
+        }
+        let stream = self.stream.get_mut();
+        while !parts.is_empty() {
+            let written = stream.write_vectored(parts).await?;
+            if written == 0 {
+                return Err(ErrorKind::WriteZero.into());
+            }
+            IoSlice::advance_slices(&mut parts, written);
+        }
+        Ok(())
+    }
 }
 
 // protocol write (dataframe)
@@ -135,19 +658,13 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
         data: &[u8],
         tsymbol: u8,
     ) -> IoResult<()> {
-        // first write the tsymbol
-        self.stream.write_u8(tsymbol).await?;
-        // now write length
-        self.stream.write_all(&Integer64::from(data.len())).await?;
-        // now write LF
-        self.stream.write_u8(P::LF).await?;
-        // now write the actual body
-        self.stream.write_all(data).await?;
-        if P::NEEDS_TERMINAL_LF {
-            self.stream.write_u8(P::LF).await
+        let tsymbol = self.response_tsymbol(tsymbol);
+        let data = if tsymbol == P::TSYMBOL_STRING {
+            self.response_body(data)
         } else {
-            Ok(())
-        }
+            Cow::Borrowed(data)
+        };
+        self.write_length_prefixed(&[tsymbol], &data).await
     }
     /// Encode and write a mon element (**without** length-prefixing)
     pub async fn write_mono_with_tsymbol(&mut self, data: &[u8], tsymbol: u8) -> IoResult<()> {
@@ -163,7 +680,6 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
             .await
     }
     /// Encode and write a blob
-    #[allow(unused)]
     pub async fn write_binary(&mut self, binary: &[u8]) -> IoResult<()> {
         self.write_mono_length_prefixed_with_tsymbol(binary, P::TSYMBOL_BINARY)
             .await
@@ -184,9 +700,24 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
             .await
     }
 
+    // array
+    /// Write an array header (the elements can be of any type, including arrays)
+    pub async fn write_array_header(&mut self, len: usize) -> IoResult<()> {
+        self.stream.write_u8(P::TSYMBOL_ARRAY).await?;
+        self.stream.write_all(&Integer64::from(len)).await?;
+        self.stream.write_u8(P::LF).await
+    }
+    /// Write a flat array header (the elements can be of any non-array type)
+    pub async fn write_flat_array_header(&mut self, len: usize) -> IoResult<()> {
+        self.stream.write_u8(P::TSYMBOL_FLAT_ARRAY).await?;
+        self.stream.write_all(&Integer64::from(len)).await?;
+        self.stream.write_u8(P::LF).await
+    }
+
     // typed array
     /// Write a typed array header (including type information and size)
     pub async fn write_typed_array_header(&mut self, len: usize, tsymbol: u8) -> IoResult<()> {
+        let tsymbol = self.response_tsymbol(tsymbol);
         self.stream
             .write_all(&[P::TSYMBOL_TYPED_ARRAY, tsymbol])
             .await?;
@@ -201,16 +732,8 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     }
     /// Encode and write a typed array element
     pub async fn write_typed_array_element(&mut self, element: &[u8]) -> IoResult<()> {
-        self.stream
-            .write_all(&Integer64::from(element.len()))
-            .await?;
-        self.stream.write_u8(P::LF).await?;
-        self.stream.write_all(element).await?;
-        if P::NEEDS_TERMINAL_LF {
-            self.stream.write_u8(P::LF).await
-        } else {
-            Ok(())
-        }
+        let element = self.response_body(element);
+        self.write_length_prefixed(&[], &element).await
     }
 
     // typed non-null array
@@ -220,6 +743,7 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
         len: usize,
         tsymbol: u8,
     ) -> IoResult<()> {
+        let tsymbol = self.response_tsymbol(tsymbol);
         self.stream
             .write_all(&[P::TSYMBOL_TYPED_NON_NULL_ARRAY, tsymbol])
             .await?;
@@ -0,0 +1,282 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Protocol negotiation
+//!
+//! A client can start a connection with a handshake (`H<version>\n`, for example `H1.0\n`) to
+//! pick the protocol that it wants to speak. The server acknowledges the handshake by echoing it
+//! back, or rejects it with an error (in the configured protocol) and closes the connection if
+//! it doesn't speak that version.
+//!
+//! Older clients don't send a handshake and simply start querying. Since the first frame of a
+//! Skyhash 1.0 query (`*<count>\n~`) can be told apart from a Skyhash 2.0 one (`*<count>\n` is
+//! followed by the length of the first element and pipelines start with `$`), such connections
+//! are served with whatever protocol the client is speaking instead of failing with a packet
+//! error. Anything else is left to the protocol that the server was configured with.
+
+use {
+    super::{
+        admission::AdmissionGuard, connection, listener::BaseListener, BufferedSocketStream,
+        Connection, ConnectionHandler,
+    },
+    crate::{
+        auth::AuthProvider,
+        config::LimitsConfig,
+        corestore::Corestore,
+        protocol::{interface::ProtocolSpec, responses, Skyhash1, Skyhash2},
+        IoResult,
+    },
+    bytes::{Buf, BytesMut},
+    std::{net::SocketAddr, sync::Arc},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        sync::{broadcast, mpsc, Semaphore},
+    },
+};
+
+/// The first byte of a handshake
+const HANDSHAKE_FIRST_BYTE: u8 = b'H';
+/// The longest first line that we'll wait for before making a decision (the handshake and the
+/// first line of a query are both tiny)
+const MAX_FIRST_LINE: usize = 32;
+const ERR_UNSUPPORTED_PROTOCOL: &str =
+    "unsupported-protocol: this server speaks Skyhash-1.0 and Skyhash-2.0";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The protocol picked for a connection
+pub(super) enum Negotiation {
+    /// Can't tell yet
+    NotEnough,
+    /// Use the configured protocol
+    Configured,
+    /// Use Skyhash 1.0. If the client sent a handshake, this is its length (the handshake has
+    /// to be acknowledged and removed from the buffer before the protocol is used)
+    Skyhash1(usize),
+    /// Use Skyhash 2.0 (see [`Negotiation::Skyhash1`] for the handshake length)
+    Skyhash2(usize),
+    /// The client asked for a protocol version that we don't speak
+    Unsupported,
+}
+
+/// Pick the protocol for a connection from the first bytes sent by the client
+pub(super) fn negotiate(buf: &[u8]) -> Negotiation {
+    let first_line = match buf.iter().position(|b| *b == b'\n') {
+        Some(lf) => lf,
+        None if buf.len() > MAX_FIRST_LINE => {
+            // this isn't a handshake or a query that we know of
+            return match buf[0] {
+                HANDSHAKE_FIRST_BYTE => Negotiation::Unsupported,
+                _ => Negotiation::Configured,
+            };
+        }
+        None => return Negotiation::NotEnough,
+    };
+    match buf[0] {
+        HANDSHAKE_FIRST_BYTE => {
+            let handshake_len = first_line + 1;
+            match &buf[1..first_line] {
+                b"1.0" => Negotiation::Skyhash1(handshake_len),
+                b"2.0" => Negotiation::Skyhash2(handshake_len),
+                _ => Negotiation::Unsupported,
+            }
+        }
+        b'*' => match buf.get(first_line + 1) {
+            Some(b'~') => Negotiation::Skyhash1(0),
+            Some(_) => Negotiation::Skyhash2(0),
+            None => Negotiation::NotEnough,
+        },
+        b'$' => Negotiation::Skyhash2(0),
+        _ => Negotiation::Configured,
+    }
+}
+
+/// Negotiate the protocol for a freshly accepted connection (from `peer`) and then run it (in
+/// a new task)
+pub(super) fn spawn<C, P>(
+    base: &BaseListener,
+    stream: C,
+    peer: SocketAddr,
+    admission: AdmissionGuard,
+) where
+    C: BufferedSocketStream + Send + 'static,
+    P: ProtocolSpec + 'static,
+{
+    self::spawn_as::<C, P>(base, base.auth.clone(), stream, peer, admission)
+}
+
+/// Same as [`spawn`], but the connection starts out with the given auth provider (which may
+/// have been logged in already; with a client certificate, for example)
+pub(super) fn spawn_as<C, P>(
+    base: &BaseListener,
+    auth: AuthProvider,
+    stream: C,
+    peer: SocketAddr,
+    admission: AdmissionGuard,
+) where
+    C: BufferedSocketStream + Send + 'static,
+    P: ProtocolSpec + 'static,
+{
+    self::spawn_with::<C, P>(
+        base.db.clone(),
+        auth,
+        base.climit.clone(),
+        base.signal.subscribe(),
+        base.terminate_tx.clone(),
+        base.limits(),
+        Some(peer),
+        Some(admission),
+        stream,
+    )
+}
+
+/// Same as [`spawn`], but for connections that weren't accepted by a Skyhash listener (like
+/// the ones tunnelled by the HTTP gateway). The connection's permit (and its admission, if
+/// any) is handed over to the task
+#[allow(clippy::too_many_arguments)]
+pub(super) fn spawn_with<C, P>(
+    db: Corestore,
+    auth: AuthProvider,
+    climit: Arc<Semaphore>,
+    mut signal: broadcast::Receiver<()>,
+    terminate_tx: mpsc::Sender<()>,
+    limits: LimitsConfig,
+    peer: Option<SocketAddr>,
+    admission: Option<AdmissionGuard>,
+    stream: C,
+) where
+    C: BufferedSocketStream + Send + 'static,
+    P: ProtocolSpec + 'static,
+{
+    tokio::spawn(async move {
+        // the connection counts against its IP till we're done with it
+        let _admission = admission;
+        let mut stream = stream;
+        let mut buffer = BytesMut::with_capacity(connection::BUF_READ_CAP);
+        let negotiation = tokio::select! {
+            negotiation = self::read_handshake::<C, P>(&mut stream, &mut buffer) => negotiation,
+            _ = signal.recv() => Ok(None),
+            _ = connection::idle_timeout(limits.idle_timeout) => Ok(None),
+        };
+        let negotiation = match negotiation {
+            Ok(Some(negotiation)) => negotiation,
+            Ok(None) => {
+                // the connection was never handed over to a handler, so we return the permit
+                climit.add_permits(1);
+                return;
+            }
+            Err(e) => {
+                climit.add_permits(1);
+                log::error!("Error: {}", e);
+                return;
+            }
+        };
+        macro_rules! run {
+            ($protocol:ty) => {{
+                let mut con = Connection::new(stream, buffer, limits);
+                con.set_peer(peer);
+                let mut chandle = ConnectionHandler::<C, $protocol>::new(
+                    db,
+                    con,
+                    auth,
+                    climit,
+                    signal,
+                    terminate_tx,
+                );
+                chandle.run().await
+            }};
+        }
+        let ret = match negotiation {
+            Negotiation::Skyhash1(_) => run!(Skyhash1),
+            Negotiation::Skyhash2(_) => run!(Skyhash2),
+            _ => run!(P),
+        };
+        if let Err(e) = ret {
+            log::error!("Error: {}", e);
+        }
+    });
+}
+
+/// Read (and acknowledge) the handshake, if any. Returns `None` if the connection should be
+/// closed
+async fn read_handshake<C, P>(
+    stream: &mut C,
+    buffer: &mut BytesMut,
+) -> IoResult<Option<Negotiation>>
+where
+    C: BufferedSocketStream,
+    P: ProtocolSpec,
+{
+    loop {
+        let negotiation = self::negotiate(buffer);
+        match negotiation {
+            Negotiation::NotEnough => {}
+            Negotiation::Skyhash1(len) | Negotiation::Skyhash2(len) if len != 0 => {
+                // acknowledge the handshake by echoing it back
+                stream.write_all(&buffer[..len]).await?;
+                stream.flush().await?;
+                buffer.advance(len);
+                return Ok(Some(negotiation));
+            }
+            Negotiation::Unsupported => {
+                let error = responses::structured_error::<P>(
+                    responses::ERRCODE_UNSUPPORTED_PROTOCOL,
+                    ERR_UNSUPPORTED_PROTOCOL,
+                );
+                stream.write_all(&error).await?;
+                stream.flush().await?;
+                return Ok(None);
+            }
+            _ => return Ok(Some(negotiation)),
+        }
+        if stream.read_buf(buffer).await? == 0 {
+            // the client disconnected before sending anything useful
+            return Ok(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{negotiate, Negotiation};
+
+    #[test]
+    fn test_negotiate_handshake() {
+        assert_eq!(negotiate(b"H1.0\n"), Negotiation::Skyhash1(5));
+        assert_eq!(negotiate(b"H2.0\n*1\n4\nheya"), Negotiation::Skyhash2(5));
+        assert_eq!(negotiate(b"H3.0\n"), Negotiation::Unsupported);
+        assert_eq!(negotiate(b"H2."), Negotiation::NotEnough);
+        assert_eq!(negotiate(&[b'H'; 64]), Negotiation::Unsupported);
+    }
+    #[test]
+    fn test_negotiate_without_handshake() {
+        assert_eq!(negotiate(b""), Negotiation::NotEnough);
+        assert_eq!(negotiate(b"*1\n"), Negotiation::NotEnough);
+        assert_eq!(negotiate(b"*1\n~1\n4\nheya\n"), Negotiation::Skyhash1(0));
+        assert_eq!(negotiate(b"*1\n4\nheya"), Negotiation::Skyhash2(0));
+        assert_eq!(negotiate(b"$2\n"), Negotiation::Skyhash2(0));
+        assert_eq!(negotiate(b"GET x\n"), Negotiation::Configured);
+    }
+}
@@ -0,0 +1,538 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # gRPC-Web
+//!
+//! The gateway serves the `Skytable` service (in `server/proto/skytable.proto`) to gRPC-Web
+//! clients, for unary calls with binary (`application/grpc-web+proto`) messages. Every RPC
+//! runs the action that it's named after with the arguments in the request (on the table in
+//! `entity`, if it's set) and `Query` runs any query, through the same dispatch as Skyhash
+//! queries. Errors returned by the action are sent in the response message (just like they're
+//! sent to Skyhash clients), so the gRPC status is only used for errors in the call itself:
+//! bad messages, bad credentials and unknown RPCs.
+//!
+//! Native gRPC needs HTTP/2, which the gateway doesn't speak, so clients need a gRPC-Web
+//! transport (most gRPC libraries have one)
+
+use {
+    super::{
+        Element, Method, RequestHead, Response, Status, ERR_BAD_CREDENTIALS, ERR_BAD_RESPONSE,
+        ERR_METHOD_NOT_ALLOWED,
+    },
+    crate::{auth::AuthProvider, corestore::Corestore, IoResult},
+    core::str,
+    std::net::SocketAddr,
+};
+
+/// The path prefix of the RPCs (`/<package>.<service>/`)
+pub(super) const SERVICE_PATH: &[u8] = b"/skytable.Skytable/";
+/// The content type that we accept
+const CONTENT_TYPE: &str = "application/grpc-web";
+/// The content type of our responses
+const CONTENT_TYPE_PROTO: &str = "application/grpc-web+proto";
+/// The RPC that runs any query (the first argument is the action)
+const RPC_QUERY: &[u8] = b"Query";
+/// The RPCs and the actions that they run. This has to mirror the service definition
+const RPCS: &[(&str, &str)] = &[
+    ("Get", "GET"),
+    ("Set", "SET"),
+    ("Update", "UPDATE"),
+    ("Cas", "CAS"),
+    ("GetSet", "GETSET"),
+    ("GetRange", "GETRANGE"),
+    ("JGet", "JGET"),
+    ("SetRange", "SETRANGE"),
+    ("Append", "APPEND"),
+    ("Del", "DEL"),
+    ("DelPrefix", "DELPREFIX"),
+    ("Exists", "EXISTS"),
+    ("USet", "USET"),
+    ("KeyLen", "KEYLEN"),
+    ("StrLen", "STRLEN"),
+    ("Type", "TYPE"),
+    ("Rename", "RENAME"),
+    ("Copy", "COPY"),
+    ("Move", "MOVE"),
+    ("Pop", "POP"),
+    ("GetDel", "GETDEL"),
+    ("Incr", "INCR"),
+    ("Decr", "DECR"),
+    ("IncrBy", "INCRBY"),
+    ("DecrBy", "DECRBY"),
+    ("SetBit", "SETBIT"),
+    ("GetBit", "GETBIT"),
+    ("BitCount", "BITCOUNT"),
+    ("MSet", "MSET"),
+    ("MSetEx", "MSETEX"),
+    ("MGet", "MGET"),
+    ("GetMany", "GETMANY"),
+    ("SetMany", "SETMANY"),
+    ("MUpdate", "MUPDATE"),
+    ("MPop", "MPOP"),
+    ("SSet", "SSET"),
+    ("SDel", "SDEL"),
+    ("SUpdate", "SUPDATE"),
+    ("DbSize", "DBSIZE"),
+    ("MemUsage", "MEMUSAGE"),
+    ("FlushDb", "FLUSHDB"),
+    ("FlushTable", "FLUSHTABLE"),
+    ("LsKeys", "LSKEYS"),
+    ("Scan", "SCAN"),
+    ("Keys", "KEYS"),
+    ("RandomKey", "RANDOMKEY"),
+    ("Sample", "SAMPLE"),
+    ("LSet", "LSET"),
+    ("LGet", "LGET"),
+    ("LMod", "LMOD"),
+    ("LPush", "LPUSH"),
+    ("RPush", "RPUSH"),
+    ("LPop", "LPOP"),
+    ("RPop", "RPOP"),
+    ("BLPop", "BLPOP"),
+    ("BRPop", "BRPOP"),
+    ("LRange", "LRANGE"),
+    ("SAdd", "SADD"),
+    ("SRem", "SREM"),
+    ("SMembers", "SMEMBERS"),
+    ("SUnion", "SUNION"),
+    ("SInter", "SINTER"),
+    ("SDiff", "SDIFF"),
+    ("ZAdd", "ZADD"),
+    ("ZRangeByScore", "ZRANGEBYSCORE"),
+    ("ZRank", "ZRANK"),
+    ("HSet", "HSET"),
+    ("HGet", "HGET"),
+    ("HDel", "HDEL"),
+    ("HGetAll", "HGETALL"),
+    ("BfReserve", "BFRESERVE"),
+    ("BfAdd", "BFADD"),
+    ("BfExists", "BFEXISTS"),
+    ("PfAdd", "PFADD"),
+    ("PfCount", "PFCOUNT"),
+    ("PfMerge", "PFMERGE"),
+    ("GeoAdd", "GEOADD"),
+    ("GeoSearch", "GEOSEARCH"),
+    ("TsAdd", "TS.ADD"),
+    ("TsRange", "TS.RANGE"),
+    ("TsLast", "TS.LAST"),
+    ("Expire", "EXPIRE"),
+    ("Ttl", "TTL"),
+    ("Persist", "PERSIST"),
+    ("Script", "SCRIPT"),
+    ("Eval", "EVAL"),
+    ("Publish", "PUBLISH"),
+    ("Notify", "NOTIFY"),
+    ("Watch", "WATCH"),
+    ("Heya", "HEYA"),
+    ("WhereAmI", "WHEREAMI"),
+    ("Sys", "SYS"),
+    ("MkSnap", "MKSNAP"),
+];
+
+// call errors
+const ERR_UNSUPPORTED_CONTENT_TYPE: &str = "unsupported-content-type";
+const ERR_UNKNOWN_RPC: &str = "unknown-rpc";
+const ERR_BAD_MESSAGE: &str = "bad-message";
+
+// frames
+/// The length of a frame header (a flags byte and a 32-bit big endian length)
+const FRAME_HEADER_LEN: usize = 5;
+const FRAME_FLAG_DATA: u8 = 0x00;
+const FRAME_FLAG_TRAILERS: u8 = 0x80;
+
+// gRPC status codes
+const GRPC_OK: u8 = 0;
+const GRPC_INVALID_ARGUMENT: u8 = 3;
+const GRPC_UNIMPLEMENTED: u8 = 12;
+const GRPC_INTERNAL: u8 = 13;
+const GRPC_UNAUTHENTICATED: u8 = 16;
+
+// protobuf wire types
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LEN: u64 = 2;
+const WIRE_FIXED32: u64 = 5;
+
+// `Request` fields
+const REQUEST_ENTITY: u64 = 1;
+const REQUEST_ARGS: u64 = 2;
+// `Response` fields
+const RESPONSE_BLOB: u64 = 1;
+const RESPONSE_INTEGER: u64 = 2;
+const RESPONSE_NUMBER: u64 = 3;
+const RESPONSE_CODE: u64 = 4;
+const RESPONSE_ERROR: u64 = 5;
+const RESPONSE_ARRAY: u64 = 6;
+// `Array` fields
+const ARRAY_ELEMENTS: u64 = 1;
+
+#[derive(Debug, PartialEq, Eq, Default)]
+/// A decoded `Request` message
+struct Request {
+    entity: Vec<u8>,
+    args: Vec<Vec<u8>>,
+}
+
+impl Request {
+    /// Decode a `Request` message. Returns `None` if the message is malformed
+    fn decode(message: &[u8]) -> Option<Self> {
+        let mut request = Self::default();
+        let mut cursor = 0;
+        while cursor < message.len() {
+            let key = self::read_varint(message, &mut cursor)?;
+            match (key >> 3, key & 7) {
+                (REQUEST_ENTITY, WIRE_LEN) => {
+                    request.entity = self::read_len_delimited(message, &mut cursor)?.to_vec()
+                }
+                (REQUEST_ARGS, WIRE_LEN) => request
+                    .args
+                    .push(self::read_len_delimited(message, &mut cursor)?.to_vec()),
+                // skip unknown fields
+                (_, WIRE_VARINT) => {
+                    self::read_varint(message, &mut cursor)?;
+                }
+                (_, WIRE_FIXED64) => cursor = cursor.checked_add(8)?,
+                (_, WIRE_LEN) => {
+                    self::read_len_delimited(message, &mut cursor)?;
+                }
+                (_, WIRE_FIXED32) => cursor = cursor.checked_add(4)?,
+                _ => return None,
+            }
+        }
+        if cursor == message.len() {
+            Some(request)
+        } else {
+            None
+        }
+    }
+    /// Get the query for this request. The action is `None` for the query RPC
+    fn into_query(self, action: Option<&str>) -> Vec<Vec<u8>> {
+        let mut query = Vec::with_capacity(self.args.len() + 2);
+        if !self.entity.is_empty() {
+            let mut entity = Vec::with_capacity(self.entity.len() + 1);
+            entity.push(b'@');
+            entity.extend(self.entity);
+            query.push(entity);
+        }
+        if let Some(action) = action {
+            query.push(action.as_bytes().to_vec());
+        }
+        query.extend(self.args);
+        query
+    }
+}
+
+fn read_varint(buf: &[u8], cursor: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    // a varint has at most 10 bytes
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*cursor)?;
+        *cursor += 1;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn read_len_delimited<'a>(buf: &'a [u8], cursor: &mut usize) -> Option<&'a [u8]> {
+    let len = usize::try_from(self::read_varint(buf, cursor)?).ok()?;
+    let data = buf.get(*cursor..cursor.checked_add(len)?)?;
+    *cursor += len;
+    Some(data)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_key(out: &mut Vec<u8>, field: u64, wire_type: u64) {
+    self::write_varint(out, (field << 3) | wire_type);
+}
+
+fn write_len_delimited(out: &mut Vec<u8>, field: u64, data: &[u8]) {
+    self::write_key(out, field, WIRE_LEN);
+    self::write_varint(out, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+/// Parse an integer (or a respcode) from a response. `int64`s are encoded as two's
+/// complement varints
+fn parse_int(int: &[u8]) -> u64 {
+    let int = str::from_utf8(int).unwrap_or_default();
+    match int.parse::<i64>() {
+        Ok(int) => int as u64,
+        Err(_) => int.parse().unwrap_or_default(),
+    }
+}
+
+/// Encode an element (or a null) as a `Response` message
+fn encode_element(out: &mut Vec<u8>, element: Option<&Element>) {
+    match element {
+        // an empty message is a null
+        None => {}
+        Some(Element::Blob(blob)) => self::write_len_delimited(out, RESPONSE_BLOB, blob),
+        Some(Element::Int(int)) => {
+            self::write_key(out, RESPONSE_INTEGER, WIRE_VARINT);
+            self::write_varint(out, self::parse_int(int));
+        }
+        Some(Element::Float(float)) => {
+            let float: f64 = str::from_utf8(float)
+                .ok()
+                .and_then(|float| float.parse().ok())
+                .unwrap_or(f64::NAN);
+            self::write_key(out, RESPONSE_NUMBER, WIRE_FIXED64);
+            out.extend_from_slice(&float.to_bits().to_le_bytes());
+        }
+        Some(Element::Code(code)) => {
+            self::write_key(out, RESPONSE_CODE, WIRE_VARINT);
+            self::write_varint(out, self::parse_int(code));
+        }
+        Some(Element::Error(error)) => self::write_len_delimited(out, RESPONSE_ERROR, error),
+        Some(Element::Array(elements)) => {
+            let mut array = Vec::new();
+            let mut message = Vec::new();
+            for element in elements {
+                message.clear();
+                self::encode_element(&mut message, element.as_ref());
+                self::write_len_delimited(&mut array, ARRAY_ELEMENTS, &message);
+            }
+            self::write_len_delimited(out, RESPONSE_ARRAY, &array);
+        }
+    }
+}
+
+fn write_frame(out: &mut Vec<u8>, flags: u8, payload: &[u8]) {
+    out.push(flags);
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Get the message from the body of a unary call, which has exactly one (uncompressed) data
+/// frame. Returns `None` for anything else
+fn unframe(body: &[u8]) -> Option<&[u8]> {
+    if body.len() < FRAME_HEADER_LEN || body[0] != FRAME_FLAG_DATA {
+        return None;
+    }
+    let mut len = [0; 4];
+    len.copy_from_slice(&body[1..FRAME_HEADER_LEN]);
+    let message = &body[FRAME_HEADER_LEN..];
+    if message.len() == u32::from_be_bytes(len) as usize {
+        Some(message)
+    } else {
+        None
+    }
+}
+
+/// Write the trailers frame, which has the status of the call
+fn write_trailers(out: &mut Vec<u8>, status: u8, message: &str) {
+    let mut trailers = format!("grpc-status:{status}\r\n");
+    if !message.is_empty() {
+        trailers.push_str(&format!("grpc-message:{message}\r\n"));
+    }
+    self::write_frame(out, FRAME_FLAG_TRAILERS, trailers.as_bytes());
+}
+
+/// A response with `message` as its only data frame
+fn message_response(message: &[u8]) -> Response {
+    let mut body = Vec::with_capacity(message.len() + FRAME_HEADER_LEN * 2 + 16);
+    self::write_frame(&mut body, FRAME_FLAG_DATA, message);
+    self::write_trailers(&mut body, GRPC_OK, "");
+    Response {
+        status: Status::OK,
+        content_type: CONTENT_TYPE_PROTO,
+        body,
+    }
+}
+
+/// A response with no messages (for failed calls)
+fn status_response(status: u8, message: &str) -> Response {
+    let mut body = Vec::new();
+    self::write_trailers(&mut body, status, message);
+    Response {
+        status: Status::OK,
+        content_type: CONTENT_TYPE_PROTO,
+        body,
+    }
+}
+
+/// Get the action that an RPC runs. Returns `Some(None)` for the query RPC and `None` for
+/// unknown RPCs
+fn action_of(rpc: &[u8]) -> Option<Option<&'static str>> {
+    if rpc == RPC_QUERY {
+        return Some(None);
+    }
+    RPCS.iter()
+        .find(|(name, _)| name.as_bytes() == rpc)
+        .map(|(_, action)| Some(*action))
+}
+
+/// Run a gRPC-Web call and return its response
+pub(super) async fn respond(
+    db: &Corestore,
+    auth: &AuthProvider,
+    peer: SocketAddr,
+    head: &RequestHead,
+    body: Vec<u8>,
+) -> IoResult<Response> {
+    if head.method != Method::Post {
+        return Ok(Response::error(
+            Status::METHOD_NOT_ALLOWED,
+            ERR_METHOD_NOT_ALLOWED,
+        ));
+    }
+    if !head.content_type.starts_with(CONTENT_TYPE) || head.content_type.contains("-text") {
+        return Ok(Response::error(
+            Status::UNSUPPORTED_MEDIA_TYPE,
+            ERR_UNSUPPORTED_CONTENT_TYPE,
+        ));
+    }
+    let action = match self::action_of(&head.path[SERVICE_PATH.len()..]) {
+        Some(action) => action,
+        None => return Ok(self::status_response(GRPC_UNIMPLEMENTED, ERR_UNKNOWN_RPC)),
+    };
+    let request = match self::unframe(&body).and_then(Request::decode) {
+        Some(request) => request,
+        None => {
+            return Ok(self::status_response(
+                GRPC_INVALID_ARGUMENT,
+                ERR_BAD_MESSAGE,
+            ))
+        }
+    };
+    let mut auth = match super::login(auth, peer, head).await {
+        Some(auth) => auth,
+        None => {
+            return Ok(self::status_response(
+                GRPC_UNAUTHENTICATED,
+                ERR_BAD_CREDENTIALS,
+            ))
+        }
+    };
+    let resp = super::execute(db, &mut auth, peer, &request.into_query(action)).await?;
+    match super::decode_response(&resp) {
+        Some(element) => {
+            let mut message = Vec::with_capacity(resp.len());
+            self::encode_element(&mut message, Some(&element));
+            Ok(self::message_response(&message))
+        }
+        None => Ok(self::status_response(GRPC_INTERNAL, ERR_BAD_RESPONSE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{action_of, encode_element, unframe, Element, Request, RPCS, RPC_QUERY};
+
+    #[test]
+    fn test_rpcs_mirror_service_definition() {
+        let proto = include_str!("../../../proto/skytable.proto");
+        let mut defined: Vec<&str> = proto
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("rpc "))
+            .map(|rpc| rpc.split('(').next().unwrap())
+            .collect();
+        let mut rpcs: Vec<&str> = RPCS.iter().map(|(rpc, _)| *rpc).collect();
+        rpcs.push(core::str::from_utf8(RPC_QUERY).unwrap());
+        defined.sort_unstable();
+        rpcs.sort_unstable();
+        assert_eq!(defined, rpcs);
+    }
+    #[test]
+    fn test_action_of() {
+        assert_eq!(action_of(b"GetSet"), Some(Some("GETSET")));
+        assert_eq!(action_of(b"TsAdd"), Some(Some("TS.ADD")));
+        assert_eq!(action_of(b"Query"), Some(None));
+        assert_eq!(action_of(b"Subscribe"), None);
+    }
+    #[test]
+    fn test_decode_request() {
+        // entity = "ks.tbl", args = ["x", "", "100"], and an unknown varint field
+        let message = b"\x0a\x06ks.tbl\x12\x01x\x12\x00\x18\x96\x01\x12\x03100";
+        let request = Request::decode(message).unwrap();
+        assert_eq!(
+            request,
+            Request {
+                entity: b"ks.tbl".to_vec(),
+                args: vec![b"x".to_vec(), vec![], b"100".to_vec()],
+            }
+        );
+        assert_eq!(
+            request.into_query(Some("SET")),
+            vec![
+                b"@ks.tbl".to_vec(),
+                b"SET".to_vec(),
+                b"x".to_vec(),
+                vec![],
+                b"100".to_vec()
+            ]
+        );
+        // truncated
+        assert!(Request::decode(b"\x12\x05abc").is_none());
+        assert!(Request::decode(b"\x12").is_none());
+    }
+    #[test]
+    fn test_unframe() {
+        assert_eq!(
+            unframe(b"\x00\x00\x00\x00\x02\x12\x00"),
+            Some(&b"\x12\x00"[..])
+        );
+        assert_eq!(unframe(b"\x00\x00\x00\x00\x00"), Some(&b""[..]));
+        // compressed
+        assert!(unframe(b"\x01\x00\x00\x00\x00").is_none());
+        // two messages
+        assert!(unframe(b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00").is_none());
+    }
+    #[test]
+    fn test_encode_element() {
+        let encode = |element: Option<Element>| {
+            let mut out = Vec::new();
+            encode_element(&mut out, element.as_ref());
+            out
+        };
+        assert_eq!(encode(Some(Element::Blob(b"hey"))), b"\x0a\x03hey");
+        assert_eq!(encode(Some(Element::Int(b"150"))), b"\x10\x96\x01");
+        assert_eq!(
+            encode(Some(Element::Int(b"-1"))),
+            b"\x10\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01"
+        );
+        assert_eq!(
+            encode(Some(Element::Float(b"1.5"))),
+            b"\x19\x00\x00\x00\x00\x00\x00\xf8\x3f"
+        );
+        assert_eq!(encode(Some(Element::Code(b"1"))), b"\x20\x01");
+        assert_eq!(encode(Some(Element::Error(b"err"))), b"\x2a\x03err");
+        assert_eq!(
+            encode(Some(Element::Array(vec![Some(Element::Blob(b"a")), None]))),
+            b"\x32\x07\x0a\x03\x0a\x01a\x0a\x00"
+        );
+    }
+}
@@ -0,0 +1,1081 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # HTTP gateway
+//!
+//! An optional HTTP/1.1 listener for clients that can't speak Skyhash. It shares the
+//! [`Corestore`] (and the connection limit) with the Skyhash listeners and runs every request
+//! as a Skyhash 2.0 query on a connection of its own:
+//!
+//! - `GET /<keyspace>/<table>/<key>` runs `GET <key>` on the table
+//! - `PUT /<keyspace>/<table>/<key>` runs `USET <key> <body>` on the table
+//! - `DELETE /<keyspace>/<table>/<key>` runs `DEL <key>` on the table
+//! - `POST /query` runs the query in the body, which is a JSON array of strings (like
+//! `["SET", "x", "100"]`)
+//!
+//! The path segments are percent-decoded, so keys can have any bytes in them. Responses are
+//! encoded as JSON:
+//! - strings become JSON strings (binary strings that aren't valid UTF-8 become
+//! `{"base64": <string>}`)
+//! - integers and floats become numbers
+//! - arrays become JSON arrays, with `null` for null elements
+//! - respcodes become `{"code": <code>}` and error strings become `{"error": <string>}`
+//!
+//! The HTTP status follows the response: `404` for `nil` and missing containers, `401`/`403`
+//! for authn/authz errors, `500` for server errors and `400` for any other error. If authn is
+//! enabled, every request needs basic credentials (`<username>:<token>`)
+//!
+//! The gateway also serves gRPC-Web calls (see [`grpc`]), tunnels Skyhash connections over
+//! WebSocket (see [`ws`]), serves the metrics for Prometheus (`GET /metrics`, see
+//! [`crate::metrics`]) and answers liveness (`GET /healthz`) and readiness (`GET /readyz`)
+//! probes without credentials (see [`crate::health`]). It can be served over TLS, with the
+//! certificate of the server
+
+use {
+    super::{
+        admission::AdmissionGuard,
+        connection,
+        listener::BaseListener,
+        tls::{self, ReloadableAcceptor},
+        AuthProviderHandle, BufferedSocketStream, NetBackoff,
+    },
+    crate::{
+        audit::{self, Event, Outcome},
+        auth::AuthProvider,
+        config::LimitsConfig,
+        corestore::Corestore,
+        health,
+        kvengine::json,
+        metrics,
+        protocol::{interface::ProtocolSpec, Skyhash2},
+        IoResult,
+    },
+    bytes::{Buf, BytesMut},
+    core::str,
+    std::{net::SocketAddr, sync::Arc},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::{broadcast, mpsc, Semaphore},
+    },
+};
+
+mod grpc;
+mod ws;
+
+/// The largest request head (the request line and the headers) that we'll accept
+const MAX_HEAD_SIZE: usize = 8 * 1024;
+/// The largest request body that we'll accept
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+/// The end of a request head
+const HEAD_TERMINATOR: &[u8] = b"\r\n\r\n";
+/// The endpoint for JSON queries
+const ENDPOINT_QUERY: &[u8] = b"/query";
+const CONTENT_TYPE_JSON: &str = "application/json";
+/// The endpoint for WebSocket tunnels
+const ENDPOINT_SKYHASH: &[u8] = b"/skyhash";
+/// The endpoint for the metrics (in the Prometheus text format)
+const ENDPOINT_METRICS: &[u8] = b"/metrics";
+const CONTENT_TYPE_METRICS: &str = "text/plain; version=0.0.4";
+/// The endpoint for liveness probes
+const ENDPOINT_HEALTHZ: &[u8] = b"/healthz";
+/// The endpoint for readiness probes
+const ENDPOINT_READYZ: &[u8] = b"/readyz";
+
+// the Skyhash 2.0 type symbols (for decoding responses)
+const TSYMBOL_STRING: u8 = Skyhash2::TSYMBOL_STRING;
+const TSYMBOL_BINARY: u8 = Skyhash2::TSYMBOL_BINARY;
+const TSYMBOL_FLOAT: u8 = Skyhash2::TSYMBOL_FLOAT;
+const TSYMBOL_INT64: u8 = Skyhash2::TSYMBOL_INT64;
+const TSYMBOL_TYPED_ARRAY: u8 = Skyhash2::TSYMBOL_TYPED_ARRAY;
+const TSYMBOL_TYPED_NON_NULL_ARRAY: u8 = Skyhash2::TSYMBOL_TYPED_NON_NULL_ARRAY;
+const TSYMBOL_ARRAY: u8 = Skyhash2::TSYMBOL_ARRAY;
+const TSYMBOL_FLAT_ARRAY: u8 = Skyhash2::TSYMBOL_FLAT_ARRAY;
+const TSYMBOL_ERROR: u8 = b'!';
+
+// gateway errors
+const ERR_BAD_REQUEST: &str = "bad-request";
+const ERR_BAD_QUERY: &str = "bad-query";
+const ERR_BAD_CREDENTIALS: &str = "bad-credentials";
+const ERR_UNKNOWN_ENDPOINT: &str = "unknown-endpoint";
+const ERR_METHOD_NOT_ALLOWED: &str = "method-not-allowed";
+const ERR_BAD_RESPONSE: &str = "bad-response";
+const ERR_BAD_HANDSHAKE: &str = "bad-websocket-handshake";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// An HTTP status
+struct Status(u16, &'static str);
+
+impl Status {
+    const OK: Self = Self(200, "OK");
+    const BAD_REQUEST: Self = Self(400, "Bad Request");
+    const UNAUTHORIZED: Self = Self(401, "Unauthorized");
+    const FORBIDDEN: Self = Self(403, "Forbidden");
+    const NOT_FOUND: Self = Self(404, "Not Found");
+    const METHOD_NOT_ALLOWED: Self = Self(405, "Method Not Allowed");
+    const PAYLOAD_TOO_LARGE: Self = Self(413, "Payload Too Large");
+    const UNSUPPORTED_MEDIA_TYPE: Self = Self(415, "Unsupported Media Type");
+    const TOO_MANY_REQUESTS: Self = Self(429, "Too Many Requests");
+    const HEADERS_TOO_LARGE: Self = Self(431, "Request Header Fields Too Large");
+    const INTERNAL_SERVER_ERROR: Self = Self(500, "Internal Server Error");
+    const NOT_IMPLEMENTED: Self = Self(501, "Not Implemented");
+    const SERVICE_UNAVAILABLE: Self = Self(503, "Service Unavailable");
+    const VERSION_NOT_SUPPORTED: Self = Self(505, "HTTP Version Not Supported");
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The request methods that the gateway cares about
+enum Method {
+    Get,
+    Put,
+    Delete,
+    Post,
+    Other,
+}
+
+impl Method {
+    fn parse(method: &str) -> Self {
+        match method {
+            "GET" => Self::Get,
+            "PUT" => Self::Put,
+            "DELETE" => Self::Delete,
+            "POST" => Self::Post,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The head of a request
+struct RequestHead {
+    method: Method,
+    /// the request target, without the query string
+    path: Vec<u8>,
+    /// the length of the head, including the blank line that ends it
+    len: usize,
+    content_length: usize,
+    /// the content type (lowercased)
+    content_type: String,
+    keep_alive: bool,
+    /// the client will wait for a `100 Continue` before sending the body
+    expect_continue: bool,
+    /// the username and token from the basic credentials (if any)
+    credentials: Option<(Vec<u8>, Vec<u8>)>,
+    /// the key of a WebSocket upgrade request (if this is one)
+    websocket_key: Option<String>,
+}
+
+/// Result of [`read_request`]
+enum ReadResult {
+    /// A request and its body
+    Request(RequestHead, Vec<u8>),
+    /// A malformed request. We can't tell where the next request starts, so the connection
+    /// has to be closed
+    Bad(Status),
+    /// The client disconnected
+    Disconnected,
+}
+
+/// A response to a request
+struct Response {
+    status: Status,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    const fn json(status: Status, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            content_type: CONTENT_TYPE_JSON,
+            body,
+        }
+    }
+    fn error(status: Status, error: &str) -> Self {
+        Self::json(status, self::error_body(error))
+    }
+}
+
+/// Parse the head of the request at the start of `buf`. Returns `None` if we haven't got the
+/// whole head yet
+fn parse_head(buf: &[u8]) -> Result<Option<RequestHead>, Status> {
+    let end = match buf
+        .windows(HEAD_TERMINATOR.len())
+        .position(|window| window == HEAD_TERMINATOR)
+    {
+        Some(end) if end <= MAX_HEAD_SIZE => end,
+        None if buf.len() <= MAX_HEAD_SIZE => return Ok(None),
+        _ => return Err(Status::HEADERS_TOO_LARGE),
+    };
+    let head = str::from_utf8(&buf[..end]).map_err(|_| Status::BAD_REQUEST)?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (method, target, version) = match (
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+    ) {
+        (Some(method), Some(target), Some(version), None) => (method, target, version),
+        _ => return Err(Status::BAD_REQUEST),
+    };
+    let mut keep_alive = match version {
+        "HTTP/1.1" => true,
+        "HTTP/1.0" => false,
+        _ => return Err(Status::VERSION_NOT_SUPPORTED),
+    };
+    let mut content_length = 0;
+    let mut content_type = String::new();
+    let mut expect_continue = false;
+    let mut credentials = None;
+    let mut upgrade_websocket = false;
+    let mut websocket_key = None;
+    let mut websocket_version = "";
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(Status::BAD_REQUEST)?;
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value.parse().map_err(|_| Status::BAD_REQUEST)?;
+            }
+            "content-type" => content_type = value.to_ascii_lowercase(),
+            // we only accept bodies with a known length
+            "transfer-encoding" => return Err(Status::NOT_IMPLEMENTED),
+            "connection" if value.eq_ignore_ascii_case("close") => keep_alive = false,
+            "connection" if value.eq_ignore_ascii_case("keep-alive") => keep_alive = true,
+            "expect" => expect_continue = value.eq_ignore_ascii_case("100-continue"),
+            "authorization" => credentials = self::parse_basic_credentials(value),
+            "upgrade" => upgrade_websocket = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => websocket_key = Some(value.to_owned()),
+            "sec-websocket-version" => websocket_version = value,
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(Status::PAYLOAD_TOO_LARGE);
+    }
+    Ok(Some(RequestHead {
+        method: Method::parse(method),
+        path: target.split('?').next().unwrap_or_default().into(),
+        len: end + HEAD_TERMINATOR.len(),
+        content_length,
+        content_type,
+        keep_alive,
+        expect_continue,
+        credentials,
+        // we only speak version 13 (RFC 6455)
+        websocket_key: websocket_key.filter(|_| upgrade_websocket && websocket_version == "13"),
+    }))
+}
+
+/// Get the username and token from basic credentials (`Basic <base64(username:token)>`)
+fn parse_basic_credentials(value: &str) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (scheme, encoded) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let mut username = base64::decode(encoded.trim()).ok()?;
+    let colon = username.iter().position(|byte| *byte == b':')?;
+    let token = username.split_off(colon + 1);
+    // drop the colon
+    username.pop();
+    Some((username, token))
+}
+
+/// Decode the percent-encoded bytes in a path segment. Returns `None` for bad escapes
+fn percent_decode(segment: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(segment.len());
+    let mut i = 0;
+    while i < segment.len() {
+        if segment[i] == b'%' {
+            let hex = segment.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            // UTF-8 since we just checked for hex digits
+            decoded.push(u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(segment[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+/// Get the query that a request runs
+fn route(
+    method: Method,
+    path: &[u8],
+    body: Vec<u8>,
+) -> Result<Vec<Vec<u8>>, (Status, &'static str)> {
+    if path == ENDPOINT_QUERY {
+        return match method {
+            Method::Post => match json::parse_string_array(&body) {
+                Some(query) if !query.is_empty() => Ok(query),
+                _ => Err((Status::BAD_REQUEST, ERR_BAD_QUERY)),
+            },
+            _ => Err((Status::METHOD_NOT_ALLOWED, ERR_METHOD_NOT_ALLOWED)),
+        };
+    }
+    let segments: Vec<Vec<u8>> = match path.strip_prefix(b"/") {
+        Some(path) => path
+            .split(|byte| *byte == b'/')
+            .map(self::percent_decode)
+            .collect::<Option<_>>()
+            .ok_or((Status::BAD_REQUEST, ERR_BAD_REQUEST))?,
+        None => return Err((Status::NOT_FOUND, ERR_UNKNOWN_ENDPOINT)),
+    };
+    let mut segments = segments.into_iter();
+    let (keyspace, table, key) = match (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) {
+        (Some(keyspace), Some(table), Some(key), None)
+            if !keyspace.is_empty() && !table.is_empty() =>
+        {
+            (keyspace, table, key)
+        }
+        _ => return Err((Status::NOT_FOUND, ERR_UNKNOWN_ENDPOINT)),
+    };
+    // run the action on the table with an entity prefix (`@keyspace.table`)
+    let mut entity = Vec::with_capacity(keyspace.len() + table.len() + 2);
+    entity.push(b'@');
+    entity.extend_from_slice(&keyspace);
+    entity.push(b'.');
+    entity.extend_from_slice(&table);
+    match method {
+        Method::Get => Ok(vec![entity, b"GET".to_vec(), key]),
+        Method::Put => Ok(vec![entity, b"USET".to_vec(), key, body]),
+        Method::Delete => Ok(vec![entity, b"DEL".to_vec(), key]),
+        _ => Err((Status::METHOD_NOT_ALLOWED, ERR_METHOD_NOT_ALLOWED)),
+    }
+}
+
+/// Write a JSON string
+fn write_json_string(out: &mut Vec<u8>, string: &str) {
+    out.push(b'"');
+    for c in string.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            '\n' => out.extend_from_slice(b"\\n"),
+            '\r' => out.extend_from_slice(b"\\r"),
+            '\t' => out.extend_from_slice(b"\\t"),
+            // control characters must be escaped
+            c if (c as u32) < 0x20 => {
+                out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    out.push(b'"');
+}
+
+/// Write a string or a binary string. Binary strings that aren't valid UTF-8 are base64
+/// encoded
+pub fn write_json_blob(out: &mut Vec<u8>, blob: &[u8]) {
+    match str::from_utf8(blob) {
+        Ok(string) => self::write_json_string(out, string),
+        Err(_) => {
+            out.extend_from_slice(b"{\"base64\":");
+            self::write_json_string(out, &base64::encode(blob));
+            out.push(b'}');
+        }
+    }
+}
+
+/// Write an integer or a float. Numbers that JSON can't represent (like `inf`) become `null`
+fn write_json_number(out: &mut Vec<u8>, number: &[u8]) {
+    let digits = number.strip_prefix(b"-").unwrap_or(number);
+    if !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) {
+        // integers are written as is, since they may not fit in a float
+        out.extend_from_slice(number);
+        return;
+    }
+    match str::from_utf8(number).map(str::parse::<f64>) {
+        Ok(Ok(float)) if float.is_finite() => out.extend_from_slice(float.to_string().as_bytes()),
+        _ => out.extend_from_slice(b"null"),
+    }
+}
+
+/// Write the body of an error response (`{"error": <error>}`)
+fn error_body(error: &str) -> Vec<u8> {
+    let mut body = b"{\"error\":".to_vec();
+    self::write_json_string(&mut body, error);
+    body.push(b'}');
+    body
+}
+
+#[derive(Debug, PartialEq)]
+/// An element in a (decoded) response
+enum Element<'a> {
+    /// A string or a binary string
+    Blob(&'a [u8]),
+    Int(&'a [u8]),
+    Float(&'a [u8]),
+    /// A respcode
+    Code(&'a [u8]),
+    /// An error string
+    Error(&'a [u8]),
+    /// An array. Elements of typed arrays can be null
+    Array(Vec<Option<Element<'a>>>),
+}
+
+/// Reads a Skyhash 2.0 response
+struct ResponseReader<'a> {
+    resp: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> ResponseReader<'a> {
+    const fn new(resp: &'a [u8]) -> Self {
+        Self { resp, cursor: 0 }
+    }
+    fn peek(&self) -> Option<u8> {
+        self.resp.get(self.cursor).copied()
+    }
+    fn byte(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.cursor += 1;
+        Some(byte)
+    }
+    /// Read up to the next LF (the LF is skipped)
+    fn line(&mut self) -> Option<&'a [u8]> {
+        let rest = self.resp.get(self.cursor..)?;
+        let lf = rest.iter().position(|byte| *byte == Skyhash2::LF)?;
+        self.cursor += lf + 1;
+        Some(&rest[..lf])
+    }
+    fn size(&mut self) -> Option<usize> {
+        str::from_utf8(self.line()?).ok()?.parse().ok()
+    }
+    /// Read a length-prefixed element (`<len>\n<data>`)
+    fn sized(&mut self) -> Option<&'a [u8]> {
+        let len = self.size()?;
+        let data = self.resp.get(self.cursor..self.cursor.checked_add(len)?)?;
+        self.cursor += len;
+        Some(data)
+    }
+    /// Read the next element. Returns `None` if the response is malformed
+    fn element(&mut self) -> Option<Element<'a>> {
+        let element = match self.byte()? {
+            TSYMBOL_STRING | TSYMBOL_BINARY => Element::Blob(self.sized()?),
+            TSYMBOL_INT64 => Element::Int(self.line()?),
+            TSYMBOL_FLOAT => Element::Float(self.line()?),
+            TSYMBOL_ERROR => {
+                let error = self.line()?;
+                if !error.is_empty() && error.iter().all(u8::is_ascii_digit) {
+                    Element::Code(error)
+                } else {
+                    Element::Error(error)
+                }
+            }
+            TSYMBOL_ARRAY | TSYMBOL_FLAT_ARRAY => {
+                let count = self.size()?;
+                let elements = (0..count)
+                    .map(|_| self.element().map(Some))
+                    .collect::<Option<_>>()?;
+                Element::Array(elements)
+            }
+            TSYMBOL_TYPED_ARRAY | TSYMBOL_TYPED_NON_NULL_ARRAY => {
+                let tsymbol = self.byte()?;
+                let count = self.size()?;
+                let mut elements = Vec::with_capacity(count.min(self.resp.len()));
+                for _ in 0..count {
+                    if self.peek()? == Skyhash2::TYPE_TYPED_ARRAY_ELEMENT_NULL[0] {
+                        self.cursor += 1;
+                        elements.push(None);
+                        continue;
+                    }
+                    let element = self.sized()?;
+                    elements.push(Some(match tsymbol {
+                        TSYMBOL_INT64 => Element::Int(element),
+                        TSYMBOL_FLOAT => Element::Float(element),
+                        _ => Element::Blob(element),
+                    }));
+                }
+                Element::Array(elements)
+            }
+            _ => return None,
+        };
+        Some(element)
+    }
+}
+
+/// Decode the response to a simple query. Returns `None` if the response is malformed
+fn decode_response(resp: &[u8]) -> Option<Element<'_>> {
+    let resp = resp
+        .strip_prefix(Skyhash2::SIMPLE_QUERY_HEADER)
+        .unwrap_or(resp);
+    ResponseReader::new(resp).element()
+}
+
+/// Write an element (or a null) as JSON
+fn write_json_element(out: &mut Vec<u8>, element: Option<&Element>) {
+    match element {
+        None => out.extend_from_slice(b"null"),
+        Some(Element::Blob(blob)) => self::write_json_blob(out, blob),
+        Some(Element::Int(number) | Element::Float(number)) => self::write_json_number(out, number),
+        Some(Element::Code(code)) => {
+            out.extend_from_slice(b"{\"code\":");
+            out.extend_from_slice(code);
+            out.push(b'}');
+        }
+        Some(Element::Error(error)) => {
+            out.extend_from_slice(&self::error_body(&String::from_utf8_lossy(error)))
+        }
+        Some(Element::Array(elements)) => {
+            out.push(b'[');
+            for (i, element) in elements.iter().enumerate() {
+                if i != 0 {
+                    out.push(b',');
+                }
+                self::write_json_element(out, element.as_ref());
+            }
+            out.push(b']');
+        }
+    }
+}
+
+/// Get the HTTP status for a response
+fn status_of(resp: &[u8]) -> Status {
+    if resp.first() != Some(&TSYMBOL_ERROR) || resp == Skyhash2::RCODE_OKAY {
+        Status::OK
+    } else if resp == Skyhash2::RCODE_NIL || resp == Skyhash2::RSTRING_CONTAINER_NOT_FOUND {
+        Status::NOT_FOUND
+    } else if resp == Skyhash2::AUTH_CODE_BAD_CREDENTIALS {
+        Status::UNAUTHORIZED
+    } else if resp == Skyhash2::AUTH_CODE_PERMS {
+        Status::FORBIDDEN
+    } else if resp == Skyhash2::RSTRING_THROTTLED {
+        Status::TOO_MANY_REQUESTS
+    } else if resp == Skyhash2::RCODE_SERVER_ERR {
+        Status::INTERNAL_SERVER_ERROR
+    } else {
+        Status::BAD_REQUEST
+    }
+}
+
+/// Encode the response to a query as JSON, along with the HTTP status for it
+fn encode_response(resp: &[u8]) -> (Status, Vec<u8>) {
+    match self::decode_response(resp) {
+        Some(element) => {
+            let mut body = Vec::with_capacity(resp.len());
+            self::write_json_element(&mut body, Some(&element));
+            let resp = resp
+                .strip_prefix(Skyhash2::SIMPLE_QUERY_HEADER)
+                .unwrap_or(resp);
+            (self::status_of(resp), body)
+        }
+        None => (
+            Status::INTERNAL_SERVER_ERROR,
+            self::error_body(ERR_BAD_RESPONSE),
+        ),
+    }
+}
+
+/// Run a query on a connection of its own and return the (Skyhash 2.0) response
+async fn execute(
+    db: &Corestore,
+    auth: &mut AuthProviderHandle,
+    peer: SocketAddr,
+    query: &[Vec<u8>],
+) -> IoResult<Vec<u8>> {
+    let packet = Skyhash2::encode_simple_query(query);
+    // (the size of the query is already bounded by the size of the request body)
+    let limits = LimitsConfig::default().query;
+    super::execute_packet(db, auth, Some(peer), &packet, limits, false).await
+}
+
+/// Log in with the basic credentials of a request (if authn is enabled). Returns `None` if
+/// the credentials are missing or bad
+async fn login(
+    auth: &AuthProvider,
+    peer: SocketAddr,
+    head: &RequestHead,
+) -> Option<AuthProviderHandle> {
+    let mut auth = AuthProviderHandle::new(auth.clone());
+    if !auth.authenticated() {
+        let logged_in = match head.credentials.as_ref() {
+            Some((username, token)) => auth
+                .provider_mut()
+                .login_with_external::<Skyhash2>(username, token)
+                .await
+                .is_ok(),
+            None => false,
+        };
+        if !logged_in {
+            // every request logs in again, so only the failures are audited
+            if audit::is_enabled() {
+                let mut action: Vec<&[u8]> = vec![&b"HTTP"[..], b"LOGIN"];
+                action.extend(head.credentials.as_ref().map(|(username, _)| &username[..]));
+                audit::record(Event::Login, Outcome::Denied, Some(peer), None, &action);
+            }
+            return None;
+        }
+        auth.set_auth();
+    }
+    Some(auth)
+}
+
+/// Run a request and return its response
+async fn respond(
+    db: &Corestore,
+    auth: &AuthProvider,
+    peer: SocketAddr,
+    head: &RequestHead,
+    body: Vec<u8>,
+) -> IoResult<Response> {
+    if head.path.starts_with(grpc::SERVICE_PATH) {
+        return grpc::respond(db, auth, peer, head, body).await;
+    }
+    if head.path == ENDPOINT_METRICS {
+        return Ok(self::respond_metrics(auth, peer, head).await);
+    }
+    if head.path == ENDPOINT_HEALTHZ || head.path == ENDPOINT_READYZ {
+        return Ok(self::respond_health(head));
+    }
+    let query = match self::route(head.method, &head.path, body) {
+        Ok(query) => query,
+        Err((status, error)) => return Ok(Response::error(status, error)),
+    };
+    let mut auth = match self::login(auth, peer, head).await {
+        Some(auth) => auth,
+        None => return Ok(Response::error(Status::UNAUTHORIZED, ERR_BAD_CREDENTIALS)),
+    };
+    let resp = self::execute(db, &mut auth, peer, &query).await?;
+    let (status, body) = self::encode_response(&resp);
+    Ok(Response::json(status, body))
+}
+
+/// Respond to a scrape of the metrics (which needs the same credentials as a query)
+async fn respond_metrics(auth: &AuthProvider, peer: SocketAddr, head: &RequestHead) -> Response {
+    if head.method != Method::Get {
+        return Response::error(Status::METHOD_NOT_ALLOWED, ERR_METHOD_NOT_ALLOWED);
+    }
+    if self::login(auth, peer, head).await.is_none() {
+        return Response::error(Status::UNAUTHORIZED, ERR_BAD_CREDENTIALS);
+    }
+    Response {
+        status: Status::OK,
+        content_type: CONTENT_TYPE_METRICS,
+        body: metrics::render().into_bytes(),
+    }
+}
+
+/// Respond to a liveness (`{"live":true}`) or a readiness probe. Readiness probes get the
+/// checks, like `{"ready":true,"storage":"okay","snapshots":"disabled","writes":"accepting"}`,
+/// with a `503` if the server isn't ready
+fn respond_health(head: &RequestHead) -> Response {
+    if head.method != Method::Get {
+        return Response::error(Status::METHOD_NOT_ALLOWED, ERR_METHOD_NOT_ALLOWED);
+    }
+    if head.path == ENDPOINT_HEALTHZ {
+        return Response::json(Status::OK, b"{\"live\":true}".to_vec());
+    }
+    let report = health::report();
+    let ready = report.is_ready();
+    let body = format!(
+        "{{\"ready\":{ready},\"storage\":\"{}\",\"snapshots\":\"{}\",\"writes\":\"{}\"}}",
+        report.storage.name(),
+        report.snapshots.name(),
+        report.writes.name()
+    );
+    let status = if ready {
+        Status::OK
+    } else {
+        Status::SERVICE_UNAVAILABLE
+    };
+    Response::json(status, body.into_bytes())
+}
+
+/// Read the next request (and its body) from the stream
+async fn read_request<S: BufferedSocketStream>(
+    stream: &mut S,
+    buffer: &mut BytesMut,
+) -> IoResult<ReadResult> {
+    let head = loop {
+        match self::parse_head(buffer) {
+            Ok(Some(head)) => break head,
+            Ok(None) => {}
+            Err(status) => return Ok(ReadResult::Bad(status)),
+        }
+        if stream.read_buf(buffer).await? == 0 {
+            return Ok(ReadResult::Disconnected);
+        }
+    };
+    let end = head.len + head.content_length;
+    if buffer.len() < end {
+        buffer.reserve(end - buffer.len());
+        if head.expect_continue {
+            stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await?;
+        }
+    }
+    while buffer.len() < end {
+        if stream.read_buf(buffer).await? == 0 {
+            return Ok(ReadResult::Disconnected);
+        }
+    }
+    let body = buffer[head.len..end].to_vec();
+    buffer.advance(end);
+    Ok(ReadResult::Request(head, body))
+}
+
+/// Write a response to the stream
+async fn write_response<S: BufferedSocketStream>(
+    stream: &mut S,
+    response: &Response,
+    keep_alive: bool,
+) -> IoResult<()> {
+    let Response {
+        status,
+        content_type,
+        body,
+    } = response;
+    let Status(code, reason) = status;
+    let mut resp = format!(
+        "HTTP/1.1 {code} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n",
+        body.len()
+    )
+    .into_bytes();
+    if !keep_alive {
+        resp.extend_from_slice(b"Connection: close\r\n");
+    }
+    if *status == Status::UNAUTHORIZED {
+        resp.extend_from_slice(b"WWW-Authenticate: Basic realm=\"skytable\"\r\n");
+    }
+    resp.extend_from_slice(b"\r\n");
+    resp.extend_from_slice(body);
+    stream.write_all(&resp).await?;
+    stream.flush().await
+}
+
+/// A connection to the HTTP gateway
+struct HttpConnection<S> {
+    db: Corestore,
+    auth: AuthProvider,
+    stream: S,
+    /// the address of the client
+    peer: SocketAddr,
+    buffer: BytesMut,
+    climit: Arc<Semaphore>,
+    /// the limits for this connection (and the one tunnelled over a WebSocket)
+    limits: LimitsConfig,
+    /// the connection was handed over to a WebSocket tunnel (which owns the permit now)
+    tunnelled: bool,
+    /// the connection counts against its IP till it's dropped
+    _admission: AdmissionGuard,
+    termination_signal: broadcast::Receiver<()>,
+    _term_sig_tx: mpsc::Sender<()>,
+}
+
+impl<S: BufferedSocketStream + Send + 'static> HttpConnection<S> {
+    fn new(base: &BaseListener, stream: S, peer: SocketAddr, admission: AdmissionGuard) -> Self {
+        Self {
+            db: base.db.clone(),
+            auth: base.auth.clone(),
+            stream,
+            peer,
+            buffer: BytesMut::with_capacity(connection::BUF_READ_CAP),
+            climit: base.climit.clone(),
+            limits: base.limits(),
+            tunnelled: false,
+            _admission: admission,
+            termination_signal: base.signal.subscribe(),
+            _term_sig_tx: base.terminate_tx.clone(),
+        }
+    }
+    async fn run(&mut self) -> IoResult<()> {
+        loop {
+            let request = tokio::select! {
+                request = self::read_request(&mut self.stream, &mut self.buffer) => request?,
+                _ = self.termination_signal.recv() => {
+                    return Ok(());
+                }
+                _ = connection::idle_timeout(self.limits.idle_timeout) => {
+                    // the client hasn't sent a request for a while
+                    return Ok(());
+                }
+            };
+            let (head, body) = match request {
+                ReadResult::Request(head, body) => (head, body),
+                ReadResult::Bad(status) => {
+                    let response = Response::error(status, ERR_BAD_REQUEST);
+                    return self::write_response(&mut self.stream, &response, false).await;
+                }
+                ReadResult::Disconnected => return Ok(()),
+            };
+            if head.path == ENDPOINT_SKYHASH {
+                match &head.websocket_key {
+                    Some(key) if head.method == Method::Get => return self.tunnel(key).await,
+                    _ => {
+                        let response = Response::error(Status::BAD_REQUEST, ERR_BAD_HANDSHAKE);
+                        self::write_response(&mut self.stream, &response, head.keep_alive).await?;
+                        if !head.keep_alive {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                }
+            }
+            // queries can park the connection (`BLPOP`, for example), so we'll keep looking out
+            // for termination signals
+            let response = tokio::select! {
+                response = self::respond(&self.db, &self.auth, self.peer, &head, body) => response?,
+                _ = self.termination_signal.recv() => {
+                    return Ok(());
+                }
+            };
+            self::write_response(&mut self.stream, &response, head.keep_alive).await?;
+            if !head.keep_alive {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<S> Drop for HttpConnection<S> {
+    fn drop(&mut self) {
+        // Make sure that the permit is returned to the semaphore
+        // in the case that there is a panic inside
+        if !self.tunnelled {
+            self.climit.add_permits(1);
+        }
+    }
+}
+
+/// The HTTP gateway listener
+pub struct HttpListener {
+    pub base: BaseListener,
+    /// the TLS acceptor, if the gateway is served over TLS
+    acceptor: Option<Arc<ReloadableAcceptor>>,
+}
+
+impl HttpListener {
+    pub fn new(base: BaseListener, acceptor: Option<Arc<ReloadableAcceptor>>) -> Self {
+        Self { base, acceptor }
+    }
+    /// Accept an incoming connection (that was admitted)
+    async fn accept(&mut self) -> IoResult<(TcpStream, SocketAddr, AdmissionGuard)> {
+        let backoff = NetBackoff::new();
+        loop {
+            match self.base.listener.accept().await {
+                Ok((stream, addr)) => match self.base.admit(&stream, addr) {
+                    Some(guard) => return Ok((stream, addr, guard)),
+                    // turned away, so we drop (close) the stream and wait for the next one
+                    None => continue,
+                },
+                Err(e) => {
+                    if backoff.should_disconnect() {
+                        // Too many retries, goodbye user
+                        return Err(e);
+                    }
+                }
+            }
+            // spin to wait for the backoff duration
+            backoff.spin().await;
+        }
+    }
+    /// Run a connection in a new task
+    fn spawn<S: BufferedSocketStream + Send + 'static>(
+        &self,
+        stream: S,
+        addr: SocketAddr,
+        guard: AdmissionGuard,
+    ) {
+        let mut con = HttpConnection::new(&self.base, stream, addr, guard);
+        tokio::spawn(async move {
+            if let Err(e) = con.run().await {
+                log::error!("Error: {}", e);
+            }
+        });
+    }
+    /// Run the gateway
+    pub async fn run(&mut self) -> IoResult<()> {
+        loop {
+            // Take the permit first, but we won't use it right now
+            // that's why we will forget it
+            self.base.climit.acquire().await.unwrap().forget();
+            let (stream, addr, guard) = skip_loop_err!(self.accept().await);
+            match &self.acceptor {
+                Some(acceptor) => match tls::accept_stream(acceptor, stream).await {
+                    Ok(stream) => self.spawn(stream, addr, guard),
+                    // the connection never made it, so we return the permit
+                    Err(_) => self.base.climit.add_permits(1),
+                },
+                None => self.spawn(stream, addr, guard),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_response, parse_head, route, Method, Status};
+
+    #[test]
+    fn test_parse_head() {
+        let req = b"PUT /ks/tbl/x?pretty HTTP/1.1\r\nContent-Length: 5\r\n\
+        authorization: Basic dXNlcjp0b2tlbg==\r\nExpect: 100-continue\r\n\r\nhello";
+        let head = parse_head(req).unwrap().unwrap();
+        assert_eq!(head.method, Method::Put);
+        assert_eq!(head.path, b"/ks/tbl/x");
+        assert_eq!(head.len, req.len() - 5);
+        assert_eq!(head.content_length, 5);
+        assert!(head.keep_alive);
+        assert!(head.expect_continue);
+        assert_eq!(
+            head.credentials,
+            Some((b"user".to_vec(), b"token".to_vec()))
+        );
+        let head = parse_head(b"GET /query HTTP/1.0\r\n\r\n").unwrap().unwrap();
+        assert!(!head.keep_alive);
+        assert!(head.websocket_key.is_none());
+        let upgrade = "GET /skyhash HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+        Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        let head = parse_head(upgrade.as_bytes()).unwrap().unwrap();
+        assert_eq!(
+            head.websocket_key.as_deref(),
+            Some("dGhlIHNhbXBsZSBub25jZQ==")
+        );
+        let old_version = upgrade.replace("Version: 13", "Version: 8");
+        let head = parse_head(old_version.as_bytes()).unwrap().unwrap();
+        assert!(head.websocket_key.is_none());
+        assert!(parse_head(b"GET /query HTTP/1.1\r\nHost: x")
+            .unwrap()
+            .is_none());
+    }
+    #[test]
+    fn test_parse_bad_head() {
+        assert_eq!(
+            parse_head(b"GET /query\r\n\r\n").unwrap_err(),
+            Status::BAD_REQUEST
+        );
+        assert_eq!(
+            parse_head(b"GET /query HTTP/2\r\n\r\n").unwrap_err(),
+            Status::VERSION_NOT_SUPPORTED
+        );
+        assert_eq!(
+            parse_head(b"POST /query HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap_err(),
+            Status::NOT_IMPLEMENTED
+        );
+        assert_eq!(
+            parse_head(&[b'A'; 16 * 1024]).unwrap_err(),
+            Status::HEADERS_TOO_LARGE
+        );
+    }
+    #[test]
+    fn test_route() {
+        assert_eq!(
+            route(Method::Get, b"/ks/tbl/my%20key", vec![]).unwrap(),
+            vec![b"@ks.tbl".to_vec(), b"GET".to_vec(), b"my key".to_vec()]
+        );
+        assert_eq!(
+            route(Method::Put, b"/ks/tbl/x", b"100".to_vec()).unwrap(),
+            vec![
+                b"@ks.tbl".to_vec(),
+                b"USET".to_vec(),
+                b"x".to_vec(),
+                b"100".to_vec()
+            ]
+        );
+        assert_eq!(
+            route(Method::Post, b"/query", br#"["SET", "x", "100"]"#.to_vec()).unwrap(),
+            vec![b"SET".to_vec(), b"x".to_vec(), b"100".to_vec()]
+        );
+        assert_eq!(
+            route(Method::Post, b"/query", b"[]".to_vec())
+                .unwrap_err()
+                .0,
+            Status::BAD_REQUEST
+        );
+        assert_eq!(
+            route(Method::Get, b"/query", vec![]).unwrap_err().0,
+            Status::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            route(Method::Post, b"/ks/tbl/x", vec![]).unwrap_err().0,
+            Status::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            route(Method::Get, b"/ks/tbl", vec![]).unwrap_err().0,
+            Status::NOT_FOUND
+        );
+        assert_eq!(
+            route(Method::Get, b"/ks/tbl/%zz", vec![]).unwrap_err().0,
+            Status::BAD_REQUEST
+        );
+    }
+    #[test]
+    fn test_encode_response() {
+        let encode = |resp: &[u8]| {
+            let (status, body) = encode_response(resp);
+            (status, String::from_utf8(body).unwrap())
+        };
+        assert_eq!(
+            encode(b"*+6\n\"hey\"\n"),
+            (Status::OK, r#""\"hey\"\n""#.to_owned())
+        );
+        assert_eq!(
+            encode(b"*?2\n\xff\xfe"),
+            (Status::OK, r#"{"base64":"//4="}"#.to_owned())
+        );
+        assert_eq!(encode(b"*:10\n"), (Status::OK, "10".to_owned()));
+        assert_eq!(encode(b"*%inf\n"), (Status::OK, "null".to_owned()));
+        assert_eq!(
+            encode(b"*&3\n+1\na:2\n@+2\n\x001\nb"),
+            (Status::OK, r#"["a",2,[null,"b"]]"#.to_owned())
+        );
+        assert_eq!(
+            encode(b"*!1\n"),
+            (Status::NOT_FOUND, r#"{"code":1}"#.to_owned())
+        );
+        assert_eq!(
+            encode(b"*!201 container-not-found\n"),
+            (
+                Status::NOT_FOUND,
+                r#"{"error":"201 container-not-found"}"#.to_owned()
+            )
+        );
+        assert_eq!(
+            encode(b"*!703 throttled\n"),
+            (
+                Status::TOO_MANY_REQUESTS,
+                r#"{"error":"703 throttled"}"#.to_owned()
+            )
+        );
+        assert_eq!(
+            encode(b"*+10\nshort"),
+            (
+                Status::INTERNAL_SERVER_ERROR,
+                r#"{"error":"bad-response"}"#.to_owned()
+            )
+        );
+    }
+}
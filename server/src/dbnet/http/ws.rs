@@ -0,0 +1,337 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # WebSocket tunnel
+//!
+//! Browsers can't open TCP connections, so the gateway tunnels Skyhash connections over
+//! WebSocket (RFC 6455). A `GET /skyhash` upgrade request turns the HTTP connection into a
+//! Skyhash connection, and the Skyhash frames are carried in the payloads of binary (or text)
+//! messages. The payloads are simply concatenated, so messages don't have to line up with
+//! queries, and responses are sent (in binary messages) as soon as they're written.
+//!
+//! The tunnelled connection is run by a regular Skyhash connection handler over an in-memory
+//! pipe, just like the connections accepted by the Skyhash listeners: the client can negotiate
+//! the protocol (it's Skyhash 2.0 otherwise) and has to `AUTH` if authn is enabled. Use
+//! `wss://` if the gateway is served over TLS
+
+use {
+    super::{HttpConnection, MAX_BODY_SIZE},
+    crate::{
+        dbnet::{connection, handshake, BufferedSocketStream},
+        protocol::Skyhash2,
+        IoResult,
+    },
+    bytes::{Buf, BytesMut},
+    openssl::sha,
+    tokio::{
+        io::{self as tio, AsyncReadExt, AsyncWriteExt, DuplexStream},
+        sync::broadcast,
+    },
+};
+
+/// The GUID that's appended to the key of an upgrade request to compute the accept key
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// The largest frame that we'll accept
+const MAX_FRAME_SIZE: usize = MAX_BODY_SIZE;
+/// The capacity of each direction of the pipe to the tunnelled connection
+const TUNNEL_CAPACITY: usize = 64 * 1024;
+
+// opcodes
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+/// Control frames have the high bit of the opcode set
+const OPCODE_CONTROL: u8 = 0x8;
+const FLAG_FIN: u8 = 0x80;
+const FLAG_MASK: u8 = 0x80;
+/// The bits reserved for extensions (we don't support any)
+const FLAGS_RESERVED: u8 = 0x70;
+
+// close codes
+const CLOSE_NORMAL: u16 = 1000;
+const CLOSE_GOING_AWAY: u16 = 1001;
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+const CLOSE_TOO_BIG: u16 = 1009;
+
+/// Tunnelled connections write to memory
+impl BufferedSocketStream for DuplexStream {}
+
+/// Compute the accept key (`Sec-WebSocket-Accept`) for the key of an upgrade request
+fn accept_key(key: &str) -> String {
+    let mut input = String::with_capacity(key.len() + WEBSOCKET_GUID.len());
+    input.push_str(key);
+    input.push_str(WEBSOCKET_GUID);
+    base64::encode(sha::sha1(input.as_bytes()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A frame sent by the client
+struct Frame {
+    opcode: u8,
+    /// the (unmasked) payload
+    payload: Vec<u8>,
+}
+
+/// Parse the frame at the start of `buf`, returning it along with its length. Returns `None` if
+/// we haven't got the whole frame yet, and the close code if the frame is bad
+fn parse_frame(buf: &[u8]) -> Result<Option<(Frame, usize)>, u16> {
+    let (first, second) = match buf {
+        [first, second, ..] => (*first, *second),
+        _ => return Ok(None),
+    };
+    let opcode = first & 0x0F;
+    // clients have to mask their frames
+    if first & FLAGS_RESERVED != 0 || second & FLAG_MASK == 0 {
+        return Err(CLOSE_PROTOCOL_ERROR);
+    }
+    let (len, mut cursor) = match second & 0x7F {
+        126 => match buf.get(2..4) {
+            Some(len) => (u64::from(u16::from_be_bytes([len[0], len[1]])), 4),
+            None => return Ok(None),
+        },
+        127 => match buf.get(2..10) {
+            Some(len) => {
+                let mut be = [0; 8];
+                be.copy_from_slice(len);
+                (u64::from_be_bytes(be), 10)
+            }
+            None => return Ok(None),
+        },
+        len => (u64::from(len), 2),
+    };
+    // control frames are short and can't be fragmented
+    if opcode & OPCODE_CONTROL != 0 && (len > 125 || first & FLAG_FIN == 0) {
+        return Err(CLOSE_PROTOCOL_ERROR);
+    }
+    if len > MAX_FRAME_SIZE as u64 {
+        return Err(CLOSE_TOO_BIG);
+    }
+    let end = cursor + 4 + len as usize;
+    if buf.len() < end {
+        return Ok(None);
+    }
+    let mask = &buf[cursor..cursor + 4];
+    cursor += 4;
+    let payload = buf[cursor..end]
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ mask[i % 4])
+        .collect();
+    Ok(Some((Frame { opcode, payload }, end)))
+}
+
+/// Write an (unfragmented and unmasked) frame
+fn write_frame(out: &mut Vec<u8>, opcode: u8, payload: &[u8]) {
+    out.push(FLAG_FIN | opcode);
+    match payload.len() {
+        len if len < 126 => out.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+}
+
+/// Send a close frame with the given code
+async fn close<S: BufferedSocketStream>(stream: &mut S, code: u16) -> IoResult<()> {
+    let mut frame = Vec::with_capacity(4);
+    self::write_frame(&mut frame, OPCODE_CLOSE, &code.to_be_bytes());
+    stream.write_all(&frame).await?;
+    stream.flush().await
+}
+
+/// Move data between the WebSocket and the tunnelled connection until either of them is
+/// closed
+async fn pump<S: BufferedSocketStream>(
+    stream: &mut S,
+    buffer: &mut BytesMut,
+    tunnel: DuplexStream,
+    termination_signal: &mut broadcast::Receiver<()>,
+) -> IoResult<()> {
+    let (mut tunnel_rx, mut tunnel_tx) = tio::split(tunnel);
+    // payloads that are yet to be written to the tunnel. We won't read from the client till
+    // they're written, but we'll keep sending responses so that the pipe doesn't get stuck
+    let mut inbound: Vec<u8> = Vec::new();
+    let mut outbound = BytesMut::with_capacity(connection::BUF_READ_CAP);
+    loop {
+        // handle the frames that we have (the client may have sent some right after the
+        // upgrade request)
+        while inbound.is_empty() {
+            let (frame, len) = match self::parse_frame(buffer) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(code) => return self::close(stream, code).await,
+            };
+            buffer.advance(len);
+            match frame.opcode {
+                OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY => inbound = frame.payload,
+                OPCODE_PING => {
+                    let mut pong = Vec::with_capacity(frame.payload.len() + 2);
+                    self::write_frame(&mut pong, OPCODE_PONG, &frame.payload);
+                    stream.write_all(&pong).await?;
+                    stream.flush().await?;
+                }
+                OPCODE_PONG => {}
+                OPCODE_CLOSE => {
+                    // echo the close code back (if there's one)
+                    let mut reply = Vec::with_capacity(4);
+                    self::write_frame(
+                        &mut reply,
+                        OPCODE_CLOSE,
+                        frame.payload.get(..2).unwrap_or_default(),
+                    );
+                    stream.write_all(&reply).await?;
+                    return stream.flush().await;
+                }
+                _ => return self::close(stream, CLOSE_PROTOCOL_ERROR).await,
+            }
+        }
+        tokio::select! {
+            read = stream.read_buf(buffer), if inbound.is_empty() => {
+                if read? == 0 {
+                    return Ok(());
+                }
+            }
+            written = tunnel_tx.write(&inbound), if !inbound.is_empty() => {
+                let written = written?;
+                inbound.drain(..written);
+            }
+            read = tunnel_rx.read_buf(&mut outbound) => {
+                if read? == 0 {
+                    // the server closed the connection
+                    return self::close(stream, CLOSE_NORMAL).await;
+                }
+                let mut frame = Vec::with_capacity(outbound.len() + 10);
+                self::write_frame(&mut frame, OPCODE_BINARY, &outbound);
+                outbound.clear();
+                stream.write_all(&frame).await?;
+                stream.flush().await?;
+            }
+            _ = termination_signal.recv() => {
+                return self::close(stream, CLOSE_GOING_AWAY).await;
+            }
+        }
+    }
+}
+
+impl<S: BufferedSocketStream + Send + 'static> HttpConnection<S> {
+    /// Accept the upgrade request and tunnel a Skyhash connection over the WebSocket
+    pub(super) async fn tunnel(&mut self, key: &str) -> IoResult<()> {
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Accept: {}\r\n\r\n",
+            self::accept_key(key)
+        );
+        self.stream.write_all(response.as_bytes()).await?;
+        self.stream.flush().await?;
+        let (tunnel, handler_end) = tio::duplex(TUNNEL_CAPACITY);
+        // the handler returns the permit once it's done
+        handshake::spawn_with::<DuplexStream, Skyhash2>(
+            self.db.clone(),
+            self.auth.clone(),
+            self.climit.clone(),
+            self.termination_signal.resubscribe(),
+            self._term_sig_tx.clone(),
+            self.limits,
+            Some(self.peer),
+            // the WebSocket is still open, so its admission covers the tunnel
+            None,
+            handler_end,
+        );
+        self.tunnelled = true;
+        self::pump(
+            &mut self.stream,
+            &mut self.buffer,
+            tunnel,
+            &mut self.termination_signal,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accept_key, parse_frame, write_frame, Frame, CLOSE_PROTOCOL_ERROR};
+
+    #[test]
+    fn test_accept_key() {
+        // the example from RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+    #[test]
+    fn test_parse_frame() {
+        // a masked "Hello" (from RFC 6455)
+        let frame = b"\x81\x85\x37\xfa\x21\x3d\x7f\x9f\x4d\x51\x58";
+        assert_eq!(
+            parse_frame(frame).unwrap(),
+            Some((
+                Frame {
+                    opcode: 0x1,
+                    payload: b"Hello".to_vec()
+                },
+                frame.len()
+            ))
+        );
+        assert_eq!(parse_frame(&frame[..6]).unwrap(), None);
+        // a 256 byte binary frame with a zero mask
+        let mut frame = b"\x82\xfe\x01\x00\0\0\0\0".to_vec();
+        frame.extend_from_slice(&[b'x'; 256]);
+        let (parsed, len) = parse_frame(&frame).unwrap().unwrap();
+        assert_eq!(parsed.payload, [b'x'; 256]);
+        assert_eq!(len, frame.len());
+        // unmasked
+        assert_eq!(
+            parse_frame(b"\x81\x05Hello").unwrap_err(),
+            CLOSE_PROTOCOL_ERROR
+        );
+        // a fragmented ping
+        assert_eq!(
+            parse_frame(b"\x09\x80\0\0\0\0").unwrap_err(),
+            CLOSE_PROTOCOL_ERROR
+        );
+    }
+    #[test]
+    fn test_write_frame() {
+        let mut out = Vec::new();
+        write_frame(&mut out, 0x2, b"hey");
+        assert_eq!(out, b"\x82\x03hey");
+        let mut out = Vec::new();
+        write_frame(&mut out, 0x2, &[0; 300]);
+        assert_eq!(&out[..4], b"\x82\x7e\x01\x2c");
+        assert_eq!(out.len(), 304);
+    }
+}
@@ -26,24 +26,47 @@
 
 use {
     super::{
+        admission::{Admission, AdmissionGuard},
+        http::HttpListener,
         tcp::{Listener, ListenerV1},
-        tls::{SslListener, SslListenerV1},
+        tls::{self, SslListener, SslListenerV1},
     },
     crate::{
         auth::AuthProvider,
-        config::{PortConfig, ProtocolVersion, SslOpts},
+        config::{HttpConfig, LimitsConfig, PortConfig, ProtocolVersion, SslOpts},
         corestore::Corestore,
-        util::error::{Error, SkyResult},
+        util::{
+            error::{Error, SkyResult},
+            os,
+        },
         IoResult,
     },
     core::future::Future,
-    std::{net::IpAddr, sync::Arc},
+    parking_lot::{const_mutex, const_rwlock, Mutex, RwLock},
+    std::{
+        net::{IpAddr, SocketAddr},
+        sync::{Arc, Weak},
+    },
     tokio::{
-        net::TcpListener,
+        net::{TcpListener, TcpStream},
         sync::{broadcast, mpsc, Semaphore},
     },
 };
 
+/// The limits for the connections that are accepted from here on (see [`set_limits`])
+static LIMITS: RwLock<LimitsConfig> = const_rwlock(LimitsConfig::default());
+/// The admission control shared by the listeners (see [`set_limits`])
+static ADMISSION: Mutex<Option<Weak<Admission>>> = const_mutex(None);
+
+/// Change the limits for the connections that are accepted from here on. The connections that
+/// are already open keep the limits that they were accepted with
+pub fn set_limits(limits: LimitsConfig) {
+    *LIMITS.write() = limits;
+    if let Some(admission) = ADMISSION.lock().as_ref().and_then(Weak::upgrade) {
+        admission.set_config(limits.admission);
+    }
+}
+
 /// The base TCP listener
 pub struct BaseListener {
     /// An atomic reference to the coretable
@@ -56,6 +79,8 @@ pub struct BaseListener {
     pub climit: Arc<Semaphore>,
     /// The shutdown broadcaster
     pub signal: broadcast::Sender<()>,
+    /// The per-IP connection limits (shared with the other listeners)
+    pub admission: Arc<Admission>,
     // When all `Sender`s are dropped - the `Receiver` gets a `None` value
     // We send a clone of `terminate_tx` to each `CHandler`
     pub terminate_tx: mpsc::Sender<()>,
@@ -63,6 +88,7 @@ pub struct BaseListener {
 }
 
 impl BaseListener {
+    #[allow(clippy::too_many_arguments)]
     pub async fn init(
         db: &Corestore,
         auth: AuthProvider,
@@ -70,6 +96,7 @@ impl BaseListener {
         port: u16,
         semaphore: Arc<Semaphore>,
         signal: broadcast::Sender<()>,
+        admission: Arc<Admission>,
     ) -> SkyResult<Self> {
         let (terminate_tx, terminate_rx) = mpsc::channel(1);
         let listener = TcpListener::bind((host, port))
@@ -81,10 +108,29 @@ impl BaseListener {
             listener,
             climit: semaphore,
             signal,
+            admission,
             terminate_tx,
             terminate_rx,
         })
     }
+    /// Returns the limits for the connections that are accepted now
+    pub fn limits(&self) -> LimitsConfig {
+        *LIMITS.read()
+    }
+    /// Admit a freshly accepted stream, setting it up if it's let in. Returns `None` if the
+    /// stream has to be closed (its IP exceeded the limits)
+    pub fn admit(&self, stream: &TcpStream, addr: SocketAddr) -> Option<AdmissionGuard> {
+        let guard = self.admission.admit(addr.ip()).ok()?;
+        self.configure_stream(stream);
+        Some(guard)
+    }
+    /// Set the socket options for a freshly accepted stream. These are nice to have, so we
+    /// won't let an error here fail the connection
+    fn configure_stream(&self, stream: &TcpStream) {
+        if let Some(idle) = self.limits().keepalive {
+            let _ = os::set_tcp_keepalive(stream, idle);
+        }
+    }
     pub async fn release_self(self) {
         let Self {
             mut terminate_rx,
@@ -153,6 +199,7 @@ impl MultiListener {
                     ssl.chain,
                     base,
                     ssl.passfile,
+                    ssl.clientca,
                 )?;
                 MultiListener::SecureOnly(listener)
             }
@@ -162,6 +209,7 @@ impl MultiListener {
                     ssl.chain,
                     base,
                     ssl.passfile,
+                    ssl.clientca,
                 )?;
                 MultiListener::SecureOnlyV1(listener)
             }
@@ -182,6 +230,7 @@ impl MultiListener {
                     ssl.chain,
                     ssl_base_listener,
                     ssl.passfile,
+                    ssl.clientca,
                 )?;
                 let insecure_listener = Listener::new(tcp_base_listener);
                 MultiListener::Multi(insecure_listener, secure_listener)
@@ -192,6 +241,7 @@ impl MultiListener {
                     ssl.chain,
                     ssl_base_listener,
                     ssl.passfile,
+                    ssl.clientca,
                 )?;
                 let insecure_listener = ListenerV1::new(tcp_base_listener);
                 MultiListener::MultiV1(insecure_listener, secure_listener)
@@ -239,16 +289,55 @@ impl MultiListener {
     }
 }
 
+/// The listeners run by the server: the Skyhash listener(s) and the HTTP gateway, if it's
+/// enabled
+pub struct Server {
+    skyhash: MultiListener,
+    http: Option<HttpListener>,
+}
+
+impl Server {
+    /// Start the server
+    pub async fn run_server(&mut self) -> IoResult<()> {
+        match &mut self.http {
+            Some(http) => {
+                let (skyhash, http) = tokio::join!(self.skyhash.run_server(), http.run());
+                if let Err(e) = http {
+                    log::error!("HTTP gateway failed with: {}", e);
+                }
+                skyhash
+            }
+            None => self.skyhash.run_server().await,
+        }
+    }
+    /// Signal the listeners to shut down and only return after they have shut down
+    ///
+    /// **Do note:** This function doesn't flush the `Corestore` object! The **caller has to
+    /// make sure that the data is saved!**
+    pub async fn finish_with_termsig(self) {
+        self.skyhash.finish_with_termsig().await;
+        if let Some(http) = self.http {
+            http.base.release_self().await;
+        }
+    }
+}
+
 /// Initialize the database networking
+#[allow(clippy::too_many_arguments)]
 pub async fn connect(
     ports: PortConfig,
     protocol: ProtocolVersion,
+    http: HttpConfig,
     maxcon: usize,
+    limits: LimitsConfig,
     db: Corestore,
     auth: AuthProvider,
     signal: broadcast::Sender<()>,
-) -> SkyResult<MultiListener> {
+) -> SkyResult<Server> {
     let climit = Arc::new(Semaphore::new(maxcon));
+    let admission = Arc::new(Admission::new(limits.admission));
+    self::set_limits(limits);
+    *ADMISSION.lock() = Some(Arc::downgrade(&admission));
     let base_listener_init = |host, port| {
         BaseListener::init(
             &db,
@@ -257,10 +346,26 @@ pub async fn connect(
             port,
             climit.clone(),
             signal.clone(),
+            admission.clone(),
         )
     };
     let description = ports.get_description();
-    let server = match ports {
+    let host = ports.get_host();
+    // the gateway uses the certificate of the server (the config makes sure that there's one),
+    // but it doesn't ask for client certificates
+    let http_acceptor = match (&http, &ports) {
+        (
+            HttpConfig::Enabled { secure: true, .. },
+            PortConfig::SecureOnly { ssl, .. } | PortConfig::Multi { ssl, .. },
+        ) => Some(tls::ReloadableAcceptor::new(
+            ssl.key.clone(),
+            ssl.chain.clone(),
+            ssl.passfile.clone(),
+            None,
+        )?),
+        _ => None,
+    };
+    let skyhash = match ports {
         PortConfig::InsecureOnly { host, port } => {
             MultiListener::new_insecure_only(base_listener_init(host, port).await?, protocol)
         }
@@ -276,5 +381,14 @@ pub async fn connect(
         }
     };
     log::info!("Server started on {description}");
-    Ok(server)
+    let http = match http {
+        HttpConfig::Enabled { port, secure } => {
+            let listener = HttpListener::new(base_listener_init(host, port).await?, http_acceptor);
+            let scheme = if secure { "https" } else { "http" };
+            log::info!("HTTP gateway started on {scheme}://{host}:{port}");
+            Some(listener)
+        }
+        HttpConfig::Disabled => None,
+    };
+    Ok(Server { skyhash, http })
 }
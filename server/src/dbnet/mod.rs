@@ -25,17 +25,30 @@
 */
 
 use {
-    self::connection::Connection,
+    self::{clients::Client, connection::Connection},
     crate::{
         actions::{ActionError, ActionResult},
         auth::AuthProvider,
+        cdc,
+        config::LimitsConfig,
         corestore::Corestore,
-        protocol::{interface::ProtocolSpec, Query},
+        logging::{self, RequestSpan},
+        protocol::{interface::ProtocolSpec, responses, Query, QueryLimits, Skyhash2},
         util::compiler,
         IoResult,
     },
-    bytes::Buf,
-    std::{cell::Cell, sync::Arc, time::Duration},
+    bytes::{Buf, BytesMut},
+    core::mem,
+    std::{
+        cell::Cell,
+        io::Cursor,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
     tokio::{
         io::{AsyncReadExt, AsyncWriteExt},
         sync::{
@@ -49,15 +62,28 @@ use {
 
 pub type QueryWithAdvance = (Query, usize);
 pub const MAXIMUM_CONNECTION_LIMIT: usize = 50000;
+/// The ID of the next request (IDs are never reused)
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
 use crate::queryengine;
 
-pub use self::listener::connect;
+pub use self::{
+    http::write_json_blob,
+    listener::{connect, set_limits},
+    tls::reload_certificates,
+};
 
+pub mod admission;
+pub mod clients;
 mod connection;
 #[macro_use]
 mod macros;
+mod handshake;
+mod http;
 mod listener;
+pub mod monitor;
+pub mod peer;
 pub mod prelude;
+pub mod pubsub;
 mod tcp;
 mod tls;
 
@@ -69,10 +95,18 @@ pub trait BufferedSocketStream: AsyncWriteExt + AsyncReadExt + Unpin {}
 enum QueryResult {
     /// A [`Query`] read to be run
     Q(QueryWithAdvance),
+    /// A pub/sub message to be pushed to the client
+    Push(pubsub::Message),
+    /// A query fed to the monitor, to be pushed to the client
+    Monitor(Arc<monitor::Event>),
+    /// A captured change (or news of lost ones), to be pushed to the client
+    Cdc(cdc::Delivery),
     /// Simply proceed to the next run loop iter
     NextLoop,
     /// The client disconnected
     Disconnected,
+    /// The connection has to be closed (it exceeded a limit)
+    Close,
 }
 
 /// A backoff implementation that is meant to be used in connection loops
@@ -107,7 +141,8 @@ pub struct AuthProviderHandle {
 
 impl AuthProviderHandle {
     pub fn new(provider: AuthProvider) -> Self {
-        let auth_good = !provider.is_enabled();
+        // the connection might have been logged in already (with a client certificate)
+        let auth_good = !provider.is_enabled() || provider.is_logged_in();
         Self {
             provider,
             auth_good,
@@ -145,6 +180,8 @@ pub struct ConnectionHandler<C, P> {
     climit: Arc<Semaphore>,
     /// the authentication handle
     auth: AuthProviderHandle,
+    /// the registration of this connection in the client registry
+    client: Client,
     /// check for termination signals
     termination_signal: broadcast::Receiver<()>,
     /// the sender that we drop when we're done with handling a connection (used for gracefule exit)
@@ -159,28 +196,40 @@ where
     /// Create a new connection handler
     pub fn new(
         db: Corestore,
-        con: Connection<C, P>,
+        mut con: Connection<C, P>,
         auth_data: AuthProvider,
         climit: Arc<Semaphore>,
         termination_signal: broadcast::Receiver<()>,
         _term_sig_tx: mpsc::Sender<()>,
     ) -> Self {
+        let client = Client::register(con.peer());
+        con.set_client_id(client.id());
         Self {
             db,
             con,
             climit,
             auth: AuthProviderHandle::new(auth_data),
+            client,
             termination_signal,
             _term_sig_tx,
         }
     }
+    /// Serve the connection (within its log span) till it's closed
     pub async fn run(&mut self) -> IoResult<()> {
+        let (client, peer) = (self.client.id(), self.con.peer());
+        logging::in_connection(client, peer, self.serve()).await
+    }
+    async fn serve(&mut self) -> IoResult<()> {
         loop {
             let packet = tokio::select! {
                 pkt = self.con.read_query() => pkt,
                 _ = self.termination_signal.recv() => {
                     return Ok(());
                 }
+                _ = self.client.killed() => {
+                    // an admin killed this connection
+                    return Ok(());
+                }
             };
             match packet {
                 Ok(QueryResult::Q((query, advance))) => {
@@ -193,12 +242,32 @@ where
                     #[cfg(debug_assertions)]
                     let eptr_at_start = sptr_at_start + len_at_start;
                     {
-                        // The actual execution (the assertions are just debug build sanity checks)
-                        match self.execute_query(query).await {
-                            Ok(()) => {}
-                            Err(ActionError::ActionError(e)) => self.con.write_error(e).await?,
-                            Err(ActionError::IoError(e)) => return Err(e),
+                        // The actual execution (the assertions are just debug build sanity checks).
+                        // Queries can park the connection (`WATCH`, for example), so we'll keep
+                        // looking out for termination signals
+                        let Self {
+                            db,
+                            con,
+                            auth,
+                            client,
+                            termination_signal,
+                            ..
+                        } = self;
+                        let request = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+                        con.set_request_id(request);
+                        let span = RequestSpan::enter(request, db.get_ids());
+                        tokio::select! {
+                            ret = Self::run_query(db, con, auth, query) => ret?,
+                            _ = termination_signal.recv() => {
+                                return Ok(());
+                            }
+                            _ = client.killed() => {
+                                return Ok(());
+                            }
                         }
+                        let action = con.take_last_action();
+                        client.touch(auth.provider().current_user(), db.get_ids(), action);
+                        span.finish(action);
                     }
                     {
                         // do these assertions to ensure memory safety (this is just for sanity sake)
@@ -223,25 +292,61 @@ where
                         self.con.buffer.advance(advance);
                     }
                 }
-                Ok(QueryResult::Disconnected) => return Ok(()),
+                Ok(QueryResult::Push(message)) => self.con.write_push_frame(message).await?,
+                Ok(QueryResult::Monitor(event)) => self.con.write_monitor_frame(&event).await?,
+                Ok(QueryResult::Cdc(delivery)) => self.con.write_cdc_frame(&delivery).await?,
+                Ok(QueryResult::Disconnected | QueryResult::Close) => return Ok(()),
                 Ok(QueryResult::NextLoop) => {}
                 Err(e) => return Err(e),
             }
         }
     }
-    async fn execute_query(&mut self, query: Query) -> ActionResult<()> {
-        let Self { db, con, auth, .. } = self;
+    /// Run a query and write its response (or the error that it failed with)
+    async fn run_query(
+        db: &mut Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        query: Query,
+    ) -> IoResult<()> {
+        match Self::execute_query(db, con, auth, query).await {
+            Ok(()) => Ok(()),
+            Err(ActionError::ActionError(e)) => con.write_error(e).await,
+            Err(ActionError::ArityError(e)) => {
+                con.write_error(&responses::arity_error::<P>(&e)).await
+            }
+            Err(ActionError::Moved(moved)) => con.write_error(&responses::moved::<P>(&moved)).await,
+            Err(ActionError::IoError(e)) => Err(e),
+        }
+    }
+    async fn execute_query(
+        db: &mut Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        query: Query,
+    ) -> ActionResult<()> {
+        if con.sends_request_id_frames() {
+            con.write_request_id_frame().await?;
+        }
         match query {
             Query::Simple(q) => {
                 con.write_simple_query_header().await?;
                 if compiler::likely(auth.authenticated()) {
+                    if compiler::unlikely(!auth.provider().try_run_queries(1)) {
+                        return Err(ActionError::ActionError(P::RSTRING_THROTTLED));
+                    }
                     queryengine::execute_simple(db, con, auth, q).await?;
                 } else {
                     queryengine::execute_simple_noauth(db, con, auth, q).await?;
                 }
             }
             Query::Pipelined(p) => {
-                if compiler::likely(auth.authenticated()) {
+                if compiler::unlikely(
+                    auth.authenticated() && !auth.provider().try_run_queries(p.len()),
+                ) {
+                    // the pipeline is turned away as a whole
+                    con.write_simple_query_header().await?;
+                    con.write_error(P::RSTRING_THROTTLED).await?;
+                } else if compiler::likely(auth.authenticated()) {
                     con.write_pipelined_query_header(p.len()).await?;
                     queryengine::execute_pipeline(db, con, auth, p).await?;
                 } else {
@@ -250,11 +355,47 @@ where
                 }
             }
         }
-        con.stream.flush().await?;
         Ok(())
     }
 }
 
+/// Queries that don't come in over a connection write their responses to memory
+impl BufferedSocketStream for Cursor<Vec<u8>> {}
+
+/// Run a Skyhash 2.0 query packet on a connection of its own and return the response. This is
+/// how the HTTP gateway runs its queries, and how the write-ahead log is replayed (on an
+/// internal connection; see [`Connection::set_internal`])
+pub(crate) async fn execute_packet(
+    db: &Corestore,
+    auth: &mut AuthProviderHandle,
+    peer: Option<SocketAddr>,
+    packet: &[u8],
+    limits: QueryLimits,
+    internal: bool,
+) -> IoResult<Vec<u8>> {
+    let query = match Skyhash2::decode_packet(packet, limits) {
+        Ok((query, _)) => query,
+        Err(_) => return Ok(Skyhash2::FULLRESP_RCODE_PACKET_ERR.to_vec()),
+    };
+    let mut db = db.clone();
+    let mut con = Connection::new(
+        Cursor::new(Vec::new()),
+        BytesMut::new(),
+        LimitsConfig {
+            query: limits,
+            ..LimitsConfig::default()
+        },
+    );
+    con.set_peer(peer);
+    if internal {
+        con.set_internal();
+    }
+    ConnectionHandler::<Cursor<Vec<u8>>, Skyhash2>::run_query(&mut db, &mut con, auth, query)
+        .await?;
+    con.flush().await?;
+    Ok(mem::take(con.stream.get_mut().get_mut()))
+}
+
 impl<C, T> Drop for ConnectionHandler<C, T> {
     fn drop(&mut self) {
         // Make sure that the permit is returned to the semaphore
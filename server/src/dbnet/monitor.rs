@@ -0,0 +1,257 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Monitor
+//!
+//! A connection that runs `SYS MONITOR ON` is sent every query that's run on the server from
+//! then on, till it runs `SYS MONITOR OFF` (or disconnects). It can also ask for just the
+//! queries that are run on a keyspace (`SYS MONITOR ON <keyspace>`) or a table
+//! (`SYS MONITOR ON <keyspace>.<table>`). Queries are delivered as push frames, like pub/sub
+//! messages, so a monitoring connection can keep running queries as usual.
+//!
+//! The queries are fed to the monitors through a broadcast channel that's tapped right before
+//! a query is dispatched, and only when somebody is monitoring. A monitor that falls behind by
+//! more than [`FEED_CAPACITY`] queries silently misses the oldest ones. The arguments of `AUTH`
+//! queries are never fed (just the action and the subaction) since they can hold tokens, and
+//! the queries queued in a transaction are only seen through the `EXEC` that runs them
+
+use {
+    crate::corestore::memstore::ObjectID,
+    chrono::Utc,
+    core::sync::atomic::{AtomicUsize, Ordering},
+    parking_lot::{const_mutex, Mutex},
+    std::{net::SocketAddr, sync::Arc},
+    tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender},
+};
+
+/// The number of queries that a monitor can fall behind by before it starts missing them
+const FEED_CAPACITY: usize = 1024;
+/// The number of connections that are monitoring
+static MONITORS: AtomicUsize = AtomicUsize::new(0);
+/// The feed (created when the first monitor starts)
+static FEED: Mutex<Option<Sender<Arc<Event>>>> = const_mutex(None);
+
+#[derive(Debug, PartialEq, Eq)]
+/// A query that was run on the server
+pub struct Event {
+    /// when the query was run, as a UNIX timestamp in milliseconds
+    pub timestamp: u64,
+    /// the ID of the client that ran it (if it's registered)
+    pub client: Option<u64>,
+    /// the ID of the request that the query was a part of (if it has one)
+    pub request: Option<u64>,
+    /// the address of the client that ran it (if it's known)
+    pub peer: Option<SocketAddr>,
+    pub keyspace: Option<ObjectID>,
+    pub table: Option<ObjectID>,
+    /// the action and its arguments
+    pub args: Vec<Vec<u8>>,
+}
+
+impl Event {
+    /// Returns the entity that the query was run on, as `keyspace` or `keyspace.table`
+    pub fn entity(&self) -> Vec<u8> {
+        let mut entity = Vec::new();
+        if let Some(keyspace) = &self.keyspace {
+            entity.extend_from_slice(keyspace);
+            if let Some(table) = &self.table {
+                entity.push(b'.');
+                entity.extend_from_slice(table);
+            }
+        }
+        entity
+    }
+}
+
+/// Returns true if any connection is monitoring (so that the dispatch path can skip
+/// [`publish`] otherwise)
+pub fn is_active() -> bool {
+    MONITORS.load(Ordering::Relaxed) != 0
+}
+
+/// Feed a query to the monitors
+pub fn publish(
+    client: Option<u64>,
+    request: Option<u64>,
+    peer: Option<SocketAddr>,
+    (keyspace, table): (Option<&ObjectID>, Option<&ObjectID>),
+    args: &[&[u8]],
+) {
+    let feed = match FEED.lock().as_ref() {
+        Some(feed) => feed.clone(),
+        None => return,
+    };
+    let args = match args.split_first() {
+        // only the subaction of an `AUTH` query is fed
+        Some((action, _)) if action.eq_ignore_ascii_case(b"auth") => &args[..args.len().min(2)],
+        _ => args,
+    };
+    let event = Event {
+        timestamp: Utc::now().timestamp_millis() as u64,
+        client,
+        request,
+        peer,
+        keyspace: keyspace.cloned(),
+        table: table.cloned(),
+        args: args.iter().map(|arg| arg.to_vec()).collect(),
+    };
+    // there's no one to receive this if the last monitor stopped in the meantime
+    let _ = feed.send(Arc::new(event));
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The entity whose queries a monitor is interested in
+pub struct Filter {
+    keyspace: Vec<u8>,
+    table: Option<Vec<u8>>,
+}
+
+impl Filter {
+    /// Parse a filter (`keyspace` or `keyspace.table`)
+    pub fn parse(entity: &[u8]) -> Self {
+        match entity.iter().position(|byte| *byte == b'.') {
+            Some(dot) => Self {
+                keyspace: entity[..dot].to_vec(),
+                table: Some(entity[dot + 1..].to_vec()),
+            },
+            None => Self {
+                keyspace: entity.to_vec(),
+                table: None,
+            },
+        }
+    }
+    fn accepts(&self, event: &Event) -> bool {
+        event.keyspace.as_deref() == Some(self.keyspace.as_slice())
+            && self
+                .table
+                .as_deref()
+                .is_none_or(|table| event.table.as_deref() == Some(table))
+    }
+}
+
+#[derive(Debug)]
+/// A monitoring connection's end of the feed. The connection stops monitoring when this is
+/// dropped
+pub struct Monitor {
+    rx: Receiver<Arc<Event>>,
+    filter: Option<Filter>,
+}
+
+impl Monitor {
+    /// Start monitoring (just the queries run on the entity of the filter, if there's one)
+    pub fn start(filter: Option<Filter>) -> Self {
+        let rx = FEED
+            .lock()
+            .get_or_insert_with(|| broadcast::channel(FEED_CAPACITY).0)
+            .subscribe();
+        MONITORS.fetch_add(1, Ordering::Relaxed);
+        Self { rx, filter }
+    }
+    /// Wait for the next query that passes the filter
+    pub async fn recv(&mut self) -> Arc<Event> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) if self.filter.as_ref().is_none_or(|f| f.accepts(&event)) => {
+                    return event
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                // the feed is never dropped, so this won't happen
+                Err(RecvError::Closed) => core::future::pending().await,
+            }
+        }
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        MONITORS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{is_active, publish, Filter, Monitor},
+        crate::corestore::memstore::ObjectID,
+    };
+
+    #[test]
+    fn filter_parse() {
+        assert_eq!(
+            Filter::parse(b"twitter.users"),
+            Filter {
+                keyspace: b"twitter".to_vec(),
+                table: Some(b"users".to_vec()),
+            }
+        );
+        assert_eq!(
+            Filter::parse(b"twitter"),
+            Filter {
+                keyspace: b"twitter".to_vec(),
+                table: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn monitor_feed() {
+        let keyspace = ObjectID::try_from_slice(b"monitorks").unwrap();
+        let table = ObjectID::try_from_slice(b"monitortbl").unwrap();
+        let other = ObjectID::try_from_slice(b"monitorother").unwrap();
+        let mut monitor = Monitor::start(Some(Filter::parse(b"monitorks.monitortbl")));
+        assert!(is_active());
+        // filtered out
+        publish(
+            None,
+            None,
+            None,
+            (Some(&keyspace), Some(&other)),
+            &[b"GET", b"x"],
+        );
+        publish(
+            Some(7),
+            Some(42),
+            None,
+            (Some(&keyspace), Some(&table)),
+            &[b"SET", b"x", b"y"],
+        );
+        let event = monitor.recv().await;
+        assert_eq!(event.client, Some(7));
+        assert_eq!(event.request, Some(42));
+        assert_eq!(event.entity(), b"monitorks.monitortbl");
+        assert_eq!(event.args, [b"SET".to_vec(), b"x".to_vec(), b"y".to_vec()]);
+        // tokens are never fed
+        publish(
+            None,
+            None,
+            None,
+            (Some(&keyspace), Some(&table)),
+            &[b"auth", b"login", b"root", b"token"],
+        );
+        let event = monitor.recv().await;
+        assert_eq!(event.args, [b"auth".to_vec(), b"login".to_vec()]);
+    }
+}
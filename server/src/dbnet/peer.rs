@@ -0,0 +1,135 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Peers
+//!
+//! Connections that we make to other servers, over Skyhash 2.0 and plain TCP: a replica
+//! connects to its primary (see [`crate::replication`]), and cluster nodes connect to each other
+//! (see [`crate::cluster`]). Queries are sent one at a time, and their responses are read before
+//! the next one is sent
+
+use {
+    crate::{
+        protocol::{interface::ProtocolSpec, Skyhash2},
+        IoResult,
+    },
+    std::io::{Error as IoError, ErrorKind},
+    tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+        time::{self, Duration},
+    },
+};
+
+/// Asks the server to speak Skyhash 2.0
+const HANDSHAKE: &[u8] = b"H2.0\n";
+/// The longest that we wait for a connection to another server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection to another server
+pub type Peer = BufReader<TcpStream>;
+
+/// Connect to the server at the given address, and log in with the given user and token (if
+/// there's a login)
+pub async fn connect(host: &str, port: u16, login: Option<&(Vec<u8>, Vec<u8>)>) -> IoResult<Peer> {
+    let connect = TcpStream::connect((host, port));
+    let stream = time::timeout(CONNECT_TIMEOUT, connect)
+        .await
+        .map_err(|_| IoError::new(ErrorKind::TimedOut, "timed out while connecting"))??;
+    stream.set_nodelay(true)?;
+    let mut stream = BufReader::new(stream);
+    stream.write_all(HANDSHAKE).await?;
+    let mut ack = [0; HANDSHAKE.len()];
+    stream.read_exact(&mut ack).await?;
+    if ack != HANDSHAKE {
+        return Err(IoError::new(
+            ErrorKind::InvalidData,
+            "the server doesn't speak Skyhash 2.0",
+        ));
+    }
+    if let Some((user, token)) = login {
+        self::query(
+            &mut stream,
+            &[b"AUTH", b"LOGIN", user.as_slice(), token.as_slice()],
+        )
+        .await?;
+    }
+    Ok(stream)
+}
+
+/// Run a query, failing if it doesn't return okay
+pub async fn query(stream: &mut Peer, query: &[&[u8]]) -> IoResult<()> {
+    let response = self::send(stream, query).await?;
+    if response.strip_prefix(Skyhash2::SIMPLE_QUERY_HEADER) == Some(Skyhash2::RCODE_OKAY) {
+        Ok(())
+    } else {
+        Err(self::refused(query, &response))
+    }
+}
+
+/// Run a query that returns a binary string, and return it
+pub async fn query_binary(stream: &mut Peer, query: &[&[u8]]) -> IoResult<Vec<u8>> {
+    let header = self::send(stream, query).await?;
+    let len = header
+        .strip_prefix(Skyhash2::SIMPLE_QUERY_HEADER)
+        .and_then(|header| header.strip_prefix(&[Skyhash2::TSYMBOL_BINARY]))
+        .and_then(|len| len.strip_suffix(b"\n"))
+        .and_then(|len| std::str::from_utf8(len).ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    let len = match len {
+        Some(len) => len,
+        None => return Err(self::refused(query, &header)),
+    };
+    // the length isn't trusted with an allocation; the string has to actually be there
+    let mut binary = Vec::new();
+    (&mut *stream).take(len).read_to_end(&mut binary).await?;
+    if binary.len() as u64 != len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    Ok(binary)
+}
+
+/// Send a query, and return the first line of its response
+async fn send(stream: &mut Peer, query: &[&[u8]]) -> IoResult<Vec<u8>> {
+    stream
+        .write_all(&Skyhash2::encode_simple_query(query))
+        .await?;
+    let mut response = Vec::new();
+    stream.read_until(b'\n', &mut response).await?;
+    Ok(response)
+}
+
+fn refused(query: &[&[u8]], response: &[u8]) -> IoError {
+    // only the action is named (so that a token is never logged)
+    IoError::new(
+        ErrorKind::Other,
+        format!(
+            "the server refused `{}`: {}",
+            String::from_utf8_lossy(&query[..2].join(&b' ')),
+            String::from_utf8_lossy(response).trim_end()
+        ),
+    )
+}
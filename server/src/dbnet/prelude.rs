@@ -31,9 +31,15 @@
 pub use {
     super::{connection::Connection, AuthProviderHandle},
     crate::{
-        actions::{ensure_boolean_or_aerr, ensure_length, translate_ddl_error},
+        actions::{
+            ensure_arity, ensure_boolean_or_aerr, ensure_subcommand_arity, translate_ddl_error,
+            translate_read_error, Arity,
+        },
         corestore::{
-            table::{KVEBlob, KVEList},
+            table::{
+                KVEBlob, KVEBloom, KVECounter, KVEGeo, KVEHash, KVEHll, KVEList, KVESet,
+                KVETimeseries, KVEZset,
+            },
             Corestore,
         },
         get_tbl, handle_entity, is_lowbit_set,
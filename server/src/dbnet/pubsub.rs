@@ -0,0 +1,239 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Pub/Sub
+//!
+//! Connections can subscribe to any number of channels (with `SUBSCRIBE`) and then receive the
+//! messages published to those channels (with `PUBLISH`) by any other connection. Messages are
+//! delivered as push frames, in between query responses, so a subscribed connection can keep
+//! running queries as usual.
+//!
+//! Every subscribed connection has a bounded mailbox. If a subscriber is too slow to drain its
+//! mailbox, new messages for it are dropped instead of stalling the publisher. Messages are
+//! not persisted, so only the connections that are subscribed when a message is published
+//! receive it
+
+use {
+    crate::corestore::SharedSlice,
+    parking_lot::RwLock,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+    tokio::sync::mpsc::{self, Receiver, Sender},
+};
+
+/// The number of messages that can be queued for a subscriber before new messages are dropped
+const MAILBOX_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A message published to a channel
+pub struct Message {
+    pub channel: SharedSlice,
+    pub payload: SharedSlice,
+}
+
+/// The subscribers of a channel, keyed by their subscriber IDs
+type Subscribers = HashMap<u64, Sender<Message>>;
+
+#[derive(Debug, Default)]
+/// The pub/sub hub that is shared by all connections
+pub struct PubSub {
+    /// the ID to be assigned to the next subscriber
+    next_id: AtomicU64,
+    channels: RwLock<HashMap<SharedSlice, Subscribers>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn subscribe(&self, channel: SharedSlice, id: u64, tx: &Sender<Message>) {
+        self.channels
+            .write()
+            .entry(channel)
+            .or_default()
+            .insert(id, tx.clone());
+    }
+    fn unsubscribe(&self, channel: &[u8], id: u64) {
+        let mut channels = self.channels.write();
+        if let Some(subscribers) = channels.get_mut(channel) {
+            subscribers.remove(&id);
+            if subscribers.is_empty() {
+                // no one's listening anymore, so get rid of the channel
+                channels.remove(channel);
+            }
+        }
+    }
+    /// Publish a message to a channel, returning the number of subscribers that the message
+    /// was queued for
+    pub fn publish(&self, channel: &[u8], payload: &[u8]) -> usize {
+        let channels = self.channels.read();
+        let subscribers = match channels.get(channel) {
+            Some(subscribers) => subscribers,
+            None => return 0,
+        };
+        let (channel, payload) = (SharedSlice::new(channel), SharedSlice::new(payload));
+        subscribers
+            .values()
+            .filter(|tx| {
+                tx.try_send(Message {
+                    channel: channel.clone(),
+                    payload: payload.clone(),
+                })
+                .is_ok()
+            })
+            .count()
+    }
+    #[cfg(test)]
+    /// Returns the number of channels that have at least one subscriber
+    pub fn channel_count(&self) -> usize {
+        self.channels.read().len()
+    }
+}
+
+/// The subscription state of a connection. Dropping it removes all of its subscriptions
+pub struct Subscriber {
+    id: u64,
+    hub: Arc<PubSub>,
+    tx: Sender<Message>,
+    rx: Receiver<Message>,
+    channels: HashSet<SharedSlice>,
+}
+
+impl Subscriber {
+    pub fn new(hub: Arc<PubSub>) -> Self {
+        let id = hub.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(MAILBOX_CAPACITY);
+        Self {
+            id,
+            hub,
+            tx,
+            rx,
+            channels: HashSet::new(),
+        }
+    }
+    /// Subscribe to a channel. Subscribing to a channel more than once has no effect
+    pub fn subscribe(&mut self, channel: SharedSlice) {
+        if !self.channels.contains(&channel) {
+            self.hub.subscribe(channel.clone(), self.id, &self.tx);
+            self.channels.insert(channel);
+        }
+    }
+    /// Unsubscribe from a channel. Returns false if we weren't subscribed to it
+    pub fn unsubscribe(&mut self, channel: &[u8]) -> bool {
+        let removed = self.channels.remove(channel);
+        if removed {
+            self.hub.unsubscribe(channel, self.id);
+        }
+        removed
+    }
+    /// Returns the number of channels that we're subscribed to
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+    /// Wait for the next message. Since we hold a sender ourselves, this never returns `None`
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.rx.recv().await
+    }
+    #[cfg(test)]
+    /// Returns the next message, if one is queued
+    pub fn try_recv(&mut self) -> Option<Message> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        for channel in self.channels.iter() {
+            self.hub.unsubscribe(channel, self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, PubSub, Subscriber, MAILBOX_CAPACITY};
+    use std::sync::Arc;
+
+    fn message(channel: &str, payload: &str) -> Message {
+        Message {
+            channel: channel.into(),
+            payload: payload.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn publish_to_subscribers() {
+        let hub = Arc::new(PubSub::new());
+        let mut first = Subscriber::new(hub.clone());
+        let mut second = Subscriber::new(hub.clone());
+        first.subscribe("news".into());
+        first.subscribe("news".into());
+        second.subscribe("news".into());
+        second.subscribe("sports".into());
+        assert_eq!(first.channel_count(), 1);
+        assert_eq!(second.channel_count(), 2);
+        assert_eq!(hub.publish(b"news", b"hello"), 2);
+        assert_eq!(hub.publish(b"sports", b"goal"), 1);
+        assert_eq!(hub.publish(b"weather", b"rain"), 0);
+        assert_eq!(first.recv().await.unwrap(), message("news", "hello"));
+        assert_eq!(second.recv().await.unwrap(), message("news", "hello"));
+        assert_eq!(second.recv().await.unwrap(), message("sports", "goal"));
+    }
+
+    #[test]
+    fn unsubscribe_and_drop() {
+        let hub = Arc::new(PubSub::new());
+        let mut first = Subscriber::new(hub.clone());
+        let mut second = Subscriber::new(hub.clone());
+        first.subscribe("news".into());
+        second.subscribe("news".into());
+        second.subscribe("sports".into());
+        assert_eq!(hub.channel_count(), 2);
+        assert!(first.unsubscribe(b"news"));
+        assert!(!first.unsubscribe(b"news"));
+        assert_eq!(hub.publish(b"news", b"hello"), 1);
+        drop(second);
+        assert_eq!(hub.channel_count(), 0);
+        assert_eq!(hub.publish(b"news", b"hello"), 0);
+    }
+
+    #[test]
+    fn drop_messages_for_slow_subscribers() {
+        let hub = Arc::new(PubSub::new());
+        let mut subscriber = Subscriber::new(hub.clone());
+        subscriber.subscribe("news".into());
+        for _ in 0..MAILBOX_CAPACITY {
+            assert_eq!(hub.publish(b"news", b"hello"), 1);
+        }
+        // the mailbox is full
+        assert_eq!(hub.publish(b"news", b"hello"), 0);
+    }
+}
@@ -28,11 +28,13 @@ pub use protocol::{ParseResult, Query};
 use {
     super::NetBackoff,
     crate::{
-        dbnet::{listener::BaseListener, BufferedSocketStream, Connection, ConnectionHandler},
+        dbnet::{
+            admission::AdmissionGuard, handshake, listener::BaseListener, BufferedSocketStream,
+        },
         protocol::{self, interface::ProtocolSpec, Skyhash1, Skyhash2},
         IoResult,
     },
-    std::marker::PhantomData,
+    std::{marker::PhantomData, net::SocketAddr},
     tokio::net::TcpStream,
 };
 
@@ -54,13 +56,16 @@ impl<P: ProtocolSpec + 'static> RawListener<P> {
             _marker: PhantomData,
         }
     }
-    /// Accept an incoming connection
-    async fn accept(&mut self) -> IoResult<TcpStream> {
+    /// Accept an incoming connection (that was admitted)
+    async fn accept(&mut self) -> IoResult<(TcpStream, SocketAddr, AdmissionGuard)> {
         let backoff = NetBackoff::new();
         loop {
             match self.base.listener.accept().await {
-                // We don't need the bindaddr
-                Ok((stream, _)) => return Ok(stream),
+                Ok((stream, addr)) => match self.base.admit(&stream, addr) {
+                    Some(guard) => return Ok((stream, addr, guard)),
+                    // turned away, so we drop (close) the stream and wait for the next one
+                    None => continue,
+                },
                 Err(e) => {
                     if backoff.should_disconnect() {
                         // Too many retries, goodbye user
@@ -86,20 +91,8 @@ impl<P: ProtocolSpec + 'static> RawListener<P> {
              can arise and it will flood the log and might also result
              in a crash
             */
-            let stream = skip_loop_err!(self.accept().await);
-            let mut chandle = ConnectionHandler::<TcpStream, P>::new(
-                self.base.db.clone(),
-                Connection::new(stream),
-                self.base.auth.clone(),
-                self.base.climit.clone(),
-                self.base.signal.subscribe(),
-                self.base.terminate_tx.clone(),
-            );
-            tokio::spawn(async move {
-                if let Err(e) = chandle.run().await {
-                    log::error!("Error: {}", e);
-                }
-            });
+            let (stream, addr, guard) = skip_loop_err!(self.accept().await);
+            handshake::spawn::<TcpStream, P>(&self.base, stream, addr, guard);
         }
     }
 }
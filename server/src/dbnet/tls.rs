@@ -26,19 +26,30 @@
 
 use {
     crate::{
+        audit::{self, Event, Outcome},
         dbnet::{
-            listener::BaseListener, BufferedSocketStream, Connection, ConnectionHandler, NetBackoff,
+            admission::AdmissionGuard, handshake, listener::BaseListener, BufferedSocketStream,
+            NetBackoff,
         },
         protocol::{interface::ProtocolSpec, Skyhash1, Skyhash2},
         util::error::{Error, SkyResult},
         IoResult,
     },
     openssl::{
+        nid::Nid,
         pkey::PKey,
         rsa::Rsa,
-        ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod},
+        ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod, SslVerifyMode},
+        x509::{X509Name, X509Ref},
+    },
+    parking_lot::{const_mutex, Mutex, RwLock},
+    std::{
+        fs,
+        marker::PhantomData,
+        net::SocketAddr,
+        pin::Pin,
+        sync::{Arc, Weak},
     },
-    std::{fs, marker::PhantomData, pin::Pin},
     tokio::net::TcpStream,
     tokio_openssl::SslStream,
 };
@@ -50,55 +61,168 @@ pub type SslListenerV1 = SslListenerRaw<Skyhash1>;
 
 pub struct SslListenerRaw<P> {
     pub base: BaseListener,
-    acceptor: SslAcceptor,
+    acceptor: Arc<ReloadableAcceptor>,
     _marker: PhantomData<P>,
 }
 
+/// The acceptors that are rebuilt by [`reload_certificates`]
+static ACCEPTORS: Mutex<Vec<Weak<ReloadableAcceptor>>> = const_mutex(Vec::new());
+
+/// A TLS acceptor that can be rebuilt from its files while the server is running. Connections
+/// that were accepted before a reload keep using the old certificate
+pub struct ReloadableAcceptor {
+    key_file: String,
+    chain_file: String,
+    tls_passfile: Option<String>,
+    client_ca_file: Option<String>,
+    current: RwLock<SslAcceptor>,
+}
+
+impl ReloadableAcceptor {
+    /// Build the acceptor (see [`new_acceptor`]) and register it for reloads
+    pub fn new(
+        key_file: String,
+        chain_file: String,
+        tls_passfile: Option<String>,
+        client_ca_file: Option<String>,
+    ) -> SkyResult<Arc<Self>> {
+        let current = self::new_acceptor(
+            &key_file,
+            &chain_file,
+            tls_passfile.as_deref(),
+            client_ca_file.as_deref(),
+        )?;
+        let slf = Arc::new(Self {
+            key_file,
+            chain_file,
+            tls_passfile,
+            client_ca_file,
+            current: RwLock::new(current),
+        });
+        let mut acceptors = ACCEPTORS.lock();
+        acceptors.retain(|acceptor| acceptor.strong_count() != 0);
+        acceptors.push(Arc::downgrade(&slf));
+        Ok(slf)
+    }
+    /// Build a new acceptor from the files
+    fn rebuild(&self) -> SkyResult<SslAcceptor> {
+        self::new_acceptor(
+            &self.key_file,
+            &self.chain_file,
+            self.tls_passfile.as_deref(),
+            self.client_ca_file.as_deref(),
+        )
+    }
+    fn new_ssl(&self) -> SkyResult<Ssl> {
+        Ok(Ssl::new(self.current.read().context())?)
+    }
+}
+
+/// Reload the certificates and keys of all the TLS listeners from disk. Either every listener
+/// picks up its new certificate or (if any of them fails to load) none of them do. Returns the
+/// number of listeners that were reloaded
+pub fn reload_certificates() -> SkyResult<usize> {
+    let acceptors: Vec<Arc<ReloadableAcceptor>> =
+        ACCEPTORS.lock().iter().filter_map(Weak::upgrade).collect();
+    let rebuilt = acceptors
+        .iter()
+        .map(|acceptor| acceptor.rebuild())
+        .collect::<SkyResult<Vec<SslAcceptor>>>()?;
+    for (acceptor, new) in acceptors.iter().zip(rebuilt) {
+        *acceptor.current.write() = new;
+    }
+    Ok(acceptors.len())
+}
+
+/// Build a TLS acceptor with the PEM private key and certificate chain. If a passphrase file is
+/// given, the private key is decrypted with it. If a client CA file is given, clients have to
+/// present a certificate that was signed by one of the CAs in it
+pub fn new_acceptor(
+    key_file: &str,
+    chain_file: &str,
+    tls_passfile: Option<&str>,
+    client_ca_file: Option<&str>,
+) -> SkyResult<SslAcceptor> {
+    let mut acceptor_builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+    // cert is the same for both
+    acceptor_builder.set_certificate_chain_file(chain_file)?;
+    if let Some(tls_passfile) = tls_passfile {
+        // first read in the private key
+        let tls_private_key =
+            fs::read(key_file).map_err(|e| Error::ioerror_extra(e, "reading TLS private key"))?;
+        // read the passphrase because the passphrase file stream was provided
+        let tls_keyfile_stream = fs::read(tls_passfile)
+            .map_err(|e| Error::ioerror_extra(e, "reading TLS password file"))?;
+        // decrypt the private key
+        let pkey = Rsa::private_key_from_pem_passphrase(&tls_private_key, &tls_keyfile_stream)?;
+        let pkey = PKey::from_rsa(pkey)?;
+        // set the private key for the acceptor
+        acceptor_builder.set_private_key(&pkey)?;
+    } else {
+        // no passphrase, needs interactive
+        acceptor_builder.set_private_key_file(key_file, SslFiletype::PEM)?;
+    }
+    if let Some(client_ca_file) = client_ca_file {
+        // verify the client certificates against the CAs (and tell the clients which ones
+        // we'll accept)
+        acceptor_builder.set_ca_file(client_ca_file)?;
+        acceptor_builder.set_client_ca_list(X509Name::load_client_ca_file(client_ca_file)?);
+        acceptor_builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+    Ok(acceptor_builder.build())
+}
+
+/// Returns the identity in a client certificate: its common name or, if it doesn't have one,
+/// the first DNS name or email address in its subject alternative names
+pub fn certificate_identity(cert: &X509Ref) -> Option<Vec<u8>> {
+    if let Some(cn) = cert.subject_name().entries_by_nid(Nid::COMMONNAME).next() {
+        return Some(cn.data().as_slice().to_vec());
+    }
+    cert.subject_alt_names()?
+        .iter()
+        .find_map(|name| name.dnsname().or_else(|| name.email()))
+        .map(|name| name.as_bytes().to_vec())
+}
+
+/// Accept a TLS connection on the stream (with the acceptor's current certificate)
+pub async fn accept_stream(
+    acceptor: &ReloadableAcceptor,
+    stream: TcpStream,
+) -> SkyResult<SslStream<TcpStream>> {
+    let ssl = acceptor.new_ssl()?;
+    let mut stream = SslStream::new(ssl, stream)?;
+    Pin::new(&mut stream).accept().await?;
+    Ok(stream)
+}
+
 impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
     pub fn new_pem_based_ssl_connection(
         key_file: String,
         chain_file: String,
         base: BaseListener,
         tls_passfile: Option<String>,
+        client_ca_file: Option<String>,
     ) -> SkyResult<SslListenerRaw<P>> {
-        let mut acceptor_builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
-        // cert is the same for both
-        acceptor_builder.set_certificate_chain_file(chain_file)?;
-        if let Some(tls_passfile) = tls_passfile {
-            // first read in the private key
-            let tls_private_key = fs::read(key_file)
-                .map_err(|e| Error::ioerror_extra(e, "reading TLS private key"))?;
-            // read the passphrase because the passphrase file stream was provided
-            let tls_keyfile_stream = fs::read(tls_passfile)
-                .map_err(|e| Error::ioerror_extra(e, "reading TLS password file"))?;
-            // decrypt the private key
-            let pkey = Rsa::private_key_from_pem_passphrase(&tls_private_key, &tls_keyfile_stream)?;
-            let pkey = PKey::from_rsa(pkey)?;
-            // set the private key for the acceptor
-            acceptor_builder.set_private_key(&pkey)?;
-        } else {
-            // no passphrase, needs interactive
-            acceptor_builder.set_private_key_file(key_file, SslFiletype::PEM)?;
-        }
         Ok(Self {
-            acceptor: acceptor_builder.build(),
+            acceptor: ReloadableAcceptor::new(key_file, chain_file, tls_passfile, client_ca_file)?,
             base,
             _marker: PhantomData,
         })
     }
-    async fn accept(&mut self) -> SkyResult<SslStream<TcpStream>> {
+    async fn accept(&mut self) -> SkyResult<(SslStream<TcpStream>, SocketAddr, AdmissionGuard)> {
         let backoff = NetBackoff::new();
         loop {
             match self.base.listener.accept().await {
-                // We don't need the bindaddr
                 // We get the encrypted stream which we need to decrypt
-                // by using the acceptor
-                Ok((stream, _)) => {
-                    let ssl = Ssl::new(self.acceptor.context())?;
-                    let mut stream = SslStream::new(ssl, stream)?;
-                    Pin::new(&mut stream).accept().await?;
-                    return Ok(stream);
-                }
+                // by using the acceptor (if it's admitted; we don't waste a TLS handshake on
+                // streams that are turned away)
+                Ok((stream, addr)) => match self.base.admit(&stream, addr) {
+                    Some(guard) => {
+                        let stream = self::accept_stream(&self.acceptor, stream).await?;
+                        return Ok((stream, addr, guard));
+                    }
+                    None => continue,
+                },
                 Err(e) => {
                     if backoff.should_disconnect() {
                         // Too many retries, goodbye user
@@ -123,20 +247,79 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
              can arise and it will flood the log and might also result
              in a crash
             */
-            let stream = skip_loop_err!(self.accept().await);
-            let mut sslhandle = ConnectionHandler::<SslStream<TcpStream>, P>::new(
-                self.base.db.clone(),
-                Connection::new(stream),
-                self.base.auth.clone(),
-                self.base.climit.clone(),
-                self.base.signal.subscribe(),
-                self.base.terminate_tx.clone(),
-            );
-            tokio::spawn(async move {
-                if let Err(e) = sslhandle.run().await {
-                    log::error!("Error: {}", e);
+            let (stream, addr, guard) = skip_loop_err!(self.accept().await);
+            // clients with a (verified) certificate are logged in as the user that it names,
+            // if there's one. Everyone else starts out anonymous
+            let mut auth = self.base.auth.clone();
+            if let Some(identity) = stream
+                .ssl()
+                .peer_certificate()
+                .and_then(|cert| self::certificate_identity(&cert))
+            {
+                let outcome = if auth.login_with_certificate(&identity) {
+                    Outcome::Okay
+                } else {
+                    Outcome::Denied
+                };
+                // (certificates can't log anyone in if auth is disabled)
+                if audit::is_enabled() && auth.is_enabled() {
+                    let action: [&[u8]; 3] = [b"TLS", b"LOGIN", &identity];
+                    audit::record(Event::Login, outcome, Some(addr), None, &action);
                 }
-            });
+            }
+            handshake::spawn_as::<SslStream<TcpStream>, P>(&self.base, auth, stream, addr, guard);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::certificate_identity,
+        openssl::{
+            hash::MessageDigest,
+            pkey::PKey,
+            rsa::Rsa,
+            x509::{extension::SubjectAlternativeName, X509Builder, X509NameBuilder, X509},
+        },
+    };
+
+    /// Build a self-signed certificate with the given common name and DNS name
+    fn certificate(cn: Option<&str>, dns: Option<&str>) -> X509 {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("O", "Skytable").unwrap();
+        if let Some(cn) = cn {
+            name.append_entry_by_text("CN", cn).unwrap();
+        }
+        let name = name.build();
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        if let Some(dns) = dns {
+            let san = SubjectAlternativeName::new()
+                .dns(dns)
+                .build(&builder.x509v3_context(None, None))
+                .unwrap();
+            builder.append_extension(san).unwrap();
+        }
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn identity_from_common_name() {
+        let cert = certificate(Some("sayan"), Some("sayan.example.com"));
+        assert_eq!(certificate_identity(&cert).unwrap(), b"sayan");
+    }
+    #[test]
+    fn identity_from_subject_alt_name() {
+        let cert = certificate(None, Some("sayan.example.com"));
+        assert_eq!(certificate_identity(&cert).unwrap(), b"sayan.example.com");
+    }
+    #[test]
+    fn no_identity() {
+        assert_eq!(certificate_identity(&certificate(None, None)), None);
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Health checks
+//!
+//! Liveness and readiness are checked separately, the way orchestrators (like Kubernetes)
+//! expect them to be:
+//! - the server is *live* as long as it answers at all, so the liveness check doesn't look at
+//! anything
+//! - the server is *ready* if none of its checks is failing: the last flush of the data
+//! (BGSAVE) went through, the last snapshot went through (if snapshots are enabled) and
+//! writes haven't been stopped by a failure. A server that was made read-only on purpose (with
+//! `SYS READONLY ON`), or that's a read-only replica, is still ready, since it can serve reads
+//!
+//! `SYS HEALTH` returns the checks, and the HTTP gateway (if it's enabled) serves them to
+//! probes without credentials: `GET /healthz` for liveness and `GET /readyz` for readiness,
+//! with a `503` if the server isn't ready. The checks just read a few atomics, so probing
+//! them often is fine
+
+use {
+    crate::{registry, replication},
+    core::sync::atomic::{AtomicU8, Ordering},
+};
+
+/// The state of the data flushes
+static STORAGE: AtomicU8 = AtomicU8::new(Check::Okay as u8);
+/// The state of the snapshots
+static SNAPSHOTS: AtomicU8 = AtomicU8::new(Check::Disabled as u8);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(u8)]
+/// The state of something that the server does in the background
+pub enum Check {
+    /// the last attempt went through (or there wasn't one yet)
+    Okay,
+    /// the last attempt failed
+    Failing,
+    /// it isn't done at all
+    Disabled,
+}
+
+impl Check {
+    fn load(state: &AtomicU8) -> Self {
+        match state.load(Ordering::Acquire) {
+            0 => Self::Okay,
+            1 => Self::Failing,
+            _ => Self::Disabled,
+        }
+    }
+    const fn from_outcome(okay: bool) -> Self {
+        if okay {
+            Self::Okay
+        } else {
+            Self::Failing
+        }
+    }
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Okay => "okay",
+            Self::Failing => "failing",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Whether the server takes writes
+pub enum Writes {
+    Accepting,
+    /// the server was made read-only
+    ReadOnly,
+    /// the server is a read-only replica (see [`replication::rejects_writes`])
+    Replica,
+    /// a failure stopped the writes (see [`registry::poison`])
+    Stopped,
+}
+
+impl Writes {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Accepting => "accepting",
+            Self::ReadOnly => "read-only",
+            Self::Replica => "replica",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The readiness checks
+pub struct Report {
+    pub storage: Check,
+    pub snapshots: Check,
+    pub writes: Writes,
+}
+
+impl Report {
+    /// Returns true if the server is ready to take traffic
+    pub fn is_ready(&self) -> bool {
+        self.storage != Check::Failing
+            && self.snapshots != Check::Failing
+            && self.writes != Writes::Stopped
+    }
+}
+
+/// Returns the readiness checks
+pub fn report() -> Report {
+    let writes = if !registry::state_okay() {
+        Writes::Stopped
+    } else if registry::is_read_only() {
+        Writes::ReadOnly
+    } else if replication::rejects_writes() {
+        Writes::Replica
+    } else {
+        Writes::Accepting
+    };
+    Report {
+        storage: Check::load(&STORAGE),
+        snapshots: Check::load(&SNAPSHOTS),
+        writes,
+    }
+}
+
+/// Record the outcome of a flush of the data
+pub fn record_flush(okay: bool) {
+    STORAGE.store(Check::from_outcome(okay) as u8, Ordering::Release);
+}
+
+/// Record that snapshots are enabled (the snapshot service calls this when it starts)
+pub fn enable_snapshots() {
+    SNAPSHOTS.store(Check::Okay as u8, Ordering::Release);
+}
+
+/// Record the outcome of a snapshot
+pub fn record_snapshot(okay: bool) {
+    SNAPSHOTS.store(Check::from_outcome(okay) as u8, Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, Report, Writes};
+
+    #[test]
+    fn readiness() {
+        let mut report = Report {
+            storage: Check::Okay,
+            snapshots: Check::Disabled,
+            writes: Writes::ReadOnly,
+        };
+        assert!(report.is_ready());
+        report.snapshots = Check::Failing;
+        assert!(!report.is_ready());
+        report.snapshots = Check::Okay;
+        report.writes = Writes::Replica;
+        assert!(report.is_ready());
+        report.writes = Writes::Stopped;
+        assert!(!report.is_ready());
+        report.writes = Writes::Accepting;
+        report.storage = Check::Failing;
+        assert!(!report.is_ready());
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Bloom filter keymaps
+//!
+//! A [`KVEBloommap`] maps every key to a [`BloomFilter`]. A filter can be created with an
+//! explicit false positive rate and capacity; adding to a filter that doesn't exist creates
+//! it with the defaults
+
+use {
+    super::{notify::Event, EncodingResult, KVEBloommap, LockedBloom},
+    crate::corestore::{bloom::BloomFilter, SharedSlice},
+};
+
+impl KVEBloommap {
+    /// Create a new filter. Returns false if the key already exists
+    pub fn bloom_reserve(&self, key: SharedSlice, filter: BloomFilter) -> EncodingResult<bool> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        match self.data.fresh_entry(key.clone()) {
+            Some(entry) => {
+                entry.insert(LockedBloom::new(filter));
+                self.notify(Event::Set, &key);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    /// Add items to a filter, creating the filter (with the default settings) if it doesn't
+    /// exist. Returns the number of items that definitely weren't present before
+    pub fn bloom_add<'a>(
+        &self,
+        key: SharedSlice,
+        items: impl Iterator<Item = &'a [u8]>,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        let (added, event) = loop {
+            if let Some(filter) = self.data.get(&key) {
+                let mut wlock = filter.write();
+                break (items.filter(|item| wlock.add(item)).count(), Event::Update);
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                let mut filter = BloomFilter::default();
+                let added = items.filter(|item| filter.add(item)).count();
+                entry.insert(LockedBloom::new(filter));
+                break (added, Event::Set);
+            }
+            // someone created the filter right after we looked for it; just retry
+        };
+        if added != 0 || event == Event::Set {
+            self.notify(event, &key);
+        }
+        Ok(added)
+    }
+    /// Check if an item was possibly added to a filter. A filter that doesn't exist doesn't
+    /// contain any items
+    pub fn bloom_exists(&self, key: &[u8], item: &[u8]) -> EncodingResult<bool> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self
+            .data
+            .get(key)
+            .map(|filter| filter.read().contains(item))
+            .unwrap_or(false))
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Compaction
+//!
+//! The maps of an engine keep the capacity that they grew to, even after most of their keys
+//! are removed. Compacting an engine shrinks its maps to fit the keys that are left, a shard
+//! at a time, so that only one shard of a map is locked at once (see
+//! [`crate::services::compaction`]). The values of an engine that keeps them on disk are then
+//! moved out of the files that mostly hold values that are no longer used, again a shard at a
+//! time (see [`ValueCompaction`])
+
+use {
+    super::{disk, KVEStandard, KVEngine},
+    std::sync::Arc,
+};
+
+impl<T> KVEngine<T> {
+    /// Returns the number of steps that compacting the engine takes (one for every shard)
+    pub fn compaction_steps(&self) -> usize {
+        self.data.shard_count()
+    }
+    /// Run a step of the compaction, returning the number of bytes freed
+    pub fn compact_step(&self, step: usize) -> usize {
+        // all the maps have the same number of shards
+        self.data.shrink_shard(step)
+            + self.ttl.shrink_shard(step)
+            + self.eviction.shrink_shard(step)
+    }
+}
+
+impl KVEStandard {
+    /// Start moving the values of an engine that keeps them on disk to a new file, if at
+    /// least half of what's in its files is no longer used (see [`disk`])
+    pub fn compact_values(&self) -> Option<ValueCompaction<'_>> {
+        let storage = self.storage.as_ref()?;
+        let disk = storage.as_disk()?;
+        let live: u64 = self.data.iter().map(|kv| disk.disk_len(kv.value())).sum();
+        // the snapshots hold on to the storage engine
+        let pinned = Arc::strong_count(storage) > 1;
+        let files = {
+            // no value can be in the middle of being written to the old file
+            let _txn_guard = self.txn_lock.write();
+            disk.start_compaction(live, pinned)?
+        };
+        Some(ValueCompaction {
+            engine: self,
+            files,
+        })
+    }
+}
+
+/// Moves the values of an engine that keeps them on disk to a new file, a shard at a time
+/// (see [`KVEStandard::compact_values`])
+pub struct ValueCompaction<'a> {
+    engine: &'a KVEStandard,
+    files: disk::Compaction<'a>,
+}
+
+impl ValueCompaction<'_> {
+    /// Returns the number of steps that moving the values takes (one for every shard)
+    pub fn steps(&self) -> usize {
+        self.engine.data.shard_count()
+    }
+    /// Move the values in a shard
+    pub fn step(&mut self, step: usize) {
+        // a transaction puts back the values that it replaced if it fails, so it can't be
+        // holding on to any of these
+        let _write = self.engine.write_guard();
+        let files = &mut self.files;
+        self.engine
+            .data
+            .update_shard(step, |stored| files.relocate(stored));
+    }
+    /// Finish moving the values, returning the number of bytes that it reclaims on disk
+    pub fn finish(self) -> u64 {
+        self.files.finish()
+    }
+}
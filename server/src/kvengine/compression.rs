@@ -0,0 +1,316 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Value compression
+//!
+//! Tables created with `with compression = lz4` store their values compressed, both in memory
+//! and on disk (since flushing writes out whatever is in memory). Every stored value is a
+//! _frame_: a single byte that tells us how the value was stored, followed by the value.
+//! Values that are too small (or that don't shrink) are stored as is, so that we don't spend
+//! time decompressing values that didn't need it
+//!
+//! The block codec is a small implementation of the [LZ4 block format], which is fast enough
+//! to sit in the read path
+
+use {crate::corestore::SharedSlice, std::mem};
+
+/// The value is stored as is
+const FRAME_RAW: u8 = 0;
+/// The value is an LZ4 block, preceded by the length of the decompressed value (u32, LE)
+const FRAME_LZ4: u8 = 1;
+/// The size of the header of an LZ4 frame
+const LZ4_HEADER_LEN: usize = 1 + mem::size_of::<u32>();
+/// Values shorter than this aren't worth compressing
+pub const MIN_COMPRESSIBLE_LEN: usize = 64;
+
+/// Returns the stored form of the value
+pub fn pack(value: &[u8]) -> SharedSlice {
+    if value.len() >= MIN_COMPRESSIBLE_LEN && value.len() <= u32::MAX as usize {
+        let block = lz4::compress(value);
+        if block.len() + LZ4_HEADER_LEN < value.len() + 1 {
+            let mut framed = Vec::with_capacity(LZ4_HEADER_LEN + block.len());
+            framed.push(FRAME_LZ4);
+            framed.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            framed.extend_from_slice(&block);
+            return SharedSlice::from(framed);
+        }
+    }
+    let mut framed = Vec::with_capacity(1 + value.len());
+    framed.push(FRAME_RAW);
+    framed.extend_from_slice(value);
+    SharedSlice::from(framed)
+}
+
+/// Returns the value from its stored form, or `None` if `stored` isn't a valid frame
+pub fn unpack(stored: &[u8]) -> Option<SharedSlice> {
+    let (frame, value) = stored.split_first()?;
+    match *frame {
+        FRAME_RAW => Some(SharedSlice::new(value)),
+        FRAME_LZ4 => {
+            let len = self::unpacked_len(stored)?;
+            lz4::decompress(&stored[LZ4_HEADER_LEN..], len).map(SharedSlice::from)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the length of the value from its stored form (without decompressing it), or `None`
+/// if `stored` isn't a valid frame
+pub fn unpacked_len(stored: &[u8]) -> Option<usize> {
+    match *stored.first()? {
+        FRAME_RAW => Some(stored.len() - 1),
+        FRAME_LZ4 if stored.len() >= LZ4_HEADER_LEN => {
+            let mut len = [0u8; mem::size_of::<u32>()];
+            len.copy_from_slice(&stored[1..LZ4_HEADER_LEN]);
+            Some(u32::from_le_bytes(len) as usize)
+        }
+        _ => None,
+    }
+}
+
+/// An implementation of the LZ4 block format. A block is a sequence of _sequences_, each of
+/// which is a run of literals followed by a back-reference (offset and length) into the
+/// decompressed output. The last sequence only has literals
+pub mod lz4 {
+    /// The shortest match that we can encode
+    const MIN_MATCH: usize = 4;
+    /// The last five bytes of a block are always literals
+    const LAST_LITERALS: usize = 5;
+    /// The last match must start at least twelve bytes before the end of the block
+    const MF_LIMIT: usize = 12;
+    /// Matches can only refer this far back
+    const MAX_OFFSET: usize = u16::MAX as usize;
+    /// We index 2^HASH_LOG positions at once
+    const HASH_LOG: u32 = 12;
+
+    #[inline(always)]
+    fn read_u32(src: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([src[at], src[at + 1], src[at + 2], src[at + 3]])
+    }
+
+    #[inline(always)]
+    fn hash(sequence: u32) -> usize {
+        (sequence.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+    }
+
+    /// Write the rest of a length that didn't fit in its nibble
+    fn write_length(dst: &mut Vec<u8>, mut len: usize) {
+        while len >= 255 {
+            dst.push(255);
+            len -= 255;
+        }
+        dst.push(len as u8);
+    }
+
+    /// Write a sequence. With no match, this is the last sequence of the block
+    fn write_sequence(dst: &mut Vec<u8>, literals: &[u8], found: Option<(usize, usize)>) {
+        let match_len = found.map_or(0, |(_, len)| len - MIN_MATCH);
+        let token = ((literals.len().min(15) as u8) << 4) | match_len.min(15) as u8;
+        dst.push(token);
+        if literals.len() >= 15 {
+            write_length(dst, literals.len() - 15);
+        }
+        dst.extend_from_slice(literals);
+        if let Some((offset, _)) = found {
+            dst.extend_from_slice(&(offset as u16).to_le_bytes());
+            if match_len >= 15 {
+                write_length(dst, match_len - 15);
+            }
+        }
+    }
+
+    /// Compress `src` into an LZ4 block
+    pub fn compress(src: &[u8]) -> Vec<u8> {
+        let mut dst = Vec::with_capacity(src.len() / 2 + 16);
+        // the last position (plus one, so that zero means nothing) we saw each hash at
+        let mut table = vec![0usize; 1 << HASH_LOG];
+        let mut anchor = 0;
+        let mut i = 0;
+        let match_start_limit = src.len().saturating_sub(MF_LIMIT);
+        let match_end_limit = src.len().saturating_sub(LAST_LITERALS);
+        while i < match_start_limit {
+            let sequence = read_u32(src, i);
+            let slot = &mut table[hash(sequence)];
+            let candidate = *slot;
+            *slot = i + 1;
+            if candidate != 0 {
+                let candidate = candidate - 1;
+                if i - candidate <= MAX_OFFSET && read_u32(src, candidate) == sequence {
+                    let mut len = MIN_MATCH;
+                    while i + len < match_end_limit && src[candidate + len] == src[i + len] {
+                        len += 1;
+                    }
+                    write_sequence(&mut dst, &src[anchor..i], Some((i - candidate, len)));
+                    i += len;
+                    anchor = i;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        write_sequence(&mut dst, &src[anchor..], None);
+        dst
+    }
+
+    /// Read the rest of a length that didn't fit in its nibble
+    fn read_length(src: &[u8], at: &mut usize) -> Option<usize> {
+        let mut len = 0usize;
+        loop {
+            let byte = *src.get(*at)?;
+            *at += 1;
+            len = len.checked_add(byte as usize)?;
+            if byte != 255 {
+                return Some(len);
+            }
+        }
+    }
+
+    /// Decompress an LZ4 block that decompresses to `len` bytes. Returns `None` if the block
+    /// is malformed
+    pub fn decompress(src: &[u8], len: usize) -> Option<Vec<u8>> {
+        let mut dst = Vec::with_capacity(len);
+        let mut i = 0;
+        loop {
+            let token = *src.get(i)?;
+            i += 1;
+            let mut literal_len = (token >> 4) as usize;
+            if literal_len == 15 {
+                literal_len += read_length(src, &mut i)?;
+            }
+            if dst.len() + literal_len > len {
+                return None;
+            }
+            dst.extend_from_slice(src.get(i..i.checked_add(literal_len)?)?);
+            i += literal_len;
+            if i == src.len() {
+                // that was the last sequence
+                break;
+            }
+            let offset = u16::from_le_bytes([*src.get(i)?, *src.get(i + 1)?]) as usize;
+            i += 2;
+            if offset == 0 || offset > dst.len() {
+                return None;
+            }
+            let mut match_len = (token & 0x0F) as usize + MIN_MATCH;
+            if token & 0x0F == 15 {
+                match_len += read_length(src, &mut i)?;
+            }
+            if dst.len() + match_len > len {
+                return None;
+            }
+            // the match can overlap with the bytes it produces, so copy byte by byte
+            for _ in 0..match_len {
+                let byte = dst[dst.len() - offset];
+                dst.push(byte);
+            }
+        }
+        if dst.len() == len {
+            Some(dst)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lz4, pack, unpack, unpacked_len, FRAME_LZ4, FRAME_RAW, MIN_COMPRESSIBLE_LEN};
+
+    fn roundtrip(value: &[u8]) {
+        let block = lz4::compress(value);
+        assert_eq!(lz4::decompress(&block, value.len()).unwrap(), value);
+    }
+
+    #[test]
+    fn lz4_roundtrip() {
+        roundtrip(b"");
+        roundtrip(b"a");
+        roundtrip(b"hello world");
+        roundtrip(&b"abcd".repeat(1000));
+        roundtrip(&[0u8; 100_000]);
+        // something that doesn't compress well
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let noise: Vec<u8> = (0..10_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        roundtrip(&noise);
+        // and something that's in between
+        let text: Vec<u8> = (0..5_000)
+            .flat_map(|i| format!("user:{} logged in from 10.0.0.{}\n", i, i % 7).into_bytes())
+            .collect();
+        roundtrip(&text);
+    }
+
+    #[test]
+    fn lz4_compresses_repetitive_data() {
+        let value = b"skytable".repeat(1000);
+        assert!(lz4::compress(&value).len() < value.len() / 10);
+    }
+
+    #[test]
+    fn lz4_rejects_malformed_blocks() {
+        let value = b"skytable".repeat(100);
+        let block = lz4::compress(&value);
+        // wrong length
+        assert!(lz4::decompress(&block, value.len() - 1).is_none());
+        assert!(lz4::decompress(&block, value.len() + 1).is_none());
+        // truncated
+        assert!(lz4::decompress(&block[..block.len() - 1], value.len()).is_none());
+        // offset past the start of the output
+        assert!(lz4::decompress(&[0x00, 0x01, 0x00], 4).is_none());
+        assert!(lz4::decompress(&[], 0).is_none());
+    }
+
+    #[test]
+    fn pack_small_values_raw() {
+        let packed = pack(b"hello");
+        assert_eq!(packed[0], FRAME_RAW);
+        assert_eq!(unpacked_len(&packed), Some(5));
+        assert_eq!(unpack(&packed).unwrap().as_ref(), b"hello");
+    }
+
+    #[test]
+    fn pack_large_values_compressed() {
+        let value = b"x".repeat(MIN_COMPRESSIBLE_LEN * 4);
+        let packed = pack(&value);
+        assert_eq!(packed[0], FRAME_LZ4);
+        assert!(packed.len() < value.len());
+        assert_eq!(unpacked_len(&packed), Some(value.len()));
+        assert_eq!(unpack(&packed).unwrap().as_ref(), value.as_slice());
+    }
+
+    #[test]
+    fn unpack_bad_frames() {
+        assert!(unpack(b"").is_none());
+        assert!(unpack(b"\x07hello").is_none());
+        assert!(unpacked_len(&[FRAME_LZ4, 1, 0]).is_none());
+    }
+}
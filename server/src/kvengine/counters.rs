@@ -0,0 +1,89 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Counter keymaps
+//!
+//! A [`KVECountermap`] maps every key to an unsigned 64-bit integer. Counters are updated
+//! atomically without taking any locks, and a counter that doesn't exist is treated as zero
+
+use {
+    super::{notify::Event, EncodingResult, KVECountermap},
+    crate::corestore::SharedSlice,
+    std::sync::atomic::{AtomicU64, Ordering},
+};
+
+impl KVECountermap {
+    /// Returns the value of a counter or `None` if it doesn't exist
+    pub fn counter_get(&self, key: &[u8]) -> EncodingResult<Option<u64>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self
+            .data
+            .get(key)
+            .map(|counter| counter.load(Ordering::Acquire)))
+    }
+    /// Increment a counter by `delta`, returning the new value. `None` is returned (and the
+    /// counter is left untouched) if the counter would overflow
+    pub fn counter_incr_by(&self, key: SharedSlice, delta: u64) -> EncodingResult<Option<u64>> {
+        self.counter_update(key, |current| current.checked_add(delta))
+    }
+    /// Decrement a counter by `delta`, returning the new value. `None` is returned (and the
+    /// counter is left untouched) if the counter would drop below zero
+    pub fn counter_decr_by(&self, key: SharedSlice, delta: u64) -> EncodingResult<Option<u64>> {
+        self.counter_update(key, |current| current.checked_sub(delta))
+    }
+    fn counter_update(
+        &self,
+        key: SharedSlice,
+        update: impl Fn(u64) -> Option<u64>,
+    ) -> EncodingResult<Option<u64>> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        loop {
+            if let Some(counter) = self.data.get(&key) {
+                let ret = counter
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, &update)
+                    .ok()
+                    // the update is pure, so this gives us what was stored
+                    .and_then(&update);
+                if ret.is_some() {
+                    self.notify(Event::Update, &key);
+                }
+                return Ok(ret);
+            }
+            let new = match update(0) {
+                Some(new) => new,
+                None => return Ok(None),
+            };
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                entry.insert(AtomicU64::new(new));
+                self.notify(Event::Set, &key);
+                return Ok(Some(new));
+            }
+            // someone created the counter right after we checked; just retry
+        }
+    }
+}
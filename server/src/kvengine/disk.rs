@@ -0,0 +1,607 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Disk-backed values
+//!
+//! Tables created with `WITH storage = disk` keep their values in a file instead of memory, so
+//! that they can hold more data than fits in memory. The keys are still kept in memory, along
+//! with a small _stub_ for every value that records which file the value is in and where. A
+//! file is append-only: a value is always written at the end of the file, so a stub stays
+//! valid for as long as the file exists. This is what lets readers and point-in-time snapshots
+//! hold on to older values without any locking. On Unix, the file is mapped into memory, so
+//! reading a value is a copy out of the page cache, and the OS decides which parts of the file
+//! stay in memory (elsewhere, the file is read directly).
+//!
+//! The files only hold the values while the server runs: the table is still flushed like any
+//! other table (with the values read back from the file) and its file is created afresh when
+//! the table is loaded (every value is written to the file as soon as it's read, see
+//! [`Loaded`]). The space used by values that are overwritten or removed is reclaimed by the
+//! compaction (see [`crate::services::compaction`]): once at least half of what's in the files
+//! is no longer used, the values that are still used are moved to a new file, a shard at a
+//! time (see [`Compaction`]), and the older files are closed by the compaction after that (or
+//! by the first one that runs while no snapshot of the table is held), since a reader may
+//! still hold a stub that points into them
+//!
+//! Values that are shorter than a stub are kept in memory, and so are the values that can't
+//! be written to the file (if the disk is full, say). If encryption is enabled (see
+//! [`crate::storage::v1::encryption`]), the values are encrypted before they're written. A
+//! value that can't be read back (or decrypted) fails the query that reads it
+
+#[cfg(unix)]
+use std::os::unix::{fs::FileExt, io::AsRawFd};
+#[cfg(windows)]
+use std::os::windows::fs::{FileExt, OpenOptionsExt};
+use {
+    super::storage::{StorageEngine, StorageKind, Unreadable},
+    crate::{
+        corestore::{htable::Coremap, SharedSlice},
+        storage::v1::{encryption, interface::DIR_DISK},
+        IoResult,
+    },
+    core::sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    parking_lot::RwLock,
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{Error as IoError, ErrorKind, Write},
+        process,
+        sync::Arc,
+    },
+};
+
+/// The value is kept in memory (the rest of the stored form is the value)
+const TAG_INLINE: u8 = 0;
+/// The value is on disk (the rest of the stored form is the ID of its file, its offset and its
+/// length, as u64s)
+const TAG_DISK: u8 = 1;
+/// The length of a stub
+const STUB_LEN: usize = 1 + 3 * core::mem::size_of::<u64>();
+/// The least that we map at once
+#[cfg(unix)]
+const MIN_MAP_LEN: usize = 64 * 1024 * 1024;
+/// `FILE_FLAG_DELETE_ON_CLOSE`
+#[cfg(windows)]
+const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x04000000;
+
+/// Used to give every file a unique ID (and name)
+static FILE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Remove the files left behind by a server that didn't shut down cleanly. The files are
+/// removed as soon as they're no longer used, so this is only needed after a crash
+pub fn remove_leftovers() -> IoResult<()> {
+    match fs::remove_dir_all(DIR_DISK) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug)]
+/// The storage engine of the tables that keep their values on disk
+pub struct Disk {
+    /// the files that hold the values
+    files: RwLock<Files>,
+    /// the values are encrypted
+    encrypted: bool,
+    /// we already complained about a failed write
+    warned: AtomicBool,
+}
+
+impl Disk {
+    pub fn new() -> Self {
+        Self {
+            files: RwLock::new(Files::default()),
+            encrypted: encryption::is_enabled(),
+            warned: AtomicBool::new(false),
+        }
+    }
+    /// Returns the number of bytes in the files that values are read from (including the
+    /// values that are no longer used)
+    pub fn disk_usage(&self) -> u64 {
+        let files = self.files.read();
+        files
+            .current
+            .iter()
+            .chain(&files.draining)
+            .map(|file| file.end())
+            .sum()
+    }
+    /// Returns the number of bytes that the stored form of a value takes up on disk
+    pub fn disk_len(&self, stored: &[u8]) -> u64 {
+        self::decode_stub(stored).map_or(0, |(_, _, len)| len)
+    }
+    /// Start moving the values out of the files that they're in now, if at least half of what's
+    /// in the files isn't `live` (the number of bytes that the values in use take up). New
+    /// values are written to the new file right away, so no value can be in the middle of
+    /// being written when this is called. If `pinned`, a snapshot may still hold stubs that
+    /// point into the files that the last compaction moved the values out of, so they're
+    /// kept around for now
+    pub fn start_compaction(&self, live: u64, pinned: bool) -> Option<Compaction<'_>> {
+        let mut files = self.files.write();
+        if !pinned {
+            files.retired.clear();
+        }
+        let used: u64 = files
+            .current
+            .iter()
+            .chain(&files.draining)
+            .map(|file| file.end())
+            .sum();
+        if used.saturating_sub(live) < live.max(1) {
+            return None;
+        }
+        let target = match ValueFile::create() {
+            Ok(file) => Arc::new(file),
+            Err(e) => {
+                log::error!("Failed to create a file to compact the values on disk into: {e}");
+                return None;
+            }
+        };
+        if let Some(current) = files.current.replace(target.clone()) {
+            files.draining.push(current);
+        }
+        Some(Compaction {
+            disk: self,
+            target,
+            sources: files.draining.clone(),
+            moved: 0,
+            failed: false,
+        })
+    }
+    /// Returns the file that new values are written to, opening it if it isn't open yet
+    fn file(&self) -> IoResult<Arc<ValueFile>> {
+        if let Some(file) = self.files.read().current.as_ref() {
+            return Ok(file.clone());
+        }
+        let mut files = self.files.write();
+        match files.current.as_ref() {
+            Some(file) => Ok(file.clone()),
+            None => Ok(files.current.insert(Arc::new(ValueFile::create()?)).clone()),
+        }
+    }
+    /// Write the value to the file, returning its stub
+    fn write(&self, value: &[u8]) -> IoResult<SharedSlice> {
+        let file = self.file()?;
+        if self.encrypted {
+            let mut writer = encryption::EncryptingWriter::new(Vec::with_capacity(
+                value.len() + encryption::OVERHEAD,
+            ))?;
+            writer.write_all(value)?;
+            let encrypted = writer.finish()?;
+            let offset = file.append(&encrypted)?;
+            Ok(self::encode_stub(file.id, offset, encrypted.len() as u64))
+        } else {
+            let offset = file.append(value)?;
+            Ok(self::encode_stub(file.id, offset, value.len() as u64))
+        }
+    }
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageEngine for Disk {
+    fn kind(&self) -> StorageKind {
+        StorageKind::Disk
+    }
+    fn pack(&self, value: &[u8]) -> SharedSlice {
+        if value.len() >= STUB_LEN {
+            match self.write(value) {
+                Ok(stub) => return stub,
+                Err(e) => {
+                    if !self.warned.swap(true, Ordering::AcqRel) {
+                        log::error!(
+                            "Failed to write a value to the disk ({e}). The values that can't be written are kept in memory"
+                        );
+                    }
+                }
+            }
+        }
+        let mut inline = Vec::with_capacity(1 + value.len());
+        inline.push(TAG_INLINE);
+        inline.extend_from_slice(value);
+        SharedSlice::from(inline)
+    }
+    fn unpack(&self, stored: &SharedSlice) -> Result<SharedSlice, Unreadable> {
+        let (id, offset, len) = match self::decode_stub(stored) {
+            Some(stub) => stub,
+            None => return Ok(SharedSlice::new(stored.get(1..).unwrap_or_default())),
+        };
+        let file = match self.files.read().find(id).cloned() {
+            Some(file) => file,
+            None => {
+                log::error!("Found a value on disk, but its file ({id}) is no longer open");
+                return Err(Unreadable);
+            }
+        };
+        // we only hand out stubs for values that were written, so failing to read one back
+        // means that something is badly wrong (but that's no reason to take the server down)
+        let value = file.read(offset, len as usize).map_err(|e| {
+            log::error!("Failed to read a value ({len} bytes at {offset}) from the disk: {e}");
+            Unreadable
+        })?;
+        if self.encrypted {
+            encryption::decrypt(DIR_DISK, value)
+                .map(SharedSlice::from)
+                .map_err(|e| {
+                    log::error!("Failed to decrypt a value ({len} bytes at {offset}): {e}");
+                    Unreadable
+                })
+        } else {
+            Ok(SharedSlice::from(value))
+        }
+    }
+    fn unpacked_len(&self, stored: &[u8]) -> usize {
+        match self::decode_stub(stored) {
+            Some((_, _, len)) if self.encrypted => {
+                (len as usize).saturating_sub(encryption::OVERHEAD)
+            }
+            Some((_, _, len)) => len as usize,
+            None => stored.len().saturating_sub(1),
+        }
+    }
+    fn is_portable(&self) -> bool {
+        // the stubs only make sense for our files
+        false
+    }
+    fn as_disk(&self) -> Option<&Disk> {
+        Some(self)
+    }
+}
+
+#[derive(Debug, Default)]
+/// The files of a [`Disk`]
+struct Files {
+    /// the file that new values are written to (opened once the first value is written)
+    current: Option<Arc<ValueFile>>,
+    /// older files that still hold values that are in use, until a compaction moves them out
+    draining: Vec<Arc<ValueFile>>,
+    /// older files that the last compaction moved all the values out of. Readers may still
+    /// hold stubs that point into them, so they're only closed by the next compaction
+    retired: Vec<Arc<ValueFile>>,
+}
+
+impl Files {
+    /// Returns the file with the given ID, if it's still open
+    fn find(&self, id: u64) -> Option<&Arc<ValueFile>> {
+        self.current
+            .iter()
+            .chain(&self.draining)
+            .chain(&self.retired)
+            .find(|file| file.id == id)
+    }
+}
+
+/// Moves the values that are in use to a new file (see [`Disk::start_compaction`]). Every
+/// stub in the map has to be passed to [`Self::relocate`] before the compaction is finished.
+/// A compaction that isn't finished leaves the values that it didn't move where they are,
+/// for the next compaction to move
+pub struct Compaction<'a> {
+    disk: &'a Disk,
+    /// where the values are moved to
+    target: Arc<ValueFile>,
+    /// the files that the values are moved out of
+    sources: Vec<Arc<ValueFile>>,
+    /// the number of bytes moved so far
+    moved: u64,
+    /// a value couldn't be moved
+    failed: bool,
+}
+
+impl Compaction<'_> {
+    /// Move the value to the new file, if it's in one of the files that are being compacted.
+    /// The caller must hold the lock on the value, so that it isn't replaced in the meantime
+    pub fn relocate(&mut self, stored: &mut SharedSlice) {
+        let (id, offset, len) = match self::decode_stub(stored) {
+            Some(stub) => stub,
+            None => return,
+        };
+        let source = match self.sources.iter().find(|file| file.id == id) {
+            Some(source) => source,
+            None => return,
+        };
+        // the value is moved as is (encrypted, if it is)
+        match source
+            .read(offset, len as usize)
+            .and_then(|value| self.target.append(&value))
+        {
+            Ok(new_offset) => {
+                *stored = self::encode_stub(self.target.id, new_offset, len);
+                self.moved += len;
+            }
+            Err(e) => {
+                if !self.failed {
+                    log::error!("Failed to move a value ({len} bytes at {offset}) on disk: {e}");
+                }
+                self.failed = true;
+            }
+        }
+    }
+    /// Finish the compaction, returning the number of bytes that it reclaims once the files
+    /// that the values were moved out of are closed
+    pub fn finish(self) -> u64 {
+        if self.failed {
+            log::warn!("Some values on disk couldn't be moved, so their files are kept for now");
+            return 0;
+        }
+        let mut files = self.disk.files.write();
+        files
+            .draining
+            .retain(|file| !self.sources.iter().any(|source| Arc::ptr_eq(file, source)));
+        let used: u64 = self.sources.iter().map(|source| source.end()).sum();
+        files.retired.extend(self.sources);
+        used.saturating_sub(self.moved)
+    }
+}
+
+/// The data of a table that keeps its values on disk, as it's being loaded. Every value is
+/// written to the file as soon as it's read, so that the values of a table never have to be
+/// in memory all at once
+pub struct Loaded {
+    /// the keys and the stored forms of their values
+    pub data: Coremap<SharedSlice, SharedSlice>,
+    /// where the values were written
+    pub storage: Disk,
+}
+
+impl Loaded {
+    pub fn new() -> Self {
+        Self {
+            data: Coremap::new(),
+            storage: Disk::new(),
+        }
+    }
+    pub fn try_with_capacity(cap: usize) -> Result<Self, ()> {
+        Ok(Self {
+            data: Coremap::try_with_capacity(cap)?,
+            storage: Disk::new(),
+        })
+    }
+    /// Write the value to the file and add the key (replacing the key, if it was already added)
+    pub fn upsert(&self, key: SharedSlice, value: &[u8]) {
+        self.data.upsert(key, self.storage.pack(value))
+    }
+}
+
+impl Default for Loaded {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the stub of a value that was written to the given file
+fn encode_stub(id: u64, offset: u64, len: u64) -> SharedSlice {
+    let mut stub = Vec::with_capacity(STUB_LEN);
+    stub.push(TAG_DISK);
+    stub.extend_from_slice(&id.to_le_bytes());
+    stub.extend_from_slice(&offset.to_le_bytes());
+    stub.extend_from_slice(&len.to_le_bytes());
+    SharedSlice::from(stub)
+}
+
+/// Returns the ID of the file, the offset and the length of the value if the stored form is a
+/// stub
+fn decode_stub(stored: &[u8]) -> Option<(u64, u64, u64)> {
+    match stored.split_first() {
+        Some((&TAG_DISK, stub)) if stub.len() == STUB_LEN - 1 => {
+            let mut fields = stub
+                .chunks_exact(core::mem::size_of::<u64>())
+                .map(|field| u64::from_le_bytes(field.try_into().unwrap_or_default()));
+            Some((fields.next()?, fields.next()?, fields.next()?))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+/// An append-only file of values
+struct ValueFile {
+    /// the stubs of the values in this file carry this
+    id: u64,
+    file: File,
+    /// where the next value goes
+    end: AtomicU64,
+    #[cfg(unix)]
+    map: RwLock<Mapping>,
+}
+
+impl ValueFile {
+    /// Create a new file. The file is removed as soon as it's closed
+    fn create() -> IoResult<Self> {
+        fs::create_dir_all(DIR_DISK)?;
+        let id = FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let path = format!("{DIR_DISK}/{}-{id}", process::id());
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(windows)]
+        options.custom_flags(FILE_FLAG_DELETE_ON_CLOSE);
+        let file = options.open(&path)?;
+        // we already have it open, so we don't need the name anymore
+        #[cfg(unix)]
+        fs::remove_file(&path)?;
+        Ok(Self {
+            id,
+            file,
+            end: AtomicU64::new(0),
+            #[cfg(unix)]
+            map: RwLock::new(Mapping::empty()),
+        })
+    }
+    /// Returns the number of bytes in the file
+    fn end(&self) -> u64 {
+        self.end.load(Ordering::Acquire)
+    }
+    /// Write the bytes at the end of the file, returning where they were written. Concurrent
+    /// appends don't wait for each other, since each one gets its own part of the file
+    fn append(&self, bytes: &[u8]) -> IoResult<u64> {
+        let offset = self.end.fetch_add(bytes.len() as u64, Ordering::AcqRel);
+        // if this fails, the part of the file that we got is just never used
+        self::write_all_at(&self.file, bytes, offset)?;
+        Ok(offset)
+    }
+    /// Read `len` bytes from `offset`
+    fn read(&self, offset: u64, len: usize) -> IoResult<Vec<u8>> {
+        // reading a mapping past the end of the file is fatal, so never trust a bad stub
+        let past_end = offset
+            .checked_add(len as u64)
+            .map_or(true, |last| last > self.end());
+        if past_end {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                "the value is past the end of the file",
+            ));
+        }
+        #[cfg(unix)]
+        {
+            if let Some(bytes) = self.map.read().get(offset, len) {
+                return Ok(bytes.to_vec());
+            }
+            let mut map = self.map.write();
+            if map.get(offset, len).is_none() {
+                // map more than we need, so that we don't have to do this again for a while.
+                // Mapping past the end of the file is fine, since we never read what wasn't
+                // written
+                let map_len = (offset as usize + len).next_power_of_two().max(MIN_MAP_LEN);
+                match Mapping::map(&self.file, map_len) {
+                    Ok(new) => *map = new,
+                    Err(e) => log::warn!("Failed to map the values on disk: {e}"),
+                }
+            }
+            if let Some(bytes) = map.get(offset, len) {
+                return Ok(bytes.to_vec());
+            }
+        }
+        // we either can't map the file or this isn't Unix, so just read it
+        let mut value = vec![0; len];
+        self::read_exact_at(&self.file, &mut value, offset)?;
+        Ok(value)
+    }
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+/// A read-only shared mapping of (a part of) a file
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// UNSAFE(@ohsayan): the mapping is read-only, and it's only unmapped when it's dropped
+#[cfg(unix)]
+unsafe impl Send for Mapping {}
+#[cfg(unix)]
+unsafe impl Sync for Mapping {}
+
+#[cfg(unix)]
+impl Mapping {
+    const fn empty() -> Self {
+        Self {
+            ptr: core::ptr::null_mut(),
+            len: 0,
+        }
+    }
+    /// Map the first `len` bytes of the file
+    fn map(file: &File, len: usize) -> IoResult<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(Self {
+                ptr: ptr as *mut u8,
+                len,
+            })
+        }
+    }
+    /// Returns the `len` bytes at `offset`, if they're mapped
+    fn get(&self, offset: u64, len: usize) -> Option<&[u8]> {
+        let offset = usize::try_from(offset).ok()?;
+        if offset.checked_add(len)? <= self.len {
+            // UNSAFE(@ohsayan): we just checked that this is within the mapping
+            Some(unsafe { core::slice::from_raw_parts(self.ptr.add(offset), len) })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                libc::munmap(self.ptr as *mut libc::c_void, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, bytes: &[u8], offset: u64) -> IoResult<()> {
+    file.write_all_at(bytes, offset)
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> IoResult<()> {
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &File, mut bytes: &[u8], mut offset: u64) -> IoResult<()> {
+    while !bytes.is_empty() {
+        match file.seek_write(bytes, offset)? {
+            0 => return Err(ErrorKind::WriteZero.into()),
+            written => {
+                bytes = &bytes[written..];
+                offset += written as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> IoResult<()> {
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset)? {
+            0 => return Err(ErrorKind::UnexpectedEof.into()),
+            read => {
+                buf = &mut buf[read..];
+                offset += read as u64;
+            }
+        }
+    }
+    Ok(())
+}
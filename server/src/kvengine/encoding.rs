@@ -77,6 +77,11 @@ pub const ENCODING_LUT_PAIR: TwoBitLUT<PairFn> = TwoBitLUT::new(
     self::is_okay_encoded_pair_tf,
     self::is_okay_encoded_pair_tt,
 );
+// for JSON values; indexed by the key's encoding
+pub const ENCODING_LUT_JSON_ITER_PAIR: BoolTable<fn(&AnyArrayIter) -> bool> =
+    BoolTable::new(pair_is_okay_json_iter_t, pair_is_okay_json_iter_f);
+pub const ENCODING_LUT_JSON_PAIR: BoolTable<PairFn> =
+    BoolTable::new(self::is_okay_json_pair_t, self::is_okay_json_pair_f);
 
 /// This table maps bytes to character classes that helps us reduce the size of the
 /// transition table and generate bitmasks
@@ -149,6 +154,35 @@ pub fn pair_is_okay_encoded_iter_tt(inp: &AnyArrayIter<'_>) -> bool {
     }
 }
 
+pub fn pair_is_okay_json_iter_f(inp: &AnyArrayIter<'_>) -> bool {
+    unsafe {
+        let mut vptr = inp.as_ptr().add(1);
+        let eptr = inp.as_ptr().add(inp.len());
+        let mut state = true;
+        while vptr < eptr && state {
+            state = self::is_okay_json((*vptr).as_slice());
+            // only forward values
+            vptr = vptr.add(2);
+        }
+        state
+    }
+}
+
+pub fn pair_is_okay_json_iter_t(inp: &AnyArrayIter<'_>) -> bool {
+    unsafe {
+        let mut kptr = inp.as_ptr();
+        let mut vptr = inp.as_ptr().add(1);
+        let eptr = kptr.add(inp.len());
+        let mut state = true;
+        while vptr < eptr && state {
+            state = self::is_utf8((*kptr).as_slice()) && self::is_okay_json((*vptr).as_slice());
+            kptr = kptr.add(2);
+            vptr = vptr.add(2);
+        }
+        state
+    }
+}
+
 pub fn is_okay_encoded_iter(mut inp: BorrowedAnyArrayIter<'_>) -> bool {
     inp.all(self::is_okay_encoded)
 }
@@ -181,6 +215,19 @@ pub const fn is_okay_encoded_pair_ff(_a: &[u8], _b: &[u8]) -> bool {
     true
 }
 
+/// Returns true if the value is valid UTF-8 and a single valid JSON value
+pub fn is_okay_json(inp: &[u8]) -> bool {
+    self::is_utf8(inp) && super::json::is_json(inp)
+}
+
+pub fn is_okay_json_pair_t(a: &[u8], b: &[u8]) -> bool {
+    is_okay_encoded(a) && is_okay_json(b)
+}
+
+pub fn is_okay_json_pair_f(_a: &[u8], b: &[u8]) -> bool {
+    is_okay_json(b)
+}
+
 macro_rules! utf_transition {
     ($idx:expr) => {
         ucidx!(UTF8_TRANSITION_MAP, $idx)
@@ -0,0 +1,278 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Eviction
+//!
+//! A volatile table can be used as a cache by capping the memory used by its data
+//! (`max_memory = <bytes>`) and picking an eviction policy (`eviction = <policy>`). The memory
+//! reporter (see [`crate::services::memreport`]) checks the tables every second and evicts
+//! keys from the volatile ones that are over their cap, until they fit again. The policies are:
+//! - `lru`: the least recently used keys are evicted first
+//! - `lfu`: the least frequently used keys are evicted first (ties go to the least recently
+//! used key)
+//! - `random`: random keys are evicted
+//! - `none` (the default): nothing is evicted
+//!
+//! For `lru` and `lfu`, the engine tracks when every key was last accessed and how often, but
+//! only while one of these policies is set. Keys that weren't accessed since then count as the
+//! least recently (and frequently) used ones. Evicted keys are published as `evicted`
+//! notifications. Tables that aren't volatile keep their cap and policy, but nothing is evicted
+//! from them until they're made volatile. Like the size limits, the cap and the policy only
+//! last until the server is restarted
+
+use {
+    super::{notify::Event, KVEValue, KVEngine},
+    crate::{
+        corestore::{htable::Coremap, SharedSlice},
+        util::compiler,
+    },
+    std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering},
+};
+
+/// The number of keys that are picked at once by the `random` policy
+const RANDOM_BATCH: usize = 64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(u8)]
+/// How the keys to evict are picked
+pub enum EvictionPolicy {
+    None,
+    Lru,
+    Lfu,
+    Random,
+}
+
+impl EvictionPolicy {
+    /// Returns the policy with the given (case insensitive) name
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        let policy = match name {
+            name if name.eq_ignore_ascii_case(b"none") => Self::None,
+            name if name.eq_ignore_ascii_case(b"lru") => Self::Lru,
+            name if name.eq_ignore_ascii_case(b"lfu") => Self::Lfu,
+            name if name.eq_ignore_ascii_case(b"random") => Self::Random,
+            _ => return None,
+        };
+        Some(policy)
+    }
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Lru => "lru",
+            Self::Lfu => "lfu",
+            Self::Random => "random",
+        }
+    }
+    const fn from_u8(policy: u8) -> Self {
+        match policy {
+            1 => Self::Lru,
+            2 => Self::Lfu,
+            3 => Self::Random,
+            _ => Self::None,
+        }
+    }
+    /// Returns true if the policy needs to know how the keys are accessed
+    const fn tracks_access(self) -> bool {
+        matches!(self, Self::Lru | Self::Lfu)
+    }
+}
+
+#[derive(Debug)]
+/// When a key was last accessed (on the clock of its engine) and how often
+struct Access {
+    last: AtomicU64,
+    hits: AtomicU64,
+}
+
+impl Access {
+    fn new(now: u64) -> Self {
+        Self {
+            last: AtomicU64::new(now),
+            hits: AtomicU64::new(1),
+        }
+    }
+    fn hit(&self, now: u64) {
+        self.last.store(now, Ordering::Relaxed);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Default)]
+/// The memory cap and the eviction policy of an engine
+pub struct Eviction {
+    /// the cap in bytes (zero if there's no cap)
+    max_memory: AtomicUsize,
+    policy: AtomicU8,
+    /// the access clock, which ticks on every tracked access
+    clock: AtomicU64,
+    /// how the keys were accessed (only tracked for `lru` and `lfu`)
+    access: Coremap<SharedSlice, Access>,
+    /// the number of keys that were evicted
+    evicted: AtomicU64,
+}
+
+impl Eviction {
+    /// Shrink a shard of the access map, returning the number of bytes freed (see
+    /// [`super::compaction`])
+    pub(super) fn shrink_shard(&self, idx: usize) -> usize {
+        self.access.shrink_shard(idx)
+    }
+    /// Returns the memory cap in bytes (zero if there's no cap)
+    pub fn max_memory(&self) -> usize {
+        self.max_memory.load(Ordering::Acquire)
+    }
+    pub fn set_max_memory(&self, max: usize) {
+        self.max_memory.store(max, Ordering::Release)
+    }
+    pub fn policy(&self) -> EvictionPolicy {
+        EvictionPolicy::from_u8(self.policy.load(Ordering::Acquire))
+    }
+    pub fn set_policy(&self, policy: EvictionPolicy) {
+        let previous = EvictionPolicy::from_u8(self.policy.swap(policy as u8, Ordering::AcqRel));
+        if previous.tracks_access() && !policy.tracks_access() {
+            self.access.clear();
+        }
+    }
+    /// Returns the number of keys that were evicted
+    pub fn evicted(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+    #[inline(always)]
+    pub(super) fn is_tracking(&self) -> bool {
+        self.policy().tracks_access()
+    }
+}
+
+impl<T> KVEngine<T> {
+    /// Returns the memory cap and the eviction policy of this engine
+    pub fn eviction(&self) -> &Eviction {
+        &self.eviction
+    }
+    /// Record an access to the key, if it exists (and the eviction policy needs it)
+    #[inline(always)]
+    pub(super) fn touch(&self, key: &[u8]) {
+        if compiler::likely(!self.eviction.is_tracking()) {
+            return;
+        }
+        let now = self.eviction.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.eviction.access.get(key) {
+            Some(access) => access.hit(now),
+            None if self.data.contains_key(key) => self
+                .eviction
+                .access
+                .upsert(SharedSlice::new(key), Access::new(now)),
+            None => {}
+        }
+    }
+    /// Forget how the keys that no longer exist were accessed
+    fn forget_removed(&self) {
+        let removed: Vec<SharedSlice> = self
+            .eviction
+            .access
+            .iter()
+            .filter(|kv| !self.data.contains_key(kv.key().as_slice()))
+            .map(|kv| kv.key().clone())
+            .collect();
+        removed.into_iter().for_each(|key| {
+            self.eviction.access.true_if_removed(key.as_slice());
+        });
+    }
+    /// Evict the key, returning true if it existed
+    fn evict_key(&self, key: &[u8]) -> bool {
+        self.clear_expiry_unchecked(key);
+        self.eviction.access.true_if_removed(key);
+        let evicted = self.data.true_if_removed(key);
+        if evicted {
+            self.notify(Event::Evicted, key);
+        }
+        evicted
+    }
+}
+
+impl<T: KVEValue> KVEngine<T> {
+    /// Evict keys until the data fits in the memory cap again, returning the number of evicted
+    /// keys. Keys are only evicted if `volatile` is set; otherwise this only cleans up
+    pub fn evict(&self, volatile: bool) -> usize {
+        let policy = self.eviction.policy();
+        if policy.tracks_access() {
+            self.forget_removed();
+        }
+        let max = self.eviction.max_memory();
+        if !volatile || max == 0 || policy == EvictionPolicy::None {
+            return 0;
+        }
+        let mut usage = self.memory_usage();
+        if usage <= max {
+            return 0;
+        }
+        let mut evicted = 0;
+        let mut evict = |key: &SharedSlice| {
+            let freed = self.key_memory_usage(key).ok().flatten().unwrap_or(0);
+            if self.evict_key(key) {
+                usage = usage.saturating_sub(freed);
+                evicted += 1;
+            }
+            usage <= max
+        };
+        match policy {
+            EvictionPolicy::Random => loop {
+                let victims = self.sample_keys(RANDOM_BATCH);
+                if victims.is_empty() || victims.iter().any(&mut evict) {
+                    break;
+                }
+            },
+            _ => {
+                let victims = self.victims(policy);
+                let _ = victims.iter().any(evict);
+            }
+        }
+        self.eviction
+            .evicted
+            .fetch_add(evicted as u64, Ordering::Relaxed);
+        evicted
+    }
+    /// Returns all the keys, in the order in which the (`lru` or `lfu`) policy evicts them
+    fn victims(&self, policy: EvictionPolicy) -> Vec<SharedSlice> {
+        let mut keys: Vec<(u64, u64, SharedSlice)> = self
+            .data
+            .iter()
+            .map(|kv| {
+                let (last, hits) = match self.eviction.access.get(kv.key().as_slice()) {
+                    Some(access) => (
+                        access.last.load(Ordering::Relaxed),
+                        access.hits.load(Ordering::Relaxed),
+                    ),
+                    None => (0, 0),
+                };
+                match policy {
+                    EvictionPolicy::Lfu => (hits, last, kv.key().clone()),
+                    _ => (last, 0, kv.key().clone()),
+                }
+            })
+            .collect();
+        keys.sort_unstable_by_key(|(first, second, _)| (*first, *second));
+        keys.into_iter().map(|(_, _, key)| key).collect()
+    }
+}
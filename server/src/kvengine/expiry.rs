@@ -0,0 +1,177 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Key expiry
+//!
+//! Any key in a [`KVEngine`] can optionally carry an expiry deadline (a UNIX timestamp in
+//! milliseconds). Expired keys are evicted lazily, that is whenever they're accessed, and
+//! periodically by the expiry sweeper service. Deadlines are flushed along with the table (see
+//! [`crate::storage::v1`]), so they survive a restart
+
+use {
+    super::{notify::Event, EncodingResult, KVEngine},
+    crate::{corestore::SharedSlice, util::compiler},
+    std::time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Returns the current UNIX time in milliseconds
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the deadline for a TTL of `secs` seconds, starting now
+pub fn deadline_after_secs(secs: u64) -> u64 {
+    now_millis().saturating_add(secs.saturating_mul(1000))
+}
+
+impl<T> KVEngine<T> {
+    /// Returns true if none of the keys have an expiry set
+    #[inline(always)]
+    pub(super) fn has_no_expiries(&self) -> bool {
+        self.ttl.len() == 0
+    }
+    /// Evict the key if its deadline has passed. Returns true if the key was evicted
+    pub(super) fn evict_if_expired(&self, key: &[u8]) -> bool {
+        if compiler::likely(self.has_no_expiries()) {
+            return false;
+        }
+        let now = now_millis();
+        let expired = |_: &SharedSlice, deadline: &u64| *deadline <= now;
+        // the deadline is checked (and dropped) under the lock of the key's entry, so that we
+        // never remove a key that was just written with a new deadline (or none)
+        if self
+            .data
+            .true_remove_if(key, |_, _| self.ttl.true_remove_if(key, expired))
+        {
+            self.notify(Event::Expired, key);
+            true
+        } else {
+            // the deadline of a key that's gone is just dropped
+            self.ttl.true_remove_if(key, expired) && !self.data.contains_key(key)
+        }
+    }
+    /// Returns true if the key has a deadline that has passed (whether or not it was evicted)
+    pub(super) fn is_expired_unchecked(&self, key: &[u8]) -> bool {
+        self.deadline_unchecked(key)
+            .is_some_and(|deadline| deadline <= now_millis())
+    }
+    /// Drop the expiry for the key (if any)
+    #[inline(always)]
+    pub(super) fn clear_expiry_unchecked(&self, key: &[u8]) -> bool {
+        !self.has_no_expiries() && self.ttl.true_if_removed(key)
+    }
+    /// Set the expiry deadline for an existing key. Returns false if the key doesn't exist
+    pub fn set_expiry(&self, key: &[u8], deadline: u64) -> EncodingResult<bool> {
+        self.check_key_encoding(key)?;
+        Ok(self.set_expiry_unchecked(key, deadline))
+    }
+    /// Same as [`Self::set_expiry`], but without encoding checks
+    pub fn set_expiry_unchecked(&self, key: &[u8], deadline: u64) -> bool {
+        let _write = self.write_guard();
+        let exists = !self.evict_if_expired(key) && self.data.contains_key(key);
+        if exists {
+            self.ttl.upsert(SharedSlice::new(key), deadline);
+        }
+        exists
+    }
+    /// Remove the expiry for a key. Returns true if the key had an expiry
+    pub fn persist(&self, key: &[u8]) -> EncodingResult<bool> {
+        self.check_key_encoding(key)?;
+        let _write = self.write_guard();
+        Ok(!self.evict_if_expired(key) && self.clear_expiry_unchecked(key))
+    }
+    /// Returns the remaining time to live for the key in milliseconds. The outer option is
+    /// `None` if the key doesn't exist, while the inner option is `None` if the key has no expiry
+    pub fn remaining_ttl(&self, key: &[u8]) -> EncodingResult<Option<Option<u64>>> {
+        self.check_key_encoding(key)?;
+        if self.evict_if_expired(key) || !self.data.contains_key(key) {
+            return Ok(None);
+        }
+        let remaining = self
+            .ttl
+            .get_cloned(key)
+            .map(|deadline| deadline.saturating_sub(now_millis()));
+        Ok(Some(remaining))
+    }
+    /// Returns at most `count` of the keys that have an expiry, along with their remaining time
+    /// to live in milliseconds, soonest first
+    pub fn expiring_soonest(&self, count: usize) -> Vec<(SharedSlice, u64)> {
+        if self.has_no_expiries() {
+            return Vec::new();
+        }
+        let now = now_millis();
+        let mut expiring: Vec<(SharedSlice, u64)> = self
+            .ttl
+            .iter()
+            .filter(|kv| *kv.value() > now)
+            .map(|kv| (kv.key().clone(), *kv.value()))
+            .collect();
+        expiring.sort_unstable_by_key(|(_, deadline)| *deadline);
+        expiring.truncate(count);
+        expiring
+            .into_iter()
+            .map(|(key, deadline)| (key, deadline - now))
+            .collect()
+    }
+    /// Returns the deadline of a key (without checking its encoding), if it has one
+    pub fn deadline_unchecked(&self, key: &[u8]) -> Option<u64> {
+        if self.has_no_expiries() {
+            return None;
+        }
+        self.ttl.get_cloned(key)
+    }
+    /// Returns all the keys that have an expiry, along with their deadlines
+    pub fn deadlines(&self) -> Vec<(SharedSlice, u64)> {
+        self.ttl
+            .iter()
+            .map(|kv| (kv.key().clone(), *kv.value()))
+            .collect()
+    }
+    /// Returns the number of keys that have an expiry set
+    pub fn expiry_count(&self) -> usize {
+        self.ttl.len()
+    }
+    /// Evict all the keys whose deadline has passed, returning the number of evicted keys
+    pub fn sweep_expired(&self) -> usize {
+        if self.has_no_expiries() {
+            return 0;
+        }
+        let now = now_millis();
+        let expired: Vec<SharedSlice> = self
+            .ttl
+            .iter()
+            .filter(|kv| *kv.value() <= now)
+            .map(|kv| kv.key().clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter(|key| self.evict_if_expired(key))
+            .count()
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Geo keymaps
+//!
+//! A [`KVEGeomap`] maps every key to a [`GeoIndex`], which holds members with a location and
+//! can find the members within a radius of any location
+
+use {
+    super::{notify::Event, EncodingResult, KVEGeomap, LockedGeo},
+    crate::corestore::{geo::GeoIndex, SharedSlice},
+};
+
+impl KVEGeomap {
+    /// Add members (or update their locations) in a geo index, creating the index if it doesn't
+    /// exist. Every member is a `(longitude, latitude, member)` triple. Returns the number of
+    /// members that were newly added. Caller must check the locations
+    pub fn geo_add(
+        &self,
+        key: SharedSlice,
+        members: Vec<(f64, f64, SharedSlice)>,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        loop {
+            if let Some(geo) = self.data.get(&key) {
+                let mut wlock = geo.write();
+                let added = members
+                    .into_iter()
+                    .map(|(lon, lat, member)| wlock.insert(member, lon, lat) as usize)
+                    .sum();
+                // the locations of existing members may have changed, so this is always an update
+                self.notify(Event::Update, &key);
+                return Ok(added);
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                let mut geo = GeoIndex::new();
+                let added = members
+                    .into_iter()
+                    .map(|(lon, lat, member)| geo.insert(member, lon, lat) as usize)
+                    .sum();
+                entry.insert(LockedGeo::new(geo));
+                self.notify(Event::Set, &key);
+                return Ok(added);
+            }
+            // someone created the geo index right after we looked for it; just retry
+        }
+    }
+    /// Returns the members within `radius` meters of the location (nearest first) along with
+    /// their distances, or `None` if the geo index doesn't exist
+    pub fn geo_search_radius(
+        &self,
+        key: &[u8],
+        lon: f64,
+        lat: f64,
+        radius: f64,
+    ) -> EncodingResult<Option<Vec<(SharedSlice, f64)>>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self
+            .data
+            .get(key)
+            .map(|geo| geo.read().search_radius(lon, lat, radius)))
+    }
+}
@@ -0,0 +1,122 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Hash keymaps
+//!
+//! A [`KVEHashmap`] maps every key to a set of named fields, so that a single key can hold a
+//! structured record. Field names are always unicode strings, while the encoding of the field
+//! values depends on the model
+
+use {
+    super::{notify::Event, EncodingResult, KVEHashmap, LockedMap},
+    crate::corestore::SharedSlice,
+    std::collections::HashMap,
+};
+
+impl KVEHashmap {
+    /// Set fields in a hash, creating the hash if it doesn't exist. Returns the number of
+    /// fields that were newly added. Caller must check the encoding of the fields and values
+    pub fn hash_set(
+        &self,
+        hashname: SharedSlice,
+        fields: Vec<(SharedSlice, SharedSlice)>,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&hashname)?;
+        self.evict_if_expired(&hashname);
+        loop {
+            if let Some(hash) = self.data.get(&hashname) {
+                let mut wlock = hash.write();
+                let added = fields
+                    .into_iter()
+                    .map(|(field, value)| wlock.insert(field, value).is_none() as usize)
+                    .sum();
+                // the values of existing fields may have changed, so this is always an update
+                self.notify(Event::Update, &hashname);
+                return Ok(added);
+            }
+            if let Some(entry) = self.data.fresh_entry(hashname.clone()) {
+                let hash: HashMap<SharedSlice, SharedSlice> = fields.into_iter().collect();
+                let added = hash.len();
+                entry.insert(LockedMap::new(hash));
+                self.notify(Event::Set, &hashname);
+                return Ok(added);
+            }
+            // someone removed the hash right after we found it; just retry
+        }
+    }
+    /// Returns the value of a field. The outer option is `None` if the hash doesn't exist,
+    /// while the inner option is `None` if the field doesn't exist
+    pub fn hash_get(
+        &self,
+        hashname: &[u8],
+        field: &[u8],
+    ) -> EncodingResult<Option<Option<SharedSlice>>> {
+        self.check_key_encoding(hashname)?;
+        self.evict_if_expired(hashname);
+        Ok(self
+            .data
+            .get(hashname)
+            .map(|hash| hash.read().get(field).cloned()))
+    }
+    /// Remove fields from a hash. Returns the number of fields that were removed or `None`
+    /// if the hash doesn't exist
+    pub fn hash_del<Q: AsRef<[u8]>>(
+        &self,
+        hashname: &[u8],
+        fields: impl Iterator<Item = Q>,
+    ) -> EncodingResult<Option<usize>> {
+        self.check_key_encoding(hashname)?;
+        self.evict_if_expired(hashname);
+        Ok(self.data.get(hashname).map(|hash| {
+            let mut wlock = hash.write();
+            let removed = fields
+                .filter(|field| wlock.remove(field.as_ref()).is_some())
+                .count();
+            if removed != 0 {
+                self.notify(Event::Update, hashname);
+            }
+            removed
+        }))
+    }
+    /// Returns all the fields and their values, sorted by the field name, or `None` if the
+    /// hash doesn't exist
+    pub fn hash_get_all(
+        &self,
+        hashname: &[u8],
+    ) -> EncodingResult<Option<Vec<(SharedSlice, SharedSlice)>>> {
+        self.check_key_encoding(hashname)?;
+        self.evict_if_expired(hashname);
+        Ok(self.data.get(hashname).map(|hash| {
+            let mut fields: Vec<(SharedSlice, SharedSlice)> = hash
+                .read()
+                .iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect();
+            fields.sort_unstable();
+            fields
+        }))
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # HyperLogLog keymaps
+//!
+//! A [`KVEHllmap`] maps every key to a [`HyperLogLog`] that estimates the number of distinct
+//! items added to it. Adding to (or merging into) an HLL that doesn't exist creates it
+
+use {
+    super::{notify::Event, EncodingResult, KVEHllmap, LockedHll},
+    crate::corestore::{hll::HyperLogLog, SharedSlice},
+};
+
+impl KVEHllmap {
+    /// Add items to an HLL, creating it if it doesn't exist. Returns true if the HLL was
+    /// created or if its estimate (possibly) changed
+    pub fn hll_add<'a>(
+        &self,
+        key: SharedSlice,
+        items: impl Iterator<Item = &'a [u8]>,
+    ) -> EncodingResult<bool> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        let (changed, event) = loop {
+            if let Some(hll) = self.data.get(&key) {
+                let mut wlock = hll.write();
+                // don't short-circuit; every item has to be added
+                let changed = items.fold(false, |changed, item| wlock.add(item) | changed);
+                break (changed, Event::Update);
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                let mut hll = HyperLogLog::new();
+                items.for_each(|item| {
+                    hll.add(item);
+                });
+                entry.insert(LockedHll::new(hll));
+                break (true, Event::Set);
+            }
+            // someone created the HLL right after we looked for it; just retry
+        };
+        if changed {
+            self.notify(event, &key);
+        }
+        Ok(changed)
+    }
+    /// Returns the estimated number of distinct items across all the given HLLs. HLLs that
+    /// don't exist are treated as empty
+    pub fn hll_count<'a>(&self, keys: impl Iterator<Item = &'a [u8]>) -> EncodingResult<u64> {
+        let mut union = HyperLogLog::new();
+        for key in keys {
+            self.check_key_encoding(key)?;
+            self.evict_if_expired(key);
+            if let Some(hll) = self.data.get(key) {
+                union.merge(&hll.read());
+            }
+        }
+        Ok(union.count())
+    }
+    /// Merge the source HLLs into the destination HLL, creating it if it doesn't exist.
+    /// Sources that don't exist are treated as empty
+    pub fn hll_merge<'a>(
+        &self,
+        dst: SharedSlice,
+        sources: impl Iterator<Item = &'a [u8]>,
+    ) -> EncodingResult<()> {
+        self.check_key_encoding(&dst)?;
+        // union the sources first so that we never hold more than one lock at a time (the
+        // destination might be one of the sources)
+        let mut union = HyperLogLog::new();
+        for key in sources {
+            self.check_key_encoding(key)?;
+            self.evict_if_expired(key);
+            if let Some(hll) = self.data.get(key) {
+                union.merge(&hll.read());
+            }
+        }
+        self.evict_if_expired(&dst);
+        let event = loop {
+            if let Some(hll) = self.data.get(&dst) {
+                hll.write().merge(&union);
+                break Event::Update;
+            }
+            if let Some(entry) = self.data.fresh_entry(dst.clone()) {
+                entry.insert(LockedHll::new(union));
+                break Event::Set;
+            }
+            // someone created the HLL right after we looked for it; just retry
+        };
+        self.notify(event, &dst);
+        Ok(())
+    }
+}
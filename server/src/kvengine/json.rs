@@ -0,0 +1,434 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # JSON values
+//!
+//! Tables declared with a `json` value type store their values as the raw JSON text that was
+//! written to them. We don't keep a parsed tree around: the text is validated on every write
+//! (see [`super::encoding::is_okay_json`]) and lookups just scan the text, skipping over the
+//! values that they aren't interested in.
+//!
+//! Both the validator and the lookups are iterative, so deeply nested documents can't blow
+//! up the stack
+
+use {
+    super::{EncodingResult, KVEStandard},
+    crate::corestore::SharedSlice,
+};
+
+/// A segment in a JSON path
+#[derive(Debug, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    /// an object member (`.name`)
+    Key(&'a [u8]),
+    /// an array element (`[index]`)
+    Index(usize),
+}
+
+/// Returns true if `bytes` is a single valid JSON value (surrounding whitespace is allowed).
+/// This doesn't check if `bytes` is valid UTF-8
+pub fn is_json(bytes: &[u8]) -> bool {
+    match skip_value(bytes, 0) {
+        Some(end) => skip_ws(bytes, end) == bytes.len(),
+        None => false,
+    }
+}
+
+/// Parse a path like `$.users[0].name`. The leading `$` (the document itself) is optional,
+/// and so is the `.` before the first member name. Returns `None` if the path is malformed
+pub fn parse_path(path: &[u8]) -> Option<Vec<PathSegment<'_>>> {
+    let mut segments = Vec::new();
+    let mut i = 0;
+    match path.first() {
+        Some(b'$') => i = 1,
+        Some(b'.') | Some(b'[') | None => {}
+        // a bare member name
+        Some(_) => {
+            let end = name_end(path, 0);
+            segments.push(PathSegment::Key(&path[..end]));
+            i = end;
+        }
+    }
+    while i < path.len() {
+        match path[i] {
+            b'.' => {
+                let end = name_end(path, i + 1);
+                if end == i + 1 {
+                    return None;
+                }
+                segments.push(PathSegment::Key(&path[i + 1..end]));
+                i = end;
+            }
+            b'[' => {
+                let close = i + 1 + path[i + 1..].iter().position(|b| *b == b']')?;
+                let digits = &path[i + 1..close];
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+                let index = std::str::from_utf8(digits).ok()?.parse().ok()?;
+                segments.push(PathSegment::Index(index));
+                i = close + 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(segments)
+}
+
+/// Returns the raw JSON text for the value at `path` in `doc`, or `None` if there's no such
+/// value. `doc` must be valid JSON
+pub fn lookup<'a>(doc: &'a [u8], path: &[PathSegment]) -> Option<&'a [u8]> {
+    let mut start = skip_ws(doc, 0);
+    for segment in path {
+        start = match segment {
+            PathSegment::Key(name) => find_member(doc, start, name)?,
+            PathSegment::Index(index) => find_element(doc, start, *index)?,
+        };
+    }
+    let end = skip_value(doc, start)?;
+    Some(&doc[start..end])
+}
+
+impl KVEStandard {
+    /// Returns the raw JSON text at `path` in the value of `key`. The outer option is `None` if
+    /// the key doesn't exist, while the inner option is `None` if there's nothing at `path`
+    pub fn json_get(
+        &self,
+        key: &[u8],
+        path: &[PathSegment],
+    ) -> EncodingResult<Option<Option<SharedSlice>>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self
+            .data
+            .get(key)
+            .map(|doc| self::lookup(&doc, path).map(SharedSlice::new)))
+    }
+}
+
+/// Parse a JSON array of strings (like `["SET", "x", "100"]`), resolving the escapes in the
+/// strings. Returns `None` if `doc` is anything else
+pub fn parse_string_array(doc: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut i = skip_ws(doc, 0);
+    if doc.get(i) != Some(&b'[') {
+        return None;
+    }
+    let mut strings = Vec::new();
+    i = skip_ws(doc, i + 1);
+    if doc.get(i) == Some(&b']') {
+        i += 1;
+    } else {
+        loop {
+            let end = skip_string(doc, i)?;
+            strings.push(decode_string(&doc[i + 1..end - 1])?);
+            i = skip_ws(doc, end);
+            match *doc.get(i)? {
+                b',' => i = skip_ws(doc, i + 1),
+                b']' => {
+                    i += 1;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+    }
+    if skip_ws(doc, i) == doc.len() {
+        Some(strings)
+    } else {
+        None
+    }
+}
+
+/// Decode a string value, given its raw JSON text (with the quotes, as returned by [`lookup`]).
+/// Returns `None` if `value` isn't a string
+pub fn parse_string(value: &[u8]) -> Option<Vec<u8>> {
+    if skip_string(value, 0) == Some(value.len()) {
+        decode_string(&value[1..value.len() - 1])
+    } else {
+        None
+    }
+}
+
+/// Returns the end of the member name starting at `start`
+fn name_end(path: &[u8], start: usize) -> usize {
+    path[start..]
+        .iter()
+        .position(|b| *b == b'.' || *b == b'[')
+        .map(|len| start + len)
+        .unwrap_or(path.len())
+}
+
+/// Returns the offset of the value of the member `name` in the object at `start`
+fn find_member(doc: &[u8], start: usize, name: &[u8]) -> Option<usize> {
+    if doc.get(start) != Some(&b'{') {
+        return None;
+    }
+    let mut i = skip_ws(doc, start + 1);
+    if doc.get(i) == Some(&b'}') {
+        return None;
+    }
+    loop {
+        let key_end = skip_string(doc, i)?;
+        let is_match = string_eq(&doc[i + 1..key_end - 1], name);
+        i = skip_ws(doc, key_end);
+        if doc.get(i) != Some(&b':') {
+            return None;
+        }
+        i = skip_ws(doc, i + 1);
+        if is_match {
+            return Some(i);
+        }
+        i = skip_ws(doc, skip_value(doc, i)?);
+        if doc.get(i) != Some(&b',') {
+            return None;
+        }
+        i = skip_ws(doc, i + 1);
+    }
+}
+
+/// Returns the offset of the element at `index` in the array at `start`
+fn find_element(doc: &[u8], start: usize, index: usize) -> Option<usize> {
+    if doc.get(start) != Some(&b'[') {
+        return None;
+    }
+    let mut i = skip_ws(doc, start + 1);
+    if doc.get(i) == Some(&b']') {
+        return None;
+    }
+    for _ in 0..index {
+        i = skip_ws(doc, skip_value(doc, i)?);
+        if doc.get(i) != Some(&b',') {
+            return None;
+        }
+        i = skip_ws(doc, i + 1);
+    }
+    Some(i)
+}
+
+/// Compare the raw text of a string (without the quotes) with `expected`, after resolving
+/// any escapes
+fn string_eq(raw: &[u8], expected: &[u8]) -> bool {
+    if !raw.contains(&b'\\') {
+        return raw == expected;
+    }
+    // a lone surrogate can never match a valid UTF-8 name
+    decode_string(raw).map_or(false, |decoded| decoded == expected)
+}
+
+/// Resolve the escapes in the raw text of a string (without the quotes). The escapes must
+/// have been validated already (see [`skip_string`]). Returns `None` if the string has a
+/// lone surrogate
+fn decode_string(raw: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] != b'\\' {
+            decoded.push(raw[i]);
+            i += 1;
+            continue;
+        }
+        let escaped = match raw[i + 1] {
+            b'b' => b'\x08',
+            b'f' => b'\x0C',
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'u' => {
+                let (c, len) = decode_escaped_char(&raw[i..])?;
+                let mut buf = [0u8; 4];
+                decoded.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                i += len;
+                continue;
+            }
+            other => other,
+        };
+        decoded.push(escaped);
+        i += 2;
+    }
+    Some(decoded)
+}
+
+/// Decode a `\uXXXX` escape (or a surrogate pair of them) at the start of `raw`, returning
+/// the character and the number of bytes consumed
+fn decode_escaped_char(raw: &[u8]) -> Option<(char, usize)> {
+    let high = hex4(&raw[2..6])?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return char::from_u32(high).map(|c| (c, 6));
+    }
+    if raw.get(6..8) != Some(b"\\u") {
+        return None;
+    }
+    let low = hex4(raw.get(8..12)?)?;
+    if !(0xDC00..0xE000).contains(&low) {
+        return None;
+    }
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).map(|c| (c, 12))
+}
+
+fn hex4(digits: &[u8]) -> Option<u32> {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+}
+
+/// Returns the offset of the first non-whitespace byte at or after `i`
+fn skip_ws(doc: &[u8], mut i: usize) -> usize {
+    while matches!(doc.get(i), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the offset right after the value starting at (or after the whitespace at) `i`,
+/// or `None` if there's no valid value there
+fn skip_value(doc: &[u8], i: usize) -> Option<usize> {
+    // the closing brackets of the containers that we're in
+    let mut stack = Vec::new();
+    let mut i = skip_ws(doc, i);
+    loop {
+        match *doc.get(i)? {
+            b'{' => {
+                i = skip_ws(doc, i + 1);
+                if doc.get(i) == Some(&b'}') {
+                    i += 1;
+                } else {
+                    stack.push(b'}');
+                    i = skip_member_name(doc, i)?;
+                    continue;
+                }
+            }
+            b'[' => {
+                i = skip_ws(doc, i + 1);
+                if doc.get(i) == Some(&b']') {
+                    i += 1;
+                } else {
+                    stack.push(b']');
+                    continue;
+                }
+            }
+            b'"' => i = skip_string(doc, i)?,
+            b't' => i = skip_literal(doc, i, b"true")?,
+            b'f' => i = skip_literal(doc, i, b"false")?,
+            b'n' => i = skip_literal(doc, i, b"null")?,
+            _ => i = skip_number(doc, i)?,
+        }
+        // we just finished a value; close all the containers that end here
+        loop {
+            let close = match stack.last() {
+                Some(close) => *close,
+                None => return Some(i),
+            };
+            i = skip_ws(doc, i);
+            match *doc.get(i)? {
+                b',' => {
+                    i = skip_ws(doc, i + 1);
+                    if close == b'}' {
+                        i = skip_member_name(doc, i)?;
+                    }
+                    break;
+                }
+                byte if byte == close => {
+                    stack.pop();
+                    i += 1;
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Skip a member name and the colon after it, returning the offset of the member's value
+fn skip_member_name(doc: &[u8], i: usize) -> Option<usize> {
+    let i = skip_ws(doc, skip_string(doc, i)?);
+    if doc.get(i) == Some(&b':') {
+        Some(skip_ws(doc, i + 1))
+    } else {
+        None
+    }
+}
+
+/// Returns the offset right after the string starting at `i`
+fn skip_string(doc: &[u8], mut i: usize) -> Option<usize> {
+    if doc.get(i) != Some(&b'"') {
+        return None;
+    }
+    i += 1;
+    loop {
+        match *doc.get(i)? {
+            b'"' => return Some(i + 1),
+            b'\\' => match *doc.get(i + 1)? {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => i += 2,
+                b'u' if doc.get(i + 2..i + 6)?.iter().all(u8::is_ascii_hexdigit) => i += 6,
+                _ => return None,
+            },
+            // control characters must be escaped
+            0x00..=0x1F => return None,
+            _ => i += 1,
+        }
+    }
+}
+
+fn skip_literal(doc: &[u8], i: usize, literal: &[u8]) -> Option<usize> {
+    if doc.get(i..i + literal.len()) == Some(literal) {
+        Some(i + literal.len())
+    } else {
+        None
+    }
+}
+
+/// Returns the offset right after the number starting at `i`
+fn skip_number(doc: &[u8], mut i: usize) -> Option<usize> {
+    fn skip_digits(doc: &[u8], mut i: usize) -> Option<usize> {
+        let start = i;
+        while matches!(doc.get(i), Some(b'0'..=b'9')) {
+            i += 1;
+        }
+        if i == start {
+            None
+        } else {
+            Some(i)
+        }
+    }
+    if doc.get(i) == Some(&b'-') {
+        i += 1;
+    }
+    // no leading zeroes
+    i = match *doc.get(i)? {
+        b'0' => i + 1,
+        _ => skip_digits(doc, i)?,
+    };
+    if doc.get(i) == Some(&b'.') {
+        i = skip_digits(doc, i + 1)?;
+    }
+    if matches!(doc.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(doc.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        i = skip_digits(doc, i)?;
+    }
+    Some(i)
+}
@@ -0,0 +1,153 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Size limits
+//!
+//! A table can optionally cap the size of its keys and of its values (for collections, the
+//! limit applies to every element). Writes that would break a limit are rejected with a
+//! `too-large` error instead of being applied. A limit of zero means that there is no limit.
+//! Like the creation time of a table, the limits aren't recorded in the PARTMAP, so they only
+//! last until the server is restarted
+
+use {
+    super::{storage::Unreadable, KVEngine},
+    crate::util::compiler,
+    std::sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[derive(Debug, Default)]
+/// The size limits for the keys and values of an engine
+pub struct SizeLimits {
+    max_key_size: AtomicUsize,
+    max_value_size: AtomicUsize,
+}
+
+impl SizeLimits {
+    pub fn new(max_key_size: usize, max_value_size: usize) -> Self {
+        Self {
+            max_key_size: AtomicUsize::new(max_key_size),
+            max_value_size: AtomicUsize::new(max_value_size),
+        }
+    }
+    /// Returns the maximum size of a key in bytes (zero if there is no limit)
+    pub fn max_key_size(&self) -> usize {
+        self.max_key_size.load(Ordering::Acquire)
+    }
+    /// Returns the maximum size of a value in bytes (zero if there is no limit)
+    pub fn max_value_size(&self) -> usize {
+        self.max_value_size.load(Ordering::Acquire)
+    }
+    pub fn set_max_key_size(&self, max: usize) {
+        self.max_key_size.store(max, Ordering::Release)
+    }
+    pub fn set_max_value_size(&self, max: usize) {
+        self.max_value_size.store(max, Ordering::Release)
+    }
+    /// Returns true if neither keys nor values are limited
+    pub fn is_unlimited(&self) -> bool {
+        self.max_key_size() == 0 && self.max_value_size() == 0
+    }
+    /// Check if a key of `len` bytes is within the limit
+    pub fn key_fits(&self, len: usize) -> bool {
+        Self::_fits(self.max_key_size(), len)
+    }
+    /// Check if a value of `len` bytes is within the limit
+    pub fn value_fits(&self, len: usize) -> bool {
+        Self::_fits(self.max_value_size(), len)
+    }
+    #[inline(always)]
+    fn _fits(max: usize, len: usize) -> bool {
+        max == 0 || len <= max
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The reason a write that grows a value failed
+pub enum WriteError {
+    /// the key or value has the wrong encoding
+    Encoding,
+    /// the resulting value would break the size limit
+    TooLarge,
+    /// the current value couldn't be read back (see [`Unreadable`])
+    Unreadable,
+}
+
+impl From<()> for WriteError {
+    fn from(_: ()) -> Self {
+        Self::Encoding
+    }
+}
+
+impl From<Unreadable> for WriteError {
+    fn from(_: Unreadable) -> Self {
+        Self::Unreadable
+    }
+}
+
+pub type WriteResult<T> = Result<T, WriteError>;
+
+impl<T> KVEngine<T> {
+    /// Returns the size limits of this engine
+    pub fn limits(&self) -> &SizeLimits {
+        &self.limits
+    }
+    /// Check if the key and all the values are within the size limits
+    pub fn fits<'a>(&self, key: &[u8], values: impl IntoIterator<Item = &'a [u8]>) -> bool {
+        if compiler::likely(self.limits.is_unlimited()) {
+            return true;
+        }
+        self.limits.key_fits(key.len())
+            && values.into_iter().all(|v| self.limits.value_fits(v.len()))
+    }
+    /// Check if the key and the value are within the size limits
+    pub fn pair_fits(&self, key: &[u8], value: &[u8]) -> bool {
+        self.fits(key, Some(value))
+    }
+    /// Check if the key is within the size limit
+    pub fn key_fits(&self, key: &[u8]) -> bool {
+        self.limits.key_fits(key.len())
+    }
+    /// Check if every key/value pair (laid out as `k1, v1, k2, v2, ...`) is within the size limits
+    pub fn pairs_fit<'a>(&self, mut pairs: impl Iterator<Item = &'a [u8]>) -> bool {
+        if compiler::likely(self.limits.is_unlimited()) {
+            return true;
+        }
+        let mut is_key = true;
+        pairs.all(|item| {
+            let fits = if is_key {
+                self.limits.key_fits(item.len())
+            } else {
+                self.limits.value_fits(item.len())
+            };
+            is_key = !is_key;
+            fits
+        })
+    }
+    /// Check if a value of `len` bytes is within the size limit
+    pub(super) fn value_len_fits(&self, len: usize) -> bool {
+        self.limits.value_fits(len)
+    }
+}
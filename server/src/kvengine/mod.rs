@@ -26,24 +26,93 @@
 
 #![allow(dead_code)] // TODO(@ohsayan): Clean this up later
 
+pub mod bloom;
+pub mod compaction;
+pub mod compression;
+pub mod counters;
+pub mod disk;
 pub mod encoding;
+pub mod eviction;
+pub mod expiry;
+pub mod geo;
+pub mod hashes;
+pub mod hll;
+pub mod json;
+pub mod limits;
+pub mod notify;
+pub mod pattern;
+pub mod sample;
+pub mod sets;
+pub mod snapshot;
+pub mod storage;
+pub mod strings;
+pub mod timeseries;
+pub mod txn;
+pub mod zsets;
+
 #[cfg(test)]
 mod tests;
 
 use {
-    self::encoding::{ENCODING_LUT, ENCODING_LUT_PAIR},
+    self::{
+        disk::Loaded,
+        encoding::{
+            ENCODING_LUT, ENCODING_LUT_ITER_PAIR, ENCODING_LUT_JSON_ITER_PAIR,
+            ENCODING_LUT_JSON_PAIR, ENCODING_LUT_PAIR,
+        },
+        eviction::Eviction,
+        limits::SizeLimits,
+        notify::{Event, Notifier},
+        pattern::Pattern,
+        storage::{Compressed, ReadError, ReadResult, StorageEngine, StorageKind, Unreadable},
+    },
     crate::{
-        corestore::{booltable::BoolTable, htable::Coremap, map::bref::Ref, SharedSlice},
+        corestore::{
+            bloom::BloomFilter,
+            booltable::BoolTable,
+            geo::{GeoIndex, Point},
+            hll::HyperLogLog,
+            htable::Coremap,
+            map::bref::Ref,
+            timeseries::{Sample, TimeSeries},
+            zset::SortedSet,
+            SharedSlice,
+        },
+        protocol::iter::AnyArrayIter,
         util::compiler,
     },
-    parking_lot::RwLock,
+    parking_lot::{RwLock, RwLockReadGuard},
+    std::{
+        collections::{HashMap, HashSet},
+        mem, ptr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
 };
 
 pub type KVEStandard = KVEngine<SharedSlice>;
 pub type KVEListmap = KVEngine<LockedVec>;
 pub type LockedVec = RwLock<Vec<SharedSlice>>;
+pub type KVESetmap = KVEngine<LockedSet>;
+pub type LockedSet = RwLock<HashSet<SharedSlice>>;
+pub type KVEZsetmap = KVEngine<LockedZset>;
+pub type LockedZset = RwLock<SortedSet>;
+pub type KVEHashmap = KVEngine<LockedMap>;
+pub type LockedMap = RwLock<HashMap<SharedSlice, SharedSlice>>;
+pub type KVECountermap = KVEngine<AtomicU64>;
+pub type KVEBloommap = KVEngine<LockedBloom>;
+pub type LockedBloom = RwLock<BloomFilter>;
+pub type KVEHllmap = KVEngine<LockedHll>;
+pub type LockedHll = RwLock<HyperLogLog>;
+pub type KVEGeomap = KVEngine<LockedGeo>;
+pub type LockedGeo = RwLock<GeoIndex>;
+pub type KVETimeseriesmap = KVEngine<LockedTimeseries>;
+pub type LockedTimeseries = RwLock<TimeSeries>;
 pub type SingleEncoder = fn(&[u8]) -> bool;
 pub type DoubleEncoder = fn(&[u8], &[u8]) -> bool;
+pub type PairIterEncoder = fn(&AnyArrayIter) -> bool;
 type EntryRef<'a, T> = Ref<'a, SharedSlice, T>;
 type EncodingResult<T> = Result<T, ()>;
 type OptionRef<'a, T> = Option<Ref<'a, SharedSlice, T>>;
@@ -51,43 +120,256 @@ type EncodingResultRef<'a, T> = EncodingResult<OptionRef<'a, T>>;
 
 const TSYMBOL_LUT: BoolTable<u8> = BoolTable::new(b'+', b'?');
 
-pub trait KVEValue {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()>;
+pub trait KVEValue: Sized {
+    /// Check the encoding of the value (or of its elements) with the engine's value encoder
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()>;
+    /// Returns an independent copy of the value
+    fn duplicate(&self) -> Self;
+    /// Returns the approximate number of bytes used by the value
+    fn memory_usage(&self) -> usize;
+    /// Returns the form in which the value is kept by the given storage engine (see
+    /// [`storage`]). Only blobs are handed to a storage engine; everything else is kept as is
+    fn pack(self, _storage: &dyn StorageEngine) -> Self {
+        self
+    }
+    /// Returns the value from the form returned by [`Self::pack`]
+    fn unpack(self, _storage: &dyn StorageEngine) -> Result<Self, Unreadable> {
+        Ok(self)
+    }
+}
+
+/// Returns the approximate number of bytes used by a slice, including its handle
+fn slice_memory_usage(slice: &SharedSlice) -> usize {
+    mem::size_of::<SharedSlice>() + slice.len()
 }
 
 impl KVEValue for SharedSlice {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()> {
-        if ENCODING_LUT[e_v](self) {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        if venc(self) {
             Ok(())
         } else {
             Err(())
         }
     }
+    fn duplicate(&self) -> Self {
+        self.clone()
+    }
+    fn memory_usage(&self) -> usize {
+        slice_memory_usage(self)
+    }
+    fn pack(self, storage: &dyn StorageEngine) -> Self {
+        storage.pack(&self)
+    }
+    fn unpack(self, storage: &dyn StorageEngine) -> Result<Self, Unreadable> {
+        storage.unpack(&self)
+    }
 }
 
 impl KVEValue for LockedVec {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()> {
-        let func = ENCODING_LUT[e_v];
-        if self.read().iter().all(|v| func(v)) {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        if self.read().iter().all(|v| venc(v)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.read().iter().map(slice_memory_usage).sum::<usize>()
+    }
+}
+
+impl KVEValue for LockedSet {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        if self.read().iter().all(|v| venc(v)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.read().iter().map(slice_memory_usage).sum::<usize>()
+    }
+}
+
+impl KVEValue for LockedZset {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        if self.read().iter().all(|(member, _)| venc(member)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        // every member is held twice: once in the score index and once in the ordered index
+        mem::size_of::<Self>()
+            + self
+                .read()
+                .iter()
+                .map(|(member, _)| 2 * (slice_memory_usage(member) + mem::size_of::<f64>()))
+                .sum::<usize>()
+    }
+}
+
+impl KVEValue for LockedGeo {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        if self.read().iter().all(|(member, _)| venc(member)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        // every member is held twice: once with its location and once in the geohash index
+        mem::size_of::<Self>()
+            + self
+                .read()
+                .iter()
+                .map(|(member, _)| {
+                    2 * slice_memory_usage(member) + mem::size_of::<Point>() + mem::size_of::<u64>()
+                })
+                .sum::<usize>()
+    }
+}
+
+impl KVEValue for LockedTimeseries {
+    fn verify_encoding(&self, _: SingleEncoder) -> EncodingResult<()> {
+        // samples are numbers, so there's nothing to check
+        Ok(())
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.read().len() * mem::size_of::<Sample>()
+    }
+}
+
+impl KVEValue for LockedMap {
+    fn verify_encoding(&self, venc: SingleEncoder) -> EncodingResult<()> {
+        // field names are always unicode strings
+        let fenc = ENCODING_LUT[true];
+        if self.read().iter().all(|(f, v)| fenc(f) && venc(v)) {
             Ok(())
         } else {
             Err(())
         }
     }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>()
+            + self
+                .read()
+                .iter()
+                .map(|(f, v)| slice_memory_usage(f) + slice_memory_usage(v))
+                .sum::<usize>()
+    }
+}
+
+impl KVEValue for AtomicU64 {
+    fn verify_encoding(&self, _: SingleEncoder) -> EncodingResult<()> {
+        // integers don't have an encoding
+        Ok(())
+    }
+    fn duplicate(&self) -> Self {
+        AtomicU64::new(self.load(Ordering::Acquire))
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>()
+    }
+}
+
+impl KVEValue for LockedBloom {
+    fn verify_encoding(&self, _: SingleEncoder) -> EncodingResult<()> {
+        // the items aren't stored, so there's nothing to check
+        Ok(())
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.read().size()
+    }
+}
+
+impl KVEValue for LockedHll {
+    fn verify_encoding(&self, _: SingleEncoder) -> EncodingResult<()> {
+        // the items aren't stored, so there's nothing to check
+        Ok(())
+    }
+    fn duplicate(&self) -> Self {
+        RwLock::new(self.read().clone())
+    }
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.read().size()
+    }
 }
 
 #[derive(Debug)]
 pub struct KVEngine<T> {
     data: Coremap<SharedSlice, T>,
+    /// expiry deadlines (UNIX millis) for the keys that have a TTL
+    ttl: Coremap<SharedSlice, u64>,
+    /// transactions (along with snapshots and moves) hold this exclusively, while every other
+    /// write holds it shared (see [`Self::write_guard`])
+    txn_lock: RwLock<()>,
     e_k: bool,
     e_v: bool,
+    /// the values must be valid JSON (this implies `e_v`)
+    json: bool,
+    /// where the values are kept, if they aren't kept in memory as is (see [`storage`])
+    storage: Option<Arc<dyn StorageEngine>>,
+    /// keyspace notifications for this engine
+    notifier: Notifier,
+    /// the size limits for keys and values
+    limits: SizeLimits,
+    /// the memory cap and the eviction policy
+    eviction: Eviction,
 }
 
 // basic method impls
 impl<T> KVEngine<T> {
     /// Create a new KVEBlob
     pub fn new(e_k: bool, e_v: bool, data: Coremap<SharedSlice, T>) -> Self {
-        Self { data, e_k, e_v }
+        Self {
+            data,
+            ttl: Coremap::new(),
+            txn_lock: RwLock::new(()),
+            e_k,
+            e_v,
+            json: false,
+            storage: None,
+            notifier: Notifier::default(),
+            limits: SizeLimits::default(),
+            eviction: Eviction::default(),
+        }
+    }
+    /// Create a new KVEBlob whose values must be valid JSON
+    pub fn new_json(e_k: bool, data: Coremap<SharedSlice, T>) -> Self {
+        Self {
+            json: true,
+            ..Self::new(e_k, true, data)
+        }
+    }
+    /// Create a new KVEBlob that stores its values compressed
+    pub fn new_compressed(e_k: bool, e_v: bool, data: Coremap<SharedSlice, T>) -> Self {
+        Self {
+            storage: Some(Arc::new(Compressed)),
+            ..Self::new(e_k, e_v, data)
+        }
     }
     /// Create a new empty KVEBlob
     pub fn init(e_k: bool, e_v: bool) -> Self {
@@ -99,19 +381,56 @@ impl<T> KVEngine<T> {
     }
     /// Delete all the key/value pairs
     pub fn truncate_table(&self) {
-        self.data.clear()
+        let _write = self.write_guard();
+        if compiler::unlikely(self.notifier.is_active()) {
+            self.data
+                .iter()
+                .for_each(|kv| self.notify(Event::Del, kv.key()));
+        }
+        self.data.clear();
+        self.ttl.clear()
     }
     /// Returns a reference to the inner structure
     pub fn get_inner_ref(&self) -> &Coremap<SharedSlice, T> {
         &self.data
     }
+    /// Returns all the keys, after evicting the expired ones
+    pub fn get_all_keys(&self) -> Vec<SharedSlice> {
+        self.sweep_expired();
+        self.data.iter().map(|kv| kv.key().clone()).collect()
+    }
+    /// Returns the next batch of keys of a scan, along with the cursor to continue from (see
+    /// [`Coremap::scan`]). Expired keys are evicted instead of being returned
+    pub fn scan_keys(&self, cursor: u64, count: usize) -> (u64, Vec<SharedSlice>) {
+        let (cursor, mut keys) = self.data.scan(cursor, count);
+        if !self.has_no_expiries() {
+            keys.retain(|key| !self.evict_if_expired(key));
+        }
+        (cursor, keys)
+    }
+    /// Returns all the keys that match the pattern, after evicting the expired ones
+    pub fn get_keys_matching(&self, pattern: &Pattern) -> Vec<SharedSlice> {
+        self.sweep_expired();
+        self.data
+            .iter()
+            .filter(|kv| pattern.matches(kv.key()))
+            .map(|kv| kv.key().clone())
+            .collect()
+    }
+    /// Hold off transactions on this engine while a plain write happens. Writes can nest (for
+    /// example, when one write is built on top of another), so this never waits behind a
+    /// transaction that's queued up for the lock
+    #[inline(always)]
+    fn write_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.txn_lock.read_recursive()
+    }
     /// Check the encoding of the key
     pub fn is_key_ok(&self, key: &[u8]) -> bool {
         self._check_encoding(key, self.e_k)
     }
     /// Check the encoding of the value
     pub fn is_val_ok(&self, val: &[u8]) -> bool {
-        self._check_encoding(val, self.e_v)
+        self.get_val_encoder()(val)
     }
     #[inline(always)]
     fn check_key_encoding(&self, item: &[u8]) -> Result<(), ()> {
@@ -119,7 +438,11 @@ impl<T> KVEngine<T> {
     }
     #[inline(always)]
     fn check_value_encoding(&self, item: &[u8]) -> Result<(), ()> {
-        self.check_encoding(item, self.e_v)
+        if compiler::likely(self.is_val_ok(item)) {
+            Ok(())
+        } else {
+            Err(())
+        }
     }
     #[inline(always)]
     fn _check_encoding(&self, item: &[u8], encoded: bool) -> bool {
@@ -139,6 +462,28 @@ impl<T> KVEngine<T> {
     pub fn is_val_encoded(&self) -> bool {
         self.e_v
     }
+    /// Returns true if the values must be valid JSON
+    pub fn is_json(&self) -> bool {
+        self.json
+    }
+    /// Returns true if the values are stored compressed
+    pub fn is_compressed(&self) -> bool {
+        self.storage_kind() == StorageKind::Compressed
+    }
+    /// Returns the kind of the storage engine that keeps the values
+    pub fn storage_kind(&self) -> StorageKind {
+        self.storage
+            .as_ref()
+            .map_or(StorageKind::Memory, |storage| storage.kind())
+    }
+    /// Returns true if the stored form of a value in this engine means the same to `other`
+    fn shares_storage_with(&self, other: &Self) -> bool {
+        match (&self.storage, &other.storage) {
+            (None, None) => true,
+            (Some(ours), Some(theirs)) => ours.is_portable() && ours.kind() == theirs.kind(),
+            _ => false,
+        }
+    }
     /// Get the key tsymbol
     pub fn get_key_tsymbol(&self) -> u8 {
         TSYMBOL_LUT[self.e_k]
@@ -157,30 +502,138 @@ impl<T> KVEngine<T> {
     }
     /// Returns an encoder fnptr for the value
     pub fn get_val_encoder(&self) -> SingleEncoder {
-        ENCODING_LUT[self.e_v]
+        if self.json {
+            encoding::is_okay_json
+        } else {
+            ENCODING_LUT[self.e_v]
+        }
+    }
+    /// Returns an encoder fnptr that checks every key and value in an iterator of
+    /// alternating keys and values
+    pub fn get_pair_iter_encoder(&self) -> PairIterEncoder {
+        if self.json {
+            ENCODING_LUT_JSON_ITER_PAIR[self.e_k]
+        } else {
+            ENCODING_LUT_ITER_PAIR[(self.e_k, self.e_v)]
+        }
     }
 }
 
 // dict impls
 impl<T: KVEValue> KVEngine<T> {
-    /// Get the value of the given key
+    /// Returns the form in which the value is stored in this engine
+    #[inline(always)]
+    pub fn pack(&self, val: T) -> T {
+        match self.storage {
+            Some(ref storage) => val.pack(storage.as_ref()),
+            None => val,
+        }
+    }
+    /// Returns the value from the form in which it is stored in this engine
+    #[inline(always)]
+    pub fn unpack(&self, val: T) -> Result<T, Unreadable> {
+        match self.storage {
+            Some(ref storage) => val.unpack(storage.as_ref()),
+            None => Ok(val),
+        }
+    }
+    /// Returns the approximate number of bytes used by the keys, values and expiry deadlines
+    /// in this engine. This doesn't account for the allocator's or the map's own overhead
+    pub fn memory_usage(&self) -> usize {
+        let data: usize = self
+            .data
+            .iter()
+            .map(|kv| slice_memory_usage(kv.key()) + kv.value().memory_usage())
+            .sum();
+        data + self.ttl.len() * (mem::size_of::<SharedSlice>() + mem::size_of::<u64>())
+    }
+    /// Returns the approximate number of bytes used by the key, its value and its expiry
+    /// deadline (if any). Returns `None` if the key doesn't exist
+    pub fn key_memory_usage(&self, key: &[u8]) -> EncodingResult<Option<usize>> {
+        let usage = self.get(key)?.map(|kv| {
+            let deadline = if self.ttl.contains_key(key) {
+                mem::size_of::<SharedSlice>() + mem::size_of::<u64>()
+            } else {
+                0
+            };
+            slice_memory_usage(kv.key()) + kv.value().memory_usage() + deadline
+        });
+        Ok(usage)
+    }
+    /// Get the value of the given key. If the engine has a storage engine, this is the stored
+    /// form of the value (see [`Self::unpack`])
     pub fn get<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResultRef<T> {
         self.check_key_encoding(key.as_ref())
             .map(|_| self.get_unchecked(key))
     }
     /// Get the value of the given key without any encoding checks
     pub fn get_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> OptionRef<T> {
+        self.evict_if_expired(key.as_ref());
+        self.touch(key.as_ref());
         self.data.get(key.as_ref())
     }
     /// Set the value of the given key
     pub fn set(&self, key: SharedSlice, val: T) -> EncodingResult<bool> {
         self.check_key_encoding(&key)
-            .and_then(|_| val.verify_encoding(self.e_v))
+            .and_then(|_| val.verify_encoding(self.get_val_encoder()))
             .map(|_| self.set_unchecked(key, val))
     }
     /// Same as set, but doesn't check encoding. Caller must check encoding
     pub fn set_unchecked(&self, key: SharedSlice, val: T) -> bool {
-        self.data.true_if_insert(key, val)
+        let _write = self.write_guard();
+        let val = self.pack(val);
+        self.evict_if_expired(&key);
+        if compiler::likely(
+            self.has_no_expiries() && !self.notifier.is_active() && !self.eviction.is_tracking(),
+        ) {
+            return self.data.true_if_insert(key, val);
+        }
+        let fresh = self.data.true_if_insert(key.clone(), val);
+        if fresh {
+            self.touch(&key);
+            // a stale deadline must never carry over to a fresh key
+            self.clear_expiry_unchecked(&key);
+            self.notify(Event::Set, &key);
+        }
+        fresh
+    }
+    /// Same as set, but also sets an expiry deadline for the key if it was freshly inserted.
+    /// Caller must check encoding
+    pub fn set_with_expiry_unchecked(&self, key: SharedSlice, val: T, deadline: u64) -> bool {
+        let _write = self.write_guard();
+        self.evict_if_expired(&key);
+        match self.data.fresh_entry(key.clone()) {
+            Some(ve) => {
+                let guard = ve.insert(self.pack(val));
+                // the deadline is set while we still hold the entry, so that nobody ever sees
+                // the key without it
+                self.ttl.upsert(key.clone(), deadline);
+                drop(guard);
+                self.touch(&key);
+                self.notify(Event::Set, &key);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Same as update, but also replaces the expiry deadline for the key if it was updated.
+    /// Caller must check encoding
+    pub fn update_with_expiry_unchecked(&self, key: SharedSlice, val: T, deadline: u64) -> bool {
+        let _write = self.write_guard();
+        self.evict_if_expired(&key);
+        match self.data.mut_entry(key.clone()) {
+            Some(mut oe) => {
+                oe.insert(self.pack(val));
+                // the value and the deadline are replaced under the same guard, so nobody ever
+                // sees the new value with the old deadline (or the other way around)
+                self.ttl.upsert(key.clone(), deadline);
+                drop(oe);
+                self.touch(&key);
+                self.notify(Event::Update, &key);
+                true
+            }
+            None => false,
+        }
     }
     /// Check if the provided key exists
     pub fn exists<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<bool> {
@@ -188,28 +641,49 @@ impl<T: KVEValue> KVEngine<T> {
         Ok(self.exists_unchecked(key.as_ref()))
     }
     pub fn exists_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> bool {
-        self.data.contains_key(key.as_ref())
+        !self.evict_if_expired(key.as_ref()) && self.data.contains_key(key.as_ref())
     }
     /// Update the value of an existing key. Returns `true` if updated
     pub fn update(&self, key: SharedSlice, val: T) -> EncodingResult<bool> {
         self.check_key_encoding(&key)?;
-        val.verify_encoding(self.e_v)?;
+        val.verify_encoding(self.get_val_encoder())?;
         Ok(self.update_unchecked(key, val))
     }
-    /// Update the value of an existing key without encoding checks
+    /// Update the value of an existing key without encoding checks. This will retain
+    /// the key's expiry, if any
     pub fn update_unchecked(&self, key: SharedSlice, val: T) -> bool {
-        self.data.true_if_update(key, val)
+        let _write = self.write_guard();
+        let val = self.pack(val);
+        self.evict_if_expired(&key);
+        if compiler::likely(!self.notifier.is_active() && !self.eviction.is_tracking()) {
+            return self.data.true_if_update(key, val);
+        }
+        let updated = self.data.true_if_update(key.clone(), val);
+        if updated {
+            self.touch(&key);
+            self.notify(Event::Update, &key);
+        }
+        updated
     }
     /// Update or insert an entry
     pub fn upsert(&self, key: SharedSlice, val: T) -> EncodingResult<()> {
         self.check_key_encoding(&key)?;
-        val.verify_encoding(self.e_v)?;
+        val.verify_encoding(self.get_val_encoder())?;
         self.upsert_unchecked(key, val);
         Ok(())
     }
-    /// Update or insert an entry without encoding checks
+    /// Update or insert an entry without encoding checks. This will drop the key's
+    /// expiry, if any
     pub fn upsert_unchecked(&self, key: SharedSlice, val: T) {
-        self.data.upsert(key, val)
+        let _write = self.write_guard();
+        let val = self.pack(val);
+        self.clear_expiry_unchecked(&key);
+        if compiler::likely(!self.notifier.is_active() && !self.eviction.is_tracking()) {
+            return self.data.upsert(key, val);
+        }
+        self.data.upsert(key.clone(), val);
+        self.touch(&key);
+        self.notify(Event::Set, &key);
     }
     /// Remove an entry
     pub fn remove<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<bool> {
@@ -218,31 +692,267 @@ impl<T: KVEValue> KVEngine<T> {
     }
     /// Remove an entry without encoding checks
     pub fn remove_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> bool {
-        self.data.true_if_removed(key.as_ref())
+        let _write = self.write_guard();
+        if self.evict_if_expired(key.as_ref()) {
+            return false;
+        }
+        self.clear_expiry_unchecked(key.as_ref());
+        let removed = self.data.true_if_removed(key.as_ref());
+        if removed {
+            self.notify(Event::Del, key.as_ref());
+        }
+        removed
+    }
+    /// Remove all the keys that start with `prefix`, returning the number of removed keys.
+    /// Keys that are created while this runs may not be removed
+    pub fn remove_prefix(&self, prefix: &[u8]) -> usize {
+        self.sweep_expired();
+        let keys: Vec<SharedSlice> = self
+            .data
+            .iter()
+            .filter(|kv| kv.key().starts_with(prefix))
+            .map(|kv| kv.key().clone())
+            .collect();
+        keys.into_iter()
+            .filter(|key| self.remove_unchecked(key))
+            .count()
+    }
+    /// Rename the key `from` to `to`, but only if `to` doesn't exist. The expiry of `from` (if
+    /// any) is carried over to `to`. Returns `None` if `from` doesn't exist and `Some(false)`
+    /// if `to` already exists
+    pub fn rename(&self, from: &[u8], to: SharedSlice) -> EncodingResult<Option<bool>> {
+        self.check_key_encoding(from)?;
+        self.check_key_encoding(&to)?;
+        let _write = self.write_guard();
+        self.evict_if_expired(from);
+        self.evict_if_expired(&to);
+        let ret = self.data.rename(from, to.clone());
+        if ret == Some(true) {
+            self.notify(Event::Del, from);
+            self.notify(Event::Set, &to);
+            if !self.has_no_expiries() {
+                if let Some((_, deadline)) = self.ttl.remove(from) {
+                    self.ttl.upsert(to, deadline);
+                }
+            }
+        }
+        Ok(ret)
+    }
+    /// Copy the value of `src` to the key `dst` in `target` (which can be this engine), but
+    /// only if `dst` doesn't exist. The expiry of `src` isn't copied. Returns `None` if `src`
+    /// doesn't exist (or has expired) and `Some(false)` if `dst` already exists
+    pub fn copy_to(&self, src: &[u8], target: &Self, dst: SharedSlice) -> ReadResult<Option<bool>> {
+        self.check_key_encoding(src)?;
+        target.check_key_encoding(&dst)?;
+        let value = match self.get_unchecked(src) {
+            // the key can expire right after the eviction check, so we look at the deadline
+            // again while we hold the value
+            Some(value) if !self.is_expired_unchecked(src) => self.unpack(value.duplicate())?,
+            _ => return Ok(None),
+        };
+        // the target might not agree with us on the encoding
+        value.verify_encoding(target.get_val_encoder())?;
+        Ok(Some(target.set_unchecked(dst, value)))
+    }
+    /// Move the key to `target`, but only if the key doesn't exist there. The expiry of the
+    /// key (if any) moves along with it. Returns `None` if the key doesn't exist and
+    /// `Some(false)` if it already exists in `target`. If the value isn't valid for the
+    /// target's encoding, an error is returned and the key is left untouched
+    pub fn move_to(&self, key: &[u8], target: &Self) -> ReadResult<Option<bool>> {
+        self.check_key_encoding(key)?;
+        target.check_key_encoding(key)?;
+        if ptr::eq(self, target) {
+            // moving a key onto itself is the same as trying to overwrite it
+            return Ok(if self.exists_unchecked(key) {
+                Some(false)
+            } else {
+                None
+            });
+        }
+        // always lock the engines in the same order so that two moves in opposite directions
+        // can't deadlock
+        let (first, second) = if (self as *const Self) < (target as *const Self) {
+            (self, target)
+        } else {
+            (target, self)
+        };
+        let _first_guard = first.txn_lock.write();
+        let _second_guard = second.txn_lock.write();
+        self.evict_if_expired(key);
+        target.evict_if_expired(key);
+        let ve = match target.data.fresh_entry(SharedSlice::new(key)) {
+            Some(ve) => ve,
+            None => {
+                return Ok(if self.data.contains_key(key) {
+                    Some(false)
+                } else {
+                    None
+                })
+            }
+        };
+        let venc = target.get_val_encoder();
+        let mut failure: Option<ReadError> = None;
+        // the value is only removed if it can be read back (if we need to) and the target
+        // accepts it
+        let mut unpacked = None;
+        let removed = self.data.remove_if(key, |_, value| {
+            let checked = if compiler::unlikely(self.storage.is_some()) {
+                match self.unpack(value.duplicate()) {
+                    Ok(value) => value.verify_encoding(venc).map(|_| unpacked = Some(value)),
+                    Err(e) => {
+                        failure = Some(e.into());
+                        return false;
+                    }
+                }
+            } else {
+                value.verify_encoding(venc)
+            };
+            if checked.is_err() {
+                failure = Some(ReadError::Encoding);
+            }
+            checked.is_ok()
+        });
+        let value = match removed {
+            Some((_, value)) if self.shares_storage_with(target) => value,
+            Some((_, value)) => target.pack(unpacked.unwrap_or(value)),
+            None => return failure.map_or(Ok(None), Err),
+        };
+        // drop the guard before we touch the deadlines
+        drop(ve.insert(value));
+        if !self.has_no_expiries() {
+            if let Some((_, deadline)) = self.ttl.remove(key) {
+                target.ttl.upsert(SharedSlice::new(key), deadline);
+            }
+        }
+        self.notify(Event::Del, key);
+        target.notify(Event::Set, key);
+        Ok(Some(true))
     }
     /// Pop an entry
-    pub fn pop<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<Option<T>> {
+    pub fn pop<Q: AsRef<[u8]>>(&self, key: Q) -> ReadResult<Option<T>> {
         self.check_key_encoding(key.as_ref())?;
-        Ok(self.pop_unchecked(key))
+        Ok(self.pop_unchecked(key)?)
     }
-    /// Pop an entry without encoding checks
-    pub fn pop_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<T> {
-        self.data.remove(key.as_ref()).map(|(_, v)| v)
+    /// Pop an entry without encoding checks. If the value can't be read back, it's left
+    /// where it is
+    pub fn pop_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Result<Option<T>, Unreadable> {
+        let _write = self.write_guard();
+        if self.evict_if_expired(key.as_ref()) {
+            return Ok(None);
+        }
+        let popped = if compiler::likely(self.storage.is_none()) {
+            self.data.remove(key.as_ref()).map(|(_, v)| v)
+        } else {
+            let mut unpacked = Err(Unreadable);
+            self.data.remove_if(key.as_ref(), |_, value| {
+                unpacked = self.unpack(value.duplicate());
+                unpacked.is_ok()
+            });
+            match unpacked {
+                Ok(value) => Some(value),
+                Err(_) if !self.data.contains_key(key.as_ref()) => None,
+                Err(e) => return Err(e),
+            }
+        };
+        self.clear_expiry_unchecked(key.as_ref());
+        if popped.is_some() {
+            self.notify(Event::Del, key.as_ref());
+        }
+        Ok(popped)
     }
 }
 
-impl<T: Clone> KVEngine<T> {
-    pub fn get_cloned<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<Option<T>> {
+impl<T: KVEValue + Clone> KVEngine<T> {
+    pub fn get_cloned<Q: AsRef<[u8]>>(&self, key: Q) -> ReadResult<Option<T>> {
         self.check_key_encoding(key.as_ref())?;
-        Ok(self.get_cloned_unchecked(key.as_ref()))
+        Ok(self.get_cloned_unchecked(key.as_ref())?)
     }
-    pub fn get_cloned_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<T> {
-        self.data.get_cloned(key.as_ref())
+    pub fn get_cloned_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Result<Option<T>, Unreadable> {
+        self.evict_if_expired(key.as_ref());
+        self.touch(key.as_ref());
+        self.data
+            .get_cloned(key.as_ref())
+            .map(|val| self.unpack(val))
+            .transpose()
     }
 }
 
 impl KVEStandard {
+    /// Create a new KVEBlob that keeps its values on disk (see [`disk`]). The values in
+    /// `data` are written out to the disk
+    pub fn new_disk(e_k: bool, e_v: bool, data: Coremap<SharedSlice, SharedSlice>) -> Self {
+        let loaded = Loaded::new();
+        for (key, value) in data {
+            loaded.upsert(key, &value);
+        }
+        Self::from_loaded(e_k, e_v, loaded)
+    }
+    /// Create a new KVEBlob from the values that were already written out to the disk
+    pub fn from_loaded(e_k: bool, e_v: bool, loaded: Loaded) -> Self {
+        Self {
+            storage: Some(Arc::new(loaded.storage)),
+            ..Self::new(e_k, e_v, loaded.data)
+        }
+    }
+    /// Atomically replace the value of an existing key with `new`, but only if the current
+    /// value is `expected`. Returns `None` if the key doesn't exist and `Some(false)` if the
+    /// current value didn't match. This will retain the key's expiry, if any
+    pub fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: &[u8],
+        new: SharedSlice,
+    ) -> ReadResult<Option<bool>> {
+        self.check_key_encoding(key)?;
+        new.verify_encoding(self.get_val_encoder())?;
+        let _write = self.write_guard();
+        self.evict_if_expired(key);
+        // the write guard is held across the comparison, so nobody can sneak in a write
+        let mut current = match self.data.get_mut(key) {
+            Some(current) => current,
+            None => return Ok(None),
+        };
+        let matches = self.unpack(current.clone())?.as_ref() == expected;
+        if matches {
+            *current = self.pack(new);
+            self.notify(Event::Update, key);
+        }
+        Ok(Some(matches))
+    }
+    /// Set the value of the key (whether or not it exists), returning its old value (if any).
+    /// Like any other overwrite, this drops the key's expiry. If the old value can't be read
+    /// back, nothing is written
+    pub fn get_and_set(
+        &self,
+        key: SharedSlice,
+        new: SharedSlice,
+    ) -> ReadResult<Option<SharedSlice>> {
+        self.check_key_encoding(&key)?;
+        new.verify_encoding(self.get_val_encoder())?;
+        let _write = self.write_guard();
+        self.evict_if_expired(&key);
+        let new = self.pack(new);
+        loop {
+            if let Some(mut current) = self.data.get_mut(&key) {
+                let old = self.unpack(current.clone())?;
+                self.clear_expiry_unchecked(&key);
+                *current = new;
+                self.notify(Event::Set, &key);
+                return Ok(Some(old));
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                self.clear_expiry_unchecked(&key);
+                entry.insert(new);
+                self.notify(Event::Set, &key);
+                return Ok(None);
+            }
+            // someone created the key right after we checked; just retry
+        }
+    }
+    /// Returns a copy of the value in the form in which it's stored, so that it can be
+    /// compared against the stored value later
     pub fn take_snapshot_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<SharedSlice> {
+        self.evict_if_expired(key.as_ref());
         self.data.get_cloned(key.as_ref())
     }
     /// Returns an encoder that checks each key and each value in turn
@@ -253,7 +963,19 @@ impl KVEStandard {
     /// }
     /// ```
     pub fn get_double_encoder(&self) -> DoubleEncoder {
-        ENCODING_LUT_PAIR[(self.e_k, self.e_v)]
+        if self.json {
+            ENCODING_LUT_JSON_PAIR[self.e_k]
+        } else {
+            ENCODING_LUT_PAIR[(self.e_k, self.e_v)]
+        }
+    }
+    /// Returns the length of the value in bytes. For values that aren't kept as is, this is
+    /// the length of the unpacked value (and we don't need to unpack it to find out)
+    pub fn value_len(&self, key: &[u8]) -> EncodingResult<Option<usize>> {
+        Ok(self.get(key)?.map(|val| match self.storage {
+            Some(ref storage) => storage.unpacked_len(&val),
+            None => val.len(),
+        }))
     }
 }
 
@@ -266,6 +988,7 @@ impl KVEListmap {
     }
     pub fn list_len(&self, listname: &[u8]) -> EncodingResult<Option<usize>> {
         self.check_key_encoding(listname)?;
+        self.evict_if_expired(listname);
         Ok(self.data.get(listname).map(|list| list.read().len()))
     }
     pub fn list_cloned(
@@ -274,6 +997,7 @@ impl KVEListmap {
         count: usize,
     ) -> EncodingResult<Option<Vec<SharedSlice>>> {
         self.check_key_encoding(listname)?;
+        self.evict_if_expired(listname);
         Ok(self
             .data
             .get(listname)
@@ -281,11 +1005,97 @@ impl KVEListmap {
     }
     pub fn list_cloned_full(&self, listname: &[u8]) -> EncodingResult<Option<Vec<SharedSlice>>> {
         self.check_key_encoding(listname)?;
+        self.evict_if_expired(listname);
         Ok(self
             .data
             .get(listname)
             .map(|list| list.read().iter().cloned().collect()))
     }
+    /// Push values to the head or the tail of a list, creating the list if it doesn't exist.
+    /// Values pushed to the head will end up in the reverse order. Returns the new length of
+    /// the list. Caller must check the encoding of the values
+    pub fn list_push(
+        &self,
+        listname: SharedSlice,
+        values: Vec<SharedSlice>,
+        at_head: bool,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&listname)?;
+        let _write = self.write_guard();
+        self.evict_if_expired(&listname);
+        loop {
+            if let Some(list) = self.data.get(&listname) {
+                let mut wlock = list.write();
+                if at_head {
+                    wlock.splice(0..0, values.into_iter().rev());
+                } else {
+                    wlock.extend(values);
+                }
+                self.notify(Event::Update, &listname);
+                return Ok(wlock.len());
+            }
+            if let Some(entry) = self.data.fresh_entry(listname.clone()) {
+                let mut values = values;
+                if at_head {
+                    values.reverse();
+                }
+                let len = values.len();
+                entry.insert(LockedVec::new(values));
+                self.notify(Event::Set, &listname);
+                return Ok(len);
+            }
+            // someone removed the list right after we found it; just retry
+        }
+    }
+    /// Pop a value from the head or the tail of a list. The outer option is `None` if the list
+    /// doesn't exist, while the inner option is `None` if the list is empty
+    pub fn list_pop(
+        &self,
+        listname: &[u8],
+        from_head: bool,
+    ) -> EncodingResult<Option<Option<SharedSlice>>> {
+        self.check_key_encoding(listname)?;
+        let _write = self.write_guard();
+        self.evict_if_expired(listname);
+        Ok(self.data.get(listname).map(|list| {
+            let mut wlock = list.write();
+            let popped = if from_head {
+                if wlock.is_empty() {
+                    None
+                } else {
+                    Some(wlock.remove(0))
+                }
+            } else {
+                wlock.pop()
+            };
+            if popped.is_some() {
+                self.notify(Event::Update, listname);
+            }
+            popped
+        }))
+    }
+    /// Returns the elements in the inclusive range `start..=stop`. Negative indices are counted
+    /// from the end of the list (`-1` is the last element) and out of range indices are clamped
+    pub fn list_range(
+        &self,
+        listname: &[u8],
+        start: i64,
+        stop: i64,
+    ) -> EncodingResult<Option<Vec<SharedSlice>>> {
+        self.check_key_encoding(listname)?;
+        self.evict_if_expired(listname);
+        Ok(self.data.get(listname).map(|list| {
+            let rlock = list.read();
+            let len = rlock.len() as i64;
+            let normalize = |idx: i64| if idx < 0 { len + idx } else { idx };
+            let (start, stop) = (normalize(start).max(0), normalize(stop).min(len - 1));
+            if start > stop {
+                Vec::new()
+            } else {
+                rlock[start as usize..=stop as usize].to_vec()
+            }
+        }))
+    }
 }
 
 impl<T> Default for KVEngine<T> {
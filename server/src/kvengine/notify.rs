@@ -0,0 +1,166 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Keyspace notifications
+//!
+//! When notifications are enabled on a table, every change to a key in the table is published
+//! (over pub/sub) to the table's notification channel, `__notify__:<keyspace>:<table>`. The
+//! payload of a notification is `<event>:<key>`, where the event is one of:
+//! - `set`: a key was created (or replaced, with `USET`)
+//! - `update`: the value of an existing key was changed
+//! - `del`: a key was removed
+//! - `expired`: a key was evicted because its expiry deadline passed
+//! - `evicted`: a key was evicted to keep the table within its memory cap (see
+//! [`super::eviction`])
+//!
+//! Notifications are published right after the change is made, so a subscriber may see
+//! changes that are later rolled back (by a failed transaction, for example); in that case,
+//! the rollback is published as well. Whether notifications are enabled isn't persisted, so
+//! they're always disabled when the server starts
+//!
+//! Connections waiting on a key (see [`Waiters`]) are woken up with the same events, regardless
+//! of whether notifications are enabled
+
+use {
+    super::KVEngine,
+    crate::{
+        corestore::{waiters::Waiters, SharedSlice},
+        dbnet::pubsub::PubSub,
+        util::compiler,
+    },
+    parking_lot::RwLock,
+    std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A change to a key
+pub enum Event {
+    Set,
+    Update,
+    Del,
+    Expired,
+    Evicted,
+}
+
+impl Event {
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            Self::Set => b"set",
+            Self::Update => b"update",
+            Self::Del => b"del",
+            Self::Expired => b"expired",
+            Self::Evicted => b"evicted",
+        }
+    }
+}
+
+/// The prefix of the notification channels
+pub const CHANNEL_PREFIX: &[u8] = b"__notify__:";
+
+/// Returns the notification channel for a table
+pub fn channel_for(keyspace: &[u8], table: &[u8]) -> SharedSlice {
+    let mut channel = CHANNEL_PREFIX.to_vec();
+    channel.extend_from_slice(keyspace);
+    channel.push(b':');
+    channel.extend_from_slice(table);
+    SharedSlice::from(channel)
+}
+
+#[derive(Debug)]
+/// Where notifications are published to
+struct Target {
+    hub: Arc<PubSub>,
+    channel: SharedSlice,
+}
+
+#[derive(Debug, Default)]
+/// The notification state of a table
+pub struct Notifier {
+    /// checked before we take the lock, so that tables without notifications pay (almost)
+    /// nothing for them
+    enabled: AtomicBool,
+    target: RwLock<Option<Target>>,
+    /// the connections waiting on keys in this table
+    waiters: Waiters,
+}
+
+impl Notifier {
+    /// Start publishing notifications to `channel`
+    pub fn enable(&self, hub: Arc<PubSub>, channel: SharedSlice) {
+        *self.target.write() = Some(Target { hub, channel });
+        self.enabled.store(true, Ordering::Release);
+    }
+    /// Stop publishing notifications
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+        *self.target.write() = None;
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+    /// Returns true if changes need to be reported, either because notifications are enabled
+    /// or because someone is waiting on a key
+    pub fn is_active(&self) -> bool {
+        self.is_enabled() || !self.waiters.is_empty()
+    }
+    /// Returns the registry of connections waiting on keys in this table
+    pub fn waiters(&self) -> &Waiters {
+        &self.waiters
+    }
+    /// Wake up the waiters for the key and publish a notification for it (if notifications
+    /// are enabled)
+    pub fn notify(&self, event: Event, key: &[u8]) {
+        self.waiters.wake(event, key);
+        if compiler::likely(!self.is_enabled()) {
+            return;
+        }
+        if let Some(ref target) = *self.target.read() {
+            let name = event.name();
+            let mut payload = Vec::with_capacity(name.len() + 1 + key.len());
+            payload.extend_from_slice(name);
+            payload.push(b':');
+            payload.extend_from_slice(key);
+            target.hub.publish(&target.channel, &payload);
+        }
+    }
+}
+
+impl<T> KVEngine<T> {
+    /// Returns the notification state of this engine
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+    /// Wake up the waiters for the key and publish a notification for it (if notifications
+    /// are enabled). Actions that modify values in place (instead of going through the
+    /// engine) must call this
+    #[inline(always)]
+    pub fn notify(&self, event: Event, key: &[u8]) {
+        self.notifier.notify(event, key)
+    }
+}
@@ -0,0 +1,191 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Key patterns
+//!
+//! Glob-style patterns that are used to filter keys on the server. The supported syntax is:
+//! - `*` matches any sequence of bytes (including an empty one)
+//! - `?` matches exactly one byte
+//! - `[abc]` matches one of the bytes in the brackets, `[a-z]` matches a range of bytes and
+//!   `[!abc]` (or `[^abc]`) matches any byte that isn't in the brackets
+//! - `\` escapes the byte that follows it
+//!
+//! A [`Pattern`] is compiled once and can then be matched against any number of keys. An
+//! unterminated `[` is matched literally. Patterns that are just a prefix followed by a `*`
+//! are matched with a plain prefix check
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// a literal byte
+    Byte(u8),
+    /// `?`
+    AnyByte,
+    /// `*`
+    AnySeq,
+    /// `[...]`, with inclusive byte ranges
+    Class {
+        negated: bool,
+        ranges: Vec<(u8, u8)>,
+    },
+}
+
+impl Token {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            Self::Byte(b) => *b == byte,
+            Self::AnyByte => true,
+            Self::AnySeq => false,
+            Self::Class { negated, ranges } => {
+                ranges.iter().any(|&(lo, hi)| lo <= byte && byte <= hi) != *negated
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PatternKind {
+    Prefix(Vec<u8>),
+    Glob(Vec<Token>),
+}
+
+#[derive(Debug)]
+/// A compiled key pattern
+pub struct Pattern {
+    kind: PatternKind,
+}
+
+impl Pattern {
+    /// Compile a pattern
+    pub fn compile(pattern: &[u8]) -> Self {
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < pattern.len() {
+            let token = match pattern[i] {
+                b'*' => {
+                    // consecutive stars are the same as a single star
+                    if tokens.last() != Some(&Token::AnySeq) {
+                        tokens.push(Token::AnySeq);
+                    }
+                    i += 1;
+                    continue;
+                }
+                b'?' => Token::AnyByte,
+                b'\\' if i + 1 < pattern.len() => {
+                    i += 1;
+                    Token::Byte(pattern[i])
+                }
+                b'[' => match Self::compile_class(&pattern[i + 1..]) {
+                    Some((class, consumed)) => {
+                        i += consumed;
+                        class
+                    }
+                    None => Token::Byte(b'['),
+                },
+                byte => Token::Byte(byte),
+            };
+            tokens.push(token);
+            i += 1;
+        }
+        let prefix: Option<Vec<u8>> = match tokens.split_last() {
+            Some((Token::AnySeq, rest)) => rest
+                .iter()
+                .map(|token| match token {
+                    Token::Byte(b) => Some(*b),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+        let kind = match prefix {
+            Some(prefix) => PatternKind::Prefix(prefix),
+            None => PatternKind::Glob(tokens),
+        };
+        Self { kind }
+    }
+    /// Compile the class that follows a `[`, returning the class and the number of bytes
+    /// that it took up (including the closing `]`). Returns `None` if the class is unterminated
+    fn compile_class(class: &[u8]) -> Option<(Token, usize)> {
+        let negated = matches!(class.first(), Some(b'!' | b'^'));
+        let first = negated as usize;
+        let mut i = first;
+        let mut ranges = Vec::new();
+        loop {
+            let mut lo = *class.get(i)?;
+            // a `]` right at the start is a member and doesn't close the class
+            if lo == b']' && i != first {
+                return Some((Token::Class { negated, ranges }, i + 1));
+            }
+            if lo == b'\\' {
+                i += 1;
+                lo = *class.get(i)?;
+            }
+            let mut hi = lo;
+            if class.get(i + 1) == Some(&b'-') && matches!(class.get(i + 2), Some(b) if *b != b']')
+            {
+                hi = class[i + 2];
+                i += 2;
+            }
+            ranges.push((lo.min(hi), lo.max(hi)));
+            i += 1;
+        }
+    }
+    /// Check if the key matches this pattern
+    pub fn matches(&self, key: &[u8]) -> bool {
+        match &self.kind {
+            PatternKind::Prefix(prefix) => key.starts_with(prefix),
+            PatternKind::Glob(tokens) => Self::matches_glob(tokens, key),
+        }
+    }
+    fn matches_glob(tokens: &[Token], key: &[u8]) -> bool {
+        let (mut t, mut k) = (0, 0);
+        // where to resume from if we need to let the last star eat one more byte
+        let mut backtrack = None;
+        while k < key.len() {
+            match tokens.get(t) {
+                Some(Token::AnySeq) => {
+                    t += 1;
+                    backtrack = Some((t, k));
+                    continue;
+                }
+                Some(token) if token.matches(key[k]) => {
+                    t += 1;
+                    k += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            match backtrack {
+                Some((bt, bk)) => {
+                    t = bt;
+                    k = bk + 1;
+                    backtrack = Some((bt, k));
+                }
+                None => return false,
+            }
+        }
+        tokens[t..].iter().all(|token| *token == Token::AnySeq)
+    }
+}
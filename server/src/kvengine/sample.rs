@@ -0,0 +1,95 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Key sampling
+//!
+//! Random keys are picked with a reservoir sample over the underlying map, so we never have to
+//! collect all the keys in a table just to pick a few of them
+
+use {
+    super::{expiry, KVEngine},
+    crate::corestore::SharedSlice,
+};
+
+/// A xorshift64* generator. This is **not** cryptographically secure, and doesn't need to be
+struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    fn new() -> Self {
+        let mut seed = [0u8; 8];
+        let seed = match openssl::rand::rand_bytes(&mut seed) {
+            Ok(()) => u64::from_le_bytes(seed),
+            Err(_) => expiry::now_millis(),
+        };
+        // the state must never be zero
+        Self { state: seed | 1 }
+    }
+    fn next(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    /// Returns a number in `0..=upper`
+    fn upto(&mut self, upper: usize) -> usize {
+        (self.next() % (upper as u64 + 1)) as usize
+    }
+}
+
+impl<T> KVEngine<T> {
+    /// Returns at most `count` distinct keys picked at random. Expired keys are never picked
+    pub fn sample_keys(&self, count: usize) -> Vec<SharedSlice> {
+        let mut reservoir = Vec::with_capacity(count.min(self.len()));
+        if count == 0 {
+            return reservoir;
+        }
+        let mut rng = Xorshift64::new();
+        let check_expiry = !self.has_no_expiries();
+        let now = expiry::now_millis();
+        let live = self.data.iter().filter(|kv| {
+            !(check_expiry
+                && self
+                    .ttl
+                    .get_cloned(kv.key())
+                    .map_or(false, |deadline| deadline <= now))
+        });
+        for (seen, kv) in live.enumerate() {
+            if seen < count {
+                reservoir.push(kv.key().clone());
+            } else {
+                let idx = rng.upto(seen);
+                if idx < count {
+                    reservoir[idx] = kv.key().clone();
+                }
+            }
+        }
+        reservoir
+    }
+}
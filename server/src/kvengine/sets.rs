@@ -0,0 +1,152 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Set-valued keymaps
+//!
+//! A [`KVESetmap`] maps every key to a set of unique members. Members are always returned
+//! in sorted order so that responses are deterministic
+
+use {
+    super::{notify::Event, EncodingResult, KVESetmap, LockedSet},
+    crate::corestore::SharedSlice,
+    std::collections::HashSet,
+};
+
+/// A set operation across multiple keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetAlgebra {
+    /// members present in any of the sets
+    Union,
+    /// members present in all of the sets
+    Intersect,
+    /// members of the first set that are not present in any of the other sets
+    Diff,
+}
+
+fn into_sorted(set: HashSet<SharedSlice>) -> Vec<SharedSlice> {
+    let mut members: Vec<SharedSlice> = set.into_iter().collect();
+    members.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    members
+}
+
+impl KVESetmap {
+    /// Add members to a set, creating the set if it doesn't exist. Returns the number of
+    /// members that were newly added. Caller must check the encoding of the members
+    pub fn set_add(
+        &self,
+        setname: SharedSlice,
+        members: Vec<SharedSlice>,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&setname)?;
+        self.evict_if_expired(&setname);
+        loop {
+            if let Some(set) = self.data.get(&setname) {
+                let mut wlock = set.write();
+                let added: usize = members.into_iter().map(|m| wlock.insert(m) as usize).sum();
+                if added != 0 {
+                    self.notify(Event::Update, &setname);
+                }
+                return Ok(added);
+            }
+            if let Some(entry) = self.data.fresh_entry(setname.clone()) {
+                let set: HashSet<SharedSlice> = members.into_iter().collect();
+                let added = set.len();
+                entry.insert(LockedSet::new(set));
+                self.notify(Event::Set, &setname);
+                return Ok(added);
+            }
+            // someone removed the set right after we found it; just retry
+        }
+    }
+    /// Remove members from a set. Returns the number of members that were removed or `None`
+    /// if the set doesn't exist
+    pub fn set_remove<Q: AsRef<[u8]>>(
+        &self,
+        setname: &[u8],
+        members: impl Iterator<Item = Q>,
+    ) -> EncodingResult<Option<usize>> {
+        self.check_key_encoding(setname)?;
+        self.evict_if_expired(setname);
+        Ok(self.data.get(setname).map(|set| {
+            let mut wlock = set.write();
+            let removed = members.filter(|m| wlock.remove(m.as_ref())).count();
+            if removed != 0 {
+                self.notify(Event::Update, setname);
+            }
+            removed
+        }))
+    }
+    /// Returns all the members of a set (sorted) or `None` if the set doesn't exist
+    pub fn set_members(&self, setname: &[u8]) -> EncodingResult<Option<Vec<SharedSlice>>> {
+        self.check_key_encoding(setname)?;
+        self.evict_if_expired(setname);
+        Ok(self
+            .data
+            .get(setname)
+            .map(|set| into_sorted(set.read().clone())))
+    }
+    /// Run a set operation across the given sets. Sets that don't exist are treated as empty
+    /// sets. The members of the result are sorted
+    pub fn set_algebra<Q: AsRef<[u8]>>(
+        &self,
+        op: SetAlgebra,
+        setnames: impl Iterator<Item = Q>,
+    ) -> EncodingResult<Vec<SharedSlice>> {
+        let mut setnames = setnames;
+        // the first set is the starting point for every operation
+        let mut ret = match setnames.next() {
+            Some(first) => {
+                let first = first.as_ref();
+                self.check_key_encoding(first)?;
+                self.evict_if_expired(first);
+                self.data
+                    .get(first)
+                    .map(|set| set.read().clone())
+                    .unwrap_or_default()
+            }
+            None => return Ok(Vec::new()),
+        };
+        for setname in setnames {
+            let setname = setname.as_ref();
+            self.check_key_encoding(setname)?;
+            self.evict_if_expired(setname);
+            match (op, self.data.get(setname)) {
+                (SetAlgebra::Union, Some(set)) => ret.extend(set.read().iter().cloned()),
+                (SetAlgebra::Intersect, Some(set)) => {
+                    let rlock = set.read();
+                    ret.retain(|m| rlock.contains(m));
+                }
+                (SetAlgebra::Intersect, None) => ret.clear(),
+                (SetAlgebra::Diff, Some(set)) => {
+                    let rlock = set.read();
+                    ret.retain(|m| !rlock.contains(m));
+                }
+                (SetAlgebra::Union | SetAlgebra::Diff, None) => {}
+            }
+        }
+        Ok(into_sorted(ret))
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Point-in-time snapshots
+//!
+//! A [`Snapshot`] is a consistent, read-only view of a [`KVEStandard`] as of the time it was
+//! taken, that can be read while writes to the table continue. Values are immutable reference
+//! counted slices, so taking a snapshot only copies the index (the keys and the handles to the
+//! values) and never the values: a later write replaces the value in the table, while the
+//! snapshot keeps the older value alive (copy-on-write).
+//!
+//! Writes to the table are only held off while a capture is started on the index and on the
+//! expiry deadlines (see [`crate::corestore::map::capture`]). They're copied afterwards, while
+//! the writes continue, and the values that the writes replaced in the meantime are put back
+
+use {
+    super::{
+        expiry,
+        storage::{StorageEngine, Unreadable},
+        KVEStandard, TSYMBOL_LUT,
+    },
+    crate::corestore::SharedSlice,
+    std::{collections::HashMap, sync::Arc},
+};
+
+#[derive(Debug)]
+/// A point-in-time view of a [`KVEStandard`]
+pub struct Snapshot {
+    data: HashMap<SharedSlice, SharedSlice>,
+    e_k: bool,
+    e_v: bool,
+    /// the values are in their stored form, so we need the storage engine to unpack them
+    storage: Option<Arc<dyn StorageEngine>>,
+}
+
+impl Snapshot {
+    /// Returns the value of the key as of the time the snapshot was taken
+    pub fn get(&self, key: &[u8]) -> Result<Option<SharedSlice>, Unreadable> {
+        let value = match self.data.get(key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        match self.storage {
+            Some(ref storage) => storage.unpack(value).map(Some),
+            None => Ok(Some(value.clone())),
+        }
+    }
+    /// Check the encoding of the key
+    pub fn is_key_ok(&self, key: &[u8]) -> bool {
+        super::encoding::ENCODING_LUT[self.e_k](key)
+    }
+    /// Get the value tsymbol
+    pub fn get_value_tsymbol(&self) -> u8 {
+        TSYMBOL_LUT[self.e_v]
+    }
+}
+
+impl KVEStandard {
+    /// Take a snapshot of this engine. Keys that have expired by now aren't part of it, while
+    /// keys that expire later remain readable in the snapshot
+    pub fn snapshot(&self) -> Snapshot {
+        let (now, data_capture, ttl_capture) = {
+            // a transaction can't be half-applied in the snapshot
+            let _txn_guard = self.txn_lock.write();
+            (
+                expiry::now_millis(),
+                self.data.start_capture(),
+                self.ttl.start_capture(),
+            )
+        };
+        let mut data = self.data.cloned_entries(data_capture);
+        let ttl = self.ttl.cloned_entries(ttl_capture);
+        if !ttl.is_empty() {
+            data.retain(|key, _| ttl.get(key).map_or(true, |deadline| *deadline > now));
+        }
+        Snapshot {
+            data,
+            e_k: self.e_k,
+            e_v: self.e_v,
+            storage: self.storage.clone(),
+        }
+    }
+}
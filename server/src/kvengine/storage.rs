@@ -0,0 +1,148 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Storage engines
+//!
+//! An engine always keeps its keys in memory, but where (and in what form) it keeps its values
+//! is up to its _storage engine_. A value is handed to the storage engine when it's written
+//! ([`StorageEngine::pack`]), which returns the form in which the value is kept in the map,
+//! and that form is turned back into the value whenever it's read
+//! ([`StorageEngine::unpack`]). The storage engines are:
+//! - **memory** (the default): the values are kept in memory as is. The engines that use it
+//! don't have a storage engine at all, so that the hot path doesn't have to go through one
+//! - **compressed** (`WITH compression = lz4`): the values are kept in memory, compressed (see
+//! [`super::compression`])
+//! - **disk** (`WITH storage = disk`): the values are kept in a file that's mapped into memory,
+//! and the map only holds where they are in the file (see [`super::disk`]). This is meant for
+//! tables that hold more data than fits in memory
+//!
+//! Only the tables with blob values (`binstr` or `str`) can pick a storage engine
+
+use {
+    super::{compression, disk::Disk},
+    crate::corestore::SharedSlice,
+    core::fmt::{self, Debug},
+    std::io::{Error as IoError, ErrorKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The kind of a storage engine
+pub enum StorageKind {
+    /// the values are kept in memory as is
+    Memory,
+    /// the values are kept in memory, compressed
+    Compressed,
+    /// the values are kept on disk
+    Disk,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A value couldn't be turned back from the form in which it's kept (for example, because a
+/// compressed frame is corrupted). The storage engine logs the reason
+pub struct Unreadable;
+
+impl fmt::Display for Unreadable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a stored value couldn't be read back")
+    }
+}
+
+impl From<Unreadable> for IoError {
+    fn from(e: Unreadable) -> Self {
+        IoError::new(ErrorKind::InvalidData, e.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The reason a read failed
+pub enum ReadError {
+    /// the key or value has the wrong encoding
+    Encoding,
+    /// the value couldn't be read back (see [`Unreadable`])
+    Unreadable,
+}
+
+impl From<()> for ReadError {
+    fn from(_: ()) -> Self {
+        Self::Encoding
+    }
+}
+
+impl From<Unreadable> for ReadError {
+    fn from(_: Unreadable) -> Self {
+        Self::Unreadable
+    }
+}
+
+pub type ReadResult<T> = Result<T, ReadError>;
+
+/// A storage engine decides where (and in what form) the values of an engine are kept
+pub trait StorageEngine: Debug + Send + Sync {
+    /// Returns the kind of this storage engine
+    fn kind(&self) -> StorageKind;
+    /// Returns the form in which the value is kept in the map
+    fn pack(&self, value: &[u8]) -> SharedSlice;
+    /// Returns the value from the form in which it's kept in the map
+    fn unpack(&self, stored: &SharedSlice) -> Result<SharedSlice, Unreadable>;
+    /// Returns the length of the value from the form in which it's kept in the map, without
+    /// unpacking it
+    fn unpacked_len(&self, stored: &[u8]) -> usize;
+    /// Returns true if the stored form of a value is understood by every storage engine of
+    /// the same kind (and not just by this one), so that it can be moved between engines as is
+    fn is_portable(&self) -> bool;
+    /// Returns the storage engine as a [`Disk`], if it is one
+    fn as_disk(&self) -> Option<&Disk> {
+        None
+    }
+}
+
+#[derive(Debug)]
+/// The storage engine of the tables that keep their values compressed
+pub struct Compressed;
+
+impl StorageEngine for Compressed {
+    fn kind(&self) -> StorageKind {
+        StorageKind::Compressed
+    }
+    fn pack(&self, value: &[u8]) -> SharedSlice {
+        compression::pack(value)
+    }
+    fn unpack(&self, stored: &SharedSlice) -> Result<SharedSlice, Unreadable> {
+        compression::unpack(stored).ok_or_else(|| {
+            log::error!(
+                "Found a corrupted compressed value ({} bytes)",
+                stored.len()
+            );
+            Unreadable
+        })
+    }
+    fn unpacked_len(&self, stored: &[u8]) -> usize {
+        compression::unpacked_len(stored).unwrap_or(stored.len())
+    }
+    fn is_portable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,227 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Byte operations on values
+//!
+//! Operations on parts of the values in a [`KVEStandard`], so that clients don't need to move
+//! entire values around to read or modify a few bytes (or bits). These hold the key's lock for
+//! the entire operation and (for string values) will never leave behind a value that isn't
+//! valid UTF-8. Operations that grow a value also respect the size limits of the table (see
+//! [`super::limits`])
+
+use {
+    super::{
+        limits::{WriteError, WriteResult},
+        notify::Event,
+        storage::ReadResult,
+        KVEStandard,
+    },
+    crate::corestore::SharedSlice,
+};
+
+impl KVEStandard {
+    /// Returns the bytes of the value in the inclusive range `start..=end`, clamped to the
+    /// length of the value. For string values, the range must not split a character
+    pub fn get_range(
+        &self,
+        key: &[u8],
+        start: usize,
+        end: usize,
+    ) -> ReadResult<Option<SharedSlice>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        let val = match self.data.get(key) {
+            Some(val) => self.unpack(val.clone())?,
+            None => return Ok(None),
+        };
+        let range = if start > end || start >= val.len() {
+            &[][..]
+        } else {
+            &val[start..=end.min(val.len() - 1)]
+        };
+        // a range of a JSON value needn't be JSON itself, but it still needs to be a string
+        self.check_encoding(range, self.e_v)?;
+        Ok(Some(SharedSlice::new(range)))
+    }
+    /// Overwrite the bytes of the value starting at `offset`, growing the value if needed.
+    /// Returns the new length of the value. The outer option is `None` if the key doesn't
+    /// exist, while the inner option is `None` if `offset` is past the end of the value
+    pub fn set_range(
+        &self,
+        key: &[u8],
+        offset: usize,
+        bytes: &[u8],
+    ) -> WriteResult<Option<Option<usize>>> {
+        self.check_key_encoding(key)?;
+        let _write = self.write_guard();
+        self.evict_if_expired(key);
+        let mut val = match self.data.get_mut(key) {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+        let current = self.unpack(val.clone())?;
+        if offset > current.len() {
+            return Ok(Some(None));
+        }
+        if !self.value_len_fits(current.len().max(offset + bytes.len())) {
+            return Err(WriteError::TooLarge);
+        }
+        let mut new = Vec::with_capacity(current.len().max(offset + bytes.len()));
+        new.extend_from_slice(&current[..offset]);
+        new.extend_from_slice(bytes);
+        if let Some(rest) = current.get(offset + bytes.len()..) {
+            new.extend_from_slice(rest);
+        }
+        self.check_value_encoding(&new)?;
+        let len = new.len();
+        *val = self.pack(SharedSlice::from(new));
+        self.notify(Event::Update, key);
+        Ok(Some(Some(len)))
+    }
+    /// Append the bytes to the value, creating the key if it doesn't exist. Returns the new
+    /// length of the value
+    pub fn append(&self, key: SharedSlice, bytes: &[u8]) -> WriteResult<usize> {
+        self.check_key_encoding(&key)?;
+        if !self.key_fits(&key) {
+            return Err(WriteError::TooLarge);
+        }
+        if !self.json {
+            self.check_value_encoding(bytes)?;
+        }
+        let _write = self.write_guard();
+        self.evict_if_expired(&key);
+        loop {
+            if let Some(mut val) = self.data.get_mut(&key) {
+                let current = self.unpack(val.clone())?;
+                if !self.value_len_fits(current.len() + bytes.len()) {
+                    return Err(WriteError::TooLarge);
+                }
+                let mut new = Vec::with_capacity(current.len() + bytes.len());
+                new.extend_from_slice(&current);
+                new.extend_from_slice(bytes);
+                if self.json {
+                    // only the whole value needs to be JSON
+                    self.check_value_encoding(&new)?;
+                }
+                let len = new.len();
+                *val = self.pack(SharedSlice::from(new));
+                self.notify(Event::Update, &key);
+                return Ok(len);
+            }
+            if self.json {
+                self.check_value_encoding(bytes)?;
+            }
+            if !self.value_len_fits(bytes.len()) {
+                return Err(WriteError::TooLarge);
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                entry.insert(self.pack(SharedSlice::new(bytes)));
+                self.notify(Event::Set, &key);
+                return Ok(bytes.len());
+            }
+            // someone created the key right after we checked; just retry
+        }
+    }
+    /// Returns the bit at `offset`, where bit `0` is the most significant bit of the first
+    /// byte. Bits past the end of the value are zero
+    pub fn get_bit(&self, key: &[u8], offset: usize) -> ReadResult<Option<bool>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        let val = match self.data.get(key) {
+            Some(val) => self.unpack(val.clone())?,
+            None => return Ok(None),
+        };
+        Ok(Some(
+            val.get(offset / 8).copied().unwrap_or(0) & bit_mask(offset) != 0,
+        ))
+    }
+    /// Set or clear the bit at `offset`, zero-padding the value if needed. The key is created
+    /// if it doesn't exist. Returns the previous value of the bit
+    pub fn set_bit(&self, key: SharedSlice, offset: usize, bit: bool) -> WriteResult<bool> {
+        self.check_key_encoding(&key)?;
+        if !self.key_fits(&key) {
+            return Err(WriteError::TooLarge);
+        }
+        let _write = self.write_guard();
+        self.evict_if_expired(&key);
+        loop {
+            if let Some(mut val) = self.data.get_mut(&key) {
+                let current = self.unpack(val.clone())?;
+                if !self.value_len_fits(current.len().max(offset / 8 + 1)) {
+                    return Err(WriteError::TooLarge);
+                }
+                let (new, previous) = with_bit(&current, offset, bit);
+                self.check_value_encoding(&new)?;
+                *val = self.pack(SharedSlice::from(new));
+                self.notify(Event::Update, &key);
+                return Ok(previous);
+            }
+            if !self.value_len_fits(offset / 8 + 1) {
+                return Err(WriteError::TooLarge);
+            }
+            let (new, _) = with_bit(&[], offset, bit);
+            self.check_value_encoding(&new)?;
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                entry.insert(self.pack(SharedSlice::from(new)));
+                self.notify(Event::Set, &key);
+                return Ok(false);
+            }
+            // someone created the key right after we checked; just retry
+        }
+    }
+    /// Returns the number of set bits in the value
+    pub fn bit_count(&self, key: &[u8]) -> ReadResult<Option<u64>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        let val = match self.data.get(key) {
+            Some(val) => self.unpack(val.clone())?,
+            None => return Ok(None),
+        };
+        Ok(Some(val.iter().map(|byte| byte.count_ones() as u64).sum()))
+    }
+}
+
+/// Returns the mask for the bit at `offset` within its byte
+const fn bit_mask(offset: usize) -> u8 {
+    0x80 >> (offset % 8)
+}
+
+/// Returns a copy of `val` with the bit at `offset` set to `bit` (growing it if needed),
+/// along with the previous value of the bit
+fn with_bit(val: &[u8], offset: usize, bit: bool) -> (Vec<u8>, bool) {
+    let idx = offset / 8;
+    let mut new = val.to_vec();
+    if new.len() <= idx {
+        new.resize(idx + 1, 0);
+    }
+    let previous = new[idx] & bit_mask(offset) != 0;
+    if bit {
+        new[idx] |= bit_mask(offset);
+    } else {
+        new[idx] &= !bit_mask(offset);
+    }
+    (new, previous)
+}
@@ -24,7 +24,24 @@
  *
 */
 
-use super::{KVEStandard, SharedSlice};
+use {
+    super::{
+        eviction::EvictionPolicy,
+        expiry,
+        json::{self, PathSegment},
+        limits::WriteError,
+        notify,
+        pattern::Pattern,
+        sets::SetAlgebra,
+        storage::{ReadError, Unreadable},
+        txn::TxnOp,
+        KVEBloommap, KVECountermap, KVEGeomap, KVEHllmap, KVESetmap, KVEStandard, KVETimeseriesmap,
+        SharedSlice,
+    },
+    crate::corestore::{bloom::BloomFilter, hll::HyperLogLog, timeseries::Sample},
+    crate::dbnet::pubsub::{PubSub, Subscriber},
+    std::{iter, sync::Arc, thread, time::Duration},
+};
 
 #[test]
 fn test_ignore_encoding() {
@@ -109,3 +126,1139 @@ fn test_encoder_validate_with_non_unicode() {
     let encoder = tbl.get_double_encoder();
     assert!(!encoder("hello".as_bytes(), b"Hello \xF0\x90\x80World"));
 }
+
+#[test]
+fn test_expired_key_is_never_returned() {
+    let tbl = KVEStandard::default();
+    // a deadline in the past
+    assert!(tbl.set_with_expiry_unchecked("a".into(), "b".into(), expiry::now_millis() - 1));
+    assert!(tbl.get("a").unwrap().is_none());
+    assert!(!tbl.exists("a").unwrap());
+    assert_eq!(tbl.len(), 0);
+    assert_eq!(tbl.expiry_count(), 0);
+}
+
+#[test]
+fn test_expiry_set_persist_and_sweep() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.set("a".into(), "1".into()).unwrap());
+    assert!(tbl.set("b".into(), "2".into()).unwrap());
+    assert!(!tbl
+        .set_expiry(b"c", expiry::deadline_after_secs(10))
+        .unwrap());
+    assert!(tbl.set_expiry(b"a", expiry::now_millis() - 1).unwrap());
+    assert!(tbl
+        .set_expiry(b"b", expiry::deadline_after_secs(10))
+        .unwrap());
+    assert!(tbl.remaining_ttl(b"b").unwrap().unwrap().is_some());
+    assert!(tbl.persist(b"b").unwrap());
+    assert_eq!(tbl.remaining_ttl(b"b").unwrap(), Some(None));
+    assert_eq!(tbl.sweep_expired(), 1);
+    assert_eq!(tbl.len(), 1);
+    assert_eq!(tbl.expiry_count(), 0);
+}
+
+#[test]
+fn test_compaction() {
+    let tbl = KVEStandard::default();
+    for i in 0..10_000 {
+        assert!(tbl.set(format!("key{i}").into(), "value".into()).unwrap());
+    }
+    for i in 10..10_000 {
+        assert!(tbl.remove(format!("key{i}").as_bytes()).unwrap());
+    }
+    let freed: usize = (0..tbl.compaction_steps())
+        .map(|step| tbl.compact_step(step))
+        .sum();
+    assert!(freed > 0);
+    assert_eq!(tbl.len(), 10);
+    assert!(tbl.exists("key0").unwrap());
+    // there's nothing left to free
+    assert_eq!(
+        (0..tbl.compaction_steps())
+            .map(|step| tbl.compact_step(step))
+            .sum::<usize>(),
+        0
+    );
+}
+
+#[test]
+fn test_expiring_soonest() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.expiring_soonest(10).is_empty());
+    for key in ["a", "b", "c", "d"] {
+        assert!(tbl.set(key.into(), "1".into()).unwrap());
+    }
+    assert!(tbl
+        .set_expiry(b"a", expiry::deadline_after_secs(30))
+        .unwrap());
+    assert!(tbl
+        .set_expiry(b"b", expiry::deadline_after_secs(10))
+        .unwrap());
+    assert!(tbl
+        .set_expiry(b"c", expiry::deadline_after_secs(20))
+        .unwrap());
+    let keys = |count| {
+        tbl.expiring_soonest(count)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(10), ["b", "c", "a"]);
+    assert_eq!(keys(2), ["b", "c"]);
+    assert!(tbl.expiring_soonest(1)[0].1 <= 10_000);
+}
+
+#[test]
+fn test_transaction_commit_and_rollback() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.set("a".into(), "1".into()).unwrap());
+    let ops = [
+        TxnOp::Upsert("b".into(), "2".into()),
+        TxnOp::Update("a".into(), "10".into()),
+    ];
+    assert!(tbl.apply_transaction(&ops).unwrap());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "10");
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "2");
+    let ops = [
+        TxnOp::Del("a".into()),
+        TxnOp::Upsert("c".into(), "3".into()),
+        // `b` already exists, so this fails
+        TxnOp::Set("b".into(), "20".into()),
+    ];
+    assert!(!tbl.apply_transaction(&ops).unwrap());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "10");
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "2");
+    assert!(tbl.get_cloned("c").unwrap().is_none());
+}
+
+#[test]
+fn test_rollback_restores_deadlines() {
+    let tbl = KVEStandard::default();
+    let deadline = expiry::deadline_after_secs(100);
+    assert!(tbl.set_with_expiry_unchecked("a".into(), "1".into(), deadline));
+    assert!(tbl.set_with_expiry_unchecked("b".into(), "2".into(), deadline));
+    assert!(tbl.set("c".into(), "3".into()).unwrap());
+    let ops = [
+        TxnOp::Del("a".into()),
+        TxnOp::Upsert("b".into(), "20".into()),
+        TxnOp::Update("c".into(), "30".into()),
+        // `c` already exists, so this fails
+        TxnOp::Set("c".into(), "300".into()),
+    ];
+    assert!(!tbl.apply_transaction(&ops).unwrap());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "1");
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "2");
+    assert_eq!(tbl.get_cloned("c").unwrap().unwrap(), "3");
+    assert_eq!(tbl.deadline_unchecked(b"a"), Some(deadline));
+    assert_eq!(tbl.deadline_unchecked(b"b"), Some(deadline));
+    assert_eq!(tbl.deadline_unchecked(b"c"), None);
+}
+
+#[test]
+fn test_transactions_hold_off_writes() {
+    let tbl = KVEStandard::default();
+    let txn_guard = tbl.txn_lock.write();
+    thread::scope(|s| {
+        let writer = s.spawn(|| tbl.set("a".into(), "1".into()).unwrap());
+        thread::sleep(Duration::from_millis(100));
+        // the write can't happen while a transaction is in progress
+        assert!(!tbl.get_inner_ref().contains_key(b"a".as_ref()));
+        drop(txn_guard);
+        assert!(writer.join().unwrap());
+    });
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "1");
+}
+
+#[test]
+fn test_set_algebra() {
+    let tbl = KVESetmap::default();
+    let members = |m: &[&str]| m.iter().map(|m| SharedSlice::from(*m)).collect::<Vec<_>>();
+    assert_eq!(
+        tbl.set_add("s1".into(), members(&["a", "b", "c"])).unwrap(),
+        3
+    );
+    assert_eq!(
+        tbl.set_add("s2".into(), members(&["c", "d", "c"])).unwrap(),
+        2
+    );
+    assert_eq!(
+        tbl.set_algebra(SetAlgebra::Union, ["s1", "s2"].iter())
+            .unwrap(),
+        members(&["a", "b", "c", "d"])
+    );
+    assert_eq!(
+        tbl.set_algebra(SetAlgebra::Intersect, ["s1", "s2"].iter())
+            .unwrap(),
+        members(&["c"])
+    );
+    assert_eq!(
+        tbl.set_algebra(SetAlgebra::Diff, ["s1", "s2", "nosuchset"].iter())
+            .unwrap(),
+        members(&["a", "b"])
+    );
+    assert_eq!(tbl.set_remove(b"s1", ["a", "z"].iter()).unwrap(), Some(1));
+    assert_eq!(
+        tbl.set_members(b"s1").unwrap().unwrap(),
+        members(&["b", "c"])
+    );
+    assert!(tbl.set_members(b"nosuchset").unwrap().is_none());
+}
+
+#[test]
+fn test_counter_overflow_and_underflow() {
+    let tbl = KVECountermap::default();
+    assert_eq!(tbl.counter_get(b"c").unwrap(), None);
+    // a counter that doesn't exist can't go below zero and is not created
+    assert_eq!(tbl.counter_decr_by("c".into(), 1).unwrap(), None);
+    assert_eq!(tbl.counter_get(b"c").unwrap(), None);
+    assert_eq!(
+        tbl.counter_incr_by("c".into(), u64::MAX - 1).unwrap(),
+        Some(u64::MAX - 1)
+    );
+    assert_eq!(tbl.counter_incr_by("c".into(), 1).unwrap(), Some(u64::MAX));
+    assert_eq!(tbl.counter_incr_by("c".into(), 1).unwrap(), None);
+    assert_eq!(tbl.counter_decr_by("c".into(), u64::MAX).unwrap(), Some(0));
+    assert_eq!(tbl.counter_get(b"c").unwrap(), Some(0));
+}
+
+#[test]
+fn test_bloom_add_and_exists() {
+    let tbl = KVEBloommap::default();
+    assert!(!tbl.bloom_exists(b"b", b"sayan").unwrap());
+    // adding to a filter that doesn't exist creates it
+    let items: [&[u8]; 3] = [b"sayan", b"nandan", b"sayan"];
+    assert_eq!(tbl.bloom_add("b".into(), items.into_iter()).unwrap(), 2);
+    assert!(tbl.bloom_exists(b"b", b"sayan").unwrap());
+    assert!(tbl.bloom_exists(b"b", b"nandan").unwrap());
+    // the filter exists now, so it can't be reserved
+    let filter = BloomFilter::new(0.001, 10).unwrap();
+    assert!(!tbl.bloom_reserve("b".into(), filter.clone()).unwrap());
+    assert!(tbl.bloom_reserve("b2".into(), filter).unwrap());
+    assert_eq!(
+        tbl.bloom_add("b2".into(), iter::once(b"x".as_ref()))
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        tbl.bloom_add("b2".into(), iter::once(b"x".as_ref()))
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_bloom_false_positive_rate() {
+    let mut filter = BloomFilter::new(0.01, 1000).unwrap();
+    for i in 0..1000u32 {
+        filter.add(&i.to_le_bytes());
+    }
+    // no false negatives
+    assert!((0..1000u32).all(|i| filter.contains(&i.to_le_bytes())));
+    // and the false positives are close to the requested rate
+    let false_positives = (1000..11000u32)
+        .filter(|i| filter.contains(&i.to_le_bytes()))
+        .count();
+    assert!(false_positives < 200, "{false_positives} false positives");
+    // invalid settings
+    assert!(BloomFilter::new(0.0, 10).is_none());
+    assert!(BloomFilter::new(1.0, 10).is_none());
+    assert!(BloomFilter::new(0.01, 0).is_none());
+}
+
+#[test]
+fn test_hll_add_count_merge() {
+    let tbl = KVEHllmap::default();
+    assert_eq!(tbl.hll_count(iter::once(b"h1".as_ref())).unwrap(), 0);
+    // creating an empty HLL counts as a change
+    assert!(tbl.hll_add("h1".into(), iter::empty()).unwrap());
+    assert!(!tbl.hll_add("h1".into(), iter::empty()).unwrap());
+    let items: [&[u8]; 3] = [b"sayan", b"nandan", b"sayan"];
+    assert!(tbl.hll_add("h1".into(), items.into_iter()).unwrap());
+    assert!(!tbl
+        .hll_add("h1".into(), iter::once(b"sayan".as_ref()))
+        .unwrap());
+    assert_eq!(tbl.hll_count(iter::once(b"h1".as_ref())).unwrap(), 2);
+    let items: [&[u8]; 2] = [b"nandan", b"ohsayan"];
+    assert!(tbl.hll_add("h2".into(), items.into_iter()).unwrap());
+    // the count of several HLLs is the count of their union
+    let keys: [&[u8]; 3] = [b"h1", b"h2", b"missing"];
+    assert_eq!(tbl.hll_count(keys.into_iter()).unwrap(), 3);
+    let keys: [&[u8]; 2] = [b"h1", b"h2"];
+    tbl.hll_merge("h3".into(), keys.into_iter()).unwrap();
+    assert_eq!(tbl.hll_count(iter::once(b"h3".as_ref())).unwrap(), 3);
+    // merging into one of the sources is fine
+    tbl.hll_merge("h1".into(), keys.into_iter()).unwrap();
+    assert_eq!(tbl.hll_count(iter::once(b"h1".as_ref())).unwrap(), 3);
+}
+
+#[test]
+fn test_geo_add_and_search() {
+    let tbl = KVEGeomap::default();
+    assert_eq!(
+        tbl.geo_search_radius(b"sicily", 15.0, 37.0, 1.0).unwrap(),
+        None
+    );
+    let members = vec![
+        (13.361389, 38.115556, SharedSlice::from("palermo")),
+        (15.087269, 37.502669, SharedSlice::from("catania")),
+    ];
+    assert_eq!(tbl.geo_add("sicily".into(), members).unwrap(), 2);
+    let found = tbl
+        .geo_search_radius(b"sicily", 15.0, 37.0, 200_000.0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        found.iter().map(|(m, _)| m.clone()).collect::<Vec<_>>(),
+        vec![SharedSlice::from("catania"), SharedSlice::from("palermo")]
+    );
+    // moving a member doesn't add it again
+    let members = vec![(0.0, 0.0, SharedSlice::from("catania"))];
+    assert_eq!(tbl.geo_add("sicily".into(), members).unwrap(), 0);
+    let found = tbl
+        .geo_search_radius(b"sicily", 15.0, 37.0, 200_000.0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, SharedSlice::from("palermo"));
+}
+
+#[test]
+fn test_ts_add_range_and_last() {
+    let tbl = KVETimeseriesmap::default();
+    assert_eq!(tbl.ts_last(b"temp").unwrap(), None);
+    assert_eq!(tbl.ts_range(b"temp", 0, u64::MAX).unwrap(), None);
+    // samples can arrive out of order
+    for (ts, value) in [(20, 2.0), (10, 1.0), (30, 3.0)] {
+        let ret = tbl.ts_add("temp".into(), Sample::new(ts, value), None);
+        assert_eq!(ret.unwrap(), Some(true));
+    }
+    // an existing timestamp is replaced
+    let ret = tbl.ts_add("temp".into(), Sample::new(20, 2.5), None);
+    assert_eq!(ret.unwrap(), Some(false));
+    assert_eq!(
+        tbl.ts_range(b"temp", 15, 30).unwrap().unwrap(),
+        vec![Sample::new(20, 2.5), Sample::new(30, 3.0)]
+    );
+    assert_eq!(
+        tbl.ts_last(b"temp").unwrap().unwrap(),
+        Some(Sample::new(30, 3.0))
+    );
+    // setting a retention period drops the old samples
+    let ret = tbl.ts_add("temp".into(), Sample::new(40, 4.0), Some(15));
+    assert_eq!(ret.unwrap(), Some(true));
+    assert_eq!(
+        tbl.ts_range(b"temp", 0, u64::MAX).unwrap().unwrap(),
+        vec![Sample::new(30, 3.0), Sample::new(40, 4.0)]
+    );
+    // and samples that are too old are rejected
+    let ret = tbl.ts_add("temp".into(), Sample::new(10, 1.0), None);
+    assert_eq!(ret.unwrap(), None);
+}
+
+#[test]
+fn test_hll_estimate_and_serialization() {
+    let mut hll = HyperLogLog::new();
+    assert!(hll.is_empty());
+    for i in 0..100_000u32 {
+        hll.add(&i.to_le_bytes());
+    }
+    let count = hll.count() as f64;
+    assert!((count - 100_000.0).abs() < 3_000.0, "estimated {count}");
+    // a large HLL is stored densely
+    let bytes = hll.to_bytes();
+    assert_eq!(HyperLogLog::from_bytes(&bytes).unwrap(), hll);
+    // a small one only stores the registers in use
+    let mut small = HyperLogLog::new();
+    small.add(b"sayan");
+    let bytes = small.to_bytes();
+    assert!(bytes.len() < 16);
+    assert_eq!(HyperLogLog::from_bytes(&bytes).unwrap(), small);
+    assert!(HyperLogLog::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    assert_eq!(
+        HyperLogLog::from_bytes(&HyperLogLog::new().to_bytes()).unwrap(),
+        HyperLogLog::new()
+    );
+}
+
+#[test]
+fn test_compare_and_swap() {
+    let tbl = KVEStandard::default();
+    assert_eq!(
+        tbl.compare_and_swap(b"k", b"v1", "v2".into()).unwrap(),
+        None
+    );
+    tbl.set("k".into(), "v1".into()).unwrap();
+    assert_eq!(
+        tbl.compare_and_swap(b"k", b"v0", "v2".into()).unwrap(),
+        Some(false)
+    );
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "v1");
+    assert_eq!(
+        tbl.compare_and_swap(b"k", b"v1", "v2".into()).unwrap(),
+        Some(true)
+    );
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "v2");
+}
+
+#[test]
+fn test_get_and_set() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.get_and_set("k".into(), "v1".into()).unwrap(), None);
+    assert!(tbl
+        .set_expiry(b"k", expiry::deadline_after_secs(100))
+        .unwrap());
+    assert_eq!(
+        tbl.get_and_set("k".into(), "v2".into()).unwrap().unwrap(),
+        "v1"
+    );
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "v2");
+    // the expiry doesn't survive the overwrite
+    assert_eq!(tbl.remaining_ttl(b"k").unwrap(), Some(None));
+    let string = KVEStandard::init(false, true);
+    assert!(string
+        .get_and_set("k".into(), SharedSlice::from(vec![0xFFu8]))
+        .is_err());
+}
+
+#[test]
+fn test_pattern_matching() {
+    let matches =
+        |pattern: &str, key: &str| Pattern::compile(pattern.as_bytes()).matches(key.as_bytes());
+    // prefixes
+    assert!(matches("user:*", "user:1"));
+    assert!(matches("user:*", "user:"));
+    assert!(!matches("user:*", "use"));
+    assert!(matches("*", ""));
+    // single bytes and sequences
+    assert!(matches("h?llo", "hello"));
+    assert!(!matches("h?llo", "hllo"));
+    assert!(matches("*:session:*", "user:session:42"));
+    assert!(matches("a*b*c", "aXbYbZc"));
+    assert!(!matches("a*b*c", "aXbYbZ"));
+    assert!(matches("exact", "exact"));
+    assert!(!matches("exact", "exactly"));
+    // classes
+    assert!(matches("h[ae]llo", "hallo"));
+    assert!(!matches("h[ae]llo", "hillo"));
+    assert!(matches("key[0-9]", "key7"));
+    assert!(!matches("key[!0-9]", "key7"));
+    assert!(matches("key[^0-9]", "keyx"));
+    assert!(matches("[]]", "]"));
+    // escapes and unterminated classes are literals
+    assert!(matches("what\\?", "what?"));
+    assert!(!matches("what\\?", "whats"));
+    assert!(matches("a[b", "a[b"));
+    assert!(matches("\\*", "*"));
+    assert!(!matches("\\*", "x"));
+}
+
+#[test]
+fn test_get_and_set_range() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.get_range(b"k", 0, 1).unwrap(), None);
+    assert_eq!(tbl.set_range(b"k", 0, b"x").unwrap(), None);
+    tbl.set("k".into(), "hello world".into()).unwrap();
+    assert_eq!(tbl.get_range(b"k", 0, 4).unwrap().unwrap(), "hello");
+    // the end is clamped to the length of the value
+    assert_eq!(tbl.get_range(b"k", 6, 100).unwrap().unwrap(), "world");
+    assert_eq!(tbl.get_range(b"k", 100, 200).unwrap().unwrap(), "");
+    assert_eq!(tbl.get_range(b"k", 4, 2).unwrap().unwrap(), "");
+    // overwrite in place
+    assert_eq!(tbl.set_range(b"k", 6, b"there").unwrap(), Some(Some(11)));
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "hello there");
+    // grow the value
+    assert_eq!(tbl.set_range(b"k", 6, b"everyone").unwrap(), Some(Some(14)));
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "hello everyone");
+    // offsets can't be past the end
+    assert_eq!(tbl.set_range(b"k", 15, b"!").unwrap(), Some(None));
+    assert_eq!(tbl.set_range(b"k", 14, b"!").unwrap(), Some(Some(15)));
+}
+
+#[test]
+fn test_range_keeps_strings_valid() {
+    let tbl = KVEStandard::init(true, true);
+    tbl.set("k".into(), "añb".into()).unwrap();
+    // 'ñ' takes up two bytes
+    assert!(tbl.get_range(b"k", 0, 1).is_err());
+    assert_eq!(tbl.get_range(b"k", 0, 2).unwrap().unwrap(), "añ");
+    assert!(tbl.set_range(b"k", 2, b"x").is_err());
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "añb");
+}
+
+#[test]
+fn test_append() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.append("log".into(), b"a").unwrap(), 1);
+    assert_eq!(tbl.append("log".into(), b"bc").unwrap(), 3);
+    assert_eq!(tbl.get_cloned("log").unwrap().unwrap(), "abc");
+}
+
+#[test]
+fn test_bits() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.get_bit(b"bits", 0).unwrap(), None);
+    assert_eq!(tbl.bit_count(b"bits").unwrap(), None);
+    // the value is zero-padded as needed
+    assert!(!tbl.set_bit("bits".into(), 9, true).unwrap());
+    assert_eq!(tbl.get_cloned("bits").unwrap().unwrap(), [0x00u8, 0x40]);
+    assert!(tbl.set_bit("bits".into(), 9, true).unwrap());
+    assert!(!tbl.set_bit("bits".into(), 0, true).unwrap());
+    assert_eq!(tbl.get_bit(b"bits", 0).unwrap(), Some(true));
+    assert_eq!(tbl.get_bit(b"bits", 1).unwrap(), Some(false));
+    // past the end
+    assert_eq!(tbl.get_bit(b"bits", 100).unwrap(), Some(false));
+    assert_eq!(tbl.bit_count(b"bits").unwrap(), Some(2));
+    assert!(tbl.set_bit("bits".into(), 0, false).unwrap());
+    assert_eq!(tbl.bit_count(b"bits").unwrap(), Some(1));
+}
+
+#[test]
+fn test_bits_keep_strings_valid() {
+    let tbl = KVEStandard::init(true, true);
+    tbl.set("k".into(), "a".into()).unwrap();
+    // setting the high bit of an ASCII byte leaves behind invalid UTF-8
+    assert!(tbl.set_bit("k".into(), 0, true).is_err());
+    assert!(!tbl.set_bit("k".into(), 6, true).unwrap());
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "c");
+}
+
+#[test]
+fn test_size_limits() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.pair_fits(&[0; 1024], &[0; 1024]));
+    tbl.limits().set_max_key_size(4);
+    tbl.limits().set_max_value_size(8);
+    assert!(tbl.pair_fits(b"key", b"value"));
+    assert!(!tbl.pair_fits(b"longkey", b"value"));
+    assert!(!tbl.pair_fits(b"key", b"longer value"));
+    assert!(tbl.pairs_fit([&b"k1"[..], b"v1", b"k2", b"v2"].into_iter()));
+    assert!(!tbl.pairs_fit([&b"k1"[..], b"v1", b"k2", b"value two"].into_iter()));
+    // growing a value past the limit fails, and leaves the value untouched
+    assert_eq!(tbl.append("k".into(), b"12345678").unwrap(), 8);
+    assert_eq!(tbl.append("k".into(), b"9"), Err(WriteError::TooLarge));
+    assert_eq!(tbl.set_range(b"k", 4, b"abcde"), Err(WriteError::TooLarge));
+    assert_eq!(tbl.set_bit("k".into(), 64, true), Err(WriteError::TooLarge));
+    assert_eq!(
+        tbl.set_bit("bits".into(), 1000, true),
+        Err(WriteError::TooLarge)
+    );
+    assert_eq!(tbl.get_cloned("k").unwrap().unwrap(), "12345678");
+    // zero removes the limit
+    tbl.limits().set_max_value_size(0);
+    assert_eq!(tbl.append("k".into(), b"9").unwrap(), 9);
+}
+
+#[test]
+fn test_rename_keeps_expiry() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.rename(b"a", "b".into()).unwrap(), None);
+    tbl.set("a".into(), "1".into()).unwrap();
+    tbl.set("c".into(), "3".into()).unwrap();
+    assert!(tbl
+        .set_expiry(b"a", expiry::deadline_after_secs(100))
+        .unwrap());
+    assert_eq!(tbl.rename(b"a", "c".into()).unwrap(), Some(false));
+    assert_eq!(tbl.rename(b"a", "b".into()).unwrap(), Some(true));
+    assert!(!tbl.exists("a").unwrap());
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "1");
+    assert!(tbl.remaining_ttl(b"b").unwrap().unwrap().is_some());
+}
+
+#[test]
+fn test_copy_across_encodings() {
+    let bin = KVEStandard::init(false, false);
+    let string = KVEStandard::init(false, true);
+    bin.set("okay".into(), "hello".into()).unwrap();
+    bin.set("bad".into(), SharedSlice::from(vec![0xFFu8]))
+        .unwrap();
+    assert_eq!(bin.copy_to(b"nope", &string, "x".into()).unwrap(), None);
+    assert_eq!(
+        bin.copy_to(b"okay", &string, "x".into()).unwrap(),
+        Some(true)
+    );
+    assert_eq!(string.get_cloned("x").unwrap().unwrap(), "hello");
+    assert_eq!(
+        bin.copy_to(b"okay", &string, "x".into()).unwrap(),
+        Some(false)
+    );
+    // the target only takes valid strings
+    assert!(bin.copy_to(b"bad", &string, "y".into()).is_err());
+    // copying within the same engine
+    assert_eq!(
+        bin.copy_to(b"okay", &bin, "okay2".into()).unwrap(),
+        Some(true)
+    );
+    assert_eq!(bin.get_cloned("okay2").unwrap().unwrap(), "hello");
+    // expired keys aren't copied
+    assert!(bin.set_with_expiry_unchecked("old".into(), "x".into(), expiry::now_millis() - 1));
+    assert_eq!(bin.copy_to(b"old", &string, "z".into()).unwrap(), None);
+    assert!(!string.exists("z").unwrap());
+}
+
+#[test]
+fn test_move_across_encodings() {
+    let bin = KVEStandard::init(false, false);
+    let string = KVEStandard::init(false, true);
+    bin.set("okay".into(), "hello".into()).unwrap();
+    bin.set("bad".into(), SharedSlice::from(vec![0xFFu8]))
+        .unwrap();
+    assert_eq!(bin.move_to(b"nope", &string).unwrap(), None);
+    assert_eq!(bin.move_to(b"okay", &string).unwrap(), Some(true));
+    assert!(!bin.exists("okay").unwrap());
+    assert_eq!(string.get_cloned("okay").unwrap().unwrap(), "hello");
+    bin.set("okay".into(), "world".into()).unwrap();
+    assert_eq!(bin.move_to(b"okay", &string).unwrap(), Some(false));
+    assert_eq!(bin.get_cloned("okay").unwrap().unwrap(), "world");
+    // the target only takes valid strings, and the key stays where it was
+    assert!(bin.move_to(b"bad", &string).is_err());
+    assert!(bin.exists("bad").unwrap());
+    assert!(!string.exists("bad").unwrap());
+    // moving within the same engine
+    assert_eq!(bin.move_to(b"okay", &bin).unwrap(), Some(false));
+    assert_eq!(bin.move_to(b"nope", &bin).unwrap(), None);
+}
+
+#[test]
+fn test_move_carries_expiry() {
+    let src = KVEStandard::default();
+    let compressed = KVEStandard::new_compressed(false, false, Default::default());
+    let value = "skytable".repeat(16);
+    src.set("a".into(), value.as_str().into()).unwrap();
+    assert!(src
+        .set_expiry(b"a", expiry::deadline_after_secs(100))
+        .unwrap());
+    assert_eq!(src.move_to(b"a", &compressed).unwrap(), Some(true));
+    assert_eq!(src.remaining_ttl(b"a").unwrap(), None);
+    assert!(compressed.remaining_ttl(b"a").unwrap().unwrap().is_some());
+    assert_eq!(compressed.get_cloned("a").unwrap().unwrap(), value);
+    // and back, without the stored form leaking out
+    assert_eq!(compressed.move_to(b"a", &src).unwrap(), Some(true));
+    assert_eq!(src.get_cloned("a").unwrap().unwrap(), value);
+}
+
+#[test]
+fn test_copy_is_independent() {
+    let tbl = KVESetmap::default();
+    tbl.set_add("s1".into(), vec!["a".into()]).unwrap();
+    assert_eq!(tbl.copy_to(b"s1", &tbl, "s2".into()).unwrap(), Some(true));
+    tbl.set_add("s1".into(), vec!["b".into()]).unwrap();
+    assert_eq!(tbl.set_members(b"s2").unwrap().unwrap().len(), 1);
+    assert_eq!(tbl.set_members(b"s1").unwrap().unwrap().len(), 2);
+}
+
+#[test]
+fn test_memory_usage() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.memory_usage(), 0);
+    tbl.set("a".into(), "1".into()).unwrap();
+    let one = tbl.memory_usage();
+    assert!(one >= 2);
+    tbl.set("b".into(), "a much longer value".into()).unwrap();
+    assert!(tbl.memory_usage() > 2 * one);
+    tbl.truncate_table();
+    assert_eq!(tbl.memory_usage(), 0);
+}
+
+#[test]
+fn test_key_memory_usage() {
+    let tbl = KVEStandard::default();
+    assert_eq!(tbl.key_memory_usage(b"a").unwrap(), None);
+    tbl.set("a".into(), "1".into()).unwrap();
+    tbl.set("b".into(), "2".into()).unwrap();
+    let a = tbl.key_memory_usage(b"a").unwrap().unwrap();
+    assert_eq!(tbl.memory_usage(), 2 * a);
+    // the deadline is accounted for too
+    tbl.set_expiry(b"a", expiry::deadline_after_secs(100))
+        .unwrap();
+    assert!(tbl.key_memory_usage(b"a").unwrap().unwrap() > a);
+    // bad keys
+    let strtbl = KVEStandard::init(true, true);
+    assert!(strtbl.key_memory_usage(b"\xFF").is_err());
+}
+
+#[test]
+fn test_sample_keys() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.sample_keys(10).is_empty());
+    for i in 0..100 {
+        tbl.set(i.to_string().into(), "v".into()).unwrap();
+    }
+    assert!(tbl.sample_keys(0).is_empty());
+    let mut sample = tbl.sample_keys(10);
+    assert_eq!(sample.len(), 10);
+    sample.sort();
+    sample.dedup();
+    assert_eq!(sample.len(), 10);
+    assert!(sample.iter().all(|key| tbl.exists(key).unwrap()));
+    // we can't sample more than we have
+    assert_eq!(tbl.sample_keys(1000).len(), 100);
+}
+
+#[test]
+fn test_sample_keys_skips_expired() {
+    let tbl = KVEStandard::default();
+    tbl.set("live".into(), "v".into()).unwrap();
+    tbl.set("dead".into(), "v".into()).unwrap();
+    tbl.set_expiry(b"dead", 0).unwrap();
+    assert_eq!(tbl.sample_keys(10), vec![SharedSlice::from("live")]);
+}
+
+/// Returns a table with the given eviction policy that holds `keys` keys of the same size and
+/// is capped to `cap` of them
+fn evicting_table(policy: EvictionPolicy, keys: usize, cap: usize) -> KVEStandard {
+    let tbl = KVEStandard::default();
+    tbl.eviction().set_policy(policy);
+    for i in 0..keys {
+        tbl.set(format!("k{i:02}").into(), "v".into()).unwrap();
+    }
+    tbl.eviction()
+        .set_max_memory(cap * tbl.memory_usage() / keys);
+    tbl
+}
+
+#[test]
+fn test_eviction_lru() {
+    let tbl = evicting_table(EvictionPolicy::Lru, 3, 2);
+    // k01 is now the least recently used key
+    assert!(tbl.get(b"k00").unwrap().is_some());
+    assert_eq!(tbl.evict(true), 1);
+    assert!(!tbl.exists(b"k01").unwrap());
+    assert!(tbl.exists(b"k00").unwrap() && tbl.exists(b"k02").unwrap());
+    assert_eq!(tbl.eviction().evicted(), 1);
+    // we fit now
+    assert_eq!(tbl.evict(true), 0);
+}
+
+#[test]
+fn test_eviction_lfu() {
+    let tbl = evicting_table(EvictionPolicy::Lfu, 3, 1);
+    for _ in 0..2 {
+        assert!(tbl.get(b"k00").unwrap().is_some());
+    }
+    assert!(tbl.get(b"k02").unwrap().is_some());
+    // k01 is the least frequently used key, followed by k02
+    assert_eq!(tbl.evict(true), 2);
+    assert!(tbl.exists(b"k00").unwrap());
+    assert_eq!(tbl.len(), 1);
+}
+
+#[test]
+fn test_eviction_random() {
+    let tbl = evicting_table(EvictionPolicy::Random, 100, 40);
+    assert_eq!(tbl.evict(true), 60);
+    assert_eq!(tbl.len(), 40);
+    assert_eq!(tbl.eviction().evicted(), 60);
+}
+
+#[test]
+fn test_eviction_needs_volatile_and_policy() {
+    let tbl = evicting_table(EvictionPolicy::Lru, 10, 5);
+    // nothing is evicted from tables that aren't volatile
+    assert_eq!(tbl.evict(false), 0);
+    tbl.eviction().set_policy(EvictionPolicy::None);
+    assert_eq!(tbl.evict(true), 0);
+    tbl.eviction().set_policy(EvictionPolicy::Random);
+    tbl.eviction().set_max_memory(0);
+    assert_eq!(tbl.evict(true), 0);
+    assert_eq!(tbl.len(), 10);
+}
+
+#[test]
+fn test_eviction_notifications() {
+    let hub = Arc::new(PubSub::new());
+    let channel = notify::channel_for(b"default", b"default");
+    let mut subscriber = Subscriber::new(hub.clone());
+    subscriber.subscribe(channel.clone());
+    let tbl = evicting_table(EvictionPolicy::Lru, 2, 1);
+    tbl.notifier().enable(hub, channel);
+    assert_eq!(tbl.evict(true), 1);
+    let payloads: Vec<SharedSlice> = iter::from_fn(|| subscriber.try_recv())
+        .map(|message| message.payload)
+        .collect();
+    assert_eq!(payloads, ["evicted:k00"]);
+}
+
+#[test]
+fn test_json_validation() {
+    let valid: [&[u8]; 10] = [
+        b"null",
+        b" true ",
+        b"-0.5e+10",
+        br#""esc\"aped \u00e9""#,
+        b"[]",
+        b"{}",
+        br#"{"a": [1, 2, {"b": null}], "c": "d"}"#,
+        "\"ユニコード\"".as_bytes(),
+        b"[[[[[[[[[[]]]]]]]]]]",
+        b"\n[1,\t2]\r\n",
+    ];
+    for src in valid {
+        assert!(json::is_json(src), "{}", String::from_utf8_lossy(src));
+    }
+    let invalid: [&[u8]; 14] = [
+        b"",
+        b"nul",
+        b"01",
+        b"1.",
+        b"-",
+        b"[1,]",
+        b"{\"a\"}",
+        b"{\"a\":1,}",
+        b"{a:1}",
+        b"[1 2]",
+        b"\"tab\there\"",
+        br#""\x""#,
+        b"[1]]",
+        b"1 2",
+    ];
+    for src in invalid {
+        assert!(!json::is_json(src), "{}", String::from_utf8_lossy(src));
+    }
+    // deep nesting doesn't blow up the stack
+    let deep = [b"[".repeat(100_000), b"]".repeat(100_000)].concat();
+    assert!(json::is_json(&deep));
+}
+
+#[test]
+fn test_json_path() {
+    assert_eq!(json::parse_path(b"$").unwrap(), vec![]);
+    assert_eq!(json::parse_path(b"").unwrap(), vec![]);
+    assert_eq!(
+        json::parse_path(b"$.users[10].name").unwrap(),
+        vec![
+            PathSegment::Key(b"users"),
+            PathSegment::Index(10),
+            PathSegment::Key(b"name")
+        ]
+    );
+    assert_eq!(
+        json::parse_path(b"users[0]").unwrap(),
+        vec![PathSegment::Key(b"users"), PathSegment::Index(0)]
+    );
+    for bad in [&b"$."[..], b"$..a", b"$[", b"$[-1]", b"$[a]", b"$a"] {
+        assert!(json::parse_path(bad).is_none());
+    }
+}
+
+#[test]
+fn test_json_lookup() {
+    let doc = br#" {"name": "sayan", "langs": ["rust", {"esc\"aped": 1}], "n\u00e9": null} "#;
+    let lookup = |path: &[u8]| json::lookup(doc, &json::parse_path(path).unwrap());
+    assert_eq!(lookup(b"$").unwrap(), &doc[1..doc.len() - 1]);
+    assert_eq!(lookup(b"$.name").unwrap(), br#""sayan""#);
+    assert_eq!(lookup(b"$.langs[0]").unwrap(), br#""rust""#);
+    assert_eq!(lookup(b"$.langs[1]").unwrap(), br#"{"esc\"aped": 1}"#);
+    assert_eq!(lookup("$.langs[1].esc\"aped".as_bytes()).unwrap(), b"1");
+    assert_eq!(lookup("$.né".as_bytes()).unwrap(), b"null");
+    // missing members, elements and bad types
+    assert!(lookup(b"$.nope").is_none());
+    assert!(lookup(b"$.langs[2]").is_none());
+    assert!(lookup(b"$.name[0]").is_none());
+    assert!(lookup(b"$[0]").is_none());
+}
+
+#[test]
+fn test_json_string_array() {
+    assert_eq!(
+        json::parse_string_array(br#" ["SET", "x", "esc\"aped \u00e9\n"] "#).unwrap(),
+        vec![
+            b"SET".to_vec(),
+            b"x".to_vec(),
+            "esc\"aped é\n".as_bytes().to_vec()
+        ]
+    );
+    assert_eq!(
+        json::parse_string_array(b"[]").unwrap(),
+        Vec::<Vec<u8>>::new()
+    );
+    for bad in [
+        &b""[..],
+        b"[",
+        b"[1]",
+        b"[\"a\",]",
+        b"[\"a\"] []",
+        b"{\"a\": \"b\"}",
+        br#"["\ud800"]"#,
+    ] {
+        assert!(json::parse_string_array(bad).is_none());
+    }
+}
+
+#[test]
+fn test_json_string() {
+    assert_eq!(
+        json::parse_string(br#""esc\"aped \u00e9""#).unwrap(),
+        "esc\"aped \u{e9}".as_bytes()
+    );
+    assert_eq!(json::parse_string(br#""""#).unwrap(), b"");
+    for bad in [&b""[..], b"\"", b"\"a\" ", b"1", b"null"] {
+        assert!(json::parse_string(bad).is_none());
+    }
+}
+
+#[test]
+fn test_json_values() {
+    let tbl = KVEStandard::new_json(true, Default::default());
+    assert!(tbl.is_json());
+    assert!(tbl.set("doc".into(), r#"{"a":1}"#.into()).unwrap());
+    assert!(tbl.set("bad".into(), "{".into()).is_err());
+    assert!(tbl.update("doc".into(), "nope".into()).is_err());
+    assert!(tbl.upsert("doc".into(), "[1]".into()).is_ok());
+    assert!(!tbl.get_double_encoder()(b"k", b"[1"));
+    assert!(tbl.get_double_encoder()(b"k", b"[1]"));
+    // appends are fine as long as we end up with JSON
+    assert!(tbl.append("doc".into(), b"1").is_err());
+    assert!(tbl.append("num".into(), b"1").is_ok());
+    assert_eq!(tbl.append("num".into(), b"2").unwrap(), 2);
+    assert_eq!(
+        tbl.json_get(b"doc", &[PathSegment::Index(0)]).unwrap(),
+        Some(Some(SharedSlice::from("1")))
+    );
+    assert_eq!(
+        tbl.json_get(b"doc", &[PathSegment::Index(1)]).unwrap(),
+        Some(None)
+    );
+    assert_eq!(tbl.json_get(b"nope", &[]).unwrap(), None);
+    // copies into a JSON table are validated too
+    let src = KVEStandard::init(true, true);
+    src.set("text".into(), "not json".into()).unwrap();
+    src.set("json".into(), "true".into()).unwrap();
+    assert!(src.copy_to(b"text", &tbl, "copy".into()).is_err());
+    assert_eq!(
+        src.copy_to(b"json", &tbl, "copy".into()).unwrap(),
+        Some(true)
+    );
+}
+
+#[test]
+fn test_notifications() {
+    let hub = Arc::new(PubSub::new());
+    let channel = notify::channel_for(b"default", b"default");
+    let mut subscriber = Subscriber::new(hub.clone());
+    subscriber.subscribe(channel.clone());
+    let tbl = KVEStandard::default();
+    // nothing is published until notifications are enabled
+    assert!(tbl.set("a".into(), "1".into()).unwrap());
+    tbl.notifier().enable(hub, channel);
+    assert!(tbl.update("a".into(), "2".into()).unwrap());
+    assert!(tbl.set("b".into(), "1".into()).unwrap());
+    assert!(tbl.set_expiry(b"b", expiry::now_millis() - 1).unwrap());
+    assert!(!tbl.exists("b").unwrap());
+    assert!(tbl.remove("a").unwrap());
+    // writes that don't change anything aren't published
+    assert!(!tbl.update("a".into(), "3".into()).unwrap());
+    tbl.notifier().disable();
+    assert!(tbl.set("c".into(), "1".into()).unwrap());
+    let payloads: Vec<SharedSlice> = iter::from_fn(|| subscriber.try_recv())
+        .map(|message| message.payload)
+        .collect();
+    assert_eq!(payloads, ["update:a", "set:b", "expired:b", "del:a"]);
+}
+
+#[test]
+fn test_notifications_for_rolled_back_transactions() {
+    let hub = Arc::new(PubSub::new());
+    let channel = notify::channel_for(b"default", b"default");
+    let mut subscriber = Subscriber::new(hub.clone());
+    subscriber.subscribe(channel.clone());
+    let tbl = KVEStandard::default();
+    assert!(tbl.set("a".into(), "1".into()).unwrap());
+    tbl.notifier().enable(hub, channel);
+    let ops = [
+        TxnOp::Upsert("b".into(), "2".into()),
+        // `a` already exists, so this fails
+        TxnOp::Set("a".into(), "10".into()),
+    ];
+    assert!(!tbl.apply_transaction(&ops).unwrap());
+    let payloads: Vec<SharedSlice> = iter::from_fn(|| subscriber.try_recv())
+        .map(|message| message.payload)
+        .collect();
+    // the rollback is published too
+    assert_eq!(payloads, ["set:b", "del:b"]);
+}
+
+#[test]
+fn test_remove_prefix() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.set("user:1".into(), "a".into()).unwrap());
+    assert!(tbl.set("user:2".into(), "b".into()).unwrap());
+    assert!(tbl.set("users".into(), "c".into()).unwrap());
+    assert!(tbl.set_with_expiry_unchecked("user:3".into(), "d".into(), expiry::now_millis() - 1));
+    // the expired key isn't counted
+    assert_eq!(tbl.remove_prefix(b"user:"), 2);
+    assert_eq!(tbl.remove_prefix(b"user:"), 0);
+    assert_eq!(tbl.len(), 1);
+    assert!(tbl.exists("users").unwrap());
+}
+
+#[test]
+fn test_compressed_values() {
+    let tbl = KVEStandard::new_compressed(false, true, Default::default());
+    let value = "skytable ".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    assert!(tbl.set("b".into(), "tiny".into()).unwrap());
+    // the value is stored compressed, but read back as is
+    assert!(tbl.get("a").unwrap().unwrap().len() < value.len());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), value.as_str());
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "tiny");
+    assert_eq!(tbl.value_len(b"a").unwrap(), Some(value.len()));
+    assert_eq!(tbl.value_len(b"b").unwrap(), Some(4));
+    // partial reads and writes see the decompressed value
+    assert_eq!(tbl.get_range(b"a", 0, 7).unwrap().unwrap(), "skytable");
+    assert_eq!(tbl.append("a".into(), b"!").unwrap(), value.len() + 1);
+    assert_eq!(tbl.set_range(b"b", 0, b"T").unwrap(), Some(Some(4)));
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "Tiny");
+    assert!(!tbl.get_bit(b"b", 0).unwrap().unwrap());
+    assert_eq!(tbl.bit_count(b"b").unwrap(), Some(17));
+    // as do comparisons
+    assert_eq!(
+        tbl.compare_and_swap(b"b", b"Tiny", "small".into()),
+        Ok(Some(true))
+    );
+    // a rolled back transaction leaves behind a compressed value
+    let ops = [
+        TxnOp::Upsert("a".into(), "x".into()),
+        TxnOp::Set("b".into(), "y".into()),
+    ];
+    assert!(!tbl.apply_transaction(&ops).unwrap());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), value.clone() + "!");
+    // copying to an uncompressed table copies the value, not its stored form
+    let uncompressed = KVEStandard::init(false, true);
+    assert_eq!(tbl.copy_to(b"a", &uncompressed, "a".into()), Ok(Some(true)));
+    assert_eq!(
+        uncompressed.get("a").unwrap().unwrap().len(),
+        value.len() + 1
+    );
+    assert_eq!(tbl.pop("b").unwrap().unwrap(), "small");
+}
+
+#[test]
+fn test_corrupted_compressed_values() {
+    let tbl = KVEStandard::new_compressed(false, true, Default::default());
+    assert!(tbl
+        .set("a".into(), "skytable ".repeat(100).as_str().into())
+        .unwrap());
+    // an LZ4 frame that ends in the middle of a sequence and a frame of an unknown kind
+    let bad = [
+        SharedSlice::from(vec![1u8, 10, 0, 0, 0, 0xFF]),
+        SharedSlice::from(vec![7u8, b'x']),
+    ];
+    for stored in bad {
+        tbl.get_inner_ref().upsert("a".into(), stored.clone());
+        assert_eq!(tbl.get_cloned("a"), Err(ReadError::Unreadable));
+        assert_eq!(tbl.get_range(b"a", 0, 1), Err(ReadError::Unreadable));
+        assert_eq!(tbl.append("a".into(), b"!"), Err(WriteError::Unreadable));
+        assert_eq!(tbl.pop("a"), Err(ReadError::Unreadable));
+        // nothing was overwritten or removed
+        assert_eq!(
+            tbl.get_inner_ref().get_cloned(b"a".as_ref()).unwrap(),
+            stored
+        );
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.set("a".into(), "1".into()).unwrap());
+    assert!(tbl.set("b".into(), "2".into()).unwrap());
+    assert!(tbl.set_with_expiry_unchecked("c".into(), "3".into(), expiry::now_millis() - 1));
+    let snapshot = tbl.snapshot();
+    assert!(tbl.update("a".into(), "10".into()).unwrap());
+    assert!(tbl.remove("b").unwrap());
+    assert!(tbl.set("d".into(), "4".into()).unwrap());
+    assert_eq!(snapshot.get(b"a").unwrap().unwrap(), "1");
+    assert_eq!(snapshot.get(b"b").unwrap().unwrap(), "2");
+    // expired keys aren't part of the snapshot, and later writes aren't seen
+    assert!(snapshot.get(b"c").unwrap().is_none());
+    assert!(snapshot.get(b"d").unwrap().is_none());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "10");
+}
+
+#[test]
+fn test_snapshot_of_compressed_values() {
+    let tbl = KVEStandard::new_compressed(false, false, Default::default());
+    let value = "skytable".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    assert_eq!(tbl.snapshot().get(b"a").unwrap().unwrap(), value.as_str());
+}
+
+#[test]
+fn test_disk_values() {
+    let tbl = KVEStandard::new_disk(false, true, Default::default());
+    let value = "skytable ".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    assert!(tbl.set("b".into(), "tiny".into()).unwrap());
+    // only a stub is kept in memory, but the value is read back as is
+    assert!(tbl.get("a").unwrap().unwrap().len() < value.len());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), value.as_str());
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), "tiny");
+    assert_eq!(tbl.value_len(b"a").unwrap(), Some(value.len()));
+    assert_eq!(tbl.value_len(b"b").unwrap(), Some(4));
+    assert_eq!(tbl.get_range(b"a", 0, 7).unwrap().unwrap(), "skytable");
+    assert_eq!(tbl.append("a".into(), b"!").unwrap(), value.len() + 1);
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), value.clone() + "!");
+    // moving to a table in memory moves the value, not its stub
+    let memory = KVEStandard::init(false, true);
+    assert_eq!(tbl.move_to(b"a", &memory).unwrap(), Some(true));
+    assert_eq!(memory.get("a").unwrap().unwrap().len(), value.len() + 1);
+    // and back
+    assert_eq!(memory.move_to(b"a", &tbl).unwrap(), Some(true));
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), value.clone() + "!");
+    assert_eq!(tbl.pop("b").unwrap().unwrap(), "tiny");
+}
+
+#[test]
+fn test_unreadable_disk_values() {
+    let tbl = KVEStandard::new_disk(false, false, Default::default());
+    let value = "skytable".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    // a stub that points past the end of the file fails the read instead of the server
+    let mut stub = tbl.get("a").unwrap().unwrap().to_vec();
+    stub[9..17].copy_from_slice(&(1u64 << 30).to_le_bytes());
+    tbl.get_inner_ref()
+        .upsert("a".into(), SharedSlice::from(stub));
+    assert_eq!(tbl.get_cloned("a"), Err(ReadError::Unreadable));
+    assert_eq!(tbl.snapshot().get(b"a"), Err(Unreadable));
+    assert_eq!(tbl.pop("a"), Err(ReadError::Unreadable));
+    assert!(tbl.exists("a").unwrap());
+}
+
+#[test]
+fn test_snapshot_of_disk_values() {
+    let tbl = KVEStandard::new_disk(false, false, Default::default());
+    let value = "skytable".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    let snapshot = tbl.snapshot();
+    // the old value is still in the file
+    assert!(tbl.update("a".into(), "b".into()).unwrap());
+    assert_eq!(snapshot.get(b"a").unwrap().unwrap(), value.as_str());
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "b");
+}
+
+#[test]
+fn test_compaction_of_disk_values() {
+    let tbl = KVEStandard::new_disk(false, false, Default::default());
+    let value = "skytable".repeat(100);
+    assert!(tbl.set("a".into(), value.as_str().into()).unwrap());
+    // nothing to reclaim yet
+    assert!(tbl.compact_values().is_none());
+    assert!(tbl.set("b".into(), value.as_str().into()).unwrap());
+    for _ in 0..2 {
+        assert!(tbl.update("b".into(), value.as_str().into()).unwrap());
+    }
+    let snapshot = tbl.snapshot();
+    assert!(tbl.update("a".into(), "tiny".into()).unwrap());
+    let mut values = tbl.compact_values().unwrap();
+    for step in 0..values.steps() {
+        values.step(step);
+    }
+    // the dead copies of `b` and the old value of `a`
+    assert_eq!(values.finish(), 3 * value.len() as u64);
+    assert_eq!(tbl.get_cloned("a").unwrap().unwrap(), "tiny");
+    assert_eq!(tbl.get_cloned("b").unwrap().unwrap(), value.as_str());
+    // the snapshot still reads from the old file
+    assert_eq!(snapshot.get(b"a").unwrap().unwrap(), value.as_str());
+    assert!(tbl.compact_values().is_none());
+}
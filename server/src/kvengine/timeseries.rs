@@ -0,0 +1,91 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Time series keymaps
+//!
+//! A [`KVETimeseriesmap`] maps every series name to a [`TimeSeries`]. Adding a sample to a
+//! series that doesn't exist creates it
+
+use {
+    super::{notify::Event, EncodingResult, KVETimeseriesmap, LockedTimeseries},
+    crate::corestore::{
+        timeseries::{Sample, TimeSeries},
+        SharedSlice,
+    },
+};
+
+impl KVETimeseriesmap {
+    /// Add a sample to a series, creating the series if it doesn't exist. If a retention
+    /// period is provided, it replaces the retention period of the series. Returns `None` if
+    /// the sample is too old for the retention period (and otherwise, whether the sample was
+    /// newly added)
+    pub fn ts_add(
+        &self,
+        key: SharedSlice,
+        sample: Sample,
+        retention: Option<u64>,
+    ) -> EncodingResult<Option<bool>> {
+        self.check_key_encoding(&key)?;
+        self.evict_if_expired(&key);
+        let (ret, event) = loop {
+            if let Some(series) = self.data.get(&key) {
+                let mut wlock = series.write();
+                if let Some(retention) = retention {
+                    wlock.set_retention(retention);
+                }
+                break (wlock.add(sample.timestamp, sample.value), Event::Update);
+            }
+            if let Some(entry) = self.data.fresh_entry(key.clone()) {
+                let mut series = TimeSeries::new(retention.unwrap_or(0));
+                let ret = series.add(sample.timestamp, sample.value);
+                entry.insert(LockedTimeseries::new(series));
+                break (ret, Event::Set);
+            }
+            // someone created the series right after we looked for it; just retry
+        };
+        if ret.is_some() {
+            self.notify(event, &key);
+        }
+        Ok(ret)
+    }
+    /// Returns the samples with a timestamp in the inclusive range `from..=to` (oldest first)
+    /// or `None` if the series doesn't exist
+    pub fn ts_range(&self, key: &[u8], from: u64, to: u64) -> EncodingResult<Option<Vec<Sample>>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self
+            .data
+            .get(key)
+            .map(|series| series.read().range(from, to)))
+    }
+    /// Returns the newest sample in a series. The outer option is `None` if the series doesn't
+    /// exist, while the inner option is `None` if the series has no samples
+    pub fn ts_last(&self, key: &[u8]) -> EncodingResult<Option<Option<Sample>>> {
+        self.check_key_encoding(key)?;
+        self.evict_if_expired(key);
+        Ok(self.data.get(key).map(|series| series.read().last()))
+    }
+}
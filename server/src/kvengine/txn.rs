@@ -0,0 +1,242 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Transactions
+//!
+//! A transaction is a batch of write operations that is applied to a [`KVEStandard`] as a
+//! single unit: either all of the operations succeed, or none of them are applied. Operations
+//! are applied in order, with an undo log that is replayed backwards if any of them fails. The
+//! undo log also remembers the deadlines of the keys, so that a rollback puts back their expiry
+//! as well. A transaction holds every other write to the table off until it's done, so nobody
+//! can see a transaction halfway through or have their write undone by a rollback
+
+use {
+    super::{notify::Event, EncodingResult, KVEStandard},
+    crate::corestore::SharedSlice,
+};
+
+/// A single write operation in a transaction
+#[derive(Debug, PartialEq)]
+pub enum TxnOp {
+    /// Insert a new key. Fails if the key already exists
+    Set(SharedSlice, SharedSlice),
+    /// Update an existing key. Fails if the key doesn't exist
+    Update(SharedSlice, SharedSlice),
+    /// Insert or update a key. Never fails
+    Upsert(SharedSlice, SharedSlice),
+    /// Remove an existing key. Fails if the key doesn't exist
+    Del(SharedSlice),
+}
+
+impl TxnOp {
+    pub fn key(&self) -> &SharedSlice {
+        match self {
+            Self::Set(k, _) | Self::Update(k, _) | Self::Upsert(k, _) | Self::Del(k) => k,
+        }
+    }
+    pub fn value(&self) -> Option<&SharedSlice> {
+        match self {
+            Self::Set(_, v) | Self::Update(_, v) | Self::Upsert(_, v) => Some(v),
+            Self::Del(_) => None,
+        }
+    }
+}
+
+/// What an operation changed, so that it can be undone
+struct Undo {
+    key: SharedSlice,
+    /// the value before the op (in the form in which it's stored) along with its deadline
+    previous: Option<(SharedSlice, Option<u64>)>,
+    /// the value written by the op (in the form in which it's stored)
+    written: Option<SharedSlice>,
+}
+
+impl KVEStandard {
+    /// Apply all the operations atomically. Returns `Ok(true)` if all the operations were applied
+    /// and `Ok(false)` if an operation failed (in which case all the changes are rolled back). An
+    /// encoding error is returned (before anything is applied) if any key or value is not
+    /// correctly encoded
+    pub fn apply_transaction(&self, ops: &[TxnOp]) -> EncodingResult<bool> {
+        let encoding_is_okay = ops.iter().all(|op| {
+            self.is_key_ok(op.key()) && op.value().map(|v| self.is_val_ok(v)).unwrap_or(true)
+        });
+        if !encoding_is_okay {
+            return Err(());
+        }
+        // no other write can happen until we're done (or have rolled back), so the write
+        // primitives can't be used from here on
+        let _txn_guard = self.txn_lock.write();
+        let mut undo_log: Vec<Undo> = Vec::with_capacity(ops.len());
+        for op in ops {
+            match self.apply_op(op) {
+                Some(undo) => undo_log.push(undo),
+                None => {
+                    self.rollback(undo_log);
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+    /// Apply a single operation, returning `None` if it failed
+    fn apply_op(&self, op: &TxnOp) -> Option<Undo> {
+        let key = op.key().clone();
+        self.evict_if_expired(&key);
+        let (undo, event) = match op {
+            TxnOp::Set(_, v) => {
+                let value = self.pack(v.clone());
+                let guard = self.data.fresh_entry(key.clone())?.insert(value.clone());
+                // a stale deadline must never carry over to a fresh key
+                self.clear_expiry_unchecked(&key);
+                drop(guard);
+                (
+                    Undo {
+                        key: key.clone(),
+                        previous: None,
+                        written: Some(value),
+                    },
+                    Event::Set,
+                )
+            }
+            TxnOp::Update(_, v) => {
+                let value = self.pack(v.clone());
+                let mut oe = self.data.mut_entry(key.clone())?;
+                // the deadline is retained, like for any other update
+                let previous = (oe.insert(value.clone()), self.ttl.get_cloned(&key));
+                drop(oe);
+                (
+                    Undo {
+                        key: key.clone(),
+                        previous: Some(previous),
+                        written: Some(value),
+                    },
+                    Event::Update,
+                )
+            }
+            TxnOp::Upsert(_, v) => {
+                let value = self.pack(v.clone());
+                let previous = match self.data.mut_entry(key.clone()) {
+                    Some(mut oe) => {
+                        let previous = (oe.insert(value.clone()), self.ttl.get_cloned(&key));
+                        self.clear_expiry_unchecked(&key);
+                        Some(previous)
+                    }
+                    None => {
+                        let guard = self.data.fresh_entry(key.clone())?.insert(value.clone());
+                        self.clear_expiry_unchecked(&key);
+                        drop(guard);
+                        None
+                    }
+                };
+                (
+                    Undo {
+                        key: key.clone(),
+                        previous,
+                        written: Some(value),
+                    },
+                    Event::Set,
+                )
+            }
+            TxnOp::Del(_) => {
+                let oe = self.data.mut_entry(key.clone())?;
+                let deadline = self.ttl.get_cloned(&key);
+                self.clear_expiry_unchecked(&key);
+                let previous = (oe.remove(), deadline);
+                (
+                    Undo {
+                        key: key.clone(),
+                        previous: Some(previous),
+                        written: None,
+                    },
+                    Event::Del,
+                )
+            }
+        };
+        if undo.written.is_some() {
+            self.touch(&key);
+        }
+        self.notify(event, &key);
+        Some(undo)
+    }
+    /// Undo the operations, most recent first. A key is only restored if it still holds what
+    /// the transaction left behind; if it has since been evicted (or has expired), it's left
+    /// alone
+    fn rollback(&self, undo_log: Vec<Undo>) {
+        for Undo {
+            key,
+            previous,
+            written,
+        } in undo_log.into_iter().rev()
+        {
+            // the key exists again after the rollback only if it existed before the transaction
+            let event = if previous.is_some() {
+                Event::Set
+            } else {
+                Event::Del
+            };
+            let restored = match written {
+                Some(written) => match self.data.mut_entry(key.clone()) {
+                    Some(mut oe) if *oe.value() == written => {
+                        match previous {
+                            Some((value, deadline)) => {
+                                oe.insert(value);
+                                self.restore_deadline(&key, deadline);
+                            }
+                            None => {
+                                self.clear_expiry_unchecked(&key);
+                                oe.remove();
+                            }
+                        }
+                        true
+                    }
+                    _ => false,
+                },
+                None => match (self.data.fresh_entry(key.clone()), previous) {
+                    (Some(ve), Some((value, deadline))) => {
+                        let guard = ve.insert(value);
+                        self.restore_deadline(&key, deadline);
+                        drop(guard);
+                        true
+                    }
+                    _ => false,
+                },
+            };
+            if restored {
+                self.notify(event, &key);
+            }
+        }
+    }
+    /// Put back the deadline the key had before the transaction (or drop it if it had none).
+    /// This must be called while holding the key's entry
+    fn restore_deadline(&self, key: &SharedSlice, deadline: Option<u64>) {
+        match deadline {
+            Some(deadline) => self.ttl.upsert(key.clone(), deadline),
+            None => {
+                self.clear_expiry_unchecked(key);
+            }
+        }
+    }
+}
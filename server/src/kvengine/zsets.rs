@@ -0,0 +1,98 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Sorted set keymaps
+//!
+//! A [`KVEZsetmap`] maps every key to a [`SortedSet`](crate::corestore::zset::SortedSet),
+//! which is what you'd want for leaderboard-style workloads
+
+use {
+    super::{notify::Event, EncodingResult, KVEZsetmap, LockedZset},
+    crate::corestore::{zset::SortedSet, SharedSlice},
+};
+
+impl KVEZsetmap {
+    /// Add members (or update their scores) in a sorted set, creating the sorted set if it
+    /// doesn't exist. Returns the number of members that were newly added. Caller must check
+    /// the encoding of the members
+    pub fn zset_add(
+        &self,
+        zsetname: SharedSlice,
+        members: Vec<(f64, SharedSlice)>,
+    ) -> EncodingResult<usize> {
+        self.check_key_encoding(&zsetname)?;
+        self.evict_if_expired(&zsetname);
+        loop {
+            if let Some(zset) = self.data.get(&zsetname) {
+                let mut wlock = zset.write();
+                let added = members
+                    .into_iter()
+                    .map(|(score, member)| wlock.insert(member, score) as usize)
+                    .sum();
+                // the scores of existing members may have changed, so this is always an update
+                self.notify(Event::Update, &zsetname);
+                return Ok(added);
+            }
+            if let Some(entry) = self.data.fresh_entry(zsetname.clone()) {
+                let mut zset = SortedSet::new();
+                let added = members
+                    .into_iter()
+                    .map(|(score, member)| zset.insert(member, score) as usize)
+                    .sum();
+                entry.insert(LockedZset::new(zset));
+                self.notify(Event::Set, &zsetname);
+                return Ok(added);
+            }
+            // someone removed the sorted set right after we found it; just retry
+        }
+    }
+    /// Returns the members with a score in the inclusive range `min..=max` (in ascending order)
+    /// or `None` if the sorted set doesn't exist
+    pub fn zset_range_by_score(
+        &self,
+        zsetname: &[u8],
+        min: f64,
+        max: f64,
+    ) -> EncodingResult<Option<Vec<SharedSlice>>> {
+        self.check_key_encoding(zsetname)?;
+        self.evict_if_expired(zsetname);
+        Ok(self
+            .data
+            .get(zsetname)
+            .map(|zset| zset.read().range_by_score(min, max)))
+    }
+    /// Returns the rank of a member. The outer option is `None` if the sorted set doesn't
+    /// exist, while the inner option is `None` if the member doesn't exist
+    pub fn zset_rank(
+        &self,
+        zsetname: &[u8],
+        member: &[u8],
+    ) -> EncodingResult<Option<Option<usize>>> {
+        self.check_key_encoding(zsetname)?;
+        self.evict_if_expired(zsetname);
+        Ok(self.data.get(zsetname).map(|zset| zset.read().rank(member)))
+    }
+}
@@ -0,0 +1,341 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Logging
+//!
+//! Records are written to `stderr` as text lines (the default) or as JSON objects, one per
+//! line (with `--logformat json` or `server.logformat = "json"`), so that log pipelines don't
+//! have to parse the messages. The `SKY_LOG` environment variable can be used to pass
+//! filters, like it always could.
+//!
+//! Every connection is served within a span that has the ID of its client (see
+//! [`crate::dbnet::clients`]) and its address, and every request (a query or a pipeline of
+//! queries) runs within a span (inside the connection's) that has the ID of the request and
+//! the entity that it's run on. Request IDs are unique (for as long as the server runs), so
+//! they tie together all the records emitted while a request is dispatched and run, including
+//! the ones from the storage engine and the error paths. The fields of the spans that a record
+//! is emitted in are added to it: as members of the JSON object, or as `name=value` pairs
+//! after the message of a text line. A `DEBUG` record is emitted when a connection opens and
+//! closes, and when a request finishes (with the action and its duration in microseconds).
+//! A connection can also get the ID of each of its requests (to correlate them with the logs
+//! on its end) by turning on request ID frames with `SYS REQUESTID ON`
+
+use {
+    crate::{config::LogFormat, corestore::memstore::ObjectID},
+    chrono::{SecondsFormat, Utc},
+    core::{
+        cell::RefCell,
+        fmt::{self, Write as _},
+        future::Future,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+    env_logger::{fmt::Formatter, Builder},
+    log::{LevelFilter, Record},
+    std::{
+        env,
+        io::{self, Write},
+        net::SocketAddr,
+        time::{Duration, Instant},
+    },
+};
+
+/// Set if the records are written as JSON objects
+static JSON: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    /// The span of the connection that the current task serves
+    static SPAN: RefCell<Span>;
+}
+
+/// Set up the logger (with the text format, till [`set_format`] is called)
+pub fn init() {
+    let mut builder = Builder::new();
+    builder.format(self::format);
+    match env::var("SKY_LOG") {
+        Ok(filters) => builder.parse_filters(&filters).init(),
+        Err(_) => {
+            // let everything through the logger and cap the level instead, so that `loglevel`
+            // can raise it later on (see `services::confreload`)
+            builder.filter_level(LevelFilter::Trace).init();
+            log::set_max_level(LevelFilter::Info);
+        }
+    }
+}
+
+/// Switch the format that records are written in
+pub fn set_format(format: LogFormat) {
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+}
+
+#[derive(Debug)]
+/// The fields of a connection span, and of the span of the request that it's running (if any)
+struct Span {
+    client: u64,
+    peer: Option<SocketAddr>,
+    request: Option<RequestFields>,
+}
+
+#[derive(Debug)]
+struct RequestFields {
+    id: u64,
+    keyspace: Option<ObjectID>,
+    table: Option<ObjectID>,
+    started: Instant,
+    /// set when the request finishes
+    action: Option<&'static [u8]>,
+    /// set when the request finishes
+    duration: Option<Duration>,
+}
+
+/// Serve a connection within its span
+pub async fn in_connection<F: Future>(
+    client: u64,
+    peer: Option<SocketAddr>,
+    serve: F,
+) -> F::Output {
+    let span = Span {
+        client,
+        peer,
+        request: None,
+    };
+    SPAN.scope(RefCell::new(span), async move {
+        log::debug!("Connection opened");
+        let ret = serve.await;
+        log::debug!("Connection closed");
+        ret
+    })
+    .await
+}
+
+/// The span of a request, which ends when this is dropped
+pub struct RequestSpan(());
+
+impl RequestSpan {
+    /// Start the span of a request that's run on the given entity. This does nothing outside
+    /// a connection span
+    pub fn enter(request: u64, (keyspace, table): (Option<&ObjectID>, Option<&ObjectID>)) -> Self {
+        let _ = SPAN.try_with(|span| {
+            span.borrow_mut().request = Some(RequestFields {
+                id: request,
+                keyspace: keyspace.cloned(),
+                table: table.cloned(),
+                started: Instant::now(),
+                action: None,
+                duration: None,
+            });
+        });
+        Self(())
+    }
+    /// End the span with a record for the finished request (the action is the last one that
+    /// it ran, or `None` if it didn't get as far as running one)
+    pub fn finish(self, action: Option<&'static [u8]>) {
+        if !log::log_enabled!(log::Level::Debug) {
+            return;
+        }
+        let _ = SPAN.try_with(|span| {
+            if let Some(request) = span.borrow_mut().request.as_mut() {
+                request.action = action;
+                request.duration = Some(request.started.elapsed());
+            }
+        });
+        log::debug!("Request finished");
+    }
+}
+
+impl Drop for RequestSpan {
+    fn drop(&mut self) {
+        let _ = SPAN.try_with(|span| span.borrow_mut().request = None);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The value of a span field
+enum Field {
+    Int(u64),
+    Str(String),
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(int) => write!(f, "{int}"),
+            Self::Str(string) => f.write_str(string),
+        }
+    }
+}
+
+/// Returns the fields of the spans that the current task is in, as pairs of names and values
+fn span_fields() -> Vec<(&'static str, Field)> {
+    SPAN.try_with(|span| {
+        let span = span.borrow();
+        let mut fields = vec![("client", Field::Int(span.client))];
+        if let Some(peer) = span.peer {
+            fields.push(("peer", Field::Str(peer.to_string())));
+        }
+        if let Some(request) = &span.request {
+            fields.push(("request", Field::Int(request.id)));
+            let mut entity = String::new();
+            if let Some(keyspace) = &request.keyspace {
+                entity.push_str(&String::from_utf8_lossy(keyspace));
+                if let Some(table) = &request.table {
+                    entity.push('.');
+                    entity.push_str(&String::from_utf8_lossy(table));
+                }
+            }
+            fields.push(("entity", Field::Str(entity)));
+            if let Some(action) = request.action {
+                let action = String::from_utf8_lossy(action).into_owned();
+                fields.push(("action", Field::Str(action)));
+            }
+            if let Some(duration) = request.duration {
+                fields.push(("duration_us", Field::Int(duration.as_micros() as u64)));
+            }
+        }
+        fields
+    })
+    .unwrap_or_default()
+}
+
+/// Write a record in the current format
+fn format(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let fields = self::span_fields();
+    if JSON.load(Ordering::Relaxed) {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        writeln!(buf, "{}", self::json_line(&timestamp, record, &fields))
+    } else {
+        let timestamp = buf.timestamp();
+        let level = buf.default_styled_level(record.level());
+        write!(
+            buf,
+            "[{timestamp} {level:<5} {}] {}",
+            record.target(),
+            record.args()
+        )?;
+        for (name, value) in fields.iter() {
+            write!(buf, " {name}={value}")?;
+        }
+        writeln!(buf)
+    }
+}
+
+/// Returns a record as a JSON object
+fn json_line(timestamp: &str, record: &Record, fields: &[(&'static str, Field)]) -> String {
+    let mut line = String::from("{\"timestamp\":");
+    self::push_json_string(&mut line, timestamp);
+    line.push_str(",\"level\":");
+    self::push_json_string(&mut line, record.level().as_str());
+    line.push_str(",\"target\":");
+    self::push_json_string(&mut line, record.target());
+    line.push_str(",\"message\":");
+    self::push_json_string(&mut line, &record.args().to_string());
+    for (name, value) in fields.iter() {
+        let _ = write!(line, ",\"{name}\":");
+        match value {
+            Field::Int(int) => {
+                let _ = write!(line, "{int}");
+            }
+            Field::Str(string) => self::push_json_string(&mut line, string),
+        }
+    }
+    line.push('}');
+    line
+}
+
+/// Append a string to a JSON document, quoted and escaped
+fn push_json_string(line: &mut String, string: &str) {
+    line.push('"');
+    for ch in string.chars() {
+        match ch {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            ch if ch.is_control() => {
+                let _ = write!(line, "\\u{:04x}", ch as u32);
+            }
+            ch => line.push(ch),
+        }
+    }
+    line.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{in_connection, json_line, span_fields, Field, RequestSpan},
+        crate::corestore::memstore::ObjectID,
+        log::{Level, Record},
+    };
+
+    #[test]
+    fn json_record() {
+        let fields = [
+            ("client", Field::Int(7)),
+            ("entity", Field::Str("default.default".to_owned())),
+        ];
+        let line = json_line(
+            "2026-10-15T00:00:00.000Z",
+            &Record::builder()
+                .args(format_args!("say \"hi\"\n\x01"))
+                .level(Level::Info)
+                .target("skyd")
+                .build(),
+            &fields,
+        );
+        assert_eq!(
+            line,
+            "{\"timestamp\":\"2026-10-15T00:00:00.000Z\",\"level\":\"INFO\",\"target\":\"skyd\",\
+            \"message\":\"say \\\"hi\\\"\\n\\u0001\",\"client\":7,\"entity\":\"default.default\"}"
+        );
+    }
+
+    #[tokio::test]
+    async fn span_lifecycle() {
+        // no span outside a connection
+        assert!(span_fields().is_empty());
+        let peer = "127.0.0.1:2003".parse().unwrap();
+        in_connection(7, Some(peer), async {
+            assert_eq!(
+                span_fields(),
+                [
+                    ("client", Field::Int(7)),
+                    ("peer", Field::Str("127.0.0.1:2003".to_owned()))
+                ]
+            );
+            let keyspace = ObjectID::try_from_slice(b"default").unwrap();
+            let span = RequestSpan::enter(42, (Some(&keyspace), None));
+            assert_eq!(span_fields()[2], ("request", Field::Int(42)));
+            assert_eq!(
+                span_fields()[3],
+                ("entity", Field::Str("default".to_owned()))
+            );
+            drop(span);
+            assert_eq!(span_fields().len(), 2);
+        })
+        .await;
+    }
+}
@@ -36,10 +36,13 @@
 //! the modules for their respective documentation.
 
 use {
-    crate::{config::ConfigurationSet, diskstore::flock::FileLock, util::exit_error},
-    env_logger::Builder,
+    crate::{
+        config::{ConfigurationSet, Restore},
+        diskstore::flock::FileLock,
+        util::exit_error,
+    },
     libsky::{URL, VERSION},
-    std::{env, process},
+    std::process,
 };
 
 #[macro_use]
@@ -47,16 +50,24 @@ pub mod util;
 mod actions;
 mod admin;
 mod arbiter;
+mod audit;
 mod auth;
 mod blueql;
+mod cdc;
+mod cluster;
 mod config;
 mod corestore;
 mod dbnet;
 mod diskstore;
+mod health;
 mod kvengine;
+mod logging;
+mod memory;
+mod metrics;
 mod protocol;
 mod queryengine;
 pub mod registry;
+mod replication;
 mod services;
 mod storage;
 #[cfg(test)]
@@ -89,9 +100,7 @@ const TEXT: &str = "
 type IoResult<T> = std::io::Result<T>;
 
 fn main() {
-    Builder::new()
-        .parse_filters(&env::var("SKY_LOG").unwrap_or_else(|_| "info".to_owned()))
-        .init();
+    logging::init();
     // Start the server which asynchronously waits for a CTRL+C signal
     // which will safely shut down the server
     let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -99,12 +108,12 @@ fn main() {
         .enable_all()
         .build()
         .unwrap();
-    let (cfg, restore_file) = check_args_and_get_cfg();
+    let (cfg, restore) = check_args_and_get_cfg();
     // check if any other process is using the data directory and lock it if not (else error)
     // important: create the pid_file just here and nowhere else because check_args can also
     // involve passing --help or wrong arguments which can falsely create a PID file
     let pid_file = run_pre_startup_tasks();
-    let db = runtime.block_on(async move { arbiter::run(cfg, restore_file).await });
+    let db = runtime.block_on(async move { arbiter::run(cfg, restore).await });
     // Make sure all background workers terminate
     drop(runtime);
     let db = match db {
@@ -127,7 +136,7 @@ fn main() {
 
 /// This function checks the command line arguments and either returns a config object
 /// or prints an error to `stderr` and terminates the server
-fn check_args_and_get_cfg() -> (ConfigurationSet, Option<String>) {
+fn check_args_and_get_cfg() -> (ConfigurationSet, Option<Restore>) {
     match config::get_config() {
         Ok(cfg) => {
             if cfg.is_artful() {
@@ -0,0 +1,235 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Memory accounting
+//!
+//! The memory reporter (see [`crate::services::memreport`]) walks all the tables every second
+//! and records the approximate number of bytes used by the data in each of them. Walking the
+//! tables is far too slow to do for every write, so everything here works off the last report:
+//! the usage is exported by the metrics (and `SYS METRIC MEMORY`) and it's compared against the
+//! memory limit (`memory.maxmemory`). The small object arena never gives its chunks back to the
+//! allocator (see [`crate::corestore::arena`]), so the part of them that isn't holding any keys
+//! or values is counted as used too: deleting small entries doesn't bring the usage down until
+//! new small entries take their place.
+//!
+//! While the usage is over the limit, the guard turns away the writes that can grow the data
+//! with `110 out-of-memory` (unless the policy is `warn`, in which case it's only logged).
+//! Reads and the writes that can only shrink the data (like `DEL` and `FLUSHDB`) still go
+//! through, so that memory can be freed. The limit is a soft one: the usage is only as fresh
+//! as the last report, so the writes that are run between two reports can overshoot it
+
+use {
+    crate::{
+        actions::ActionResult,
+        auth::acl,
+        config::{MemoryConfig, MemoryPolicy},
+        corestore::memstore::ObjectID,
+        protocol::interface::ProtocolSpec,
+        util,
+    },
+    core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    parking_lot::{const_rwlock, RwLock},
+};
+
+/// Writes that can't grow the data: they remove data, move it around or leave it alone. The
+/// actions queued in a transaction are checked when they're queued, so `EXEC` is let through
+const SHRINKING_ACTIONS: [&[u8]; 20] = [
+    b"DEL",
+    b"DELPREFIX",
+    b"SDEL",
+    b"FLUSHDB",
+    b"FLUSHTABLE",
+    b"POP",
+    b"GETDEL",
+    b"MPOP",
+    b"LPOP",
+    b"RPOP",
+    b"BLPOP",
+    b"BRPOP",
+    b"SREM",
+    b"HDEL",
+    b"EXPIRE",
+    b"PERSIST",
+    b"RENAME",
+    b"MOVE",
+    b"NOTIFY",
+    b"EXEC",
+];
+
+/// the limit, in bytes (zero if there's no limit)
+static LIMIT: AtomicUsize = AtomicUsize::new(0);
+/// whether writes are rejected while the usage is over the limit (or only logged)
+static REJECT: AtomicBool = AtomicBool::new(true);
+/// the bytes used by the data, as of the last report
+static USED: AtomicUsize = AtomicUsize::new(0);
+/// whether the usage was over the limit, as of the last report
+static OVER: AtomicBool = AtomicBool::new(false);
+/// the number of writes that were rejected
+static REJECTED: AtomicU64 = AtomicU64::new(0);
+/// the usage of every table, as of the last report
+static TABLES: RwLock<Vec<TableUsage>> = const_rwlock(Vec::new());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The approximate memory used by the data in a table (and the number of keys that were
+/// evicted from it)
+pub struct TableUsage {
+    pub keyspace: ObjectID,
+    pub table: ObjectID,
+    pub bytes: usize,
+    pub evicted: u64,
+}
+
+impl TableUsage {
+    pub const fn new(keyspace: ObjectID, table: ObjectID, bytes: usize, evicted: u64) -> Self {
+        Self {
+            keyspace,
+            table,
+            bytes,
+            evicted,
+        }
+    }
+}
+
+/// Apply the memory limit (this is done on startup and whenever the configuration is reloaded)
+pub fn configure(cfg: MemoryConfig) {
+    LIMIT.store(cfg.maxmemory.unwrap_or(0), Ordering::Release);
+    REJECT.store(cfg.policy == MemoryPolicy::Reject, Ordering::Release);
+    self::update(USED.load(Ordering::Acquire));
+}
+
+/// Record a report of the usage of every table, along with the bytes that the small object
+/// arena holds on to without using them
+pub fn record(tables: Vec<TableUsage>, arena_idle: usize) {
+    let used = tables.iter().map(|table| table.bytes).sum::<usize>() + arena_idle;
+    *TABLES.write() = tables;
+    USED.store(used, Ordering::Release);
+    self::update(used);
+}
+
+/// Check the usage against the limit, logging it if that changed anything
+fn update(used: usize) {
+    let limit = LIMIT.load(Ordering::Acquire);
+    let over = limit != 0 && used > limit;
+    if OVER.swap(over, Ordering::AcqRel) == over {
+        return;
+    }
+    if !over {
+        log::info!("Memory usage ({used} bytes) is no longer over the limit");
+    } else if REJECT.load(Ordering::Acquire) {
+        log::warn!(
+            "Memory usage ({used} bytes) is over the limit ({limit} bytes). Writes will be rejected until memory is freed"
+        );
+    } else {
+        log::warn!("Memory usage ({used} bytes) is over the limit ({limit} bytes)");
+    }
+}
+
+/// Returns the approximate number of bytes used by the data (and held on to by the arena), as
+/// of the last report
+pub fn used() -> usize {
+    USED.load(Ordering::Acquire)
+}
+
+/// Returns the memory limit, if there is one
+pub fn limit() -> Option<usize> {
+    Some(LIMIT.load(Ordering::Acquire)).filter(|limit| *limit != 0)
+}
+
+/// Returns the number of writes that were rejected because the usage was over the limit
+pub fn rejected() -> u64 {
+    REJECTED.load(Ordering::Relaxed)
+}
+
+/// Returns the usage of every table, as of the last report
+pub fn tables() -> Vec<TableUsage> {
+    TABLES.read().clone()
+}
+
+/// Returns true if the writes that can grow the data are being rejected
+pub fn rejects_writes() -> bool {
+    OVER.load(Ordering::Acquire) && REJECT.load(Ordering::Acquire)
+}
+
+/// Reject the (uppercased) action if it can grow the data. Only call this when
+/// [`rejects_writes`] returns true
+pub fn check_write<P: ProtocolSpec>(action: &[u8]) -> ActionResult<()> {
+    if acl::writes(action) && !SHRINKING_ACTIONS.contains(&action) {
+        REJECTED.fetch_add(1, Ordering::Relaxed);
+        util::err(P::RSTRING_OUT_OF_MEMORY)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{check_write, configure, record, rejects_writes, used, TableUsage},
+        crate::{
+            config::{MemoryConfig, MemoryPolicy},
+            corestore::memstore::{DEFAULT, SYSTEM},
+            protocol::Skyhash2,
+        },
+    };
+
+    #[test]
+    fn guard() {
+        configure(MemoryConfig::new(Some(1024), MemoryPolicy::Reject));
+        record(
+            vec![
+                TableUsage::new(DEFAULT, DEFAULT, 1000, 0),
+                TableUsage::new(SYSTEM, DEFAULT, 100, 0),
+            ],
+            0,
+        );
+        assert_eq!(used(), 1100);
+        assert!(rejects_writes());
+        assert!(check_write::<Skyhash2>(b"SET").is_err());
+        assert!(check_write::<Skyhash2>(b"LPUSH").is_err());
+        // reads and deletes go through
+        assert!(check_write::<Skyhash2>(b"GET").is_ok());
+        assert!(check_write::<Skyhash2>(b"DEL").is_ok());
+        // warnings only
+        configure(MemoryConfig::new(Some(1024), MemoryPolicy::Warn));
+        assert!(!rejects_writes());
+        // back under the limit
+        configure(MemoryConfig::new(Some(1024), MemoryPolicy::Reject));
+        assert!(rejects_writes());
+        record(vec![TableUsage::new(DEFAULT, DEFAULT, 1000, 0)], 0);
+        assert!(!rejects_writes());
+        // the memory that the arena holds on to counts too
+        record(vec![TableUsage::new(DEFAULT, DEFAULT, 1000, 0)], 100);
+        assert_eq!(used(), 1100);
+        assert!(rejects_writes());
+        record(vec![TableUsage::new(DEFAULT, DEFAULT, 1000, 0)], 0);
+        assert!(!rejects_writes());
+        // over it again, until the limit is removed
+        record(vec![TableUsage::new(DEFAULT, DEFAULT, 4096, 0)], 0);
+        assert!(rejects_writes());
+        configure(MemoryConfig::default());
+        assert!(!rejects_writes());
+    }
+}
@@ -0,0 +1,576 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Metrics
+//!
+//! The metrics registry keeps the counters that describe what the server has been doing since
+//! it started:
+//! - the number of queries and their latencies, by action (BlueQL statements are counted as
+//! `BLUEQL`)
+//! - the latency percentiles of the most recent queries, by action
+//! - the connections (see [`crate::dbnet::admission`])
+//! - the approximate memory used by the data, by table, and the memory limit (see
+//! [`crate::memory`])
+//! - the BGSAVEs, their failures and how long they took
+//! - the tables that were quarantined (see [`crate::storage::v1::quarantine`])
+//!
+//! The metrics are rendered in the Prometheus text format, both by `SYS METRICS` and by the
+//! `/metrics` endpoint of the HTTP gateway (if it's enabled). The percentiles are returned by
+//! `SYS LATENCY`
+
+use {
+    crate::{
+        dbnet::admission,
+        memory::{self, TableUsage},
+        registry,
+        storage::v1::quarantine,
+    },
+    chrono::Utc,
+    core::{
+        fmt::Write,
+        sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+        time::Duration,
+    },
+    parking_lot::{const_rwlock, RwLock},
+    std::collections::BTreeMap,
+};
+
+/// The name that BlueQL statements are counted under
+pub const BLUEQL: &[u8] = b"BLUEQL";
+/// The upper bounds of the latency buckets, in microseconds
+const LATENCY_BUCKETS: [u64; 12] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 500_000, 1_000_000,
+];
+/// The number of the most recent queries that the percentiles of an action are taken over
+const WINDOW: usize = 1024;
+
+static QUERIES: RwLock<BTreeMap<&'static [u8], QueryStats>> = const_rwlock(BTreeMap::new());
+static BGSAVE_RUNS: AtomicU64 = AtomicU64::new(0);
+static BGSAVE_FAILURES: AtomicU64 = AtomicU64::new(0);
+/// the time spent on BGSAVEs, in microseconds
+static BGSAVE_TIME: AtomicU64 = AtomicU64::new(0);
+/// the time that the last BGSAVE took, in microseconds
+static BGSAVE_LAST_TIME: AtomicU64 = AtomicU64::new(0);
+/// when the last BGSAVE succeeded, as a UNIX timestamp (zero if none has)
+static BGSAVE_LAST_SUCCESS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Default)]
+/// A histogram of latencies
+struct Histogram {
+    /// the number of observations in each bucket (the last one is for the observations above
+    /// the largest bound)
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    /// the sum of the observations, in microseconds
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, micros: u64) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(micros, Ordering::Relaxed);
+    }
+    /// Returns the cumulative counts of the buckets (the last one is the total count)
+    fn cumulative(&self) -> [u64; LATENCY_BUCKETS.len() + 1] {
+        let mut counts = [0; LATENCY_BUCKETS.len() + 1];
+        let mut total = 0;
+        for (count, bucket) in counts.iter_mut().zip(self.buckets.iter()) {
+            total += bucket.load(Ordering::Relaxed);
+            *count = total;
+        }
+        counts
+    }
+}
+
+#[derive(Debug)]
+/// The latencies of the most recent queries. The samples are written to a ring without locking,
+/// so a read that races with the queries can see a few samples out of order (which doesn't
+/// matter for percentiles)
+struct Window {
+    samples: Box<[AtomicU64]>,
+    /// the number of samples ever written (the next one goes in at this, modulo the length)
+    written: AtomicUsize,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            samples: (0..WINDOW).map(|_| AtomicU64::new(0)).collect(),
+            written: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Window {
+    fn observe(&self, micros: u64) {
+        let slot = self.written.fetch_add(1, Ordering::Relaxed) % WINDOW;
+        self.samples[slot].store(micros, Ordering::Relaxed);
+    }
+    /// Returns the samples in the window, sorted
+    fn sorted(&self) -> Vec<u64> {
+        let count = self.written.load(Ordering::Relaxed).min(WINDOW);
+        let mut samples: Vec<u64> = self.samples[..count]
+            .iter()
+            .map(|sample| sample.load(Ordering::Relaxed))
+            .collect();
+        samples.sort_unstable();
+        samples
+    }
+}
+
+#[derive(Debug, Default)]
+/// What we know about the queries that ran an action
+struct QueryStats {
+    /// the latencies of all the queries
+    histogram: Histogram,
+    /// the latencies of the most recent queries
+    recent: Window,
+}
+
+impl QueryStats {
+    fn observe(&self, micros: u64) {
+        self.histogram.observe(micros);
+        self.recent.observe(micros);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The latency percentiles of the most recent queries that ran an action, in microseconds
+pub struct Latency {
+    /// the number of queries that the percentiles were taken over
+    pub samples: usize,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl Latency {
+    /// Returns the percentiles of the given (sorted) samples, or `None` if there aren't any
+    fn of(sorted: &[u64]) -> Option<Self> {
+        let max = *sorted.last()?;
+        // the nearest rank, so that a percentile is always one of the samples
+        let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100) - 1];
+        Some(Self {
+            samples: sorted.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max,
+        })
+    }
+}
+
+/// Returns the latency percentiles of the given (uppercased) action, or of every action that
+/// has been run if it's `None`
+pub fn latencies(action: Option<&[u8]>) -> Vec<(&'static [u8], Latency)> {
+    let queries = QUERIES.read();
+    queries
+        .iter()
+        .filter(|(name, _)| action.is_none_or(|action| **name == action))
+        .filter_map(|(name, stats)| Some((*name, Latency::of(&stats.recent.sorted())?)))
+        .collect()
+}
+
+/// Returns a duration in microseconds
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+/// Returns microseconds in seconds
+fn seconds(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// Record a query that ran the given action (or [`BLUEQL`]) and took `elapsed`
+pub fn record_query(action: &'static [u8], elapsed: Duration) {
+    let micros = self::micros(elapsed);
+    if let Some(stats) = QUERIES.read().get(action) {
+        stats.observe(micros);
+        return;
+    }
+    // the first query that ran this action
+    QUERIES.write().entry(action).or_default().observe(micros);
+}
+
+/// Record a BGSAVE that took `elapsed`
+pub fn record_bgsave(elapsed: Duration, okay: bool) {
+    let micros = self::micros(elapsed);
+    BGSAVE_RUNS.fetch_add(1, Ordering::Relaxed);
+    BGSAVE_TIME.fetch_add(micros, Ordering::Relaxed);
+    BGSAVE_LAST_TIME.store(micros, Ordering::Relaxed);
+    if okay {
+        BGSAVE_LAST_SUCCESS.store(Utc::now().timestamp() as u64, Ordering::Relaxed);
+    } else {
+        BGSAVE_FAILURES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Write the `HELP` and `TYPE` lines of a metric
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Write the query counts and latencies
+fn write_queries(out: &mut String, queries: &BTreeMap<&'static [u8], QueryStats>) {
+    let counts: Vec<(String, [u64; LATENCY_BUCKETS.len() + 1], u64)> = queries
+        .iter()
+        .map(|(action, stats)| {
+            (
+                String::from_utf8_lossy(action).into_owned(),
+                stats.histogram.cumulative(),
+                stats.histogram.sum.load(Ordering::Relaxed),
+            )
+        })
+        .collect();
+    self::write_header(
+        out,
+        "skytable_queries_total",
+        "counter",
+        "The number of queries run, by action",
+    );
+    for (action, buckets, _) in counts.iter() {
+        let total = buckets[LATENCY_BUCKETS.len()];
+        let _ = writeln!(out, "skytable_queries_total{{action=\"{action}\"}} {total}");
+    }
+    self::write_header(
+        out,
+        "skytable_query_duration_seconds",
+        "histogram",
+        "The time taken to run queries, by action",
+    );
+    for (action, buckets, sum) in counts.iter() {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(buckets.iter()) {
+            let _ = writeln!(
+                out,
+                "skytable_query_duration_seconds_bucket{{action=\"{action}\",le=\"{}\"}} {count}",
+                self::seconds(*bound)
+            );
+        }
+        let total = buckets[LATENCY_BUCKETS.len()];
+        let _ = writeln!(
+            out,
+            "skytable_query_duration_seconds_bucket{{action=\"{action}\",le=\"+Inf\"}} {total}\n\
+            skytable_query_duration_seconds_sum{{action=\"{action}\"}} {}\n\
+            skytable_query_duration_seconds_count{{action=\"{action}\"}} {total}",
+            self::seconds(*sum)
+        );
+    }
+}
+
+/// Write the memory used by every table
+fn write_memory(out: &mut String, tables: &[TableUsage]) {
+    self::write_header(
+        out,
+        "skytable_table_memory_bytes",
+        "gauge",
+        "The approximate number of bytes used by the data, by table",
+    );
+    for usage in tables {
+        let _ = writeln!(
+            out,
+            "skytable_table_memory_bytes{{keyspace=\"{}\",table=\"{}\"}} {}",
+            String::from_utf8_lossy(&usage.keyspace),
+            String::from_utf8_lossy(&usage.table),
+            usage.bytes
+        );
+    }
+    self::write_header(
+        out,
+        "skytable_evicted_keys_total",
+        "counter",
+        "The number of keys that were evicted to keep volatile tables within their memory cap, by table",
+    );
+    for usage in tables {
+        let _ = writeln!(
+            out,
+            "skytable_evicted_keys_total{{keyspace=\"{}\",table=\"{}\"}} {}",
+            String::from_utf8_lossy(&usage.keyspace),
+            String::from_utf8_lossy(&usage.table),
+            usage.evicted
+        );
+    }
+}
+
+/// Render the metrics in the Prometheus text format
+pub fn render() -> String {
+    let mut out = String::new();
+    self::write_queries(&mut out, &QUERIES.read());
+    let connections = admission::stats();
+    self::write_header(
+        &mut out,
+        "skytable_connections_active",
+        "gauge",
+        "The number of connections that are open",
+    );
+    let _ = writeln!(out, "skytable_connections_active {}", connections.active);
+    self::write_header(
+        &mut out,
+        "skytable_connections_accepted_total",
+        "counter",
+        "The number of connections accepted",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_connections_accepted_total {}",
+        connections.accepted
+    );
+    self::write_header(
+        &mut out,
+        "skytable_connections_rejected_total",
+        "counter",
+        "The number of connections turned away, by reason",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_connections_rejected_total{{reason=\"per-ip\"}} {}\n\
+        skytable_connections_rejected_total{{reason=\"rate\"}} {}",
+        connections.rejected_per_ip, connections.rejected_rate
+    );
+    self::write_header(
+        &mut out,
+        "skytable_memory_bytes",
+        "gauge",
+        "The approximate number of bytes used by the data and held by the small object arena",
+    );
+    let _ = writeln!(out, "skytable_memory_bytes {}", memory::used());
+    self::write_memory(&mut out, &memory::tables());
+    self::write_header(
+        &mut out,
+        "skytable_memory_limit_bytes",
+        "gauge",
+        "The most bytes that the data can use (zero if there's no limit)",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_memory_limit_bytes {}",
+        memory::limit().unwrap_or(0)
+    );
+    self::write_header(
+        &mut out,
+        "skytable_memory_rejected_writes_total",
+        "counter",
+        "The number of writes rejected because the memory usage was over the limit",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_memory_rejected_writes_total {}",
+        memory::rejected()
+    );
+    self::write_header(
+        &mut out,
+        "skytable_quarantined_tables",
+        "gauge",
+        "The number of tables that were loaded empty since their files were corrupted",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_quarantined_tables {}",
+        quarantine::quarantined().len()
+    );
+    self::write_header(
+        &mut out,
+        "skytable_bgsave_total",
+        "counter",
+        "The number of BGSAVEs run",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_bgsave_total {}",
+        BGSAVE_RUNS.load(Ordering::Relaxed)
+    );
+    self::write_header(
+        &mut out,
+        "skytable_bgsave_failures_total",
+        "counter",
+        "The number of BGSAVEs that failed",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_bgsave_failures_total {}",
+        BGSAVE_FAILURES.load(Ordering::Relaxed)
+    );
+    self::write_header(
+        &mut out,
+        "skytable_bgsave_duration_seconds_total",
+        "counter",
+        "The time spent on BGSAVEs",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_bgsave_duration_seconds_total {}",
+        self::seconds(BGSAVE_TIME.load(Ordering::Relaxed))
+    );
+    self::write_header(
+        &mut out,
+        "skytable_bgsave_last_duration_seconds",
+        "gauge",
+        "The time that the last BGSAVE took",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_bgsave_last_duration_seconds {}",
+        self::seconds(BGSAVE_LAST_TIME.load(Ordering::Relaxed))
+    );
+    self::write_header(
+        &mut out,
+        "skytable_bgsave_last_success_timestamp_seconds",
+        "gauge",
+        "When the last BGSAVE succeeded (zero if none has)",
+    );
+    let _ = writeln!(
+        out,
+        "skytable_bgsave_last_success_timestamp_seconds {}",
+        BGSAVE_LAST_SUCCESS.load(Ordering::Relaxed)
+    );
+    self::write_header(
+        &mut out,
+        "skytable_healthy",
+        "gauge",
+        "Whether writes are going through (one) or not because of a storage failure (zero)",
+    );
+    let _ = writeln!(out, "skytable_healthy {}", registry::state_okay() as u8);
+    self::write_header(
+        &mut out,
+        "skytable_read_only",
+        "gauge",
+        "Whether the server is read-only (one) or not (zero)",
+    );
+    let _ = writeln!(out, "skytable_read_only {}", registry::is_read_only() as u8);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            write_memory, write_queries, Histogram, Latency, QueryStats, Window, LATENCY_BUCKETS,
+            WINDOW,
+        },
+        crate::{
+            corestore::memstore::{DEFAULT, SYSTEM},
+            memory::TableUsage,
+        },
+        std::collections::BTreeMap,
+    };
+
+    #[test]
+    fn histogram_buckets() {
+        let histogram = Histogram::default();
+        histogram.observe(50);
+        histogram.observe(100);
+        histogram.observe(101);
+        histogram.observe(10_000_000);
+        let counts = histogram.cumulative();
+        assert_eq!(counts[0], 2);
+        assert_eq!(counts[1], 3);
+        assert_eq!(counts[LATENCY_BUCKETS.len() - 1], 3);
+        assert_eq!(counts[LATENCY_BUCKETS.len()], 4);
+    }
+
+    #[test]
+    fn render_queries() {
+        let mut queries = BTreeMap::new();
+        let get = QueryStats::default();
+        get.observe(300);
+        get.observe(2_000_000);
+        queries.insert(&b"GET"[..], get);
+        let mut out = String::new();
+        write_queries(&mut out, &queries);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"skytable_queries_total{action=\"GET\"} 2"));
+        assert!(lines
+            .contains(&"skytable_query_duration_seconds_bucket{action=\"GET\",le=\"0.00025\"} 0"));
+        assert!(lines
+            .contains(&"skytable_query_duration_seconds_bucket{action=\"GET\",le=\"0.0005\"} 1"));
+        assert!(
+            lines.contains(&"skytable_query_duration_seconds_bucket{action=\"GET\",le=\"1\"} 1")
+        );
+        assert!(
+            lines.contains(&"skytable_query_duration_seconds_bucket{action=\"GET\",le=\"+Inf\"} 2")
+        );
+        assert!(lines.contains(&"skytable_query_duration_seconds_sum{action=\"GET\"} 2.0003"));
+        assert!(lines.contains(&"skytable_query_duration_seconds_count{action=\"GET\"} 2"));
+    }
+
+    #[test]
+    fn render_memory() {
+        let mut out = String::new();
+        write_memory(
+            &mut out,
+            &[
+                TableUsage::new(DEFAULT, DEFAULT, 1024, 16),
+                TableUsage::new(SYSTEM, DEFAULT, 0, 0),
+            ],
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"# TYPE skytable_table_memory_bytes gauge"));
+        assert!(lines
+            .contains(&"skytable_table_memory_bytes{keyspace=\"default\",table=\"default\"} 1024"));
+        assert!(
+            lines.contains(&"skytable_table_memory_bytes{keyspace=\"system\",table=\"default\"} 0")
+        );
+        assert!(lines.contains(&"# TYPE skytable_evicted_keys_total counter"));
+        assert!(lines
+            .contains(&"skytable_evicted_keys_total{keyspace=\"default\",table=\"default\"} 16"));
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        assert_eq!(Latency::of(&[]), None);
+        let samples: Vec<u64> = (1..=200).collect();
+        assert_eq!(
+            Latency::of(&samples),
+            Some(Latency {
+                samples: 200,
+                p50: 100,
+                p90: 180,
+                p99: 198,
+                max: 200
+            })
+        );
+        let one = Latency::of(&[42]).unwrap();
+        assert_eq!((one.p50, one.p99, one.max), (42, 42, 42));
+    }
+
+    #[test]
+    fn window_keeps_recent() {
+        let window = Window::default();
+        window.observe(7);
+        window.observe(3);
+        assert_eq!(window.sorted(), vec![3, 7]);
+        // the oldest samples are overwritten
+        for micros in 0..WINDOW as u64 {
+            window.observe(1000 + micros);
+        }
+        let sorted = window.sorted();
+        assert_eq!(sorted.len(), WINDOW);
+        assert_eq!(sorted[0], 1000);
+    }
+}
@@ -25,7 +25,7 @@
 */
 
 use {
-    super::ParseError,
+    super::{ParseError, QueryLimits},
     crate::{
         corestore::booltable::{BytesBoolTable, BytesNicheLUT},
         dbnet::QueryWithAdvance,
@@ -69,6 +69,8 @@ pub trait ProtocolSpec: Send + Sync {
     const SIMPLE_QUERY_HEADER: &'static [u8];
     /// The header for pipelined queries (excluding length, obviously)
     const PIPELINED_QUERY_FIRST_BYTE: u8;
+    /// The header for push frames (pub/sub messages sent outside of a response)
+    const PUSH_FRAME_HEADER: &'static [u8];
 
     // typed array
     /// Null element represenation for a typed array
@@ -98,7 +100,7 @@ pub trait ProtocolSpec: Send + Sync {
     /// Respcode 10: Encoding error
     const RCODE_ENCODING_ERROR: &'static [u8];
 
-    // respstrings
+    // respstrings (these carry an error code; see [`crate::protocol::responses`])
     /// Respstring when snapshot engine is busy
     const RSTRING_SNAPSHOT_BUSY: &'static [u8];
     /// Respstring when snapshots are disabled
@@ -141,6 +143,8 @@ pub trait ProtocolSpec: Send + Sync {
     const RSTRING_UNKNOWN_INSPECT_QUERY: &'static [u8];
     /// Respstring when an unknown table property is passed during table creation
     const RSTRING_UNKNOWN_PROPERTY: &'static [u8];
+    /// Respstring when an unknown metric is requested
+    const RSTRING_UNKNOWN_METRIC: &'static [u8];
     /// Respstring when a non-empty keyspace is attempted to be dropped
     const RSTRING_KEYSPACE_NOT_EMPTY: &'static [u8];
     /// Respstring when a bad type is provided for a key in the K/V engine (like using a `list`
@@ -150,6 +154,69 @@ pub trait ProtocolSpec: Send + Sync {
     const RSTRING_LISTMAP_BAD_INDEX: &'static [u8];
     /// Respstring when a list is empty and we attempt to access/modify it
     const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8];
+    /// Respstring when the TTL is requested for a key that has no expiry
+    const RSTRING_NO_EXPIRY: &'static [u8];
+    /// Respstring when a transaction is started while another one is in progress
+    const RSTRING_TXN_ALREADY_ACTIVE: &'static [u8];
+    /// Respstring when a transaction is committed/discarded without starting one
+    const RSTRING_TXN_NOT_ACTIVE: &'static [u8];
+    /// Respstring when a transaction was rolled back because one of its actions failed
+    const RSTRING_TXN_ABORTED: &'static [u8];
+    /// Respstring when an action that can't be used in a transaction is queued
+    const RSTRING_TXN_UNSUPPORTED_ACTION: &'static [u8];
+    /// Respstring when incrementing a counter would overflow it
+    const RSTRING_COUNTER_OVERFLOW: &'static [u8];
+    /// Respstring when decrementing a counter would take it below zero
+    const RSTRING_COUNTER_UNDERFLOW: &'static [u8];
+    /// Respstring when a compare-and-swap fails because the current value didn't match
+    const RSTRING_CAS_MISMATCH: &'static [u8];
+    /// Respstring when an offset is past the end of a value
+    const RSTRING_OUT_OF_RANGE: &'static [u8];
+    /// Respstring when a script that wasn't loaded is run
+    const RSTRING_SCRIPT_NOT_FOUND: &'static [u8];
+    /// Respstring when a script fails to compile
+    const RSTRING_BAD_SCRIPT: &'static [u8];
+    /// Respstring when a script is loaded while the script cache is full
+    const RSTRING_SCRIPT_CACHE_FULL: &'static [u8];
+    /// Respstring when a snapshot is read from or ended without starting one
+    const RSTRING_NO_SNAPSHOT: &'static [u8];
+    /// Respstring when a key or value is larger than the size limit of the table
+    const RSTRING_TOO_LARGE: &'static [u8];
+    /// Respstring when a user has run through their query budget
+    const RSTRING_THROTTLED: &'static [u8];
+    /// Respstring when the TLS certificates are reloaded on a server that doesn't use TLS
+    const RSTRING_TLS_DISABLED: &'static [u8];
+    /// Respstring when a write is run while the server is read-only
+    const RSTRING_READ_ONLY: &'static [u8];
+    /// Respstring when the configuration is reloaded on a server that wasn't started with a
+    /// configuration file
+    const RSTRING_NO_CONFIG_FILE: &'static [u8];
+    /// Respstring when the configuration file can't be reloaded because it has errors
+    const RSTRING_BAD_CONFIG: &'static [u8];
+    /// Respstring when a write is run while the memory usage is over the limit
+    const RSTRING_OUT_OF_MEMORY: &'static [u8];
+    /// Respstring when a compaction is requested while one is already running
+    const RSTRING_COMPACTION_BUSY: &'static [u8];
+    /// Respstring when an export archive is malformed or can't be loaded by this server
+    const RSTRING_BAD_ARCHIVE: &'static [u8];
+    /// Respstring when a write is run on a replica
+    const RSTRING_READ_ONLY_REPLICA: &'static [u8];
+    /// Respstring when a failover couldn't be completed
+    const RSTRING_FAILOVER_FAILED: &'static [u8];
+    /// Respstring when the keys of a query are owned by more than one cluster node
+    const RSTRING_CROSS_SLOT: &'static [u8];
+    /// Respstring when a key's slot isn't owned by any cluster node
+    const RSTRING_CLUSTER_DOWN: &'static [u8];
+    /// Respstring when a cluster action is run while cluster mode is off
+    const RSTRING_CLUSTER_DISABLED: &'static [u8];
+    /// Respstring when slots couldn't be migrated to another cluster node
+    const RSTRING_MIGRATION_FAILED: &'static [u8];
+    /// Respstring when a backup of the whole cluster couldn't be taken
+    const RSTRING_BACKUP_FAILED: &'static [u8];
+    /// Respstring when change data capture is needed but it isn't enabled
+    const RSTRING_CDC_DISABLED: &'static [u8];
+    /// Respstring when an export path is absolute or leaves the export directory
+    const RSTRING_BAD_EXPORT_PATH: &'static [u8];
 
     // element responses
     /// A string element containing the text "HEY!"
@@ -160,6 +227,8 @@ pub trait ProtocolSpec: Send + Sync {
     const FULLRESP_RCODE_PACKET_ERR: &'static [u8];
     /// A **full response** for a wrongtype error
     const FULLRESP_RCODE_WRONG_TYPE: &'static [u8];
+    /// A **full response** for a query that exceeds the query limits
+    const FULLRESP_QUERY_TOO_LARGE: &'static [u8];
 
     // LUTs
     /// A LUT for SET operations
@@ -183,11 +252,15 @@ pub trait ProtocolSpec: Send + Sync {
         Self::RCODE_OKAY,
         Self::RCODE_NIL,
     );
-    const SKYHASH_PARSE_ERROR_LUT: [&'static [u8]; 4] = [
+    /// A LUT for the per-element status of batched SET operations. These are written as
+    /// elements of a typed array and hence only carry the respcode
+    const BATCH_SET_NLUT: BytesNicheLUT = BytesNicheLUT::new(b"10", b"0", b"2");
+    const SKYHASH_PARSE_ERROR_LUT: [&'static [u8]; 5] = [
         Self::FULLRESP_RCODE_PACKET_ERR,
         Self::FULLRESP_RCODE_PACKET_ERR,
         Self::FULLRESP_RCODE_WRONG_TYPE,
         Self::FULLRESP_RCODE_WRONG_TYPE,
+        Self::FULLRESP_QUERY_TOO_LARGE,
     ];
 
     // auth error respstrings
@@ -217,6 +290,8 @@ pub trait ProtocolSpec: Send + Sync {
 
     /// The body is terminated by a linefeed
     const NEEDS_TERMINAL_LF: bool;
+    /// Error strings have a size line (between the type symbol and the payload)
+    const ERROR_STRING_HAS_SIZELINE: bool;
 
-    fn decode_packet(input: &[u8]) -> Result<QueryWithAdvance, ParseError>;
+    fn decode_packet(input: &[u8], limits: QueryLimits) -> Result<QueryWithAdvance, ParseError>;
 }
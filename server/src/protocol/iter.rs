@@ -139,6 +139,14 @@ impl<'a> AnyArrayIter<'a> {
         self.map_next(|v| String::from_utf8_lossy(v).to_string())
     }
     #[inline(always)]
+    /// Returns the last value without consuming it
+    pub fn peek_back(&self) -> Option<&'a [u8]> {
+        self.iter.as_slice().last().map(|v| unsafe {
+            // UNSAFE(@ohsayan): The ctor of `Self` allows us to "assume" this is safe
+            v.as_slice()
+        })
+    }
+    #[inline(always)]
     pub unsafe fn into_inner(self) -> Iter<'a, UnsafeSlice> {
         self.iter
     }
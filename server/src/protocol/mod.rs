@@ -33,6 +33,7 @@ use {
 // pub mods
 pub mod interface;
 pub mod iter;
+pub mod responses;
 // internal mods
 mod raw_parser;
 // versions
@@ -112,6 +113,63 @@ pub enum ParseError {
     DatatypeParseFailure = 3u8,
     /// The client supplied the wrong query data type for the given query
     WrongType = 4u8,
+    /// The query is larger than the [`QueryLimits`] allow (too many bytes or elements)
+    QueryTooLarge = 5u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// # Query limits
+///
+/// The limits that the decoder enforces on a single query (or pipeline) to protect the server
+/// from clients that send huge payloads. Since the sizes are checked as soon as they're read,
+/// a query exceeding the limits is rejected before we wait for (or allocate for) its data
+pub struct QueryLimits {
+    /// The maximum size of a query, in bytes
+    pub max_size: usize,
+    /// The maximum number of elements in a query (or queries in a pipeline)
+    pub max_args: usize,
+}
+
+impl QueryLimits {
+    /// The default maximum size of a query (128 MiB)
+    pub const DEFAULT_MAX_SIZE: usize = 128 * 1024 * 1024;
+    /// The default maximum number of elements in a query
+    pub const DEFAULT_MAX_ARGS: usize = 1024 * 1024;
+    pub const fn new(max_size: usize, max_args: usize) -> Self {
+        Self { max_size, max_args }
+    }
+    /// Returns an error if a query can't have `count` elements
+    fn check_args(&self, count: usize) -> ParseResult<()> {
+        if count > self.max_args {
+            Err(ParseError::QueryTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+    /// Returns an error if a query can't be `size` bytes long
+    fn check_size(&self, size: usize) -> ParseResult<()> {
+        if size > self.max_size {
+            Err(ParseError::QueryTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+    /// Map the result of a parse: running out of data is an error if the buffer already
+    /// holds more than a query can have
+    fn check_parse<T>(&self, buffered: usize, result: ParseResult<T>) -> ParseResult<T> {
+        match result {
+            Err(ParseError::NotEnough) if buffered > self.max_size => {
+                Err(ParseError::QueryTooLarge)
+            }
+            result => result,
+        }
+    }
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_SIZE, Self::DEFAULT_MAX_ARGS)
+    }
 }
 
 /// A generic result to indicate parsing errors thorugh the [`ParseError`] enum
@@ -0,0 +1,152 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Parameterized responses
+//!
+//! Most responses are pregenerated for every protocol version (see [`ProtocolSpec`]). The
+//! responses in this module carry parameters (like the name of an action) and hence are built
+//! when they're needed
+//!
+//! ## Error codes
+//!
+//! Error strings carry a numeric code followed by a message (`201 container-not-found`), so
+//! that clients can branch on the code instead of matching the message. The codes are grouped
+//! by the hundreds digit:
+//! - `1xx`: snapshots and the server state
+//! - `2xx`: keyspaces, tables and their properties
+//! - `3xx`: data errors (bad indices, overflows, sizes, ...)
+//! - `4xx`: transactions and scripts
+//! - `5xx`: authn/authz
+//! - `6xx`: BlueQL
+//! - `7xx`: malformed queries, protocol errors and limits
+//!
+//! The respcodes (`0` to `11`) and `Unknown action` predate the error codes and are left as is
+
+use {
+    super::interface::ProtocolSpec,
+    crate::{actions::ArityError, cluster::Moved},
+};
+
+/// Error code: a write was run while the server is read-only (see
+/// [`crate::registry::is_read_only`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_READ_ONLY`])
+pub const ERRCODE_READ_ONLY: u16 = 107;
+/// Error code: a write that can grow the data was run while the memory usage is over the
+/// limit (see [`crate::memory`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_OUT_OF_MEMORY`])
+pub const ERRCODE_OUT_OF_MEMORY: u16 = 110;
+/// Error code: a compaction was requested while one is already running (see
+/// [`crate::services::compaction`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_COMPACTION_BUSY`])
+pub const ERRCODE_COMPACTION_BUSY: u16 = 111;
+/// Error code: an export archive is malformed, or can't be loaded by this server (see
+/// [`crate::storage::v1::export`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_BAD_ARCHIVE`])
+pub const ERRCODE_BAD_ARCHIVE: u16 = 112;
+/// Error code: a write was run on a replica, which only takes the writes fed by its primary
+/// (see [`crate::replication::rejects_writes`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_READ_ONLY_REPLICA`])
+pub const ERRCODE_READ_ONLY_REPLICA: u16 = 113;
+/// Error code: a failover (or the promotion of a replica that was handed over to) couldn't be
+/// completed (see [`crate::replication::failover`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_FAILOVER_FAILED`])
+pub const ERRCODE_FAILOVER_FAILED: u16 = 114;
+/// Error code: the key's slot is owned by another cluster node, which the query should be sent
+/// to (see [`crate::cluster`]). For example: `115 moved 3999 10.0.0.2:2003`
+pub const ERRCODE_MOVED: u16 = 115;
+/// Error code: the keys of a query are owned by more than one cluster node. The response is
+/// pregenerated ([`ProtocolSpec::RSTRING_CROSS_SLOT`])
+pub const ERRCODE_CROSS_SLOT: u16 = 116;
+/// Error code: the key's slot isn't owned by any cluster node. The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_CLUSTER_DOWN`])
+pub const ERRCODE_CLUSTER_DOWN: u16 = 117;
+/// Error code: a cluster action was run while cluster mode is off. The response is
+/// pregenerated ([`ProtocolSpec::RSTRING_CLUSTER_DISABLED`])
+pub const ERRCODE_CLUSTER_DISABLED: u16 = 118;
+/// Error code: slots couldn't be migrated to another cluster node (see
+/// [`crate::cluster::migration`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_MIGRATION_FAILED`])
+pub const ERRCODE_MIGRATION_FAILED: u16 = 119;
+/// Error code: a backup of the whole cluster couldn't be taken (see
+/// [`crate::cluster::backup`]). The response is pregenerated
+/// ([`ProtocolSpec::RSTRING_BACKUP_FAILED`])
+pub const ERRCODE_BACKUP_FAILED: u16 = 120;
+/// Error code: change data capture is needed but it isn't enabled (see [`crate::cdc`]). The
+/// response is pregenerated ([`ProtocolSpec::RSTRING_CDC_DISABLED`])
+pub const ERRCODE_CDC_DISABLED: u16 = 121;
+/// Error code: a path given to `SYS EXPORT` or `SYS IMPORT` is absolute or leaves the export
+/// directory. The response is pregenerated ([`ProtocolSpec::RSTRING_BAD_EXPORT_PATH`])
+pub const ERRCODE_BAD_EXPORT_PATH: u16 = 122;
+/// Error code: the action was run with the wrong number of arguments
+pub const ERRCODE_ARITY: u16 = 700;
+/// Error code: the client asked for a protocol version that isn't supported
+pub const ERRCODE_UNSUPPORTED_PROTOCOL: u16 = 701;
+/// Error code: the query exceeded the query limits (see [`super::QueryLimits`]). The response
+/// is pregenerated ([`ProtocolSpec::FULLRESP_QUERY_TOO_LARGE`])
+pub const ERRCODE_QUERY_TOO_LARGE: u16 = 702;
+/// Error code: the user ran through their query budget (see [`crate::auth::ratelimit`]). The
+/// response is pregenerated ([`ProtocolSpec::RSTRING_THROTTLED`])
+pub const ERRCODE_THROTTLED: u16 = 703;
+
+/// Build an error string response with the given payload
+pub fn error_string<P: ProtocolSpec>(payload: &str) -> Vec<u8> {
+    let mut resp = Vec::with_capacity(payload.len() + 24);
+    resp.push(b'!');
+    if P::ERROR_STRING_HAS_SIZELINE {
+        resp.extend_from_slice(payload.len().to_string().as_bytes());
+        resp.push(P::LF);
+    }
+    resp.extend_from_slice(payload.as_bytes());
+    resp.push(P::LF);
+    resp
+}
+
+/// Build an error string response with the given error code and message
+pub fn structured_error<P: ProtocolSpec>(code: u16, message: &str) -> Vec<u8> {
+    error_string::<P>(&format!("{code} {message}"))
+}
+
+/// Build the response that redirects a query to the cluster node that owns its key
+pub fn moved<P: ProtocolSpec>(moved: &Moved) -> Vec<u8> {
+    structured_error::<P>(
+        ERRCODE_MOVED,
+        &format!("moved {} {}", moved.slot(), moved.node()),
+    )
+}
+
+/// Build the response for an arity error. For example: `700 arity-error: GET expects 1
+/// argument, got 2`
+pub fn arity_error<P: ProtocolSpec>(e: &ArityError) -> Vec<u8> {
+    let payload = match e.action() {
+        Some(action) => format!(
+            "arity-error: {action} expects {}, got {}",
+            e.expected(),
+            e.got()
+        ),
+        None => format!("arity-error: expected {}, got {}", e.expected(), e.got()),
+    };
+    structured_error::<P>(ERRCODE_ARITY, &payload)
+}
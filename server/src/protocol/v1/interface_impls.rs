@@ -27,7 +27,7 @@
 use {
     crate::{
         dbnet::QueryWithAdvance,
-        protocol::{interface::ProtocolSpec, ParseError, Skyhash1},
+        protocol::{interface::ProtocolSpec, ParseError, QueryLimits, Skyhash1},
     },
     ::sky_macros::compiled_eresp_bytes_v1 as eresp,
 };
@@ -53,6 +53,7 @@ impl ProtocolSpec for Skyhash1 {
     // metaframe
     const SIMPLE_QUERY_HEADER: &'static [u8] = b"*1\n";
     const PIPELINED_QUERY_FIRST_BYTE: u8 = b'$';
+    const PUSH_FRAME_HEADER: &'static [u8] = b">1\n";
 
     // respcodes
     const RCODE_OKAY: &'static [u8] = eresp!("0");
@@ -68,33 +69,65 @@ impl ProtocolSpec for Skyhash1 {
     const RCODE_ENCODING_ERROR: &'static [u8] = eresp!("9");
 
     // respstrings
-    const RSTRING_SNAPSHOT_BUSY: &'static [u8] = eresp!("err-snapshot-busy");
-    const RSTRING_SNAPSHOT_DISABLED: &'static [u8] = eresp!("err-snapshot-disabled");
-    const RSTRING_SNAPSHOT_DUPLICATE: &'static [u8] = eresp!("duplicate-snapshot");
-    const RSTRING_SNAPSHOT_ILLEGAL_NAME: &'static [u8] = eresp!("err-invalid-snapshot-name");
-    const RSTRING_ERR_ACCESS_AFTER_TERMSIG: &'static [u8] = eresp!("err-access-after-termsig");
+    const RSTRING_SNAPSHOT_BUSY: &'static [u8] = eresp!(100, "err-snapshot-busy");
+    const RSTRING_SNAPSHOT_DISABLED: &'static [u8] = eresp!(101, "err-snapshot-disabled");
+    const RSTRING_SNAPSHOT_DUPLICATE: &'static [u8] = eresp!(102, "duplicate-snapshot");
+    const RSTRING_SNAPSHOT_ILLEGAL_NAME: &'static [u8] = eresp!(103, "err-invalid-snapshot-name");
+    const RSTRING_ERR_ACCESS_AFTER_TERMSIG: &'static [u8] = eresp!(104, "err-access-after-termsig");
 
     // keyspace related resps
-    const RSTRING_DEFAULT_UNSET: &'static [u8] = eresp!("default-container-unset");
-    const RSTRING_CONTAINER_NOT_FOUND: &'static [u8] = eresp!("container-not-found");
-    const RSTRING_STILL_IN_USE: &'static [u8] = eresp!("still-in-use");
-    const RSTRING_PROTECTED_OBJECT: &'static [u8] = eresp!("err-protected-object");
-    const RSTRING_WRONG_MODEL: &'static [u8] = eresp!("wrong-model");
-    const RSTRING_ALREADY_EXISTS: &'static [u8] = eresp!("err-already-exists");
-    const RSTRING_NOT_READY: &'static [u8] = eresp!("not-ready");
-    const RSTRING_DDL_TRANSACTIONAL_FAILURE: &'static [u8] = eresp!("transactional-failure");
-    const RSTRING_UNKNOWN_DDL_QUERY: &'static [u8] = eresp!("unknown-ddl-query");
-    const RSTRING_BAD_EXPRESSION: &'static [u8] = eresp!("malformed-expression");
-    const RSTRING_UNKNOWN_MODEL: &'static [u8] = eresp!("unknown-model");
-    const RSTRING_TOO_MANY_ARGUMENTS: &'static [u8] = eresp!("too-many-args");
-    const RSTRING_CONTAINER_NAME_TOO_LONG: &'static [u8] = eresp!("container-name-too-long");
-    const RSTRING_BAD_CONTAINER_NAME: &'static [u8] = eresp!("bad-container-name");
-    const RSTRING_UNKNOWN_INSPECT_QUERY: &'static [u8] = eresp!("unknown-inspect-query");
-    const RSTRING_UNKNOWN_PROPERTY: &'static [u8] = eresp!("unknown-property");
-    const RSTRING_KEYSPACE_NOT_EMPTY: &'static [u8] = eresp!("keyspace-not-empty");
-    const RSTRING_BAD_TYPE_FOR_KEY: &'static [u8] = eresp!("bad-type-for-key");
-    const RSTRING_LISTMAP_BAD_INDEX: &'static [u8] = eresp!("bad-list-index");
-    const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8] = eresp!("list-is-empty");
+    const RSTRING_DEFAULT_UNSET: &'static [u8] = eresp!(200, "default-container-unset");
+    const RSTRING_CONTAINER_NOT_FOUND: &'static [u8] = eresp!(201, "container-not-found");
+    const RSTRING_STILL_IN_USE: &'static [u8] = eresp!(202, "still-in-use");
+    const RSTRING_PROTECTED_OBJECT: &'static [u8] = eresp!(203, "err-protected-object");
+    const RSTRING_WRONG_MODEL: &'static [u8] = eresp!(204, "wrong-model");
+    const RSTRING_ALREADY_EXISTS: &'static [u8] = eresp!(205, "err-already-exists");
+    const RSTRING_NOT_READY: &'static [u8] = eresp!(206, "not-ready");
+    const RSTRING_DDL_TRANSACTIONAL_FAILURE: &'static [u8] = eresp!(207, "transactional-failure");
+    const RSTRING_UNKNOWN_DDL_QUERY: &'static [u8] = eresp!(208, "unknown-ddl-query");
+    const RSTRING_BAD_EXPRESSION: &'static [u8] = eresp!(209, "malformed-expression");
+    const RSTRING_UNKNOWN_MODEL: &'static [u8] = eresp!(210, "unknown-model");
+    const RSTRING_TOO_MANY_ARGUMENTS: &'static [u8] = eresp!(211, "too-many-args");
+    const RSTRING_CONTAINER_NAME_TOO_LONG: &'static [u8] = eresp!(212, "container-name-too-long");
+    const RSTRING_BAD_CONTAINER_NAME: &'static [u8] = eresp!(213, "bad-container-name");
+    const RSTRING_UNKNOWN_INSPECT_QUERY: &'static [u8] = eresp!(214, "unknown-inspect-query");
+    const RSTRING_UNKNOWN_PROPERTY: &'static [u8] = eresp!(215, "unknown-property");
+    const RSTRING_UNKNOWN_METRIC: &'static [u8] = eresp!(218, "unknown-metric");
+    const RSTRING_KEYSPACE_NOT_EMPTY: &'static [u8] = eresp!(216, "keyspace-not-empty");
+    const RSTRING_BAD_TYPE_FOR_KEY: &'static [u8] = eresp!(217, "bad-type-for-key");
+    const RSTRING_LISTMAP_BAD_INDEX: &'static [u8] = eresp!(300, "bad-list-index");
+    const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8] = eresp!(301, "list-is-empty");
+    const RSTRING_NO_EXPIRY: &'static [u8] = eresp!(302, "no-expiry");
+    const RSTRING_TXN_ALREADY_ACTIVE: &'static [u8] = eresp!(400, "txn-already-active");
+    const RSTRING_TXN_NOT_ACTIVE: &'static [u8] = eresp!(401, "txn-not-active");
+    const RSTRING_TXN_ABORTED: &'static [u8] = eresp!(402, "txn-aborted");
+    const RSTRING_TXN_UNSUPPORTED_ACTION: &'static [u8] = eresp!(403, "txn-unsupported-action");
+    const RSTRING_COUNTER_OVERFLOW: &'static [u8] = eresp!(303, "counter-overflow");
+    const RSTRING_COUNTER_UNDERFLOW: &'static [u8] = eresp!(304, "counter-underflow");
+    const RSTRING_CAS_MISMATCH: &'static [u8] = eresp!(305, "cas-mismatch");
+    const RSTRING_OUT_OF_RANGE: &'static [u8] = eresp!(307, "out-of-range");
+    const RSTRING_SCRIPT_NOT_FOUND: &'static [u8] = eresp!(410, "script-not-found");
+    const RSTRING_BAD_SCRIPT: &'static [u8] = eresp!(411, "bad-script");
+    const RSTRING_SCRIPT_CACHE_FULL: &'static [u8] = eresp!(412, "script-cache-full");
+    const RSTRING_NO_SNAPSHOT: &'static [u8] = eresp!(105, "no-snapshot");
+    const RSTRING_TOO_LARGE: &'static [u8] = eresp!(308, "too-large");
+    const RSTRING_THROTTLED: &'static [u8] = eresp!(703, "throttled");
+    const RSTRING_TLS_DISABLED: &'static [u8] = eresp!(106, "err-tls-disabled");
+    const RSTRING_READ_ONLY: &'static [u8] = eresp!(107, "read-only");
+    const RSTRING_NO_CONFIG_FILE: &'static [u8] = eresp!(108, "no-config-file");
+    const RSTRING_BAD_CONFIG: &'static [u8] = eresp!(109, "bad-config");
+    const RSTRING_OUT_OF_MEMORY: &'static [u8] = eresp!(110, "out-of-memory");
+    const RSTRING_COMPACTION_BUSY: &'static [u8] = eresp!(111, "err-compaction-busy");
+    const RSTRING_BAD_ARCHIVE: &'static [u8] = eresp!(112, "bad-archive");
+    const RSTRING_READ_ONLY_REPLICA: &'static [u8] = eresp!(113, "read-only-replica");
+    const RSTRING_FAILOVER_FAILED: &'static [u8] = eresp!(114, "err-failover-failed");
+    const RSTRING_CROSS_SLOT: &'static [u8] = eresp!(116, "cross-slot");
+    const RSTRING_CLUSTER_DOWN: &'static [u8] = eresp!(117, "cluster-down");
+    const RSTRING_CLUSTER_DISABLED: &'static [u8] = eresp!(118, "err-cluster-disabled");
+    const RSTRING_MIGRATION_FAILED: &'static [u8] = eresp!(119, "err-migration-failed");
+    const RSTRING_BACKUP_FAILED: &'static [u8] = eresp!(120, "err-backup-failed");
+    const RSTRING_CDC_DISABLED: &'static [u8] = eresp!(121, "err-cdc-disabled");
+    const RSTRING_BAD_EXPORT_PATH: &'static [u8] = eresp!(122, "err-bad-export-path");
 
     // elements
     const ELEMRESP_HEYA: &'static [u8] = b"+4\nHEY!\n";
@@ -102,29 +135,31 @@ impl ProtocolSpec for Skyhash1 {
     // full responses
     const FULLRESP_RCODE_PACKET_ERR: &'static [u8] = b"*1\n!1\n4\n";
     const FULLRESP_RCODE_WRONG_TYPE: &'static [u8] = b"*1\n!1\n7\n";
+    const FULLRESP_QUERY_TOO_LARGE: &'static [u8] = b"*1\n!19\n702 query-too-large\n";
 
     // auth rcodes/strings
-    const AUTH_ERROR_ALREADYCLAIMED: &'static [u8] = eresp!("err-auth-already-claimed");
+    const AUTH_ERROR_ALREADYCLAIMED: &'static [u8] = eresp!(500, "err-auth-already-claimed");
     const AUTH_CODE_BAD_CREDENTIALS: &'static [u8] = eresp!("10");
-    const AUTH_ERROR_DISABLED: &'static [u8] = eresp!("err-auth-disabled");
+    const AUTH_ERROR_DISABLED: &'static [u8] = eresp!(501, "err-auth-disabled");
     const AUTH_CODE_PERMS: &'static [u8] = eresp!("11");
-    const AUTH_ERROR_ILLEGAL_USERNAME: &'static [u8] = eresp!("err-auth-illegal-username");
-    const AUTH_ERROR_FAILED_TO_DELETE_USER: &'static [u8] = eresp!("err-auth-deluser-fail");
+    const AUTH_ERROR_ILLEGAL_USERNAME: &'static [u8] = eresp!(502, "err-auth-illegal-username");
+    const AUTH_ERROR_FAILED_TO_DELETE_USER: &'static [u8] = eresp!(503, "err-auth-deluser-fail");
 
     // bql respstrings
-    const BQL_BAD_EXPRESSION: &'static [u8] = eresp!("bql-bad-expression");
-    const BQL_EXPECTED_STMT: &'static [u8] = eresp!("bql-expected-statement");
-    const BQL_INVALID_NUMERIC_LITERAL: &'static [u8] = eresp!("bql-bad-numeric-literal");
-    const BQL_INVALID_STRING_LITERAL: &'static [u8] = eresp!("bql-bad-string-literal");
-    const BQL_INVALID_SYNTAX: &'static [u8] = eresp!("bql-invalid-syntax");
-    const BQL_UNEXPECTED_EOF: &'static [u8] = eresp!("bql-unexpected-eof");
-    const BQL_UNKNOWN_CREATE_QUERY: &'static [u8] = eresp!("bql-unknown-create-query");
-    const BQL_UNSUPPORTED_MODEL_DECL: &'static [u8] = eresp!("bql-unsupported-model-decl");
-    const BQL_UNEXPECTED_CHAR: &'static [u8] = eresp!("bql-unexpected-char");
+    const BQL_BAD_EXPRESSION: &'static [u8] = eresp!(600, "bql-bad-expression");
+    const BQL_EXPECTED_STMT: &'static [u8] = eresp!(601, "bql-expected-statement");
+    const BQL_INVALID_NUMERIC_LITERAL: &'static [u8] = eresp!(602, "bql-bad-numeric-literal");
+    const BQL_INVALID_STRING_LITERAL: &'static [u8] = eresp!(603, "bql-bad-string-literal");
+    const BQL_INVALID_SYNTAX: &'static [u8] = eresp!(604, "bql-invalid-syntax");
+    const BQL_UNEXPECTED_EOF: &'static [u8] = eresp!(605, "bql-unexpected-eof");
+    const BQL_UNKNOWN_CREATE_QUERY: &'static [u8] = eresp!(606, "bql-unknown-create-query");
+    const BQL_UNSUPPORTED_MODEL_DECL: &'static [u8] = eresp!(607, "bql-unsupported-model-decl");
+    const BQL_UNEXPECTED_CHAR: &'static [u8] = eresp!(608, "bql-unexpected-char");
 
     const NEEDS_TERMINAL_LF: bool = true;
+    const ERROR_STRING_HAS_SIZELINE: bool = true;
 
-    fn decode_packet(input: &[u8]) -> Result<QueryWithAdvance, ParseError> {
-        Skyhash1::parse(input)
+    fn decode_packet(input: &[u8], limits: QueryLimits) -> Result<QueryWithAdvance, ParseError> {
+        Skyhash1::parse(input, limits)
     }
 }
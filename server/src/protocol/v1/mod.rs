@@ -27,7 +27,7 @@
 use {
     super::{
         raw_parser::{RawParser, RawParserExt, RawParserMeta},
-        ParseError, ParseResult, PipelinedQuery, Query, SimpleQuery, UnsafeSlice,
+        ParseError, ParseResult, PipelinedQuery, Query, QueryLimits, SimpleQuery, UnsafeSlice,
     },
     crate::{
         corestore::heap_array::{HeapArray, HeapArrayWriter},
@@ -57,8 +57,10 @@ mod tests;
 /// 100\n
 /// ```
 pub struct Parser {
+    start: *const u8,
     end: *const u8,
     cursor: *const u8,
+    limits: QueryLimits,
 }
 
 unsafe impl RawParser for Parser {
@@ -78,14 +80,24 @@ unsafe impl Sync for Parser {}
 
 impl Parser {
     /// Initialize a new parser
-    fn new(slice: &[u8]) -> Self {
+    fn new(slice: &[u8], limits: QueryLimits) -> Self {
         unsafe {
             Self {
+                start: slice.as_ptr(),
                 end: slice.as_ptr().add(slice.len()),
                 cursor: slice.as_ptr(),
+                limits,
             }
         }
     }
+    /// Returns the number of bytes consumed so far
+    fn consumed(&self) -> usize {
+        self.cursor as usize - self.start as usize
+    }
+    /// Returns an error if the query won't fit in the size limit after the next `size` bytes
+    fn check_size_ahead(&self, size: usize) -> ParseResult<()> {
+        self.limits.check_size(self.consumed().saturating_add(size))
+    }
 }
 
 // utility methods
@@ -113,6 +125,7 @@ impl Parser {
     /// Gets the _next element. **The cursor should be at the tsymbol (passed)**
     fn _next(&mut self) -> ParseResult<UnsafeSlice> {
         let element_size = self.read_usize()?;
+        self.check_size_ahead(element_size)?;
         self.read_until(element_size)
     }
 }
@@ -159,6 +172,8 @@ impl Parser {
                 self.incr_cursor();
             }
             let query_count = self.read_usize()?;
+            // check the count before we allocate for it
+            self.limits.check_args(query_count)?;
             let mut writer = HeapArrayWriter::with_capacity(query_count);
             for i in 0..query_count {
                 unsafe {
@@ -224,6 +239,7 @@ impl Parser {
                 self.incr_cursor()
             };
             let query_count = self.read_usize()?; // get the length
+            self.limits.check_args(query_count)?;
             if query_count == 1 {
                 Ok(Query::Simple(self.parse_simple_query()?))
             } else {
@@ -233,10 +249,9 @@ impl Parser {
             Err(ParseError::NotEnough)
         }
     }
-    pub fn parse(buf: &[u8]) -> ParseResult<QueryWithAdvance> {
-        let mut slf = Self::new(buf);
-        let body = slf._parse()?;
-        let consumed = slf.cursor_ptr() as usize - buf.as_ptr() as usize;
-        Ok((body, consumed))
+    pub fn parse(buf: &[u8], limits: QueryLimits) -> ParseResult<QueryWithAdvance> {
+        let mut slf = Self::new(buf, limits);
+        let body = limits.check_parse(buf.len(), slf._parse())?;
+        Ok((body, slf.consumed()))
     }
 }
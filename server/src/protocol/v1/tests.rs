@@ -26,7 +26,7 @@
 
 use {
     super::Parser,
-    crate::protocol::{ParseError, Query},
+    crate::protocol::{ParseError, Query, QueryLimits},
 };
 
 #[cfg(test)]
@@ -37,7 +37,7 @@ const PQPAYLOAD: &[u8] = b"*2\n~3\n3\nSET\n1\nx\n3\n100\n~2\n3\nGET\n1\nx\n";
 #[test]
 fn parse_simple_query() {
     let payload = SQPAYLOAD.to_vec();
-    let (q, f) = Parser::parse(&payload).unwrap();
+    let (q, f) = Parser::parse(&payload, QueryLimits::default()).unwrap();
     let q: Vec<String> = if let Query::Simple(q) = q {
         q.as_slice()
             .iter()
@@ -54,14 +54,17 @@ fn parse_simple_query() {
 fn parse_simple_query_incomplete() {
     for i in 0..SQPAYLOAD.len() - 1 {
         let slice = &SQPAYLOAD[..i];
-        assert_eq!(Parser::parse(slice).unwrap_err(), ParseError::NotEnough);
+        assert_eq!(
+            Parser::parse(slice, QueryLimits::default()).unwrap_err(),
+            ParseError::NotEnough
+        );
     }
 }
 
 #[test]
 fn parse_pipelined_query() {
     let payload = PQPAYLOAD.to_vec();
-    let (q, f) = Parser::parse(&payload).unwrap();
+    let (q, f) = Parser::parse(&payload, QueryLimits::default()).unwrap();
     let q: Vec<Vec<String>> = if let Query::Pipelined(q) = q {
         q.into_inner()
             .iter()
@@ -88,6 +91,56 @@ fn parse_pipelined_query() {
 fn parse_pipelined_query_incomplete() {
     for i in 0..PQPAYLOAD.len() - 1 {
         let slice = &PQPAYLOAD[..i];
-        assert_eq!(Parser::parse(slice).unwrap_err(), ParseError::NotEnough);
+        assert_eq!(
+            Parser::parse(slice, QueryLimits::default()).unwrap_err(),
+            ParseError::NotEnough
+        );
     }
 }
+
+#[test]
+fn test_parameterized_responses() {
+    use crate::{
+        actions::{Arity, ArityError},
+        protocol::{interface::ProtocolSpec, responses},
+    };
+    // error strings have a size line in Skyhash 1.0
+    assert_eq!(
+        responses::structured_error::<Parser>(307, "out-of-range"),
+        Parser::RSTRING_OUT_OF_RANGE
+    );
+    let e = ArityError::new(Arity::AtLeast(1), 0);
+    assert_eq!(
+        responses::arity_error::<Parser>(&e),
+        b"!52\n700 arity-error: expected at least 1 argument, got 0\n"
+    );
+}
+
+#[test]
+fn parse_fail_because_too_large() {
+    let limits = QueryLimits::new(SQPAYLOAD.len(), 2);
+    // the number of elements in a query
+    assert_eq!(
+        Parser::parse(PQPAYLOAD, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    let limits = QueryLimits::new(SQPAYLOAD.len(), 3);
+    assert_eq!(Parser::parse(SQPAYLOAD, limits).unwrap().1, SQPAYLOAD.len());
+    // the size of the query
+    let limits = QueryLimits::new(SQPAYLOAD.len() - 2, 3);
+    assert_eq!(
+        Parser::parse(SQPAYLOAD, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+}
+
+#[test]
+fn query_too_large_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    let mut expected = b"*1\n".to_vec();
+    expected.extend(responses::structured_error::<Parser>(
+        responses::ERRCODE_QUERY_TOO_LARGE,
+        "query-too-large",
+    ));
+    assert_eq!(Parser::FULLRESP_QUERY_TOO_LARGE, expected);
+}
@@ -28,8 +28,9 @@ mod interface_impls;
 
 use {
     super::{
+        interface::ProtocolSpec,
         raw_parser::{RawParser, RawParserExt, RawParserMeta},
-        ParseError, ParseResult, PipelinedQuery, Query, SimpleQuery, UnsafeSlice,
+        ParseError, ParseResult, PipelinedQuery, Query, QueryLimits, SimpleQuery, UnsafeSlice,
     },
     crate::{corestore::heap_array::HeapArray, dbnet::QueryWithAdvance},
 };
@@ -42,8 +43,10 @@ mod tests;
 
 /// A parser for Skyhash 2.0
 pub struct Parser {
+    start: *const u8,
     end: *const u8,
     cursor: *const u8,
+    limits: QueryLimits,
 }
 
 unsafe impl RawParser for Parser {
@@ -63,14 +66,24 @@ unsafe impl Send for Parser {}
 
 impl Parser {
     /// Initialize a new parser
-    fn new(slice: &[u8]) -> Self {
+    fn new(slice: &[u8], limits: QueryLimits) -> Self {
         unsafe {
             Self {
+                start: slice.as_ptr(),
                 end: slice.as_ptr().add(slice.len()),
                 cursor: slice.as_ptr(),
+                limits,
             }
         }
     }
+    /// Returns the number of bytes consumed so far
+    fn consumed(&self) -> usize {
+        self.cursor as usize - self.start as usize
+    }
+    /// Returns an error if the query won't fit in the size limit after the next `size` bytes
+    fn check_size_ahead(&self, size: usize) -> ParseResult<()> {
+        self.limits.check_size(self.consumed().saturating_add(size))
+    }
 }
 
 // query impls
@@ -89,10 +102,13 @@ impl Parser {
     /// ```
     fn _next_simple_query(&mut self) -> ParseResult<HeapArray<UnsafeSlice>> {
         let element_count = self.read_usize()?;
+        // check the count before we allocate for it
+        self.limits.check_args(element_count)?;
         unsafe {
             let mut data = HeapArray::new_writer(element_count);
             for i in 0..element_count {
                 let element_size = self.read_usize()?;
+                self.check_size_ahead(element_size)?;
                 let element = self.read_until(element_size)?;
                 data.write_to_index(i, element);
             }
@@ -143,6 +159,7 @@ impl Parser {
     /// ```
     fn next_pipeline(&mut self) -> ParseResult<PipelinedQuery> {
         let query_count = self.read_usize()?;
+        self.limits.check_args(query_count)?;
         unsafe {
             let mut queries = HeapArray::new_writer(query_count);
             for i in 0..query_count {
@@ -178,10 +195,57 @@ impl Parser {
     }
     // only expose this. don't expose Self::new since that'll be _relatively easier_ to
     // invalidate invariants for
-    pub fn parse(buf: &[u8]) -> ParseResult<QueryWithAdvance> {
-        let mut slf = Self::new(buf);
-        let body = slf._parse()?;
-        let consumed = slf.cursor_ptr() as usize - buf.as_ptr() as usize;
-        Ok((body, consumed))
+    pub fn parse(buf: &[u8], limits: QueryLimits) -> ParseResult<QueryWithAdvance> {
+        let mut slf = Self::new(buf, limits);
+        let body = limits.check_parse(buf.len(), slf._parse())?;
+        Ok((body, slf.consumed()))
+    }
+}
+
+// encoding
+impl Parser {
+    /// Encode a query as a simple query (this is what clients send, and [`Self::parse`] can read
+    /// it back)
+    pub fn encode_simple_query<T: AsRef<[u8]>>(query: &[T]) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(Self::encoded_len(query) + 1);
+        packet.extend_from_slice(Self::SIMPLE_QUERY_HEADER);
+        Self::encode_elements(&mut packet, query);
+        packet
+    }
+    /// Encode queries as a pipelined query (the queries are run in this order)
+    pub fn encode_pipelined_query<Q: AsRef<[T]>, T: AsRef<[u8]>>(queries: &[Q]) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(
+            queries
+                .iter()
+                .map(|query| Self::encoded_len::<T>(query.as_ref()))
+                .sum::<usize>()
+                + 8,
+        );
+        packet.push(Self::PIPELINED_QUERY_FIRST_BYTE);
+        packet.extend_from_slice(queries.len().to_string().as_bytes());
+        packet.push(Self::LF);
+        for query in queries {
+            Self::encode_elements::<T>(&mut packet, query.as_ref());
+        }
+        packet
+    }
+    /// Returns (roughly) the number of bytes that the elements of a query are encoded in
+    fn encoded_len<T: AsRef<[u8]>>(query: &[T]) -> usize {
+        query
+            .iter()
+            .map(|arg| arg.as_ref().len() + 8)
+            .sum::<usize>()
+            + 8
+    }
+    /// Encode the elements of a query (along with their count)
+    fn encode_elements<T: AsRef<[u8]>>(packet: &mut Vec<u8>, query: &[T]) {
+        packet.extend_from_slice(query.len().to_string().as_bytes());
+        packet.push(Self::LF);
+        for arg in query {
+            let arg = arg.as_ref();
+            packet.extend_from_slice(arg.len().to_string().as_bytes());
+            packet.push(Self::LF);
+            packet.extend_from_slice(arg);
+        }
     }
 }
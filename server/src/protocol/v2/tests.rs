@@ -29,7 +29,7 @@ use {
         super::raw_parser::{RawParser, RawParserExt, RawParserMeta},
         Parser, PipelinedQuery, Query, SimpleQuery,
     },
-    crate::protocol::{iter::AnyArrayIter, ParseError},
+    crate::protocol::{iter::AnyArrayIter, ParseError, QueryLimits},
     std::{iter::Map, vec::IntoIter as VecIntoIter},
 };
 
@@ -150,7 +150,7 @@ fn pipelined_query(query: Query) -> PipelinedQuery {
 #[test]
 fn data_end_ptr() {
     for (len, src) in slices_with_len() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         unsafe {
             assert_eq!(parser.data_end_ptr(), src.as_ptr().add(len));
         }
@@ -161,14 +161,14 @@ fn data_end_ptr() {
 #[test]
 fn cursor_ptr() {
     for src in slices() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         assert_eq!(parser.cursor_ptr(), src.as_ptr())
     }
 }
 #[test]
 fn cursor_ptr_with_incr() {
     for src in slices() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         unsafe {
             parser.incr_cursor_by(src.len());
             assert_eq!(parser.cursor_ptr(), src.as_ptr().add(src.len()));
@@ -180,14 +180,14 @@ fn cursor_ptr_with_incr() {
 #[test]
 fn remaining() {
     for (len, src) in slices_with_len() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         assert_eq!(parser.remaining(), len);
     }
 }
 #[test]
 fn remaining_with_incr() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         unsafe {
             // no change
             parser.incr_cursor_by(0);
@@ -208,14 +208,14 @@ fn remaining_with_incr() {
 #[test]
 fn has_remaining() {
     for (len, src) in slices_with_len() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         assert!(parser.has_remaining(len), "should have {len} remaining")
     }
 }
 #[test]
 fn has_remaining_with_incr() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         unsafe {
             // no change
             parser.incr_cursor_by(0);
@@ -238,7 +238,7 @@ fn has_remaining_with_incr() {
 #[test]
 fn exhausted() {
     for src in slices() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         if src.is_empty() {
             assert!(parser.exhausted());
         } else {
@@ -249,7 +249,7 @@ fn exhausted() {
 #[test]
 fn exhausted_with_incr() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         if len == 0 {
             assert!(parser.exhausted());
         } else {
@@ -272,7 +272,7 @@ fn exhausted_with_incr() {
 #[test]
 fn not_exhausted() {
     for src in slices() {
-        let parser = Parser::new(&src);
+        let parser = Parser::new(&src, QueryLimits::default());
         if src.is_empty() {
             assert!(!parser.not_exhausted());
         } else {
@@ -283,7 +283,7 @@ fn not_exhausted() {
 #[test]
 fn not_exhausted_with_incr() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         if len == 0 {
             assert!(!parser.not_exhausted());
         } else {
@@ -306,7 +306,7 @@ fn not_exhausted_with_incr() {
 #[test]
 fn read_until_empty() {
     let b = v!(b"");
-    let mut parser = Parser::new(&b);
+    let mut parser = Parser::new(&b, QueryLimits::default());
     ensure_zero_reads(&mut parser);
     assert_eq!(parser.read_until(1).unwrap_err(), ParseError::NotEnough);
 }
@@ -314,7 +314,7 @@ fn read_until_empty() {
 #[test]
 fn read_until_nonempty() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         // should always work
         ensure_zero_reads(&mut parser);
         // now read the entire length; should always work
@@ -330,7 +330,7 @@ fn read_until_nonempty() {
 #[test]
 fn read_until_not_enough() {
     for (len, src) in slices_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         ensure_zero_reads(&mut parser);
         // try to read more than the amount of data bufferred
         assert_eq!(
@@ -345,7 +345,7 @@ fn read_until_not_enough() {
 #[test]
 fn read_until_more_bytes() {
     let sample1 = v!(b"abcd1");
-    let mut p1 = Parser::new(&sample1);
+    let mut p1 = Parser::new(&sample1, QueryLimits::default());
     assert_eq!(
         unsafe { p1.read_until(&sample1.len() - 1).unwrap().as_slice() },
         &sample1[..&sample1.len() - 1]
@@ -354,7 +354,7 @@ fn read_until_more_bytes() {
     ensure_not_exhausted(&p1);
     ensure_remaining(&p1, 1);
     let sample2 = v!(b"abcd1234567890!@#$");
-    let mut p2 = Parser::new(&sample2);
+    let mut p2 = Parser::new(&sample2, QueryLimits::default());
     assert_eq!(
         unsafe { p2.read_until(4).unwrap().as_slice() },
         &sample2[..4]
@@ -368,7 +368,7 @@ fn read_until_more_bytes() {
 #[test]
 fn read_line_special_case_only_lf() {
     let b = v!(b"\n");
-    let mut parser = Parser::new(&b);
+    let mut parser = Parser::new(&b, QueryLimits::default());
     let r = parser.read_line().unwrap();
     let slice = unsafe { r.as_slice() };
     assert_eq!(slice, b"");
@@ -380,7 +380,7 @@ fn read_line_special_case_only_lf() {
 #[test]
 fn read_line() {
     for (len, src) in slices_lf_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         if len == 0 {
             // should be empty, so NotEnough
             assert_eq!(parser.read_line().unwrap_err(), ParseError::NotEnough);
@@ -407,7 +407,7 @@ fn read_line() {
 #[test]
 fn read_line_more_bytes() {
     let sample1 = v!(b"abcd\n1");
-    let mut p1 = Parser::new(&sample1);
+    let mut p1 = Parser::new(&sample1, QueryLimits::default());
     let line = p1.read_line().unwrap();
     assert_eq!(unsafe { line.as_slice() }, b"abcd");
     // we should still have one remaining
@@ -418,7 +418,7 @@ fn read_line_more_bytes() {
 #[test]
 fn read_line_subsequent_lf() {
     let sample1 = v!(b"abcd\n1\n");
-    let mut p1 = Parser::new(&sample1);
+    let mut p1 = Parser::new(&sample1, QueryLimits::default());
     let line = p1.read_line().unwrap();
     assert_eq!(unsafe { line.as_slice() }, b"abcd");
     // we should still have two octets remaining
@@ -433,7 +433,7 @@ fn read_line_subsequent_lf() {
 #[test]
 fn read_line_pedantic_okay() {
     for (len, src) in slices_lf_with_len() {
-        let mut parser = Parser::new(&src);
+        let mut parser = Parser::new(&src, QueryLimits::default());
         if len == 0 {
             // should be empty, so NotEnough
             assert_eq!(
@@ -467,7 +467,9 @@ fn read_line_pedantic_okay() {
 fn read_line_pedantic_fail_empty() {
     let payload = v!(b"");
     assert_eq!(
-        Parser::new(&payload).read_line_pedantic().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_line_pedantic()
+            .unwrap_err(),
         ParseError::NotEnough
     );
 }
@@ -476,7 +478,9 @@ fn read_line_pedantic_fail_empty() {
 fn read_line_pedantic_fail_only_lf() {
     let payload = v!(b"\n");
     assert_eq!(
-        Parser::new(&payload).read_line_pedantic().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_line_pedantic()
+            .unwrap_err(),
         ParseError::BadPacket
     );
 }
@@ -485,7 +489,9 @@ fn read_line_pedantic_fail_only_lf() {
 fn read_line_pedantic_fail_only_lf_extra_data() {
     let payload = v!(b"\n1");
     assert_eq!(
-        Parser::new(&payload).read_line_pedantic().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_line_pedantic()
+            .unwrap_err(),
         ParseError::BadPacket
     );
 }
@@ -494,12 +500,16 @@ fn read_line_pedantic_fail_only_lf_extra_data() {
 fn read_usize_fail_empty() {
     let payload = v!(b"");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::NotEnough
     );
     let payload = v!(b"\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::BadPacket
     );
 }
@@ -508,7 +518,9 @@ fn read_usize_fail_empty() {
 fn read_usize_fail_no_lf() {
     let payload = v!(b"1");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::NotEnough
     );
 }
@@ -516,36 +528,56 @@ fn read_usize_fail_no_lf() {
 #[test]
 fn read_usize_okay() {
     let payload = v!(b"1\n");
-    assert_eq!(Parser::new(&payload).read_usize().unwrap(), 1);
+    assert_eq!(
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap(),
+        1
+    );
     let payload = v!(b"1234\n");
-    assert_eq!(Parser::new(&payload).read_usize().unwrap(), 1234);
+    assert_eq!(
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap(),
+        1234
+    );
 }
 
 #[test]
 fn read_usize_fail() {
     let payload = v!(b"a\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::DatatypeParseFailure
     );
     let payload = v!(b"1a\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::DatatypeParseFailure
     );
     let payload = v!(b"a1\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::DatatypeParseFailure
     );
     let payload = v!(b"aa\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::DatatypeParseFailure
     );
     let payload = v!(b"12345abcde\n");
     assert_eq!(
-        Parser::new(&payload).read_usize().unwrap_err(),
+        Parser::new(&payload, QueryLimits::default())
+            .read_usize()
+            .unwrap_err(),
         ParseError::DatatypeParseFailure
     );
 }
@@ -554,7 +586,7 @@ fn read_usize_fail() {
 fn parse_fail_because_unknown_query_scheme() {
     let body = v!(b"?3\n3\nSET1\nx3\n100");
     assert_eq!(
-        Parser::parse(&body).unwrap_err(),
+        Parser::parse(&body, QueryLimits::default()).unwrap_err(),
         ParseError::UnexpectedByte
     )
 }
@@ -562,7 +594,7 @@ fn parse_fail_because_unknown_query_scheme() {
 #[test]
 fn simple_query_okay() {
     let body = v!(b"*3\n3\nSET1\nx3\n100");
-    let (ret, skip) = Parser::parse(&body).unwrap();
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
     assert_eq!(skip, body.len());
     let query = simple_query(ret);
     assert_eq!(query.into_owned().data, v!["SET", "x", "100"]);
@@ -571,12 +603,35 @@ fn simple_query_okay() {
 #[test]
 fn simple_query_okay_empty_elements() {
     let body = v!(b"*3\n3\nSET0\n0\n");
-    let (ret, skip) = Parser::parse(&body).unwrap();
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
     assert_eq!(skip, body.len());
     let query = simple_query(ret);
     assert_eq!(query.into_owned().data, v!["SET", "", ""]);
 }
 
+#[test]
+fn simple_query_encode() {
+    let body = Parser::encode_simple_query(&["SET", "x", "100"]);
+    assert_eq!(body, b"*3\n3\nSET1\nx3\n100");
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
+    assert_eq!(skip, body.len());
+    let query = simple_query(ret);
+    assert_eq!(query.into_owned().data, v!["SET", "x", "100"]);
+}
+
+#[test]
+fn pipelined_query_encode() {
+    let body = Parser::encode_pipelined_query(&[&["SET", "x", "100"][..], &["GET", "x"]]);
+    assert_eq!(body, b"$2\n3\n3\nSET1\nx3\n1002\n3\nGET1\nx");
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
+    assert_eq!(skip, body.len());
+    let query = pipelined_query(ret);
+    assert_eq!(
+        query.into_owned().data,
+        vec![v!["SET", "x", "100"], v!["GET", "x"]]
+    )
+}
+
 #[test]
 fn parse_fail_because_not_enough() {
     let full_payload = b"*3\n3\nSET1\nx3\n100";
@@ -585,7 +640,7 @@ fn parse_fail_because_not_enough() {
         .collect();
     for body in samples {
         assert_eq!(
-            Parser::parse(&body).unwrap_err(),
+            Parser::parse(&body, QueryLimits::default()).unwrap_err(),
             ParseError::NotEnough,
             "Failed with body len: {}",
             body.len()
@@ -596,7 +651,7 @@ fn parse_fail_because_not_enough() {
 #[test]
 fn pipelined_query_okay() {
     let body = v!(b"$2\n3\n3\nSET1\nx3\n1002\n3\nGET1\nx");
-    let (ret, skip) = Parser::parse(&body).unwrap();
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
     assert_eq!(skip, body.len());
     let query = pipelined_query(ret);
     assert_eq!(
@@ -608,7 +663,7 @@ fn pipelined_query_okay() {
 #[test]
 fn pipelined_query_okay_empty_elements() {
     let body = v!(b"$2\n3\n3\nSET0\n3\n1002\n3\nGET0\n");
-    let (ret, skip) = Parser::parse(&body).unwrap();
+    let (ret, skip) = Parser::parse(&body, QueryLimits::default()).unwrap();
     assert_eq!(skip, body.len());
     let query = pipelined_query(ret);
     assert_eq!(
@@ -624,15 +679,222 @@ fn pipelined_query_fail_because_not_enough() {
         .map(|i| full_payload.iter().cloned().take(i).collect())
         .collect();
     for body in samples {
-        let ret = Parser::parse(&body).unwrap_err();
+        let ret = Parser::parse(&body, QueryLimits::default()).unwrap_err();
         assert_eq!(ret, ParseError::NotEnough)
     }
 }
 
+#[test]
+fn parse_fail_because_too_many_elements() {
+    let limits = QueryLimits::new(1024, 2);
+    assert_eq!(Parser::parse(b"*2\n3\nGET1\nx", limits).unwrap().1, 11);
+    // we don't need the elements to reject the query
+    assert_eq!(
+        Parser::parse(b"*3\n", limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    assert_eq!(
+        Parser::parse(b"$3\n", limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+}
+
+#[test]
+fn parse_fail_because_too_large() {
+    let body = v!(b"*3\n3\nSET1\nx3\n100");
+    let limits = QueryLimits::new(body.len(), 1024);
+    assert_eq!(Parser::parse(&body, limits).unwrap().1, body.len());
+    let limits = QueryLimits::new(body.len() - 1, 1024);
+    // the size of the last element is enough to reject the query
+    assert_eq!(
+        Parser::parse(&body[..14], limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // and so is the size of a pipeline (even if its queries are small)
+    let body = v!(b"$2\n3\n3\nSET1\nx3\n1002\n3\nGET1\nx");
+    let limits = QueryLimits::new(body.len() - 1, 1024);
+    assert_eq!(
+        Parser::parse(&body, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // a line that never ends
+    let limits = QueryLimits::new(16, 1024);
+    assert_eq!(
+        Parser::parse(b"*123456789012345", limits).unwrap_err(),
+        ParseError::NotEnough
+    );
+    assert_eq!(
+        Parser::parse(b"*1234567890123456", limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+}
+
+#[test]
+fn query_too_large_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    let mut expected = Parser::SIMPLE_QUERY_HEADER.to_vec();
+    expected.extend(responses::structured_error::<Parser>(
+        responses::ERRCODE_QUERY_TOO_LARGE,
+        "query-too-large",
+    ));
+    assert_eq!(Parser::FULLRESP_QUERY_TOO_LARGE, expected);
+    assert_eq!(
+        Parser::SKYHASH_PARSE_ERROR_LUT[ParseError::QueryTooLarge as usize - 1],
+        Parser::FULLRESP_QUERY_TOO_LARGE
+    );
+}
+
+#[test]
+fn throttled_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_THROTTLED,
+        responses::structured_error::<Parser>(responses::ERRCODE_THROTTLED, "throttled")
+    );
+}
+
+#[test]
+fn read_only_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_READ_ONLY,
+        responses::structured_error::<Parser>(responses::ERRCODE_READ_ONLY, "read-only")
+    );
+}
+
+#[test]
+fn out_of_memory_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_OUT_OF_MEMORY,
+        responses::structured_error::<Parser>(responses::ERRCODE_OUT_OF_MEMORY, "out-of-memory")
+    );
+}
+
+#[test]
+fn compaction_busy_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_COMPACTION_BUSY,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_COMPACTION_BUSY,
+            "err-compaction-busy"
+        )
+    );
+}
+
+#[test]
+fn bad_archive_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_BAD_ARCHIVE,
+        responses::structured_error::<Parser>(responses::ERRCODE_BAD_ARCHIVE, "bad-archive")
+    );
+}
+
+#[test]
+fn read_only_replica_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_READ_ONLY_REPLICA,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_READ_ONLY_REPLICA,
+            "read-only-replica"
+        )
+    );
+}
+
+#[test]
+fn failover_failed_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_FAILOVER_FAILED,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_FAILOVER_FAILED,
+            "err-failover-failed"
+        )
+    );
+}
+
+#[test]
+fn cross_slot_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_CROSS_SLOT,
+        responses::structured_error::<Parser>(responses::ERRCODE_CROSS_SLOT, "cross-slot")
+    );
+}
+
+#[test]
+fn cluster_down_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_CLUSTER_DOWN,
+        responses::structured_error::<Parser>(responses::ERRCODE_CLUSTER_DOWN, "cluster-down")
+    );
+}
+
+#[test]
+fn cluster_disabled_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_CLUSTER_DISABLED,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_CLUSTER_DISABLED,
+            "err-cluster-disabled"
+        )
+    );
+}
+
+#[test]
+fn migration_failed_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_MIGRATION_FAILED,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_MIGRATION_FAILED,
+            "err-migration-failed"
+        )
+    );
+}
+
+#[test]
+fn backup_failed_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_BACKUP_FAILED,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_BACKUP_FAILED,
+            "err-backup-failed"
+        )
+    );
+}
+
+#[test]
+fn cdc_disabled_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_CDC_DISABLED,
+        responses::structured_error::<Parser>(responses::ERRCODE_CDC_DISABLED, "err-cdc-disabled")
+    );
+}
+
+#[test]
+fn bad_export_path_response() {
+    use crate::protocol::{interface::ProtocolSpec, responses};
+    assert_eq!(
+        Parser::RSTRING_BAD_EXPORT_PATH,
+        responses::structured_error::<Parser>(
+            responses::ERRCODE_BAD_EXPORT_PATH,
+            "err-bad-export-path"
+        )
+    );
+}
+
 #[test]
 fn test_iter() {
     use super::{Parser, Query};
-    let (q, _fwby) = Parser::parse(b"*3\n3\nset1\nx3\n100").unwrap();
+    let (q, _fwby) = Parser::parse(b"*3\n3\nset1\nx3\n100", QueryLimits::default()).unwrap();
     let r = match q {
         Query::Simple(q) => q,
         _ => panic!("Wrong query"),
@@ -643,3 +905,31 @@ fn test_iter() {
     assert_eq!(iter.next().unwrap(), "x".as_bytes());
     assert_eq!(iter.next().unwrap(), "100".as_bytes());
 }
+
+#[test]
+fn test_parameterized_responses() {
+    use crate::{
+        actions::{ActionError, Arity, ArityError},
+        protocol::{interface::ProtocolSpec, responses},
+    };
+    // should be no different from the pregenerated error strings
+    assert_eq!(
+        responses::structured_error::<Parser>(307, "out-of-range"),
+        Parser::RSTRING_OUT_OF_RANGE
+    );
+    let e = ArityError::new(Arity::Exactly(1), 2);
+    assert_eq!(
+        responses::arity_error::<Parser>(&e),
+        b"!700 arity-error: expected 1 argument, got 2\n"
+    );
+    let e = match ActionError::ArityError(ArityError::new(Arity::Groups { head: 1, group: 2 }, 2))
+        .in_action(b"HSET")
+    {
+        ActionError::ArityError(e) => e,
+        e => panic!("Expected an arity error, found {:?}", e),
+    };
+    assert_eq!(
+        responses::arity_error::<Parser>(&e),
+        b"!700 arity-error: HSET expects 3, 5, 7, ... arguments, got 2\n"
+    );
+}
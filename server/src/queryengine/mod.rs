@@ -26,46 +26,136 @@
 
 //! # The Query Engine
 
-use crate::{
-    actions::{self, ActionError, ActionResult},
-    admin, auth, blueql,
-    corestore::Corestore,
-    dbnet::{prelude::*, BufferedSocketStream},
-    protocol::{iter::AnyArrayIter, PipelinedQuery, SimpleQuery, UnsafeSlice},
+use {
+    crate::{
+        actions::{self, ActionError, ActionResult},
+        admin, audit, auth, blueql,
+        corestore::{
+            memstore::{Keyspace, Memstore},
+            Corestore,
+        },
+        dbnet::{monitor, prelude::*, BufferedSocketStream},
+        kvengine::encoding,
+        memory, metrics,
+        protocol::{iter::AnyArrayIter, responses, PipelinedQuery, SimpleQuery, UnsafeSlice},
+        replication,
+        storage::v1::wal,
+    },
+    std::{sync::Arc, time::Instant},
 };
 
+pub mod script;
+
 pub type ActionIter<'a> = AnyArrayIter<'a>;
 
 const ACTION_AUTH: &[u8] = b"auth";
 
+/// The name of an action. This is the identifier itself, unless an explicit name is given (for
+/// names that aren't valid identifiers, like `TS.ADD`)
+macro_rules! action_name {
+    ($action:ident) => {
+        stringify!($action).as_bytes()
+    };
+    ($action:ident $name:literal) => {
+        $name.as_bytes()
+    };
+}
+
 macro_rules! gen_constants_and_matches {
     (
-        $con:expr, $buf:ident, $db:ident, $($action:ident => $fns:path),*,
+        $con:expr, $buf:ident, $db:ident, $grants:ident, $ret:ident,
+        $($action:ident $(($name:literal))? => $fns:path),*,
         {$($action2:ident => $fns2:expr),*}
     ) => {
         mod tags {
             //! This module is a collection of tags/strings used for evaluating queries
             //! and responses
             $(
-                pub const $action: &[u8] = stringify!($action).as_bytes();
+                pub const $action: &[u8] = action_name!($action $($name)?);
             )*
             $(
                 pub const $action2: &[u8] = stringify!($action2).as_bytes();
             )*
+            /// The length of the longest action name
+            pub const MAX_LEN: usize = {
+                let mut max = 0;
+                $(
+                    if $action.len() > max {
+                        max = $action.len();
+                    }
+                )*
+                $(
+                    if $action2.len() > max {
+                        max = $action2.len();
+                    }
+                )*
+                max
+            };
         }
         let first_slice = $buf.next().unwrap_or_custom_aerr(P::RCODE_PACKET_ERR)?;
-        let first = first_slice.to_ascii_uppercase();
-        match first.as_ref() {
+        // action names are case insensitive. fold the name on the stack so that we don't have
+        // to allocate for every query; anything longer than the longest action name can't be
+        // an action and is left for BlueQL
+        let mut folded = [0u8; tags::MAX_LEN];
+        let first: &[u8] = match folded.get_mut(..first_slice.len()) {
+            Some(folded) => {
+                folded.copy_from_slice(first_slice);
+                folded.make_ascii_uppercase();
+                folded
+            }
+            None => &[],
+        };
+        // the permission check hook: restricted users can only run actions on the tables that
+        // they were granted permissions on (BlueQL checks its statements itself)
+        if let Some(grants) = $grants.as_deref() {
+            if matches!(first, $(tags::$action)|* $(| tags::$action2)*) {
+                auth::acl::check_action::<P>(grants, $db, first, $buf.as_ref())?;
+            }
+        }
+        // the cluster hook: in cluster mode, queries on keys that are owned by another node
+        // are redirected to it (queries between nodes are never redirected)
+        if cluster::is_enabled()
+            && !$con.is_internal()
+            && matches!(first, $(tags::$action)|* $(| tags::$action2)*)
+        {
+            cluster::check_route::<P>(first, $buf.as_ref())?;
+        }
+        // the read-only hook: writes are turned away while the server is read-only, or while
+        // it's a read-only replica (again, BlueQL checks its statements itself)
+        if let Some(rstring) = self::rejects_writes($con) {
+            if matches!(first, $(tags::$action)|* $(| tags::$action2)*) {
+                self::check_read_only::<P>(first, rstring)?;
+            }
+        }
+        // the memory guard: writes that can grow the data are turned away while the memory
+        // usage is over the limit
+        if memory::rejects_writes() && matches!(first, $(tags::$action)|* $(| tags::$action2)*) {
+            memory::check_write::<P>(first)?;
+        }
+        // the dirty hook: the keyspaces that the query may write to are flushed by the next
+        // BGSAVE. They're marked once it's done (even if it fails)
+        let _dirty = if matches!(first, $(tags::$action)|* $(| tags::$action2)*) {
+            self::MarkDirty::for_action($db, first, $buf.as_ref())
+        } else {
+            self::MarkDirty::for_statement($db, first_slice)
+        };
+        let start = Instant::now();
+        let (action, ret) = match first {
             $(
-                tags::$action => $fns($db, $con, $buf).await?,
+                tags::$action => (tags::$action, $fns($db, $con, $buf).await),
             )*
             $(
-                tags::$action2 => $fns2.await?,
+                tags::$action2 => (tags::$action2, $fns2.await),
             )*
-            _ => {
-                blueql::execute($db, $con, first_slice, $buf.len()).await?;
-            }
-        }
+            _ => (
+                metrics::BLUEQL,
+                blueql::execute($db, $con, first_slice, $buf.len(), $grants.as_deref()).await,
+            ),
+        };
+        metrics::record_query(action, start.elapsed());
+        $con.set_last_action(action);
+        // arity errors are reported with the name of the action
+        let $ret = ret.map_err(|e| e.in_action(first));
     };
 }
 
@@ -78,10 +168,22 @@ action! {
         buf: SimpleQuery
     ) {
         let bufref = buf.as_slice();
+        let ret = self::execute_stage_noauth(con, auth, bufref).await;
+        if audit::is_enabled() {
+            // nobody is logged in, so it can only be a login (or an attempt to run something)
+            audit::record_query::<P>(&self::query_args(bufref), &ret, con.peer(), None);
+        }
+        ret
+    }
+    fn execute_stage_noauth(
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        buf: &[UnsafeSlice]
+    ) {
         let mut iter = unsafe {
             // UNSAFE(@ohsayan): The presence of the connection guarantees that this
             // won't suddenly become invalid
-            AnyArrayIter::new(bufref.iter())
+            AnyArrayIter::new(buf.iter())
         };
         match iter.next_lowercase().unwrap_or_custom_aerr(P::RCODE_PACKET_ERR)?.as_ref() {
             ACTION_AUTH => auth::auth_login_only(con, auth, iter).await,
@@ -99,52 +201,336 @@ action! {
     }
 }
 
+/// Returns the respstring that writes are turned away with on this connection, if they are:
+/// while the server is read-only, or while it's a read-only replica (see
+/// [`replication::rejects_writes`]). Writes are never turned away on internal connections
+pub fn rejects_writes<C, P: ProtocolSpec>(con: &Connection<C, P>) -> Option<&'static [u8]> {
+    if con.is_internal() {
+        None
+    } else if registry::is_read_only() {
+        Some(P::RSTRING_READ_ONLY)
+    } else if replication::rejects_writes() {
+        Some(P::RSTRING_READ_ONLY_REPLICA)
+    } else {
+        None
+    }
+}
+
+/// Reject the (uppercased) action with `rstring` if it writes. Only call this when writes are
+/// turned away (see [`rejects_writes`])
+fn check_read_only<P: ProtocolSpec>(action: &[u8], rstring: &'static [u8]) -> ActionResult<()> {
+    if auth::acl::writes(action) {
+        util::err(rstring)
+    } else {
+        Ok(())
+    }
+}
+
+/// Marks the keyspaces that a query may have written to as dirty when it's dropped, so that
+/// the next BGSAVE flushes them (see [`Keyspace::mark_dirty`])
+enum MarkDirty {
+    /// the query doesn't write
+    Nothing,
+    /// the query can only write to the current keyspace
+    Current(Arc<Keyspace>),
+    /// the query can write to any keyspace
+    Everything(Arc<Memstore>),
+}
+
+impl MarkDirty {
+    /// For an (uppercased) action
+    fn for_action<'a>(db: &Corestore, action: &[u8], args: impl Iterator<Item = &'a [u8]>) -> Self {
+        if !auth::acl::writes(action) {
+            Self::Nothing
+        } else if auth::acl::writes_elsewhere(action, args) {
+            Self::Everything(db.clone_store())
+        } else {
+            db.get_cks_atomic_ref().map_or(Self::Nothing, Self::Current)
+        }
+    }
+    /// For a BlueQL statement. Only the ones that change the schema write, and they can name
+    /// any entity
+    fn for_statement(db: &Corestore, statement: &[u8]) -> Self {
+        if audit::is_ddl_statement(statement) {
+            Self::Everything(db.clone_store())
+        } else {
+            Self::Nothing
+        }
+    }
+}
+
+impl Drop for MarkDirty {
+    fn drop(&mut self) {
+        match self {
+            Self::Nothing => {}
+            Self::Current(ks) => ks.mark_dirty(),
+            Self::Everything(store) => store.mark_all_dirty(),
+        }
+    }
+}
+
+/// Returns the arguments of a query (for the audit log and the monitor)
+fn query_args(buf: &[UnsafeSlice]) -> Vec<&[u8]> {
+    buf.iter()
+        .map(|arg| unsafe {
+            // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+            // won't suddenly become invalid
+            arg.as_slice()
+        })
+        .collect()
+}
+
 async fn execute_stage<'a, P: ProtocolSpec, C: BufferedSocketStream>(
     db: &mut Corestore,
     con: &mut Connection<C, P>,
     auth: &mut AuthProviderHandle,
     buf: &[UnsafeSlice],
 ) -> ActionResult<()> {
+    if !audit::is_enabled() {
+        return self::dispatch_stage(db, con, auth, buf).await;
+    }
+    // the action is attributed to the user that ran it (even if it logs in as someone else)
+    let user = auth.provider().current_user().map(<[u8]>::to_vec);
+    let ret = self::dispatch_stage(db, con, auth, buf).await;
+    audit::record_query::<P>(&self::query_args(buf), &ret, con.peer(), user.as_deref());
+    ret
+}
+
+async fn dispatch_stage<'a, P: ProtocolSpec, C: BufferedSocketStream>(
+    db: &mut Corestore,
+    con: &mut Connection<C, P>,
+    auth: &mut AuthProviderHandle,
+    buf: &[UnsafeSlice],
+) -> ActionResult<()> {
+    // a restored snapshot replaces the keyspace and table that we're using
+    db.refresh_entity();
+    if con.is_strict_utf8()
+        && !buf.iter().all(|arg| unsafe {
+            // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+            // won't suddenly become invalid
+            encoding::is_okay_encoded(arg.as_slice())
+        })
+    {
+        // strict connections can't send binary data
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    let grants = auth.provider().grants();
+    if con.in_transaction() {
+        // a transaction is in progress, so everything is queued until it ends
+        let mut iter = unsafe {
+            // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+            // won't suddenly become invalid
+            AnyArrayIter::new(buf.iter())
+        };
+        let read_only = self::rejects_writes(con);
+        let out_of_memory = memory::rejects_writes();
+        let routed = cluster::is_enabled() && !con.is_internal();
+        if grants.is_some() || read_only.is_some() || out_of_memory || routed {
+            let action = iter
+                .next_uppercase()
+                .unwrap_or_custom_aerr(P::RCODE_PACKET_ERR)?;
+            if routed {
+                // the queued actions have to run on this node
+                cluster::check_route::<P>(&action, iter.as_ref())?;
+            }
+            if let Some(rstring) = read_only {
+                // writes can't be queued (or run with `EXEC`) while the server is read-only
+                self::check_read_only::<P>(&action, rstring)?;
+            }
+            if out_of_memory {
+                // nor can writes that grow the data while the memory usage is over the limit
+                memory::check_write::<P>(&action)?;
+            }
+            if let Some(grants) = grants.as_deref() {
+                // queued actions (and `EXEC`) only ever run on the current table
+                auth::acl::check_action::<P>(grants, db, &action, iter)?;
+            }
+            iter = unsafe {
+                // UNSAFE(@ohsayan): Same as above
+                AnyArrayIter::new(buf.iter())
+            };
+        }
+        // `EXEC` applies the queued writes to the current table
+        let _dirty = db
+            .get_cks_atomic_ref()
+            .map_or(MarkDirty::Nothing, MarkDirty::Current);
+        return actions::txn::queue(db, con, iter).await;
+    }
+    // an `@<entity>` prefix runs this query (and only this query) on another entity. The
+    // override is applied to a copy of the handle so that it never leaks into the rest of
+    // the connection
+    let mut override_db;
+    let prefix = buf.split_first().and_then(|(first, rest)| unsafe {
+        // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+        // won't suddenly become invalid
+        first
+            .as_slice()
+            .strip_prefix(b"@")
+            .map(|entity| (entity, rest))
+    });
+    let (db, buf) = match prefix {
+        Some((entity, rest)) => {
+            if rest.is_empty() {
+                return util::err(P::RCODE_ACTION_ERR);
+            }
+            let entity = blueql::util::from_slice_action_result::<P>(entity)?;
+            override_db = db.clone();
+            actions::translate_ddl_error::<P, ()>(override_db.swap_entity(&entity))?;
+            (&mut override_db, rest)
+        }
+        None => (db, buf),
+    };
+    if monitor::is_active() {
+        monitor::publish(
+            con.client_id(),
+            con.request_id(),
+            con.peer(),
+            db.get_ids(),
+            &self::query_args(buf),
+        );
+    }
+    // writes are logged (in the order that they're applied to the tables that they can touch,
+    // and with the durability of the current table) and fed to the replicas before they're
+    // acknowledged. Our turn is given up without logging anything if the action fails
+    let (first, args) = match buf.split_first() {
+        Some((first, args)) => unsafe {
+            // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+            // won't suddenly become invalid
+            (first.as_slice(), args)
+        },
+        None => (&[][..], &[][..]),
+    };
+    let sequence = match wal::durability_of(first, db) {
+        Some(durability) => {
+            let args = args.iter().map(|arg| unsafe {
+                // UNSAFE(@ohsayan): Same as above
+                arg.as_slice()
+            });
+            Some(wal::sequence(durability, wal::scope_of(first, args, db)).await)
+        }
+        None => None,
+    };
     let mut iter = unsafe {
         // UNSAFE(@ohsayan): The presence of the connection guarantees that this
         // won't suddenly become invalid
         AnyArrayIter::new(buf.iter())
     };
-    {
+    let ret = {
         gen_constants_and_matches!(
-            con, iter, db,
+            con, iter, db, grants, ret,
             GET => actions::get::get,
             SET => actions::set::set,
             UPDATE => actions::update::update,
+            CAS => actions::cas::cas,
+            GETSET => actions::getset::getset,
+            GETRANGE => actions::range::getrange,
+            JGET => actions::json::jget,
+            SETRANGE => actions::range::setrange,
+            APPEND => actions::append::append,
+            SETBIT => actions::bits::setbit,
+            GETBIT => actions::bits::getbit,
+            BITCOUNT => actions::bits::bitcount,
             DEL => actions::del::del,
+            DELPREFIX => actions::del::delprefix,
             HEYA => actions::heya::heya,
             EXISTS => actions::exists::exists,
             MSET => actions::mset::mset,
+            MSETEX => actions::mset::msetex,
             MGET => actions::mget::mget,
+            GETMANY => actions::mget::getmany,
+            SETMANY => actions::mset::setmany,
             MUPDATE => actions::mupdate::mupdate,
             SSET => actions::strong::sset,
             SDEL => actions::strong::sdel,
             SUPDATE => actions::strong::supdate,
             DBSIZE => actions::dbsize::dbsize,
+            MEMUSAGE => actions::dbsize::memusage,
             FLUSHDB => actions::flushdb::flushdb,
+            FLUSHTABLE => actions::flushdb::flushdb,
             USET => actions::uset::uset,
             KEYLEN => actions::keylen::keylen,
+            STRLEN => actions::keylen::keylen,
+            TYPE => actions::keytype::keytype,
+            RENAME => actions::rename::rename,
+            COPY => actions::rename::copy,
+            MOVE => actions::rename::move_key,
             MKSNAP => admin::mksnap::mksnap,
             LSKEYS => actions::lskeys::lskeys,
+            SCAN => actions::scan::scan,
+            KEYS => actions::keys::keys,
+            RANDOMKEY => actions::randomkey::randomkey,
+            SAMPLE => actions::randomkey::sample,
             POP => actions::pop::pop,
+            GETDEL => actions::pop::pop,
             MPOP => actions::mpop::mpop,
             LSET => actions::lists::lset,
             LGET => actions::lists::lget::lget,
             LMOD => actions::lists::lmod::lmod,
+            LPUSH => actions::lists::lpush::lpush,
+            RPUSH => actions::lists::lpush::rpush,
+            LPOP => actions::lists::lpop::lpop,
+            RPOP => actions::lists::lpop::rpop,
+            BLPOP => actions::lists::lpop::blpop,
+            BRPOP => actions::lists::lpop::brpop,
+            LRANGE => actions::lists::lrange::lrange,
+            SADD => actions::sets::sadd,
+            SREM => actions::sets::srem,
+            SMEMBERS => actions::sets::smembers,
+            SUNION => actions::sets::sunion,
+            SINTER => actions::sets::sinter,
+            SDIFF => actions::sets::sdiff,
+            ZADD => actions::zsets::zadd,
+            ZRANGEBYSCORE => actions::zsets::zrangebyscore,
+            ZRANK => actions::zsets::zrank,
+            HSET => actions::hashes::hset,
+            HGET => actions::hashes::hget,
+            HDEL => actions::hashes::hdel,
+            HGETALL => actions::hashes::hgetall,
+            INCR => actions::counters::incr,
+            DECR => actions::counters::decr,
+            INCRBY => actions::counters::incrby,
+            DECRBY => actions::counters::decrby,
+            BFRESERVE => actions::bloom::bfreserve,
+            BFADD => actions::bloom::bfadd,
+            BFEXISTS => actions::bloom::bfexists,
+            PFADD => actions::hll::pfadd,
+            PFCOUNT => actions::hll::pfcount,
+            PFMERGE => actions::hll::pfmerge,
+            GEOADD => actions::geo::geoadd,
+            GEOSEARCH => actions::geo::geosearch,
+            TS_ADD("TS.ADD") => actions::timeseries::ts_add,
+            TS_RANGE("TS.RANGE") => actions::timeseries::ts_range,
+            TS_LAST("TS.LAST") => actions::timeseries::ts_last,
             WHEREAMI => actions::whereami::whereami,
-            SYS => admin::sys::sys,
+            EXPIRE => actions::expire::expire,
+            TTL => actions::expire::ttl,
+            PERSIST => actions::expire::persist,
+            MULTI => actions::txn::multi,
+            EXEC => actions::txn::exec,
+            DISCARD => actions::txn::discard,
+            EVAL => script::eval,
+            SUBSCRIBE => actions::pubsub::subscribe,
+            UNSUBSCRIBE => actions::pubsub::unsubscribe,
+            PUBLISH => actions::pubsub::publish,
+            NOTIFY => actions::notify::notify,
+            WATCH => actions::watch::watch,
+            SNAPSHOT => actions::snapshot::snapshot,
             {
                 // actions that need other arguments
-                AUTH => auth::auth(con, auth, iter)
+                AUTH => auth::auth(con, auth, iter),
+                SYS => admin::sys::sys(db, con, auth, iter),
+                SCRIPT => script::script(db, con, auth, iter)
             }
         );
+        ret
+    };
+    // a write whose response couldn't be sent has still been applied, so it's logged too
+    if let Some(sequence) = sequence {
+        if matches!(ret, Ok(()) | Err(ActionError::IoError(_))) {
+            sequence.log(db.get_ids(), &[self::query_args(buf)]).await;
+        }
     }
-    Ok(())
+    ret
 }
 
 /// Execute a stage **completely**. This means that action errors are never propagated
@@ -162,6 +548,8 @@ async fn execute_stage_pedantic<'a, C: BufferedSocketStream, P: ProtocolSpec>(
     match ret.await {
         Ok(()) => Ok(()),
         Err(ActionError::ActionError(e)) => con._write_raw(e).await,
+        Err(ActionError::ArityError(e)) => con._write_raw(&responses::arity_error::<P>(&e)).await,
+        Err(ActionError::Moved(moved)) => con._write_raw(&responses::moved::<P>(&moved)).await,
         Err(ActionError::IoError(ioe)) => Err(ioe),
     }
 }
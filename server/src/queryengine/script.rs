@@ -0,0 +1,374 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Scripts
+//!
+//! Scripts let users register a batch of writes once (with `SCRIPT LOAD`) and then run it any
+//! number of times with different arguments (with `EVAL`). Scripts are deliberately **not** a
+//! general purpose language (there's no embedded Lua or WASM runtime): a script is a list of statements
+//! separated by `;`, where each statement is a `SET`, `UPDATE`, `USET` or `DEL` followed by its
+//! arguments. An argument is either a literal or a parameter (`$1`, `$2`, ...) that is bound to
+//! the arguments passed to `EVAL`. For example:
+//! ```text
+//! SCRIPT LOAD rotate "DEL $1; SET $2 $3; USET last-rotated $2"
+//! EVAL rotate oldkey newkey newvalue
+//! ```
+//!
+//! ## Guarantees
+//! - **Atomicity**: a script is run as a single transaction on the current table, so either all
+//! of its statements are applied, or none of them are. The transaction holds the table's
+//! transaction lock exclusively, so no other write can interleave with it and no other
+//! connection can see it half-applied (see [`KVEStandard::apply_transaction`])
+//! - **Sandboxing**: a script can only write to the table that it's run on, and can't run any
+//! other action
+//! - **Determinism**: there are no branches, loops, clocks or random numbers, so running a script
+//! with the same arguments against the same data always results in the same writes
+//!
+//! Scripts are compiled when they're loaded and the compiled scripts are cached (by name) for
+//! the lifetime of the server. They aren't persisted. Since the cache is shared by every
+//! connection, only superusers can load or drop scripts, a script's source can be at most
+//! [`MAX_SCRIPT_LEN`] bytes long and at most [`MAX_SCRIPTS`] scripts can be cached at a time
+//!
+//! [`KVEStandard::apply_transaction`]: crate::kvengine::KVEStandard::apply_transaction
+
+use {
+    crate::{
+        corestore::{htable::Coremap, SharedSlice},
+        dbnet::prelude::*,
+        kvengine::txn::TxnOp,
+        storage::v1::wal,
+    },
+    parking_lot::Mutex,
+    std::sync::Arc,
+};
+
+const LOAD: &[u8] = b"load";
+const DROP: &[u8] = b"drop";
+/// The maximum number of scripts that can be cached at a time
+pub const MAX_SCRIPTS: usize = 1024;
+/// The maximum length of a script's source, in bytes
+pub const MAX_SCRIPT_LEN: usize = 64 * 1024;
+
+#[derive(Debug, PartialEq)]
+/// An argument to a statement
+enum Arg {
+    Literal(SharedSlice),
+    /// a parameter (zero-indexed)
+    Param(usize),
+}
+
+impl Arg {
+    fn parse(token: &[u8]) -> Option<Self> {
+        match token.strip_prefix(b"$") {
+            Some(digits) => {
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+                let param: usize = std::str::from_utf8(digits).ok()?.parse().ok()?;
+                // parameters are one-indexed
+                param.checked_sub(1).map(Self::Param)
+            }
+            None => Some(Self::Literal(SharedSlice::new(token))),
+        }
+    }
+    fn bind(&self, args: &[SharedSlice]) -> SharedSlice {
+        match self {
+            Self::Literal(literal) => literal.clone(),
+            Self::Param(param) => args[*param].clone(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// A statement in a script. These map one-to-one to [`TxnOp`]s
+enum Statement {
+    Set(Arg, Arg),
+    Update(Arg, Arg),
+    Upsert(Arg, Arg),
+    Del(Arg),
+}
+
+#[derive(Debug, PartialEq)]
+/// A compiled script
+pub struct Script {
+    statements: Vec<Statement>,
+    /// the number of arguments that the script needs
+    arity: usize,
+}
+
+impl Script {
+    /// Compile a script. Returns `None` if the script is malformed or empty
+    pub fn compile(src: &[u8]) -> Option<Self> {
+        let mut statements = Vec::new();
+        let mut arity = 0;
+        for statement in src.split(|b| *b == b';') {
+            let mut tokens = statement
+                .split(u8::is_ascii_whitespace)
+                .filter(|token| !token.is_empty());
+            let action = match tokens.next() {
+                Some(action) => action.to_ascii_uppercase(),
+                // an empty statement (a trailing `;`, for example)
+                None => continue,
+            };
+            let mut args = Vec::with_capacity(2);
+            for token in tokens {
+                let arg = Arg::parse(token)?;
+                if let Arg::Param(param) = arg {
+                    arity = arity.max(param + 1);
+                }
+                args.push(arg);
+            }
+            let statement = match action.as_slice() {
+                b"SET" | b"UPDATE" | b"USET" => {
+                    let [key, value] = <[Arg; 2]>::try_from(args).ok()?;
+                    match action.as_slice() {
+                        b"SET" => Statement::Set(key, value),
+                        b"UPDATE" => Statement::Update(key, value),
+                        _ => Statement::Upsert(key, value),
+                    }
+                }
+                b"DEL" => {
+                    let [key] = <[Arg; 1]>::try_from(args).ok()?;
+                    Statement::Del(key)
+                }
+                _ => return None,
+            };
+            statements.push(statement);
+        }
+        if statements.is_empty() {
+            None
+        } else {
+            Some(Self { statements, arity })
+        }
+    }
+    /// Returns the number of arguments that the script needs
+    pub const fn arity(&self) -> usize {
+        self.arity
+    }
+    /// Bind the arguments to the script's parameters, returning the writes to apply. The caller
+    /// must ensure that exactly [`Self::arity`] arguments are passed
+    pub fn bind(&self, args: &[SharedSlice]) -> Vec<TxnOp> {
+        self.statements
+            .iter()
+            .map(|statement| match statement {
+                Statement::Set(k, v) => TxnOp::Set(k.bind(args), v.bind(args)),
+                Statement::Update(k, v) => TxnOp::Update(k.bind(args), v.bind(args)),
+                Statement::Upsert(k, v) => TxnOp::Upsert(k.bind(args), v.bind(args)),
+                Statement::Del(k) => TxnOp::Del(k.bind(args)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+/// The compiled scripts, by name. This is shared by all connections
+pub struct ScriptCache {
+    scripts: Coremap<SharedSlice, Arc<Script>>,
+    /// held by loads, so that concurrent loads can't push the cache past [`MAX_SCRIPTS`]
+    load_lock: Mutex<()>,
+}
+
+impl ScriptCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Cache a script, replacing any older script with the same name. Returns false (and
+    /// doesn't cache the script) if the cache is full and there's no older script to replace
+    pub fn load(&self, name: SharedSlice, script: Script) -> bool {
+        let _load = self.load_lock.lock();
+        if self.scripts.len() >= MAX_SCRIPTS && !self.scripts.contains_key(&name) {
+            return false;
+        }
+        self.scripts.upsert(name, Arc::new(script));
+        true
+    }
+    pub fn get(&self, name: &[u8]) -> Option<Arc<Script>> {
+        self.scripts.get_cloned(name)
+    }
+    /// Remove a script. Returns false if there was no such script
+    pub fn remove(&self, name: &[u8]) -> bool {
+        self.scripts.true_if_removed(name)
+    }
+}
+
+action!(
+    /// Run a `SCRIPT` query. Only superusers can run this
+    /// ## Syntax
+    /// - `SCRIPT LOAD <name> <source>`: compile and cache a script, replacing any older script
+    /// with the same name
+    /// - `SCRIPT DROP <name>`: remove a script
+    fn script(
+        handle: &Corestore,
+        con: &mut Connection<C, P>,
+        auth: &mut AuthProviderHandle,
+        act: ActionIter<'_>,
+    ) {
+        let mut act = act;
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        auth.provider().ensure_superuser::<P>()?;
+        match unsafe { act.next_lowercase_unchecked() }.as_ref() {
+            LOAD => {
+                ensure_subcommand_arity(act.len(), Arity::Exactly(2), b"SCRIPT LOAD")?;
+                let (name, src) = unsafe {
+                    // UNSAFE(@ohsayan): This is completely safe as we've already checked
+                    // that there are exactly 2 arguments
+                    (act.next_unchecked_bytes(), act.next_unchecked())
+                };
+                if src.len() > MAX_SCRIPT_LEN {
+                    return util::err(P::RSTRING_TOO_LARGE);
+                }
+                let script = match Script::compile(src) {
+                    Some(script) => script,
+                    None => return util::err(P::RSTRING_BAD_SCRIPT),
+                };
+                if !handle.get_scripts().load(name, script) {
+                    return util::err(P::RSTRING_SCRIPT_CACHE_FULL);
+                }
+                con._write_raw(P::RCODE_OKAY).await?;
+            }
+            DROP => {
+                ensure_subcommand_arity(act.len(), Arity::Exactly(1), b"SCRIPT DROP")?;
+                let name = unsafe {
+                    // UNSAFE(@ohsayan): This is completely safe as we've already checked
+                    // that there is exactly 1 argument
+                    act.next_unchecked()
+                };
+                if handle.get_scripts().remove(name) {
+                    con._write_raw(P::RCODE_OKAY).await?;
+                } else {
+                    return util::err(P::RCODE_NIL);
+                }
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Run an `EVAL` query. This runs a cached script on the current table, atomically
+    /// ## Syntax
+    /// `EVAL <name> <arg1> <arg2> ...`
+    fn eval(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_arity(act.len(), Arity::AtLeast(1))?;
+        let name = unsafe {
+            // UNSAFE(@ohsayan): This is completely safe as we've already checked
+            // that there is at least 1 argument
+            act.next_unchecked()
+        };
+        let script = match handle.get_scripts().get(name) {
+            Some(script) => script,
+            None => return util::err(P::RSTRING_SCRIPT_NOT_FOUND),
+        };
+        // the name counts too, so that the error matches the whole query
+        ensure_arity(act.len() + 1, Arity::Exactly(script.arity() + 1))?;
+        let args: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            let ops = script.bind(&args);
+            let scope = wal::Scope::current(handle);
+            let sequence = wal::sequence(wal::durability(handle), scope).await;
+            match kve.apply_transaction(&ops) {
+                Ok(true) => {
+                    // the writes are logged rather than the script, since scripts aren't persisted
+                    sequence.log_transaction(handle.get_ids(), &ops).await;
+                    con._write_raw(P::RCODE_OKAY).await?
+                }
+                Ok(false) => con._write_raw(P::RSTRING_TXN_ABORTED).await?,
+                Err(()) => con._write_raw(P::RCODE_ENCODING_ERROR).await?,
+            }
+        } else {
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+        Ok(())
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{Arg, Script, ScriptCache, Statement, TxnOp, MAX_SCRIPTS};
+
+    #[test]
+    fn compile_script() {
+        let script = Script::compile(b"del $1; SET $2 $3;\n uset last $2;").unwrap();
+        assert_eq!(
+            script,
+            Script {
+                statements: vec![
+                    Statement::Del(Arg::Param(0)),
+                    Statement::Set(Arg::Param(1), Arg::Param(2)),
+                    Statement::Upsert(Arg::Literal("last".into()), Arg::Param(1)),
+                ],
+                arity: 3,
+            }
+        );
+    }
+    #[test]
+    fn compile_bad_script() {
+        let bad: [&[u8]; 8] = [
+            b"",
+            b" ; ;",
+            b"GET $1",
+            b"SET $1",
+            b"DEL $1 $2",
+            b"SET $0 x",
+            b"SET $ x",
+            b"SET $1a x",
+        ];
+        for src in bad {
+            assert!(
+                Script::compile(src).is_none(),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    #[test]
+    fn bind_script() {
+        let script = Script::compile(b"UPDATE $2 v; DEL $1").unwrap();
+        assert_eq!(script.arity(), 2);
+        assert_eq!(
+            script.bind(&["a".into(), "b".into()]),
+            vec![
+                TxnOp::Update("b".into(), "v".into()),
+                TxnOp::Del("a".into())
+            ]
+        );
+    }
+    #[test]
+    fn script_cache_is_capped() {
+        let cache = ScriptCache::new();
+        let script = || Script::compile(b"DEL $1").unwrap();
+        for i in 0..MAX_SCRIPTS {
+            assert!(cache.load(format!("script{i}").as_str().into(), script()));
+        }
+        assert!(!cache.load("one-too-many".into(), script()));
+        assert!(cache.get(b"one-too-many").is_none());
+        // replacing a script doesn't grow the cache
+        assert!(cache.load("script0".into(), script()));
+        // and dropping one makes room again
+        assert!(cache.remove(b"script1"));
+        assert!(cache.load("one-too-many".into(), script()));
+        assert_eq!(cache.scripts.len(), MAX_SCRIPTS);
+    }
+}
@@ -81,6 +81,8 @@ static FLUSH_STATE: QuickLock<()> = QuickLock::new(());
 /// The preload trip switch
 static PRELOAD_TRIPSWITCH: Trip = Trip::new_untripped();
 static CLEANUP_TRIPSWITCH: Trip = Trip::new_untripped();
+/// The global read-only switch
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
 
 /// Check the global system state
 pub fn state_okay() -> bool {
@@ -103,6 +105,17 @@ pub fn unpoison() {
     GLOBAL_STATE.store(true, ORD_REL)
 }
 
+/// Check if the server is read-only (writes are rejected with
+/// [`ProtocolSpec::RSTRING_READ_ONLY`](crate::protocol::interface::ProtocolSpec::RSTRING_READ_ONLY))
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(ORD_ACQ)
+}
+
+/// Make the server read-only (or writable again). Returns the previous state
+pub fn set_read_only(read_only: bool) -> bool {
+    READ_ONLY.swap(read_only, ORD_SEQ)
+}
+
 /// Get a static reference to the global preload trip switch
 pub fn get_preload_tripswitch() -> &'static Trip {
     &PRELOAD_TRIPSWITCH
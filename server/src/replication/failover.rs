@@ -0,0 +1,147 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Failover
+//!
+//! A primary hands over to one of its replicas by feeding it a handover (after every write
+//! that it applied) and then asking it to promote itself once it has the handover. The old
+//! primary then replicates the new one (see the [module docs](super#failover))
+
+use {
+    super::{Feed, Primary, HANDING_OVER, PROGRESS, REPLICATING},
+    crate::{corestore::Corestore, dbnet::peer, storage::v1::wal, IoResult},
+    core::sync::atomic::Ordering,
+    std::{
+        io::{Error as IoError, ErrorKind},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tokio::time::{self, Duration},
+};
+
+/// The longest that a replica waits for the handover before it gives up
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(5);
+/// The longest that a primary waits for the replica to promote itself (this covers connecting
+/// to it, and the replica's own wait)
+const FAILOVER_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Promote ourselves to a primary, keeping the data that we have. If we're given the ID of a
+/// handover, we first wait (for a little while) till our primary feeds it to us. Returns false
+/// if it didn't, in which case we go on replicating it
+pub async fn promote(handover: Option<u64>) -> bool {
+    if let Some(handover) = handover {
+        let progress = PROGRESS
+            .lock()
+            .as_ref()
+            .map(|progress| progress.handover.subscribe());
+        let mut handed_over = match progress {
+            Some(handed_over) => handed_over,
+            // we aren't replicating anyone, so no one's handing over to us
+            None => return false,
+        };
+        let arrived = time::timeout(HANDOVER_TIMEOUT, async {
+            while *handed_over.borrow() != handover {
+                if handed_over.changed().await.is_err() {
+                    // we stopped replicating the primary in the meantime
+                    return false;
+                }
+            }
+            true
+        })
+        .await;
+        if !matches!(arrived, Ok(true)) {
+            return false;
+        }
+    }
+    if REPLICATING.load(Ordering::Acquire) {
+        super::stop().await;
+        log::info!("Promoted to a primary");
+    }
+    true
+}
+
+/// Hand over to the replica at the given address, and then replicate it. If this fails, we
+/// take writes again
+pub async fn run(db: &Corestore, replica: Primary) -> IoResult<()> {
+    if REPLICATING.load(Ordering::Acquire) {
+        return Err(self::failed("we're a replica"));
+    }
+    if !super::is_feeding() {
+        return Err(self::failed("there are no replicas to hand over to"));
+    }
+    if HANDING_OVER.swap(true, Ordering::AcqRel) {
+        return Err(self::failed("a failover is already in progress"));
+    }
+    let handed_over = self::hand_over(db, replica).await;
+    if handed_over.is_err() {
+        HANDING_OVER.store(false, Ordering::Release);
+    }
+    handed_over
+}
+
+async fn hand_over(db: &Corestore, replica: Primary) -> IoResult<()> {
+    let handover = self::handover_id();
+    {
+        // the writes that are being applied (and hence fed) go first. The ones that take their
+        // turn after this see that we're handing over, and are turned away
+        let _paused = wal::pause().await;
+        super::feed(Feed::Handover(handover));
+    }
+    log::info!("Handing over to the replica at {replica}");
+    let handover = handover.to_string();
+    let promoted = time::timeout(FAILOVER_TIMEOUT, async {
+        let login = replica.login.as_ref();
+        let mut stream = peer::connect(&replica.host, replica.port, login).await?;
+        peer::query(
+            &mut stream,
+            &[b"SYS", b"REPLICAOF", b"NO", b"ONE", handover.as_bytes()],
+        )
+        .await
+    })
+    .await
+    .map_err(|_| {
+        IoError::new(
+            ErrorKind::TimedOut,
+            "the replica didn't promote itself in time",
+        )
+    })?;
+    promoted?;
+    // we're a replica before the writes are let through again
+    super::replicate(db, replica);
+    HANDING_OVER.store(false, Ordering::Release);
+    Ok(())
+}
+
+/// Returns a (practically) unique ID for a handover. It's never zero
+fn handover_id() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (now.as_nanos() as u64).max(1)
+}
+
+fn failed(reason: &str) -> IoError {
+    IoError::new(ErrorKind::Other, reason)
+}
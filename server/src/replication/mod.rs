@@ -0,0 +1,416 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Replication
+//!
+//! A server can keep a copy of the data of another server (its primary) that follows the
+//! writes made on it: `SYS REPLICAOF <host> <port> [<user> <token>]` turns the server into a
+//! replica of the primary at that address (in place of the one that it was replicating, if
+//! any). The replica connects to the primary over Skyhash 2.0 (logging in as the given user if
+//! the primary has auth enabled) and runs `SYS SYNC`, which makes the primary send it:
+//! 1. A full sync: a copy of all the data (every keyspace, and every table in it as an export
+//! archive; see [`crate::storage::v1::export`]). This replaces all the data on the replica,
+//! and it's flushed right away
+//! 2. Every write made on the primary after the copy was taken, as it's made. The replica runs
+//! them again, in the same order
+//!
+//! Replication is asynchronous: the primary doesn't wait for its replicas before it
+//! acknowledges a write. If the connection breaks (or goes quiet for too long), the replica
+//! connects again and starts over with a full sync.
+//!
+//! ## The stream
+//! Once `SYS SYNC` is acknowledged, the primary only sends frames. A frame is
+//! `[u8 kind][u64 length][payload]` (little endian), where the kind is one of:
+//! - `K`: a keyspace (the payload is its name). The tables in it follow
+//! - `T`: a table in the last keyspace (the payload is an export archive)
+//! - `E`: the expiry deadlines in the last table (the payload is `[u32 length][key][u64
+//! deadline]` for every key that has one)
+//! - `S`: the full sync is complete
+//! - `W`: a write, encoded like a record in the write-ahead log (see [`wal`])
+//! - `P`: a ping, sent when nothing else was sent for a second, so that both ends can tell if
+//! the other one is gone (the payload is the primary's time, in milliseconds since the epoch)
+//! - `H`: the primary is handing over to one of its replicas (the payload is the ID of the
+//! handover; see [Failover](#failover))
+//! - `R`: the replica has to sync again, since the data on the primary was replaced (by a
+//! snapshot restore or an import) or since it fell behind by more than [`FEED_CAPACITY`] writes
+//!
+//! ## Ordering
+//! While there's a replica to feed, writes take turns like they do for the write-ahead log, so
+//! that the writes to a table are fed in the order that they're applied (see
+//! [`wal::sequence`]). The copy is taken while no writes are applied (see [`wal::pause`]), so
+//! that every write is either in the copy or fed after it. Writes wait while the copy is taken, like they wait for BGSAVE while
+//! the write-ahead log is on.
+//!
+//! ## Replicas
+//! A replica turns away the writes of its clients with `read-only-replica` (the writes that
+//! its primary feeds it are always applied), unless `replication.read_only` is turned off.
+//! `SYS REPLICATION INFO` returns how replication is doing, as pairs of names and values:
+//! - `role`: `primary` or `replica`
+//! - `primary`: the address of the primary (empty on a primary)
+//! - `link`: `down` (connecting, or waiting to connect again), `syncing` (loading a full
+//! sync) or `up` (applying the writes that are fed). This is `none` on a primary
+//! - `lag-ms`: how long the last write (or ping) took to reach us, going by the primary's
+//! clock. A primary that's gone quiet shows up in `last-contact-ms` instead
+//! - `last-contact-ms`: the time since the primary last sent anything (or since we started
+//! replicating it, if it didn't)
+//! - `read-only`: whether writes are turned away since we're a replica
+//! - `replicas`: the number of replicas that we're feeding
+//!
+//! So a load balancer can send reads to a replica if its link is `up`, and its lag (and last
+//! contact) is as small as the reads need it to be.
+//!
+//! ## Failover
+//! `SYS REPLICAOF NO ONE` promotes a replica: it stops replicating its primary and takes writes
+//! again, keeping the data that it has. To hand over to a replica without losing any writes,
+//! run `SYS FAILOVER <host> <port> [<user> <token>]` on the primary instead, with the address
+//! of the replica (and the login to use on it). The primary then:
+//! 1. Turns away the writes of its clients (with `read-only-replica`), once the writes that
+//! are being applied are complete
+//! 2. Feeds an `H` frame to its replicas, after every write that it applied
+//! 3. Runs `SYS REPLICAOF NO ONE <handover>` on the replica, which waits for the `H` frame
+//! with that ID (so that it has every write) before it promotes itself
+//! 4. Replicates the replica (like it does with `SYS REPLICAOF`)
+//!
+//! If the replica doesn't promote itself in time, the primary takes writes again and the
+//! failover fails with `err-failover-failed`.
+//!
+//! ## Caveats
+//! - The replica connects over plain TCP, so the primary needs a port without TLS
+//! - Users (and the `AUTH` actions) aren't replicated
+//! - Relative TTLs (like the ones set by `EXPIRE`) start over when the writes are run again,
+//! and keys removed by the expiry sweeper or evicted from volatile tables on the primary are
+//! removed by the replica on its own
+//! - Writes made on a replica that isn't read-only aren't sent anywhere, and they're lost with
+//! the next full sync
+//! - A replica doesn't remember its primary across restarts
+//! - The other replicas aren't moved to the new primary by a failover. They go on replicating
+//! the old primary, which now replicates the new one
+//! - If the replica promoted itself but the old primary didn't hear back from it in time, both
+//! of them take writes. Check `SYS REPLICATION INFO` on both when a failover fails
+
+use {
+    crate::{config::ReplicationConfig, corestore::Corestore},
+    chrono::Utc,
+    core::{
+        fmt,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    parking_lot::{const_mutex, Mutex},
+    std::{sync::Arc, time::Instant},
+    tokio::{
+        sync::{
+            broadcast::{self, Receiver, Sender},
+            watch,
+        },
+        task::JoinHandle,
+    },
+};
+
+pub mod failover;
+pub mod primary;
+mod replica;
+
+/// The number of writes that a replica can fall behind by before it has to sync again
+const FEED_CAPACITY: usize = 16384;
+/// The number of replicas that are being fed
+static REPLICAS: AtomicUsize = AtomicUsize::new(0);
+/// The feed of writes (created when the first replica syncs, and replaced when the replicas
+/// have to sync again)
+static FEED: Mutex<Option<Sender<Arc<Feed>>>> = const_mutex(None);
+/// The primary that we're replicating (if any)
+static LINK: Mutex<Option<Link>> = const_mutex(None);
+/// Set while we're replicating a primary
+static REPLICATING: AtomicBool = AtomicBool::new(false);
+/// Whether a replica turns away the writes of its clients (`replication.read_only`)
+static READ_ONLY: AtomicBool = AtomicBool::new(true);
+/// How the link to the primary is doing (if we're replicating one)
+static PROGRESS: Mutex<Option<Progress>> = const_mutex(None);
+/// Set while we're handing over to a replica (see [`failover`])
+static HANDING_OVER: AtomicBool = AtomicBool::new(false);
+
+/// A keyspace
+const FRAME_KEYSPACE: u8 = b'K';
+/// A table in the last keyspace
+const FRAME_TABLE: u8 = b'T';
+/// The expiry deadlines in the last table
+const FRAME_EXPIRIES: u8 = b'E';
+/// The full sync is complete
+const FRAME_SYNCED: u8 = b'S';
+/// A write
+const FRAME_WRITE: u8 = b'W';
+/// Nothing happened
+const FRAME_PING: u8 = b'P';
+/// Sync again
+const FRAME_RESYNC: u8 = b'R';
+/// The primary is handing over
+const FRAME_HANDOVER: u8 = b'H';
+/// The size of the kind and the length in front of every frame
+const FRAME_HEADER_SIZE: usize = 9;
+
+/// Apply the replication settings (on startup, and on every reload)
+pub fn configure(config: ReplicationConfig) {
+    READ_ONLY.store(config.read_only, Ordering::Release);
+}
+
+/// Returns true if the writes of clients are turned away since we're a (read-only) replica, or
+/// since we're handing over to one. The writes that the primary feeds us are applied regardless
+pub fn rejects_writes() -> bool {
+    (REPLICATING.load(Ordering::Acquire) && READ_ONLY.load(Ordering::Acquire))
+        || HANDING_OVER.load(Ordering::Acquire)
+}
+
+/// Returns true if there's a replica to feed the writes to (so that writes take their turn;
+/// see [`crate::storage::v1::wal::sequence`])
+pub fn is_feeding() -> bool {
+    REPLICAS.load(Ordering::Acquire) != 0
+}
+
+/// Feed a write (encoded as a record of the write-ahead log) to the replicas. This has to be
+/// called in the write's turn
+pub fn publish(record: Vec<u8>) {
+    self::feed(Feed::Write(record))
+}
+
+/// What's fed to the replicas
+enum Feed {
+    /// a write, encoded as a record of the write-ahead log
+    Write(Vec<u8>),
+    /// a handover (see [`failover`]), with its ID
+    Handover(u64),
+}
+
+fn feed(fed: Feed) {
+    if let Some(feed) = FEED.lock().as_ref() {
+        // there's no one to receive this if the last replica went away in the meantime
+        let _ = feed.send(Arc::new(fed));
+    }
+}
+
+/// Make every replica that's being fed sync again (once it has the writes that it was fed so
+/// far). This has to be called while no writes are applied
+pub fn resync() {
+    FEED.lock().take();
+}
+
+/// Start receiving the writes. This has to be called while no writes are applied (see
+/// [`crate::storage::v1::wal::pause`]), right before the data is copied
+fn subscribe() -> Receiver<Arc<Feed>> {
+    FEED.lock()
+        .get_or_insert_with(|| broadcast::channel(FEED_CAPACITY).0)
+        .subscribe()
+}
+
+/// A replica that's being fed (from before its data is copied). Writes take their turn while
+/// this is around
+struct Replica;
+
+impl Replica {
+    fn attach() -> Self {
+        REPLICAS.fetch_add(1, Ordering::AcqRel);
+        Self
+    }
+}
+
+impl Drop for Replica {
+    fn drop(&mut self) {
+        REPLICAS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Returns the header of a frame with the given kind and the length of its payload
+fn frame_header(kind: u8, len: usize) -> [u8; FRAME_HEADER_SIZE] {
+    let mut header = [0; FRAME_HEADER_SIZE];
+    header[0] = kind;
+    header[1..].copy_from_slice(&(len as u64).to_le_bytes());
+    header
+}
+
+/// Append a frame to `buf`
+fn encode_frame(buf: &mut Vec<u8>, kind: u8, payload: &[u8]) {
+    buf.extend(self::frame_header(kind, payload.len()));
+    buf.extend(payload);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The server that a replica replicates
+pub struct Primary {
+    pub host: String,
+    pub port: u16,
+    /// the user to log in as, and their token (if the primary has auth enabled)
+    pub login: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl fmt::Display for Primary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// The task that replicates the primary
+struct Link {
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+/// Start replicating the given primary (in the background), in place of the one that we're
+/// replicating (if any)
+pub fn replicate(db: &Corestore, primary: Primary) {
+    let mut link = LINK.lock();
+    // the new task waits for the old one, so that they never apply writes at the same time
+    let previous = link.take().map(|previous| {
+        let _ = previous.stop.send(true);
+        previous.task
+    });
+    log::info!("Replicating the primary at {primary}");
+    *PROGRESS.lock() = Some(Progress::new(primary.to_string()));
+    REPLICATING.store(true, Ordering::Release);
+    let (stop, stopped) = watch::channel(false);
+    let task = tokio::spawn(replica::run(db.clone(), primary, previous, stopped));
+    *link = Some(Link { stop, task });
+}
+
+/// Stop replicating the primary (if we are). This waits for the write that's being applied (or
+/// the full sync that's being loaded) to complete
+pub async fn stop() {
+    let link = LINK.lock().take();
+    if let Some(link) = link {
+        REPLICATING.store(false, Ordering::Release);
+        PROGRESS.lock().take();
+        let _ = link.stop.send(true);
+        let _ = link.task.await;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The state of the link to the primary
+pub enum LinkState {
+    /// we're connecting to the primary (or waiting to connect again)
+    Down,
+    /// we're loading a full sync
+    Syncing,
+    /// we're applying the writes that the primary feeds us
+    Up,
+}
+
+impl LinkState {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Down => "down",
+            Self::Syncing => "syncing",
+            Self::Up => "up",
+        }
+    }
+}
+
+/// How the link to the primary is doing
+struct Progress {
+    primary: String,
+    link: LinkState,
+    /// how long the last write (or ping) took to reach us, in milliseconds
+    lag: u64,
+    /// when the primary last sent anything (or when we started replicating it)
+    last_contact: Instant,
+    /// the ID of the last handover that the primary fed us (zero if there wasn't one)
+    handover: watch::Sender<u64>,
+}
+
+impl Progress {
+    fn new(primary: String) -> Self {
+        Self {
+            primary,
+            link: LinkState::Down,
+            lag: 0,
+            last_contact: Instant::now(),
+            handover: watch::channel(0).0,
+        }
+    }
+}
+
+/// Record the state of the link to the primary
+fn set_link(link: LinkState) {
+    if let Some(progress) = PROGRESS.lock().as_mut() {
+        progress.link = link;
+    }
+}
+
+/// Record that the primary sent us something, along with the time that it was sent at (in
+/// milliseconds since the epoch, going by the primary's clock) if we know it
+fn record_contact(sent_at: Option<u64>) {
+    if let Some(progress) = PROGRESS.lock().as_mut() {
+        progress.last_contact = Instant::now();
+        if let Some(sent_at) = sent_at {
+            progress.lag = (Utc::now().timestamp_millis() as u64).saturating_sub(sent_at);
+        }
+    }
+}
+
+/// Record that the primary handed over to one of its replicas (which may be us)
+fn record_handover(handover: u64) {
+    if let Some(progress) = PROGRESS.lock().as_ref() {
+        progress.handover.send_replace(handover);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// How a replica's link to its primary is doing
+pub struct ReplicaInfo {
+    /// the address of the primary
+    pub primary: String,
+    pub link: LinkState,
+    /// how long the last write (or ping) took to reach us, in milliseconds
+    pub lag: u64,
+    /// the time since the primary last sent anything, in milliseconds
+    pub last_contact: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// How replication is doing (see `SYS REPLICATION INFO`)
+pub struct Info {
+    /// the link to the primary (if we're a replica)
+    pub replica: Option<ReplicaInfo>,
+    /// whether writes are turned away since we're a replica
+    pub read_only: bool,
+    /// the number of replicas that we're feeding
+    pub replicas: usize,
+}
+
+/// Returns how replication is doing
+pub fn info() -> Info {
+    let replica = PROGRESS.lock().as_ref().map(|progress| ReplicaInfo {
+        primary: progress.primary.clone(),
+        link: progress.link,
+        lag: progress.lag,
+        last_contact: progress.last_contact.elapsed().as_millis() as u64,
+    });
+    Info {
+        replica,
+        read_only: self::rejects_writes(),
+        replicas: REPLICAS.load(Ordering::Acquire),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,184 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # The primary's end
+//!
+//! A replica runs `SYS SYNC` on a connection to get a copy of the data, and the connection is
+//! then used to feed it the writes (see the [module docs](super))
+
+use {
+    super::{
+        Feed, Replica, FRAME_EXPIRIES, FRAME_HANDOVER, FRAME_KEYSPACE, FRAME_PING, FRAME_RESYNC,
+        FRAME_SYNCED, FRAME_TABLE, FRAME_WRITE,
+    },
+    crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID, SYSTEM},
+            table::Table,
+        },
+        dbnet::{prelude::*, BufferedSocketStream},
+        storage::v1::{
+            export::{self, ExportResult},
+            wal,
+        },
+        IoResult,
+    },
+    chrono::Utc,
+    std::sync::Arc,
+    tokio::{
+        sync::broadcast::error::{RecvError, TryRecvError},
+        time::{self, Duration},
+    },
+};
+
+/// The longest that the stream goes quiet for (a ping is sent otherwise)
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The tables in a keyspace, along with their names
+type Tables = Vec<(ObjectID, Arc<Table>)>;
+
+action! {
+    /// Send a copy of the data to a replica and then feed it every write made after it (till
+    /// it goes away or has to sync again)
+    fn sync(handle: &Corestore, con: &mut Connection<C, P>) {
+        let _replica = Replica::attach();
+        let paused = wal::pause().await;
+        let mut feed = super::subscribe();
+        let keyspaces = self::collect(handle.get_store());
+        let copied = tokio::task::spawn_blocking(move || {
+            // the writes go on once the copy is taken
+            let _paused = paused;
+            self::encode_full_sync(&keyspaces)
+        })
+        .await
+        .expect("Something caused the full sync to panic");
+        let copy = match copied {
+            Ok(copy) => copy,
+            Err(e) => {
+                log::error!("Failed to copy the data for a replica: {e}");
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+        };
+        con._write_raw(P::RCODE_OKAY).await?;
+        con._write_raw(&copy).await?;
+        con.flush().await?;
+        drop(copy);
+        let peer = con.peer().map(|peer| peer.to_string()).unwrap_or_default();
+        log::info!("Sent a full sync to the replica at {peer}");
+        loop {
+            let mut next = tokio::select! {
+                next = feed.recv() => next,
+                _ = time::sleep(PING_INTERVAL) => {
+                    let now = Utc::now().timestamp_millis() as u64;
+                    self::write_frame(con, FRAME_PING, &now.to_le_bytes()).await?;
+                    con.flush().await?;
+                    continue;
+                }
+            };
+            // send everything that's there before we flush
+            loop {
+                match next {
+                    Ok(fed) => match fed.as_ref() {
+                        Feed::Write(record) => self::write_frame(con, FRAME_WRITE, record).await?,
+                        Feed::Handover(handover) => {
+                            self::write_frame(con, FRAME_HANDOVER, &handover.to_le_bytes()).await?
+                        }
+                    },
+                    Err(e) => {
+                        if matches!(e, RecvError::Lagged(_)) {
+                            log::warn!("The replica at {peer} fell behind, so it has to sync again");
+                        }
+                        self::write_frame(con, FRAME_RESYNC, &[]).await?;
+                        con.flush().await?;
+                        return Ok(());
+                    }
+                }
+                next = match feed.try_recv() {
+                    Ok(fed) => Ok(fed),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Lagged(n)) => Err(RecvError::Lagged(n)),
+                    Err(TryRecvError::Closed) => Err(RecvError::Closed),
+                };
+            }
+            con.flush().await?;
+        }
+    }
+}
+
+async fn write_frame<C: BufferedSocketStream, P: ProtocolSpec>(
+    con: &mut Connection<C, P>,
+    kind: u8,
+    payload: &[u8],
+) -> IoResult<()> {
+    con._write_raw(&super::frame_header(kind, payload.len()))
+        .await?;
+    con._write_raw(payload).await
+}
+
+/// Returns the keyspaces (other than the system keyspace) and their tables
+pub(super) fn collect(store: &Memstore) -> Vec<(ObjectID, Tables)> {
+    store
+        .keyspaces
+        .iter()
+        .filter(|ks| ks.key() != &SYSTEM)
+        .map(|ks| {
+            let tables = ks
+                .value()
+                .tables
+                .iter()
+                .map(|tbl| (tbl.key().clone(), tbl.value().clone()))
+                .collect();
+            (ks.key().clone(), tables)
+        })
+        .collect()
+}
+
+/// Encode a full sync of the given keyspaces (up to and including the `S` frame)
+pub(super) fn encode_full_sync(keyspaces: &[(ObjectID, Tables)]) -> ExportResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    for (ksid, tables) in keyspaces {
+        super::encode_frame(&mut buf, FRAME_KEYSPACE, ksid);
+        let keyspace = String::from_utf8_lossy(ksid);
+        for (tblid, table) in tables {
+            let name = String::from_utf8_lossy(tblid);
+            let archive = export::encode(&keyspace, &name, table)?;
+            super::encode_frame(&mut buf, FRAME_TABLE, &archive);
+            // the archives don't have the deadlines
+            let deadlines = table.deadlines();
+            if !deadlines.is_empty() {
+                let mut payload = Vec::new();
+                for (key, deadline) in deadlines {
+                    payload.extend((key.len() as u32).to_le_bytes());
+                    payload.extend_from_slice(&key);
+                    payload.extend(deadline.to_le_bytes());
+                }
+                super::encode_frame(&mut buf, FRAME_EXPIRIES, &payload);
+            }
+        }
+    }
+    super::encode_frame(&mut buf, FRAME_SYNCED, &[]);
+    Ok(buf)
+}
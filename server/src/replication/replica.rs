@@ -0,0 +1,323 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # The replica's end
+//!
+//! A replica connects to its primary, loads the full sync that it sends and then applies the
+//! writes that it's fed, till it's told to stop. When the connection is lost, it connects (and
+//! syncs) again, waiting a little longer after every failed attempt
+
+use {
+    super::{
+        LinkState, Primary, FRAME_EXPIRIES, FRAME_HANDOVER, FRAME_KEYSPACE, FRAME_PING,
+        FRAME_RESYNC, FRAME_SYNCED, FRAME_TABLE, FRAME_WRITE,
+    },
+    crate::{
+        auth::AuthProvider,
+        corestore::{
+            htable::Coremap,
+            memstore::{Keyspace, Memstore, ObjectID, SystemKeyspace, SYSTEM},
+            table::Table,
+            Corestore,
+        },
+        dbnet::{
+            peer::{self, Peer},
+            AuthProviderHandle,
+        },
+        registry,
+        storage::v1::{
+            export,
+            flush::{self, Autoflush},
+            wal,
+        },
+        IoResult,
+    },
+    std::{
+        io::{Error as IoError, ErrorKind},
+        sync::Arc,
+    },
+    tokio::{
+        io::AsyncReadExt,
+        sync::watch,
+        task::JoinHandle,
+        time::{self, Duration},
+    },
+};
+
+/// The longest that the primary can stay quiet for (it pings us every second otherwise)
+const PRIMARY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The wait before connecting again, after the first failed attempt
+const RETRY_MIN: Duration = Duration::from_secs(1);
+/// The longest wait before connecting again
+const RETRY_MAX: Duration = Duration::from_secs(32);
+
+/// Why we stopped listening to the primary (if nothing went wrong)
+enum Ended {
+    /// we were told to stop
+    Stopped,
+    /// the primary asked us to sync again
+    Resync,
+}
+
+/// Replicate the primary till we're told to stop (see [`super::replicate`]). The task that
+/// replicated the previous primary (if any) is waited for first
+pub(super) async fn run(
+    db: Corestore,
+    primary: Primary,
+    previous: Option<JoinHandle<()>>,
+    mut stop: watch::Receiver<bool>,
+) {
+    if let Some(previous) = previous {
+        let _ = previous.await;
+    }
+    let mut retry = RETRY_MIN;
+    loop {
+        match self::replicate(&db, &primary, &mut stop, &mut retry).await {
+            Ok(Ended::Stopped) => break,
+            Ok(Ended::Resync) => log::info!("The primary at {primary} asked us to sync again"),
+            Err(e) => {
+                log::warn!(
+                    "Lost the primary at {primary} (connecting again in {}s): {e}",
+                    retry.as_secs()
+                );
+                tokio::select! {
+                    _ = time::sleep(retry) => {}
+                    _ = self::stopped(&mut stop) => break,
+                }
+                retry = (retry * 2).min(RETRY_MAX);
+            }
+        }
+    }
+    log::info!("Stopped replicating the primary at {primary}");
+}
+
+/// Resolves once we're told to stop
+async fn stopped(stop: &mut watch::Receiver<bool>) {
+    while !*stop.borrow() {
+        if stop.changed().await.is_err() {
+            // the link is gone, and so are we
+            return;
+        }
+    }
+}
+
+/// Connect to the primary, load its full sync and then apply the writes that it feeds us
+async fn replicate(
+    db: &Corestore,
+    primary: &Primary,
+    stop: &mut watch::Receiver<bool>,
+    retry: &mut Duration,
+) -> IoResult<Ended> {
+    super::set_link(LinkState::Down);
+    let connect = async {
+        let mut stream = peer::connect(&primary.host, primary.port, primary.login.as_ref()).await?;
+        peer::query(&mut stream, &[b"SYS", b"SYNC"]).await?;
+        IoResult::Ok(stream)
+    };
+    let mut stream = tokio::select! {
+        stream = connect => stream?,
+        _ = self::stopped(stop) => return Ok(Ended::Stopped),
+    };
+    super::set_link(LinkState::Syncing);
+    let mut full_sync = Some(FullSync::default());
+    // the writes were already authorized by the primary
+    let mut auth = AuthProviderHandle::new(AuthProvider::new_disabled());
+    loop {
+        // only waiting for a frame is cancelled; applying one never is
+        let (kind, payload) = tokio::select! {
+            frame = time::timeout(PRIMARY_TIMEOUT, self::read_frame(&mut stream)) => {
+                frame.map_err(|_| IoError::new(ErrorKind::TimedOut, "the primary went quiet"))??
+            }
+            _ = self::stopped(stop) => return Ok(Ended::Stopped),
+        };
+        match kind {
+            FRAME_PING => {
+                let sent_at = payload.as_slice().try_into().ok().map(u64::from_le_bytes);
+                super::record_contact(sent_at);
+            }
+            FRAME_RESYNC => return Ok(Ended::Resync),
+            FRAME_WRITE if full_sync.is_none() => {
+                let sent_at = wal::apply_record(db, &mut auth, &payload).await?;
+                super::record_contact(Some(sent_at));
+            }
+            FRAME_HANDOVER if full_sync.is_none() => {
+                let handover: [u8; 8] = payload
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| self::unexpected(kind))?;
+                super::record_handover(u64::from_le_bytes(handover));
+                super::record_contact(None);
+            }
+            FRAME_SYNCED => {
+                let store = full_sync
+                    .take()
+                    .ok_or_else(|| self::unexpected(kind))?
+                    .finish();
+                let db = db.clone();
+                tokio::task::spawn_blocking(move || self::install(&db, store))
+                    .await
+                    .expect("Something caused the full sync to panic")?;
+                log::info!("Synced with the primary at {primary}");
+                super::record_contact(None);
+                super::set_link(LinkState::Up);
+                *retry = RETRY_MIN;
+            }
+            _ => match full_sync.as_mut() {
+                Some(loading) => {
+                    loading.load(kind, &payload)?;
+                    super::record_contact(None);
+                }
+                None => return Err(self::unexpected(kind)),
+            },
+        }
+    }
+}
+
+/// Read a frame, returning its kind and its payload
+async fn read_frame(stream: &mut Peer) -> IoResult<(u8, Vec<u8>)> {
+    let kind = stream.read_u8().await?;
+    let len = stream.read_u64_le().await?;
+    // the length isn't trusted with an allocation; the payload has to actually be there
+    let mut payload = Vec::new();
+    (&mut *stream).take(len).read_to_end(&mut payload).await?;
+    if payload.len() as u64 != len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    Ok((kind, payload))
+}
+
+/// Replace our data with the full sync, and flush it
+fn install(db: &Corestore, restored: Memstore) -> IoResult<()> {
+    let store = db.get_store();
+    // the writes in the write-ahead log were made to the data that we're replacing, so it's
+    // checkpointed too (and the history breaks here)
+    let flushed = wal::checkpoint_replaced(|| {
+        store.replace_keyspaces(restored);
+        let _flush_lock = registry::lock_flush_state();
+        flush::flush_full(Autoflush, store)
+    });
+    if flushed.is_ok() {
+        registry::unpoison();
+    } else {
+        registry::poison();
+    }
+    flushed
+}
+
+fn unexpected(kind: u8) -> IoError {
+    IoError::new(
+        ErrorKind::InvalidData,
+        format!("unexpected frame `{}` from the primary", kind as char),
+    )
+}
+
+#[derive(Default)]
+/// A full sync, as it's being loaded
+pub(super) struct FullSync {
+    keyspaces: Vec<(ObjectID, Keyspace)>,
+    /// the table that was loaded last
+    last: Option<Arc<Table>>,
+}
+
+impl FullSync {
+    /// Load a frame of the full sync
+    pub(super) fn load(&mut self, kind: u8, payload: &[u8]) -> IoResult<()> {
+        match kind {
+            FRAME_KEYSPACE => {
+                self.keyspaces
+                    .push((self::object_id(payload)?, Keyspace::empty()));
+                self.last = None;
+            }
+            FRAME_TABLE => {
+                let (manifest, table) = export::decode(payload).map_err(|e| {
+                    IoError::new(
+                        ErrorKind::InvalidData,
+                        format!("bad table in full sync: {e}"),
+                    )
+                })?;
+                let (_, keyspace) = self
+                    .keyspaces
+                    .last()
+                    .ok_or_else(|| self::unexpected(kind))?;
+                let tblid = self::object_id(manifest.table.as_bytes())?;
+                if !keyspace.create_table(tblid.clone(), table) {
+                    return Err(IoError::new(
+                        ErrorKind::InvalidData,
+                        format!("table `{}` was sent twice", manifest.table),
+                    ));
+                }
+                self.last = keyspace.get_table_atomic_ref(&tblid);
+            }
+            FRAME_EXPIRIES => {
+                let table = self.last.as_ref().ok_or_else(|| self::unexpected(kind))?;
+                let mut payload = payload;
+                while !payload.is_empty() {
+                    let len = self::take(&mut payload, 4)?;
+                    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+                    let key = self::take(&mut payload, len)?;
+                    let deadline = self::take(&mut payload, 8)?;
+                    table.set_deadline(key, u64::from_le_bytes(deadline.try_into().unwrap()));
+                }
+            }
+            _ => return Err(self::unexpected(kind)),
+        }
+        Ok(())
+    }
+    /// Returns the loaded keyspaces, with an empty system keyspace (like the one that's read
+    /// from disk)
+    pub(super) fn finish(self) -> Memstore {
+        let keyspaces = Coremap::with_capacity(self.keyspaces.len() + 1);
+        for (ksid, keyspace) in self.keyspaces {
+            keyspaces.upsert(ksid, Arc::new(keyspace));
+        }
+        keyspaces.upsert(SYSTEM, Arc::new(Keyspace::empty()));
+        Memstore::init_with_all(keyspaces, SystemKeyspace::new(Coremap::new()))
+    }
+}
+
+/// Returns the name of a keyspace or a table
+fn object_id(name: &[u8]) -> IoResult<ObjectID> {
+    match ObjectID::try_from_slice(name) {
+        Some(id) if !name.is_empty() => Ok(id),
+        _ => Err(IoError::new(
+            ErrorKind::InvalidData,
+            format!("bad name `{}` in full sync", String::from_utf8_lossy(name)),
+        )),
+    }
+}
+
+/// Split `len` bytes off the front of `payload`
+fn take<'a>(payload: &mut &'a [u8], len: usize) -> IoResult<&'a [u8]> {
+    if payload.len() < len {
+        return Err(IoError::new(
+            ErrorKind::InvalidData,
+            "truncated expiries in full sync",
+        ));
+    }
+    let (taken, rest) = payload.split_at(len);
+    *payload = rest;
+    Ok(taken)
+}
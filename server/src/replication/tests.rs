@@ -0,0 +1,146 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{
+        failover, primary, replica::FullSync, FRAME_HEADER_SIZE, FRAME_KEYSPACE, FRAME_SYNCED,
+        FRAME_TABLE, FRAME_WRITE,
+    },
+    crate::corestore::{
+        memstore::{Memstore, ObjectID, SYSTEM},
+        table::Table,
+    },
+    std::io::ErrorKind,
+};
+
+/// Split a buffer of frames into their kinds and payloads
+fn frames(mut buf: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut frames = Vec::new();
+    while !buf.is_empty() {
+        let len = u64::from_le_bytes(buf[1..FRAME_HEADER_SIZE].try_into().unwrap()) as usize;
+        frames.push((buf[0], &buf[FRAME_HEADER_SIZE..FRAME_HEADER_SIZE + len]));
+        buf = &buf[FRAME_HEADER_SIZE + len..];
+    }
+    frames
+}
+
+#[test]
+fn test_frame_header() {
+    let header = super::frame_header(FRAME_WRITE, 300);
+    assert_eq!(header[0], b'W');
+    assert_eq!(header[1..], 300u64.to_le_bytes());
+    let mut buf = Vec::new();
+    super::encode_frame(&mut buf, FRAME_WRITE, b"record");
+    assert_eq!(frames(&buf), vec![(FRAME_WRITE, &b"record"[..])]);
+}
+
+#[test]
+fn test_full_sync_roundtrip() {
+    let store = Memstore::new_empty();
+    let ksid = unsafe { ObjectID::from_slice("myks") };
+    let tblid = unsafe { ObjectID::from_slice("mytbl") };
+    store.create_keyspace(ksid.clone());
+    store.create_keyspace(SYSTEM);
+    let keyspace = store.get_keyspace_atomic_ref(&ksid).unwrap();
+    keyspace.create_table(tblid.clone(), Table::new_default_kve());
+    let table = keyspace.get_table_atomic_ref(&tblid).unwrap();
+    let kve = table.get_kvstore().unwrap();
+    kve.set("hello".into(), "world".into()).unwrap();
+    kve.set("sayan".into(), "nandan".into()).unwrap();
+    assert!(table.set_deadline(b"hello", u64::MAX));
+    let encoded = primary::encode_full_sync(&primary::collect(&store)).unwrap();
+    let frames = frames(&encoded);
+    // the system keyspace isn't sent
+    assert_eq!(frames[0], (FRAME_KEYSPACE, &b"myks"[..]));
+    assert_eq!(frames[1].0, FRAME_TABLE);
+    assert_eq!(frames.last().unwrap(), &(FRAME_SYNCED, &[][..]));
+    let mut loading = FullSync::default();
+    for (kind, payload) in &frames[..frames.len() - 1] {
+        loading.load(*kind, payload).unwrap();
+    }
+    let loaded = loading.finish();
+    assert!(loaded.keyspaces.contains_key(&SYSTEM));
+    let table = loaded
+        .get_keyspace_atomic_ref(&ksid)
+        .unwrap()
+        .get_table_atomic_ref(&tblid)
+        .unwrap();
+    let kve = table.get_kvstore().unwrap();
+    assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+    assert_eq!(kve.get_cloned("sayan").unwrap().unwrap(), "nandan");
+    let deadlines = table.deadlines();
+    assert_eq!(deadlines.len(), 1);
+    assert_eq!(deadlines[0].0.as_ref(), b"hello");
+    assert_eq!(deadlines[0].1, u64::MAX);
+}
+
+#[test]
+fn test_full_sync_bad_frames() {
+    let bad = |frames: &[(u8, &[u8])]| {
+        let mut loading = FullSync::default();
+        frames
+            .iter()
+            .map(|(kind, payload)| loading.load(*kind, payload))
+            .find_map(Result::err)
+            .map(|e| e.kind())
+    };
+    let archive =
+        crate::storage::v1::export::encode("myks", "mytbl", &Table::new_default_kve()).unwrap();
+    // a table outside a keyspace
+    assert_eq!(
+        bad(&[(FRAME_TABLE, &archive)]),
+        Some(ErrorKind::InvalidData)
+    );
+    // expiries outside a table
+    assert_eq!(
+        bad(&[(FRAME_KEYSPACE, b"myks"), (b'E', &[0; 12])]),
+        Some(ErrorKind::InvalidData)
+    );
+    // truncated expiries
+    assert_eq!(
+        bad(&[
+            (FRAME_KEYSPACE, b"myks"),
+            (FRAME_TABLE, &archive),
+            (b'E', &[5, 0, 0, 0, b'h'])
+        ]),
+        Some(ErrorKind::InvalidData)
+    );
+    // a bad name, and a write in the middle of a full sync
+    assert_eq!(bad(&[(FRAME_KEYSPACE, &[])]), Some(ErrorKind::InvalidData));
+    assert_eq!(bad(&[(FRAME_WRITE, b"")]), Some(ErrorKind::InvalidData));
+    assert_eq!(
+        bad(&[(FRAME_KEYSPACE, b"myks"), (FRAME_TABLE, &archive)]),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_promote_without_primary() {
+    // there's nothing to stop, so this is a no-op
+    assert!(failover::promote(None).await);
+    // but no one can hand over to us
+    assert!(!failover::promote(Some(1)).await);
+}
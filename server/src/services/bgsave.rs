@@ -27,13 +27,14 @@
 use {
     crate::{
         config::BGSave,
-        corestore::Corestore,
-        registry,
-        storage::{self, v1::flush::Autoflush},
+        corestore::{memstore::ObjectID, Corestore},
+        health, metrics, registry,
+        storage::{self, v1::wal},
         IoResult,
     },
+    std::time::Instant,
     tokio::{
-        sync::broadcast::Receiver,
+        sync::{broadcast::Receiver, watch},
         time::{self, Duration},
     },
 };
@@ -41,36 +42,39 @@ use {
 /// The bgsave_scheduler calls the bgsave task in `Corestore` after `every` seconds
 ///
 /// The time after which the scheduler will wake up the BGSAVE task is determined by
-/// `bgsave_cfg` which is to be passed as an argument. If BGSAVE is disabled, this function
-/// immediately returns
-pub async fn bgsave_scheduler(handle: Corestore, bgsave_cfg: BGSave, mut terminator: Receiver<()>) {
-    match bgsave_cfg {
-        BGSave::Enabled(duration) => {
-            // If we're here - the user doesn't trust his power supply or just values
-            // his data - which is good! So we'll turn this into a `Duration`
-            let duration = Duration::from_secs(duration);
-            loop {
-                tokio::select! {
-                    // Sleep until `duration` from the current time instant
-                    _ = time::sleep_until(time::Instant::now() + duration) => {
-                        let cloned_handle = handle.clone();
-                        // we spawn this process just to ensure that it doesn't block the runtime's workers
-                        // dedicated to async tasks (non-blocking)
-                        tokio::task::spawn_blocking(move || {
-                            let owned_handle = cloned_handle;
-                            let _ = bgsave_blocking_section(owned_handle);
-                        }).await.expect("Something caused the background service to panic");
-                    }
-                    // Otherwise wait for a notification
-                    _ = terminator.recv() => {
-                        // we got a notification to quit; so break out
-                        break;
-                    }
-                }
+/// `bgsave_cfg` which is to be passed as an argument. The configuration can change while the
+/// server is running (see [`super::confreload`]); if BGSAVE is disabled, the scheduler just
+/// waits for it to be enabled again
+pub async fn bgsave_scheduler(
+    handle: Corestore,
+    mut bgsave_cfg: watch::Receiver<BGSave>,
+    mut terminator: Receiver<()>,
+) {
+    // we stop looking for changes if nobody can send them anymore
+    let mut reloadable = true;
+    loop {
+        let every = match *bgsave_cfg.borrow() {
+            BGSave::Enabled(every) => Some(Duration::from_secs(every)),
+            BGSave::Disabled => None,
+        };
+        tokio::select! {
+            // Sleep until `every` from the current time instant
+            _ = time::sleep(every.unwrap_or_default()), if every.is_some() => {
+                let cloned_handle = handle.clone();
+                // we spawn this process just to ensure that it doesn't block the runtime's workers
+                // dedicated to async tasks (non-blocking)
+                tokio::task::spawn_blocking(move || {
+                    let owned_handle = cloned_handle;
+                    let _ = bgsave_blocking_section(owned_handle);
+                }).await.expect("Something caused the background service to panic");
+            }
+            // the configuration changed, so start over with the new one
+            ret = bgsave_cfg.changed(), if reloadable => reloadable = ret.is_ok(),
+            // Otherwise wait for a notification
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
             }
-        }
-        BGSave::Disabled => {
-            // the user doesn't bother about his data; cool, let's not bother about it either
         }
     }
     log::info!("BGSAVE service has exited");
@@ -78,15 +82,58 @@ pub async fn bgsave_scheduler(handle: Corestore, bgsave_cfg: BGSave, mut termina
 
 /// Run bgsave
 ///
-/// This function just hides away the BGSAVE blocking section from the _public API_
+/// This function just hides away the BGSAVE blocking section from the _public API_. Only the
+/// keyspaces that changed are flushed (see [`storage::v1::flush::flush_dirty`]), and the
+/// write-ahead log (if any) is checkpointed along with the flush
 pub fn run_bgsave(handle: &Corestore) -> IoResult<()> {
-    storage::v1::flush::flush_full(Autoflush, handle.get_store())
+    wal::checkpoint(|| storage::v1::flush::flush_dirty(handle.get_store()))
+}
+
+/// Flush the keyspaces that changed right away (`SYS FLUSH`). Returns true if it succeeded
+pub fn flush_now(handle: Corestore) -> bool {
+    self::bgsave_blocking_section(handle)
+}
+
+/// Flush a single keyspace right away (`SYS FLUSH <keyspace>`), whether or not it changed.
+/// Returns `None` if there's no such keyspace, and otherwise whether the flush succeeded.
+///
+/// The write-ahead log can only be truncated once everything in it is on disk, so while it's
+/// on, this runs a BGSAVE instead (with the keyspace marked as dirty). Replaying the log on
+/// top of a keyspace that already has its writes would apply them twice
+pub fn flush_keyspace(handle: &Corestore, ksid: &ObjectID) -> Option<bool> {
+    let store = handle.get_store();
+    let keyspace = store.get_keyspace_atomic_ref(ksid)?;
+    let ret = if wal::is_enabled() {
+        wal::checkpoint(|| {
+            let _flush_lock = registry::lock_flush_state();
+            keyspace.mark_dirty();
+            storage::v1::flush::flush_dirty(store)
+        })
+    } else {
+        let _flush_lock = registry::lock_flush_state();
+        storage::v1::flush::flush_one(store, ksid, &keyspace)
+    };
+    let name = unsafe { ksid.as_str() };
+    let okay = match ret {
+        Ok(()) => {
+            log::info!("Flushed the keyspace `{name}`");
+            true
+        }
+        Err(e) => {
+            log::error!("Failed to flush the keyspace `{name}`: {e}");
+            registry::poison();
+            health::record_flush(false);
+            false
+        }
+    };
+    Some(okay)
 }
 
 /// This just wraps around [`_bgsave_blocking_section`] and prints nice log messages depending on the outcome
 fn bgsave_blocking_section(handle: Corestore) -> bool {
     registry::lock_flush_state();
-    match run_bgsave(&handle) {
+    let start = Instant::now();
+    let okay = match run_bgsave(&handle) {
         Ok(_) => {
             log::info!("BGSAVE completed successfully");
             registry::unpoison();
@@ -97,5 +144,8 @@ fn bgsave_blocking_section(handle: Corestore) -> bool {
             registry::poison();
             false
         }
-    }
+    };
+    metrics::record_bgsave(start.elapsed(), okay);
+    health::record_flush(okay);
+    okay
 }
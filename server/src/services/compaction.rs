@@ -0,0 +1,286 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Compaction
+//!
+//! Every flush rewrites the table files from scratch, so deleted keys never linger on disk for
+//! longer than a BGSAVE. Memory is another story: the tables keep the capacity that they grew
+//! to even after most of their keys are deleted, and expired keys that nobody accesses stay
+//! around until the expiry sweeper gets to them. Compaction reclaims all of it:
+//! 1. The expired keys of every table are removed
+//! 2. The maps of every table are shrunk to fit the keys that are left, a shard at a time (see
+//! [`crate::kvengine::compaction`]). The compaction pauses for [`THROTTLE`] after every
+//! [`STEPS_PER_PAUSE`] shards, so that it doesn't starve the queries
+//! 3. The values of the tables that keep them on disk are moved to new files once at least
+//! half of what's in their files is no longer used (see [`crate::kvengine::disk`]), a shard at
+//! a time and with the same pauses
+//! 4. The data is flushed like it is by BGSAVE (checkpointing the write-ahead log), and the
+//! files of the tables and keyspaces that were dropped are removed
+//!
+//! The tables are compacted every `compaction.every` seconds (if set) and whenever `SYS COMPACT`
+//! is run. Only one compaction runs at a time, and its progress is reported by
+//! `SYS COMPACT STATUS`. A compaction that is running when the server shuts down is stopped
+//! before it flushes (the data is flushed on shutdown anyway)
+
+use {
+    crate::{
+        config::CompactionConfig,
+        corestore::{table::Table, Corestore},
+        health,
+        kvengine::expiry,
+        registry,
+        storage::v1::{
+            flush::{self, Autoflush},
+            interface, wal,
+        },
+    },
+    core::sync::atomic::{AtomicBool, Ordering},
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        sync::{Arc, Weak},
+        thread,
+        time::Instant,
+    },
+    tokio::{
+        sync::{broadcast::Receiver, Notify},
+        time::{self, Duration},
+    },
+};
+
+/// The number of shards that are compacted between two pauses
+const STEPS_PER_PAUSE: usize = 8;
+/// How long the compaction pauses for, every [`STEPS_PER_PAUSE`] shards
+const THROTTLE: Duration = Duration::from_millis(2);
+
+static PROGRESS: Mutex<Progress> = const_mutex(Progress::new());
+/// Wakes up the compaction service when a compaction is requested (set while it's running)
+static REQUESTS: Mutex<Option<Arc<Notify>>> = const_mutex(None);
+/// Set when the server is shutting down, to stop a compaction that is running
+static STOP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The progress of the running compaction (or of the last one, if none is running)
+pub struct Progress {
+    /// whether a compaction is running
+    pub running: bool,
+    /// the number of tables compacted so far
+    pub tables: usize,
+    /// the number of tables to compact
+    pub total: usize,
+    /// the number of expired keys removed
+    pub expired: usize,
+    /// the number of bytes freed
+    pub freed: usize,
+    /// the number of bytes reclaimed on disk (see [`crate::kvengine::disk`])
+    pub reclaimed: u64,
+    /// when the last compaction finished (UNIX millis, zero if none has)
+    pub finished: u64,
+}
+
+impl Progress {
+    const fn new() -> Self {
+        Self {
+            running: false,
+            tables: 0,
+            total: 0,
+            expired: 0,
+            freed: 0,
+            reclaimed: 0,
+            finished: 0,
+        }
+    }
+}
+
+/// Returns the progress of the running compaction (or of the last one)
+pub fn progress() -> Progress {
+    *PROGRESS.lock()
+}
+
+/// Request a compaction (`SYS COMPACT`). Returns false if one is already running
+pub fn request() -> bool {
+    let requests = match REQUESTS.lock().clone() {
+        Some(requests) => requests,
+        None => return false,
+    };
+    if !self::claim() {
+        return false;
+    }
+    requests.notify_one();
+    true
+}
+
+/// Mark a compaction as running, unless one already is
+fn claim() -> bool {
+    let mut progress = PROGRESS.lock();
+    if progress.running {
+        return false;
+    }
+    *progress = Progress {
+        running: true,
+        finished: progress.finished,
+        ..Progress::new()
+    };
+    true
+}
+
+/// The compaction service runs the compactions that are requested with `SYS COMPACT`, and
+/// the scheduled ones (if any)
+pub async fn compaction_service(
+    handle: Corestore,
+    cfg: CompactionConfig,
+    mut terminator: Receiver<()>,
+) {
+    let requests = Arc::new(Notify::new());
+    *REQUESTS.lock() = Some(requests.clone());
+    let every = cfg.every.map(Duration::from_secs);
+    loop {
+        tokio::select! {
+            _ = time::sleep(every.unwrap_or_default()), if every.is_some() => {
+                if !self::claim() {
+                    // it was requested just now, so it'll run anyway
+                    continue;
+                }
+            }
+            _ = requests.notified() => {}
+            _ = terminator.recv() => break,
+        }
+        let cloned_handle = handle.clone();
+        let mut compaction = tokio::task::spawn_blocking(move || self::compact(&cloned_handle));
+        tokio::select! {
+            ret = &mut compaction => ret.expect("Something caused the compaction to panic"),
+            _ = terminator.recv() => {
+                STOP.store(true, Ordering::Release);
+                let _ = compaction.await;
+                break;
+            }
+        }
+    }
+    *REQUESTS.lock() = None;
+    log::info!("Compaction service has exited");
+}
+
+/// Compact all the tables and flush the data. This blocks, so it has to be run on a blocking
+/// thread
+fn compact(handle: &Corestore) {
+    let start = Instant::now();
+    let store = handle.get_store();
+    // we don't hold on to the tables, so that they can still be dropped while we compact
+    let tables: Vec<Weak<Table>> = store
+        .keyspaces
+        .iter()
+        .flat_map(|ks| {
+            ks.value()
+                .tables
+                .iter()
+                .map(|tbl| Arc::downgrade(tbl.value()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    PROGRESS.lock().total = tables.len();
+    log::info!("Compacting {} table(s)", tables.len());
+    let mut steps = 0;
+    for table in tables {
+        let table = match table.upgrade() {
+            Some(table) => table,
+            None => {
+                // it was dropped in the meantime
+                PROGRESS.lock().tables += 1;
+                continue;
+            }
+        };
+        let expired = table.sweep_expired();
+        let mut freed = 0;
+        for step in 0..table.compaction_steps() {
+            if self::stopping() {
+                return;
+            }
+            freed += table.compact_step(step);
+            self::pause(&mut steps);
+        }
+        let mut reclaimed = 0;
+        if let Some(mut values) = table.compact_values() {
+            for step in 0..values.steps() {
+                if self::stopping() {
+                    // the values that weren't moved yet are moved by the next compaction
+                    return;
+                }
+                values.step(step);
+                self::pause(&mut steps);
+            }
+            reclaimed = values.finish();
+        }
+        let mut progress = PROGRESS.lock();
+        progress.tables += 1;
+        progress.expired += expired;
+        progress.freed += freed;
+        progress.reclaimed += reclaimed;
+    }
+    let flushed = wal::checkpoint(|| {
+        let _flush_lock = registry::lock_flush_state();
+        flush::flush_full(Autoflush, store)?;
+        interface::cleanup_tree(store)
+    });
+    health::record_flush(flushed.is_ok());
+    let mut progress = PROGRESS.lock();
+    match flushed {
+        Ok(()) => {
+            log::info!(
+                "Compaction completed in {:?}: removed {} expired key(s), freed {} bytes and \
+                reclaimed {} bytes on disk",
+                start.elapsed(),
+                progress.expired,
+                progress.freed,
+                progress.reclaimed
+            );
+            registry::unpoison();
+        }
+        Err(e) => {
+            log::error!("Compacted the tables, but failed to flush them: {e}");
+            registry::poison();
+        }
+    }
+    progress.running = false;
+    progress.finished = expiry::now_millis();
+}
+
+/// Returns true (and gives up on the compaction) if the server is shutting down
+fn stopping() -> bool {
+    if STOP.load(Ordering::Acquire) {
+        log::info!("Stopped compacting since the server is shutting down");
+        PROGRESS.lock().running = false;
+        true
+    } else {
+        false
+    }
+}
+
+/// Count a step, pausing after every [`STEPS_PER_PAUSE`] of them
+fn pause(steps: &mut usize) {
+    *steps += 1;
+    if *steps % STEPS_PER_PAUSE == 0 {
+        thread::sleep(THROTTLE);
+    }
+}
@@ -0,0 +1,392 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Configuration reloads
+//!
+//! Some of the settings in the configuration file can be changed while the server is running,
+//! by editing the file and then sending `SIGHUP` or running `SYS RELOADCONF`:
+//! - `server.loglevel` and `server.logformat`
+//! - the `bgsave` section
+//! - `snapshot.every` and `snapshot.failsafe`
+//! - the `limits` section (for the connections that are accepted from then on)
+//! - the `memory` section
+//! - the `replication` section
+//!
+//! The file is validated just like it is on startup and nothing is changed if it has errors.
+//! Changes to the other settings are reported, but they only take effect after a restart
+
+use {
+    crate::{
+        config::{
+            self, BGSave, ConfigurationSet, PortConfig, SnapshotConfig, SnapshotPref, SslOpts,
+        },
+        dbnet, logging, memory, replication,
+    },
+    core::fmt,
+    log::LevelFilter,
+    parking_lot::{const_mutex, Mutex},
+    tokio::sync::watch,
+};
+
+#[derive(Debug, PartialEq, Eq, Default)]
+/// The keys that were changed in the configuration file
+pub struct Report {
+    /// the keys whose new values are now in effect
+    pub applied: Vec<&'static str>,
+    /// the keys whose new values only take effect after a restart
+    pub restart: Vec<&'static str>,
+}
+
+impl Report {
+    /// Returns true if nothing was changed
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.restart.is_empty()
+    }
+}
+
+#[derive(Debug)]
+/// The reasons why a reload can fail
+pub enum ReloadError {
+    /// The server wasn't started with a configuration file
+    NoConfigFile,
+    /// The configuration file couldn't be read or has errors
+    BadConfig(String),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoConfigFile => write!(f, "the server wasn't started with a configuration file"),
+            Self::BadConfig(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// What we need to apply the changes
+struct Reloader {
+    /// the configuration that is in effect
+    running: ConfigurationSet,
+    /// the log level that the logger was set up with (for when `loglevel` is unset)
+    base_level: LevelFilter,
+    /// the BGSAVE service watches this
+    bgsave: watch::Sender<BGSave>,
+    /// the snapshot service watches this
+    snapshot: watch::Sender<SnapshotConfig>,
+}
+
+static RELOADER: Mutex<Option<Reloader>> = const_mutex(None);
+
+/// Set things up for reloads with the configuration that the server was started with. This
+/// applies the log level (and format), the memory limit and the replication settings, and
+/// returns the receivers that the BGSAVE and snapshot services should watch
+pub fn init(
+    running: ConfigurationSet,
+) -> (watch::Receiver<BGSave>, watch::Receiver<SnapshotConfig>) {
+    let base_level = log::max_level();
+    if let Some(level) = running.loglevel {
+        log::set_max_level(level);
+    }
+    logging::set_format(running.logformat);
+    memory::configure(running.memory);
+    replication::configure(running.replication);
+    let (bgsave, bgsave_rx) = watch::channel(running.bgsave);
+    let (snapshot, snapshot_rx) = watch::channel(running.snapshot);
+    *RELOADER.lock() = Some(Reloader {
+        running,
+        base_level,
+        bgsave,
+        snapshot,
+    });
+    (bgsave_rx, snapshot_rx)
+}
+
+/// Read the configuration file again and apply whatever changed
+pub fn reload() -> Result<Report, ReloadError> {
+    let new = match config::reload_file() {
+        Some(Ok(new)) => {
+            new.print_warnings();
+            new.finish().0
+        }
+        Some(Err(e)) => return Err(ReloadError::BadConfig(e.to_string())),
+        None => return Err(ReloadError::NoConfigFile),
+    };
+    let mut reloader = RELOADER.lock();
+    let reloader = match reloader.as_mut() {
+        Some(reloader) => reloader,
+        // the services haven't been started yet, so there's nothing to reload
+        None => return Err(ReloadError::NoConfigFile),
+    };
+    let report = self::diff(&reloader.running, &new);
+    let running = &mut reloader.running;
+    if running.loglevel != new.loglevel {
+        log::set_max_level(new.loglevel.unwrap_or(reloader.base_level));
+        running.loglevel = new.loglevel;
+    }
+    if running.logformat != new.logformat {
+        logging::set_format(new.logformat);
+        running.logformat = new.logformat;
+    }
+    if running.bgsave != new.bgsave {
+        running.bgsave = new.bgsave;
+        reloader.bgsave.send_replace(new.bgsave);
+    }
+    if let (SnapshotConfig::Enabled(current), SnapshotConfig::Enabled(next)) =
+        (&mut running.snapshot, new.snapshot)
+    {
+        // the number of snapshots that are kept can't be changed
+        let updated = SnapshotPref::new(next.every, current.atmost, next.poison);
+        if *current != updated {
+            *current = updated;
+            reloader.snapshot.send_replace(running.snapshot);
+        }
+    }
+    if running.limits != new.limits {
+        running.limits = new.limits;
+        dbnet::set_limits(new.limits);
+    }
+    if running.memory != new.memory {
+        running.memory = new.memory;
+        memory::configure(new.memory);
+    }
+    if running.replication != new.replication {
+        running.replication = new.replication;
+        replication::configure(new.replication);
+    }
+    if report.is_empty() {
+        log::info!("Reloaded the configuration (nothing changed)");
+    } else if !report.applied.is_empty() {
+        log::info!(
+            "Reloaded the configuration (changed: {})",
+            report.applied.join(", ")
+        );
+    }
+    for key in report.restart.iter() {
+        log::warn!("`{key}` was changed, but it only takes effect after a restart");
+    }
+    Ok(report)
+}
+
+/// Returns the insecure port and the TLS settings of the given port configuration
+fn split_ports(ports: &PortConfig) -> (Option<u16>, Option<&SslOpts>) {
+    match ports {
+        PortConfig::InsecureOnly { port, .. } => (Some(*port), None),
+        PortConfig::SecureOnly { ssl, .. } => (None, Some(ssl)),
+        PortConfig::Multi { port, ssl, .. } => (Some(*port), Some(ssl)),
+    }
+}
+
+/// Returns the keys that differ between the running and the new configuration
+fn diff(running: &ConfigurationSet, new: &ConfigurationSet) -> Report {
+    let mut report = Report::default();
+    let mut applied = |changed: bool, key| {
+        if changed {
+            report.applied.push(key);
+        }
+    };
+    applied(running.loglevel != new.loglevel, "server.loglevel");
+    applied(running.logformat != new.logformat, "server.logformat");
+    match (running.bgsave, new.bgsave) {
+        (BGSave::Enabled(current), BGSave::Enabled(next)) => {
+            applied(current != next, "bgsave.every")
+        }
+        (current, next) => applied(current != next, "bgsave.enabled"),
+    }
+    if let (SnapshotConfig::Enabled(current), SnapshotConfig::Enabled(next)) =
+        (running.snapshot, new.snapshot)
+    {
+        applied(current.every != next.every, "snapshot.every");
+        applied(current.poison != next.poison, "snapshot.failsafe");
+    }
+    let (current, next) = (&running.limits, &new.limits);
+    applied(
+        current.query.max_size != next.query.max_size,
+        "limits.maxquerysize",
+    );
+    applied(
+        current.query.max_args != next.query.max_args,
+        "limits.maxqueryargs",
+    );
+    applied(
+        current.close_on_violation != next.close_on_violation,
+        "limits.closeonviolation",
+    );
+    applied(
+        current.idle_timeout != next.idle_timeout,
+        "limits.idletimeout",
+    );
+    applied(current.keepalive != next.keepalive, "limits.keepalive");
+    applied(
+        current.admission.max_per_ip != next.admission.max_per_ip,
+        "limits.maxconperip",
+    );
+    applied(
+        current.admission.max_rate != next.admission.max_rate,
+        "limits.maxconrate",
+    );
+    applied(
+        running.memory.maxmemory != new.memory.maxmemory,
+        "memory.maxmemory",
+    );
+    applied(running.memory.policy != new.memory.policy, "memory.policy");
+    applied(
+        running.replication.read_only != new.replication.read_only,
+        "replication.read_only",
+    );
+    let mut restart = |changed: bool, key| {
+        if changed {
+            report.restart.push(key);
+        }
+    };
+    let (current_port, current_ssl) = self::split_ports(&running.ports);
+    let (next_port, next_ssl) = self::split_ports(&new.ports);
+    restart(
+        running.ports.get_host() != new.ports.get_host(),
+        "server.host",
+    );
+    restart(current_port != next_port, "server.port");
+    restart(current_ssl != next_ssl, "ssl");
+    restart(running.maxcon != new.maxcon, "server.maxcon");
+    restart(running.shards != new.shards, "server.shards");
+    restart(running.mode != new.mode, "server.mode");
+    restart(running.protocol != new.protocol, "server.protocol");
+    restart(
+        running.qualified_keys != new.qualified_keys,
+        "server.qualified_keys",
+    );
+    match (running.snapshot, new.snapshot) {
+        (SnapshotConfig::Enabled(current), SnapshotConfig::Enabled(next)) => {
+            restart(current.atmost != next.atmost, "snapshot.atmost")
+        }
+        (current, next) => restart(current != next, "snapshot"),
+    }
+    restart(running.snapshot_sink != new.snapshot_sink, "snapshot.sink");
+    restart(running.auth != new.auth, "auth");
+    restart(running.http != new.http, "http");
+    restart(running.ratelimit != new.ratelimit, "ratelimit");
+    restart(running.audit != new.audit, "audit");
+    restart(running.wal != new.wal, "wal");
+    restart(running.encryption != new.encryption, "encryption");
+    restart(running.compaction != new.compaction, "compaction");
+    restart(running.cluster != new.cluster, "cluster");
+    restart(running.cdc != new.cdc, "cdc");
+    restart(running.export != new.export, "export");
+    report
+}
+
+#[cfg(unix)]
+/// Reload the configuration whenever we get a `SIGHUP` (this does the same thing as
+/// `SYS RELOADCONF`)
+pub async fn reload_on_hangup(mut terminator: tokio::sync::broadcast::Receiver<()>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            log::error!("Failed to bind to SIGHUP (use `SYS RELOADCONF` instead): {e}");
+            return;
+        }
+    };
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                match tokio::task::spawn_blocking(self::reload).await {
+                    // without a configuration file, the hangup is only meant for the TLS
+                    // listeners (if any)
+                    Ok(Ok(_)) | Ok(Err(ReloadError::NoConfigFile)) => {}
+                    Ok(Err(e)) => log::error!("Failed to reload the configuration: {e}"),
+                    Err(_) => log::error!("Something caused the configuration reload to panic"),
+                }
+            }
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    log::info!("Configuration reload service has exited");
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{diff, Report},
+        crate::config::{BGSave, ConfigurationSet, SnapshotConfig, SnapshotPref},
+        log::LevelFilter,
+    };
+
+    #[test]
+    fn diff_nothing() {
+        let cfg = ConfigurationSet::default();
+        assert!(diff(&cfg, &cfg.clone()).is_empty());
+    }
+
+    #[test]
+    fn diff_applied() {
+        let running = ConfigurationSet::default();
+        let mut new = running.clone();
+        new.loglevel = Some(LevelFilter::Debug);
+        new.bgsave = BGSave::Enabled(60);
+        new.limits.idle_timeout = Some(300);
+        new.limits.admission.max_rate = Some(8);
+        new.memory.maxmemory = Some(1073741824);
+        assert_eq!(
+            diff(&running, &new),
+            Report {
+                applied: vec![
+                    "server.loglevel",
+                    "bgsave.every",
+                    "limits.idletimeout",
+                    "limits.maxconrate",
+                    "memory.maxmemory"
+                ],
+                restart: vec![],
+            }
+        );
+        new.bgsave = BGSave::Disabled;
+        assert!(diff(&running, &new).applied.contains(&"bgsave.enabled"));
+    }
+
+    #[test]
+    fn diff_restart() {
+        let mut running = ConfigurationSet::default();
+        running.snapshot = SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true));
+        let mut new = running.clone();
+        new.snapshot = SnapshotConfig::Enabled(SnapshotPref::new(600, 8, true));
+        new.maxcon = 16;
+        assert_eq!(
+            diff(&running, &new),
+            Report {
+                applied: vec!["snapshot.every"],
+                restart: vec!["server.maxcon", "snapshot.atmost"],
+            }
+        );
+        // turning snapshots off needs a restart
+        new.snapshot = SnapshotConfig::Disabled;
+        assert_eq!(
+            diff(&running, &new).restart,
+            vec!["server.maxcon", "snapshot"]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::{
+        corestore::{arena, Corestore},
+        memory,
+    },
+    tokio::{
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The interval after which the memory usage is reported again
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The memory reporter periodically evicts keys from the volatile tables that are over their
+/// memory cap (see [`crate::kvengine::eviction`]) and then records the approximate memory used
+/// by the data in every table, along with the memory that the small object arena holds on to
+/// (see [`memory`])
+pub async fn memory_reporter(handle: Corestore, mut terminator: Receiver<()>) {
+    loop {
+        let cloned_handle = handle.clone();
+        let (usage, arena_idle) = tokio::task::spawn_blocking(move || {
+            let store = cloned_handle.get_store();
+            let evicted = store.evict();
+            if evicted != 0 {
+                log::debug!("Evicted {evicted} key(s) from volatile tables over their memory cap");
+            }
+            let arena = arena::stats();
+            (
+                store.memory_usage(),
+                arena.reserved.saturating_sub(arena.used),
+            )
+        })
+        .await
+        .expect("Something caused the memory reporter to panic");
+        memory::record(usage, arena_idle);
+        tokio::select! {
+            _ = time::sleep_until(time::Instant::now() + REPORT_INTERVAL) => {}
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    log::info!("Memory reporter has exited");
+}
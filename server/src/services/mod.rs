@@ -25,7 +25,14 @@
 */
 
 pub mod bgsave;
+pub mod compaction;
+pub mod confreload;
+pub mod memreport;
 pub mod snapshot;
+pub mod sweeper;
+#[cfg(unix)]
+pub mod tlsreload;
+pub mod walsync;
 use crate::{
     corestore::memstore::Memstore, diskstore::flock::FileLock, storage, util::os, IoResult,
 };
@@ -28,12 +28,12 @@ use {
     crate::{
         config::SnapshotConfig,
         corestore::Corestore,
-        registry,
+        health, registry,
         storage::v1::sengine::{SnapshotActionResult, SnapshotEngine},
     },
     std::sync::Arc,
     tokio::{
-        sync::broadcast::Receiver,
+        sync::{broadcast::Receiver, watch},
         time::{self, Duration},
     },
 };
@@ -44,48 +44,57 @@ use {
 /// the interval for snapshotting expires or elapses, we create a snapshot. The snapshot service
 /// keeps creating snapshots, as long as the database keeps running. Once [`dbnet::run`] broadcasts
 /// a termination signal, we're ready to quit. This function will, by default, poison the database
-/// if snapshotting fails, unless customized by the user.
+/// if snapshotting fails, unless customized by the user. The interval and the failsafe can change
+/// while the server is running (see [`super::confreload`])
 pub async fn snapshot_service(
     engine: Arc<SnapshotEngine>,
     handle: Corestore,
-    ss_config: SnapshotConfig,
+    mut ss_config: watch::Receiver<SnapshotConfig>,
     mut termination_signal: Receiver<()>,
 ) {
-    match ss_config {
-        SnapshotConfig::Disabled => {
-            // since snapshotting is disabled, we'll imediately return
-            return;
-        }
-        SnapshotConfig::Enabled(configuration) => {
-            let (duration, _, failsafe) = configuration.decompose();
-            let duration = Duration::from_secs(duration);
-            loop {
-                tokio::select! {
-                    _ = time::sleep_until(time::Instant::now() + duration) => {
-                        let succeeded = engine.mksnap(handle.clone_store()).await == SnapshotActionResult::Ok;
-                        #[cfg(test)]
-                        {
-                            use std::env::set_var;
-                            if succeeded {
-                                set_var("SKYTEST_SNAPSHOT_OKAY", "true");
-                            } else {
-                                set_var("SKYTEST_SNAPSHOT_OKAY", "false");
-                            }
-                        }
-                        if succeeded {
-                            // it passed, so unpoison the handle
-                            registry::unpoison();
-                        } else if failsafe {
-                            // mksnap returned false and we are set to stop writes if snapshotting failed
-                            // so let's poison the handle
-                            registry::poison();
-                        }
-                    },
-                    _ = termination_signal.recv() => {
-                        // time to terminate; goodbye!
-                        break;
+    if matches!(*ss_config.borrow(), SnapshotConfig::Disabled) {
+        // since snapshotting is disabled, we'll imediately return
+        return;
+    }
+    health::enable_snapshots();
+    // we stop looking for changes if nobody can send them anymore
+    let mut reloadable = true;
+    loop {
+        let (duration, failsafe) = match *ss_config.borrow() {
+            SnapshotConfig::Enabled(configuration) => {
+                let (duration, _, failsafe) = configuration.decompose();
+                (Some(Duration::from_secs(duration)), failsafe)
+            }
+            // snapshots can't be turned off without a restart, but let's not take chances
+            SnapshotConfig::Disabled => (None, false),
+        };
+        tokio::select! {
+            _ = time::sleep(duration.unwrap_or_default()), if duration.is_some() => {
+                let succeeded = engine.mksnap(handle.clone_store()).await == SnapshotActionResult::Ok;
+                #[cfg(test)]
+                {
+                    use std::env::set_var;
+                    if succeeded {
+                        set_var("SKYTEST_SNAPSHOT_OKAY", "true");
+                    } else {
+                        set_var("SKYTEST_SNAPSHOT_OKAY", "false");
                     }
                 }
+                health::record_snapshot(succeeded);
+                if succeeded {
+                    // it passed, so unpoison the handle
+                    registry::unpoison();
+                } else if failsafe {
+                    // mksnap returned false and we are set to stop writes if snapshotting failed
+                    // so let's poison the handle
+                    registry::poison();
+                }
+            },
+            // the configuration changed, so start over with the new one
+            ret = ss_config.changed(), if reloadable => reloadable = ret.is_ok(),
+            _ = termination_signal.recv() => {
+                // time to terminate; goodbye!
+                break;
             }
         }
     }
@@ -0,0 +1,61 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::corestore::Corestore,
+    tokio::{
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The interval after which the sweeper wakes up
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The expiry sweeper periodically evicts expired keys from all tables
+///
+/// Expired keys are already evicted lazily when they're accessed; the sweeper only makes sure
+/// that keys which are never accessed again don't keep hogging memory
+pub async fn expiry_sweeper(handle: Corestore, mut terminator: Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = time::sleep_until(time::Instant::now() + SWEEP_INTERVAL) => {
+                let cloned_handle = handle.clone();
+                let evicted = tokio::task::spawn_blocking(move || {
+                    cloned_handle.get_store().sweep_expired()
+                }).await.expect("Something caused the expiry sweeper to panic");
+                if evicted != 0 {
+                    log::trace!("Expiry sweeper evicted {} keys", evicted);
+                }
+            }
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    log::info!("Expiry sweeper has exited");
+}
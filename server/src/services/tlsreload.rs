@@ -0,0 +1,65 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::dbnet,
+    tokio::{
+        signal::unix::{signal, SignalKind},
+        sync::broadcast::Receiver,
+        task,
+    },
+};
+
+/// Reload the TLS certificates and keys from disk whenever we get a `SIGHUP` (this does the
+/// same thing as `SYS RELOAD TLS`). If they fail to load, the listeners keep using the ones
+/// that they already have
+pub async fn reload_on_hangup(mut terminator: Receiver<()>) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            log::error!("Failed to bind to SIGHUP (use `SYS RELOAD TLS` instead): {e}");
+            return;
+        }
+    };
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                let ret = task::spawn_blocking(dbnet::reload_certificates)
+                    .await
+                    .expect("Something caused the TLS reload to panic");
+                match ret {
+                    Ok(count) => log::info!("Reloaded the TLS certificates of {count} listener(s)"),
+                    Err(e) => log::error!("Failed to reload the TLS certificates: {e}"),
+                }
+            }
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    log::info!("TLS reload service has exited");
+}
@@ -0,0 +1,74 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::storage::v1::wal,
+    core::future,
+    std::{sync::Arc, time::Instant},
+    tokio::{
+        sync::{broadcast::Receiver, Notify},
+        time,
+    },
+};
+
+/// The WAL writer appends the records that the writes have queued to the write-ahead log, all
+/// of them in one go, and syncs the log once for all the writes that wait for it (this is the
+/// group commit; see [`wal::write`]). It also syncs the log once the writes that haven't been
+/// synced yet are due (see [`wal::next_sync`]), which is how the `<N>ms` fsync policies (and
+/// durabilities) work. `writer` wakes us up when records are queued
+pub async fn wal_writer(writer: Arc<Notify>, mut terminator: Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = self::sleep_until(wal::next_sync()) => {}
+            _ = writer.notified() => {}
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+        self::write().await;
+    }
+    // nothing is queued anymore, so write what's left
+    self::write().await;
+    log::info!("WAL writer has exited");
+}
+
+async fn write() {
+    let ret = tokio::task::spawn_blocking(wal::write)
+        .await
+        .expect("Something caused the WAL writer to panic");
+    if let Err(e) = ret {
+        log::error!("Failed to write to the write-ahead log: {e}");
+    }
+}
+
+/// Sleep until the deadline, or forever if there isn't one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline.into()).await,
+        None => future::pending().await,
+    }
+}
@@ -58,6 +58,8 @@ pub const BYTEMARK_MODEL_KV_BINSTR_LIST_STR: u8 = 5;
 pub const BYTEMARK_MODEL_KV_STR_LIST_BINSTR: u8 = 6;
 /// KVEBlob model bytemark with key:str, val: list<str>
 pub const BYTEMARK_MODEL_KV_STR_LIST_STR: u8 = 7;
+/// No model (used for the defaults of a keyspace that has no default model)
+pub const BYTEMARK_MODEL_NONE: u8 = u8::MAX;
 
 // storage bym
 /// Persistent storage bytemark
@@ -67,3 +69,5 @@ pub const BYTEMARK_STORAGE_VOLATILE: u8 = 1;
 
 // system bym
 pub const SYSTEM_TABLE_AUTH: u8 = 0;
+pub const SYSTEM_TABLE_ACL: u8 = 1;
+pub const SYSTEM_TABLE_ROTATION: u8 = 2;
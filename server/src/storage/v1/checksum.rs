@@ -0,0 +1,207 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Checksums
+//!
+//! Every file that the flush routines write (the `PRELOAD`, the `PARTMAP`s and the tables)
+//! ends with a trailer: `[u32 CRC-32 of the data][TRAILER_MAGIC]`, little endian. The checksum
+//! is verified when the file is read, so that a file that was corrupted on disk is caught
+//! instead of being loaded. Files that were written before the trailer was added don't have
+//! one, and they're read as they are (they still have to decode)
+
+use {
+    super::error::{StorageEngineError, StorageEngineResult},
+    crate::IoResult,
+    std::io::Write,
+};
+
+/// The end of the trailer
+const TRAILER_MAGIC: [u8; 4] = *b"SKYC";
+/// The size of the checksum and the magic at the end of every file
+const TRAILER_SIZE: usize = 8;
+/// The lookup table for CRC-32 (IEEE)
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+#[derive(Debug, Clone, Copy)]
+/// A running CRC-32 (IEEE) checksum
+struct Crc32(u32);
+
+impl Crc32 {
+    const fn new() -> Self {
+        Self(!0)
+    }
+    fn update(&mut self, data: &[u8]) {
+        self.0 = data.iter().fold(self.0, |crc, byte| {
+            CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+        });
+    }
+    const fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Compute the CRC-32 (IEEE) checksum of the data
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// A writer that checksums everything written to it, and writes the trailer once it's
+/// [finished](Self::finish)
+pub struct ChecksumWriter<W: Write> {
+    inner: W,
+    crc: Crc32,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            crc: Crc32::new(),
+        }
+    }
+    /// Write the trailer and return the writer. Nothing should be written after this
+    pub fn finish(mut self) -> IoResult<W> {
+        self.inner.write_all(&self.crc.finish().to_le_bytes())?;
+        self.inner.write_all(&TRAILER_MAGIC)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc.update(&buf[..written]);
+        Ok(written)
+    }
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// Verify the checksum of the file at `path` (with the contents `file`) and decode its data
+/// with `decode`. A file without a trailer is decoded as it is
+pub fn decode<T>(
+    path: &str,
+    file: &[u8],
+    decode: impl Fn(&[u8]) -> StorageEngineResult<T>,
+) -> StorageEngineResult<T> {
+    match self::split_trailer(file) {
+        Some((data, checksum)) if self::crc32(data) == checksum => decode(data),
+        // either the file was corrupted, or it's an old file whose data just happens to end
+        // like a trailer (in which case it decodes)
+        Some(_) => decode(file).map_err(|_| StorageEngineError::ChecksumMismatch(path.into())),
+        None => decode(file),
+    }
+}
+
+/// Split a file into its data and the checksum in its trailer. Returns `None` if there's no
+/// trailer
+fn split_trailer(file: &[u8]) -> Option<(&[u8], u32)> {
+    if file.len() < TRAILER_SIZE || !file.ends_with(&TRAILER_MAGIC) {
+        return None;
+    }
+    let (data, trailer) = file.split_at(file.len() - TRAILER_SIZE);
+    let checksum = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    Some((data, checksum))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{crc32, decode, ChecksumWriter},
+        crate::storage::v1::error::StorageEngineError,
+        std::io::Write,
+    };
+
+    fn decode_exact(data: &[u8]) -> Result<Vec<u8>, StorageEngineError> {
+        if data.starts_with(b"skytable") && data.len() == 11 {
+            Ok(data.to_vec())
+        } else {
+            Err(StorageEngineError::CorruptedFile("test".into()))
+        }
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn trailer_roundtrip() {
+        let mut writer = ChecksumWriter::new(Vec::new());
+        writer.write_all(b"sky").unwrap();
+        writer.write_all(b"table").unwrap();
+        writer.write_all(b"!!!").unwrap();
+        let file = writer.finish().unwrap();
+        assert_eq!(file.len(), 11 + 8);
+        assert_eq!(decode("test", &file, decode_exact).unwrap(), b"skytable!!!");
+    }
+
+    #[test]
+    fn corrupted_data() {
+        let mut writer = ChecksumWriter::new(Vec::new());
+        writer.write_all(b"skytable!!!").unwrap();
+        let mut file = writer.finish().unwrap();
+        file[3] ^= 0xFF;
+        assert!(matches!(
+            decode("test", &file, decode_exact).unwrap_err(),
+            StorageEngineError::ChecksumMismatch(path) if path == "test"
+        ));
+    }
+
+    #[test]
+    fn file_without_trailer() {
+        assert_eq!(
+            decode("test", b"skytable!!!", decode_exact).unwrap(),
+            b"skytable!!!"
+        );
+        assert!(matches!(
+            decode("test", b"skytable", decode_exact).unwrap_err(),
+            StorageEngineError::CorruptedFile(_)
+        ));
+    }
+}
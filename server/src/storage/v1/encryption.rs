@@ -0,0 +1,321 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Encryption at rest
+//!
+//! If a key is configured (in the `encryption` section of the configuration file), every file
+//! that the flush routines write (the `PRELOAD`, the `PARTMAP`s and the tables, for the data
+//! and for snapshots) is encrypted with AES-256-GCM, and so is every record in the write-ahead
+//! log and in its archive (see [`super::wal`]). An encrypted file is
+//! `[MAGIC][nonce][ciphertext][tag]`, where the nonce is random and the ciphertext is the file
+//! (with its checksum, see [`super::checksum`]) as it would have been written without
+//! encryption. The key is 32 bytes encoded in base64, and it's either read from a file or
+//! printed by a command (so that it can be kept in a KMS) when the server starts up.
+//!
+//! Once a key is configured, the data can only be read with it: a file that can't be decrypted
+//! (because the key is wrong, or because the file was tampered with) stops the server from
+//! starting up instead of being quarantined, and so does a file that isn't encrypted at all
+//! (since it could have been swapped in for one that was). Data that was stored without a key
+//! has to be moved over with `SYS EXPORT` and `SYS IMPORT` (the archives aren't encrypted).
+
+use {
+    super::error::{StorageEngineError, StorageEngineResult},
+    crate::{config::EncryptionConfig, IoResult},
+    openssl::{
+        rand,
+        symm::{self, Cipher, Crypter, Mode},
+    },
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        fs,
+        io::{Error as IoError, ErrorKind, Write},
+        process::{Command, Stdio},
+    },
+};
+
+/// A 256-bit key
+type Key = [u8; 32];
+
+/// The key that the files are encrypted with (if any)
+static KEY: Mutex<Option<Key>> = const_mutex(None);
+
+/// The bytes that every encrypted file starts with (these are also authenticated)
+const MAGIC: [u8; 8] = *b"SKYAEAD1";
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+/// The number of bytes that encrypting something adds to it
+pub const OVERHEAD: usize = MAGIC.len() + NONCE_SIZE + TAG_SIZE;
+
+/// Load the key (if encryption is turned on). This has to be done before the data is read
+pub fn init(cfg: &EncryptionConfig) -> IoResult<()> {
+    let encoded = match cfg {
+        EncryptionConfig::Disabled => return Ok(()),
+        EncryptionConfig::KeyFile(path) => fs::read_to_string(path)?,
+        EncryptionConfig::KeyCommand(command) => self::run(command)?,
+    };
+    let key = self::parse_key(&encoded).ok_or_else(|| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            "the key has to be 32 bytes encoded in base64",
+        )
+    })?;
+    *KEY.lock() = Some(key);
+    log::info!("The data and the snapshots will be encrypted");
+    Ok(())
+}
+
+/// Returns true if the files are encrypted
+pub fn is_enabled() -> bool {
+    KEY.lock().is_some()
+}
+
+fn key() -> Option<Key> {
+    *KEY.lock()
+}
+
+fn parse_key(encoded: &str) -> Option<Key> {
+    base64::decode(encoded.trim()).ok()?.try_into().ok()
+}
+
+/// Run the key command and return what it printed
+fn run(command: &str) -> IoResult<String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(IoError::new(
+            ErrorKind::Other,
+            format!("the key command failed ({})", output.status),
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|_| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            "the key command didn't print the key as text",
+        )
+    })
+}
+
+/// A writer that encrypts everything written to it (if there's a key), and writes the tag once
+/// it's [finished](Self::finish). Without a key, everything is written as it is
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    crypter: Option<Crypter>,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// Encrypt with the configured key (if any)
+    pub fn new(inner: W) -> IoResult<Self> {
+        Self::with_key(inner, self::key().as_ref())
+    }
+    fn with_key(mut inner: W, key: Option<&Key>) -> IoResult<Self> {
+        let crypter = match key {
+            Some(key) => {
+                let mut nonce = [0u8; NONCE_SIZE];
+                rand::rand_bytes(&mut nonce)?;
+                let mut crypter =
+                    Crypter::new(Cipher::aes_256_gcm(), Mode::Encrypt, key, Some(&nonce))?;
+                crypter.aad_update(&MAGIC)?;
+                inner.write_all(&MAGIC)?;
+                inner.write_all(&nonce)?;
+                Some(crypter)
+            }
+            None => None,
+        };
+        Ok(Self {
+            inner,
+            crypter,
+            buf: Vec::new(),
+        })
+    }
+    /// Write the tag and return the writer. Nothing should be written after this
+    pub fn finish(mut self) -> IoResult<W> {
+        if let Some(crypter) = self.crypter.as_mut() {
+            // GCM doesn't hold anything back, but this still has to be called to get the tag
+            self.buf.resize(Cipher::aes_256_gcm().block_size(), 0);
+            let rest = crypter.finalize(&mut self.buf)?;
+            self.inner.write_all(&self.buf[..rest])?;
+            let mut tag = [0u8; TAG_SIZE];
+            crypter.get_tag(&mut tag)?;
+            self.inner.write_all(&tag)?;
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        match self.crypter.as_mut() {
+            Some(crypter) => {
+                self.buf
+                    .resize(buf.len() + Cipher::aes_256_gcm().block_size(), 0);
+                let encrypted = crypter.update(buf, &mut self.buf)?;
+                self.inner.write_all(&self.buf[..encrypted])?;
+                Ok(buf.len())
+            }
+            None => self.inner.write(buf),
+        }
+    }
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// Encrypt `data` with the configured key, like a file. Returns `None` if there's no key
+pub fn seal(data: &[u8]) -> IoResult<Option<Vec<u8>>> {
+    let key = match self::key() {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+    let mut writer =
+        EncryptingWriter::with_key(Vec::with_capacity(data.len() + OVERHEAD), Some(&key))?;
+    writer.write_all(data)?;
+    writer.finish().map(Some)
+}
+
+/// Decrypt the file at `path` (with the contents `file`) with the configured key. Without a
+/// key, a file that isn't encrypted is returned as it is
+pub fn decrypt(path: &str, file: Vec<u8>) -> StorageEngineResult<Vec<u8>> {
+    self::decrypt_with(path, file, self::key().as_ref())
+}
+
+fn decrypt_with(path: &str, file: Vec<u8>, key: Option<&Key>) -> StorageEngineResult<Vec<u8>> {
+    if !file.starts_with(&MAGIC) {
+        return match key {
+            Some(_) => Err(StorageEngineError::Unencrypted(path.into())),
+            None => Ok(file),
+        };
+    }
+    let key = key.ok_or_else(|| StorageEngineError::MissingKey(path.into()))?;
+    if file.len() < OVERHEAD {
+        return Err(StorageEngineError::DecryptionFailed(path.into()));
+    }
+    let (nonce, encrypted) = file[MAGIC.len()..].split_at(NONCE_SIZE);
+    let (encrypted, tag) = encrypted.split_at(encrypted.len() - TAG_SIZE);
+    symm::decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(nonce),
+        &MAGIC,
+        encrypted,
+        tag,
+    )
+    .map_err(|_| StorageEngineError::DecryptionFailed(path.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{decrypt_with, parse_key, EncryptingWriter, Key, MAGIC},
+        crate::storage::v1::error::StorageEngineError,
+        std::io::Write,
+    };
+
+    const KEY: Key = [7; 32];
+
+    fn encrypt(data: &[u8], key: Option<&Key>) -> Vec<u8> {
+        let mut writer = EncryptingWriter::with_key(Vec::new(), key).unwrap();
+        writer.write_all(&data[..3]).unwrap();
+        writer.write_all(&data[3..]).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let file = encrypt(b"skytable!!!", Some(&KEY));
+        assert!(file.starts_with(&MAGIC));
+        assert_eq!(file.len(), 8 + 12 + 11 + 16);
+        assert!(!file.windows(8).any(|window| window == b"skytable"));
+        assert_eq!(
+            decrypt_with("test", file, Some(&KEY)).unwrap(),
+            b"skytable!!!"
+        );
+    }
+
+    #[test]
+    fn nonces_are_random() {
+        assert_ne!(
+            encrypt(b"skytable!!!", Some(&KEY)),
+            encrypt(b"skytable!!!", Some(&KEY))
+        );
+    }
+
+    #[test]
+    fn without_key() {
+        let file = encrypt(b"skytable!!!", None);
+        assert_eq!(file, b"skytable!!!");
+        // files that aren't encrypted are only read without a key
+        assert!(matches!(
+            decrypt_with("test", file.clone(), Some(&KEY)).unwrap_err(),
+            StorageEngineError::Unencrypted(path) if path == "test"
+        ));
+        assert_eq!(decrypt_with("test", file, None).unwrap(), b"skytable!!!");
+        let file = encrypt(b"skytable!!!", Some(&KEY));
+        assert!(matches!(
+            decrypt_with("test", file, None).unwrap_err(),
+            StorageEngineError::MissingKey(path) if path == "test"
+        ));
+    }
+
+    #[test]
+    fn wrong_key_or_tampered() {
+        let file = encrypt(b"skytable!!!", Some(&KEY));
+        assert!(matches!(
+            decrypt_with("test", file.clone(), Some(&[8; 32])).unwrap_err(),
+            StorageEngineError::DecryptionFailed(_)
+        ));
+        let mut tampered = file.clone();
+        tampered[8 + 12] ^= 0xFF;
+        assert!(matches!(
+            decrypt_with("test", tampered, Some(&KEY)).unwrap_err(),
+            StorageEngineError::DecryptionFailed(_)
+        ));
+        assert!(matches!(
+            decrypt_with("test", file[..30].to_vec(), Some(&KEY)).unwrap_err(),
+            StorageEngineError::DecryptionFailed(_)
+        ));
+    }
+
+    #[test]
+    fn keys() {
+        let encoded = base64::encode(KEY);
+        assert_eq!(parse_key(&format!("{encoded}\n")), Some(KEY));
+        assert_eq!(parse_key(&base64::encode([7; 16])), None);
+        assert_eq!(parse_key("not base64"), None);
+    }
+}
@@ -24,7 +24,10 @@
  *
 */
 
-use {crate::corestore::memstore::ObjectID, core::fmt, std::io::Error as IoError};
+use {
+    super::preload::FORMAT_VERSION, crate::corestore::memstore::ObjectID, core::fmt,
+    std::io::Error as IoError,
+};
 
 pub type StorageEngineResult<T> = Result<T, StorageEngineError>;
 
@@ -47,8 +50,19 @@ pub enum StorageEngineError {
     IoErrorExtra(IoError, String),
     /// A corrupted file
     CorruptedFile(String),
+    /// A file whose data doesn't match its checksum
+    ChecksumMismatch(String),
     /// The file contains bad metadata
     BadMetadata(String),
+    /// The data is in a (newer) format that we can't read
+    UnsupportedFormat(u8),
+    /// An encrypted file, but there's no encryption key
+    MissingKey(String),
+    /// An encrypted file that couldn't be decrypted (with the wrong key, or because it was
+    /// tampered with)
+    DecryptionFailed(String),
+    /// A file that isn't encrypted, even though there's an encryption key
+    Unencrypted(String),
 }
 
 impl StorageEngineError {
@@ -70,6 +84,10 @@ impl StorageEngineError {
     pub fn ioerror_extra(ioe: IoError, extra: impl ToString) -> Self {
         Self::IoErrorExtra(ioe, extra.to_string())
     }
+    /// Returns true if the data in a file is corrupted
+    pub const fn is_corruption(&self) -> bool {
+        matches!(self, Self::CorruptedFile(_) | Self::ChecksumMismatch(_))
+    }
 }
 
 impl From<IoError> for StorageEngineError {
@@ -84,7 +102,26 @@ impl fmt::Display for StorageEngineError {
             Self::IoError(ioe) => write!(f, "I/O error: {}", ioe),
             Self::IoErrorExtra(ioe, extra) => write!(f, "I/O error while {extra}: {ioe}"),
             Self::CorruptedFile(cfile) => write!(f, "file `{cfile}` is corrupted"),
+            Self::ChecksumMismatch(file) => write!(f, "file `{file}` failed its checksum"),
             Self::BadMetadata(file) => write!(f, "bad metadata in file `{file}`"),
+            Self::UnsupportedFormat(version) => write!(
+                f,
+                "the data is in format v{version}, but this version of the server only supports \
+                formats up to v{FORMAT_VERSION} (was it written by a newer version?)"
+            ),
+            Self::MissingKey(file) => write!(
+                f,
+                "file `{file}` is encrypted, but no encryption key was configured"
+            ),
+            Self::DecryptionFailed(file) => write!(
+                f,
+                "file `{file}` couldn't be decrypted (is the encryption key right?)"
+            ),
+            Self::Unencrypted(file) => write!(
+                f,
+                "file `{file}` isn't encrypted, but an encryption key was configured (data \
+                stored without a key can be moved over with `SYS EXPORT` and `SYS IMPORT`)"
+            ),
         }
     }
 }
@@ -0,0 +1,322 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Table exports
+//!
+//! `SYS EXPORT` writes a single table to a portable archive, that `SYS IMPORT` can load on
+//! another server (or on a later version). The archive describes itself, so it doesn't depend
+//! on the layout of the data directory:
+//!
+//! ```text
+//! [b"SKYEXPORT"][VERSION: u8][MANIFEST LEN: u32][MANIFEST][DATA LEN: u64][DATA][CRC-32: u32]
+//! ```
+//!
+//! The integers are little endian and the checksum covers everything before it. The manifest
+//! is a TOML document with the model and the metadata of the table (see [`Manifest`]). The
+//! data is the table serialized like it is in the data directory, except that the values are
+//! always written as they are (even if the table compresses them or keeps them on disk). The
+//! whole data section is then compressed (see [`compression`]).
+//!
+//! The sizes in the serialized data are in the byte order of the server that wrote it (see the
+//! appendix in [`super`]), and this is recorded in the manifest so that an archive from a
+//! server with the other byte order is refused instead of being misread. Archives aren't
+//! encrypted, even if the data directory is (see [`super::encryption`]), since they're meant to
+//! be loaded by other servers
+//!
+//! The archives are kept in the export directory (see [`ExportConfig`]): the paths that are
+//! given to `SYS EXPORT` and `SYS IMPORT` are relative to it, and can't leave it
+
+use {
+    super::{
+        checksum,
+        de::DeserializeInto,
+        error::{StorageEngineError, StorageEngineResult},
+        flush::FlushableTable,
+        unflush::{self, TableSource},
+        Coremap,
+    },
+    crate::{
+        config::ExportConfig,
+        corestore::{
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{compression, expiry, storage::StorageKind},
+    },
+    core::{fmt, str},
+    libsky::VERSION,
+    parking_lot::{const_mutex, Mutex},
+    serde::{Deserialize, Serialize},
+    std::{
+        fs::{self, OpenOptions},
+        io::{Error as IoError, Write},
+        path::{Component, Path, PathBuf},
+    },
+};
+
+/// The magic at the start of every archive
+const MAGIC: &[u8; 9] = b"SKYEXPORT";
+/// The version of the archive format
+const FORMAT_VERSION: u8 = 1;
+/// The byte order of the sizes in the serialized data
+#[cfg(target_endian = "little")]
+const ENDIAN: &str = "little";
+#[cfg(target_endian = "big")]
+const ENDIAN: &str = "big";
+const COMPRESSION_NONE: &str = "none";
+const COMPRESSION_LZ4: &str = "lz4";
+const STORAGE_MEMORY: &str = "memory";
+const STORAGE_DISK: &str = "disk";
+
+/// Where the archives are kept, and who can export and import tables
+static CONFIG: Mutex<ExportConfig> = const_mutex(ExportConfig::default());
+
+/// Set the export directory (and whether tables can be exported while auth is disabled)
+pub fn init(cfg: &ExportConfig) {
+    *CONFIG.lock() = cfg.clone();
+}
+
+/// Returns true if anyone can export and import tables while auth is disabled
+pub fn allows_noauth() -> bool {
+    CONFIG.lock().allow_noauth
+}
+
+/// Resolve a path that was given to `SYS EXPORT` or `SYS IMPORT` against the export directory.
+/// Returns `None` if the path is absolute or if it could leave the directory
+pub fn resolve(path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    let contained = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if path.is_empty() || !contained {
+        return None;
+    }
+    Some(Path::new(CONFIG.lock().dir()).join(relative))
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
+
+#[derive(Debug)]
+pub enum ExportError {
+    /// The archive couldn't be read or written
+    Io(IoError),
+    /// The archive is malformed, or it can't be loaded by this server
+    BadArchive(&'static str),
+}
+
+impl From<IoError> for ExportError {
+    fn from(e: IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::BadArchive(e) => write!(f, "bad archive: {e}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The manifest of an archive
+pub struct Manifest {
+    /// the version of the server that wrote the archive
+    pub server: String,
+    /// when the archive was written (UNIX millis)
+    pub exported: u64,
+    /// the keyspace that the table was exported from
+    pub keyspace: String,
+    /// the name of the table
+    pub table: String,
+    /// the data declaration, for example `(str,str)`
+    pub data: String,
+    pub volatile: bool,
+    /// how the table stores its values (`none` or `lz4`)
+    pub compression: String,
+    /// where the table keeps its values (`memory` or `disk`)
+    pub storage: String,
+    /// when the table was created (UNIX millis)
+    pub created: u64,
+    /// the number of entries
+    pub entries: u64,
+    /// the byte order of the sizes in the data (`little` or `big`)
+    pub endian: String,
+}
+
+/// The data section of an archive, once it's decompressed
+struct ArchivedData<'a>(&'a [u8]);
+
+impl<'a> TableSource for ArchivedData<'a> {
+    fn decode<T: DeserializeInto>(&self) -> StorageEngineResult<T> {
+        super::de::deserialize_into(self.0)
+            .ok_or_else(|| StorageEngineError::CorruptedFile("export archive".into()))
+    }
+    fn decode_packed(&self) -> StorageEngineResult<Coremap<SharedSlice, SharedSlice>> {
+        // the archive has the values as they are
+        let data: Coremap<SharedSlice, SharedSlice> = self.decode()?;
+        Ok(data
+            .into_iter()
+            .map(|(key, value)| (key, compression::pack(&value)))
+            .collect())
+    }
+}
+
+/// Write the table to a new archive at `path`, creating the directories that it's in. This never
+/// overwrites a file: if there's one at `path` already, this fails with
+/// [`std::io::ErrorKind::AlreadyExists`]
+pub fn export(path: &Path, keyspace: &str, name: &str, table: &Table) -> ExportResult<()> {
+    let archive = self::encode(keyspace, name, table)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let ret = file.write_all(&archive).and_then(|_| file.sync_all());
+    if ret.is_err() {
+        // don't leave half an archive behind
+        let _ = fs::remove_file(path);
+    }
+    ret.map_err(ExportError::from)
+}
+
+/// Read the archive at `path` and return its manifest and the table in it
+pub fn import(path: &Path) -> ExportResult<(Manifest, Table)> {
+    let archive = fs::read(path)?;
+    self::decode(&archive)
+}
+
+/// Encode the table into an archive
+pub fn encode(keyspace: &str, name: &str, table: &Table) -> ExportResult<Vec<u8>> {
+    let description = table.description();
+    let manifest = Manifest {
+        server: VERSION.to_owned(),
+        exported: expiry::now_millis(),
+        keyspace: keyspace.to_owned(),
+        table: name.to_owned(),
+        data: description.data.to_owned(),
+        volatile: description.volatile,
+        compression: if description.compressed {
+            COMPRESSION_LZ4
+        } else {
+            COMPRESSION_NONE
+        }
+        .to_owned(),
+        storage: description.storage.to_owned(),
+        created: description.created,
+        entries: description.entries as u64,
+        endian: ENDIAN.to_owned(),
+    };
+    let manifest = toml::to_string(&manifest)
+        .map_err(|_| ExportError::BadArchive("failed to encode the manifest"))?;
+    let mut data = Vec::new();
+    self::write_data(table, &mut data)?;
+    let data = compression::pack(&data);
+    let mut archive = Vec::with_capacity(MAGIC.len() + manifest.len() + data.len() + 32);
+    archive.extend_from_slice(MAGIC);
+    archive.push(FORMAT_VERSION);
+    archive.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+    archive.extend_from_slice(manifest.as_bytes());
+    archive.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    archive.extend_from_slice(&data);
+    let checksum = checksum::crc32(&archive);
+    archive.extend_from_slice(&checksum.to_le_bytes());
+    Ok(archive)
+}
+
+/// Serialize the data of the table, with the values as they are
+fn write_data<W: Write>(table: &Table, writer: &mut W) -> Result<(), IoError> {
+    match table.get_model_ref() {
+        DataModel::KV(ref kve) if kve.storage_kind() != StorageKind::Memory => {
+            super::se::raw_serialize_map_with(kve.get_inner_ref(), writer, |value| {
+                Ok(kve.unpack(value.clone())?)
+            })
+        }
+        _ => table.write_table_to(writer),
+    }
+}
+
+/// Decode an archive and return its manifest and the table in it
+pub fn decode(archive: &[u8]) -> ExportResult<(Manifest, Table)> {
+    let body_len = archive
+        .len()
+        .checked_sub(4)
+        .ok_or(ExportError::BadArchive("the archive is truncated"))?;
+    let (mut body, checksum) = archive.split_at(body_len);
+    if checksum::crc32(body) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(ExportError::BadArchive("checksum mismatch"));
+    }
+    if self::take(&mut body, MAGIC.len())? != MAGIC {
+        return Err(ExportError::BadArchive("not an export archive"));
+    }
+    if self::take(&mut body, 1)?[0] != FORMAT_VERSION {
+        return Err(ExportError::BadArchive("unsupported format version"));
+    }
+    let len = u32::from_le_bytes(self::take(&mut body, 4)?.try_into().unwrap());
+    let manifest: Manifest = str::from_utf8(self::take(&mut body, len as usize)?)
+        .ok()
+        .and_then(|manifest| toml::from_str(manifest).ok())
+        .ok_or(ExportError::BadArchive("bad manifest"))?;
+    let len = u64::from_le_bytes(self::take(&mut body, 8)?.try_into().unwrap());
+    let len = usize::try_from(len).map_err(|_| ExportError::BadArchive("the data is too large"))?;
+    let data = self::take(&mut body, len)?;
+    if !body.is_empty() {
+        return Err(ExportError::BadArchive("trailing data"));
+    }
+    if manifest.endian != ENDIAN {
+        return Err(ExportError::BadArchive(
+            "the archive was written by a server with another byte order",
+        ));
+    }
+    let compressed = match manifest.compression.as_str() {
+        COMPRESSION_NONE => false,
+        COMPRESSION_LZ4 => true,
+        _ => return Err(ExportError::BadArchive("unknown compression")),
+    };
+    let on_disk = match manifest.storage.as_str() {
+        STORAGE_MEMORY => false,
+        STORAGE_DISK => true,
+        _ => return Err(ExportError::BadArchive("unknown storage")),
+    };
+    let model_code = Table::model_code_for(&manifest.data, compressed, on_disk)
+        .ok_or(ExportError::BadArchive("unknown model"))?;
+    let data = compression::unpack(data).ok_or(ExportError::BadArchive("bad data"))?;
+    let table = unflush::table_from_source(&ArchivedData(&data), model_code, manifest.volatile)
+        .ok()
+        .flatten()
+        .ok_or(ExportError::BadArchive("bad data"))?;
+    Ok((manifest, table))
+}
+
+/// Take the next `len` bytes from `input`
+fn take<'a>(input: &mut &'a [u8], len: usize) -> ExportResult<&'a [u8]> {
+    if input.len() < len {
+        return Err(ExportError::BadArchive("the archive is truncated"));
+    }
+    let (taken, rest) = input.split_at(len);
+    *input = rest;
+    Ok(taken)
+}
@@ -38,6 +38,7 @@ use {
             memstore::{Keyspace, Memstore, ObjectID, SystemKeyspace},
             table::{DataModel, SystemDataModel, SystemTable, Table},
         },
+        kvengine::storage::StorageKind,
         registry,
         util::Wrapper,
         IoResult,
@@ -54,6 +55,9 @@ pub trait StorageTarget {
     ///
     /// Example cases where this doesn't apply: snapshots
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool;
+    /// Flushing to this storage target brings the data directory up to date, so it clears the
+    /// dirty flags of the keyspaces (see [`Keyspace::mark_dirty`])
+    const CLEANS_KEYSPACES: bool;
     /// The root for this storage target. **Must not be separator terminated!**
     fn root(&self) -> String;
     /// Returns the path to the `PRELOAD_` **temporary file** ($ROOT/PRELOAD)
@@ -86,6 +90,20 @@ pub trait StorageTarget {
         p.push('_');
         p
     }
+    /// Write a file with `with_open`, given the path to its **temporary file** (as returned by
+    /// the methods above). The temporary file is written and synced, and then renamed over the
+    /// file
+    fn write_file(
+        &self,
+        tmp_path: &str,
+        with_open: impl FnOnce(&mut dyn Write) -> IoResult<()>,
+    ) -> IoResult<()> {
+        self::oneshot::cowfile(tmp_path, |file| with_open(file))
+    }
+    /// Create the directories of all the keyspaces in the store
+    fn create_tree(&self, store: &Memstore) -> IoResult<()> {
+        super::interface::create_tree(self, store)
+    }
 }
 
 /// The autoflush target (BGSAVE target)
@@ -94,6 +112,7 @@ pub struct Autoflush;
 impl StorageTarget for Autoflush {
     const NEEDS_TREE_INIT: bool = false;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = true;
+    const CLEANS_KEYSPACES: bool = true;
     fn root(&self) -> String {
         String::from(interface::DIR_KSROOT)
     }
@@ -113,6 +132,7 @@ impl<'a> RemoteSnapshot<'a> {
 impl<'a> StorageTarget for RemoteSnapshot<'a> {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const CLEANS_KEYSPACES: bool = false;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_RSNAPROOT);
         p.push('/');
@@ -135,6 +155,7 @@ impl LocalSnapshot {
 impl StorageTarget for LocalSnapshot {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const CLEANS_KEYSPACES: bool = false;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_SNAPROOT);
         p.push('/');
@@ -150,6 +171,13 @@ pub trait FlushableKeyspace<T: FlushableTable, U: Deref<Target = T>> {
     /// An iterator to the tables in this keyspace.
     /// All of them implement [`FlushableTable`]
     fn get_iter(&self) -> BorrowedIter<'_, ObjectID, U>;
+    /// The storage and model bytemarks of the defaults for the tables created in this keyspace
+    fn defaults_bytemarks(&self) -> (u8, u8) {
+        (
+            bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+            bytemarks::BYTEMARK_MODEL_NONE,
+        )
+    }
 }
 
 impl FlushableKeyspace<Table, Arc<Table>> for Keyspace {
@@ -159,6 +187,16 @@ impl FlushableKeyspace<Table, Arc<Table>> for Keyspace {
     fn get_iter(&self) -> BorrowedIter<'_, ObjectID, Arc<Table>> {
         self.tables.iter()
     }
+    fn defaults_bytemarks(&self) -> (u8, u8) {
+        let defaults = self.defaults();
+        let storage = if defaults.volatile {
+            bytemarks::BYTEMARK_STORAGE_VOLATILE
+        } else {
+            bytemarks::BYTEMARK_STORAGE_PERSISTENT
+        };
+        let model = defaults.model_code;
+        (storage, model.unwrap_or(bytemarks::BYTEMARK_MODEL_NONE))
+    }
 }
 
 impl FlushableKeyspace<SystemTable, Wrapper<SystemTable>> for SystemKeyspace {
@@ -177,6 +215,8 @@ pub trait FlushableTable {
     fn storage_code(&self) -> u8;
     /// Serializes the table and writes it to the provided buffer
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
+    /// Serializes the expiry deadlines of the keys and writes them to the provided buffer
+    fn write_deadlines_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
     /// Returns the model code bytemark
     fn model_code(&self) -> u8;
 }
@@ -187,12 +227,45 @@ impl FlushableTable for Table {
     }
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
         match self.get_model_ref() {
+            DataModel::KV(ref kve) if kve.storage_kind() == StorageKind::Disk => {
+                // the file only holds the values while we run, so we write out the values
+                super::se::raw_serialize_map_with(kve.get_inner_ref(), writer, |value| {
+                    Ok(kve.unpack(value.clone())?)
+                })
+            }
             DataModel::KV(ref kve) => super::se::raw_serialize_map(kve.get_inner_ref(), writer),
             DataModel::KVExtListmap(ref kvl) => {
                 super::se::raw_serialize_list_map(kvl.get_inner_ref(), writer)
             }
+            DataModel::KVExtSetmap(ref kvs) => {
+                super::se::raw_serialize_set_map(kvs.get_inner_ref(), writer)
+            }
+            DataModel::KVExtZsetmap(ref kvz) => {
+                super::se::raw_serialize_zset_map(kvz.get_inner_ref(), writer)
+            }
+            DataModel::KVExtHashmap(ref kvh) => {
+                super::se::raw_serialize_hash_map(kvh.get_inner_ref(), writer)
+            }
+            DataModel::KVExtCountermap(ref kvc) => {
+                super::se::raw_serialize_counter_map(kvc.get_inner_ref(), writer)
+            }
+            DataModel::KVExtBloommap(ref kvb) => {
+                super::se::raw_serialize_bloom_map(kvb.get_inner_ref(), writer)
+            }
+            DataModel::KVExtHllmap(ref kvh) => {
+                super::se::raw_serialize_hll_map(kvh.get_inner_ref(), writer)
+            }
+            DataModel::KVExtGeomap(ref kvg) => {
+                super::se::raw_serialize_geo_map(kvg.get_inner_ref(), writer)
+            }
+            DataModel::KVExtTimeseriesmap(ref kvt) => {
+                super::se::raw_serialize_timeseries_map(kvt.get_inner_ref(), writer)
+            }
         }
     }
+    fn write_deadlines_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        super::se::raw_serialize_deadlines(&self.deadlines(), writer)
+    }
     fn storage_code(&self) -> u8 {
         self.storage_type()
     }
@@ -208,20 +281,38 @@ impl FlushableTable for SystemTable {
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
         match self.get_model_ref() {
             SystemDataModel::Auth(amap) => super::se::raw_serialize_map(amap.as_ref(), writer),
+            SystemDataModel::Acl(aclmap) => super::se::raw_serialize_map(aclmap.as_ref(), writer),
+            SystemDataModel::Rotation(rmap) => super::se::raw_serialize_map(rmap.as_ref(), writer),
         }
     }
+    fn write_deadlines_to<W: Write>(&self, _: &mut W) -> IoResult<()> {
+        // nothing in the system tables expires
+        Ok(())
+    }
     fn storage_code(&self) -> u8 {
         0
     }
     fn model_code(&self) -> u8 {
         match self.get_model_ref() {
             SystemDataModel::Auth(_) => bytemarks::SYSTEM_TABLE_AUTH,
+            SystemDataModel::Acl(_) => bytemarks::SYSTEM_TABLE_ACL,
+            SystemDataModel::Rotation(_) => bytemarks::SYSTEM_TABLE_ROTATION,
         }
     }
 }
 
 /// Flush the entire **preload + keyspaces + their partmaps**
 pub fn flush_full<T: StorageTarget>(target: T, store: &Memstore) -> IoResult<()> {
+    let ret = self::flush_everything(&target, store);
+    if ret.is_err() && T::CLEANS_KEYSPACES {
+        // we don't know which keyspaces made it to disk, so the next flush has to be a full
+        // one again
+        registry::get_preload_tripswitch().trip();
+    }
+    ret
+}
+
+fn flush_everything<T: StorageTarget>(target: &T, store: &Memstore) -> IoResult<()> {
     // IMPORTANT: Just untrip and get the status at this exact point in time
     // don't spread it over two atomic accesses because another thread may have updated
     // it in-between. Even if it was untripped, we'll get the expected outcome here: false
@@ -232,22 +323,76 @@ pub fn flush_full<T: StorageTarget>(target: T, store: &Memstore) -> IoResult<()>
     }
     if should_create_tree {
         // re-init the tree as new tables/keyspaces may have been added
-        super::interface::create_tree(&target, store)?;
-        self::oneshot::flush_preload(&target, store)?;
+        target.create_tree(store)?;
+        self::oneshot::flush_preload(target, store)?;
     }
     // flush userspace keyspaces
     for keyspace in store.keyspaces.iter() {
-        self::flush_keyspace_full(&target, keyspace.key(), keyspace.value().as_ref())?;
+        if T::CLEANS_KEYSPACES {
+            keyspace.value().take_dirty();
+            self::flush_keyspace_clean(target, keyspace.key(), keyspace.value())?;
+        } else {
+            self::flush_keyspace_full(target, keyspace.key(), keyspace.value().as_ref())?;
+        }
     }
     // flush system tables
     // HACK(@ohsayan): DO NOT REORDER THIS. THE above loop will flush a PARTMAP and an empty
     // keyspace once. But this has to be done again! The system keyspace in the above loop is a
     // dummy one because it is located in a different field. So, we need to flush the actual
     // tables
-    self::flush_keyspace_full(&target, &SYSTEM, &store.system)?;
+    self::flush_keyspace_full(target, &SYSTEM, &store.system)?;
+    Ok(())
+}
+
+/// Flush the keyspaces that may have changed since they were last flushed (see
+/// [`Keyspace::mark_dirty`]) and the system tables to the data directory. This is what BGSAVE
+/// runs, so that keyspaces that are rarely written to aren't written out over and over again.
+/// If the tree changed (a keyspace or table was created, dropped or renamed, or the keyspaces
+/// were restored from a snapshot), everything is flushed with [`flush_full`] instead
+pub fn flush_dirty(store: &Memstore) -> IoResult<()> {
+    if registry::get_preload_tripswitch().is_tripped() {
+        return self::flush_full(Autoflush, store);
+    }
+    for keyspace in store.keyspaces.iter() {
+        if keyspace.value().take_dirty() {
+            self::flush_keyspace_clean(&Autoflush, keyspace.key(), keyspace.value())?;
+        }
+    }
+    self::flush_keyspace_full(&Autoflush, &SYSTEM, &store.system)
+}
+
+/// Flush a single keyspace to the data directory, whether or not it changed (for `SYS FLUSH
+/// <keyspace>`). The `PRELOAD` is left alone, so if the tree changed, it's only brought up to
+/// date by the next full flush
+pub fn flush_one(store: &Memstore, ksid: &ObjectID, keyspace: &Keyspace) -> IoResult<()> {
+    if registry::get_preload_tripswitch().is_tripped() {
+        // the keyspace may be a new one
+        Autoflush.create_tree(store)?;
+    }
+    keyspace.take_dirty();
+    self::flush_keyspace_clean(&Autoflush, ksid, keyspace)?;
+    if ksid == &SYSTEM {
+        // the system tables are held elsewhere (see [`flush_full`])
+        self::flush_keyspace_full(&Autoflush, &SYSTEM, &store.system)?;
+    }
     Ok(())
 }
 
+/// Flush a keyspace whose dirty flag was just cleared (see [`Keyspace::take_dirty`]) to the
+/// data directory. If the flush fails, or if the keyspace has keys that can expire (and hence
+/// go away without a write), it's marked as dirty again
+fn flush_keyspace_clean<T: StorageTarget>(
+    target: &T,
+    ksid: &ObjectID,
+    keyspace: &Keyspace,
+) -> IoResult<()> {
+    let ret = self::flush_keyspace_full(target, ksid, keyspace);
+    if ret.is_err() || keyspace.has_expiring_keys() {
+        keyspace.mark_dirty();
+    }
+    ret
+}
+
 /// Flushes the entire **keyspace + partmap**
 pub fn flush_keyspace_full<T, U, Tbl, K>(target: &T, ksid: &ObjectID, keyspace: &K) -> IoResult<()>
 where
@@ -270,7 +415,7 @@ pub mod oneshot {
     use std::fs::{self, File};
 
     #[inline(always)]
-    fn cowfile(
+    pub(super) fn cowfile(
         cowfile_name: &str,
         with_open: impl FnOnce(&mut File) -> IoResult<()>,
     ) -> IoResult<()> {
@@ -292,8 +437,8 @@ pub mod oneshot {
             Ok(())
         } else {
             let path = unsafe { target.table_target(ksid.as_str(), tableid.as_str()) };
-            cowfile(&path, |file| {
-                super::interface::serialize_table_into_slow_buffer(file, table)
+            target.write_file(&path, |mut file| {
+                super::interface::serialize_table_into_slow_buffer(&mut file, table)
             })
         }
     }
@@ -321,16 +466,16 @@ pub mod oneshot {
         K: FlushableKeyspace<Tbl, U>,
     {
         let path = unsafe { target.partmap_target(ksid.as_str()) };
-        cowfile(&path, |file| {
-            super::interface::serialize_partmap_into_slow_buffer(file, keyspace)
+        target.write_file(&path, |mut file| {
+            super::interface::serialize_partmap_into_slow_buffer(&mut file, keyspace)
         })
     }
 
     // Flush the `PRELOAD`
     pub fn flush_preload<T: StorageTarget>(target: &T, store: &Memstore) -> IoResult<()> {
         let preloadtmp = target.preload_target();
-        cowfile(&preloadtmp, |file| {
-            super::interface::serialize_preload_into_slow_buffer(file, store)
+        target.write_file(&preloadtmp, |mut file| {
+            super::interface::serialize_preload_into_slow_buffer(&mut file, store)
         })
     }
 }
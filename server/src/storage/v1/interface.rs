@@ -32,7 +32,11 @@ use {
     crate::{
         corestore::memstore::Memstore,
         registry,
-        storage::v1::flush::{FlushableKeyspace, FlushableTable, StorageTarget},
+        storage::v1::{
+            checksum::ChecksumWriter,
+            encryption::EncryptingWriter,
+            flush::{FlushableKeyspace, FlushableTable, StorageTarget},
+        },
         IoResult,
     },
     core::ops::Deref,
@@ -48,9 +52,14 @@ pub const DIR_SNAPROOT: &str = "data/snaps";
 pub const DIR_RSNAPROOT: &str = "data/rsnap";
 pub const DIR_BACKUPS: &str = "data/backups";
 pub const DIR_ROOT: &str = "data";
+pub const DIR_QUARANTINE: &str = "data/quarantine";
+pub const DIR_DISK: &str = "data/disk";
+pub const FILE_WAL: &str = "data/wal";
+pub const DIR_WALARCHIVE: &str = "data/walarchive";
+pub const FILE_CDCOFFSET: &str = "data/cdcoffset";
 
 /// Creates the directories for the keyspaces
-pub fn create_tree<T: StorageTarget>(target: &T, memroot: &Memstore) -> IoResult<()> {
+pub fn create_tree<T: StorageTarget + ?Sized>(target: &T, memroot: &Memstore) -> IoResult<()> {
     for ks in memroot.keyspaces.iter() {
         unsafe {
             try_dir_ignore_existing!(target.keyspace_target(ks.key().as_str()))?;
@@ -148,14 +157,17 @@ pub fn cleanup_tree(memroot: &Memstore) -> IoResult<()> {
 
 /// Uses a buffered writer under the hood to improve write performance as the provided
 /// writable interface might be very slow. The buffer does flush once done, however, it
-/// is important that you fsync yourself!
+/// is important that you fsync yourself! The expiry deadlines of the keys are written before
+/// the data of the table. Like every file, the table ends with a checksum
+/// (see [`super::checksum`]), and it's encrypted if there's a key (see [`super::encryption`])
 pub fn serialize_table_into_slow_buffer<T: Write, U: FlushableTable>(
     buffer: &mut T,
     writable_item: &U,
 ) -> IoResult<()> {
-    let mut buffer = BufWriter::new(buffer);
+    let mut buffer = ChecksumWriter::new(EncryptingWriter::new(BufWriter::new(buffer))?);
+    writable_item.write_deadlines_to(&mut buffer)?;
     writable_item.write_table_to(&mut buffer)?;
-    buffer.flush()?;
+    buffer.finish()?.finish()?.flush()?;
     Ok(())
 }
 
@@ -166,9 +178,9 @@ where
     Tbl: FlushableTable,
     K: FlushableKeyspace<Tbl, U>,
 {
-    let mut buffer = BufWriter::new(buffer);
+    let mut buffer = ChecksumWriter::new(EncryptingWriter::new(BufWriter::new(buffer))?);
     super::se::raw_serialize_partmap(&mut buffer, ks)?;
-    buffer.flush()?;
+    buffer.finish()?.finish()?.flush()?;
     Ok(())
 }
 
@@ -176,8 +188,8 @@ pub fn serialize_preload_into_slow_buffer<T: Write>(
     buffer: &mut T,
     store: &Memstore,
 ) -> IoResult<()> {
-    let mut buffer = BufWriter::new(buffer);
+    let mut buffer = ChecksumWriter::new(EncryptingWriter::new(BufWriter::new(buffer))?);
     super::preload::raw_generate_preload(&mut buffer, store)?;
-    buffer.flush()?;
+    buffer.finish()?.finish()?.flush()?;
     Ok(())
 }
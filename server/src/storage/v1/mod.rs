@@ -43,6 +43,25 @@ All sizes are stored in native endian. If a dataset is imported from a system fr
 simply translated into the host's native endian. How everything else is stored is not worth
 discussing here. Byte swaps just need one instruction on most architectures
 
+## Checksums
+
+Every file ends with a CRC-32 checksum of its data, which is verified when it's read. A
+corrupted table is quarantined instead of being loaded (see [`checksum`] and [`quarantine`])
+
+## Expiry deadlines
+
+The expiry deadlines of the keys of a table are written at the start of its file, so that
+keys with a TTL still expire after a restart (see `se::raw_serialize_deadlines`)
+
+## Encryption
+
+If an encryption key is configured, every file is encrypted with AES-256-GCM (see [`encryption`])
+
+## Format versions
+
+The `PRELOAD` is stamped with the version of the format that the data is in. Data in an older
+format is backed up and upgraded when the server starts up (see [`upgrade`])
+
 ## Safety
 
 > Trust me, all methods are bombingly unsafe. They do such crazy things that you might not
@@ -62,17 +81,27 @@ use {
 mod macros;
 // endof do not mess
 pub mod bytemarks;
+pub mod checksum;
+pub mod encryption;
 pub mod error;
+pub mod export;
 pub mod flush;
 pub mod interface;
 pub mod iter;
 pub mod preload;
+pub mod quarantine;
 pub mod sengine;
+pub mod sink;
 pub mod unflush;
+pub mod upgrade;
+pub mod wal;
 // test
 #[cfg(test)]
 mod tests;
 
+/// The marker at the start of the expiry deadlines of a table
+const DEADLINES_MARKER: [u8; 8] = [0xFF; 8];
+
 /*
     Endian and pointer "appendix":
     We assume a fixed size of 1 for all the cases. All sizes don't hit over isize::MAX as
@@ -184,11 +213,15 @@ unsafe fn raw_byte_repr<'a, T: 'a>(len: &'a T) -> &'a [u8] {
 
 mod se {
     use super::*;
-    use crate::kvengine::LockedVec;
+    use crate::kvengine::{
+        LockedBloom, LockedGeo, LockedHll, LockedMap, LockedSet, LockedTimeseries, LockedVec,
+        LockedZset,
+    };
     use crate::storage::v1::flush::FlushableKeyspace;
     use crate::storage::v1::flush::FlushableTable;
     use crate::IoResult;
     use core::ops::Deref;
+    use core::sync::atomic::{AtomicU64, Ordering};
 
     macro_rules! unsafe_sz_byte_repr {
         ($e:expr) => {
@@ -234,6 +267,59 @@ mod se {
         Ok(())
     }
 
+    /// Serialize a map with the values returned by `value` (instead of the ones in the map)
+    /// and write it to a provided buffer. This has the same layout as [`raw_serialize_map`].
+    /// If `value` fails for any value, so does the serialization
+    pub fn raw_serialize_map_with<W, F>(
+        map: &Coremap<SharedSlice, SharedSlice>,
+        w: &mut W,
+        value: F,
+    ) -> IoResult<()>
+    where
+        W: Write,
+        F: Fn(&SharedSlice) -> IoResult<SharedSlice>,
+    {
+        unsafe {
+            w.write_all(raw_byte_repr(&to_64bit_native_endian!(map.len())))?;
+            for kv in map.iter() {
+                let (k, v) = (kv.key(), value(kv.value())?);
+                w.write_all(raw_byte_repr(&to_64bit_native_endian!(k.len())))?;
+                w.write_all(raw_byte_repr(&to_64bit_native_endian!(v.len())))?;
+                w.write_all(k)?;
+                w.write_all(&v)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize the expiry deadlines of the keys of a table and write them to a provided
+    /// buffer. This goes before the data of the table
+    pub fn raw_serialize_deadlines<W: Write>(
+        deadlines: &[(SharedSlice, u64)],
+        w: &mut W,
+    ) -> IoResult<()> {
+        /*
+        [8B: Marker][8B: Section extent][8B: Extent]([8B: Key extent][?B: Key][8B: Deadline])*
+        The marker has all its bits set, which no table can start with (its first 8 bytes are
+        the number of entries). The deadlines are UNIX timestamps in milliseconds, in little
+        endian
+        */
+        let section = deadlines
+            .iter()
+            .fold(8, |extent, (key, _)| extent + 16 + key.len());
+        unsafe {
+            w.write_all(&DEADLINES_MARKER)?;
+            w.write_all(unsafe_sz_byte_repr!(section))?;
+            w.write_all(unsafe_sz_byte_repr!(deadlines.len()))?;
+            for (key, deadline) in deadlines {
+                w.write_all(unsafe_sz_byte_repr!(key.len()))?;
+                w.write_all(key)?;
+                w.write_all(&deadline.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
     /// Serialize a set and write it to a provided buffer
     pub fn raw_serialize_set<W, K, V>(map: &Coremap<K, V>, w: &mut W) -> IoResult<()>
     where
@@ -252,9 +338,11 @@ mod se {
         Ok(())
     }
 
-    /// Generate a partition map for the given keyspace
+    /// Generate a partition map for the given keyspace. It ends with the storage and model
+    /// types of the defaults for the tables created in the keyspace
     /// ```text
     /// [8B: EXTENT]([8B: LEN][?B: PARTITION ID][1B: Storage type][1B: Model type])*
+    /// [1B: Default storage type][1B: Default model type]
     /// ```
     pub fn raw_serialize_partmap<W, U, Tbl, K>(w: &mut W, keyspace: &K) -> IoResult<()>
     where
@@ -278,6 +366,10 @@ mod se {
                 // now model type
                 w.write_all(raw_byte_repr(&table.model_code()))?;
             }
+            // and the defaults
+            let (storage, model) = keyspace.defaults_bytemarks();
+            w.write_all(raw_byte_repr(&storage))?;
+            w.write_all(raw_byte_repr(&model))?;
         }
         Ok(())
     }
@@ -311,6 +403,290 @@ mod se {
         }
         Ok(())
     }
+    pub fn raw_serialize_set_map<W>(
+        data: &Coremap<SharedSlice, LockedSet>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Set extent]([8B: Member extent][?B: Member])*)*
+        (this is exactly the layout of a list map)
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // set payload
+                let sread = key.value().read();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the set payload
+                w.write_all(unsafe_sz_byte_repr!(sread.len()))?;
+                for member in sread.iter() {
+                    // write member extent
+                    w.write_all(unsafe_sz_byte_repr!(member.len()))?;
+                    // write member
+                    w.write_all(member)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_zset_map<W>(
+        data: &Coremap<SharedSlice, LockedZset>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Zset extent]([8B: Element extent][8B: Score][?B: Member])*)*
+        This is the layout of a list map, where every element is the score (as the little endian
+        bits of an `f64`) followed by the member
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // zset payload
+                let zread = key.value().read();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the zset payload
+                w.write_all(unsafe_sz_byte_repr!(zread.len()))?;
+                for (member, score) in zread.iter() {
+                    // write element extent
+                    w.write_all(unsafe_sz_byte_repr!(member.len() + 8))?;
+                    // write score
+                    w.write_all(&score.to_bits().to_le_bytes())?;
+                    // write member
+                    w.write_all(member)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_hash_map<W>(
+        data: &Coremap<SharedSlice, LockedMap>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Element count]([8B: Field extent][?B: Field][8B: Value extent][?B: Value])*)*
+        This is the layout of a list map, where every field is followed by its value (so the
+        element count is twice the number of fields)
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // hash payload
+                let hread = key.value().read();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the hash payload
+                w.write_all(unsafe_sz_byte_repr!(hread.len() * 2))?;
+                for (field, value) in hread.iter() {
+                    // write field extent
+                    w.write_all(unsafe_sz_byte_repr!(field.len()))?;
+                    // write field
+                    w.write_all(field)?;
+                    // write value extent
+                    w.write_all(unsafe_sz_byte_repr!(value.len()))?;
+                    // write value
+                    w.write_all(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_counter_map<W>(
+        data: &Coremap<SharedSlice, AtomicU64>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Counter])*
+        The counter is stored in little endian
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the counter
+                w.write_all(&key.value().load(Ordering::Acquire).to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_bloom_map<W>(
+        data: &Coremap<SharedSlice, LockedBloom>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Filter extent][?B: Filter])*
+        The filter is written in the format produced by `BloomFilter::to_bytes`
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // filter payload
+                let filter = key.value().read().to_bytes();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the filter extent
+                w.write_all(unsafe_sz_byte_repr!(filter.len()))?;
+                // write the filter
+                w.write_all(&filter)?;
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_hll_map<W>(
+        data: &Coremap<SharedSlice, LockedHll>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: HLL extent][?B: HLL])*
+        The HLL is written in the (sparse or dense) format produced by `HyperLogLog::to_bytes`
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // HLL payload
+                let hll = key.value().read().to_bytes();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the HLL extent
+                w.write_all(unsafe_sz_byte_repr!(hll.len()))?;
+                // write the HLL
+                w.write_all(&hll)?;
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_geo_map<W>(
+        data: &Coremap<SharedSlice, LockedGeo>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Geo extent]([8B: Element extent][8B: Longitude][8B: Latitude][?B: Member])*)*
+        This is the layout of a list map, where every element is the location (as the little
+        endian bits of two `f64`s) followed by the member
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // geo payload
+                let gread = key.value().read();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the geo payload
+                w.write_all(unsafe_sz_byte_repr!(gread.len()))?;
+                for (member, point) in gread.iter() {
+                    // write element extent
+                    w.write_all(unsafe_sz_byte_repr!(member.len() + 16))?;
+                    // write location
+                    w.write_all(&point.lon.to_bits().to_le_bytes())?;
+                    w.write_all(&point.lat.to_bits().to_le_bytes())?;
+                    // write member
+                    w.write_all(member)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn raw_serialize_timeseries_map<W>(
+        data: &Coremap<SharedSlice, LockedTimeseries>,
+        w: &mut W,
+    ) -> IoResult<()>
+    where
+        W: Write,
+    {
+        /*
+        [8B: Extent]([8B: Key extent][?B: Key][8B: Retention][8B: Sample count]([8B: Timestamp][8B: Value])*)*
+        The retention, timestamps and values (the bits of an `f64`) are stored in little endian
+        */
+        unsafe {
+            // Extent
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            // Enter iter
+            '_1: for key in data.iter() {
+                // key
+                let k = key.key();
+                // series payload
+                let sread = key.value().read();
+                // write the key extent
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                // write the key
+                w.write_all(k)?;
+                // write the retention
+                w.write_all(&sread.retention().to_le_bytes())?;
+                // write the samples
+                w.write_all(unsafe_sz_byte_repr!(sread.len()))?;
+                for sample in sread.iter() {
+                    w.write_all(&sample.timestamp.to_le_bytes())?;
+                    w.write_all(&sample.value.to_bits().to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
     /// Serialize a `[[u8]]` (i.e a slice of slices)
     pub fn raw_serialize_nested_list<'a, W, T: 'a + ?Sized, U: 'a>(
         w: &mut W,
@@ -341,9 +717,20 @@ mod se {
 
 mod de {
     use super::iter::{RawSliceIter, RawSliceIterBorrowed};
-    use super::{Array, Coremap, Hash, HashSet, SharedSlice};
-    use crate::kvengine::LockedVec;
+    use super::{Array, Coremap, Hash, HashSet, SharedSlice, DEADLINES_MARKER};
+    use crate::corestore::{
+        bloom::BloomFilter,
+        geo::{self, GeoIndex},
+        hll::HyperLogLog,
+        timeseries::{Sample, TimeSeries},
+        zset::SortedSet,
+    };
+    use crate::kvengine::{
+        disk::Loaded, LockedBloom, LockedGeo, LockedHll, LockedMap, LockedSet, LockedTimeseries,
+        LockedVec, LockedZset,
+    };
     use core::ptr;
+    use core::sync::atomic::AtomicU64;
     use parking_lot::RwLock;
     use std::collections::HashMap;
 
@@ -366,6 +753,15 @@ mod de {
         }
     }
 
+    impl DeserializeInto for Loaded {
+        fn new_empty() -> Self {
+            Loaded::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_disk_map(slice)
+        }
+    }
+
     impl DeserializeInto for Coremap<SharedSlice, LockedVec> {
         fn new_empty() -> Self {
             Coremap::new()
@@ -375,6 +771,78 @@ mod de {
         }
     }
 
+    impl DeserializeInto for Coremap<SharedSlice, LockedSet> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_set_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedZset> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_zset_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedMap> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_hash_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, AtomicU64> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_counter_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedBloom> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_bloom_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedHll> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_hll_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedGeo> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_geo_map(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedTimeseries> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_timeseries_map(slice)
+        }
+    }
+
     impl<T, U> DeserializeInto for Coremap<T, U>
     where
         T: Hash + Eq + DeserializeFrom,
@@ -392,6 +860,36 @@ mod de {
         T::from_slice(input)
     }
 
+    /// Split the expiry deadlines (see [`super::se::raw_serialize_deadlines`]) off the data of
+    /// a table, returning them along with the rest of the data. Tables that were written
+    /// before the deadlines were persisted don't have any
+    pub fn split_deadlines(data: &[u8]) -> Option<(Vec<(SharedSlice, u64)>, &[u8])> {
+        if !data.starts_with(&DEADLINES_MARKER) {
+            return Some((Vec::new(), data));
+        }
+        let data = &data[DEADLINES_MARKER.len()..];
+        let mut rawiter = RawSliceIter::new(data);
+        let extent = rawiter.next_64bit_integer_to_usize()?;
+        let section = rawiter.next_borrowed_slice(extent)?;
+        let rest = &data[8 + extent..];
+        let mut rawiter = RawSliceIter::new(section);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let mut deadlines = Vec::new();
+        deadlines.try_reserve(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let deadline = rawiter.next_borrowed_slice(8)?;
+            deadlines.push((key, u64::from_le_bytes(deadline.try_into().ok()?)));
+        }
+        if rawiter.end_of_allocation() {
+            Some((deadlines, rest))
+        } else {
+            // the extent doesn't match what's in the section
+            None
+        }
+    }
+
     impl<const N: usize> DeserializeFrom for Array<u8, N> {
         fn is_expected_len(clen: usize) -> bool {
             clen <= N
@@ -463,8 +961,11 @@ mod de {
         }
     }
 
-    /// Deserializes a map-like set which has an 2x1B _bytemark_ for every entry
-    pub fn deserialize_set_ctype_bytemark<T>(data: &[u8]) -> Option<HashMap<T, (u8, u8)>>
+    /// Deserialize a partition map (see [`super::se::raw_serialize_partmap`]): a map-like set
+    /// which has an 2x1B _bytemark_ for every entry, followed by the 2x1B bytemark of the
+    /// defaults of the keyspace. Partition maps that were written before the defaults were
+    /// persisted don't have it
+    pub fn deserialize_partmap<T>(data: &[u8]) -> Option<(HashMap<T, (u8, u8)>, Option<(u8, u8)>)>
     where
         T: DeserializeFrom + Eq + Hash,
     {
@@ -489,7 +990,11 @@ mod de {
             }
         }
         if rawiter.end_of_allocation() {
-            Some(set)
+            return Some((set, None));
+        }
+        let defaults = (rawiter.next_8bit_integer()?, rawiter.next_8bit_integer()?);
+        if rawiter.end_of_allocation() {
+            Some((set, Some(defaults)))
         } else {
             // nope, someone gave us more data
             None
@@ -515,6 +1020,25 @@ mod de {
         }
     }
 
+    /// Deserialize a file that contains a serialized map (see [`deserialize_map`]), writing
+    /// every value out to the disk as soon as it's read
+    pub fn deserialize_disk_map(data: &[u8]) -> Option<Loaded> {
+        let mut rawiter = RawSliceIter::new(data);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let loaded = Loaded::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let (lenkey, lenval) = rawiter.next_64bit_integer_pair_to_usize()?;
+            let key = rawiter.next_owned_data(lenkey)?;
+            let val = rawiter.next_borrowed_slice(lenval)?;
+            loaded.upsert(key, val);
+        }
+        if rawiter.end_of_allocation() {
+            Some(loaded)
+        } else {
+            None
+        }
+    }
+
     pub fn deserialize_list_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedVec>> {
         let mut rawiter = RawSliceIter::new(bytes);
         // get the len
@@ -539,6 +1063,257 @@ mod de {
         }
     }
 
+    pub fn deserialize_set_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedSet>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            let borrowed_iter = rawiter.get_borrowed_iter();
+            // a set has the same layout as a nested list
+            let set = self::deserialize_nested_list(borrowed_iter)?;
+            // push it in
+            map.true_if_insert(key, RwLock::new(set.into_iter().collect()));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_zset_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedZset>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            let borrowed_iter = rawiter.get_borrowed_iter();
+            // a zset has the same layout as a nested list, with the score prefixed to every element
+            let elements = self::deserialize_nested_list(borrowed_iter)?;
+            let mut zset = SortedSet::new();
+            for element in elements {
+                if element.len() < 8 {
+                    // not even a score in there
+                    return None;
+                }
+                let (score, member) = element.split_at(8);
+                let score = f64::from_bits(u64::from_le_bytes(score.try_into().ok()?));
+                zset.insert(SharedSlice::new(member), score);
+            }
+            // push it in
+            map.true_if_insert(key, RwLock::new(zset));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_hash_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedMap>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            let borrowed_iter = rawiter.get_borrowed_iter();
+            // a hash has the same layout as a nested list, with every field followed by its value
+            let elements = self::deserialize_nested_list(borrowed_iter)?;
+            if elements.len() % 2 != 0 {
+                // a field without a value
+                return None;
+            }
+            let mut hash = HashMap::new();
+            hash.try_reserve(elements.len() / 2).ok()?;
+            let mut elements = elements.into_iter();
+            while let (Some(field), Some(value)) = (elements.next(), elements.next()) {
+                hash.insert(field, value);
+            }
+            // push it in
+            map.true_if_insert(key, RwLock::new(hash));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_counter_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, AtomicU64>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            // get the counter
+            let counter = rawiter.next_borrowed_slice(8)?;
+            let counter = u64::from_le_bytes(counter.try_into().ok()?);
+            // push it in
+            map.true_if_insert(key, AtomicU64::new(counter));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_bloom_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedBloom>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            // get the filter
+            let filterlen = rawiter.next_64bit_integer_to_usize()?;
+            let filter = BloomFilter::from_bytes(rawiter.next_borrowed_slice(filterlen)?)?;
+            // push it in
+            map.true_if_insert(key, RwLock::new(filter));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_hll_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedHll>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            // get the HLL
+            let hlllen = rawiter.next_64bit_integer_to_usize()?;
+            let hll = HyperLogLog::from_bytes(rawiter.next_borrowed_slice(hlllen)?)?;
+            // push it in
+            map.true_if_insert(key, RwLock::new(hll));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_geo_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedGeo>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            let borrowed_iter = rawiter.get_borrowed_iter();
+            // a geo index has the same layout as a nested list, with the location prefixed to
+            // every element
+            let elements = self::deserialize_nested_list(borrowed_iter)?;
+            let mut geo = GeoIndex::new();
+            for element in elements {
+                if element.len() < 16 {
+                    // not even a location in there
+                    return None;
+                }
+                let (location, member) = element.split_at(16);
+                let lon = f64::from_bits(u64::from_le_bytes(location[..8].try_into().ok()?));
+                let lat = f64::from_bits(u64::from_le_bytes(location[8..].try_into().ok()?));
+                if !geo::is_valid_location(lon, lat) {
+                    return None;
+                }
+                geo.insert(SharedSlice::new(member), lon, lat);
+            }
+            // push it in
+            map.true_if_insert(key, RwLock::new(geo));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    pub fn deserialize_timeseries_map(
+        bytes: &[u8],
+    ) -> Option<Coremap<SharedSlice, LockedTimeseries>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        // get the len
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        // allocate a map
+        let map = Coremap::try_with_capacity(len).ok()?;
+        // now enter a loop
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            // get key
+            let key = rawiter.next_owned_data(keylen)?;
+            // get the retention
+            let retention = rawiter.next_borrowed_slice(8)?;
+            let retention = u64::from_le_bytes(retention.try_into().ok()?);
+            // get the samples
+            let count = rawiter.next_64bit_integer_to_usize()?;
+            let samples = rawiter.next_borrowed_slice(count.checked_mul(16)?)?;
+            let samples = samples
+                .chunks_exact(16)
+                .map(|sample| {
+                    let (timestamp, value) = sample.split_at(8);
+                    Sample::new(
+                        u64::from_le_bytes(timestamp.try_into().unwrap()),
+                        f64::from_bits(u64::from_le_bytes(value.try_into().unwrap())),
+                    )
+                })
+                .collect();
+            let series = TimeSeries::from_samples(retention, samples)?;
+            // push it in
+            map.true_if_insert(key, RwLock::new(series));
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
     /// Deserialize a nested list: `[EXTENT]([EL_EXT][EL])*`
     ///
     pub fn deserialize_nested_list(mut iter: RawSliceIterBorrowed<'_>) -> Option<Vec<SharedSlice>> {
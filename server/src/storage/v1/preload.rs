@@ -47,16 +47,30 @@ use {
 
 pub type LoadedPartfile = HashMap<ObjectID, (u8, u8)>;
 
-// our version and endian are based on nibbles
+// our version and endian are based on nibbles: the high nibble is the format version (as
+// `0b0111 + version`, since the first version was stamped as `0b1000`) and the low nibble is
+// the endian
 
-const META_SEGMENT_LE: u8 = 0b1000_0000;
-const META_SEGMENT_BE: u8 = 0b1000_0001;
+/// The format version of the data files:
+/// - `1`: the first format
+/// - `2`: every file ends with a checksum (see [`super::checksum`])
+/// - `3`: the tables start with the expiry deadlines of their keys
+///
+/// Older formats are upgraded when the server starts up (see [`super::upgrade`])
+pub const FORMAT_VERSION: u8 = 3;
+/// The version nibble of the first format
+const FORMAT_BASE: u8 = 0b0111;
+
+const ENDIAN_LE: u8 = 0b0000;
+const ENDIAN_BE: u8 = 0b0001;
 
 #[cfg(target_endian = "little")]
-const META_SEGMENT: u8 = META_SEGMENT_LE;
+const ENDIAN: u8 = ENDIAN_LE;
 
 #[cfg(target_endian = "big")]
-const META_SEGMENT: u8 = META_SEGMENT_BE;
+const ENDIAN: u8 = ENDIAN_BE;
+
+const META_SEGMENT: u8 = ((FORMAT_BASE + FORMAT_VERSION) << 4) | ENDIAN;
 
 /// Generate the `PRELOAD` disk file for this instance
 /// ```text
@@ -72,20 +86,36 @@ pub(super) fn raw_generate_preload<W: Write>(w: &mut W, store: &Memstore) -> IoR
     Ok(())
 }
 
-/// Reads the preload file and returns a set
-pub(super) fn read_preload_raw(preload: Vec<u8>) -> StorageEngineResult<HashSet<ObjectID>> {
+/// Returns the format version that the preload file was written in
+pub(super) fn read_format_version(preload: &[u8]) -> StorageEngineResult<u8> {
+    let meta_segment = *preload
+        .first()
+        .ok_or_else(StorageEngineError::corrupted_preload)?;
+    match (meta_segment >> 4).checked_sub(FORMAT_BASE) {
+        Some(version) if version != 0 => Ok(version),
+        _ => Err(StorageEngineError::BadMetadata("preload".into())),
+    }
+}
+
+/// Reads the preload file and returns a set. The preload file can be in any format up to
+/// [`FORMAT_VERSION`]
+pub(super) fn read_preload_raw(preload: &[u8]) -> StorageEngineResult<HashSet<ObjectID>> {
     if preload.len() < 16 {
         // nah, this is a bad disk file
         return Err(StorageEngineError::corrupted_preload());
     }
     // first read in the meta segment
+    let version = self::read_format_version(preload)?;
+    if version > FORMAT_VERSION {
+        return Err(StorageEngineError::UnsupportedFormat(version));
+    }
     unsafe {
         let meta_segment: u8 = ptr::read(preload.as_ptr());
-        match meta_segment {
-            META_SEGMENT_BE => {
+        match meta_segment & 0x0F {
+            ENDIAN_BE => {
                 super::iter::endian_set_big();
             }
-            META_SEGMENT_LE => {
+            ENDIAN_LE => {
                 super::iter::endian_set_little();
             }
             _ => return Err(StorageEngineError::BadMetadata("preload".into())),
@@ -0,0 +1,66 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Quarantine
+//!
+//! A table whose file is corrupted (it fails its checksum or it doesn't decode) doesn't stop
+//! the server from starting up. Instead, the file is moved to
+//! `data/quarantine/<keyspace>/<table>.<UNIX millis>`, where it can be looked at (and
+//! recovered by hand), and the table is loaded empty. The quarantined tables are logged, and
+//! listed by `SYS METRIC quarantined`.
+//!
+//! Only the tables in the data directory are quarantined: a corrupted system table, `PARTMAP`
+//! or `PRELOAD` still stops the server from starting up, and a snapshot with a corrupted table
+//! can't be restored
+
+use {
+    super::interface::{DIR_KSROOT, DIR_QUARANTINE},
+    crate::{corestore::memstore::ObjectID, IoResult},
+    chrono::Utc,
+    parking_lot::{const_mutex, Mutex},
+    std::{fs, path::PathBuf},
+};
+
+/// The tables that were quarantined (as `<keyspace>.<table>`)
+static QUARANTINED: Mutex<Vec<String>> = const_mutex(Vec::new());
+
+/// Returns the tables that were quarantined since the server started, as
+/// `<keyspace>.<table>`
+pub fn quarantined() -> Vec<String> {
+    QUARANTINED.lock().clone()
+}
+
+/// Move the file of the given table (in the data directory) to the quarantine, and return
+/// where it was moved to
+pub fn quarantine(ksid: &ObjectID, tblid: &ObjectID) -> IoResult<PathBuf> {
+    let (ks, tbl) = unsafe { (ksid.as_str(), tblid.as_str()) };
+    let dir = concat_path!(DIR_QUARANTINE, ks);
+    fs::create_dir_all(&dir)?;
+    let to = dir.join(format!("{tbl}.{}", Utc::now().timestamp_millis()));
+    fs::rename(concat_path!(DIR_KSROOT, ks, tbl), &to)?;
+    QUARANTINED.lock().push(format!("{ks}.{tbl}"));
+    Ok(to)
+}
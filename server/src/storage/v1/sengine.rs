@@ -26,10 +26,15 @@
 
 use {
     self::queue::Queue,
-    super::interface::{DIR_RSNAPROOT, DIR_SNAPROOT},
+    super::{
+        error::{StorageEngineError, StorageEngineResult},
+        interface::{DIR_RSNAPROOT, DIR_SNAPROOT},
+        sink::{self, SnapshotSink},
+    },
     crate::{
         corestore::{iarray::IArray, lazy::Lazy, lock::QuickLock, memstore::Memstore},
-        storage::v1::flush::{LocalSnapshot, RemoteSnapshot},
+        registry,
+        storage::v1::flush::{Autoflush, LocalSnapshot, RemoteSnapshot},
     },
     chrono::prelude::Utc,
     core::{fmt, str},
@@ -90,6 +95,9 @@ pub struct SnapshotEngine {
     local_queue: QuickLock<Queue>,
     /// the remote snapshot lock
     remote_queue: QuickLock<HashSet<Box<[u8]>>>,
+    /// the sink that local snapshots are streamed to, if they don't go to the snapshot
+    /// directory
+    sink: Option<Arc<dyn SnapshotSink>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -99,6 +107,16 @@ pub enum SnapshotActionResult {
     Disabled,
     Failure,
     AlreadyExists,
+    NotFound,
+}
+
+/// The snapshots that are on disk
+#[derive(Debug, PartialEq, Eq)]
+pub struct SnapshotList {
+    /// the local snapshots, oldest first
+    pub local: Vec<String>,
+    /// the remote snapshots, sorted by name
+    pub remote: Vec<String>,
 }
 
 impl SnapshotEngine {
@@ -108,6 +126,7 @@ impl SnapshotEngine {
             local_enabled: true,
             local_queue: QuickLock::new(Queue::new(maxlen, maxlen == 0)),
             remote_queue: QuickLock::new(HashSet::new()),
+            sink: None,
         }
     }
     pub fn new_disabled() -> Self {
@@ -115,8 +134,19 @@ impl SnapshotEngine {
             local_enabled: false,
             local_queue: QuickLock::new(Queue::new(0, true)),
             remote_queue: QuickLock::new(HashSet::new()),
+            sink: None,
         }
     }
+    /// Stream the local snapshots to the given sink instead of writing them to the snapshot
+    /// directory
+    pub fn with_sink(mut self, sink: Arc<dyn SnapshotSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+    /// Returns true if local snapshots are enabled
+    pub fn is_local_enabled(&self) -> bool {
+        self.local_enabled
+    }
     fn _parse_dir(
         dir: &str,
         is_okay: impl Fn(&str) -> bool,
@@ -140,11 +170,24 @@ impl SnapshotEngine {
     }
     pub fn parse_dir(&self) -> SnapshotResult<()> {
         let mut local_queue = self.local_queue.lock();
-        Self::_parse_dir(
-            DIR_SNAPROOT,
-            |name| SNAP_MATCH.is_match(name),
-            |snapshot| local_queue.push(snapshot),
-        )?;
+        match &self.sink {
+            Some(sink) => match sink.list() {
+                // the names are sorted, and hence so are the times
+                Ok(snapshots) => snapshots
+                    .into_iter()
+                    .filter(|name| SNAP_MATCH.is_match(name))
+                    .for_each(|snapshot| local_queue.push(snapshot)),
+                Err(e) => {
+                    // we can still make new snapshots, but the old ones won't be rotated out
+                    log::warn!("Failed to list the snapshots in {}: {e}", sink.describe());
+                }
+            },
+            None => Self::_parse_dir(
+                DIR_SNAPROOT,
+                |name| SNAP_MATCH.is_match(name),
+                |snapshot| local_queue.push(snapshot),
+            )?,
+        }
         let mut remote_queue = self.remote_queue.lock();
         Self::_parse_dir(
             DIR_RSNAPROOT,
@@ -159,14 +202,24 @@ impl SnapshotEngine {
     fn get_snapname(&self) -> String {
         Utc::now().format("%Y%m%d-%H%M%S").to_string()
     }
-    fn _mksnap_blocking_section(store: &Memstore, name: String) -> SnapshotResult<()> {
-        if Path::new(&format!("{DIR_SNAPROOT}/{name}")).exists() {
-            Err(SnapshotEngineError::Engine("Server time is incorrect"))
-        } else {
-            let snapshot = LocalSnapshot::new(name);
-            super::flush::flush_full(snapshot, store)?;
-            Ok(())
-        }
+    fn _mksnap_blocking_section(
+        store: &Memstore,
+        name: String,
+        sink: Option<&dyn SnapshotSink>,
+    ) -> SnapshotResult<()> {
+        // mark the snapshot in the write-ahead log, so that the data can be recovered from it
+        super::wal::snapshot(&name, || {
+            if let Some(sink) = sink {
+                sink::flush_full(sink, &name, store)?;
+                Ok(())
+            } else if Path::new(&format!("{DIR_SNAPROOT}/{name}")).exists() {
+                Err(SnapshotEngineError::Engine("Server time is incorrect"))
+            } else {
+                let snapshot = LocalSnapshot::new(name.clone());
+                super::flush::flush_full(snapshot, store)?;
+                Ok(())
+            }
+        })
     }
     fn _rmksnap_blocking_section(store: &Memstore, name: &str) -> SnapshotResult<()> {
         let snapshot = RemoteSnapshot::new(name);
@@ -188,8 +241,9 @@ impl SnapshotEngine {
             let name = self.get_snapname();
             let nameclone = name.clone();
             let todel = queue.add_new(name);
+            let sink = self.sink.clone();
             let snap_create_result = tokio::task::spawn_blocking(move || {
-                Self::_mksnap_blocking_section(&store, nameclone)
+                Self::_mksnap_blocking_section(&store, nameclone, sink.as_deref())
             })
             .await
             .expect("mksnap thread panicked");
@@ -209,12 +263,26 @@ impl SnapshotEngine {
 
             // Now delete the older snap (if any)
             if let Some(snap) = todel {
+                let sink = self.sink.clone();
+                let oldest = queue.iter().next().cloned();
                 tokio::task::spawn_blocking(move || {
-                    if let Err(e) = fs::remove_dir_all(concat_path!(DIR_SNAPROOT, snap)) {
+                    let ret = match sink {
+                        Some(sink) => sink.delete(&snap),
+                        None => fs::remove_dir_all(concat_path!(DIR_SNAPROOT, snap)),
+                    };
+                    if let Err(e) = ret {
                         log::warn!("Failed to remove older snapshot (ignored): {}", e);
                     } else {
                         log::info!("Successfully removed older snapshot");
                     }
+                    // the data can't be recovered from before the oldest snapshot now
+                    if let Some(oldest) = oldest {
+                        if let Err(e) = super::wal::prune_archive(&oldest) {
+                            log::warn!(
+                                "Failed to prune the write-ahead log archive (ignored): {e}"
+                            );
+                        }
+                    }
                 })
                 .await
                 .expect("mksnap thread panicked");
@@ -258,6 +326,161 @@ impl SnapshotEngine {
             ret
         }
     }
+    /// Returns the local and remote snapshots, or `None` if a snapshot is being created (or
+    /// deleted, or restored) right now
+    pub fn list(&self) -> Option<SnapshotList> {
+        let local = self.local_queue.try_lock()?;
+        let remote = self.remote_queue.try_lock()?;
+        let mut remote: Vec<String> = remote
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect();
+        remote.sort();
+        Some(SnapshotList {
+            local: local.iter().cloned().collect(),
+            remote,
+        })
+    }
+    /// Find the directory of the local or remote snapshot with the given name, and whether it's
+    /// a local one. A local snapshot wins if there's a remote one with the same name
+    fn find_snapshot(
+        local: &Queue,
+        remote: &HashSet<Box<[u8]>>,
+        name: &[u8],
+    ) -> Option<(String, bool)> {
+        let name = str::from_utf8(name).ok()?;
+        if local.contains(name) {
+            Some((concat_str!(DIR_SNAPROOT, "/", name), true))
+        } else if remote.contains(name.as_bytes()) {
+            Some((concat_str!(DIR_RSNAPROOT, "/", name), false))
+        } else {
+            None
+        }
+    }
+    /// Delete the local or remote snapshot with the given name. Only snapshots that the engine
+    /// knows about can be deleted, so the name can't point outside the snapshot directories
+    pub async fn delete(&self, name: &[u8]) -> SnapshotActionResult {
+        let mut local = match self.local_queue.try_lock() {
+            Some(lck) => lck,
+            None => return SnapshotActionResult::Busy,
+        };
+        let mut remote = match self.remote_queue.try_lock() {
+            Some(lck) => lck,
+            None => return SnapshotActionResult::Busy,
+        };
+        let (path, is_local) = match Self::find_snapshot(&local, &remote, name) {
+            Some(found) => found,
+            None => return SnapshotActionResult::NotFound,
+        };
+        let pathclone = path.clone();
+        let sink = self.sink.clone().filter(|_| is_local);
+        let nameclone = String::from_utf8_lossy(name).into_owned();
+        let ret = tokio::task::spawn_blocking(move || match sink {
+            Some(sink) => sink.delete(&nameclone),
+            None => fs::remove_dir_all(pathclone),
+        })
+        .await
+        .expect("snapshot deletion thread panicked");
+        match ret {
+            Ok(()) => {
+                if is_local {
+                    // the name was found, so it's valid UTF-8
+                    local.remove(&String::from_utf8_lossy(name));
+                } else {
+                    remote.remove(name);
+                }
+                log::info!("Deleted snapshot at {path}");
+                SnapshotActionResult::Ok
+            }
+            Err(e) => {
+                log::error!("Failed to delete snapshot at {path}: {e}");
+                SnapshotActionResult::Failure
+            }
+        }
+    }
+    /// Download the snapshot with the given path from the sink, unpack it there and read it.
+    /// The download and the unpacked snapshot are removed once it has been read
+    fn read_from_sink(sink: &dyn SnapshotSink, path: &str) -> StorageEngineResult<Memstore> {
+        let name = &path[DIR_SNAPROOT.len() + 1..];
+        let download = format!("{path}.download");
+        let ret = sink
+            .download(name, Path::new(&download))
+            .and_then(|_| sink::unpack(Path::new(&download), Path::new(path)))
+            .map_err(StorageEngineError::from)
+            .and_then(|_| super::unflush::read_full_from(path));
+        let _ = fs::remove_file(&download);
+        let _ = fs::remove_dir_all(path);
+        ret
+    }
+    /// Read the local snapshot with the given name (for [`super::wal::recover`]). Returns `None`
+    /// if there's no such snapshot
+    pub fn read_local(&self, name: &str) -> Option<StorageEngineResult<Memstore>> {
+        if !self.local_queue.lock().contains(name) {
+            return None;
+        }
+        let path = concat_str!(DIR_SNAPROOT, "/", name);
+        Some(match &self.sink {
+            Some(sink) => Self::read_from_sink(sink.as_ref(), &path),
+            None => super::unflush::read_full_from(&path),
+        })
+    }
+    /// Replace the data in the store with the data in the local or remote snapshot with the
+    /// given name, and then flush it to disk (see [`Memstore::replace_keyspaces`]). The users
+    /// and their permissions are left as they are. Nothing is changed if the snapshot can't be
+    /// read
+    pub async fn restore(&self, name: &[u8], store: Arc<Memstore>) -> SnapshotActionResult {
+        // no snapshots are created (or deleted) while we're restoring one
+        let local = match self.local_queue.try_lock() {
+            Some(lck) => lck,
+            None => return SnapshotActionResult::Busy,
+        };
+        let remote = match self.remote_queue.try_lock() {
+            Some(lck) => lck,
+            None => return SnapshotActionResult::Busy,
+        };
+        let (path, is_local) = match Self::find_snapshot(&local, &remote, name) {
+            Some(found) => found,
+            None => return SnapshotActionResult::NotFound,
+        };
+        let sink = self.sink.clone().filter(|_| is_local);
+        let ret = tokio::task::spawn_blocking(move || {
+            let restored = match &sink {
+                Some(sink) => Self::read_from_sink(sink.as_ref(), &path),
+                None => super::unflush::read_full_from(&path),
+            };
+            let restored = match restored {
+                Ok(restored) => restored,
+                Err(e) => {
+                    log::error!("Failed to read snapshot at {path}: {e}");
+                    return SnapshotActionResult::Failure;
+                }
+            };
+            // the data on disk is now stale, so flush it right away. The writes in the
+            // write-ahead log were made to the data that we're replacing, so it's checkpointed too
+            // (and the history breaks here)
+            let flushed = super::wal::checkpoint_replaced(|| {
+                store.replace_keyspaces(restored);
+                let _flush_lock = registry::lock_flush_state();
+                super::flush::flush_full(Autoflush, &store)
+            });
+            match flushed {
+                Ok(()) => {
+                    log::info!("Restored snapshot at {path}");
+                    registry::unpoison();
+                    SnapshotActionResult::Ok
+                }
+                Err(e) => {
+                    log::error!("Restored snapshot at {path}, but failed to flush it: {e}");
+                    registry::poison();
+                    SnapshotActionResult::Failure
+                }
+            }
+        })
+        .await
+        .expect("snapshot restore thread panicked");
+        drop((local, remote));
+        ret
+    }
 }
 
 mod queue {
@@ -321,6 +544,26 @@ mod queue {
         pub fn pop_last(&mut self) -> Option<String> {
             self.queue.pop()
         }
+        /// Returns an iterator over the items, oldest first
+        pub fn iter(&self) -> impl Iterator<Item = &String> {
+            self.queue.iter()
+        }
+        pub fn contains(&self, item: &str) -> bool {
+            self.queue.iter().any(|queued| queued == item)
+        }
+        /// Remove the given item, returning true if it was in the queue
+        pub fn remove(&mut self, item: &str) -> bool {
+            match self.queue.iter().position(|queued| queued == item) {
+                Some(idx) => {
+                    unsafe {
+                        // SAFETY: The index was just found in the queue
+                        self.queue.remove(idx);
+                    }
+                    true
+                }
+                None => false,
+            }
+        }
     }
 
     #[test]
@@ -351,4 +594,22 @@ mod queue {
         assert!(q.add_new(String::from("snap5")).is_none());
         assert!(q.add_new(String::from("snap6")).is_none());
     }
+
+    #[test]
+    fn test_queue_remove() {
+        let mut q = Queue::new(4, false);
+        assert!(q.add_new(String::from("snap1")).is_none());
+        assert!(q.add_new(String::from("snap2")).is_none());
+        assert!(q.add_new(String::from("snap3")).is_none());
+        assert!(q.remove("snap2"));
+        assert!(!q.remove("snap2"));
+        assert!(!q.contains("snap2"));
+        assert!(q.iter().map(String::as_str).eq(["snap1", "snap3"]));
+        // there's room for two more before the oldest is popped
+        assert!(q.add_new(String::from("snap4")).is_none());
+        assert_eq!(
+            q.add_new(String::from("snap5")),
+            Some(String::from("snap1"))
+        );
+    }
 }
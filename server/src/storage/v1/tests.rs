@@ -53,6 +53,26 @@ fn test_serialize_deserialize_map_with_empty_elements() {
     assert!(cmap.into_iter().all(|(k, v)| de.get(&k).unwrap().eq(&v)));
 }
 
+#[test]
+fn test_ser_de_disk_map() {
+    let cmap = Coremap::new();
+    let value = "sayan".repeat(100);
+    cmap.upsert("big".into(), value.as_str().into());
+    cmap.upsert("small".into(), "tiny".into());
+    let ser = se::serialize_map(&cmap).unwrap();
+    // the values are written out as they're read, so only the stubs are in the map
+    let loaded = de::deserialize_disk_map(&ser).unwrap();
+    assert_eq!(loaded.data.len(), 2);
+    assert!(loaded.data.get("big".as_bytes()).unwrap().len() < value.len());
+    let kve = crate::kvengine::KVEStandard::from_loaded(false, false, loaded);
+    assert_eq!(kve.get_cloned("big").unwrap().unwrap(), value.as_str());
+    assert_eq!(kve.get_cloned("small").unwrap().unwrap(), "tiny");
+    // trailing bytes are still rejected
+    let mut ser = ser;
+    ser.push(0);
+    assert!(de::deserialize_disk_map(&ser).is_none());
+}
+
 #[test]
 fn test_ser_de_few_elements() {
     let cmap = Coremap::new();
@@ -199,23 +219,54 @@ mod interface_tests {
 mod preload_tests {
     use super::*;
     use crate::corestore::memstore::Memstore;
+    use crate::storage::v1::error::StorageEngineError;
     #[test]
     fn test_preload() {
         let memstore = Memstore::new_default();
         let mut v = Vec::new();
         preload::raw_generate_preload(&mut v, &memstore).unwrap();
-        let de: Vec<String> = preload::read_preload_raw(v)
+        let de: Vec<String> = preload::read_preload_raw(&v)
             .unwrap()
             .into_iter()
             .map(|each| unsafe { each.as_str().to_owned() })
             .collect();
         assert_veceq!(de, vec!["default".to_owned(), "system".to_owned()]);
     }
+    #[test]
+    fn test_preload_format_version() {
+        let memstore = Memstore::new_default();
+        let mut v = Vec::new();
+        preload::raw_generate_preload(&mut v, &memstore).unwrap();
+        assert_eq!(
+            preload::read_format_version(&v).unwrap(),
+            preload::FORMAT_VERSION
+        );
+        // a preload in the first format is still read
+        v[0] = (0b1000 << 4) | (v[0] & 0x0F);
+        assert_eq!(preload::read_format_version(&v).unwrap(), 1);
+        assert_eq!(preload::read_preload_raw(&v).unwrap().len(), 2);
+    }
+    #[test]
+    fn test_preload_newer_format() {
+        let memstore = Memstore::new_default();
+        let mut v = Vec::new();
+        preload::raw_generate_preload(&mut v, &memstore).unwrap();
+        v[0] = (0b1011 << 4) | (v[0] & 0x0F);
+        assert!(matches!(
+            preload::read_preload_raw(&v),
+            Err(StorageEngineError::UnsupportedFormat(4))
+        ));
+        v[0] = 0b0101_0000;
+        assert!(matches!(
+            preload::read_format_version(&v),
+            Err(StorageEngineError::BadMetadata(_))
+        ));
+    }
 }
 
 mod bytemark_set_tests {
     use super::*;
-    use crate::corestore::memstore::{Keyspace, ObjectID};
+    use crate::corestore::memstore::{Keyspace, KeyspaceDefaults, ObjectID};
     use crate::corestore::table::Table;
     use std::collections::HashMap;
     #[test]
@@ -223,7 +274,7 @@ mod bytemark_set_tests {
         let ks = Keyspace::empty_default();
         let mut v = Vec::new();
         se::raw_serialize_partmap(&mut v, &ks).unwrap();
-        let ret: HashMap<ObjectID, (u8, u8)> = de::deserialize_set_ctype_bytemark(&v).unwrap();
+        let (ret, _): (HashMap<ObjectID, (u8, u8)>, _) = de::deserialize_partmap(&v).unwrap();
         let mut expected = HashMap::new();
         unsafe {
             expected.insert(
@@ -255,7 +306,7 @@ mod bytemark_set_tests {
         }
         let mut v = Vec::new();
         se::raw_serialize_partmap(&mut v, &ks).unwrap();
-        let ret: HashMap<ObjectID, (u8, u8)> = de::deserialize_set_ctype_bytemark(&v).unwrap();
+        let (ret, _): (HashMap<ObjectID, (u8, u8)>, _) = de::deserialize_partmap(&v).unwrap();
         let mut expected = HashMap::new();
         unsafe {
             // our cache is volatile
@@ -284,6 +335,42 @@ mod bytemark_set_tests {
         }
         assert_hmeq!(expected, ret);
     }
+    #[test]
+    fn test_bytemark_for_keyspace_defaults() {
+        let ks = Keyspace::empty_with_defaults(KeyspaceDefaults {
+            model_code: Some(bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR),
+            volatile: true,
+        });
+        let mut v = Vec::new();
+        se::raw_serialize_partmap(&mut v, &ks).unwrap();
+        let (ret, defaults): (HashMap<ObjectID, (u8, u8)>, _) =
+            de::deserialize_partmap(&v).unwrap();
+        assert!(ret.is_empty());
+        assert_eq!(
+            defaults,
+            Some((
+                bytemarks::BYTEMARK_STORAGE_VOLATILE,
+                bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR
+            ))
+        );
+        // a keyspace without a default model
+        let mut v = Vec::new();
+        se::raw_serialize_partmap(&mut v, &Keyspace::empty()).unwrap();
+        let (_, defaults): (HashMap<ObjectID, (u8, u8)>, _) = de::deserialize_partmap(&v).unwrap();
+        assert_eq!(
+            defaults,
+            Some((
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                bytemarks::BYTEMARK_MODEL_NONE
+            ))
+        );
+        // partmaps written before the defaults were persisted don't have them
+        let (_, defaults): (HashMap<ObjectID, (u8, u8)>, _) =
+            de::deserialize_partmap(&v[..v.len() - 2]).unwrap();
+        assert!(defaults.is_none());
+        // but a partmap can't end with half of them
+        assert!(de::deserialize_partmap::<ObjectID>(&v[..v.len() - 1]).is_none());
+    }
 }
 
 mod bytemark_actual_table_restore {
@@ -295,6 +382,7 @@ mod bytemark_actual_table_restore {
     use crate::kvengine::LockedVec;
     use crate::storage::v1::{
         flush::{oneshot::flush_table, Autoflush},
+        interface::DIR_KSROOT,
         unflush::read_table,
     };
 
@@ -346,7 +434,9 @@ mod bytemark_actual_table_restore {
         let mut read_tables: Vec<Table> = Vec::with_capacity(4);
         // read each of them
         for (tableid, _, modelcode) in names {
-            read_tables.push(read_table(&default_keyspace, tableid, false, modelcode).unwrap());
+            read_tables.push(
+                read_table(DIR_KSROOT, &default_keyspace, tableid, false, modelcode).unwrap(),
+            );
         }
         for (index, (table, code)) in read_tables
             .iter()
@@ -401,7 +491,9 @@ mod bytemark_actual_table_restore {
         let mut read_tables: Vec<Table> = Vec::with_capacity(4);
         // read each of them
         for (tableid, _, modelcode) in names {
-            read_tables.push(read_table(&default_keyspace, tableid, false, modelcode).unwrap());
+            read_tables.push(
+                read_table(DIR_KSROOT, &default_keyspace, tableid, false, modelcode).unwrap(),
+            );
         }
         for (index, (table, code)) in read_tables
             .iter()
@@ -424,14 +516,17 @@ mod bytemark_actual_table_restore {
 mod flush_routines {
     use crate::{
         corestore::{
-            memstore::{Keyspace, ObjectID},
+            memstore::{Keyspace, KeyspaceDefaults, Memstore, ObjectID},
             table::{DataModel, Table},
             SharedSlice,
         },
-        kvengine::LockedVec,
-        storage::v1::{bytemarks, flush::Autoflush, Coremap},
+        kvengine::{expiry, LockedVec},
+        storage::v1::{
+            bytemarks, error::StorageEngineError, flush::Autoflush, interface::DIR_KSROOT,
+            quarantine, Coremap,
+        },
     };
-    use std::fs;
+    use std::{fs, path::Path, sync::Arc};
     #[test]
     fn test_flush_unflush_table_pure_kve() {
         let tbl = Table::new_default_kve();
@@ -446,6 +541,7 @@ mod flush_routines {
         super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
         // now that it's flushed, let's read the table using and unflush routine
         let ret = super::unflush::read_table::<Table>(
+            DIR_KSROOT,
             &ksid,
             &tblid,
             false,
@@ -480,6 +576,7 @@ mod flush_routines {
         super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
         // now that it's flushed, let's read the table using and unflush routine
         let ret = super::unflush::read_table::<Table>(
+            DIR_KSROOT,
             &ksid,
             &tblid,
             false,
@@ -497,6 +594,122 @@ mod flush_routines {
         }
     }
     #[test]
+    fn test_flush_unflush_table_jsonmap() {
+        let tbl = Table::new_kve_jsonmap_with_data(Coremap::new(), false, true);
+        tbl.get_kvstore()
+            .unwrap()
+            .set("user".into(), r#"{"name":"sayan"}"#.into())
+            .unwrap();
+        let tblid = unsafe { ObjectID::from_slice("myjson1") };
+        let ksid = unsafe { ObjectID::from_slice("myjsonks") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/myjsonks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret =
+            super::unflush::read_table::<Table>(DIR_KSROOT, &ksid, &tblid, false, 23).unwrap();
+        assert_eq!(ret.get_model_code(), 23);
+        let kve = ret.get_kvstore().unwrap();
+        assert_eq!(
+            kve.get("user".as_bytes()).unwrap().unwrap().clone(),
+            SharedSlice::from(r#"{"name":"sayan"}"#)
+        );
+        // the restored table still only accepts JSON
+        assert!(kve.set("bad".into(), "{".into()).is_err());
+    }
+    #[test]
+    fn test_flush_unflush_table_compressed() {
+        let tbl = Table::from_model_code(26, false).unwrap();
+        let value = "sayan".repeat(100);
+        tbl.get_kvstore()
+            .unwrap()
+            .set("user".into(), value.as_str().into())
+            .unwrap();
+        let tblid = unsafe { ObjectID::from_slice("mycompressed1") };
+        let ksid = unsafe { ObjectID::from_slice("mycompressedks") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/mycompressedks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret =
+            super::unflush::read_table::<Table>(DIR_KSROOT, &ksid, &tblid, false, 26).unwrap();
+        assert_eq!(ret.get_model_code(), 26);
+        assert!(ret.is_compressed());
+        let kve = ret.get_kvstore().unwrap();
+        // the value was written out compressed, and is still compressed
+        assert!(kve.get("user".as_bytes()).unwrap().unwrap().len() < value.len());
+        assert_eq!(kve.get_cloned("user").unwrap().unwrap(), value.as_str());
+    }
+    #[test]
+    fn test_flush_unflush_table_disk() {
+        let tbl = Table::from_model_code(38, false).unwrap();
+        let value = "sayan".repeat(100);
+        tbl.get_kvstore()
+            .unwrap()
+            .set("user".into(), value.as_str().into())
+            .unwrap();
+        let tblid = unsafe { ObjectID::from_slice("mydisk1") };
+        let ksid = unsafe { ObjectID::from_slice("mydiskks") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/mydiskks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret =
+            super::unflush::read_table::<Table>(DIR_KSROOT, &ksid, &tblid, false, 38).unwrap();
+        assert_eq!(ret.get_model_code(), 38);
+        assert!(ret.is_on_disk());
+        let kve = ret.get_kvstore().unwrap();
+        // the value was written out as is, and is back on disk
+        assert!(kve.get("user".as_bytes()).unwrap().unwrap().len() < value.len());
+        assert_eq!(kve.get_cloned("user").unwrap().unwrap(), value.as_str());
+    }
+    #[test]
+    fn test_flush_unflush_table_deadlines() {
+        let tbl = Table::new_default_kve();
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("session".into(), "token".into()).unwrap();
+        kve.set("expired".into(), "token".into()).unwrap();
+        kve.set("forever".into(), "token".into()).unwrap();
+        let deadline = expiry::deadline_after_secs(3600);
+        assert!(kve.set_expiry(b"session", deadline).unwrap());
+        assert!(kve.set_expiry(b"expired", 1).unwrap());
+        let tblid = unsafe { ObjectID::from_slice("myttl1") };
+        let ksid = unsafe { ObjectID::from_slice("myttlks") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/myttlks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        // this is what a restart reads
+        let ret = super::unflush::read_table::<Table>(
+            DIR_KSROOT,
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_BIN_BIN,
+        )
+        .unwrap();
+        assert_eq!(ret.deadline_of(b"session"), Some(deadline));
+        assert_eq!(ret.deadline_of(b"forever"), None);
+        // the deadline passed while we were down
+        let kve = ret.get_kvstore().unwrap();
+        assert!(kve.get_cloned(b"expired").unwrap().is_none());
+        assert_eq!(
+            kve.get_cloned(b"session").unwrap().unwrap(),
+            SharedSlice::from("token")
+        );
+    }
+    #[test]
+    fn test_unflush_table_without_deadlines() {
+        // tables that were written before the deadlines were persisted are read as they are
+        let data = Coremap::new();
+        data.upsert(SharedSlice::from("hello"), SharedSlice::from("world"));
+        let mut file = Vec::new();
+        super::se::raw_serialize_map(&data, &mut file).unwrap();
+        let (deadlines, rest) = super::de::split_deadlines(&file).unwrap();
+        assert!(deadlines.is_empty());
+        assert_eq!(rest, file.as_slice());
+        // and a truncated section is caught
+        let mut file = Vec::new();
+        super::se::raw_serialize_deadlines(&[(SharedSlice::from("hello"), 1)], &mut file).unwrap();
+        assert!(super::de::split_deadlines(&file[..file.len() - 1]).is_none());
+    }
+    #[test]
     fn test_flush_unflush_keyspace() {
         // create the temp dir for this test
         fs::create_dir_all("data/ks/myks_1").unwrap();
@@ -504,7 +717,11 @@ mod flush_routines {
         let tbl1 = unsafe { ObjectID::from_slice("mytbl_1") };
         let tbl2 = unsafe { ObjectID::from_slice("mytbl_2") };
         let list_tbl = unsafe { ObjectID::from_slice("mylist_1") };
-        let ks = Keyspace::empty();
+        let defaults = KeyspaceDefaults {
+            model_code: Some(bytemarks::BYTEMARK_MODEL_KV_STR_STR),
+            volatile: true,
+        };
+        let ks = Keyspace::empty_with_defaults(defaults);
 
         // a persistent table
         let mytbl = Table::new_default_kve();
@@ -526,10 +743,12 @@ mod flush_routines {
 
         // now flush it
         super::flush::flush_keyspace_full(&Autoflush, &ksid, &ks).unwrap();
-        let ret = super::unflush::read_keyspace::<Keyspace>(&ksid).unwrap();
+        let ret = super::unflush::read_keyspace::<Keyspace>(DIR_KSROOT, &ksid).unwrap();
         let tbl1_ret = ret.tables.get(&tbl1).unwrap();
         let tbl2_ret = ret.tables.get(&tbl2).unwrap();
         let tbl3_ret_list = ret.tables.get(&list_tbl).unwrap();
+        // the defaults for new tables are restored too
+        assert_eq!(*ret.defaults(), defaults);
         // should be a persistent table with the value we set
         assert_eq!(tbl1_ret.count(), 1);
         assert_eq!(
@@ -559,15 +778,92 @@ mod flush_routines {
             );
         }
     }
+    #[test]
+    fn test_flush_one_keyspace() {
+        fs::create_dir_all("data/ks/myflushoneks").unwrap();
+        let ksid = unsafe { ObjectID::from_slice("myflushoneks") };
+        let tblid = unsafe { ObjectID::from_slice("mytbl") };
+        let ks = Keyspace::empty();
+        let tbl = Table::new_default_kve();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        assert!(ks.create_table(tblid.clone(), tbl));
+        let store = Memstore::new_empty();
+        assert!(store.keyspaces.true_if_insert(ksid.clone(), Arc::new(ks)));
+        let ks = store.get_keyspace_atomic_ref(&ksid).unwrap();
+        // keyspaces start out dirty, and flushing one brings it up to date
+        assert!(ks.is_dirty());
+        super::flush::flush_one(&store, &ksid, &ks).unwrap();
+        assert!(!ks.is_dirty());
+        let ret = super::unflush::read_keyspace::<Keyspace>(DIR_KSROOT, &ksid).unwrap();
+        assert_eq!(ret.tables.get(&tblid).unwrap().count(), 1);
+        // but a keyspace with keys that can expire never stays clean
+        let tbl = ks.get_table_atomic_ref(&tblid).unwrap();
+        assert!(tbl
+            .get_kvstore()
+            .unwrap()
+            .set_expiry(b"hello", expiry::deadline_after_secs(100))
+            .unwrap());
+        super::flush::flush_one(&store, &ksid, &ks).unwrap();
+        assert!(ks.is_dirty());
+    }
+    #[test]
+    fn test_unflush_corrupted_table() {
+        fs::create_dir_all("data/ks/mycorruptks").unwrap();
+        let _ = fs::remove_dir_all("data/quarantine/mycorruptks");
+        let ksid = unsafe { ObjectID::from_slice("mycorruptks") };
+        let tblid = unsafe { ObjectID::from_slice("mycorrupttbl") };
+        let ks = Keyspace::empty();
+        let tbl = Table::new_default_kve();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        assert!(ks.create_table(tblid.clone(), tbl));
+        super::flush::flush_keyspace_full(&Autoflush, &ksid, &ks).unwrap();
+        // flip a bit in the value
+        let mut file = fs::read("data/ks/mycorruptks/mycorrupttbl").unwrap();
+        let at = file.windows(5).position(|w| w == b"world").unwrap();
+        file[at] ^= 1;
+        fs::write("data/ks/mycorruptks/mycorrupttbl", file).unwrap();
+        let e = super::unflush::read_table::<Table>(
+            DIR_KSROOT,
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_BIN_BIN,
+        )
+        .unwrap_err();
+        assert!(matches!(e, StorageEngineError::ChecksumMismatch(_)));
+        // the keyspace still loads, with the table quarantined and empty
+        let ret = super::unflush::read_keyspace::<Keyspace>(DIR_KSROOT, &ksid).unwrap();
+        assert_eq!(ret.tables.get(&tblid).unwrap().count(), 0);
+        assert!(!Path::new("data/ks/mycorruptks/mycorrupttbl").exists());
+        assert_eq!(
+            fs::read_dir("data/quarantine/mycorruptks").unwrap().count(),
+            1
+        );
+        assert!(quarantine::quarantined().contains(&"mycorruptks.mycorrupttbl".to_owned()));
+    }
 }
 
 mod list_tests {
     use super::iter::RawSliceIter;
     use super::{de, se};
+    use crate::corestore::{
+        bloom::BloomFilter,
+        geo::GeoIndex,
+        hll::HyperLogLog,
+        timeseries::{Sample, TimeSeries},
+        zset::SortedSet,
+    };
     use crate::corestore::{htable::Coremap, SharedSlice};
-    use crate::kvengine::LockedVec;
+    use crate::kvengine::{LockedSet, LockedVec};
     use core::ops::Deref;
     use parking_lot::RwLock;
+    use std::collections::{HashMap, HashSet};
     #[test]
     fn test_list_se_de() {
         let mylist = vec![
@@ -664,6 +960,161 @@ mod list_tests {
         let de = de::deserialize_list_map(&v).unwrap();
         assert_eq!(de.len(), 0)
     }
+    #[test]
+    fn test_set_map_se_de() {
+        let mymap: Coremap<SharedSlice, LockedSet> = Coremap::new();
+        let key: SharedSlice = "myset".into();
+        let members: HashSet<SharedSlice> = ["apples", "bananas", "carrots"]
+            .into_iter()
+            .map(SharedSlice::from)
+            .collect();
+        mymap.true_if_insert(key.clone(), RwLock::new(members.clone()));
+        mymap.true_if_insert("myemptyset".into(), RwLock::new(HashSet::new()));
+        let mut v = Vec::new();
+        se::raw_serialize_set_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_set_map(&v).unwrap();
+        assert_eq!(de.len(), 2);
+        assert_eq!(
+            de.get(&key).unwrap().value().deref().read().clone(),
+            members
+        );
+        assert!(de
+            .get("myemptyset".as_bytes())
+            .unwrap()
+            .value()
+            .read()
+            .is_empty());
+    }
+    #[test]
+    fn test_zset_map_se_de() {
+        let mymap = Coremap::new();
+        let mut zset = SortedSet::new();
+        zset.insert("alice".into(), 10.5);
+        zset.insert("bob".into(), -2.0);
+        zset.insert("".into(), f64::INFINITY);
+        mymap.true_if_insert(SharedSlice::from("myzset"), RwLock::new(zset));
+        let mut v = Vec::new();
+        se::raw_serialize_zset_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_zset_map(&v).unwrap();
+        assert_eq!(de.len(), 1);
+        let zset = de.get("myzset".as_bytes()).unwrap();
+        let zread = zset.value().read();
+        assert_eq!(
+            zread.iter().collect::<Vec<_>>(),
+            vec![
+                (&SharedSlice::from("bob"), -2.0),
+                (&SharedSlice::from("alice"), 10.5),
+                (&SharedSlice::from(""), f64::INFINITY)
+            ]
+        );
+    }
+    #[test]
+    fn test_hash_map_se_de() {
+        let mymap = Coremap::new();
+        let fields: HashMap<SharedSlice, SharedSlice> =
+            [("name", "sayan"), ("city", ""), ("", "x")]
+                .into_iter()
+                .map(|(f, v)| (SharedSlice::from(f), SharedSlice::from(v)))
+                .collect();
+        mymap.true_if_insert(SharedSlice::from("myhash"), RwLock::new(fields.clone()));
+        let mut v = Vec::new();
+        se::raw_serialize_hash_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_hash_map(&v).unwrap();
+        assert_eq!(de.len(), 1);
+        assert_eq!(
+            de.get("myhash".as_bytes()).unwrap().value().read().clone(),
+            fields
+        );
+    }
+    #[test]
+    fn test_bloom_map_se_de() {
+        let mymap = Coremap::new();
+        let mut filter = BloomFilter::new(0.001, 100).unwrap();
+        filter.add(b"sayan");
+        filter.add(b"");
+        mymap.true_if_insert(SharedSlice::from("mybloom"), RwLock::new(filter.clone()));
+        mymap.true_if_insert(SharedSlice::from(""), RwLock::new(BloomFilter::default()));
+        let mut v = Vec::new();
+        se::raw_serialize_bloom_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_bloom_map(&v).unwrap();
+        assert_eq!(de.len(), 2);
+        let restored = de.get("mybloom".as_bytes()).unwrap().value().read().clone();
+        assert_eq!(restored, filter);
+        assert!(restored.contains(b"sayan"));
+        assert!(restored.contains(b""));
+        assert!(de.get("".as_bytes()).unwrap().value().read().is_empty());
+        // a truncated filter is rejected
+        assert!(de::deserialize_bloom_map(&v[..v.len() - 1]).is_none());
+    }
+    #[test]
+    fn test_hll_map_se_de() {
+        let mymap = Coremap::new();
+        let mut sparse = HyperLogLog::new();
+        sparse.add(b"sayan");
+        let mut dense = HyperLogLog::new();
+        for i in 0..50_000u32 {
+            dense.add(&i.to_le_bytes());
+        }
+        mymap.true_if_insert(SharedSlice::from("sparse"), RwLock::new(sparse.clone()));
+        mymap.true_if_insert(SharedSlice::from("dense"), RwLock::new(dense.clone()));
+        mymap.true_if_insert(SharedSlice::from(""), RwLock::new(HyperLogLog::new()));
+        let mut v = Vec::new();
+        se::raw_serialize_hll_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_hll_map(&v).unwrap();
+        assert_eq!(de.len(), 3);
+        assert_eq!(*de.get("sparse".as_bytes()).unwrap().value().read(), sparse);
+        assert_eq!(*de.get("dense".as_bytes()).unwrap().value().read(), dense);
+        assert!(de.get("".as_bytes()).unwrap().value().read().is_empty());
+        // a truncated HLL is rejected
+        assert!(de::deserialize_hll_map(&v[..v.len() - 1]).is_none());
+    }
+    #[test]
+    fn test_geo_map_se_de() {
+        let mymap = Coremap::new();
+        let mut geo = GeoIndex::new();
+        geo.insert("palermo".into(), 13.361389, 38.115556);
+        geo.insert("".into(), -180.0, -85.05112878);
+        mymap.true_if_insert(SharedSlice::from("sicily"), RwLock::new(geo.clone()));
+        mymap.true_if_insert(SharedSlice::from("empty"), RwLock::new(GeoIndex::new()));
+        let mut v = Vec::new();
+        se::raw_serialize_geo_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_geo_map(&v).unwrap();
+        assert_eq!(de.len(), 2);
+        let restored = de.get("sicily".as_bytes()).unwrap().value().read().clone();
+        assert_eq!(restored, geo);
+        assert_eq!(restored.search_radius(13.361389, 38.115556, 1.0).len(), 1);
+        assert!(de
+            .get("empty".as_bytes())
+            .unwrap()
+            .value()
+            .read()
+            .is_empty());
+    }
+    #[test]
+    fn test_timeseries_map_se_de() {
+        let mymap = Coremap::new();
+        let mut series = TimeSeries::new(1000);
+        series.add(100, 1.5);
+        series.add(200, -0.25);
+        mymap.true_if_insert(SharedSlice::from("temp"), RwLock::new(series.clone()));
+        mymap.true_if_insert(SharedSlice::from("empty"), RwLock::new(TimeSeries::new(0)));
+        let mut v = Vec::new();
+        se::raw_serialize_timeseries_map(&mymap, &mut v).unwrap();
+        let de = de::deserialize_timeseries_map(&v).unwrap();
+        assert_eq!(de.len(), 2);
+        let restored = de.get("temp".as_bytes()).unwrap().value().read().clone();
+        assert_eq!(restored, series);
+        assert_eq!(restored.retention(), 1000);
+        assert_eq!(restored.last(), Some(Sample::new(200, -0.25)));
+        assert!(de
+            .get("empty".as_bytes())
+            .unwrap()
+            .value()
+            .read()
+            .is_empty());
+        // a truncated series is rejected
+        assert!(de::deserialize_timeseries_map(&v[..v.len() - 1]).is_none());
+    }
 }
 
 mod corruption_tests {
@@ -826,3 +1277,107 @@ mod storage_target_directory_structure {
         fs::remove_dir_all("data/rsnap/wisnap").unwrap();
     }
 }
+
+mod export_tests {
+    use {
+        super::export::{self, ExportError},
+        crate::corestore::{
+            htable::Coremap,
+            table::{DataModel, Table},
+        },
+        std::{fs, io::ErrorKind, path::Path},
+    };
+
+    #[test]
+    fn test_export_import_table() {
+        let tbl = Table::new_kve_listmap_with_data(Coremap::new(), false, true, true);
+        if let DataModel::KVExtListmap(kvl) = tbl.get_model_ref() {
+            kvl.add_list("mylist".into()).unwrap();
+            let list = kvl.get("mylist".as_bytes()).unwrap().unwrap();
+            list.write().push("mysupervalue".into());
+        } else {
+            panic!("Bad model!");
+        }
+        let archive = export::encode("myks", "mylists", &tbl).unwrap();
+        let (manifest, ret) = export::decode(&archive).unwrap();
+        assert_eq!(manifest.keyspace, "myks");
+        assert_eq!(manifest.table, "mylists");
+        assert_eq!(manifest.data, "(str,list<str>)");
+        assert_eq!(manifest.entries, 1);
+        assert_eq!(ret.get_model_code(), tbl.get_model_code());
+        if let DataModel::KVExtListmap(kvl) = ret.get_model_ref() {
+            let list = kvl.get("mylist".as_bytes()).unwrap().unwrap();
+            assert_eq!(list.read()[0].as_ref(), "mysupervalue".as_bytes());
+        } else {
+            panic!("Bad model!");
+        }
+    }
+    #[test]
+    fn test_export_import_table_compressed_and_disk() {
+        let value = "sayan".repeat(100);
+        for code in [26, 38] {
+            let tbl = Table::from_model_code(code, false).unwrap();
+            tbl.get_kvstore()
+                .unwrap()
+                .set("user".into(), value.as_str().into())
+                .unwrap();
+            let archive = export::encode("myks", "mytbl", &tbl).unwrap();
+            // the values are in the archive as they are, and the archive is compressed
+            assert!(archive.len() < value.len());
+            let (_, ret) = export::decode(&archive).unwrap();
+            assert_eq!(ret.get_model_code(), code);
+            let kve = ret.get_kvstore().unwrap();
+            assert!(kve.get("user".as_bytes()).unwrap().unwrap().len() < value.len());
+            assert_eq!(kve.get_cloned("user").unwrap().unwrap(), value.as_str());
+        }
+    }
+    #[test]
+    fn test_import_bad_archive() {
+        let tbl = Table::new_default_kve();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        let archive = export::encode("myks", "mytbl", &tbl).unwrap();
+        let bad =
+            |archive: &[u8]| matches!(export::decode(archive), Err(ExportError::BadArchive(_)));
+        // truncated
+        assert!(bad(&archive[..archive.len() - 1]));
+        assert!(bad(&archive[..2]));
+        // corrupted
+        let mut corrupted = archive.clone();
+        corrupted[20] ^= 0xFF;
+        assert!(bad(&corrupted));
+        // not an archive at all
+        assert!(bad(b"skytable"));
+        assert!(export::decode(&archive).is_ok());
+    }
+    #[test]
+    fn test_export_never_overwrites() {
+        let path = Path::new("data/export_never_overwrites.skyexport");
+        let tbl = Table::new_default_kve();
+        export::export(path, "myks", "mytbl", &tbl).unwrap();
+        assert!(matches!(
+            export::export(path, "myks", "mytbl", &tbl),
+            Err(ExportError::Io(e)) if e.kind() == ErrorKind::AlreadyExists
+        ));
+        let (manifest, _) = export::import(path).unwrap();
+        assert_eq!(manifest.table, "mytbl");
+        fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn test_resolve_export_path() {
+        assert_eq!(
+            export::resolve("backups/mytbl.skyexport"),
+            Some(Path::new("exports/backups/mytbl.skyexport").to_owned())
+        );
+        assert_eq!(
+            export::resolve("./mytbl.skyexport"),
+            Some(Path::new("exports/mytbl.skyexport").to_owned())
+        );
+        assert_eq!(export::resolve(""), None);
+        assert_eq!(export::resolve("../mytbl.skyexport"), None);
+        assert_eq!(export::resolve("backups/../../mytbl.skyexport"), None);
+        assert_eq!(export::resolve("/tmp/mytbl.skyexport"), None);
+    }
+}
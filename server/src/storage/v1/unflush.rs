@@ -32,56 +32,102 @@ use {
     super::bytemarks,
     crate::{
         corestore::{
-            memstore::{Keyspace, Memstore, ObjectID, SystemKeyspace, SYSTEM},
+            memstore::{Keyspace, KeyspaceDefaults, Memstore, ObjectID, SystemKeyspace, SYSTEM},
             table::{SystemTable, Table},
+            SharedSlice,
         },
         storage::v1::{
+            checksum,
             de::DeserializeInto,
+            encryption,
             error::{ErrorContext, StorageEngineError, StorageEngineResult},
             flush::Autoflush,
             interface::DIR_KSROOT,
             preload::LoadedPartfile,
-            Coremap,
+            quarantine, Coremap,
         },
         util::Wrapper,
     },
-    core::mem::transmute,
+    core::{cell::RefCell, mem::transmute},
     std::{fs, io::ErrorKind, path::Path, sync::Arc},
 };
 
 type PreloadSet = std::collections::HashSet<ObjectID>;
-const PRELOAD_PATH: &str = "data/ks/PRELOAD";
 
 /// A keyspace that can be restored from disk storage
 pub trait UnflushableKeyspace: Sized {
-    /// Unflush routine for a keyspace
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self>;
+    /// Unflush routine for a keyspace (in the tree at `root`)
+    fn unflush_keyspace(
+        root: &str,
+        partmap: LoadedPartfile,
+        defaults: KeyspaceDefaults,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self>;
 }
 
 impl UnflushableKeyspace for Keyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        root: &str,
+        partmap: LoadedPartfile,
+        defaults: KeyspaceDefaults,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Arc<Table>> = Coremap::with_capacity(partmap.len());
         for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
             if table_storage_type > 1 {
                 return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
             }
             let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<Table>(ksid, &tableid, is_volatile, model_code)?;
+            let read = self::read_table::<Table>(root, ksid, &tableid, is_volatile, model_code);
+            let tbl = match read {
+                Ok(tbl) => tbl,
+                // a corrupted table in the data directory is quarantined (see [`quarantine`]),
+                // but a snapshot with one can't be restored
+                Err(e) if e.is_corruption() && root == DIR_KSROOT => {
+                    self::quarantine_table(ksid, &tableid, is_volatile, model_code, e)?
+                }
+                Err(e) => return Err(e),
+            };
             ks.true_if_insert(tableid, Arc::new(tbl));
         }
-        Ok(Keyspace::init_with_all_def_strategy(ks))
+        Ok(Keyspace::init_with_all_def_strategy(ks, defaults))
     }
 }
 
+/// Move the file of a corrupted table to the quarantine and return an empty table in its place
+fn quarantine_table(
+    ksid: &ObjectID,
+    tblid: &ObjectID,
+    volatile: bool,
+    model_code: u8,
+    error: StorageEngineError,
+) -> StorageEngineResult<Table> {
+    let (ks, tbl) = unsafe { (ksid.as_str(), tblid.as_str()) };
+    let to = quarantine::quarantine(ksid, tblid)
+        .map_err_context(format!("quarantining the corrupted table `{ks}.{tbl}`"))?;
+    log::error!(
+        "Quarantined the table `{ks}.{tbl}` since its {error}. The file was moved to `{}` and the table was loaded empty",
+        to.to_string_lossy()
+    );
+    Table::from_model_code(model_code, volatile)
+        .ok_or_else(|| StorageEngineError::bad_metadata_in_table(ksid, tblid))
+}
+
 impl UnflushableKeyspace for SystemKeyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        root: &str,
+        partmap: LoadedPartfile,
+        _: KeyspaceDefaults,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Wrapper<SystemTable>> = Coremap::with_capacity(partmap.len());
         for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
             if table_storage_type > 1 {
                 return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
             }
             let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<SystemTable>(ksid, &tableid, is_volatile, model_code)?;
+            let tbl =
+                self::read_table::<SystemTable>(root, ksid, &tableid, is_volatile, model_code)?;
             ks.true_if_insert(tableid, Wrapper::new(tbl));
         }
         Ok(SystemKeyspace::new(ks))
@@ -98,47 +144,186 @@ pub trait UnflushableTable: Sized {
     ) -> StorageEngineResult<Self>;
 }
 
-#[allow(clippy::transmute_int_to_bool)]
 impl UnflushableTable for Table {
     fn unflush_table(
         filepath: impl AsRef<Path>,
         model_code: u8,
         volatile: bool,
     ) -> StorageEngineResult<Self> {
-        let ret = match model_code {
-            // pure KVEBlob: [0, 3]
-            x if x < 4 => {
-                let data = decode(filepath, volatile)?;
-                let (k_enc, v_enc) = unsafe {
-                    // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
-                    let key: bool = transmute(model_code >> 1);
-                    let value: bool = transmute(((model_code >> 1) + (model_code & 1)) % 2);
-                    (key, value)
-                };
-                Table::new_pure_kve_with_data(data, volatile, k_enc, v_enc)
-            }
-            // KVExtlistmap: [4, 7]
-            x if x < 8 => {
-                let data = decode(filepath, volatile)?;
-                let (k_enc, v_enc) = unsafe {
-                    // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
-                    let code = model_code - 4;
-                    let key: bool = transmute(code >> 1);
-                    let value: bool = transmute(code % 2);
-                    (key, value)
-                };
-                Table::new_kve_listmap_with_data(data, volatile, k_enc, v_enc)
-            }
-            _ => {
-                return Err(StorageEngineError::BadMetadata(
-                    filepath.as_ref().to_string_lossy().to_string(),
-                ))
-            }
+        let source = TableFile {
+            path: filepath.as_ref(),
+            volatile,
+            deadlines: RefCell::default(),
         };
-        Ok(ret)
+        let table = self::table_from_source(&source, model_code, volatile)?.ok_or_else(|| {
+            StorageEngineError::BadMetadata(filepath.as_ref().to_string_lossy().to_string())
+        })?;
+        // keys whose deadlines passed while we were down are evicted like any other
+        for (key, deadline) in source.deadlines.into_inner() {
+            table.set_deadline(&key, deadline);
+        }
+        Ok(table)
     }
 }
 
+/// Where the data of a table is decoded from: its file in the data directory (or in a
+/// snapshot), or an export archive (see [`super::export`])
+pub(super) trait TableSource {
+    /// Decode the data of the table
+    fn decode<T: DeserializeInto>(&self) -> StorageEngineResult<T>;
+    /// Decode the data of a table that stores its values compressed. The values have to be
+    /// packed (see [`crate::kvengine::compression`])
+    fn decode_packed(&self) -> StorageEngineResult<Coremap<SharedSlice, SharedSlice>> {
+        self.decode()
+    }
+}
+
+/// The file of a table. Compressed tables write their values packed, so they're read as is.
+/// The expiry deadlines of the keys are split off the data when it's decoded
+struct TableFile<'a> {
+    path: &'a Path,
+    volatile: bool,
+    deadlines: RefCell<Vec<(SharedSlice, u64)>>,
+}
+
+impl<'a> TableSource for TableFile<'a> {
+    fn decode<T: DeserializeInto>(&self) -> StorageEngineResult<T> {
+        self::decode_with(self.path, self.volatile, |data| {
+            let (deadlines, data) = super::de::split_deadlines(data)?;
+            let decoded = super::de::deserialize_into(data)?;
+            *self.deadlines.borrow_mut() = deadlines;
+            Some(decoded)
+        })
+    }
+}
+
+/// Decode a table with the given model code from `source`. Returns `None` if there's no
+/// model with that code
+#[allow(clippy::transmute_int_to_bool)]
+pub(super) fn table_from_source<S: TableSource>(
+    source: &S,
+    model_code: u8,
+    volatile: bool,
+) -> StorageEngineResult<Option<Table>> {
+    let ret = match model_code {
+        // pure KVEBlob: [0, 3]
+        x if x < 4 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
+                let key: bool = transmute(model_code >> 1);
+                let value: bool = transmute(((model_code >> 1) + (model_code & 1)) % 2);
+                (key, value)
+            };
+            Table::new_pure_kve_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExtlistmap: [4, 7]
+        x if x < 8 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
+                let code = model_code - 4;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(code % 2);
+                (key, value)
+            };
+            Table::new_kve_listmap_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExtsetmap: [8, 11]
+        x if x < 12 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
+                let code = model_code - 8;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(code % 2);
+                (key, value)
+            };
+            Table::new_kve_setmap_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExtzsetmap: [12, 15]
+        x if x < 16 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
+                let code = model_code - 12;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(code % 2);
+                (key, value)
+            };
+            Table::new_kve_zsetmap_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExthashmap: [16, 19]
+        x if x < 20 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
+                let code = model_code - 16;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(code % 2);
+                (key, value)
+            };
+            Table::new_kve_hashmap_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExtcountermap: [20, 21]
+        x if x < 22 => {
+            let data = source.decode()?;
+            Table::new_kve_countermap_with_data(data, volatile, model_code == 21)
+        }
+        // KVEBlob with JSON values: [22, 23]
+        x if x < 24 => {
+            let data = source.decode()?;
+            Table::new_kve_jsonmap_with_data(data, volatile, model_code == 23)
+        }
+        // compressed KVEBlob: [24, 27]
+        x if x < 28 => {
+            let data = source.decode_packed()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Same bitmagic as [0, 3]
+                let code = model_code - 24;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(((code >> 1) + (code & 1)) % 2);
+                (key, value)
+            };
+            Table::new_kve_compressed_with_data(data, volatile, k_enc, v_enc)
+        }
+        // KVExtbloommap: [28, 29]
+        x if x < 30 => {
+            let data = source.decode()?;
+            Table::new_kve_bloommap_with_data(data, volatile, model_code == 29)
+        }
+        // KVExthllmap: [30, 31]
+        x if x < 32 => {
+            let data = source.decode()?;
+            Table::new_kve_hllmap_with_data(data, volatile, model_code == 31)
+        }
+        // KVExtgeomap: [32, 33]
+        x if x < 34 => {
+            let data = source.decode()?;
+            Table::new_kve_geomap_with_data(data, volatile, model_code == 33)
+        }
+        // KVExttimeseriesmap: [34, 35]
+        x if x < 36 => {
+            let data = source.decode()?;
+            Table::new_kve_timeseriesmap_with_data(data, volatile, model_code == 35)
+        }
+        // KVEBlob with its values on disk: [36, 39]
+        x if x < 40 => {
+            let data = source.decode()?;
+            let (k_enc, v_enc) = unsafe {
+                // UNSAFE(@ohsayan): Safe because of the above match. Same bitmagic as [0, 3]
+                let code = model_code - 36;
+                let key: bool = transmute(code >> 1);
+                let value: bool = transmute(((code >> 1) + (code & 1)) % 2);
+                (key, value)
+            };
+            Table::new_kve_disk_with_data(data, volatile, k_enc, v_enc)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(ret))
+}
+
 impl UnflushableTable for SystemTable {
     fn unflush_table(
         filepath: impl AsRef<Path>,
@@ -151,6 +336,16 @@ impl UnflushableTable for SystemTable {
                 let authmap = decode(filepath, volatile)?;
                 Ok(SystemTable::new_auth(Arc::new(authmap)))
             }
+            1 => {
+                // this is the aclmap
+                let aclmap = decode(filepath, volatile)?;
+                Ok(SystemTable::new_acl(Arc::new(aclmap)))
+            }
+            2 => {
+                // this is the map of rotated tokens
+                let rotationmap = decode(filepath, volatile)?;
+                Ok(SystemTable::new_rotation(Arc::new(rotationmap)))
+            }
             _ => Err(StorageEngineError::BadMetadata(
                 filepath.as_ref().to_string_lossy().to_string(),
             )),
@@ -162,55 +357,99 @@ impl UnflushableTable for SystemTable {
 fn decode<T: DeserializeInto>(
     filepath: impl AsRef<Path>,
     volatile: bool,
+) -> StorageEngineResult<T> {
+    self::decode_with(filepath, volatile, super::de::deserialize_into)
+}
+
+/// Read the file at `filepath` and decode its data with `decode`
+fn decode_with<T: DeserializeInto>(
+    filepath: impl AsRef<Path>,
+    volatile: bool,
+    decode: impl Fn(&[u8]) -> Option<T>,
 ) -> StorageEngineResult<T> {
     if volatile {
         Ok(T::new_empty())
     } else {
-        let data = fs::read(filepath.as_ref()).map_err_context(format!(
-            "reading file {}",
-            filepath.as_ref().to_string_lossy()
-        ))?;
-        super::de::deserialize_into(&data).ok_or_else(|| {
-            StorageEngineError::CorruptedFile(filepath.as_ref().to_string_lossy().to_string())
+        let path = filepath.as_ref().to_string_lossy();
+        let data = fs::read(filepath.as_ref()).map_err_context(format!("reading file {path}"))?;
+        let data = encryption::decrypt(&path, data)?;
+        checksum::decode(&path, &data, |data| {
+            decode(data).ok_or_else(|| StorageEngineError::CorruptedFile(path.to_string()))
         })
     }
 }
 
-/// Read a given table into a [`Table`] object
+/// Read a given table (in the tree at `root`) into a [`Table`] object
 ///
 /// This will take care of volatility and the model_code. Just make sure that you pass the proper
 /// keyspace ID and a valid table ID
 pub fn read_table<T: UnflushableTable>(
+    root: &str,
     ksid: &ObjectID,
     tblid: &ObjectID,
     volatile: bool,
     model_code: u8,
 ) -> StorageEngineResult<T> {
-    let filepath = unsafe { concat_path!(DIR_KSROOT, ksid.as_str(), tblid.as_str()) };
+    let filepath = unsafe { concat_path!(root, ksid.as_str(), tblid.as_str()) };
     let tbl = T::unflush_table(filepath, model_code, volatile)?;
     Ok(tbl)
 }
 
-/// Read an entire keyspace into a Coremap. You'll need to initialize the rest
-pub fn read_keyspace<K: UnflushableKeyspace>(ksid: &ObjectID) -> StorageEngineResult<K> {
-    let partmap = self::read_partmap(ksid)?;
-    K::unflush_keyspace(partmap, ksid)
+/// Read an entire keyspace (in the tree at `root`) into a Coremap. You'll need to initialize
+/// the rest
+pub fn read_keyspace<K: UnflushableKeyspace>(
+    root: &str,
+    ksid: &ObjectID,
+) -> StorageEngineResult<K> {
+    let (partmap, defaults) = self::read_partmap(root, ksid)?;
+    K::unflush_keyspace(root, partmap, defaults, ksid)
 }
 
-/// Read the `PARTMAP` for a given keyspace
-pub fn read_partmap(ksid: &ObjectID) -> StorageEngineResult<LoadedPartfile> {
+/// Read the `PARTMAP` for a given keyspace (in the tree at `root`), along with the defaults for
+/// the tables created in the keyspace
+pub fn read_partmap(
+    root: &str,
+    ksid: &ObjectID,
+) -> StorageEngineResult<(LoadedPartfile, KeyspaceDefaults)> {
     let ksid_str = unsafe { ksid.as_str() };
-    let filepath = concat_path!(DIR_KSROOT, ksid_str, "PARTMAP");
-    let partmap_raw = fs::read(&filepath)
-        .map_err_context(format!("while reading {}", filepath.to_string_lossy()))?;
-    super::de::deserialize_set_ctype_bytemark(&partmap_raw)
-        .ok_or_else(|| StorageEngineError::corrupted_partmap(ksid))
+    let filepath = concat_path!(root, ksid_str, "PARTMAP");
+    let path = filepath.to_string_lossy();
+    let partmap_raw = fs::read(&filepath).map_err_context(format!("while reading {path}"))?;
+    let partmap_raw = encryption::decrypt(&path, partmap_raw)?;
+    checksum::decode(&path, &partmap_raw, |partmap| {
+        let (partmap, defaults) = super::de::deserialize_partmap(partmap)
+            .ok_or_else(|| StorageEngineError::corrupted_partmap(ksid))?;
+        let defaults = match defaults {
+            Some(bytemarks) => self::defaults_from_bytemarks(bytemarks)
+                .ok_or_else(|| StorageEngineError::corrupted_partmap(ksid))?,
+            // this keyspace was flushed before the defaults were persisted
+            None => KeyspaceDefaults::default(),
+        };
+        Ok((partmap, defaults))
+    })
+}
+
+/// Decode the storage and model bytemarks of the defaults of a keyspace
+fn defaults_from_bytemarks((storage, model): (u8, u8)) -> Option<KeyspaceDefaults> {
+    let volatile = match storage {
+        bytemarks::BYTEMARK_STORAGE_PERSISTENT => false,
+        bytemarks::BYTEMARK_STORAGE_VOLATILE => true,
+        _ => return None,
+    };
+    let model_code = (model != bytemarks::BYTEMARK_MODEL_NONE).then_some(model);
+    Some(KeyspaceDefaults {
+        model_code,
+        volatile,
+    })
 }
 
-/// Read the `PRELOAD`
-pub fn read_preload() -> StorageEngineResult<PreloadSet> {
-    let read = fs::read(PRELOAD_PATH).map_err_context("reading PRELOAD")?;
-    super::preload::read_preload_raw(read)
+/// Read the `PRELOAD` (of the tree at `root`)
+pub fn read_preload(root: &str) -> StorageEngineResult<PreloadSet> {
+    let filepath = concat_path!(root, "PRELOAD");
+    let path = filepath.to_string_lossy();
+    let read = fs::read(&filepath).map_err_context("reading PRELOAD")?;
+    let read = encryption::decrypt(&path, read)?;
+    checksum::decode(&path, &read, super::preload::read_preload_raw)
 }
 
 /// Read everything and return a [`Memstore`]
@@ -239,13 +478,23 @@ pub fn read_full() -> StorageEngineResult<Memstore> {
         super::flush::flush_full(target, &store)?;
         return Ok(store);
     }
-    let mut preload = self::read_preload()?;
+    // upgrade the data first if it was written by an older version
+    super::upgrade::upgrade()?;
+    self::read_full_from(DIR_KSROOT)
+}
+
+/// Read everything in the tree at `root` (which is [`DIR_KSROOT`] for the data, and the
+/// directory of a snapshot for snapshots) and return a [`Memstore`]
+pub fn read_full_from(root: &str) -> StorageEngineResult<Memstore> {
+    let mut preload = self::read_preload(root)?;
     // HACK(@ohsayan): Pop off the preload from the serial read_keyspace list. It will fail
-    assert!(preload.remove(&SYSTEM));
-    let system_keyspace = self::read_keyspace::<SystemKeyspace>(&SYSTEM)?;
+    if !preload.remove(&SYSTEM) {
+        return Err(StorageEngineError::BadMetadata(format!("{root}/PRELOAD")));
+    }
+    let system_keyspace = self::read_keyspace::<SystemKeyspace>(root, &SYSTEM)?;
     let ksmap = Coremap::with_capacity(preload.len());
     for ksid in preload {
-        let ks = self::read_keyspace::<Keyspace>(&ksid)?;
+        let ks = self::read_keyspace::<Keyspace>(root, &ksid)?;
         ksmap.upsert(ksid, Arc::new(ks));
     }
     // HACK(@ohsayan): Now pop system back in here
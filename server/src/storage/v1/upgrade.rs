@@ -0,0 +1,123 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Upgrades
+//!
+//! The data files are stamped with the version of their format (see
+//! [`preload::FORMAT_VERSION`]). If the server starts up on data in an older format, the data
+//! is backed up to `data/backups/upgrade-v<version>-<UNIX millis>` and then upgraded one
+//! version at a time, with the steps in [`UPGRADES`]. Data in a newer format (written by a
+//! newer version of the server) is never touched, and the server refuses to start up.
+//!
+//! Snapshots aren't upgraded: every older format can still be read, so older snapshots can
+//! be restored as they are (and they're written back in the current format)
+
+use {
+    super::{
+        encryption,
+        error::{ErrorContext, StorageEngineError, StorageEngineResult},
+        flush::{self, Autoflush},
+        interface::{DIR_BACKUPS, DIR_KSROOT},
+        preload::{self, FORMAT_VERSION},
+        unflush,
+    },
+    crate::util::os,
+    chrono::Utc,
+    std::fs,
+};
+
+/// A step that upgrades the data to the next format
+struct Upgrade {
+    /// the format that the data is in once this step is done
+    to: u8,
+    /// what the step does
+    description: &'static str,
+    run: fn() -> StorageEngineResult<()>,
+}
+
+/// The steps that upgrade the data, in order. A step that only changes how the files are
+/// encoded can just [`rewrite`] the data, since every older format can still be read
+const UPGRADES: [Upgrade; 2] = [
+    Upgrade {
+        to: 2,
+        description: "adding checksums to the data files",
+        run: rewrite,
+    },
+    Upgrade {
+        to: 3,
+        description: "adding expiry deadlines to the tables",
+        run: rewrite,
+    },
+];
+
+/// Upgrade the data (in the data directory) if it's in an older format
+pub fn upgrade() -> StorageEngineResult<()> {
+    let path = concat_path!(DIR_KSROOT, "PRELOAD");
+    let preload = fs::read(&path).map_err_context("reading PRELOAD")?;
+    let preload = encryption::decrypt(&path.to_string_lossy(), preload)?;
+    let version = preload::read_format_version(&preload)?;
+    if version > FORMAT_VERSION {
+        return Err(StorageEngineError::UnsupportedFormat(version));
+    }
+    if version == FORMAT_VERSION {
+        return Ok(());
+    }
+    let backup = format!(
+        "{DIR_BACKUPS}/upgrade-v{version}-{}",
+        Utc::now().timestamp_millis()
+    );
+    log::info!(
+        "The data is in format v{version}, so it will be upgraded to v{FORMAT_VERSION}. Backing it up to `{backup}` first"
+    );
+    os::recursive_copy(DIR_KSROOT, &backup)
+        .map_err_context("backing up the data before upgrading it")?;
+    for upgrade in UPGRADES.iter().filter(|upgrade| upgrade.to > version) {
+        log::info!(
+            "Upgrading the data to format v{} ({})",
+            upgrade.to,
+            upgrade.description
+        );
+        (upgrade.run)()?;
+    }
+    log::info!("Upgraded the data to format v{FORMAT_VERSION}");
+    Ok(())
+}
+
+/// Read the data and write it back in the current format. The `PRELOAD` (with the format
+/// version) is written last, so if we crash in between, the upgrade is just run again
+fn rewrite() -> StorageEngineResult<()> {
+    let store = unflush::read_full_from(DIR_KSROOT)?;
+    flush::flush_full(Autoflush, &store).map_err_context("writing the upgraded data")?;
+    flush::oneshot::flush_preload(&Autoflush, &store)
+        .map_err_context("writing the upgraded PRELOAD")?;
+    Ok(())
+}
+
+#[test]
+fn upgrades_are_in_order() {
+    assert!(UPGRADES.windows(2).all(|pair| pair[0].to + 1 == pair[1].to));
+    assert_eq!(UPGRADES.last().unwrap().to, FORMAT_VERSION);
+}
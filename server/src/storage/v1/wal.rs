@@ -0,0 +1,1319 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Write-ahead log
+//!
+//! BGSAVE only flushes the data every so often, so a crash loses every write made since the
+//! last flush. With the write-ahead log (WAL) turned on, every write is appended to
+//! `data/wal` before it's acknowledged, and the log is replayed on top of the last flush when
+//! the server starts up again. Every successful BGSAVE checkpoints the log: the writes that it
+//! had are on disk now, so it's truncated.
+//!
+//! Writes are logged as the queries that made them (in the order that they were applied), so
+//! replaying the log is just running those queries again. The log is only as durable as the
+//! `fsync` policy makes it:
+//! - `always`: the log is synced before every write is acknowledged (the writes that come in
+//! while the log is being synced wait for the next sync, which they then share)
+//! - `<N>ms`: the log is synced at most `N` milliseconds after a write, so a power loss can
+//! lose (at most) the writes made in that window. A crash of the server alone loses nothing.
+//! `everysec` is `1000ms`
+//! - `no`: the OS decides when the log is synced
+//!
+//! ## Durability of tables
+//! Every table can override the `fsync` policy with `ALTER MODEL <model> WITH durability =
+//! <durability>` (or when it's created), where the durability is one of:
+//! - `default`: use the `fsync` policy (this is what tables start out with)
+//! - `always`: sync the log before every write to the table is acknowledged
+//! - `everysec` or a number of milliseconds: sync the log at most that long after a write
+//! - `none`: don't log the writes at all, so a crash loses every write made to the table since
+//! the last BGSAVE (in exchange, the writes don't wait for their turn to be logged)
+//!
+//! Writes to volatile tables are never logged. A table's durability is only used while the log
+//! is on, and like its limits, it isn't saved to disk: it goes back to `default` when the
+//! server restarts (unless the `ALTER MODEL` is replayed from the log). Actions are logged with
+//! the durability of the table that they were run on, even if they write to another table (like
+//! `MOVE`), and schema changes (`CREATE`, `ALTER`, `DROP`, ...) always use the `fsync` policy
+//!
+//! While the log is on, writes wait for BGSAVE (and snapshots, and snapshot restores) to
+//! complete, since the log can only be truncated if no write can slip in between the flush and
+//! the truncation.
+//!
+//! ## Writing the log
+//! A write only has to be logged in the same order as the other writes to its table, so every
+//! table takes its own turns (see [`sequence`]): writes to a table are applied one at a time,
+//! while writes to other tables are applied alongside them. The writes that can touch any table
+//! (like `MOVE` and the schema changes) take the turns of every table. In its turn, a write
+//! only queues its record in memory. The WAL writer (see [`crate::services::walsync`]) appends
+//! everything that has been queued in one go, and syncs the log once for all the queued writes
+//! that have to be synced before they're acknowledged (which wait for it after they've given up
+//! their turn), so that no write ever waits for the disk while it holds its turn.
+//!
+//! ## Point-in-time recovery
+//! When local snapshots are enabled too, a checkpoint moves the writes in the log to a new
+//! segment of the archive (`data/walarchive/<ms>`) instead of throwing them away, and every
+//! snapshot marks the point in the log where it was taken. `skyd --recover-to <time>` then
+//! recovers the data to how it was at that time (see [`recover`]): the latest snapshot taken
+//! before it is loaded and the writes logged after the snapshot (up to that time) are run again.
+//! Everything logged after that time is thrown away, since it no longer happened. The segments
+//! archived before the oldest snapshot are removed when the snapshot is rotated out.
+//!
+//! Restoring a backup or a snapshot (or running without the log, or without archiving it) breaks
+//! the history, and this is marked as well: the data can only be recovered from a snapshot taken
+//! after the last break.
+//!
+//! ## Record format
+//! A record is `[u32 length][u32 CRC-32 of the payload][payload]`, where the payload is
+//! `[u64 timestamp (ms)][u8 length][entity][packet]`. The entity (`<keyspace>.<table>`) is the
+//! one that the queries were run on, and the packet is a (simple or pipelined) Skyhash 2.0
+//! query. Everything is little endian. A torn or corrupted record (from a crash in the middle
+//! of a write) ends the log, and it's truncated there when the log is replayed. The marks (for
+//! snapshots and breaks) are records with an entity that can't be a real one (`#snapshot`, with
+//! the name of the snapshot as the packet, and `#break`).
+//!
+//! If there's an encryption key (see [`super::encryption`]), the payload of every record in the
+//! log (and in the archive) is encrypted like a file is, and the length and the checksum are
+//! those of the encrypted payload. A record that can't be decrypted, or that isn't encrypted,
+//! stops the server from starting up instead of ending the log. The records that are fed to the
+//! replicas (and to the other nodes of a cluster) aren't encrypted, since they're never stored
+//! as they are.
+//!
+//! ## Caveats
+//! - Relative TTLs (like the ones set by `EXPIRE`) start over when they're replayed
+//! - Keys removed by the expiry sweeper or evicted from volatile tables aren't logged (they're
+//! just removed again, later)
+//! - Changes to users (the `AUTH` actions) aren't logged, since tokens are generated randomly
+
+use {
+    super::{
+        checksum, encryption,
+        flush::{self, Autoflush},
+        interface::{DIR_WALARCHIVE, FILE_WAL},
+        sengine::SnapshotEngine,
+    },
+    crate::{
+        audit,
+        auth::{acl, AuthProvider},
+        blueql, cdc, cluster,
+        config::{WalConfig, WalFsync},
+        corestore::{memstore::ObjectID, Corestore},
+        dbnet::{self, AuthProviderHandle},
+        kvengine::txn::TxnOp,
+        protocol::{QueryLimits, Skyhash2},
+        registry, replication, IoResult,
+    },
+    chrono::{NaiveDateTime, Utc},
+    core::{
+        fmt,
+        hash::{Hash, Hasher},
+        mem,
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    parking_lot::{const_mutex, Mutex},
+    std::{
+        borrow::Cow,
+        collections::hash_map::DefaultHasher,
+        fs::{self, File, OpenOptions},
+        io::{self, BufReader, Error as IoError, ErrorKind, Read, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    tokio::sync::{
+        oneshot, Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, Notify, RwLock,
+        RwLockReadGuard, RwLockWriteGuard,
+    },
+};
+
+/// Set once the log has been opened, so that queries can skip logging cheaply
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The open log. This is held while the log is written to, so that it can't be truncated (or
+/// marked) in the middle of a write
+static WAL: Mutex<Option<Wal>> = const_mutex(None);
+/// The records that are waiting for the WAL writer. This is only ever held briefly, so that the
+/// writes never wait for the disk
+static QUEUE: Mutex<Option<Queue>> = const_mutex(None);
+/// Writes to a table are logged in the order that they're applied by running them one at a
+/// time. Every table takes its turn here (see [`turn_of`])
+static TURNS: [AsyncMutex<()>; TURN_COUNT] = [const { AsyncMutex::const_new(()) }; TURN_COUNT];
+/// The writes that don't take turns hold this (shared) while they're applied, so that
+/// [`pause`] can wait for them
+static UNSEQUENCED: RwLock<()> = RwLock::const_new(());
+
+/// The number of turns that the tables share
+const TURN_COUNT: usize = 64;
+/// The size of the length and the checksum in front of every record
+const RECORD_HEADER_SIZE: usize = 8;
+/// The size of the timestamp and the entity length at the start of every payload
+const PAYLOAD_HEADER_SIZE: usize = 9;
+/// The actions that log themselves, and the ones that don't change the data
+const UNLOGGED: [&[u8]; 5] = [b"EXEC", b"EVAL", b"BLPOP", b"BRPOP", b"NOTIFY"];
+/// The entity of the records that mark where a snapshot was taken
+const SNAPSHOT_MARK: &[u8] = b"#snapshot";
+/// The entity of the records that mark where the history breaks
+const BREAK_MARK: &[u8] = b"#break";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// How the writes to a table are logged (see the [module docs](self))
+pub enum Durability {
+    /// use the `fsync` policy
+    Default,
+    /// don't log the writes
+    None,
+    /// sync the log like this instead of using the `fsync` policy
+    Fsync(WalFsync),
+}
+
+impl Durability {
+    // the raw values of the durabilities that aren't an interval
+    const RAW_DEFAULT: u64 = u64::MAX;
+    const RAW_NONE: u64 = u64::MAX - 1;
+    const RAW_NO: u64 = u64::MAX - 2;
+    const RAW_ALWAYS: u64 = 0;
+    /// Returns the durability with the given name (`default`, `none`, `always` or
+    /// `everysec`), ignoring the case
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"default" => Some(Self::Default),
+            b"none" => Some(Self::None),
+            b"always" => Some(Self::Fsync(WalFsync::Always)),
+            b"everysec" => Some(Self::Fsync(WalFsync::EVERYSEC)),
+            _ => None,
+        }
+    }
+    const fn to_raw(self) -> u64 {
+        match self {
+            Self::Default => Self::RAW_DEFAULT,
+            Self::None => Self::RAW_NONE,
+            Self::Fsync(WalFsync::No) => Self::RAW_NO,
+            Self::Fsync(WalFsync::Always) => Self::RAW_ALWAYS,
+            Self::Fsync(WalFsync::Every(ms)) => ms,
+        }
+    }
+    const fn from_raw(raw: u64) -> Self {
+        match raw {
+            Self::RAW_DEFAULT => Self::Default,
+            Self::RAW_NONE => Self::None,
+            Self::RAW_NO => Self::Fsync(WalFsync::No),
+            Self::RAW_ALWAYS => Self::Fsync(WalFsync::Always),
+            ms => Self::Fsync(WalFsync::Every(ms)),
+        }
+    }
+}
+
+impl fmt::Display for Durability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::None => f.write_str("none"),
+            Self::Fsync(fsync) => fmt::Display::fmt(fsync, f),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A [`Durability`] that can be changed while it's shared
+pub struct AtomicDurability(AtomicU64);
+
+impl AtomicDurability {
+    pub const fn new(durability: Durability) -> Self {
+        Self(AtomicU64::new(durability.to_raw()))
+    }
+    pub fn load(&self) -> Durability {
+        Durability::from_raw(self.0.load(Ordering::Acquire))
+    }
+    pub fn store(&self, durability: Durability) {
+        self.0.store(durability.to_raw(), Ordering::Release)
+    }
+}
+
+/// The open log
+struct Wal {
+    file: File,
+    /// whether checkpoints move the writes to the archive
+    archive: bool,
+}
+
+impl Wal {
+    /// Append the queued records and sync the log if they're due (see [`write`])
+    fn write_queued(&mut self) -> IoResult<()> {
+        let now = Instant::now();
+        let (records, waiters, due) = match QUEUE.lock().as_mut() {
+            Some(queue) => {
+                let due = !queue.waiters.is_empty()
+                    || queue.deadline.is_some_and(|deadline| deadline <= now);
+                if due {
+                    // everything that has been written is synced now
+                    queue.deadline = None;
+                }
+                let records = mem::take(&mut queue.records);
+                (records, mem::take(&mut queue.waiters), due)
+            }
+            None => return Ok(()),
+        };
+        // the records are written in one go, so that a crash can only ever tear the last one
+        let mut ret = self.file.write_all(&records);
+        if ret.is_ok() && due {
+            ret = self.file.sync_data();
+        }
+        if ret.is_err() {
+            // the writes were applied already, so all we can do is to flag it
+            registry::poison();
+        }
+        // the waiters are let go even if this failed, since the poisoned registry turns away
+        // the writes that come after them
+        for waiter in waiters {
+            let _ = waiter.send(());
+        }
+        ret
+    }
+    /// Append a mark (see the [module docs](self)) after the queued records, and sync it
+    fn mark(&mut self, mark: &[u8], packet: &[u8]) -> IoResult<()> {
+        self.write_queued()?;
+        let timestamp = Utc::now().timestamp_millis() as u64;
+        let record = self::encode_record(timestamp, mark, packet);
+        self.file.write_all(&self::seal_record(&record)?)?;
+        self.file.sync_data()
+    }
+}
+
+/// The records that are waiting for the WAL writer (see [`write`])
+struct Queue {
+    records: Vec<u8>,
+    /// the `fsync` policy
+    fsync: WalFsync,
+    /// the writes that wait for their records to be synced
+    waiters: Vec<oneshot::Sender<()>>,
+    /// when the records that haven't been synced yet have to be synced by
+    deadline: Option<Instant>,
+    /// wakes up the WAL writer
+    writer: Arc<Notify>,
+}
+
+impl Queue {
+    /// Queue a record, returning what to wait on if it has to be synced before the write is
+    /// acknowledged
+    fn push(&mut self, record: &[u8], durability: Durability) -> Option<oneshot::Receiver<()>> {
+        self.records.extend_from_slice(record);
+        let fsync = match durability {
+            Durability::Fsync(fsync) => fsync,
+            _ => self.fsync,
+        };
+        let waiter = match fsync {
+            WalFsync::Always => {
+                let (tx, rx) = oneshot::channel();
+                self.waiters.push(tx);
+                Some(rx)
+            }
+            WalFsync::Every(ms) => {
+                let deadline = Instant::now() + Duration::from_millis(ms);
+                if self.deadline.is_none_or(|current| deadline < current) {
+                    self.deadline = Some(deadline);
+                }
+                None
+            }
+            WalFsync::No => None,
+        };
+        self.writer.notify_one();
+        waiter
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The tables that a write can touch, so that it knows which turns to take (see [`sequence`])
+pub enum Scope<'a> {
+    /// only the table with this keyspace and table name
+    Table(&'a [u8], &'a [u8]),
+    /// any table (or the schema)
+    Everything,
+}
+
+impl<'a> Scope<'a> {
+    /// The table with these IDs (see [`Corestore::get_ids`])
+    pub fn table(ids: (Option<&'a ObjectID>, Option<&'a ObjectID>)) -> Self {
+        match ids {
+            (Some(ks), Some(tbl)) => Self::Table(&ks[..], &tbl[..]),
+            (Some(ks), None) => Self::Table(&ks[..], b""),
+            _ => Self::Table(b"", b""),
+        }
+    }
+    /// The current table of `db`
+    pub fn current(db: &'a Corestore) -> Self {
+        Self::table(db.get_ids())
+    }
+}
+
+/// Returns the turn that writes to the given table take
+fn turn_of(keyspace: &[u8], table: &[u8]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    keyspace.hash(&mut hasher);
+    table.hash(&mut hasher);
+    (hasher.finish() % TURN_COUNT as u64) as usize
+}
+
+/// Take the turns of every table (always in the same order, so that two writes that take them
+/// can't deadlock)
+async fn all_turns() -> Vec<AsyncMutexGuard<'static, ()>> {
+    let mut turns = Vec::with_capacity(TURN_COUNT);
+    for turn in TURNS.iter() {
+        turns.push(turn.lock().await);
+    }
+    turns
+}
+
+/// Like [`all_turns`], but for a blocking context
+fn blocking_all_turns() -> Vec<AsyncMutexGuard<'static, ()>> {
+    TURNS.iter().map(AsyncMutex::blocking_lock).collect()
+}
+
+/// How a write waits for its turn (see [`sequence`])
+enum Turn {
+    /// the write is logged or fed to the replicas, so it runs on its own (with respect to the
+    /// other writes to the tables that it can touch)
+    Exclusive {
+        _turns: Vec<AsyncMutexGuard<'static, ()>>,
+    },
+    /// the write runs alongside the other ones that aren't
+    Shared {
+        _unsequenced: RwLockReadGuard<'static, ()>,
+    },
+}
+
+/// Our turn to write (see [`sequence`]). The turn is given up once the write has been queued
+/// for the log (or once this is dropped, if it isn't logged)
+pub struct Sequence {
+    turn: Turn,
+    /// the durability that the write is logged with (if it's logged)
+    durability: Option<Durability>,
+}
+
+impl Sequence {
+    /// Log the queries that were run (in this order) on the given entity, feed them to the
+    /// replicas and capture their changes. Once the write has been queued for the log, our turn
+    /// is given up and this waits for it to be synced (if its durability says so)
+    pub async fn log<Q: AsRef<[T]>, T: AsRef<[u8]>>(
+        self,
+        entity: (Option<&ObjectID>, Option<&ObjectID>),
+        queries: &[Q],
+    ) {
+        // (a replica that starts syncing only ever sees the writes that took their turn after
+        // it; see `pause`. The same goes for a migration of slots)
+        let Self { turn, durability } = self;
+        let exclusive = matches!(turn, Turn::Exclusive { .. });
+        let feeds = exclusive && replication::is_feeding();
+        let forwards = exclusive && cluster::migration::is_active();
+        let captures = exclusive && cdc::is_enabled();
+        if durability.is_none() && !feeds && !forwards && !captures {
+            return;
+        }
+        let entity = match entity {
+            (Some(ks), Some(tbl)) => [&ks[..], b".", &tbl[..]].concat(),
+            (Some(ks), None) => ks.to_vec(),
+            _ => Vec::new(),
+        };
+        let timestamp = Utc::now().timestamp_millis() as u64;
+        if captures {
+            cdc::capture(timestamp, &entity, queries);
+        }
+        if durability.is_none() && !feeds && !forwards {
+            return;
+        }
+        let packet = match queries {
+            [query] => Skyhash2::encode_simple_query::<T>(query.as_ref()),
+            queries => Skyhash2::encode_pipelined_query(queries),
+        };
+        let record = self::encode_record(timestamp, &entity, &packet);
+        let waiter = match durability {
+            Some(durability) => self::queue(&record, durability),
+            None => None,
+        };
+        if forwards {
+            cluster::migration::forward(queries, &record);
+        }
+        if feeds {
+            replication::publish(record);
+        }
+        drop(turn);
+        if let Some(waiter) = waiter {
+            // the WAL writer lets us go once it has synced the record (or failed to)
+            let _ = waiter.await;
+        }
+    }
+    /// Log a transaction that was applied on the given entity
+    pub async fn log_transaction(
+        self,
+        entity: (Option<&ObjectID>, Option<&ObjectID>),
+        ops: &[TxnOp],
+    ) {
+        let mut queries: Vec<Vec<&[u8]>> = Vec::with_capacity(ops.len() + 2);
+        queries.push(vec![&b"MULTI"[..]]);
+        for op in ops {
+            let action: &[u8] = match op {
+                TxnOp::Set(..) => b"SET",
+                TxnOp::Update(..) => b"UPDATE",
+                TxnOp::Upsert(..) => b"USET",
+                TxnOp::Del(_) => b"DEL",
+            };
+            let mut query = vec![action, op.key().as_ref()];
+            if let Some(value) = op.value() {
+                query.push(value.as_ref());
+            }
+            queries.push(query);
+        }
+        queries.push(vec![&b"EXEC"[..]]);
+        self.log(entity, &queries).await
+    }
+}
+
+/// Returns true if writes are being logged
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Returns the durability of the writes made to the current table
+pub fn durability(db: &Corestore) -> Durability {
+    match db.get_ctable_ref() {
+        Some(table) if table.is_volatile() => Durability::None,
+        Some(table) => table.durability(),
+        None => Durability::Default,
+    }
+}
+
+/// Returns the durability that the dispatcher has to log a query that starts with `first` (an
+/// action or a BlueQL statement) with, or `None` if it isn't logged by the dispatcher
+pub fn durability_of(first: &[u8], db: &Corestore) -> Option<Durability> {
+    if !self::logs(first) {
+        None
+    } else if self::is_blueql(first) {
+        Some(Durability::Default)
+    } else {
+        Some(self::durability(db))
+    }
+}
+
+/// Returns the tables that a query that starts with `first` (an action or a BlueQL statement)
+/// and goes on with `args` can write to
+pub fn scope_of<'a, 'b>(
+    first: &[u8],
+    args: impl Iterator<Item = &'b [u8]>,
+    db: &'a Corestore,
+) -> Scope<'a> {
+    if self::is_blueql(first) {
+        // schema changes can name any entity
+        return Scope::Everything;
+    }
+    let mut folded = [0u8; 16];
+    match self::fold(first, &mut folded) {
+        Some(action) if !acl::writes_elsewhere(action, args) => Scope::current(db),
+        _ => Scope::Everything,
+    }
+}
+
+fn is_blueql(first: &[u8]) -> bool {
+    first.iter().any(u8::is_ascii_whitespace)
+}
+
+/// Uppercase an action into `folded`. Returns `None` if it's too long to be an action
+fn fold<'a>(first: &[u8], folded: &'a mut [u8; 16]) -> Option<&'a [u8]> {
+    let folded = folded.get_mut(..first.len())?;
+    folded.copy_from_slice(first);
+    folded.make_ascii_uppercase();
+    Some(folded)
+}
+
+/// Returns true if the dispatcher has to log a query that starts with `first`. The actions in
+/// [`UNLOGGED`] log themselves (if they write at all)
+fn logs(first: &[u8]) -> bool {
+    if self::is_blueql(first) {
+        // a BlueQL statement. only the ones that change the schema write
+        return audit::is_ddl_statement(first);
+    }
+    // this is looked at for every query, so fold the name on the stack (nothing this long is
+    // an action)
+    let mut folded = [0u8; 16];
+    match self::fold(first, &mut folded) {
+        Some(action) => acl::writes(action) && !UNLOGGED.contains(&action),
+        None => false,
+    }
+}
+
+/// Queue a record (encrypted, if there's a key) for the WAL writer. Returns what to wait on if
+/// it has to be synced before the write is acknowledged
+fn queue(record: &[u8], durability: Durability) -> Option<oneshot::Receiver<()>> {
+    let sealed = match self::seal_record(record) {
+        Ok(sealed) => sealed,
+        Err(e) => {
+            // the write was applied already, so all we can do is to flag it
+            log::error!("Failed to encrypt a record for the write-ahead log: {e}");
+            registry::poison();
+            return None;
+        }
+    };
+    QUEUE
+        .lock()
+        .as_mut()
+        .and_then(|queue| queue.push(&sealed, durability))
+}
+
+/// Wait for our turn to write with the given durability to the tables in `scope`. Writes that
+/// are logged, and all the writes while there are replicas to feed (see [`crate::replication`]),
+/// slots to migrate (see [`crate::cluster::migration`]) or changes to capture (see
+/// [`crate::cdc`]), are applied one at a time per table (see the [module docs](self)). The rest
+/// are applied alongside each other, and only wait for [`pause`]
+pub async fn sequence(durability: Durability, scope: Scope<'_>) -> Sequence {
+    let shared = UNSEQUENCED.read().await;
+    // (this is looked at once we hold it, so that `pause` can't miss a write that should
+    // have taken its turn)
+    let logged = self::is_enabled() && durability != Durability::None;
+    if logged || replication::is_feeding() || cluster::migration::is_active() || cdc::is_enabled() {
+        // `pause` takes its turn while it holds this, so let go of it first
+        drop(shared);
+        let turns = match scope {
+            Scope::Table(keyspace, table) => {
+                vec![TURNS[self::turn_of(keyspace, table)].lock().await]
+            }
+            Scope::Everything => self::all_turns().await,
+        };
+        Sequence {
+            turn: Turn::Exclusive { _turns: turns },
+            durability: logged.then_some(durability),
+        }
+    } else {
+        Sequence {
+            turn: Turn::Shared {
+                _unsequenced: shared,
+            },
+            durability: None,
+        }
+    }
+}
+
+/// No writes are applied while this is held (see [`pause`])
+pub struct Paused {
+    _unsequenced: RwLockWriteGuard<'static, ()>,
+    _turns: Vec<AsyncMutexGuard<'static, ()>>,
+}
+
+/// Wait for the writes that are being applied to complete, and hold off the rest till the
+/// returned guard is dropped. Writes that start after this has returned take their turn (and
+/// hence are fed to the replicas) if there's a replica to feed
+pub async fn pause() -> Paused {
+    let unsequenced = UNSEQUENCED.write().await;
+    Paused {
+        _unsequenced: unsequenced,
+        _turns: self::all_turns().await,
+    }
+}
+
+/// Run a flush of all the data and truncate the log if it succeeds. No writes are applied
+/// until it's done. This blocks, so it has to be called from a blocking context
+pub fn checkpoint(flush: impl FnOnce() -> IoResult<()>) -> IoResult<()> {
+    self::_checkpoint(flush, false)
+}
+
+/// Like [`checkpoint`], but for a flush that replaces the data (with a snapshot), so the history
+/// in the log breaks there
+pub fn checkpoint_replaced(flush: impl FnOnce() -> IoResult<()>) -> IoResult<()> {
+    self::_checkpoint(flush, true)
+}
+
+fn _checkpoint(flush: impl FnOnce() -> IoResult<()>, replaced: bool) -> IoResult<()> {
+    // the replicas have to sync again once the data is replaced, so nothing is applied (or fed
+    // to them) till it's done
+    let _unsequenced = replaced.then(|| UNSEQUENCED.blocking_write());
+    if !self::is_enabled() && !replaced {
+        return flush();
+    }
+    let _turns = self::blocking_all_turns();
+    if replaced {
+        replication::resync();
+    }
+    flush()?;
+    if let Some(wal) = WAL.lock().as_mut() {
+        // the flush has the writes that are still queued, so they go before the truncation
+        wal.write_queued()?;
+        if wal.archive {
+            self::archive(wal.file.metadata()?.len())?;
+        }
+        wal.file.set_len(0)?;
+        if replaced {
+            wal.mark(BREAK_MARK, b"")?;
+        }
+        wal.file.sync_data()?;
+    }
+    Ok(())
+}
+
+/// Take a snapshot and mark where it was taken in the log, so that the data can be recovered
+/// from it (see [`recover`]). No writes are applied until it's done. This blocks, so it has to
+/// be called from a blocking context
+pub fn snapshot<E>(name: &str, take: impl FnOnce() -> Result<(), E>) -> Result<(), E> {
+    if !self::is_enabled() {
+        return take();
+    }
+    let _turns = self::blocking_all_turns();
+    take()?;
+    if let Some(wal) = WAL.lock().as_mut() {
+        if let Err(e) = wal.mark(SNAPSHOT_MARK, name.as_bytes()) {
+            log::warn!("Failed to mark the snapshot `{name}` in the write-ahead log (the data can't be recovered from it): {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Move the first `len` bytes of the log to a new segment of the archive
+fn archive(len: u64) -> IoResult<()> {
+    if len == 0 {
+        return Ok(());
+    }
+    let mut segment = self::new_segment()?;
+    io::copy(&mut File::open(FILE_WAL)?.take(len), &mut segment)?;
+    segment.sync_all()
+}
+
+/// Create a new segment in the archive. It's named after the time that it was created at (and
+/// always comes after the segments that are there already)
+fn new_segment() -> IoResult<File> {
+    fs::create_dir_all(DIR_WALARCHIVE)?;
+    let mut name = Utc::now().timestamp_millis() as u64;
+    if let Some((last, _)) = self::segments()?.last() {
+        name = name.max(last + 1);
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(format!("{DIR_WALARCHIVE}/{name}"))
+}
+
+/// Returns the segments of the archive (and their names), oldest first
+fn segments() -> IoResult<Vec<(u64, PathBuf)>> {
+    let dir = match fs::read_dir(DIR_WALARCHIVE) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut segments = Vec::new();
+    for entry in dir {
+        let entry = entry?;
+        if let Some(name) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        {
+            segments.push((name, entry.path()));
+        }
+    }
+    segments.sort_unstable_by_key(|(name, _)| *name);
+    Ok(segments)
+}
+
+/// Remove the segments of the archive that were archived before the snapshot with the given
+/// name (the oldest one that's left) was taken, since the data can't be recovered from any
+/// point in them anymore
+pub fn prune_archive(oldest_snapshot: &str) -> IoResult<()> {
+    let taken = match NaiveDateTime::parse_from_str(oldest_snapshot, "%Y%m%d-%H%M%S") {
+        Ok(taken) => taken.timestamp_millis() as u64,
+        Err(_) => return Ok(()),
+    };
+    for (name, path) in self::segments()? {
+        // everything in a segment was logged before it was archived
+        if name >= taken {
+            break;
+        }
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Returns the notification that the WAL writer waits on for records to be queued (or for
+/// [`next_sync`] to move up)
+pub fn writer() -> Option<Arc<Notify>> {
+    QUEUE.lock().as_ref().map(|queue| queue.writer.clone())
+}
+
+/// Returns when the writes that haven't been synced yet have to be synced by
+pub fn next_sync() -> Option<Instant> {
+    QUEUE.lock().as_ref().and_then(|queue| queue.deadline)
+}
+
+/// Append the records that have been queued to the log in one go, and sync the log if any of
+/// them has to be synced before its write is acknowledged, or if the writes that haven't been
+/// synced yet are due (see [`next_sync`]). This blocks, so it has to be called from a blocking
+/// context
+pub fn write() -> IoResult<()> {
+    match WAL.lock().as_mut() {
+        Some(wal) => wal.write_queued(),
+        None => Ok(()),
+    }
+}
+
+/// Replay the log (if there is one) on top of the data that was loaded from the last flush,
+/// and then open it for the writes to come. If `discard` is set (when the data was restored
+/// from a backup), the log is thrown away instead of being replayed. If `archive` is set (when
+/// local snapshots are enabled), checkpoints move the writes to the archive
+pub async fn init(cfg: &WalConfig, db: &Corestore, discard: bool, archive: bool) -> IoResult<()> {
+    let path = Path::new(FILE_WAL);
+    let existed = path.exists();
+    if !cfg.enabled && !existed {
+        return Ok(());
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    if !discard {
+        self::replay(&mut file, db).await?;
+    } else if file.metadata()?.len() != 0 {
+        log::warn!("Discarding the write-ahead log since the data was restored from a backup");
+        file.set_len(0)?;
+    }
+    if cfg.enabled {
+        let mut wal = Wal { file, archive };
+        if !archive {
+            // the writes won't be archived, so the history in the archive ends here
+            self::break_archive()?;
+        } else if discard || !existed {
+            // the data doesn't follow from the writes in the archive
+            wal.mark(BREAK_MARK, b"")?;
+        }
+        *WAL.lock() = Some(wal);
+        *QUEUE.lock() = Some(Queue {
+            records: Vec::new(),
+            fsync: cfg.fsync,
+            waiters: Vec::new(),
+            deadline: None,
+            writer: Arc::new(Notify::new()),
+        });
+        ENABLED.store(true, Ordering::Release);
+    } else {
+        // the log has been turned off, so save what it had before we get rid of it
+        flush::flush_full(Autoflush, db.get_store())?;
+        drop(file);
+        fs::remove_file(path)?;
+        log::info!("Removed the write-ahead log since it's disabled");
+    }
+    Ok(())
+}
+
+/// Mark a break at the end of the archive (if there is one), unless it ends with a break
+/// already
+fn break_archive() -> IoResult<()> {
+    let last = match self::segments()?.pop() {
+        Some((_, last)) => last,
+        None => return Ok(()),
+    };
+    let mut reader = BufReader::new(File::open(last)?);
+    let mut ends_with_break = false;
+    while let Ok(Some(record)) = self::read_logged_record(&mut reader) {
+        ends_with_break = record.entity == BREAK_MARK;
+    }
+    if !ends_with_break {
+        let timestamp = Utc::now().timestamp_millis() as u64;
+        let record = self::encode_record(timestamp, BREAK_MARK, b"");
+        let mut segment = self::new_segment()?;
+        segment.write_all(&self::seal_record(&record)?)?;
+        segment.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Run every write in the log again
+async fn replay(file: &mut File, db: &Corestore) -> IoResult<()> {
+    if file.metadata()?.len() == 0 {
+        return Ok(());
+    }
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(&*file);
+    // the writes were authorized when they were logged
+    let mut auth = AuthProviderHandle::new(AuthProvider::new_disabled());
+    let (mut offset, mut replayed, mut last) = (0, 0usize, 0);
+    loop {
+        let record = match self::read_logged_record(&mut reader) {
+            Ok(Some(record)) => record,
+            Ok(None) => break,
+            Err(e) if matches!(e.kind(), ErrorKind::UnexpectedEof | ErrorKind::InvalidData) => {
+                log::warn!(
+                    "Truncating the write-ahead log at a torn or corrupted record (at byte {offset}): {e}"
+                );
+                file.set_len(offset)?;
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        offset += record.size();
+        if !record.is_mark() && self::apply(db, &mut auth, &record).await? {
+            replayed += 1;
+            last = record.timestamp;
+        }
+    }
+    if replayed != 0 {
+        log::info!("Replayed {replayed} write(s) from the write-ahead log");
+        if let Some(last) = NaiveDateTime::from_timestamp_millis(last as i64) {
+            log::info!("The last write in the write-ahead log was made at {last} UTC");
+        }
+    }
+    Ok(())
+}
+
+/// Run a write that was fed to us by a primary (as an encoded record; see
+/// [`crate::replication`]) again. Returns the time that it was made at on the primary
+pub async fn apply_record(
+    db: &Corestore,
+    auth: &mut AuthProviderHandle,
+    record: &[u8],
+) -> IoResult<u64> {
+    let record = self::read_record(&mut &record[..])?
+        .ok_or_else(|| IoError::from(ErrorKind::UnexpectedEof))?;
+    if !record.is_mark() {
+        self::apply(db, auth, &record).await?;
+    }
+    Ok(record.timestamp)
+}
+
+/// Run the writes in a run of encoded records (that another cluster node forwarded to us; see
+/// [`crate::cluster::migration`]) again
+pub async fn apply_records(
+    db: &Corestore,
+    auth: &mut AuthProviderHandle,
+    mut records: &[u8],
+) -> IoResult<()> {
+    while let Some(record) = self::read_record(&mut records)? {
+        if !record.is_mark() {
+            self::apply(db, auth, &record).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a write from the log again. Returns false if it was skipped since the entity that it was
+/// run on doesn't exist
+async fn apply(db: &Corestore, auth: &mut AuthProviderHandle, record: &Record) -> IoResult<bool> {
+    let mut db = db.clone();
+    if !record.entity.is_empty() {
+        let swapped = blueql::util::from_slice_action_result::<Skyhash2>(&record.entity)
+            .ok()
+            .map(|entity| db.swap_entity(&entity));
+        if !matches!(swapped, Some(Ok(()))) {
+            log::warn!(
+                "Skipped a write on `{}` since it doesn't exist",
+                String::from_utf8_lossy(&record.entity)
+            );
+            return Ok(false);
+        }
+    }
+    // (the packets were limited when they were received)
+    let limits = QueryLimits::new(usize::MAX, usize::MAX);
+    dbnet::execute_packet(&db, auth, None, &record.packet, limits, true).await?;
+    Ok(true)
+}
+
+/// Recover the data to how it was at the given time (in milliseconds since the epoch): the
+/// latest local snapshot taken before then is loaded, and the writes logged after it (up to
+/// that time) are run again. The writes logged after that time are thrown away, and the
+/// recovered data is flushed. The users and their permissions are left as they are
+pub async fn recover(db: &Corestore, engine: &SnapshotEngine, time: u64) -> IoResult<()> {
+    let live = Path::new(FILE_WAL);
+    let mut sources: Vec<PathBuf> = self::segments()?
+        .into_iter()
+        .map(|(_, segment)| segment)
+        .collect();
+    if live.exists() {
+        sources.push(live.to_path_buf());
+    }
+    // find where the history ends (at the first write made after that time), and the
+    // snapshots taken before then (as the source and the offset of their marks)
+    let (mut end, mut snapshots) = (None, Vec::new());
+    'scan: for (source, path) in sources.iter().enumerate() {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut offset = 0;
+        loop {
+            let record = match self::read_logged_record(&mut reader) {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(e) if matches!(e.kind(), ErrorKind::UnexpectedEof | ErrorKind::InvalidData) => {
+                    log::warn!("The write-ahead log ends at a torn or corrupted record: {e}");
+                    end = Some((source, offset));
+                    break 'scan;
+                }
+                Err(e) => return Err(e),
+            };
+            if record.timestamp > time {
+                end = Some((source, offset));
+                break 'scan;
+            }
+            offset += record.size();
+            match &record.entity[..] {
+                SNAPSHOT_MARK => {
+                    let name = String::from_utf8_lossy(&record.packet).into_owned();
+                    snapshots.push((source, offset, name));
+                }
+                BREAK_MARK => snapshots.clear(),
+                _ => {}
+            }
+        }
+    }
+    let (name, start, restored) = loop {
+        let (source, offset, name) = match snapshots.pop() {
+            Some(snapshot) => snapshot,
+            None => {
+                return Err(IoError::new(
+                    ErrorKind::NotFound,
+                    "there's no snapshot to recover the data from (taken before that time with the write-ahead log on)",
+                ))
+            }
+        };
+        match engine.read_local(&name) {
+            Some(Ok(restored)) => break (name, (source, offset), restored),
+            Some(Err(e)) => {
+                return Err(IoError::new(
+                    ErrorKind::Other,
+                    format!("failed to read the snapshot `{name}`: {e}"),
+                ))
+            }
+            None => log::warn!("Skipped the snapshot `{name}` since it no longer exists"),
+        }
+    };
+    db.get_store().replace_keyspaces(restored);
+    let mut auth = AuthProviderHandle::new(AuthProvider::new_disabled());
+    let mut replayed = 0usize;
+    for (source, path) in sources.iter().enumerate().skip(start.0) {
+        let mut file = File::open(path)?;
+        let from = if source == start.0 { start.1 } else { 0 };
+        let to = match end {
+            Some((end, _)) if source > end => break,
+            Some((end, offset)) if source == end => offset,
+            _ => file.metadata()?.len(),
+        };
+        file.seek(SeekFrom::Start(from))?;
+        let mut reader = BufReader::new(file.take(to - from));
+        while let Some(record) = self::read_logged_record(&mut reader)? {
+            if !record.is_mark() && self::apply(db, &mut auth, &record).await? {
+                replayed += 1;
+            }
+        }
+    }
+    flush::flush_full(Autoflush, db.get_store())?;
+    // the writes made after that time never happened now
+    if let Some((end, offset)) = end {
+        for (source, path) in sources.iter().enumerate().skip(end) {
+            let len = if source == end { offset } else { 0 };
+            if len == 0 && path != live {
+                fs::remove_file(path)?;
+            } else {
+                OpenOptions::new().write(true).open(path)?.set_len(len)?;
+            }
+        }
+    }
+    // and the ones before it are in the data
+    if live.exists() {
+        self::archive(fs::metadata(live)?.len())?;
+        OpenOptions::new().write(true).open(live)?.set_len(0)?;
+    }
+    if let Some(time) = NaiveDateTime::from_timestamp_millis(time as i64) {
+        log::info!(
+            "Recovered the data to {time} UTC from the snapshot `{name}` and {replayed} write(s) in the write-ahead log"
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+/// A record read from the log
+struct Record {
+    timestamp: u64,
+    entity: Vec<u8>,
+    packet: Vec<u8>,
+    /// the number of bytes that the record takes up in the log
+    size: u64,
+}
+
+impl Record {
+    /// Decode the (decrypted) payload of a record that takes up `size` bytes in the log
+    fn decode(payload: &[u8], size: u64) -> IoResult<Self> {
+        let bad_record = || IoError::new(ErrorKind::InvalidData, "bad record");
+        if payload.len() < PAYLOAD_HEADER_SIZE {
+            return Err(bad_record());
+        }
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&payload[..8]);
+        let entity_end = PAYLOAD_HEADER_SIZE + payload[8] as usize;
+        if entity_end > payload.len() {
+            return Err(bad_record());
+        }
+        Ok(Self {
+            timestamp: u64::from_le_bytes(timestamp),
+            entity: payload[PAYLOAD_HEADER_SIZE..entity_end].to_vec(),
+            packet: payload[entity_end..].to_vec(),
+            size,
+        })
+    }
+    /// Returns the number of bytes that the record takes up in the log
+    fn size(&self) -> u64 {
+        self.size
+    }
+    /// Returns true if this is a mark and not a write
+    fn is_mark(&self) -> bool {
+        self.entity.first() == Some(&b'#')
+    }
+}
+
+/// Encode a record
+fn encode_record(timestamp: u64, entity: &[u8], packet: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(PAYLOAD_HEADER_SIZE + entity.len() + packet.len());
+    payload.extend(timestamp.to_le_bytes());
+    // (entities are never longer than 129 bytes)
+    payload.push(entity.len() as u8);
+    payload.extend(entity);
+    payload.extend(packet);
+    self::frame(&payload)
+}
+
+/// Put the length and the checksum in front of a payload
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+    record.extend((payload.len() as u32).to_le_bytes());
+    record.extend(checksum::crc32(payload).to_le_bytes());
+    record.extend(payload);
+    record
+}
+
+/// Encrypt the payload of an encoded record for the log, if there's a key (see the
+/// [module docs](self))
+fn seal_record(record: &[u8]) -> IoResult<Cow<'_, [u8]>> {
+    match encryption::seal(&record[RECORD_HEADER_SIZE..])? {
+        Some(sealed) => Ok(Cow::Owned(self::frame(&sealed))),
+        None => Ok(Cow::Borrowed(record)),
+    }
+}
+
+/// Read the next record (that isn't encrypted). Returns `None` at the end of the records, and
+/// an `UnexpectedEof` or an `InvalidData` error if the record is torn or corrupted
+fn read_record(src: &mut impl Read) -> IoResult<Option<Record>> {
+    match self::read_payload(src)? {
+        Some((payload, size)) => Record::decode(&payload, size).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Like [`read_record`], but for a record in the log (or in the archive), that's decrypted if
+/// there's a key. A record that can't be decrypted (or that isn't encrypted) is an error of
+/// another kind, since it isn't torn or corrupted
+fn read_logged_record(src: &mut impl Read) -> IoResult<Option<Record>> {
+    let (payload, size) = match self::read_payload(src)? {
+        Some(read) => read,
+        None => return Ok(None),
+    };
+    let payload = encryption::decrypt(FILE_WAL, payload)
+        .map_err(|e| IoError::new(ErrorKind::Other, e.to_string()))?;
+    Record::decode(&payload, size).map(Some)
+}
+
+/// Read the payload of the next record, and the number of bytes that the record takes up
+fn read_payload(src: &mut impl Read) -> IoResult<Option<(Vec<u8>, u64)>> {
+    let mut header = [0u8; RECORD_HEADER_SIZE];
+    match self::read_fully(src, &mut header)? {
+        0 => return Ok(None),
+        RECORD_HEADER_SIZE => {}
+        _ => return Err(ErrorKind::UnexpectedEof.into()),
+    }
+    let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    // don't trust the length until we've read that much
+    let mut payload = Vec::new();
+    src.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() != len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    if checksum::crc32(&payload) != checksum {
+        return Err(IoError::new(ErrorKind::InvalidData, "checksum mismatch"));
+    }
+    Ok(Some((payload, (RECORD_HEADER_SIZE + len) as u64)))
+}
+
+/// Like [`Read::read_exact`], but returns the number of bytes read if we hit EOF first
+fn read_fully(src: &mut impl Read, buf: &mut [u8]) -> IoResult<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match src.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            encode_record, logs, read_record, AtomicDurability, Durability, Queue, Record,
+            BREAK_MARK, SNAPSHOT_MARK,
+        },
+        crate::config::WalFsync,
+        std::{
+            io::{Cursor, ErrorKind},
+            sync::Arc,
+        },
+        tokio::sync::Notify,
+    };
+
+    #[test]
+    fn record_roundtrip() {
+        let mut log = encode_record(1, b"default.default", b"*3\n3\nSET1\nx3\n100");
+        let first_size = log.len() as u64;
+        log.extend(encode_record(2, b"", b"*1\n7\nFLUSHDB"));
+        let mut log = Cursor::new(log);
+        let first = read_record(&mut log).unwrap().unwrap();
+        assert_eq!(
+            first,
+            Record {
+                timestamp: 1,
+                entity: b"default.default".to_vec(),
+                packet: b"*3\n3\nSET1\nx3\n100".to_vec(),
+                size: first_size,
+            }
+        );
+        assert_eq!(
+            read_record(&mut log).unwrap(),
+            Some(Record {
+                timestamp: 2,
+                entity: Vec::new(),
+                packet: b"*1\n7\nFLUSHDB".to_vec(),
+                size: 8 + 9 + 12,
+            })
+        );
+        assert_eq!(read_record(&mut log).unwrap(), None);
+    }
+
+    #[test]
+    fn marks() {
+        for (mark, packet) in [
+            (SNAPSHOT_MARK, &b"20220705-143000"[..]),
+            (BREAK_MARK, &b""[..]),
+        ] {
+            let record = encode_record(1, mark, packet);
+            let record = read_record(&mut Cursor::new(record)).unwrap().unwrap();
+            assert!(record.is_mark());
+            assert_eq!(record.packet, packet);
+        }
+        for entity in [&b"default.default"[..], b""] {
+            let write = encode_record(2, entity, b"*1\n7\nFLUSHDB");
+            let write = read_record(&mut Cursor::new(write)).unwrap().unwrap();
+            assert!(!write.is_mark());
+        }
+    }
+
+    #[test]
+    fn torn_records() {
+        let record = encode_record(1, b"default.default", b"*3\n3\nSET1\nx3\n100");
+        for len in 1..record.len() {
+            let e = read_record(&mut Cursor::new(&record[..len])).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::UnexpectedEof, "torn at {len}");
+        }
+        let mut corrupted = record.clone();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        let e = read_record(&mut Cursor::new(corrupted)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn logged_queries() {
+        assert!(logs(b"set"));
+        assert!(logs(b"LPUSH"));
+        assert!(logs(b"create model mymodel(string, string)"));
+        // reads
+        assert!(!logs(b"GET"));
+        assert!(!logs(b"inspect spaces"));
+        assert!(!logs(b"AUTH"));
+        // these log themselves, or don't change the data
+        assert!(!logs(b"exec"));
+        assert!(!logs(b"BLPOP"));
+        assert!(!logs(b"NOTIFY"));
+    }
+
+    #[test]
+    fn durability_roundtrip() {
+        let durability = AtomicDurability::new(Durability::Default);
+        assert_eq!(durability.load(), Durability::Default);
+        for expected in [
+            Durability::None,
+            Durability::Fsync(WalFsync::Always),
+            Durability::Fsync(WalFsync::Every(1)),
+            Durability::Fsync(WalFsync::Every(250)),
+            Durability::Fsync(WalFsync::No),
+            Durability::Default,
+        ] {
+            durability.store(expected);
+            assert_eq!(durability.load(), expected);
+        }
+    }
+
+    #[test]
+    fn durability_names() {
+        assert_eq!(Durability::from_name(b"DEFAULT"), Some(Durability::Default));
+        assert_eq!(Durability::from_name(b"none"), Some(Durability::None));
+        assert_eq!(
+            Durability::from_name(b"everysec"),
+            Some(Durability::Fsync(WalFsync::Every(1000)))
+        );
+        assert_eq!(Durability::from_name(b"sometimes"), None);
+        assert_eq!(Durability::Fsync(WalFsync::Every(250)).to_string(), "250ms");
+        assert_eq!(Durability::Fsync(WalFsync::Always).to_string(), "always");
+    }
+
+    #[test]
+    fn queued_records() {
+        let mut queue = Queue {
+            records: Vec::new(),
+            fsync: WalFsync::No,
+            waiters: Vec::new(),
+            deadline: None,
+            writer: Arc::new(Notify::new()),
+        };
+        // nothing to wait for or sync with the `fsync` policy
+        assert!(queue.push(b"first", Durability::Default).is_none());
+        assert!(queue.deadline.is_none());
+        // a deadline for the writes that are synced every few milliseconds
+        assert!(queue
+            .push(b"second", Durability::Fsync(WalFsync::EVERYSEC))
+            .is_none());
+        let deadline = queue.deadline.unwrap();
+        // (which only ever moves up)
+        queue.push(b"third", Durability::Fsync(WalFsync::Every(60_000)));
+        assert_eq!(queue.deadline, Some(deadline));
+        // and a waiter for the writes that are synced before they're acknowledged
+        let mut waiter = queue
+            .push(b"fourth", Durability::Fsync(WalFsync::Always))
+            .unwrap();
+        assert_eq!(queue.records, b"firstsecondthirdfourth");
+        assert_eq!(queue.waiters.len(), 1);
+        assert!(waiter.try_recv().is_err());
+        queue.waiters.pop().unwrap().send(()).unwrap();
+        assert!(waiter.try_recv().is_ok());
+    }
+}
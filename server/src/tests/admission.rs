@@ -0,0 +1,141 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the per-IP connection limits (the third test server allows 16 concurrent
+//! connections and 32 new connections per second from an IP). The connections are opened
+//! from other loopback addresses so that the rest of the tests that run against the server
+//! don't count against the limits
+
+use {
+    skytable::{
+        query,
+        types::{Array, FlatElement},
+        AsyncConnection, Element,
+    },
+    std::net::{IpAddr, Ipv4Addr, SocketAddr},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpSocket, TcpStream},
+        time::{self, Duration},
+    },
+};
+
+const SERVER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 2007);
+const MAX_PER_IP: usize = 16;
+const MAX_RATE: usize = 32;
+
+async fn connect_from(ip: Ipv4Addr) -> TcpStream {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(SocketAddr::new(IpAddr::V4(ip), 0)).unwrap();
+    socket.connect(SERVER).await.unwrap()
+}
+
+/// Returns true if the server answers a query on the connection (the ones that are turned
+/// away are closed right after they're accepted)
+async fn is_served(con: &mut TcpStream) -> bool {
+    if con.write_all(b"*1\n4\nheya").await.is_err() {
+        return false;
+    }
+    let mut response = [0u8; 8];
+    let read = time::timeout(Duration::from_secs(10), con.read_exact(&mut response))
+        .await
+        .expect("timed out waiting for the response");
+    match read {
+        Ok(_) => {
+            assert_eq!(&response, b"*+4\nHEY!");
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Returns the value of a connection counter
+async fn counter(name: &str) -> u64 {
+    let mut con = AsyncConnection::new("127.0.0.1", 2007).await.unwrap();
+    let fields = match con
+        .run_query_raw(&query!("sys", "metric", "connections"))
+        .await
+        .unwrap()
+    {
+        Element::Array(Array::Flat(fields)) => fields,
+        other => panic!("Bad response for sys metric connections: {:?}", other),
+    };
+    let position = fields
+        .iter()
+        .position(|field| *field == FlatElement::String(name.to_owned()))
+        .unwrap();
+    match fields[position + 1] {
+        FlatElement::UnsignedInt(value) => value,
+        ref other => panic!("Bad value for {name}: {:?}", other),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_max_connections_per_ip() {
+    let ip = Ipv4Addr::new(127, 0, 0, 2);
+    let mut open = Vec::with_capacity(MAX_PER_IP);
+    for _ in 0..MAX_PER_IP {
+        let mut con = self::connect_from(ip).await;
+        assert!(is_served(&mut con).await);
+        open.push(con);
+    }
+    let mut con = self::connect_from(ip).await;
+    assert!(!is_served(&mut con).await);
+    assert!(counter("rejected-per-ip").await >= 1);
+    // closing the connections makes room for new ones (once the server notices)
+    drop(open);
+    let mut served = false;
+    for _ in 0..10 {
+        time::sleep(Duration::from_millis(100)).await;
+        let mut con = self::connect_from(ip).await;
+        if is_served(&mut con).await {
+            served = true;
+            break;
+        }
+    }
+    assert!(served);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_max_connection_rate() {
+    let ip = Ipv4Addr::new(127, 0, 0, 3);
+    // however slow we are, we'll open more than `MAX_RATE` connections in one of the windows
+    let rejected = {
+        let mut rejected = false;
+        for _ in 0..MAX_RATE * 8 {
+            let mut con = self::connect_from(ip).await;
+            if !is_served(&mut con).await {
+                rejected = true;
+                break;
+            }
+        }
+        rejected
+    };
+    assert!(rejected);
+    assert!(counter("rejected-rate").await >= 1);
+}
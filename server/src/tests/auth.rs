@@ -39,7 +39,7 @@ macro_rules! assert_auth_disabled {
         assert_autherror!(
             $con,
             $query,
-            RespCode::ErrorString("err-auth-disabled".to_owned())
+            RespCode::ErrorString("501 err-auth-disabled".to_owned())
         )
     };
 }
@@ -99,7 +99,9 @@ async fn claim_root_fail_already_claimed() {
     runeq!(
         con,
         query!("auth", "claim", crate::TEST_AUTH_ORIGIN_KEY),
-        Element::RespCode(RespCode::ErrorString("err-auth-already-claimed".to_owned()))
+        Element::RespCode(RespCode::ErrorString(
+            "500 err-auth-already-claimed".to_owned()
+        ))
     )
 }
 
@@ -287,6 +289,125 @@ async fn auth_whoami_okay_rootuser() {
     )
 }
 
+// rate limits
+// `throttleduser` has a budget of two queries per second on the second test server
+#[sky_macros::dbtest_func(port = 2005, norun = true, auth_rootuser = true)]
+async fn ratelimit_throttles_user() {
+    let token: String = con
+        .run_query(query!("auth", "adduser", "throttleduser"))
+        .await
+        .unwrap();
+    runeq!(
+        con,
+        query!("auth", "login", "throttleduser", token),
+        Element::RespCode(RespCode::Okay)
+    );
+    runeq!(con, query!("heya"), Element::String("HEY!".to_owned()));
+    runeq!(con, query!("heya"), Element::String("HEY!".to_owned()));
+    runeq!(
+        con,
+        query!("heya"),
+        Element::RespCode(RespCode::ErrorString("703 throttled".to_owned()))
+    );
+}
+
+// user management
+#[sky_macros::dbtest_func(port = 2005, norun = true, auth_rootuser = true)]
+async fn rotate_keeps_old_token_in_grace_period() {
+    let oldtoken: String = con
+        .run_query(query!("auth", "adduser", "rotateuser"))
+        .await
+        .unwrap();
+    let newtoken: String = con
+        .run_query(query!("auth", "rotate", "rotateuser", "60"))
+        .await
+        .unwrap();
+    runeq!(
+        con,
+        query!("auth", "login", "rotateuser", oldtoken),
+        Element::RespCode(RespCode::Okay)
+    );
+    runeq!(
+        con,
+        query!("auth", "login", "rotateuser", newtoken),
+        Element::RespCode(RespCode::Okay)
+    );
+}
+
+#[sky_macros::dbtest_func(port = 2005, norun = true, auth_rootuser = true)]
+async fn renameuser_okay_because_root() {
+    let token: String = con
+        .run_query(query!("auth", "adduser", "renameduser"))
+        .await
+        .unwrap();
+    runeq!(
+        con,
+        query!("auth", "renameuser", "renameduser", "renameduser2"),
+        Element::RespCode(RespCode::Okay)
+    );
+    let ret: Vec<String> = con.run_query(query!("auth", "listuser")).await.unwrap();
+    assert!(!ret.contains(&"renameduser".to_owned()));
+    assert!(ret.contains(&"renameduser2".to_owned()));
+    runeq!(
+        con,
+        query!("auth", "login", "renameduser2", token),
+        Element::RespCode(RespCode::Okay)
+    );
+}
+
+#[sky_macros::dbtest_func(port = 2005, auth_testuser = true)]
+async fn renameuser_fail_because_not_root() {
+    assert_auth_perm_error!(con, query!("auth", "renameuser", "testuser", "testuser2"));
+}
+
+// ACLs
+#[sky_macros::dbtest_func(port = 2005, norun = true, auth_rootuser = true)]
+async fn acl_restricts_user() {
+    let token: String = con
+        .run_query(query!("auth", "adduser", "acluser"))
+        .await
+        .unwrap();
+    runeq!(
+        con,
+        query!("auth", "grant", "acluser", "default.default", "read"),
+        Element::RespCode(RespCode::Okay)
+    );
+    let grants: Vec<String> = con
+        .run_query(query!("auth", "grants", "acluser"))
+        .await
+        .unwrap();
+    assert_eq!(grants, vec!["default.default read".to_owned()]);
+    runeq!(
+        con,
+        query!("auth", "login", "acluser", token),
+        Element::RespCode(RespCode::Okay)
+    );
+    // reads are allowed, but writes and DDL aren't
+    runeq!(
+        con,
+        query!("get", "acl-missing-key"),
+        Element::RespCode(RespCode::NotFound)
+    );
+    assert_auth_perm_error!(con, query!("set", "acl-missing-key", "100"));
+    assert_auth_perm_error!(con, query!("create model default.aclmodel(string, string)"));
+    // and the same goes for the notifications of other tables
+    assert_auth_perm_error!(con, query!("subscribe", "__notify__:default:aclmodel"));
+    assert_auth_perm_error!(
+        con,
+        query!("publish", "__notify__:default:default", "set:x")
+    );
+}
+
+#[sky_macros::dbtest_func(port = 2005, auth_testuser = true)]
+async fn acl_grant_fail_because_not_root() {
+    assert_auth_perm_error!(con, query!("auth", "grant", "testuser", "default", "all"));
+}
+
+#[sky_macros::dbtest_func(port = 2005, auth_testuser = true)]
+async fn script_load_fail_because_not_root() {
+    assert_auth_perm_error!(con, query!("script", "load", "notroot", "DEL $1"));
+}
+
 mod syntax_checks {
     use super::{NOAUTH, ONLYAUTH};
     use crate::auth::provider::testsuite_data::{
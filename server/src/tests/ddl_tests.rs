@@ -28,7 +28,13 @@
 mod __private {
     use {
         libstress::utils,
-        skytable::{query, types::Array, Element, Query, RespCode},
+        skytable::{
+            query,
+            types::{Array, FlatElement},
+            Element, Pipeline, Query, RespCode,
+        },
+        std::time::Duration,
+        tokio::time,
     };
 
     async fn test_create_keyspace() {
@@ -133,7 +139,7 @@ mod __private {
         query.push(format!("USE {__MYENTITY__} wiwofjwjfio"));
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ErrorString("bql-invalid-syntax".into()))
+            Element::RespCode(RespCode::ErrorString("604 bql-invalid-syntax".into()))
         )
     }
     async fn test_whereami() {
@@ -166,4 +172,641 @@ mod __private {
             ]))
         );
     }
+    async fn test_alter_model_volatility() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create model {tblname}(string, string) volatile")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!(
+                "alter model {__MYKS__}.{tblname} with volatile = false"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{__MYKS__}.{tblname}"), "(str,str)", false);
+        runeq!(
+            con,
+            query!(format!("alter model {tblname} with volatile=true")),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{__MYKS__}.{tblname}"), "(str,str)", true);
+    }
+    async fn test_alter_model_bad_expression() {
+        runeq!(
+            con,
+            query!(format!("alter model {__MYENTITY__} with volatile = maybe")),
+            Element::RespCode(RespCode::ErrorString("600 bql-bad-expression".to_owned()))
+        );
+        runeq!(
+            con,
+            query!(format!("alter model {__MYENTITY__} volatile = true")),
+            Element::RespCode(RespCode::ErrorString("600 bql-bad-expression".to_owned()))
+        );
+    }
+    async fn test_alter_model_nonexistent() {
+        runeq!(
+            con,
+            query!("alter model nosuchks.nosuchtbl with volatile = false"),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+    async fn test_rename_model() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        let newname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create model {tblname}(string, string) volatile")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("rename model {__MYKS__}.{tblname} {newname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{__MYKS__}.{newname}"), "(str,str)", true);
+        runeq!(
+            con,
+            query!(format!("inspect model {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+    async fn test_rename_model_in_use() {
+        // we're using this table
+        runeq!(
+            con,
+            query!(format!("rename model {__MYENTITY__} inuse")),
+            Element::RespCode(RespCode::ErrorString("202 still-in-use".to_owned()))
+        );
+    }
+    async fn test_rename_space() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        let newname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create space {ksname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("rename space {ksname} {newname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("drop space {newname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("drop space {ksname}")),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+    async fn test_rename_space_protected() {
+        runeq!(
+            con,
+            query!("rename space default mydefault"),
+            Element::RespCode(RespCode::ErrorString("203 err-protected-object".to_owned()))
+        );
+    }
+    async fn test_create_space_with_defaults() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create space {ksname} with model = (string, string), volatile = true"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        // no declaration; inherit the space's defaults
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.inherits")),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{ksname}.inherits"), "(str,str)", true);
+        // an explicit declaration overrides the default model
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.overrides(binary, binary)")),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{ksname}.overrides"), "(binstr,binstr)", true);
+        // as does an explicit volatility
+        runeq!(
+            con,
+            query!(format!(
+                "create model {ksname}.persistent with volatile = false"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{ksname}.persistent"), "(str,str)", false);
+    }
+    async fn test_create_model_without_defaults() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create space {ksname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.nomodel")),
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+    async fn test_shards() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create space {ksname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.users(string, binary)")),
+            Element::RespCode(RespCode::Okay)
+        );
+        // the shard inherits the model of its table
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.users.eu")),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{ksname}.users.eu"), "(str,binstr)", false);
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.users.us(string, string)")),
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.nosuchmodel.eu")),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+        // shards are independent of their table
+        runeq!(
+            con,
+            query!(format!("use {ksname}.users.eu")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("dbsize", format!("{ksname}.users")),
+            Element::UnsignedInt(0)
+        );
+        runeq!(
+            con,
+            query!("dbsize", format!("{ksname}.users.eu")),
+            Element::UnsignedInt(1)
+        );
+        runeq!(
+            con,
+            query!(format!("use {__MYENTITY__}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        // a table can't be dropped while it has shards
+        runeq!(
+            con,
+            query!(format!("drop model {ksname}.users")),
+            Element::RespCode(RespCode::ErrorString("202 still-in-use".to_owned()))
+        );
+        runeq!(
+            con,
+            query!(format!("drop model {ksname}.users.eu force")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("drop model {ksname}.users")),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+    async fn test_shards_wildcard_listing() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create space {ksname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("create model {ksname}.users(string, string)")),
+            Element::RespCode(RespCode::Okay)
+        );
+        for shard in ["eu", "us"] {
+            runeq!(
+                con,
+                query!(format!("create model {ksname}.users.{shard}")),
+                Element::RespCode(RespCode::Okay)
+            );
+        }
+        // shards are only listed with their table
+        match con
+            .run_query_raw(&query!(format!("inspect model {ksname}.*")))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Recursive(models)) => assert_eq!(models.len(), 1),
+            other => panic!("Bad response for inspect: {:?}", other),
+        }
+        match con
+            .run_query_raw(&query!(format!("inspect model {ksname}.users.*")))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Recursive(shards)) => assert_eq!(shards.len(), 2),
+            other => panic!("Bad response for inspect: {:?}", other),
+        }
+        runmatch!(con, query!("inspect space *"), Element::Array);
+    }
+    async fn test_create_compressed() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, string) with compression = lz4"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{__MYKS__}.{tblname}"), "(str,str)", false);
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        let value = "skytable".repeat(100);
+        runeq!(
+            con,
+            query!("set", "x", value.clone()),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("get", "x"), Element::String(value));
+        runeq!(con, query!("strlen", "x"), Element::UnsignedInt(800));
+        runeq!(con, query!("append", "x", "!"), Element::UnsignedInt(801));
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "7"),
+            Element::String("skytable".to_owned())
+        );
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[12..14],
+            &[
+                FlatElement::String("compression".to_owned()),
+                FlatElement::String("lz4".to_owned())
+            ]
+        );
+    }
+    async fn test_create_compressed_unsupported_model() {
+        runeq!(
+            con,
+            query!("create model mylists(string, list<string>) with compression = lz4"),
+            Element::RespCode(RespCode::ErrorString(
+                "607 bql-unsupported-model-decl".to_owned()
+            ))
+        );
+        runeq!(
+            con,
+            query!("create model mycompressed(string, string) with compression = zip"),
+            Element::RespCode(RespCode::ErrorString("600 bql-bad-expression".to_owned()))
+        );
+    }
+    async fn test_create_on_disk() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, string) with storage = disk"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_model_decl!(con, format!("{__MYKS__}.{tblname}"), "(str,str)", false);
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        let value = "skytable".repeat(100);
+        runeq!(
+            con,
+            query!("set", "x", value.clone()),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("get", "x"), Element::String(value));
+        runeq!(con, query!("strlen", "x"), Element::UnsignedInt(800));
+        runeq!(con, query!("append", "x", "!"), Element::UnsignedInt(801));
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "7"),
+            Element::String("skytable".to_owned())
+        );
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[26..],
+            &[
+                FlatElement::String("storage".to_owned()),
+                FlatElement::String("disk".to_owned())
+            ]
+        );
+    }
+    async fn test_create_on_disk_unsupported_model() {
+        runeq!(
+            con,
+            query!("create model mylists(string, list<string>) with storage = disk"),
+            Element::RespCode(RespCode::ErrorString(
+                "607 bql-unsupported-model-decl".to_owned()
+            ))
+        );
+        runeq!(
+            con,
+            query!("create model mydisk(string, string) with storage = disk, compression = lz4"),
+            Element::RespCode(RespCode::ErrorString(
+                "607 bql-unsupported-model-decl".to_owned()
+            ))
+        );
+    }
+    async fn test_create_with_size_limits() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, string) with max_key_size = 8, max_value_size = 16"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[14..18],
+            &[
+                FlatElement::String("max_key_size".to_owned()),
+                FlatElement::UnsignedInt(8),
+                FlatElement::String("max_value_size".to_owned()),
+                FlatElement::UnsignedInt(16),
+            ]
+        );
+        runeq!(
+            con,
+            query!("set", "x", "sixteen bytes ok"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "y", "a".repeat(17)),
+            Element::RespCode(RespCode::ErrorString("308 too-large".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("set", "keyislong", "v"),
+            Element::RespCode(RespCode::ErrorString("308 too-large".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("append", "x", "!"),
+            Element::RespCode(RespCode::ErrorString("308 too-large".to_owned()))
+        );
+        runeq!(
+            con,
+            query!(format!(
+                "alter model {__MYKS__}.{tblname} with max_value_size = 0"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "y", "a".repeat(17)),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "keyislong", "v"),
+            Element::RespCode(RespCode::ErrorString("308 too-large".to_owned()))
+        );
+    }
+    async fn test_create_with_eviction() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, string) volatile with max_memory = 1, eviction = random"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // the memory reporter evicts the key (it runs every second)
+        let mut evicted = false;
+        for _ in 0..30 {
+            time::sleep(Duration::from_millis(100)).await;
+            if con.run_query_raw(&query!("get", "x")).await.unwrap()
+                == Element::RespCode(RespCode::NotFound)
+            {
+                evicted = true;
+                break;
+            }
+        }
+        assert!(evicted);
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[18..24],
+            &[
+                FlatElement::String("max_memory".to_owned()),
+                FlatElement::UnsignedInt(1),
+                FlatElement::String("eviction".to_owned()),
+                FlatElement::String("random".to_owned()),
+                FlatElement::String("evicted".to_owned()),
+                FlatElement::UnsignedInt(1),
+            ]
+        );
+        // nothing is evicted once the policy is gone
+        runeq!(
+            con,
+            query!(format!(
+                "alter model {__MYKS__}.{tblname} with eviction = none"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        time::sleep(Duration::from_millis(1500)).await;
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_create_with_durability() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, string) with durability = 250"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[24..26],
+            &[
+                FlatElement::String("durability".to_owned()),
+                FlatElement::String("250ms".to_owned()),
+            ]
+        );
+        runeq!(
+            con,
+            query!(format!(
+                "alter model {__MYKS__}.{tblname} with durability = none"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        let fields = match con.run_query_raw(&query!("inspect model")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(fields[25], FlatElement::String("none".to_owned()));
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        for durability in ["sometimes", "0"] {
+            runeq!(
+                con,
+                query!(format!(
+                    "alter model {__MYKS__}.{tblname} with durability = {durability}"
+                )),
+                Element::RespCode(RespCode::ErrorString("600 bql-bad-expression".to_owned()))
+            );
+        }
+    }
+    async fn test_entity_prefix() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        let entity = format!("@{__MYKS__}.{tblname}");
+        runeq!(
+            con,
+            query!(format!("create model {__MYKS__}.{tblname}(string, string)")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(entity.clone(), "set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(entity.clone(), "get", "x"),
+            Element::String("100".to_owned())
+        );
+        // the override only applies to the query it was used with
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!("whereami"),
+            Element::Array(Array::NonNullStr(vec![__MYKS__, __MYTABLE__]))
+        );
+        // so does a `USE` that is run with an override
+        runeq!(
+            con,
+            query!(entity, "use default"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("whereami"),
+            Element::Array(Array::NonNullStr(vec![__MYKS__, __MYTABLE__]))
+        );
+    }
+    async fn test_entity_prefix_pipeline() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        let entity = format!("@{__MYKS__}.{tblname}");
+        runeq!(
+            con,
+            query!(format!("create model {__MYKS__}.{tblname}(string, string)")),
+            Element::RespCode(RespCode::Okay)
+        );
+        let pipe = Pipeline::new()
+            .append(query!(entity.clone(), "set", "x", "100"))
+            .append(query!("set", "x", "200"))
+            .append(query!(entity, "get", "x"))
+            .append(query!("get", "x"));
+        assert_eq!(
+            con.run_pipeline(pipe).await.unwrap(),
+            vec![
+                Element::RespCode(RespCode::Okay),
+                Element::RespCode(RespCode::Okay),
+                Element::String("100".to_owned()),
+                Element::String("200".to_owned()),
+            ]
+        );
+    }
+    async fn test_entity_prefix_errors() {
+        runeq!(
+            con,
+            query!("@nosuchks.nosuchtbl", "get", "x"),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+        runeq!(
+            con,
+            query!(format!("@{__MYENTITY__}")),
+            Element::RespCode(RespCode::ActionError)
+        );
+    }
 }
@@ -0,0 +1,198 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for key expiry
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use {
+        skytable::{
+            query,
+            types::{Array, FlatElement},
+            Element, RespCode,
+        },
+        tokio::time::{self, Duration},
+    };
+
+    async fn test_set_with_expiry() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "100"));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_set_with_expiry_bad_ttl() {
+        assert_respcode!(
+            con,
+            query!("set", "x", "100", "EX", "never"),
+            RespCode::Wrongtype
+        );
+    }
+    async fn test_set_with_expiry_bad_flag() {
+        assert_respcode!(
+            con,
+            query!("set", "x", "100", "TTL", "100"),
+            RespCode::ActionError
+        );
+    }
+    async fn test_set_nx() {
+        assert_okay!(con, query!("set", "x", "100", "NX", "EX", "100"));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+        assert_respcode!(
+            con,
+            query!("set", "x", "200", "NX"),
+            RespCode::OverwriteError
+        );
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_set_xx() {
+        assert_respcode!(con, query!("set", "x", "100", "XX"), RespCode::NotFound);
+        setkeys!(con, "x":"100");
+        assert_okay!(con, query!("set", "x", "200", "XX", "EX", "100"));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+        // the expiry is retained if no new one is provided
+        assert_okay!(con, query!("set", "x", "300", "XX"));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+        runeq!(con, query!("get", "x"), Element::String("300".to_owned()));
+    }
+    async fn test_set_conflicting_flags() {
+        assert_respcode!(
+            con,
+            query!("set", "x", "100", "NX", "XX"),
+            RespCode::ActionError
+        );
+        assert_respcode!(
+            con,
+            query!("set", "x", "100", "EX", "10", "EX"),
+            RespCode::ActionError
+        );
+        assert_respcode!(
+            con,
+            query!("set", "x", "100", "XX", "EX"),
+            RespCode::ActionError
+        );
+    }
+    async fn test_expire_okay() {
+        setkeys!(con, "x":"100");
+        assert_okay!(con, query!("expire", "x", "100"));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+    }
+    async fn test_expire_nil() {
+        assert_respcode!(con, query!("expire", "x", "100"), RespCode::NotFound);
+    }
+    async fn test_expire_syntax_error() {
+        runeq!(
+            con,
+            query!("expire", "x"),
+            arity_err!("EXPIRE expects 2 arguments, got 1")
+        );
+        runeq!(
+            con,
+            query!("expire", "x", "10", "y"),
+            arity_err!("EXPIRE expects 2 arguments, got 3")
+        );
+    }
+    async fn test_expired_key_is_gone() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "1"));
+        time::sleep(Duration::from_millis(1500)).await;
+        assert_respcode!(con, query!("get", "x"), RespCode::NotFound);
+        runeq!(con, query!("exists", "x"), Element::UnsignedInt(0));
+        // the key can be set again
+        assert_okay!(con, query!("set", "x", "200"));
+        assert_respcode!(
+            con,
+            query!("ttl", "x"),
+            RespCode::ErrorString("302 no-expiry".into())
+        );
+    }
+    async fn test_ttl_nil() {
+        assert_respcode!(con, query!("ttl", "x"), RespCode::NotFound);
+    }
+    async fn test_ttl_no_expiry() {
+        setkeys!(con, "x":"100");
+        assert_respcode!(
+            con,
+            query!("ttl", "x"),
+            RespCode::ErrorString("302 no-expiry".into())
+        );
+    }
+    async fn test_persist_okay() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "1"));
+        assert_okay!(con, query!("persist", "x"));
+        time::sleep(Duration::from_millis(1500)).await;
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_persist_nil() {
+        setkeys!(con, "x":"100");
+        assert_respcode!(con, query!("persist", "x"), RespCode::NotFound);
+        assert_respcode!(con, query!("persist", "y"), RespCode::NotFound);
+    }
+    async fn test_uset_drops_expiry() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "100"));
+        runeq!(con, query!("uset", "x", "200"), Element::UnsignedInt(1));
+        assert_respcode!(
+            con,
+            query!("ttl", "x"),
+            RespCode::ErrorString("302 no-expiry".into())
+        );
+    }
+    async fn test_exists_with_ttl() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "100"));
+        setkeys!(con, "y":"200");
+        runeq!(
+            con,
+            query!("exists", "x", "y", "z", "WITHTTL"),
+            Element::Array(Array::Flat(vec![
+                FlatElement::UnsignedInt(2),
+                FlatElement::String("x".to_owned()),
+                FlatElement::UnsignedInt(100)
+            ]))
+        );
+        // without any other key, the flag is just a key
+        runeq!(con, query!("exists", "withttl"), Element::UnsignedInt(0));
+    }
+    async fn test_lskeys_with_ttl() {
+        assert_okay!(con, query!("set", "x", "100", "EX", "200"));
+        assert_okay!(con, query!("set", "y", "200", "EX", "100"));
+        setkeys!(con, "z":"300");
+        runeq!(
+            con,
+            query!("lskeys", "WITHTTL"),
+            Element::Array(Array::Flat(vec![
+                FlatElement::String("y".to_owned()),
+                FlatElement::UnsignedInt(100),
+                FlatElement::String("x".to_owned()),
+                FlatElement::UnsignedInt(200)
+            ]))
+        );
+        runeq!(
+            con,
+            query!("lskeys", __MYENTITY__, "1", "withttl"),
+            Element::Array(Array::Flat(vec![
+                FlatElement::String("y".to_owned()),
+                FlatElement::UnsignedInt(100)
+            ]))
+        );
+    }
+}
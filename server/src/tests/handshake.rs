@@ -0,0 +1,80 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for protocol negotiation. These talk to the server over a plain socket since the
+//! client library always speaks the latest protocol
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{self, Duration},
+};
+
+/// Send the given bytes on a new connection and read the given number of bytes back
+async fn roundtrip(send: &[u8], expect_len: usize) -> Vec<u8> {
+    let mut stream = TcpStream::connect("127.0.0.1:2003").await.unwrap();
+    stream.write_all(send).await.unwrap();
+    let mut response = vec![0; expect_len];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut response))
+        .await
+        .expect("timed out waiting for the response")
+        .unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_skyhash1_without_handshake() {
+    const RESPONSE: &[u8] = b"*1\n+4\nHEY!\n";
+    assert_eq!(
+        roundtrip(b"*1\n~1\n4\nheya\n", RESPONSE.len()).await,
+        RESPONSE
+    );
+}
+
+#[tokio::test]
+async fn test_skyhash1_with_handshake() {
+    const RESPONSE: &[u8] = b"H1.0\n*1\n+4\nHEY!\n";
+    assert_eq!(
+        roundtrip(b"H1.0\n*1\n~1\n4\nheya\n", RESPONSE.len()).await,
+        RESPONSE
+    );
+}
+
+#[tokio::test]
+async fn test_skyhash2_with_handshake() {
+    const RESPONSE: &[u8] = b"H2.0\n*+4\nHEY!";
+    assert_eq!(
+        roundtrip(b"H2.0\n*1\n4\nheya", RESPONSE.len()).await,
+        RESPONSE
+    );
+}
+
+#[tokio::test]
+async fn test_unsupported_handshake() {
+    const RESPONSE: &[u8] =
+        b"!701 unsupported-protocol: this server speaks Skyhash-1.0 and Skyhash-2.0\n";
+    assert_eq!(roundtrip(b"H3.0\n", RESPONSE.len()).await, RESPONSE);
+}
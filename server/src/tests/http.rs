@@ -0,0 +1,356 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the HTTP gateway (which is enabled on the first test server)
+
+use {
+    libstress::utils,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::{self, Duration},
+    },
+};
+
+const GATEWAY: &str = "127.0.0.1:2009";
+
+/// Read a response from the stream, returning its status and (raw) body
+async fn read_raw_response(stream: &mut TcpStream) -> (u16, Vec<u8>) {
+    let mut buf = Vec::new();
+    let head_end = loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let mut chunk = [0u8; 1024];
+        let read = time::timeout(Duration::from_secs(10), stream.read(&mut chunk))
+            .await
+            .expect("timed out waiting for the response")
+            .unwrap();
+        assert_ne!(read, 0, "the gateway closed the connection");
+        buf.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8(buf[..head_end].to_vec()).unwrap();
+    let status = head[9..12].parse().unwrap();
+    let content_length: usize = head
+        .lines()
+        .find_map(|line| line.strip_prefix("Content-Length: "))
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut body = buf[head_end..].to_vec();
+    body.resize(content_length, 0);
+    let already_read = buf.len() - head_end;
+    stream.read_exact(&mut body[already_read..]).await.unwrap();
+    (status, body)
+}
+
+/// Read a response from the stream, returning its status and body
+async fn read_response(stream: &mut TcpStream) -> (u16, String) {
+    let (status, body) = self::read_raw_response(stream).await;
+    (status, String::from_utf8(body).unwrap())
+}
+
+/// Send a request on the stream and read its response
+async fn request(stream: &mut TcpStream, method: &str, path: &str, body: &str) -> (u16, String) {
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    self::read_response(stream).await
+}
+
+/// Make a gRPC-Web call (with the arguments in the request) and read its response
+async fn grpc_call(stream: &mut TcpStream, rpc: &str, args: &[&str]) -> (u16, Vec<u8>) {
+    let mut message = Vec::new();
+    for arg in args {
+        // field 2, length-delimited (the args are short enough for one byte varints)
+        message.extend_from_slice(&[0x12, arg.len() as u8]);
+        message.extend_from_slice(arg.as_bytes());
+    }
+    let mut request = format!(
+        "POST /skytable.Skytable/{rpc} HTTP/1.1\r\nContent-Type: application/grpc-web+proto\r\n\
+        Content-Length: {}\r\n\r\n",
+        message.len() + 5
+    )
+    .into_bytes();
+    // a data frame (the flags, the length and the message)
+    request.push(0);
+    request.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    request.extend_from_slice(&message);
+    stream.write_all(&request).await.unwrap();
+    self::read_raw_response(stream).await
+}
+
+/// The frames for a successful gRPC-Web call
+fn grpc_frames(message: &[u8]) -> Vec<u8> {
+    let mut frames = vec![0];
+    frames.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frames.extend_from_slice(message);
+    frames.extend_from_slice(b"\x80\0\0\0\x0fgrpc-status:0\r\n");
+    frames
+}
+
+/// Send a masked WebSocket frame
+async fn ws_send(stream: &mut TcpStream, opcode: u8, payload: &[u8]) {
+    const MASK: [u8; 4] = [0x37, 0xfa, 0x21, 0x3d];
+    // the payloads are short enough for a one byte length
+    let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+    frame.extend_from_slice(&MASK);
+    frame.extend(
+        payload
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ MASK[i % 4]),
+    );
+    stream.write_all(&frame).await.unwrap();
+}
+
+/// Read a (short) WebSocket frame, returning its opcode and payload
+async fn ws_recv(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut head = [0u8; 2];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut head))
+        .await
+        .expect("timed out waiting for a frame")
+        .unwrap();
+    assert!(head[1] < 126, "unexpected frame length");
+    let mut payload = vec![0; head[1] as usize];
+    stream.read_exact(&mut payload).await.unwrap();
+    (head[0], payload)
+}
+
+async fn connect() -> TcpStream {
+    TcpStream::connect(GATEWAY).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_rest_endpoints() {
+    let key = utils::rand_alphastring(10, &mut rand::thread_rng());
+    let path = format!("/default/default/{key}");
+    // the requests share a connection (keep-alive)
+    let mut con = self::connect().await;
+    assert_eq!(
+        request(&mut con, "GET", &path, "").await,
+        (404, r#"{"code":1}"#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "PUT", &path, "hello").await,
+        (200, "1".to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "GET", &path, "").await,
+        (200, r#""hello""#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "DELETE", &path, "").await,
+        (200, "1".to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "GET", &path, "").await,
+        (404, r#"{"code":1}"#.to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_percent_encoded_key() {
+    let key = utils::rand_alphastring(10, &mut rand::thread_rng());
+    let mut con = self::connect().await;
+    assert_eq!(
+        request(
+            &mut con,
+            "PUT",
+            &format!("/default/default/{key}%2Fa%20b"),
+            "x"
+        )
+        .await,
+        (200, "1".to_owned())
+    );
+    assert_eq!(
+        request(
+            &mut con,
+            "POST",
+            "/query",
+            &format!(r#"["GET", "{key}/a b"]"#)
+        )
+        .await,
+        (200, r#""x""#.to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_query_endpoint() {
+    let mut con = self::connect().await;
+    assert_eq!(
+        request(&mut con, "POST", "/query", r#"["HEYA"]"#).await,
+        (200, r#""HEY!""#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "POST", "/query", r#"["HEYA", "sayan"]"#).await,
+        (200, r#""sayan""#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "POST", "/query", r#"["GET"]"#).await,
+        (
+            400,
+            r#"{"error":"700 arity-error: GET expects 1 argument, got 0"}"#.to_owned()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_gateway_errors() {
+    let mut con = self::connect().await;
+    assert_eq!(
+        request(&mut con, "GET", "/nokeyspace/notable/x", "").await,
+        (404, r#"{"error":"201 container-not-found"}"#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "GET", "/nope", "").await,
+        (404, r#"{"error":"unknown-endpoint"}"#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "PATCH", "/default/default/x", "").await,
+        (405, r#"{"error":"method-not-allowed"}"#.to_owned())
+    );
+    assert_eq!(
+        request(&mut con, "POST", "/query", r#"{"action": "HEYA"}"#).await,
+        (400, r#"{"error":"bad-query"}"#.to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    let mut con = self::connect().await;
+    // run a query first, so that there's something to count
+    assert_eq!(
+        request(&mut con, "POST", "/query", r#"["HEYA"]"#).await,
+        (200, r#""HEY!""#.to_owned())
+    );
+    let (status, metrics) = request(&mut con, "GET", "/metrics", "").await;
+    assert_eq!(status, 200);
+    assert!(metrics
+        .lines()
+        .any(|line| line.starts_with("skytable_queries_total{action=\"HEYA\"}")));
+    assert!(metrics
+        .lines()
+        .any(|line| line == "# TYPE skytable_memory_bytes gauge"));
+    assert_eq!(
+        request(&mut con, "POST", "/metrics", "").await,
+        (405, r#"{"error":"method-not-allowed"}"#.to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_health_endpoints() {
+    let mut con = self::connect().await;
+    assert_eq!(
+        request(&mut con, "GET", "/healthz", "").await,
+        (200, r#"{"live":true}"#.to_owned())
+    );
+    // (whether the server is ready depends on what the other tests did to it)
+    let (status, body) = request(&mut con, "GET", "/readyz", "").await;
+    if body.starts_with(r#"{"ready":true,"#) {
+        assert_eq!(status, 200);
+    } else {
+        assert!(body.starts_with(r#"{"ready":false,"#));
+        assert_eq!(status, 503);
+    }
+    assert_eq!(
+        request(&mut con, "POST", "/readyz", "").await,
+        (405, r#"{"error":"method-not-allowed"}"#.to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_request_closes_connection() {
+    let mut con = self::connect().await;
+    con.write_all(b"GET /query\r\n\r\n").await.unwrap();
+    assert_eq!(
+        read_response(&mut con).await,
+        (400, r#"{"error":"bad-request"}"#.to_owned())
+    );
+    let mut buf = [0u8; 1];
+    assert_eq!(con.read(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_grpc_web() {
+    let key = utils::rand_alphastring(10, &mut rand::thread_rng());
+    let mut con = self::connect().await;
+    assert_eq!(
+        grpc_call(&mut con, "Get", &[&key]).await,
+        // `Response { code: 1 }`
+        (200, grpc_frames(b"\x20\x01"))
+    );
+    assert_eq!(
+        grpc_call(&mut con, "Query", &["SET", &key, "100"]).await,
+        (200, grpc_frames(b"\x20\x00"))
+    );
+    assert_eq!(
+        grpc_call(&mut con, "Get", &[&key]).await,
+        // `Response { blob: "100" }`
+        (200, grpc_frames(b"\x0a\x03100"))
+    );
+    assert_eq!(
+        grpc_call(&mut con, "Subscribe", &["chan"]).await,
+        (
+            200,
+            b"\x80\0\0\0\x2agrpc-status:12\r\ngrpc-message:unknown-rpc\r\n".to_vec()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_tunnel() {
+    let mut con = self::connect().await;
+    con.write_all(
+        b"GET /skyhash HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+        Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+        Sec-WebSocket-Version: 13\r\n\r\n",
+    )
+    .await
+    .unwrap();
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(con.read_u8().await.unwrap());
+    }
+    let head = String::from_utf8(head).unwrap();
+    assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+    assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    // a query split across two messages
+    ws_send(&mut con, 0x2, b"*1\n4\nhe").await;
+    ws_send(&mut con, 0x2, b"ya").await;
+    assert_eq!(ws_recv(&mut con).await, (0x82, b"*+4\nHEY!".to_vec()));
+    // pings are answered
+    ws_send(&mut con, 0x9, b"ping").await;
+    assert_eq!(ws_recv(&mut con).await, (0x8A, b"ping".to_vec()));
+    // and the close code is echoed back
+    ws_send(&mut con, 0x8, &1000u16.to_be_bytes()).await;
+    assert_eq!(
+        ws_recv(&mut con).await,
+        (0x88, 1000u16.to_be_bytes().to_vec())
+    );
+}
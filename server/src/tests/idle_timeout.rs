@@ -0,0 +1,63 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the idle timeout (which is set to 5 seconds on the third test server)
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{self, Duration},
+};
+
+const SERVER: &str = "127.0.0.1:2007";
+
+/// Wait for the server to close the connection
+async fn assert_closed_within(stream: &mut TcpStream, secs: u64) {
+    let mut buf = [0u8; 16];
+    let read = time::timeout(Duration::from_secs(secs), stream.read(&mut buf))
+        .await
+        .expect("the idle connection wasn't closed");
+    assert_eq!(read.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_idle_connection_is_closed() {
+    let mut con = TcpStream::connect(SERVER).await.unwrap();
+    assert_closed_within(&mut con, 15).await;
+}
+
+#[tokio::test]
+async fn test_activity_resets_idle_timeout() {
+    let mut con = TcpStream::connect(SERVER).await.unwrap();
+    for _ in 0..3 {
+        time::sleep(Duration::from_secs(3)).await;
+        con.write_all(b"*1\n4\nheya").await.unwrap();
+        let mut response = [0u8; 8];
+        con.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"*+4\nHEY!");
+    }
+    assert_closed_within(&mut con, 15).await;
+}
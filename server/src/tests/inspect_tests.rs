@@ -24,11 +24,13 @@
  *
 */
 
-const TABLE_DECL_KM_STR_STR_VOLATILE: &str = "Keymap { data:(str,str), volatile:true }";
-
 #[sky_macros::dbtest_module]
 mod __private {
-    use skytable::{types::Array, Element, RespCode};
+    use skytable::{
+        query,
+        types::{Array, FlatElement},
+        Element, RespCode,
+    };
     async fn test_inspect_keyspaces() {
         query.push("INSPECT SPACES");
         assert!(matches!(
@@ -40,56 +42,74 @@ mod __private {
         query.push(format!("INSPECT SPACE {__MYKS__}"));
         assert!(matches!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::Array(Array::NonNullStr(_))
+            Element::Array(Array::Recursive(_))
         ))
     }
     async fn test_inspect_current_keyspace() {
         query.push("INSPECT SPACE");
-        let ret: Vec<String> = con.run_query(&query).await.unwrap();
-        assert!(ret.contains(&__MYTABLE__));
+        let models = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::Recursive(models)) => models,
+            other => panic!("Bad response for inspect space: {:?}", other),
+        };
+        let name = FlatElement::String(__MYTABLE__.clone());
+        assert!(models.iter().any(|model| match model {
+            Element::Array(Array::Flat(fields)) => {
+                fields[0] == FlatElement::String("name".to_owned()) && fields[1] == name
+            }
+            _ => false,
+        }));
     }
     async fn test_inspect_table() {
-        query.push(format!("INSPECT MODEL {__MYTABLE__}"));
-        match con.run_query_raw(&query).await.unwrap() {
-            Element::String(st) => {
-                assert_eq!(st, TABLE_DECL_KM_STR_STR_VOLATILE.to_owned())
-            }
-            _ => panic!("Bad response for inspect table"),
-        }
+        assert_model_decl!(con, __MYTABLE__, "(str,str)", true);
     }
     async fn test_inspect_current_table() {
-        query.push("INSPECT MODEL");
-        let ret: String = con.run_query(&query).await.unwrap();
-        assert_eq!(ret, TABLE_DECL_KM_STR_STR_VOLATILE);
+        assert_model_decl!(con, "", "(str,str)", true);
     }
     async fn test_inspect_table_fully_qualified_entity() {
+        assert_model_decl!(con, __MYENTITY__, "(str,str)", true);
+    }
+    async fn test_inspect_table_stats() {
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
         query.push(format!("INSPECT MODEL {__MYENTITY__}"));
-        match con.run_query_raw(&query).await.unwrap() {
-            Element::String(st) => {
-                assert_eq!(st, TABLE_DECL_KM_STR_STR_VOLATILE.to_owned())
-            }
-            _ => panic!("Bad response for inspect table"),
-        }
+        let fields = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for inspect model: {:?}", other),
+        };
+        assert_eq!(
+            &fields[6..8],
+            &[
+                FlatElement::String("entries".to_owned()),
+                FlatElement::UnsignedInt(1)
+            ]
+        );
+        assert_eq!(fields[8], FlatElement::String("memory".to_owned()));
+        assert!(matches!(fields[9], FlatElement::UnsignedInt(mem) if mem > 0));
+        assert_eq!(fields[10], FlatElement::String("created".to_owned()));
+        assert!(matches!(fields[11], FlatElement::UnsignedInt(created) if created > 0));
     }
     async fn test_inspect_keyspaces_syntax_error() {
         query.push("INSPECT SPACES iowjfjofoe");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ErrorString("bql-invalid-syntax".into()))
+            Element::RespCode(RespCode::ErrorString("604 bql-invalid-syntax".into()))
         );
     }
     async fn test_inspect_keyspace_syntax_error() {
         query.push("INSPECT SPACE ijfwijifwjo oijfwirfjwo");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ErrorString("bql-invalid-syntax".into()))
+            Element::RespCode(RespCode::ErrorString("604 bql-invalid-syntax".into()))
         );
     }
     async fn test_inspect_table_syntax_error() {
         query.push("INSPECT MODEL ijfwijifwjo oijfwirfjwo");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ErrorString("bql-invalid-syntax".into()))
+            Element::RespCode(RespCode::ErrorString("604 bql-invalid-syntax".into()))
         );
     }
 }
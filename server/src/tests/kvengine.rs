@@ -50,6 +50,13 @@ mod __private {
         assert_eq!(resp, Element::String("sayan".to_owned()));
     }
 
+    /// Action names are matched regardless of their case
+    async fn test_heya_any_case() {
+        query.push("hEyA");
+        let resp = con.run_query_raw(&query).await.unwrap();
+        assert_eq!(resp, Element::String("HEY!".to_owned()));
+    }
+
     /// Test a GET query: for a non-existing key
     async fn test_get_single_nil() {
         query.push("get");
@@ -72,12 +79,27 @@ mod __private {
         assert_eq!(resp, Element::String("100".to_owned()));
     }
 
+    /// Test a GET query for a value that's large enough to skip the write buffer
+    async fn test_get_single_large() {
+        let value = "x".repeat(1 << 20);
+        query.push("set");
+        query.push("x");
+        query.push(&value);
+        let resp = con.run_query_raw(&query).await.unwrap();
+        assert_eq!(resp, Element::RespCode(RespCode::Okay));
+        let mut query = Query::new();
+        query.push("get");
+        query.push("x");
+        let resp = con.run_query_raw(&query).await.unwrap();
+        assert_eq!(resp, Element::String(value));
+    }
+
     /// Test a GET query with an incorrect number of arguments
     async fn test_get_syntax_error() {
         query.push("get");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("GET expects 1 argument, got 0")
         );
         let mut query = Query::new();
         query.push("get");
@@ -85,7 +107,7 @@ mod __private {
         query.push("y");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("GET expects 1 argument, got 2")
         );
     }
 
@@ -127,7 +149,7 @@ mod __private {
         query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("SET expects 2 to 5 arguments, got 1")
         );
         let mut query = Query::new();
         query.push("set");
@@ -178,7 +200,7 @@ mod __private {
         query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("UPDATE expects 2 arguments, got 1")
         );
         let mut query = Query::new();
         query.push("update");
@@ -187,562 +209,557 @@ mod __private {
         query.push("z");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("UPDATE expects 2 arguments, got 3")
         );
     }
 
-    /// Test a DEL query: which should return int 0
-    async fn test_del_single_zero() {
-        query.push("del");
+    /// Test a CAS query: which should swap the value since it matches
+    async fn test_cas_okay() {
+        query.push("set");
         query.push("x");
+        query.push("100");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(0)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test a DEL query: which should return int 1
-    async fn test_del_single_one() {
-        // first set the key
-        query.push("set");
+        let mut query = Query::new();
+        query.push("cas");
         query.push("x");
         query.push("100");
+        query.push("200");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now delete it
         let mut query = Query::new();
-        query.push("del");
+        query.push("get");
         query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(1)
+            Element::String("200".to_owned())
         );
     }
 
-    /// Test a DEL query: which should return the number of keys deleted
-    async fn test_del_multiple() {
-        // first set the keys
-        query.push("mset");
+    /// Test a CAS query: which should leave the value untouched since it doesn't match
+    async fn test_cas_mismatch() {
+        query.push("set");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::Okay)
         );
-        // now delete them
         let mut query = Query::new();
-        query.push("del");
+        query.push("cas");
         query.push("x");
-        query.push("y");
-        query.push("z");
+        query.push("150");
+        query.push("200");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::ErrorString("305 cas-mismatch".to_owned()))
         );
-    }
-
-    /// Test a DEL query with an incorrect number of arguments
-    async fn test_del_syntax_error() {
-        query.push("del");
+        let mut query = Query::new();
+        query.push("get");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::String("100".to_owned())
         );
     }
 
-    /// Test an EXISTS query
-    async fn test_exists_multiple() {
-        // first set the keys
-        query.push("mset");
+    /// Test a CAS query: which should return code: 1
+    async fn test_cas_nil() {
+        query.push("cas");
         query.push("x");
         query.push("100");
-        query.push("y");
         query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::NotFound)
         );
-        // now check if they exist
-        let mut query = Query::new();
-        query.push("exists");
+    }
+
+    async fn test_cas_syntax_error() {
+        query.push("cas");
         query.push("x");
-        query.push("y");
-        query.push("z");
-        query.push("a");
+        query.push("100");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            arity_err!("CAS expects 3 arguments, got 2")
         );
     }
 
-    /// Test an EXISTS query with an incorrect number of arguments
-    async fn test_exists_syntax_error() {
-        query.push("exists");
+    async fn test_getset_okay() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test an MGET query on a single existing key
-    async fn test_mget_multiple_okay() {
-        // first set the keys
-        query.push("mset");
+        let mut query = Query::new();
+        query.push("getset");
         query.push("x");
-        query.push("100");
-        query.push("y");
         query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::String("100".to_owned())
         );
-        // now get them
         let mut query = Query::new();
-        query.push("mget");
+        query.push("get");
         query.push("x");
-        query.push("y");
-        query.push("z");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::Array(Array::Str(vec![
-                Some("100".to_owned()),
-                Some("200".to_owned()),
-                Some("300".to_owned())
-            ]))
+            Element::String("200".to_owned())
         );
     }
 
-    /// Test an MGET query with different outcomes
-    async fn test_mget_multiple_mixed() {
-        // first set the keys
-        query.push("mset");
+    /// Test a GETSET query on a key that doesn't exist: the key is set and code: 1 is returned
+    async fn test_getset_nil() {
+        query.push("getset");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::NotFound)
         );
         let mut query = Query::new();
-        query.push("mget");
+        query.push("get");
         query.push("x");
-        query.push("y");
-        query.push("a");
-        query.push("z");
-        query.push("b");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::Array(Array::Str(vec![
-                Some("100".to_owned()),
-                Some("200".to_owned()),
-                None,
-                Some("300".to_owned()),
-                None
-            ]))
+            Element::String("100".to_owned())
         );
     }
 
-    /// Test an MGET query with an incorrect number of arguments
-    async fn test_mget_syntax_error() {
-        query.push("mget");
+    async fn test_getset_syntax_error() {
+        query.push("getset");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("GETSET expects 2 arguments, got 1")
         );
     }
 
-    /// Test an MSET query with a single non-existing key
-    async fn test_mset_single_okay() {
-        // first set the keys
-        query.push("mset");
+    async fn test_getdel_okay() {
+        query.push("set");
         query.push("x");
         query.push("100");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(1)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test an MSET query with non-existing keys
-    async fn test_mset_multiple_okay() {
-        // first set the keys
-        query.push("mset");
+        let mut query = Query::new();
+        query.push("getdel");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::String("100".to_owned())
+        );
+        let mut query = Query::new();
+        query.push("getdel");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
         );
     }
 
-    /// Test an MSET query with a mixed set of outcomes
-    async fn test_mset_multiple_mixed() {
-        // first set the keys
-        query.push("mset");
+    async fn test_getrange_okay() {
+        query.push("set");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
+        query.push("hello world");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::Okay)
         );
-        // now try to set them again with just another new key
         let mut query = Query::new();
-        query.push("mset");
+        query.push("getrange");
         query.push("x");
+        query.push("6");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
-        query.push("a");
-        query.push("apple");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(1)
+            Element::String("world".to_owned())
         );
     }
 
-    /// Test an MSET query with the wrong number of arguments
-    async fn test_mset_syntax_error_args_one() {
-        query.push("mset");
+    async fn test_getrange_nil() {
+        query.push("getrange");
+        query.push("x");
+        query.push("0");
+        query.push("1");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::NotFound)
         );
     }
-    async fn test_mset_syntax_error_args_three() {
-        query.push("mset");
+
+    async fn test_jget_wrong_model() {
+        query.push("jget");
         query.push("x");
-        query.push("y");
-        query.push("z");
+        query.push("$");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
         );
     }
 
-    /// Test an MUPDATE query with a single non-existing key
-    async fn test_mupdate_single_okay() {
-        // first set the key
-        query.push("mset");
+    async fn test_getrange_bad_offset() {
+        query.push("getrange");
         query.push("x");
-        query.push("100");
+        query.push("-1");
+        query.push("1");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(1)
+            Element::RespCode(RespCode::Wrongtype)
         );
-        // now attempt to update it
-        // first set the keys
-        let mut query = Query::new();
-        query.push("mupdate");
+    }
+
+    async fn test_setrange_okay() {
+        query.push("set");
         query.push("x");
-        query.push("200");
+        query.push("hello world");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(1)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test an MUPDATE query with a mixed set of outcomes
-    async fn test_mupdate_multiple_mixed() {
-        // first set the keys
-        query.push("mset");
+        let mut query = Query::new();
+        query.push("setrange");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
+        query.push("6");
+        query.push("everyone");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::UnsignedInt(14)
         );
-        // now try to update them with just another new key
         let mut query = Query::new();
-        query.push("mupdate");
+        query.push("get");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
-        query.push("a");
-        query.push("apple");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::String("hello everyone".to_owned())
         );
     }
 
-    /// Test an MUPDATE query with the wrong number of arguments
-    async fn test_mupdate_syntax_error_args_one() {
-        query.push("mupdate");
+    async fn test_setrange_out_of_range() {
+        query.push("set");
+        query.push("x");
+        query.push("hello");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("setrange");
+        query.push("x");
+        query.push("6");
+        query.push("world");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
         );
     }
 
-    async fn test_mupdate_syntax_error_args_three() {
-        query.push("mupdate");
+    async fn test_setrange_nil() {
+        query.push("setrange");
         query.push("x");
-        query.push("y");
-        query.push("z");
+        query.push("0");
+        query.push("hello");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::NotFound)
         );
     }
 
-    /// Test an SSET query: which should return code: 0
-    async fn test_sset_single_okay() {
-        // first set the keys
-        query.push("sset");
+    async fn test_range_syntax_error() {
+        query.push("getrange");
         query.push("x");
-        query.push("100");
+        query.push("0");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            arity_err!("GETRANGE expects 3 arguments, got 2")
+        );
+        let mut query = Query::new();
+        query.push("setrange");
+        query.push("x");
+        query.push("0");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SETRANGE expects 3 arguments, got 2")
         );
     }
 
-    /// Test an SSET query: which should return code: 2
-    async fn test_sset_single_overwrite_error() {
-        // first set the keys
-        query.push("set");
+    async fn test_append_okay() {
+        query.push("append");
         query.push("x");
-        query.push("100");
+        query.push("hello");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(5)
         );
-        // now attempt to overwrite it
         let mut query = Query::new();
-        query.push("sset");
+        query.push("append");
         query.push("x");
-        query.push("100");
+        query.push(" world");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::OverwriteError)
+            Element::UnsignedInt(11)
+        );
+        let mut query = Query::new();
+        query.push("get");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::String("hello world".to_owned())
         );
     }
 
-    /// Test an SSET query: which should return code: 0
-    async fn test_sset_multiple_okay() {
-        // first set the keys
-        query.push("sset");
+    async fn test_append_syntax_error() {
+        query.push("append");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            arity_err!("APPEND expects 2 arguments, got 1")
         );
     }
 
-    /// Test an SSET query: which should return code: 2
-    async fn test_sset_multiple_overwrite_error() {
-        // first set the keys
-        query.push("sset");
+    async fn test_bits_okay() {
+        query.push("setbit");
         query.push("x");
-        query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
+        query.push("1");
+        query.push("1");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(0)
         );
-        // now attempt to sset again with just one new extra key
+        // 0b0100_0000 is an `@`
         let mut query = Query::new();
-        query.push("sset");
+        query.push("get");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::String("@".to_owned())
+        );
+        let mut query = Query::new();
+        query.push("getbit");
+        query.push("x");
+        query.push("1");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
+        );
+        let mut query = Query::new();
+        query.push("getbit");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("b");
-        query.push("bananas");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::OverwriteError)
+            Element::UnsignedInt(0)
         );
-    }
-
-    /// Test an SSET query with the wrong number of arguments
-    async fn test_sset_syntax_error_args_one() {
-        query.push("sset");
+        let mut query = Query::new();
+        query.push("bitcount");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::UnsignedInt(1)
         );
     }
 
-    async fn test_sset_syntax_error_args_three() {
-        query.push("sset");
+    async fn test_bits_nil() {
+        query.push("getbit");
         query.push("x");
-        query.push("y");
-        query.push("z");
+        query.push("0");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::NotFound)
+        );
+        let mut query = Query::new();
+        query.push("bitcount");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
         );
     }
 
-    /// Test an SUPDATE query: which should return code: 0
-    async fn test_supdate_single_okay() {
-        // set the key
-        query.push("sset");
+    async fn test_setbit_bad_args() {
+        query.push("setbit");
         query.push("x");
-        query.push("100");
+        query.push("0");
+        query.push("2");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::RespCode(RespCode::Wrongtype)
         );
-        // update it
         let mut query = Query::new();
-        query.push("supdate");
+        query.push("setbit");
         query.push("x");
-        query.push("200");
+        query.push("4294967296");
+        query.push("1");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+        let mut query = Query::new();
+        query.push("setbit");
+        query.push("x");
+        query.push("0");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SETBIT expects 3 arguments, got 2")
         );
     }
 
-    /// Test an SUPDATE query: which should return code: 1
-    async fn test_supdate_single_nil() {
-        query.push("supdate");
+    /// Test a DEL query: which should return int 0
+    async fn test_del_single_zero() {
+        query.push("del");
         query.push("x");
-        query.push("200");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::NotFound)
+            Element::UnsignedInt(0)
         );
     }
 
-    /// Test an SUPDATE query: which should return code: 0
-    async fn test_supdate_multiple_okay() {
-        // first set the keys
-        query.push("sset");
+    /// Test a DEL query: which should return int 1
+    async fn test_del_single_one() {
+        // first set the key
+        query.push("set");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now update all of them
+        // now delete it
         let mut query = Query::new();
-        query.push("supdate");
+        query.push("del");
         query.push("x");
-        query.push("200");
-        query.push("y");
-        query.push("300");
-        query.push("z");
-        query.push("400");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(1)
         );
     }
 
-    async fn test_supdate_multiple_nil() {
-        // no keys exist, so we get a nil
-        query.push("supdate");
+    /// Test a DEL query: which should return the number of keys deleted
+    async fn test_del_multiple() {
+        // first set the keys
+        query.push("mset");
         query.push("x");
-        query.push("200");
+        query.push("100");
         query.push("y");
+        query.push("200");
+        query.push("z");
         query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        // now delete them
+        let mut query = Query::new();
+        query.push("del");
+        query.push("x");
+        query.push("y");
         query.push("z");
-        query.push("400");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::NotFound)
+            Element::UnsignedInt(3)
         );
     }
 
-    /// Test an SUPDATE query with the wrong number of arguments
-    async fn test_supdate_syntax_error_args_one() {
-        query.push("mupdate");
+    /// Test a DEL query with an incorrect number of arguments
+    async fn test_del_syntax_error() {
+        query.push("del");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("DEL expects at least 1 argument, got 0")
         );
     }
 
-    async fn test_supdate_syntax_error_args_three() {
-        query.push("mupdate");
-        query.push("x");
-        query.push("y");
-        query.push("z");
+    /// Test a DELPREFIX query: which should return the number of keys deleted
+    async fn test_delprefix() {
+        query.push("mset");
+        query.push("user:1");
+        query.push("a");
+        query.push("user:2");
+        query.push("b");
+        query.push("session:1");
+        query.push("c");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::UnsignedInt(3)
+        );
+        let mut query = Query::new();
+        query.push("delprefix");
+        query.push("user:");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(2)
+        );
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
         );
     }
 
-    /// Test an SDEL query: which should return nil
-    async fn test_sdel_single_nil() {
-        query.push("sdel");
-        query.push("x");
+    /// Test a DELPREFIX query with an incorrect number of arguments or an empty prefix
+    async fn test_delprefix_syntax_error() {
+        query.push("delprefix");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::NotFound)
+            arity_err!("DELPREFIX expects 1 argument, got 0")
+        );
+        let mut query = Query::new();
+        query.push("delprefix");
+        query.push("");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ActionError)
         );
     }
 
-    /// Test an SDEL query: which should return okay
-    async fn test_sdel_single_okay() {
-        query.push("sset");
+    /// Test an EXISTS query
+    async fn test_exists_multiple() {
+        // first set the keys
+        query.push("mset");
         query.push("x");
         query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(3)
         );
+        // now check if they exist
         let mut query = Query::new();
-        query.push("sdel");
+        query.push("exists");
         query.push("x");
+        query.push("y");
+        query.push("z");
+        query.push("a");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(3)
         );
     }
 
-    /// Test an SDEL query: which should return okay
-    async fn test_sdel_multiple_okay() {
+    /// Test an EXISTS query with an incorrect number of arguments
+    async fn test_exists_syntax_error() {
+        query.push("exists");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("EXISTS expects at least 1 argument, got 0")
+        );
+    }
+
+    /// Test an MGET query on a single existing key
+    async fn test_mget_multiple_okay() {
         // first set the keys
-        query.push("sset");
+        query.push("mset");
         query.push("x");
         query.push("100");
         query.push("y");
@@ -751,242 +768,1181 @@ mod __private {
         query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(3)
         );
-        // now delete them
+        // now get them
         let mut query = Query::new();
-        query.push("sdel");
+        query.push("mget");
         query.push("x");
         query.push("y");
         query.push("z");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("200".to_owned()),
+                Some("300".to_owned())
+            ]))
         );
     }
 
-    async fn test_sdel_multiple_nil() {
-        query.push("sdel");
+    /// Test an MGET query where large values sit between small ones
+    async fn test_mget_multiple_large() {
+        let value = "y".repeat(1 << 20);
+        query.push("mset");
         query.push("x");
+        query.push("100");
         query.push("y");
+        query.push(&value);
         query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        let mut query = Query::new();
+        query.push("mget");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some(value),
+                Some("300".to_owned())
+            ]))
+        );
+    }
+
+    /// Test an MGET query with different outcomes
+    async fn test_mget_multiple_mixed() {
+        // first set the keys
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        let mut query = Query::new();
+        query.push("mget");
+        query.push("x");
+        query.push("y");
+        query.push("a");
+        query.push("z");
+        query.push("b");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("200".to_owned()),
+                None,
+                Some("300".to_owned()),
+                None
+            ]))
+        );
+    }
+
+    /// Test an MGET query with qualified keys
+    async fn test_mget_qualified() {
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y:z");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(2)
+        );
+        let mut query = Query::new();
+        query.push("mget");
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:x"));
+        query.push("y:z");
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:y:z"));
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:a"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("200".to_owned()),
+                Some("200".to_owned()),
+                None
+            ]))
+        );
+        let mut query = Query::new();
+        query.push("mget");
+        query.push("x");
+        query.push("nosuchks:nosuchtbl:x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+
+    /// Test an MGET query with an incorrect number of arguments
+    async fn test_mget_syntax_error() {
+        query.push("mget");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MGET expects at least 1 argument, got 0")
+        );
+    }
+
+    /// Test an MSET query with a single non-existing key
+    async fn test_mset_single_okay() {
+        // first set the keys
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
+        );
+    }
+
+    /// Test an MSET query with non-existing keys
+    async fn test_mset_multiple_okay() {
+        // first set the keys
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+    }
+
+    /// Test an MSET query with a mixed set of outcomes
+    async fn test_mset_multiple_mixed() {
+        // first set the keys
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        // now try to set them again with just another new key
+        let mut query = Query::new();
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        query.push("a");
+        query.push("apple");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
+        );
+    }
+
+    /// Test an MSET query with the wrong number of arguments
+    async fn test_mset_syntax_error_args_one() {
+        query.push("mset");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MSET expects 2, 4, 6, ... arguments, got 0")
+        );
+    }
+    async fn test_mset_syntax_error_args_three() {
+        query.push("mset");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MSET expects 2, 4, 6, ... arguments, got 3")
+        );
+    }
+
+    async fn test_msetex_mixed() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("msetex");
+        query.push("100");
+        query.push("x");
+        query.push("200");
+        query.push("y");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::NonNullStr(vec!["2".to_owned(), "0".to_owned()]))
+        );
+        let mut query = Query::new();
+        query.push("mget");
+        query.push("x");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("200".to_owned())
+            ]))
+        );
+        let mut query = Query::new();
+        query.push("ttl");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(100)
+        );
+    }
+    async fn test_msetex_bad_ttl() {
+        query.push("msetex");
+        query.push("abc");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_msetex_syntax_error() {
+        query.push("msetex");
+        query.push("100");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MSETEX expects 3, 5, 7, ... arguments, got 2")
+        );
+    }
+    async fn test_setmany_getmany() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("setmany");
+        query.push("x");
+        query.push("200");
+        query.push("y");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::NonNullStr(vec!["2".to_owned(), "0".to_owned()]))
+        );
+        let mut query = Query::new();
+        query.push("getmany");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::Str(vec![
+                Some("x".to_owned()),
+                Some("100".to_owned()),
+                Some("y".to_owned()),
+                Some("200".to_owned()),
+                Some("z".to_owned()),
+                None
+            ]))
+        );
+    }
+    async fn test_setmany_getmany_syntax_error() {
+        query.push("setmany");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SETMANY expects 2, 4, 6, ... arguments, got 1")
+        );
+        let query = Query::from("getmany");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("GETMANY expects at least 1 argument, got 0")
+        );
+    }
+
+    /// Test an MUPDATE query with a single non-existing key
+    async fn test_mupdate_single_okay() {
+        // first set the key
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
+        );
+        // now attempt to update it
+        // first set the keys
+        let mut query = Query::new();
+        query.push("mupdate");
+        query.push("x");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(1)
+        );
+    }
+
+    /// Test an MUPDATE query with a mixed set of outcomes
+    async fn test_mupdate_multiple_mixed() {
+        // first set the keys
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        // now try to update them with just another new key
+        let mut query = Query::new();
+        query.push("mupdate");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        query.push("a");
+        query.push("apple");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+    }
+
+    /// Test an MUPDATE query with the wrong number of arguments
+    async fn test_mupdate_syntax_error_args_one() {
+        query.push("mupdate");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MUPDATE expects 2, 4, 6, ... arguments, got 0")
+        );
+    }
+
+    async fn test_mupdate_syntax_error_args_three() {
+        query.push("mupdate");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MUPDATE expects 2, 4, 6, ... arguments, got 3")
+        );
+    }
+
+    /// Test an SSET query: which should return code: 0
+    async fn test_sset_single_okay() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    /// Test an SSET query: which should return code: 2
+    async fn test_sset_single_overwrite_error() {
+        // first set the keys
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now attempt to overwrite it
+        let mut query = Query::new();
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::OverwriteError)
+        );
+    }
+
+    /// Test an SSET query: which should return code: 0
+    async fn test_sset_multiple_okay() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    /// Test an SSET query: which should return code: 2
+    async fn test_sset_multiple_overwrite_error() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now attempt to sset again with just one new extra key
+        let mut query = Query::new();
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("b");
+        query.push("bananas");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::OverwriteError)
+        );
+    }
+
+    /// Test an SSET query with the wrong number of arguments
+    async fn test_sset_syntax_error_args_one() {
+        query.push("sset");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SSET expects 2, 4, 6, ... arguments, got 0")
+        );
+    }
+
+    async fn test_sset_syntax_error_args_three() {
+        query.push("sset");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SSET expects 2, 4, 6, ... arguments, got 3")
+        );
+    }
+
+    /// Test an SUPDATE query: which should return code: 0
+    async fn test_supdate_single_okay() {
+        // set the key
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // update it
+        let mut query = Query::new();
+        query.push("supdate");
+        query.push("x");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    /// Test an SUPDATE query: which should return code: 1
+    async fn test_supdate_single_nil() {
+        query.push("supdate");
+        query.push("x");
+        query.push("200");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+
+    /// Test an SUPDATE query: which should return code: 0
+    async fn test_supdate_multiple_okay() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now update all of them
+        let mut query = Query::new();
+        query.push("supdate");
+        query.push("x");
+        query.push("200");
+        query.push("y");
+        query.push("300");
+        query.push("z");
+        query.push("400");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    async fn test_supdate_multiple_nil() {
+        // no keys exist, so we get a nil
+        query.push("supdate");
+        query.push("x");
+        query.push("200");
+        query.push("y");
+        query.push("300");
+        query.push("z");
+        query.push("400");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+
+    /// Test an SUPDATE query with the wrong number of arguments
+    async fn test_supdate_syntax_error_args_one() {
+        query.push("mupdate");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MUPDATE expects 2, 4, 6, ... arguments, got 0")
+        );
+    }
+
+    async fn test_supdate_syntax_error_args_three() {
+        query.push("mupdate");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MUPDATE expects 2, 4, 6, ... arguments, got 3")
+        );
+    }
+
+    /// Test an SDEL query: which should return nil
+    async fn test_sdel_single_nil() {
+        query.push("sdel");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+
+    /// Test an SDEL query: which should return okay
+    async fn test_sdel_single_okay() {
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("sdel");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    /// Test an SDEL query: which should return okay
+    async fn test_sdel_multiple_okay() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now delete them
+        let mut query = Query::new();
+        query.push("sdel");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+
+    async fn test_sdel_multiple_nil() {
+        query.push("sdel");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+
+    /// Test an SDEL query with an incorrect number of arguments
+    async fn test_sdel_syntax_error() {
+        query.push("sdel");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SDEL expects at least 1 argument, got 0")
+        );
+    }
+
+    /// Test a `DBSIZE` query
+    async fn test_dbsize() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now check the size
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        let mut query = Query::new();
+        query.push("dbsize");
+        query.push(__MYENTITY__);
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+    }
+
+    /// Test `DBSIZE` with an incorrect number of arguments
+    async fn test_dbsize_syntax_error() {
+        query.push("dbsize");
+        query.push("iroegjoeijgor");
+        query.push("roigjoigjj094");
+        query.push("ioewjforfifrj");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("DBSIZE expects at most 1 argument, got 3")
+        );
+    }
+
+    /// Test `MEMUSAGE`
+    async fn test_memusage() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("memusage");
+        query.push("x");
+        let small = match con.run_query_raw(&query).await.unwrap() {
+            Element::UnsignedInt(usage) => usage,
+            other => panic!("Bad response for memusage: {:?}", other),
+        };
+        // the key and the value take up at least 4 bytes
+        assert!(small >= 4);
+        let mut query = Query::new();
+        query.push("set");
+        query.push("y");
+        query.push("a".repeat(1000));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("memusage");
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:y"));
+        assert!(matches!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(usage) if usage >= small + 999
+        ));
+    }
+
+    /// Test `MEMUSAGE` for a key that doesn't exist
+    async fn test_memusage_nil() {
+        query.push("memusage");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::NotFound)
         );
     }
 
-    /// Test an SDEL query with an incorrect number of arguments
-    async fn test_sdel_syntax_error() {
-        query.push("sdel");
+    /// Test `MEMUSAGE` with an incorrect number of arguments
+    async fn test_memusage_syntax_error() {
+        query.push("memusage");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("MEMUSAGE expects 1 argument, got 0")
+        );
+    }
+
+    /// Test `FLUSHDB`
+    async fn test_flushdb_okay() {
+        // first set the keys
+        query.push("sset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now flush the database
+        let mut query = Query::new();
+        query.push("flushdb");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now check the size
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(0)
+        );
+    }
+
+    async fn test_flushdb_fqe() {
+        setkeys!(
+            con,
+            "w":"000",
+            "x":"100",
+            "y":"200",
+            "z":"300"
+        );
+        // now flush the database
+        let mut query = Query::new();
+        query.push("flushdb");
+        // add the fqe
+        query.push(__MYENTITY__);
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now check the size
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(0)
+        );
+    }
+
+    /// Test `FLUSHDB` with an incorrect number of arguments
+    async fn test_flushdb_syntax_error() {
+        query.push("flushdb");
+        query.push("x");
+        query.push("y");
+        query.push("z");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("FLUSHDB expects at most 1 argument, got 3")
+        );
+    }
+
+    /// Test `FLUSHTABLE`, which should retain the table and its model
+    async fn test_flushtable_okay() {
+        setkeys!(
+            con,
+            "x":"100",
+            "y":"200"
+        );
+        let mut query = Query::new();
+        query.push("flushtable");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(0)
+        );
+        // the table is still usable
+        let mut query = Query::new();
+        query.push("set");
+        query.push("x");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("type");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::NonNullStr(vec![
+                "2".to_owned(),
+                "Keymap { data:(str,str), volatile:true }".to_owned()
+            ]))
+        );
+    }
+
+    async fn test_flushtable_fqe() {
+        setkeys!(
+            con,
+            "x":"100",
+            "y":"200"
+        );
+        let mut query = Query::new();
+        query.push("flushtable");
+        query.push(__MYENTITY__);
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("dbsize");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(0)
+        );
+        let mut query = Query::new();
+        query.push("flushtable");
+        query.push("nosuchks.nosuchtbl");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+
+    /// Test `USET` which returns okay
+    ///
+    /// `USET` almost always returns okay for the correct number of key(s)/value(s)
+    async fn test_uset_all_okay() {
+        query.push("uset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+        // now that the keys already exist, do it all over again
+        let mut query = Query::new();
+        query.push("uset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(3)
+        );
+    }
+
+    /// Test `USET` with an incorrect number of arguments
+    async fn test_uset_syntax_error_args_one() {
+        query.push("uset");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("USET expects 2, 4, 6, ... arguments, got 0")
+        );
+    }
+
+    async fn test_uset_syntax_error_args_three() {
+        query.push("uset");
+        query.push("one");
+        query.push("two");
+        query.push("three");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("USET expects 2, 4, 6, ... arguments, got 3")
+        );
+    }
+
+    /// Test `KEYLEN`
+    async fn test_keylen() {
+        // first set the key
+        query.push("set");
+        query.push("x");
+        query.push("helloworld");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        // now check for the length
+        let mut query = Query::new();
+        query.push("keylen");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(10)
+        );
+    }
+
+    /// Test `KEYLEN` with an incorrect number of arguments
+    async fn test_keylen_syntax_error_args_one() {
+        query.push("keylen");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("KEYLEN expects 1 argument, got 0")
+        );
+    }
+    async fn test_keylen_syntax_error_args_two() {
+        query.push("keylen");
+        query.push("x");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("KEYLEN expects 1 argument, got 2")
+        );
+    }
+    async fn test_strlen() {
+        query.push("set");
+        query.push("x");
+        query.push("helloworld");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("strlen");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::UnsignedInt(10)
         );
     }
-
-    /// Test a `DBSIZE` query
-    async fn test_dbsize() {
-        // first set the keys
-        query.push("sset");
+    async fn test_type_okay() {
+        query.push("set");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now check the size
         let mut query = Query::new();
-        query.push("dbsize");
+        query.push("type");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::Array(Array::NonNullStr(vec![
+                "2".to_owned(),
+                "Keymap { data:(str,str), volatile:true }".to_owned()
+            ]))
         );
-        let mut query = Query::new();
-        query.push("dbsize");
-        query.push(__MYENTITY__);
+    }
+    async fn test_type_nil() {
+        query.push("type");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::NotFound)
         );
     }
-
-    /// Test `DBSIZE` with an incorrect number of arguments
-    async fn test_dbsize_syntax_error() {
-        query.push("dbsize");
-        query.push("iroegjoeijgor");
-        query.push("roigjoigjj094");
-        query.push("ioewjforfifrj");
+    async fn test_type_syntax_error() {
+        query.push("type");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("TYPE expects 1 argument, got 0")
         );
     }
-
-    /// Test `FLUSHDB`
-    async fn test_flushdb_okay() {
-        // first set the keys
-        query.push("sset");
+    async fn test_rename_okay() {
+        query.push("set");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now flush the database
         let mut query = Query::new();
-        query.push("flushdb");
+        query.push("rename");
+        query.push("x");
+        query.push("y");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now check the size
         let mut query = Query::new();
-        query.push("dbsize");
+        query.push("get");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::String("100".to_owned())
+        );
+        let mut query = Query::new();
+        query.push("exists");
+        query.push("x");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::UnsignedInt(0)
         );
     }
-
-    async fn test_flushdb_fqe() {
-        setkeys!(
-            con,
-            "w":"000",
-            "x":"100",
-            "y":"200",
-            "z":"300"
-        );
-        // now flush the database
-        let mut query = Query::new();
-        query.push("flushdb");
-        // add the fqe
-        query.push(__MYENTITY__);
+    async fn test_rename_overwrite_error() {
+        query.push("mset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::Okay)
+            Element::UnsignedInt(2)
         );
-        // now check the size
         let mut query = Query::new();
-        query.push("dbsize");
+        query.push("rename");
+        query.push("x");
+        query.push("y");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(0)
+            Element::RespCode(RespCode::OverwriteError)
         );
     }
-
-    /// Test `FLUSHDB` with an incorrect number of arguments
-    async fn test_flushdb_syntax_error() {
-        query.push("flushdb");
+    async fn test_rename_nil() {
+        query.push("rename");
         query.push("x");
         query.push("y");
-        query.push("z");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::NotFound)
         );
     }
-
-    /// Test `USET` which returns okay
-    ///
-    /// `USET` almost always returns okay for the correct number of key(s)/value(s)
-    async fn test_uset_all_okay() {
-        query.push("uset");
+    async fn test_rename_syntax_error() {
+        query.push("rename");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("RENAME expects 2 arguments, got 1")
+        );
+    }
+    async fn test_copy_okay() {
+        query.push("set");
         query.push("x");
         query.push("100");
-        query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::Okay)
         );
-        // now that the keys already exist, do it all over again
         let mut query = Query::new();
-        query.push("uset");
+        query.push("copy");
         query.push("x");
-        query.push("100");
         query.push("y");
-        query.push("200");
-        query.push("z");
-        query.push("300");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(3)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test `USET` with an incorrect number of arguments
-    async fn test_uset_syntax_error_args_one() {
-        query.push("uset");
+        let mut query = Query::new();
+        query.push("mget");
+        query.push("x");
+        query.push("y");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("100".to_owned())
+            ]))
         );
     }
-
-    async fn test_uset_syntax_error_args_three() {
-        query.push("uset");
-        query.push("one");
-        query.push("two");
-        query.push("three");
+    async fn test_copy_qualified() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::Okay)
         );
-    }
-
-    /// Test `KEYLEN`
-    async fn test_keylen() {
-        // first set the key
-        query.push("set");
+        let mut query = Query::new();
+        query.push("copy");
         query.push("x");
-        query.push("helloworld");
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:y"));
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::Okay)
         );
-        // now check for the length
         let mut query = Query::new();
-        query.push("keylen");
+        query.push("copy");
         query.push("x");
+        query.push(format!("{__MYKS__}:{__MYTABLE__}:y"));
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::UnsignedInt(10)
+            Element::RespCode(RespCode::OverwriteError)
+        );
+        let mut query = Query::new();
+        query.push("copy");
+        query.push("x");
+        query.push("nosuchks:nosuchtbl:y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
         );
     }
-
-    /// Test `KEYLEN` with an incorrect number of arguments
-    async fn test_keylen_syntax_error_args_one() {
-        query.push("keylen");
+    async fn test_copy_nil() {
+        query.push("copy");
+        query.push("x");
+        query.push("y");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            Element::RespCode(RespCode::NotFound)
         );
     }
-    async fn test_keylen_syntax_error_args_two() {
-        query.push("keylen");
+    async fn test_copy_syntax_error() {
+        query.push("copy");
         query.push("x");
         query.push("y");
+        query.push("z");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("COPY expects 2 arguments, got 3")
         );
     }
     async fn test_mksnap_disabled() {
         query.push("mksnap");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ErrorString("err-snapshot-disabled".to_owned()))
+            Element::RespCode(RespCode::ErrorString(
+                "101 err-snapshot-disabled".to_owned()
+            ))
         );
     }
     async fn test_mksnap_sanitization() {
@@ -995,7 +1951,7 @@ mod __private {
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::ErrorString(
-                "err-invalid-snapshot-name".to_owned()
+                "103 err-invalid-snapshot-name".to_owned()
             ))
         );
         let mut query = Query::new();
@@ -1004,7 +1960,7 @@ mod __private {
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
             Element::RespCode(RespCode::ErrorString(
-                "err-invalid-snapshot-name".to_owned()
+                "103 err-invalid-snapshot-name".to_owned()
             ))
         );
     }
@@ -1127,14 +2083,128 @@ mod __private {
         query.push("fvnjnvv");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("LSKEYS expects at most 3 arguments, got 4")
+        );
+    }
+    async fn test_scan_in_batches() {
+        query.push("uset");
+        query.push("x");
+        query.push("100");
+        query.push("y");
+        query.push("200");
+        query.push("z");
+        query.push("300");
+        query.push("a");
+        query.push("apples");
+        query.push("b");
+        query.push("burgers");
+        query.push("c");
+        query.push("carrots");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(6)
+        );
+        // the first batch starts a scan
+        let mut query = Query::new();
+        query.push("scan");
+        query.push("0");
+        query.push("4");
+        let mut first = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::NonNullStr(arr)) => arr,
+            _ => panic!("Expected flat string array"),
+        };
+        assert_eq!(first.len(), 5);
+        let cursor = first.remove(0);
+        assert_ne!(cursor, "0");
+        // the second batch has whatever is left
+        let mut query = Query::new();
+        query.push("scan");
+        query.push(cursor);
+        query.push("4");
+        let mut second = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::NonNullStr(arr)) => arr,
+            _ => panic!("Expected flat string array"),
+        };
+        assert_eq!(second.len(), 3);
+        assert_eq!(second.remove(0), "0");
+        let mut keys: Vec<String> = first.into_iter().chain(second).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "b", "c", "x", "y", "z"]);
+    }
+    async fn test_scan_bad_cursor() {
+        query.push("scan");
+        query.push("-1");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        let mut query = Query::new();
+        query.push("scan");
+        query.push("cursor");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_scan_syntax_error() {
+        query.push("scan");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SCAN expects 1 to 2 arguments, got 0")
+        );
+    }
+    async fn test_keys_pattern() {
+        query.push("uset");
+        query.push("user:1");
+        query.push("alice");
+        query.push("user:2");
+        query.push("bob");
+        query.push("user:10");
+        query.push("carol");
+        query.push("session:1");
+        query.push("token");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(4)
+        );
+        let mut query = Query::new();
+        query.push("keys");
+        query.push("user:?");
+        let mut keys = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::NonNullStr(arr)) => arr,
+            _ => panic!("Expected flat string array"),
+        };
+        keys.sort();
+        assert_eq!(keys, vec!["user:1", "user:2"]);
+        let mut query = Query::new();
+        query.push("keys");
+        query.push("user:*");
+        let mut keys = match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::NonNullStr(arr)) => arr,
+            _ => panic!("Expected flat string array"),
+        };
+        keys.sort();
+        assert_eq!(keys, vec!["user:1", "user:10", "user:2"]);
+        let mut query = Query::new();
+        query.push("keys");
+        query.push("nothing*");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::Array(Array::NonNullStr(vec![]))
+        );
+    }
+    async fn test_keys_syntax_error() {
+        query.push("keys");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("KEYS expects 1 argument, got 0")
         );
     }
     async fn test_mpop_syntax_error() {
         query.push("mpop");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("MPOP expects at least 1 argument, got 0")
         );
     }
 
@@ -1179,7 +2249,7 @@ mod __private {
         query.push("pop");
         assert_eq!(
             con.run_query_raw(&query).await.unwrap(),
-            Element::RespCode(RespCode::ActionError)
+            arity_err!("POP expects 1 argument, got 0")
         );
     }
     async fn test_pop_okay() {
@@ -1202,4 +2272,78 @@ mod __private {
             Element::RespCode(RespCode::NotFound)
         );
     }
+    async fn test_randomkey_okay() {
+        setkeys!(
+            con,
+            "x":"100",
+            "y":"200"
+        );
+        query.push("randomkey");
+        match con.run_query_raw(&query).await.unwrap() {
+            Element::String(key) => assert!(key == "x" || key == "y"),
+            other => panic!("Bad response for randomkey: {:?}", other),
+        }
+        let mut query = Query::new();
+        query.push("randomkey");
+        query.push(__MYENTITY__);
+        assert!(matches!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::String(_)
+        ));
+    }
+    async fn test_randomkey_nil() {
+        query.push("randomkey");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+    async fn test_randomkey_syntax_error() {
+        query.push("randomkey");
+        query.push(__MYENTITY__);
+        query.push("extra");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("RANDOMKEY expects at most 1 argument, got 2")
+        );
+    }
+    async fn test_sample_okay() {
+        setkeys!(
+            con,
+            "x":"100",
+            "y":"200",
+            "z":"300"
+        );
+        query.push("sample");
+        query.push("2");
+        let mut keys: Vec<String> = con.run_query(&query).await.unwrap();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 2);
+        assert!(keys
+            .iter()
+            .all(|key| ["x", "y", "z"].contains(&key.as_str())));
+        // can't sample more than we have
+        let mut query = Query::new();
+        query.push("sample");
+        query.push(__MYENTITY__);
+        query.push("100");
+        let keys: Vec<String> = con.run_query(&query).await.unwrap();
+        assert_eq!(keys.len(), 3);
+    }
+    async fn test_sample_bad_count() {
+        query.push("sample");
+        query.push("many");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_sample_syntax_error() {
+        query.push("sample");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            arity_err!("SAMPLE expects 1 to 2 arguments, got 0")
+        );
+    }
 }
@@ -0,0 +1,105 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,bloom)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_bfadd_creates_filter() {
+        let q = query!("BFADD", "seen", "sayan", "nandan");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("BFADD", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("BFEXISTS", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("BFEXISTS", "seen", "nandan");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+    async fn test_bfexists_missing_filter() {
+        let q = query!("BFEXISTS", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_bfreserve_okay() {
+        let q = query!("BFRESERVE", "seen", "0.001", "100");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("BFEXISTS", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("BFADD", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("BFEXISTS", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("EXISTS", "seen");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+    async fn test_bfreserve_overwrite_error() {
+        let q = query!("BFADD", "seen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("BFRESERVE", "seen", "0.01");
+        runeq!(con, q, Element::RespCode(RespCode::OverwriteError));
+    }
+    async fn test_bfreserve_bad_settings() {
+        let q = query!("BFRESERVE", "seen", "one");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("BFRESERVE", "seen", "0.01", "-1");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("BFRESERVE", "seen", "1.5");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+        let q = query!("BFRESERVE", "seen", "0.01", "0");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+    }
+    async fn test_bloom_syntax_error() {
+        let q = query!("BFADD", "seen");
+        runeq!(
+            con,
+            q,
+            arity_err!("BFADD expects at least 2 arguments, got 1")
+        );
+        let q = query!("BFEXISTS", "seen", "sayan", "nandan");
+        runeq!(con, q, arity_err!("BFEXISTS expects 2 arguments, got 3"));
+        let q = query!("BFRESERVE", "seen");
+        runeq!(
+            con,
+            q,
+            arity_err!("BFRESERVE expects 2 to 3 arguments, got 1")
+        );
+    }
+    async fn test_set_model_error() {
+        let q = query!("SET", "seen", "sayan");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,98 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,u64)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_incr_creates_counter() {
+        let q = query!("INCR", "visits");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("INCRBY", "visits", "41");
+        runeq!(con, q, Element::UnsignedInt(42));
+        let q = query!("GET", "visits");
+        runeq!(con, q, Element::UnsignedInt(42));
+    }
+    async fn test_decr_okay() {
+        let q = query!("INCRBY", "stock", "10");
+        runeq!(con, q, Element::UnsignedInt(10));
+        let q = query!("DECR", "stock");
+        runeq!(con, q, Element::UnsignedInt(9));
+        let q = query!("DECRBY", "stock", "9");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_decr_underflow() {
+        let q = query!("DECR", "stock");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("304 counter-underflow".to_owned()))
+        );
+        let q = query!("INCRBY", "stock", "1");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("DECRBY", "stock", "2");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("304 counter-underflow".to_owned()))
+        );
+        // the counter is left untouched
+        let q = query!("GET", "stock");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+    async fn test_incr_overflow() {
+        let q = query!("INCRBY", "big", u64::MAX.to_string());
+        runeq!(con, q, Element::UnsignedInt(u64::MAX));
+        let q = query!("INCR", "big");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("303 counter-overflow".to_owned()))
+        );
+    }
+    async fn test_incrby_bad_delta() {
+        let q = query!("INCRBY", "visits", "-1");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("INCRBY", "visits", "one");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+    async fn test_incrby_syntax_error() {
+        let q = query!("INCRBY", "visits");
+        runeq!(con, q, arity_err!("INCRBY expects 2 arguments, got 1"));
+    }
+    async fn test_get_nil() {
+        let q = query!("GET", "visits");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_set_model_error() {
+        let q = query!("SET", "visits", "100");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
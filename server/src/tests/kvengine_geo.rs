@@ -0,0 +1,127 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,geo)")]
+mod __private {
+    use skytable::{query, types::Array, Element, RespCode};
+
+    async fn test_geoadd_okay() {
+        let q = query!(
+            "GEOADD",
+            "sicily",
+            "13.361389",
+            "38.115556",
+            "palermo",
+            "15.087269",
+            "37.502669",
+            "catania"
+        );
+        runeq!(con, q, Element::UnsignedInt(2));
+        // updating a location doesn't add the member again
+        let q = query!("GEOADD", "sicily", "15.087269", "37.502669", "catania");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_geosearch_okay() {
+        let q = query!(
+            "GEOADD",
+            "sicily",
+            "13.361389",
+            "38.115556",
+            "palermo",
+            "15.087269",
+            "37.502669",
+            "catania"
+        );
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("GEOSEARCH", "sicily", "15", "37", "200", "km");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::NonNullBin(vec![
+                b"catania".to_vec(),
+                b"palermo".to_vec()
+            ]))
+        );
+        let q = query!("GEOSEARCH", "sicily", "15", "37", "100000");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::NonNullBin(vec![b"catania".to_vec()]))
+        );
+        let q = query!("GEOSEARCH", "sicily", "0", "0", "1", "km");
+        runeq!(con, q, Element::Array(Array::NonNullBin(vec![])));
+    }
+    async fn test_geosearch_nil() {
+        let q = query!("GEOSEARCH", "sicily", "15", "37", "200", "km");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_geo_bad_location() {
+        let q = query!("GEOADD", "sicily", "181", "0", "nowhere");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+        let q = query!("GEOADD", "sicily", "0", "89", "north-pole");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+        let q = query!("GEOADD", "sicily", "east", "0", "nowhere");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("GEOSEARCH", "sicily", "0", "0", "-1");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+    }
+    async fn test_geo_syntax_error() {
+        let q = query!("GEOADD", "sicily", "13.361389", "38.115556");
+        runeq!(
+            con,
+            q,
+            arity_err!("GEOADD expects 4, 7, 10, ... arguments, got 3")
+        );
+        let q = query!("GEOSEARCH", "sicily", "15", "37");
+        runeq!(
+            con,
+            q,
+            arity_err!("GEOSEARCH expects 4 to 5 arguments, got 3")
+        );
+        let q = query!("GEOSEARCH", "sicily", "15", "37", "200", "parsecs");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_set_model_error() {
+        let q = query!("SET", "sicily", "palermo");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,map<string,string>)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    // hset tests
+    async fn test_hset_creates_hash() {
+        let q = query!("HSET", "user", "name", "sayan", "city", "kolkata");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("HGET", "user", "name");
+        runeq!(con, q, Element::String("sayan".to_owned()));
+    }
+    async fn test_hset_overwrites_fields() {
+        let q = query!("HSET", "user", "name", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("HSET", "user", "name", "ohsayan", "lang", "rust");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("HGET", "user", "name");
+        runeq!(con, q, Element::String("ohsayan".to_owned()));
+    }
+    async fn test_hset_syntax_error() {
+        let q = query!("HSET", "user", "name");
+        runeq!(
+            con,
+            q,
+            arity_err!("HSET expects 3, 5, 7, ... arguments, got 2")
+        );
+    }
+
+    // hget tests
+    async fn test_hget_nil() {
+        let q = query!("HGET", "user", "name");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("HSET", "user", "name", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("HGET", "user", "city");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // hdel tests
+    async fn test_hdel_okay() {
+        let q = query!("HSET", "user", "name", "sayan", "city", "kolkata");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("HDEL", "user", "city", "lang");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("HGETALL", "user");
+        assert_skyhash_arrayeq!(str, con, q, "name", "sayan");
+    }
+    async fn test_hdel_nil() {
+        let q = query!("HDEL", "user", "name");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // hgetall tests
+    async fn test_hgetall_sorted_by_field() {
+        let q = query!("HSET", "user", "name", "sayan", "city", "kolkata", "lang", "rust");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("HGETALL", "user");
+        assert_skyhash_arrayeq!(str, con, q, "city", "kolkata", "lang", "rust", "name", "sayan");
+    }
+    async fn test_hgetall_nil() {
+        let q = query!("HGETALL", "user");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // sanity tests
+    async fn test_get_model_error() {
+        let q = query!("GET", "user");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
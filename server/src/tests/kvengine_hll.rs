@@ -0,0 +1,96 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,hll)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_pfadd_creates_hll() {
+        let q = query!("PFADD", "visitors", "sayan", "nandan", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFADD", "visitors", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("PFCOUNT", "visitors");
+        runeq!(con, q, Element::UnsignedInt(2));
+    }
+    async fn test_pfadd_without_items() {
+        let q = query!("PFADD", "visitors");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFADD", "visitors");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("PFCOUNT", "visitors");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("EXISTS", "visitors");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+    async fn test_pfcount_missing_hll() {
+        let q = query!("PFCOUNT", "visitors");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_pfcount_union() {
+        let q = query!("PFADD", "monday", "sayan", "nandan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFADD", "tuesday", "nandan", "ohsayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFCOUNT", "monday", "tuesday", "wednesday");
+        runeq!(con, q, Element::UnsignedInt(3));
+    }
+    async fn test_pfmerge_okay() {
+        let q = query!("PFADD", "monday", "sayan", "nandan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFADD", "tuesday", "nandan", "ohsayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("PFMERGE", "week", "monday", "tuesday");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("PFCOUNT", "week");
+        runeq!(con, q, Element::UnsignedInt(3));
+        // the sources are left untouched
+        let q = query!("PFCOUNT", "monday");
+        runeq!(con, q, Element::UnsignedInt(2));
+    }
+    async fn test_hll_syntax_error() {
+        let q = query!("PFCOUNT");
+        runeq!(
+            con,
+            q,
+            arity_err!("PFCOUNT expects at least 1 argument, got 0")
+        );
+        let q = query!("PFMERGE", "week");
+        runeq!(
+            con,
+            q,
+            arity_err!("PFMERGE expects at least 2 arguments, got 1")
+        );
+    }
+    async fn test_set_model_error() {
+        let q = query!("SET", "visitors", "sayan");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
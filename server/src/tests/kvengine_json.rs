@@ -0,0 +1,86 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,json)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    const DOC: &str = r#"{"name": "sayan", "langs": ["rust", "c"], "meta": {"stars": 100}}"#;
+
+    async fn test_set_and_get_json() {
+        let q = query!("SET", "user", DOC);
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("GET", "user");
+        runeq!(con, q, Element::String(DOC.to_owned()));
+    }
+    async fn test_set_invalid_json() {
+        let q = query!("SET", "user", r#"{"name": "sayan""#);
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+        let q = query!("MSET", "a", "1", "b", "two");
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+        // nothing was written
+        let q = query!("EXISTS", "user", "a", "b");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_update_invalid_json() {
+        let q = query!("SET", "user", DOC);
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("UPDATE", "user", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+        let q = query!("USET", "user", "[]");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+    async fn test_jget_okay() {
+        let q = query!("SET", "user", DOC);
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("JGET", "user", "$.name");
+        runeq!(con, q, Element::String(r#""sayan""#.to_owned()));
+        let q = query!("JGET", "user", "$.langs[1]");
+        runeq!(con, q, Element::String(r#""c""#.to_owned()));
+        let q = query!("JGET", "user", "meta");
+        runeq!(con, q, Element::String(r#"{"stars": 100}"#.to_owned()));
+        let q = query!("JGET", "user", "$");
+        runeq!(con, q, Element::String(DOC.to_owned()));
+    }
+    async fn test_jget_nil() {
+        let q = query!("JGET", "user", "$.name");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("SET", "user", DOC);
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("JGET", "user", "$.langs[2]");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("JGET", "user", "$.meta.forks");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_jget_bad_path() {
+        let q = query!("JGET", "user", "$..name");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+    async fn test_jget_syntax_error() {
+        let q = query!("JGET", "user");
+        runeq!(con, q, arity_err!("JGET expects 2 arguments, got 1"));
+    }
+}
@@ -49,7 +49,11 @@ mod __private {
     }
     async fn test_lset_syntax_error() {
         let q = query!("LSET");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(
+            con,
+            q,
+            arity_err!("LSET expects at least 1 argument, got 0")
+        );
     }
     async fn test_lset_overwrite_error() {
         lset!(con, "mylist");
@@ -89,7 +93,7 @@ mod __private {
     /// lget syntax error
     async fn test_lget_with_limit_syntax_error() {
         let q = query!("lget", "mylist", "LIMIT", "100", "200");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LGET LIMIT expects 1 argument, got 2"));
     }
     /// lget limit non-existent key
     async fn test_lget_with_limit_nil() {
@@ -105,7 +109,7 @@ mod __private {
     /// lget len syntax error
     async fn test_lget_with_len_syntax_error() {
         let q = query!("lget", "mysuperlist", "len", "whatthe");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LGET LEN expects no arguments, got 1"));
     }
     /// lget len nil
     async fn test_lget_with_len_nil() {
@@ -125,7 +129,7 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+            Element::RespCode(RespCode::ErrorString("300 bad-list-index".to_owned()))
         )
     }
     /// lget valueat (invalid index)
@@ -147,7 +151,7 @@ mod __private {
     /// lget valueat (syntax error)
     async fn test_lget_with_valueat_syntax_error() {
         let q = query!("lget", "mybadlist", "valueat", "2", "3");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LGET VALUEAT expects 1 argument, got 2"));
     }
     // lget last
     /// lget last with one element
@@ -169,13 +173,13 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("list-is-empty".to_owned()))
+            Element::RespCode(RespCode::ErrorString("301 list-is-empty".to_owned()))
         );
     }
     /// lget last syntax error
     async fn test_lget_last_syntax_error() {
         let q = query!("lget", "mylist", "last", "abcd");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LGET LAST expects no arguments, got 1"));
     }
     // lget first
     /// lget first with one element
@@ -197,13 +201,13 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("list-is-empty".to_owned()))
+            Element::RespCode(RespCode::ErrorString("301 list-is-empty".to_owned()))
         );
     }
     /// lget last syntax error
     async fn test_lget_first_syntax_error() {
         let q = query!("lget", "mylist", "first", "abcd");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LGET FIRST expects no arguments, got 1"));
     }
     // lmod tests
     // lmod push
@@ -226,7 +230,11 @@ mod __private {
     /// lmod push (syntax error)
     async fn test_lmod_syntax_error() {
         let q = query!("lmod", "mylist", "push");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(
+            con,
+            q,
+            arity_err!("LMOD PUSH expects at least 1 argument, got 0")
+        );
     }
     // lmod pop
     /// lmod pop (okay)
@@ -259,7 +267,11 @@ mod __private {
     /// lmod pop (syntax error)
     async fn test_lmod_pop_syntax_error() {
         let q = query!("lmod", "mylist", "pop", "whatthe", "whatthe2");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(
+            con,
+            q,
+            arity_err!("LMOD POP expects at most 1 argument, got 2")
+        );
     }
     // lmod clear
     /// lmod clear (okay)
@@ -276,7 +288,7 @@ mod __private {
     /// lmod clear (syntax error)
     async fn test_lmod_clear_syntax_error() {
         let q = query!("lmod", "mylist", "clear", "unneeded arg");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LMOD CLEAR expects no arguments, got 1"));
     }
     // lmod remove
     /// lmod remove (okay)
@@ -303,7 +315,7 @@ mod __private {
     /// lmod remove (syntax error)
     async fn test_lmod_remove_syntax_error() {
         let q = query!("lmod", "mylist", "remove", "a", "b");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LMOD REMOVE expects 1 argument, got 2"));
     }
     // lmod insert
     /// lmod insert (okay)
@@ -327,9 +339,9 @@ mod __private {
     /// lmod insert (syntax error)
     async fn test_lmod_insert_syntax_error() {
         let q = query!("lmod", "mylist", "insert", "1");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LMOD INSERT expects 2 arguments, got 1"));
         let q = query!("lmod", "mylist", "insert");
-        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        runeq!(con, q, arity_err!("LMOD INSERT expects 2 arguments, got 0"));
     }
     /// lmod insert (present; non-existent index)
     async fn test_lmod_insert_non_existent_index() {
@@ -344,7 +356,7 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+            Element::RespCode(RespCode::ErrorString("300 bad-list-index".to_owned()))
         )
     }
     /// del <list> (existent; non-existent)
@@ -385,7 +397,7 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+            Element::RespCode(RespCode::ErrorString("300 bad-list-index".to_owned()))
         )
     }
 
@@ -401,7 +413,7 @@ mod __private {
         runeq!(
             con,
             q,
-            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+            Element::RespCode(RespCode::ErrorString("300 bad-list-index".to_owned()))
         )
     }
 
@@ -418,6 +430,130 @@ mod __private {
         runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
     }
 
+    // lpush/rpush tests
+    async fn test_rpush_creates_list() {
+        let q = query!("RPUSH", "mylist", "a", "b");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("RPUSH", "mylist", "c");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c");
+    }
+    async fn test_lpush_reverses_order() {
+        lset!(con, "mylist", "c");
+        let q = query!("LPUSH", "mylist", "b", "a");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c");
+    }
+    async fn test_lpush_syntax_error() {
+        let q = query!("LPUSH", "mylist");
+        runeq!(
+            con,
+            q,
+            arity_err!("LPUSH expects at least 2 arguments, got 1")
+        );
+    }
+
+    // type tests
+    async fn test_type_list() {
+        lset!(con, "mylist", "a");
+        let q = query!("TYPE", "mylist");
+        assert_skyhash_arrayeq!(
+            str,
+            con,
+            q,
+            "7",
+            "Keymap { data:(str,list<str>), volatile:true }"
+        );
+    }
+
+    // lpop/rpop tests
+    async fn test_lpop_rpop_okay() {
+        lset!(con, "mylist", "a", "b", "c");
+        let q = query!("LPOP", "mylist");
+        runeq!(con, q, Element::String("a".to_owned()));
+        let q = query!("RPOP", "mylist");
+        runeq!(con, q, Element::String("c".to_owned()));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "b");
+    }
+    async fn test_lpop_empty_list() {
+        lset!(con, "mylist");
+        let q = query!("LPOP", "mylist");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("301 list-is-empty".to_owned()))
+        );
+    }
+    async fn test_rpop_nil() {
+        let q = query!("RPOP", "mylist");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // blpop/brpop tests
+    async fn test_blpop_brpop_okay() {
+        lset!(con, "mylist", "a", "b", "c");
+        let q = query!("BLPOP", "mylist", "1");
+        runeq!(con, q, Element::String("a".to_owned()));
+        let q = query!("BRPOP", "mylist", "1");
+        runeq!(con, q, Element::String("c".to_owned()));
+    }
+    async fn test_blpop_timeout() {
+        lset!(con, "mylist");
+        let q = query!("BLPOP", "mylist", "1");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_blpop_waits_for_push() {
+        let mut worker = skytable::AsyncConnection::new("127.0.0.1", 2003)
+            .await
+            .unwrap();
+        let q = query!(format!("USE {__MYENTITY__}"));
+        assert_eq!(
+            worker.run_query_raw(&q).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let pop = tokio::spawn(async move {
+            worker
+                .run_query_raw(&query!("BLPOP", "mylist", "10"))
+                .await
+                .unwrap()
+        });
+        // give the worker some time to park
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let q = query!("LPUSH", "mylist", "job");
+        runeq!(con, q, Element::UnsignedInt(1));
+        assert_eq!(pop.await.unwrap(), Element::String("job".to_owned()));
+    }
+    async fn test_blpop_syntax_error() {
+        let q = query!("BLPOP", "mylist");
+        runeq!(con, q, arity_err!("BLPOP expects 2 arguments, got 1"));
+        let q = query!("BLPOP", "mylist", "never");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+
+    // lrange tests
+    async fn test_lrange_full_and_negative() {
+        lset!(con, "mylist", "a", "b", "c", "d");
+        let q = query!("LRANGE", "mylist", "0", "-1");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c", "d");
+        let q = query!("LRANGE", "mylist", "-3", "-2");
+        assert_skyhash_arrayeq!(str, con, q, "b", "c");
+    }
+    async fn test_lrange_out_of_range_is_clamped() {
+        lset!(con, "mylist", "a", "b");
+        let q = query!("LRANGE", "mylist", "1", "100");
+        assert_skyhash_arrayeq!(str, con, q, "b");
+        let q = query!("LRANGE", "mylist", "5", "10");
+        runeq!(con, q, Element::Array(Array::NonNullStr(vec![])));
+    }
+    async fn test_lrange_bad_index() {
+        lset!(con, "mylist", "a", "b");
+        let q = query!("LRANGE", "mylist", "0", "last");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+
     // sanity tests
     async fn test_get_model_error() {
         query.push("GET");
@@ -425,7 +561,7 @@ mod __private {
         runeq!(
             con,
             query,
-            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
         );
     }
     async fn test_set_model_error() {
@@ -435,7 +571,7 @@ mod __private {
         runeq!(
             con,
             query,
-            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
         );
     }
     async fn test_update_model_error() {
@@ -445,7 +581,7 @@ mod __private {
         runeq!(
             con,
             query,
-            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
         );
     }
 }
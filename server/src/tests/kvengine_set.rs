@@ -0,0 +1,120 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+macro_rules! sadd {
+    ($con:expr, $setname:expr, $($member:expr),* ; $added:expr) => {
+        let mut q = skytable::Query::from("SADD");
+        q.push($setname);
+        $(q.push($member);)*
+        runeq!($con, q, skytable::Element::UnsignedInt($added));
+    };
+}
+
+#[sky_macros::dbtest_module(table = "(string,set<string>)")]
+mod __private {
+    use skytable::{query, types::Array, Element, RespCode};
+
+    // sadd tests
+    async fn test_sadd_creates_set() {
+        sadd!(con, "myset", "a", "b", "c"; 3);
+        let q = query!("SMEMBERS", "myset");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c");
+    }
+    async fn test_sadd_ignores_duplicates() {
+        sadd!(con, "myset", "b", "a", "b"; 2);
+        sadd!(con, "myset", "a", "c"; 1);
+        let q = query!("SMEMBERS", "myset");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c");
+    }
+    async fn test_sadd_syntax_error() {
+        let q = query!("SADD", "myset");
+        runeq!(
+            con,
+            q,
+            arity_err!("SADD expects at least 2 arguments, got 1")
+        );
+    }
+
+    // srem tests
+    async fn test_srem_okay() {
+        sadd!(con, "myset", "a", "b", "c"; 3);
+        let q = query!("SREM", "myset", "a", "c", "d");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("SMEMBERS", "myset");
+        assert_skyhash_arrayeq!(str, con, q, "b");
+    }
+    async fn test_srem_nil() {
+        let q = query!("SREM", "myset", "a");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // smembers tests
+    async fn test_smembers_nil() {
+        let q = query!("SMEMBERS", "myset");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // algebra tests
+    async fn test_sunion() {
+        sadd!(con, "set1", "a", "b"; 2);
+        sadd!(con, "set2", "b", "c"; 2);
+        let q = query!("SUNION", "set1", "set2", "nosuchset");
+        assert_skyhash_arrayeq!(str, con, q, "a", "b", "c");
+    }
+    async fn test_sinter() {
+        sadd!(con, "set1", "a", "b", "c"; 3);
+        sadd!(con, "set2", "b", "c", "d"; 3);
+        let q = query!("SINTER", "set1", "set2");
+        assert_skyhash_arrayeq!(str, con, q, "b", "c");
+        let q = query!("SINTER", "set1", "nosuchset");
+        runeq!(con, q, Element::Array(Array::NonNullStr(vec![])));
+    }
+    async fn test_sdiff() {
+        sadd!(con, "set1", "a", "b", "c"; 3);
+        sadd!(con, "set2", "b"; 1);
+        sadd!(con, "set3", "c"; 1);
+        let q = query!("SDIFF", "set1", "set2", "set3");
+        assert_skyhash_arrayeq!(str, con, q, "a");
+    }
+
+    // sanity tests
+    async fn test_get_model_error() {
+        let q = query!("GET", "myset");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+    async fn test_lget_model_error() {
+        let q = query!("LGET", "myset");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,140 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,timeseries)")]
+mod __private {
+    use skytable::{
+        query,
+        types::{Array, FlatElement},
+        Element, RespCode,
+    };
+
+    async fn test_ts_add_okay() {
+        let q = query!("TS.ADD", "temp", "1000", "21.5");
+        runeq!(con, q, Element::UnsignedInt(1000));
+        // samples can arrive out of order
+        let q = query!("TS.ADD", "temp", "500", "20");
+        runeq!(con, q, Element::UnsignedInt(500));
+        // action names are case insensitive
+        let q = query!("ts.add", "temp", "1500", "22");
+        runeq!(con, q, Element::UnsignedInt(1500));
+    }
+    async fn test_ts_range_okay() {
+        for (ts, value) in [("1000", "1.5"), ("2000", "2"), ("3000", "-3.25")] {
+            let q = query!("TS.ADD", "temp", ts, value);
+            runeq!(con, q, Element::UnsignedInt(ts.parse().unwrap()));
+        }
+        let q = query!("TS.RANGE", "temp", "1500", "+");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::Flat(vec![
+                FlatElement::UnsignedInt(2000),
+                FlatElement::String("2".to_owned()),
+                FlatElement::UnsignedInt(3000),
+                FlatElement::String("-3.25".to_owned()),
+            ]))
+        );
+        let q = query!("TS.RANGE", "temp", "-", "1000");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::Flat(vec![
+                FlatElement::UnsignedInt(1000),
+                FlatElement::String("1.5".to_owned()),
+            ]))
+        );
+        let q = query!("TS.RANGE", "temp", "4000", "+");
+        runeq!(con, q, Element::Array(Array::Flat(vec![])));
+    }
+    async fn test_ts_last_okay() {
+        let q = query!("TS.ADD", "temp", "2000", "2");
+        runeq!(con, q, Element::UnsignedInt(2000));
+        let q = query!("TS.ADD", "temp", "1000", "1");
+        runeq!(con, q, Element::UnsignedInt(1000));
+        let q = query!("TS.LAST", "temp");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::Flat(vec![
+                FlatElement::UnsignedInt(2000),
+                FlatElement::String("2".to_owned()),
+            ]))
+        );
+    }
+    async fn test_ts_retention() {
+        let q = query!("TS.ADD", "temp", "1000", "1", "RETENTION", "500");
+        runeq!(con, q, Element::UnsignedInt(1000));
+        let q = query!("TS.ADD", "temp", "2000", "2");
+        runeq!(con, q, Element::UnsignedInt(2000));
+        // the first sample is out of the retention period now
+        let q = query!("TS.RANGE", "temp", "-", "+");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::Flat(vec![
+                FlatElement::UnsignedInt(2000),
+                FlatElement::String("2".to_owned()),
+            ]))
+        );
+        let q = query!("TS.ADD", "temp", "1000", "1");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("307 out-of-range".to_owned()))
+        );
+    }
+    async fn test_ts_nil() {
+        let q = query!("TS.RANGE", "temp", "-", "+");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("TS.LAST", "temp");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_ts_bad_sample() {
+        let q = query!("TS.ADD", "temp", "yesterday", "1");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("TS.ADD", "temp", "1000", "warm");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("TS.ADD", "temp", "1000", "NaN");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+    async fn test_ts_syntax_error() {
+        let q = query!("TS.ADD", "temp", "1000");
+        runeq!(con, q, arity_err!("TS.ADD expects 3 or 5 arguments, got 2"));
+        let q = query!("TS.ADD", "temp", "1000", "1", "KEEP", "500");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        let q = query!("TS.RANGE", "temp", "-");
+        runeq!(con, q, arity_err!("TS.RANGE expects 3 arguments, got 2"));
+    }
+    async fn test_set_model_error() {
+        let q = query!("SET", "temp", "21.5");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
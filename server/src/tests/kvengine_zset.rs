@@ -0,0 +1,96 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string,zset<string>)")]
+mod __private {
+    use skytable::{query, types::Array, Element, RespCode};
+
+    // zadd tests
+    async fn test_zadd_creates_zset() {
+        let q = query!("ZADD", "board", "30", "carol", "10", "alice", "20", "bob");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("ZRANGEBYSCORE", "board", "-inf", "+inf");
+        assert_skyhash_arrayeq!(str, con, q, "alice", "bob", "carol");
+    }
+    async fn test_zadd_updates_score() {
+        let q = query!("ZADD", "board", "10", "alice", "20", "bob");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("ZADD", "board", "25", "alice", "5", "dave");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("ZRANGEBYSCORE", "board", "0", "100");
+        assert_skyhash_arrayeq!(str, con, q, "dave", "bob", "alice");
+    }
+    async fn test_zadd_syntax_error() {
+        let q = query!("ZADD", "board", "10");
+        runeq!(
+            con,
+            q,
+            arity_err!("ZADD expects 3, 5, 7, ... arguments, got 2")
+        );
+    }
+    async fn test_zadd_bad_score() {
+        let q = query!("ZADD", "board", "ten", "alice");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("ZADD", "board", "NaN", "alice");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+
+    // zrangebyscore tests
+    async fn test_zrangebyscore_bounds_are_inclusive() {
+        let q = query!("ZADD", "board", "1", "a", "2", "b", "3", "c", "4", "d");
+        runeq!(con, q, Element::UnsignedInt(4));
+        let q = query!("ZRANGEBYSCORE", "board", "2", "3");
+        assert_skyhash_arrayeq!(str, con, q, "b", "c");
+        let q = query!("ZRANGEBYSCORE", "board", "5", "10");
+        runeq!(con, q, Element::Array(Array::NonNullStr(vec![])));
+    }
+    async fn test_zrangebyscore_nil() {
+        let q = query!("ZRANGEBYSCORE", "board", "0", "1");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // zrank tests
+    async fn test_zrank_okay() {
+        let q = query!("ZADD", "board", "30", "carol", "10", "alice", "20", "bob");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("ZRANK", "board", "alice");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("ZRANK", "board", "carol");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("ZRANK", "board", "dave");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+
+    // sanity tests
+    async fn test_smembers_model_error() {
+        let q = query!("SMEMBERS", "board");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+    }
+}
@@ -133,3 +133,38 @@ macro_rules! assert_respcode {
         runeq!($con, $query, ::skytable::Element::RespCode($code))
     };
 }
+
+macro_rules! assert_model_decl {
+    ($con:expr, $entity:expr, $data:expr, $volatile:expr) => {{
+        use ::skytable::types::FlatElement;
+        match $con
+            .run_query_raw(&::skytable::query!(format!("inspect model {}", $entity)))
+            .await
+            .unwrap()
+        {
+            ::skytable::Element::Array(::skytable::types::Array::Flat(fields)) => {
+                assert_eq!(fields.len(), 28);
+                assert_eq!(
+                    &fields[..6],
+                    &[
+                        FlatElement::String("model".to_owned()),
+                        FlatElement::String("keymap".to_owned()),
+                        FlatElement::String("data".to_owned()),
+                        FlatElement::String($data.to_owned()),
+                        FlatElement::String("volatile".to_owned()),
+                        FlatElement::String($volatile.to_string()),
+                    ]
+                );
+            }
+            other => panic!("Bad response for inspect model: {:?}", other),
+        }
+    }};
+}
+
+macro_rules! arity_err {
+    ($message:literal) => {
+        ::skytable::Element::RespCode(::skytable::RespCode::ErrorString(
+            concat!("700 arity-error: ", $message).to_owned(),
+        ))
+    };
+}
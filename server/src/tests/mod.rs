@@ -28,16 +28,39 @@
 
 #[macro_use]
 mod macros;
+mod admission;
 #[cfg(not(feature = "persist-suite"))]
 mod auth;
 mod ddl_tests;
+mod expiry;
+mod handshake;
+mod http;
+mod idle_timeout;
 mod inspect_tests;
 mod kvengine;
+mod kvengine_bloom;
+mod kvengine_counter;
 mod kvengine_encoding;
+mod kvengine_geo;
+mod kvengine_hash;
+mod kvengine_hll;
+mod kvengine_json;
 mod kvengine_list;
+mod kvengine_set;
+mod kvengine_timeseries;
+mod kvengine_zset;
+mod move_keys;
 mod persist;
 mod pipeline;
+mod pubsub;
+mod query_limits;
+mod replication;
+mod script;
 mod snapshot;
+mod snapshot_reads;
+mod strict_utf8;
+mod txn;
+mod watch;
 mod issue_tests;
 
 mod tls {
@@ -57,7 +80,13 @@ mod sys {
         crate::protocol::{LATEST_PROTOCOL_VERSION, LATEST_PROTOCOL_VERSIONSTRING},
         libsky::VERSION,
         sky_macros::dbtest_func as dbtest,
-        skytable::{query, Element, RespCode},
+        skytable::{
+            query,
+            types::{Array, FlatElement},
+            AsyncConnection, Element, RespCode,
+        },
+        std::time::Duration,
+        tokio::time,
     };
 
     #[dbtest]
@@ -131,6 +160,497 @@ mod sys {
             Element::UnsignedInt
         )
     }
+    #[dbtest]
+    async fn sys_metric_connections() {
+        let fields = match con
+            .run_query_raw(&query!("sys", "metric", "connections"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys metric connections: {:?}", other),
+        };
+        let names = ["active", "accepted", "rejected-per-ip", "rejected-rate"]
+            .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        // at least this connection is open
+        assert!(matches!(fields[1], FlatElement::UnsignedInt(active) if active >= 1));
+    }
+    #[dbtest]
+    async fn sys_metric_memory() {
+        let fields = match con
+            .run_query_raw(&query!("sys", "metric", "memory"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys metric memory: {:?}", other),
+        };
+        let names = ["used", "limit", "rejected-writes"]
+            .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        assert!(fields
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|value| matches!(value, FlatElement::UnsignedInt(_))));
+    }
+    #[dbtest]
+    async fn sys_metric_quarantined() {
+        // none of the test tables are corrupted
+        match con
+            .run_query_raw(&query!("sys", "metric", "quarantined"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(tables)) => assert!(tables.is_empty()),
+            other => panic!("Bad response for sys metric quarantined: {:?}", other),
+        }
+    }
+    #[dbtest]
+    async fn sys_metrics() {
+        runeq!(con, query!("heya"), Element::String("HEY!".to_owned()));
+        let metrics = match con.run_query_raw(&query!("sys", "metrics")).await.unwrap() {
+            Element::String(metrics) => metrics,
+            other => panic!("Bad response for sys metrics: {:?}", other),
+        };
+        let lines: Vec<&str> = metrics.lines().collect();
+        assert!(lines.contains(&"# TYPE skytable_query_duration_seconds histogram"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("skytable_queries_total{action=\"HEYA\"}")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("skytable_connections_active ")));
+        runeq!(
+            con,
+            query!("sys", "metrics", "health"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_latency() {
+        runeq!(con, query!("heya"), Element::String("HEY!".to_owned()));
+        let fields = match con
+            .run_query_raw(&query!("sys", "latency", "heya"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys latency: {:?}", other),
+        };
+        let names = ["action", "samples", "p50", "p90", "p99", "max"]
+            .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        assert_eq!(fields[1], FlatElement::String("HEYA".to_owned()));
+        runeq!(
+            con,
+            query!("sys", "latency", "nosuchaction"),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!("sys", "latency", "heya", "extra"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_health() {
+        let fields = match con.run_query_raw(&query!("sys", "health")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys health: {:?}", other),
+        };
+        let names = ["ready", "storage", "snapshots", "writes"]
+            .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        runeq!(
+            con,
+            query!("sys", "health", "now"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_memusage() {
+        let fields = match con.run_query_raw(&query!("sys", "memusage")).await.unwrap() {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys memusage: {:?}", other),
+        };
+        let names = ["used", "arena-blocks", "arena-used", "arena-reserved"]
+            .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        // the blocks in use come out of the chunks that were reserved
+        match (&fields[5], &fields[7]) {
+            (FlatElement::UnsignedInt(used), FlatElement::UnsignedInt(reserved)) => {
+                assert!(used <= reserved)
+            }
+            other => panic!("Bad arena stats: {:?}", other),
+        }
+        runeq!(
+            con,
+            query!("sys", "memusage", "now"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_compact() {
+        // another test may have started one already
+        match con.run_query_raw(&query!("sys", "compact")).await.unwrap() {
+            Element::RespCode(RespCode::Okay) => {}
+            Element::RespCode(RespCode::ErrorString(e)) if e == "111 err-compaction-busy" => {}
+            other => panic!("Bad response for sys compact: {:?}", other),
+        }
+        let fields = match con
+            .run_query_raw(&query!("sys", "compact", "status"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys compact status: {:?}", other),
+        };
+        let names = [
+            "running",
+            "tables",
+            "total",
+            "expired",
+            "freed",
+            "reclaimed",
+            "finished",
+        ]
+        .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        runeq!(
+            con,
+            query!("sys", "compact", "now"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "compact", "status", "now"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_flush() {
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "flush"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "flush", __MYKS__),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "flush", "nosuchks"),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "flush", "a", "b"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_export_import() {
+        // the path is in the export directory of the server
+        let path = format!("tests/{__MYTABLE__}.skyexport");
+        let imported = format!("{__MYKS__}.sysimported");
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "export", __MYENTITY__, path.clone()),
+            Element::RespCode(RespCode::Okay)
+        );
+        // an archive is never overwritten
+        runeq!(
+            con,
+            query!("sys", "export", __MYENTITY__, path.clone()),
+            Element::RespCode(RespCode::ErrorString("205 err-already-exists".to_owned()))
+        );
+        // the table that it was exported from is still around
+        runeq!(
+            con,
+            query!("sys", "import", path.clone()),
+            Element::RespCode(RespCode::ErrorString("205 err-already-exists".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "import", path.clone(), imported.clone()),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!(format!("@{imported}"), "get", "x"),
+            Element::String("100".to_owned())
+        );
+        runeq!(
+            con,
+            query!("sys", "import", format!("{path}.nope")),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!("sys", "export", __MYENTITY__),
+            Element::RespCode(RespCode::ActionError)
+        );
+        // archives can't be written or read outside the export directory
+        runeq!(
+            con,
+            query!("sys", "export", __MYENTITY__, format!("../{__MYTABLE__}.skyexport")),
+            Element::RespCode(RespCode::ErrorString("122 err-bad-export-path".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "import", "/etc/passwd"),
+            Element::RespCode(RespCode::ErrorString("122 err-bad-export-path".to_owned()))
+        );
+    }
+    #[dbtest]
+    async fn sys_client() {
+        let mut victim = AsyncConnection::new("127.0.0.1", 2003).await.unwrap();
+        let id = match victim
+            .run_query_raw(&query!("sys", "client", "id"))
+            .await
+            .unwrap()
+        {
+            Element::UnsignedInt(id) => id,
+            other => panic!("Bad response for sys client id: {:?}", other),
+        };
+        let clients = match con
+            .run_query_raw(&query!("sys", "client", "list"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Recursive(clients)) => clients,
+            other => panic!("Bad response for sys client list: {:?}", other),
+        };
+        // the victim is listed, along with the last action that it ran
+        assert!(clients.iter().any(|client| match client {
+            Element::Array(Array::Flat(fields)) => {
+                fields[1] == FlatElement::UnsignedInt(id)
+                    && fields[9] == FlatElement::String("SYS".to_owned())
+            }
+            _ => false,
+        }));
+        runeq!(
+            con,
+            query!("sys", "client", "kill", id.to_string()),
+            Element::RespCode(RespCode::Okay)
+        );
+        // the victim's connection is closed (give it a moment to notice)
+        time::sleep(Duration::from_millis(100)).await;
+        assert!(victim.run_query_raw(&query!("heya")).await.is_err());
+        runeq!(
+            con,
+            query!("sys", "client", "kill", id.to_string()),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!("sys", "client", "kill", "me"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("sys", "client", "list", "all"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_monitor() {
+        // the other tests don't run queries on this entity, so nothing is pushed to us
+        runeq!(
+            con,
+            query!("sys", "monitor", "on", "sysmonitor.nothing"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "monitor", "off"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "monitor", "off", "sysmonitor"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("sys", "monitor", "maybe"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "monitor"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_requestid() {
+        // (the client can't read the frames, so we leave them off)
+        runeq!(
+            con,
+            query!("sys", "requestid", "off"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "requestid", "maybe"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "requestid"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_reload_tls() {
+        // the test servers have TLS listeners
+        runeq!(
+            con,
+            query!("sys", "reload", "tls"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("sys", "reload", "nothing"),
+            Element::RespCode(RespCode::ErrorString("215 unknown-property".to_owned()))
+        )
+    }
+    #[dbtest]
+    async fn sys_reloadconf() {
+        // the configuration file of the test server doesn't change
+        runeq!(
+            con,
+            query!("sys", "reloadconf"),
+            Element::Array(Array::Flat(vec![]))
+        );
+        runeq!(
+            con,
+            query!("sys", "reloadconf", "now"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
+    #[dbtest]
+    async fn sys_replication_info() {
+        let fields = match con
+            .run_query_raw(&query!("sys", "replication", "info"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(fields)) => fields,
+            other => panic!("Bad response for sys replication info: {:?}", other),
+        };
+        let names = [
+            "role",
+            "primary",
+            "link",
+            "lag-ms",
+            "last-contact-ms",
+            "read-only",
+            "replicas",
+        ]
+        .map(|name| FlatElement::String(name.to_owned()));
+        assert!(fields.iter().step_by(2).eq(names.iter()));
+        // the test servers don't replicate anything
+        assert_eq!(fields[1], FlatElement::String("primary".to_owned()));
+        assert_eq!(fields[5], FlatElement::String("none".to_owned()));
+        assert_eq!(fields[11], FlatElement::String("false".to_owned()));
+        runeq!(
+            con,
+            query!("sys", "replication", "status"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        )
+    }
+    async fn sys_replicaof_no_one() {
+        // the test servers aren't replicas, so there's nothing to stop
+        runeq!(
+            con,
+            query!("sys", "replicaof", "no", "one"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // and no one can hand over to them
+        runeq!(
+            con,
+            query!("sys", "replicaof", "no", "one", "42"),
+            Element::RespCode(RespCode::ErrorString("114 err-failover-failed".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "replicaof", "no", "one", "handover"),
+            Element::RespCode(RespCode::Wrongtype)
+        )
+    }
+    async fn sys_cluster_disabled() {
+        // the slot of a key is the same with or without cluster mode
+        runeq!(
+            con,
+            query!("sys", "cluster", "keyslot", "foo"),
+            Element::UnsignedInt(12182)
+        );
+        // but the test servers don't run in cluster mode
+        runeq!(
+            con,
+            query!("sys", "cluster", "addslots", "0", "1"),
+            Element::RespCode(RespCode::ErrorString("118 err-cluster-disabled".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "cluster", "addslots", "0", "16384"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("sys", "cluster", "migrate", "0", "1", "127.0.0.1:2004"),
+            Element::RespCode(RespCode::ErrorString("118 err-cluster-disabled".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "cluster", "backup", "nightly"),
+            Element::RespCode(RespCode::ErrorString("118 err-cluster-disabled".to_owned()))
+        )
+    }
+    async fn sys_cdc_disabled() {
+        // the test servers don't capture changes
+        runeq!(
+            con,
+            query!("sys", "cdc", "stream"),
+            Element::RespCode(RespCode::ErrorString("121 err-cdc-disabled".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "cdc", "stream", "42"),
+            Element::RespCode(RespCode::ErrorString("121 err-cdc-disabled".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("sys", "cdc", "stream", "latest"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("sys", "cdc", "info"),
+            Element::RespCode(RespCode::ErrorString("121 err-cdc-disabled".to_owned()))
+        );
+        // stopping is always okay
+        runeq!(
+            con,
+            query!("sys", "cdc", "stop"),
+            Element::RespCode(RespCode::Okay)
+        )
+    }
 }
 
 use skytable::{query, Element, RespCode};
@@ -140,6 +660,6 @@ async fn blueql_extra_args() {
     runeq!(
         con,
         query!("use default.default", "extra useless arg"),
-        Element::RespCode(RespCode::ErrorString("bql-invalid-syntax".into()))
+        Element::RespCode(RespCode::ErrorString("604 bql-invalid-syntax".into()))
     );
 }
@@ -0,0 +1,115 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use {
+        libstress::utils,
+        skytable::{query, Element, RespCode},
+    };
+
+    async fn test_move_okay() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!("create model {tblname}(string, string) volatile")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("SET", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("MOVE", "x", format!("{__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("EXISTS", "x"), Element::UnsignedInt(0));
+        // the key is gone, so there's nothing more to move
+        runeq!(
+            con,
+            query!("MOVE", "x", format!("{__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!(format!("use {__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("GET", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_move_overwrite() {
+        runeq!(
+            con,
+            query!("SET", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("MOVE", "x", __MYENTITY__),
+            Element::RespCode(RespCode::OverwriteError)
+        );
+        runeq!(con, query!("GET", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_move_wrong_model() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        runeq!(
+            con,
+            query!(format!(
+                "create model {tblname}(string, list<string>) volatile"
+            )),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("SET", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("MOVE", "x", format!("{__MYKS__}.{tblname}")),
+            Element::RespCode(RespCode::ErrorString("204 wrong-model".to_owned()))
+        );
+        runeq!(con, query!("GET", "x"), Element::String("100".to_owned()));
+    }
+    async fn test_move_nonexistent_table() {
+        runeq!(
+            con,
+            query!("MOVE", "x", "nosuchks.nosuchtbl"),
+            Element::RespCode(RespCode::ErrorString("201 container-not-found".to_owned()))
+        );
+    }
+    async fn test_move_syntax_error() {
+        runeq!(
+            con,
+            query!("MOVE", "x"),
+            arity_err!("MOVE expects 2 arguments, got 1")
+        );
+    }
+}
@@ -63,7 +63,7 @@ mod tests {
             ret,
             vec![
                 Element::String("HEY!".to_owned()),
-                Element::RespCode(RespCode::ActionError)
+                arity_err!("GET expects 1 argument, got 2")
             ]
         );
     }
@@ -76,10 +76,62 @@ mod tests {
         assert_eq!(
             ret,
             vec![
-                Element::RespCode(RespCode::ActionError),
+                arity_err!("MSET expects 2, 4, 6, ... arguments, got 3"),
                 Element::Array(Array::Str(vec![None, None, None])),
                 Element::String("finally".to_owned())
             ]
         )
     }
 }
+
+/// Queries that are sent back to back (without waiting for the responses) should all be
+/// answered, and in order
+#[tokio::test]
+async fn test_back_to_back_queries() {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::{self, Duration},
+    };
+    const QUERIES: &[u8] = b"*2\n4\nheya5\nfirst*2\n4\nheya6\nsecond";
+    const RESPONSES: &[u8] = b"*+5\nfirst*+6\nsecond";
+    let mut stream = TcpStream::connect("127.0.0.1:2003").await.unwrap();
+    stream.write_all(QUERIES).await.unwrap();
+    let mut responses = vec![0; RESPONSES.len()];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut responses))
+        .await
+        .expect("timed out waiting for the responses")
+        .unwrap();
+    assert_eq!(responses, RESPONSES);
+}
+
+/// The responses are held back while the rest of a query is on its way, but only for a little
+/// while: a client that stops half-way through a query still gets the responses so far
+#[tokio::test]
+async fn test_held_responses_are_flushed() {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::{self, Duration},
+    };
+    const QUERIES: &[u8] = b"*2\n4\nheya5\nfirst*2\n4\nheya6\nsecond";
+    const FIRST_RESPONSE: &[u8] = b"*+5\nfirst";
+    const SECOND_RESPONSE: &[u8] = b"*+6\nsecond";
+    // the first query, and a part of the second
+    let (head, tail) = QUERIES.split_at(24);
+    let mut stream = TcpStream::connect("127.0.0.1:2003").await.unwrap();
+    stream.write_all(head).await.unwrap();
+    let mut response = vec![0; FIRST_RESPONSE.len()];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut response))
+        .await
+        .expect("the first response was held back")
+        .unwrap();
+    assert_eq!(response, FIRST_RESPONSE);
+    stream.write_all(tail).await.unwrap();
+    let mut response = vec![0; SECOND_RESPONSE.len()];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut response))
+        .await
+        .expect("timed out waiting for the second response")
+        .unwrap();
+    assert_eq!(response, SECOND_RESPONSE);
+}
@@ -0,0 +1,72 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_subscribe_unsubscribe() {
+        let q = query!("SUBSCRIBE", "test_subscribe_a", "test_subscribe_b");
+        runeq!(con, q, Element::UnsignedInt(2));
+        // subscribing again has no effect
+        let q = query!("SUBSCRIBE", "test_subscribe_a");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("UNSUBSCRIBE", "test_subscribe_a", "test_subscribe_c");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("UNSUBSCRIBE");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_publish_without_subscribers() {
+        let q = query!("PUBLISH", "test_publish_without_subscribers", "hello");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_subscribe_syntax_error() {
+        let q = query!("SUBSCRIBE");
+        runeq!(
+            con,
+            q,
+            arity_err!("SUBSCRIBE expects at least 1 argument, got 0")
+        );
+    }
+    async fn test_publish_syntax_error() {
+        let q = query!("PUBLISH", "test_publish_syntax_error");
+        runeq!(con, q, arity_err!("PUBLISH expects 2 arguments, got 1"));
+    }
+    async fn test_notify_on_off() {
+        match con.run_query_raw(&query!("NOTIFY", "ON")).await.unwrap() {
+            Element::String(channel) => assert!(channel.starts_with("__notify__:")),
+            other => panic!("expected a channel, got {:?}", other),
+        }
+        let q = query!("NOTIFY", "OFF");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("NOTIFY", "MAYBE");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+    }
+}
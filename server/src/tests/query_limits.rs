@@ -0,0 +1,106 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the query limits. These talk to the server over a plain socket since the client
+//! library can't send malformed queries
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{self, Duration},
+};
+
+const RESP_OKAY: &[u8] = b"*!0\n";
+const RESP_HEYA: &[u8] = b"*+4\nHEY!";
+const RESP_QUERY_TOO_LARGE: &[u8] = b"*!702 query-too-large\n";
+
+/// Send a query and check its response
+async fn roundtrip(stream: &mut TcpStream, send: &[u8], expect: &[u8]) {
+    stream.write_all(send).await.unwrap();
+    let mut response = vec![0; expect.len()];
+    time::timeout(Duration::from_secs(10), stream.read_exact(&mut response))
+        .await
+        .expect("timed out waiting for the response")
+        .unwrap();
+    assert_eq!(response, expect);
+}
+
+async fn connect() -> TcpStream {
+    TcpStream::connect("127.0.0.1:2003").await.unwrap()
+}
+
+#[tokio::test]
+async fn test_query_size_limit() {
+    let mut con = self::connect().await;
+    roundtrip(&mut con, b"*3\n3\nsys12\nmaxquerysize2\n32", RESP_OKAY).await;
+    // the declared size is enough to reject the query (we don't wait for the data)
+    roundtrip(&mut con, b"*2\n4\nheya100\n", RESP_QUERY_TOO_LARGE).await;
+    // the connection is still usable
+    roundtrip(&mut con, b"*1\n4\nheya", RESP_HEYA).await;
+}
+
+#[tokio::test]
+async fn test_query_size_limit_unterminated_line() {
+    let mut con = self::connect().await;
+    roundtrip(&mut con, b"*3\n3\nsys12\nmaxquerysize2\n32", RESP_OKAY).await;
+    // a size line that never ends
+    let mut query = vec![b'*'];
+    query.extend_from_slice(&[b'1'; 40]);
+    roundtrip(&mut con, &query, RESP_QUERY_TOO_LARGE).await;
+}
+
+#[tokio::test]
+async fn test_query_args_limit() {
+    let mut con = self::connect().await;
+    roundtrip(&mut con, b"*3\n3\nsys12\nmaxqueryargs1\n2", RESP_OKAY).await;
+    roundtrip(&mut con, b"*3\n", RESP_QUERY_TOO_LARGE).await;
+    roundtrip(&mut con, b"*2\n4\nheya2\nhi", b"*+2\nhi").await;
+    // the number of queries in a pipeline is limited too
+    roundtrip(&mut con, b"$3\n", RESP_QUERY_TOO_LARGE).await;
+}
+
+#[tokio::test]
+async fn test_query_limits_cannot_be_raised() {
+    let mut con = self::connect().await;
+    roundtrip(
+        &mut con,
+        b"*3\n3\nsys12\nmaxquerysize20\n99999999999999999999",
+        b"*!7\n",
+    )
+    .await;
+    roundtrip(
+        &mut con,
+        b"*3\n3\nsys12\nmaxquerysize19\n9999999999999999999",
+        b"*!307 out-of-range\n",
+    )
+    .await;
+    roundtrip(
+        &mut con,
+        b"*3\n3\nsys12\nmaxqueryargs1\n0",
+        b"*!307 out-of-range\n",
+    )
+    .await;
+}
@@ -0,0 +1,83 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the primary's end of replication. These talk to the server over a plain socket
+//! since the frames that follow `SYS SYNC` aren't Skyhash
+
+use {
+    skytable::{query, AsyncConnection, Element, RespCode},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+        time::{self, Duration},
+    },
+};
+
+/// Read a frame, returning its kind and its payload
+async fn read_frame(stream: &mut BufReader<TcpStream>) -> (u8, Vec<u8>) {
+    time::timeout(Duration::from_secs(10), async {
+        let kind = stream.read_u8().await.unwrap();
+        let len = stream.read_u64_le().await.unwrap();
+        let mut payload = vec![0; len as usize];
+        stream.read_exact(&mut payload).await.unwrap();
+        (kind, payload)
+    })
+    .await
+    .expect("timed out waiting for a frame")
+}
+
+#[tokio::test]
+async fn test_sync_sends_copy_and_feeds_writes() {
+    const KEY: &[u8] = b"replication_feed_key";
+    let mut stream = BufReader::new(TcpStream::connect("127.0.0.1:2003").await.unwrap());
+    stream.write_all(b"H2.0\n*2\n3\nSYS4\nSYNC").await.unwrap();
+    let mut response = [0; 9];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, b"H2.0\n*!0\n");
+    // the copy of the data comes first
+    let (kind, _) = read_frame(&mut stream).await;
+    assert_eq!(kind, b'K');
+    while read_frame(&mut stream).await.0 != b'S' {}
+    // and then the writes
+    let mut con = AsyncConnection::new("127.0.0.1", 2003).await.unwrap();
+    let q = query!("SET", "replication_feed_key", "value");
+    assert_eq!(
+        con.run_query_raw(&q).await.unwrap(),
+        Element::RespCode(RespCode::Okay)
+    );
+    loop {
+        let (kind, payload) = read_frame(&mut stream).await;
+        assert_ne!(kind, b'R', "the feed was closed");
+        if kind == b'W' && payload.windows(KEY.len()).any(|window| window == KEY) {
+            break;
+        }
+    }
+    let q = query!("DEL", "replication_feed_key");
+    assert_eq!(
+        con.run_query_raw(&q).await.unwrap(),
+        Element::UnsignedInt(1)
+    );
+}
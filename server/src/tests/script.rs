@@ -0,0 +1,97 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_eval_okay() {
+        let q = query!(
+            "SCRIPT",
+            "LOAD",
+            "test_eval_okay",
+            "SET $1 $2; USET last $1"
+        );
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("EVAL", "test_eval_okay", "user", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("GET", "user");
+        runeq!(con, q, Element::String("sayan".to_owned()));
+        let q = query!("GET", "last");
+        runeq!(con, q, Element::String("user".to_owned()));
+    }
+    async fn test_eval_is_atomic() {
+        let q = query!("SCRIPT", "LOAD", "test_eval_is_atomic", "USET a 1; SET b 2");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("SET", "b", "0");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        // `SET b` fails, so `USET a` is rolled back
+        let q = query!("EVAL", "test_eval_is_atomic");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("402 txn-aborted".to_owned()))
+        );
+        let q = query!("EXISTS", "a");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_eval_wrong_arity() {
+        let q = query!("SCRIPT", "LOAD", "test_eval_wrong_arity", "SET $1 $2");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("EVAL", "test_eval_wrong_arity", "x");
+        runeq!(con, q, arity_err!("EVAL expects 3 arguments, got 2"));
+    }
+    async fn test_eval_not_found() {
+        let q = query!("EVAL", "test_eval_not_found");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("410 script-not-found".to_owned()))
+        );
+    }
+    async fn test_script_load_bad_script() {
+        let q = query!("SCRIPT", "LOAD", "test_script_load_bad_script", "GET $1");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("411 bad-script".to_owned()))
+        );
+    }
+    async fn test_script_drop() {
+        let q = query!("SCRIPT", "LOAD", "test_script_drop", "DEL $1");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("SCRIPT", "DROP", "test_script_drop");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("SCRIPT", "DROP", "test_script_drop");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("EVAL", "test_script_drop", "x");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("410 script-not-found".to_owned()))
+        );
+    }
+}
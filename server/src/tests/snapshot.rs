@@ -26,10 +26,14 @@
 
 use {
     sky_macros::dbtest_func as dbtest,
-    skytable::{query, Element, RespCode},
+    skytable::{
+        query,
+        types::{Array, FlatElement},
+        Element, RespCode,
+    },
 };
 
-const SNAPSHOT_DISABLED: &str = "err-snapshot-disabled";
+const SNAPSHOT_DISABLED: &str = "101 err-snapshot-disabled";
 
 #[dbtest]
 async fn snapshot_fail_because_local_disabled() {
@@ -45,7 +49,7 @@ async fn rsnap_okay() {
     loop {
         match con.run_query_raw(query!("mksnap", "myremo")).await.unwrap() {
             Element::RespCode(RespCode::Okay) => break,
-            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("err-snapshot-busy") => {}
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
             x => panic!("snapshot failed: {:?}", x),
         }
     }
@@ -61,7 +65,7 @@ async fn remote_snapshot_okay_with_local_enabled() {
     loop {
         match con.run_query_raw(query!("mksnap", "myremo")).await.unwrap() {
             Element::RespCode(RespCode::Okay) => break,
-            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("err-snapshot-busy") => {}
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
             x => panic!("snapshot failed: {:?}", x),
         }
     }
@@ -72,18 +76,84 @@ async fn remote_snapshot_fail_because_already_exists() {
     loop {
         match con.run_query_raw(query!("mksnap", "dupe")).await.unwrap() {
             Element::RespCode(RespCode::Okay) => break,
-            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("err-snapshot-busy") => {}
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
             x => panic!("snapshot failed: {:?}", x),
         }
     }
     loop {
         match con.run_query_raw(query!("mksnap", "dupe")).await.unwrap() {
             Element::RespCode(RespCode::ErrorString(estr)) => match estr.as_str() {
-                "err-snapshot-busy" => {}
-                "duplicate-snapshot" => break,
+                "100 err-snapshot-busy" => {}
+                "102 duplicate-snapshot" => break,
                 _ => panic!("Got error string: {estr} instead"),
             },
             x => panic!("snapshot failed: {:?}", x),
         }
     }
 }
+
+#[dbtest(port = 2007, skip_if_cfg = "persist-suite")]
+async fn snapshot_list_and_delete() {
+    loop {
+        match con
+            .run_query_raw(query!("mksnap", "todelete"))
+            .await
+            .unwrap()
+        {
+            Element::RespCode(RespCode::Okay) => break,
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
+            x => panic!("snapshot failed: {:?}", x),
+        }
+    }
+    let snapshots = loop {
+        match con
+            .run_query_raw(query!("sys", "snapshot", "list"))
+            .await
+            .unwrap()
+        {
+            Element::Array(Array::Flat(snapshots)) => break snapshots,
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
+            x => panic!("snapshot list failed: {:?}", x),
+        }
+    };
+    assert!(snapshots.chunks(2).any(|snapshot| snapshot
+        == [
+            FlatElement::String("todelete".to_owned()),
+            FlatElement::String("remote".to_owned())
+        ]));
+    loop {
+        match con
+            .run_query_raw(query!("sys", "snapshot", "delete", "todelete"))
+            .await
+            .unwrap()
+        {
+            Element::RespCode(RespCode::Okay) => break,
+            Element::RespCode(RespCode::ErrorString(estr)) if estr.eq("100 err-snapshot-busy") => {}
+            x => panic!("snapshot deletion failed: {:?}", x),
+        }
+    }
+    runeq!(
+        con,
+        query!("sys", "snapshot", "delete", "todelete"),
+        Element::RespCode(RespCode::NotFound)
+    );
+}
+
+#[dbtest]
+async fn snapshot_restore_missing() {
+    runeq!(
+        con,
+        query!("sys", "snapshot", "restore", "nosuchsnapshot"),
+        Element::RespCode(RespCode::NotFound)
+    );
+    runeq!(
+        con,
+        query!("sys", "snapshot", "list", "extra"),
+        Element::RespCode(RespCode::ActionError)
+    );
+    runeq!(
+        con,
+        query!("sys", "snapshot", "restore"),
+        Element::RespCode(RespCode::ActionError)
+    );
+}
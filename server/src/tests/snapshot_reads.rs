@@ -0,0 +1,104 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use skytable::{query, types::Array, Element, RespCode};
+
+    async fn test_snapshot_read_is_point_in_time() {
+        runeq!(
+            con,
+            query!("MSET", "x", "100", "y", "200"),
+            Element::UnsignedInt(2)
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "BEGIN"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // writes continue, but the snapshot doesn't see them
+        runeq!(
+            con,
+            query!("UPDATE", "x", "1000"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("DEL", "y"), Element::UnsignedInt(1));
+        runeq!(
+            con,
+            query!("SET", "z", "300"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "READ", "x", "y", "z"),
+            Element::Array(Array::Str(vec![
+                Some("100".to_owned()),
+                Some("200".to_owned()),
+                None
+            ]))
+        );
+        runeq!(con, query!("GET", "x"), Element::String("1000".to_owned()));
+        runeq!(
+            con,
+            query!("SNAPSHOT", "END"),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+    async fn test_snapshot_read_without_snapshot() {
+        runeq!(
+            con,
+            query!("SNAPSHOT", "READ", "x"),
+            Element::RespCode(RespCode::ErrorString("105 no-snapshot".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "END"),
+            Element::RespCode(RespCode::ErrorString("105 no-snapshot".to_owned()))
+        );
+    }
+    async fn test_snapshot_syntax_error() {
+        runeq!(
+            con,
+            query!("SNAPSHOT"),
+            arity_err!("SNAPSHOT expects at least 1 argument, got 0")
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "BEGIN", "x"),
+            arity_err!("SNAPSHOT BEGIN expects no arguments, got 1")
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "READ"),
+            arity_err!("SNAPSHOT READ expects at least 1 argument, got 0")
+        );
+        runeq!(
+            con,
+            query!("SNAPSHOT", "LATER"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(binary, binary)")]
+mod __private {
+    use skytable::{query, types::RawString, Element, RespCode};
+
+    async fn test_strict_utf8_responses() {
+        runeq!(
+            con,
+            query!("set", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("get", "x"), Element::Binstr(b"100".to_vec()));
+        runeq!(
+            con,
+            query!("sys", "strictutf8", "on"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+        runeq!(
+            con,
+            query!("sys", "strictutf8", "off"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("get", "x"), Element::Binstr(b"100".to_vec()));
+    }
+    async fn test_strict_utf8_rejects_binary() {
+        runeq!(
+            con,
+            query!("sys", "strictutf8", "on"),
+            Element::RespCode(RespCode::Okay)
+        );
+        push!(
+            query,
+            "set",
+            "x",
+            RawString::from(b"Hello \xF0\x90\x80World".to_vec())
+        );
+        runeq!(con, query, Element::RespCode(RespCode::EncodingError));
+    }
+    async fn test_strict_utf8_replaces_invalid_sequences() {
+        push!(
+            query,
+            "set",
+            "x",
+            RawString::from(b"Hello \xF0\x90\x80World".to_vec())
+        );
+        runeq!(con, query, Element::RespCode(RespCode::Okay));
+        runeq!(
+            con,
+            query!("sys", "strictutf8", "on"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::String("Hello \u{FFFD}World".to_owned())
+        );
+    }
+    async fn test_strict_utf8_bad_option() {
+        runeq!(
+            con,
+            query!("sys", "strictutf8", "maybe"),
+            Element::RespCode(RespCode::ErrorString("Unknown action".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for transactions
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_txn_commit() {
+        assert_okay!(con, query!("multi"));
+        assert_okay!(con, query!("set", "x", "100"));
+        assert_okay!(con, query!("uset", "y", "200"));
+        // nothing is applied until exec
+        assert_okay!(con, query!("discard"));
+        assert_okay!(con, query!("multi"));
+        assert_okay!(con, query!("set", "x", "100"));
+        assert_okay!(con, query!("uset", "y", "200"));
+        assert_okay!(con, query!("exec"));
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+        runeq!(con, query!("get", "y"), Element::String("200".to_owned()));
+    }
+    async fn test_txn_discard() {
+        assert_okay!(con, query!("multi"));
+        assert_okay!(con, query!("set", "x", "100"));
+        assert_okay!(con, query!("discard"));
+        assert_respcode!(con, query!("get", "x"), RespCode::NotFound);
+    }
+    async fn test_txn_rollback() {
+        setkeys!(con, "x":"100");
+        assert_okay!(con, query!("multi"));
+        assert_okay!(con, query!("uset", "y", "200"));
+        assert_okay!(con, query!("del", "x"));
+        // x won't exist when this runs, so the update will fail
+        assert_okay!(con, query!("update", "x", "300"));
+        assert_respcode!(
+            con,
+            query!("exec"),
+            RespCode::ErrorString("402 txn-aborted".into())
+        );
+        runeq!(con, query!("get", "x"), Element::String("100".to_owned()));
+        assert_respcode!(con, query!("get", "y"), RespCode::NotFound);
+    }
+    async fn test_txn_unsupported_action() {
+        assert_okay!(con, query!("multi"));
+        assert_respcode!(
+            con,
+            query!("get", "x"),
+            RespCode::ErrorString("403 txn-unsupported-action".into())
+        );
+        runeq!(
+            con,
+            query!("set", "x"),
+            arity_err!("SET expects 2 arguments, got 1")
+        );
+        assert_okay!(con, query!("discard"));
+    }
+    async fn test_txn_nested() {
+        assert_okay!(con, query!("multi"));
+        assert_respcode!(
+            con,
+            query!("multi"),
+            RespCode::ErrorString("400 txn-already-active".into())
+        );
+        assert_okay!(con, query!("discard"));
+    }
+    async fn test_txn_not_active() {
+        assert_respcode!(
+            con,
+            query!("exec"),
+            RespCode::ErrorString("401 txn-not-active".into())
+        );
+        assert_respcode!(
+            con,
+            query!("discard"),
+            RespCode::ErrorString("401 txn-not-active".into())
+        );
+    }
+}
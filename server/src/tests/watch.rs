@@ -0,0 +1,64 @@
+/*
+ * Created on Thu Oct 15 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use {
+        skytable::{query, AsyncConnection, Element, RespCode},
+        std::time::Duration,
+        tokio::time,
+    };
+
+    async fn test_watch_timeout() {
+        let q = query!("WATCH", "x", "1");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_watch_wakes_up_on_change() {
+        let mut watcher = AsyncConnection::new("127.0.0.1", 2003).await.unwrap();
+        let q = query!(format!("USE {__MYENTITY__}"));
+        assert_eq!(
+            watcher.run_query_raw(&q).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let watch = tokio::spawn(async move {
+            watcher
+                .run_query_raw(&query!("WATCH", "x", "10"))
+                .await
+                .unwrap()
+        });
+        // give the watcher some time to park
+        time::sleep(Duration::from_millis(500)).await;
+        let q = query!("SET", "x", "100");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        assert_eq!(watch.await.unwrap(), Element::String("set".to_owned()));
+    }
+    async fn test_watch_syntax_error() {
+        let q = query!("WATCH", "x");
+        runeq!(con, q, arity_err!("WATCH expects 2 arguments, got 1"));
+        let q = query!("WATCH", "x", "soon");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+    }
+}
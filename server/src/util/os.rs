@@ -37,10 +37,12 @@ use {
 #[cfg(unix)]
 mod unix {
     use {
-        libc::{rlimit, RLIMIT_NOFILE},
+        libc::{c_int, c_void, rlimit, socklen_t, RLIMIT_NOFILE},
         std::{
             future::Future,
             io::Error as IoError,
+            mem,
+            os::unix::io::{AsRawFd, RawFd},
             pin::Pin,
             task::{Context, Poll},
         },
@@ -95,6 +97,47 @@ mod unix {
         let _ = ResourceLimit::get().unwrap();
     }
 
+    /// Set an integer socket option
+    fn setsockopt(fd: RawFd, level: c_int, option: c_int, value: c_int) -> Result<(), IoError> {
+        let ret = unsafe {
+            // UNSAFE(@ohsayan): The pointer and the length are of the value on the stack
+            libc::setsockopt(
+                fd,
+                level,
+                option,
+                &value as *const c_int as *const c_void,
+                mem::size_of::<c_int>() as socklen_t,
+            )
+        };
+        if ret != 0 {
+            Err(IoError::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Turn on TCP keepalive for a socket. Where the platform lets us, the first probe is sent
+    /// once the connection has been idle for `idle` seconds (otherwise, the system default is
+    /// used)
+    pub fn set_tcp_keepalive(socket: &impl AsRawFd, idle: u64) -> Result<(), IoError> {
+        let fd = socket.as_raw_fd();
+        setsockopt(fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1)?;
+        // Linux doesn't take anything above 32767 seconds
+        let idle = idle.min(i16::MAX as u64) as c_int;
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, idle)?;
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, idle)?;
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios"
+        )))]
+        let _ = idle;
+        Ok(())
+    }
+
     pub struct TerminationSignal {
         sigint: Signal,
         sigterm: Signal,
@@ -133,6 +176,12 @@ mod windows {
         tokio::signal::windows::{ctrl_break, ctrl_c, CtrlBreak, CtrlC},
     };
 
+    /// TCP keepalive isn't supported on Windows (the config warns about this), so this does
+    /// nothing
+    pub fn set_tcp_keepalive<S>(_socket: &S, _idle: u64) -> crate::IoResult<()> {
+        Ok(())
+    }
+
     pub struct TerminationSignal {
         ctrl_c: CtrlC,
         ctrl_break: CtrlBreak,
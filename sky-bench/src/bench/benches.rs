@@ -28,7 +28,7 @@
 
 use {
     super::{
-        report::{AggregateReport, SingleReport},
+        report::{self, AggregateReport, SingleReport},
         validation, vec_with_cap, BenchmarkConfig, LoopMonitor,
     },
     crate::error::BResult,
@@ -38,6 +38,12 @@ use {
     std::{
         io::{Read, Write},
         net::{Shutdown, TcpStream},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Instant,
     },
 };
 
@@ -243,3 +249,109 @@ pub fn bench_get(
         reports,
     )
 }
+
+/// Benchmark GET latencies under a mixed load. Half of the connections (rounded up) run GETs
+/// for all the keys while the rest keep running UPDATEs on the same keys, and the time taken
+/// by every GET is recorded to report the percentiles
+pub fn bench_mixed(
+    keys: &[Vec<u8>],
+    new_value: &[u8],
+    bench_config: &BenchmarkConfig,
+    create_table: &[u8],
+    reports: &mut AggregateReport,
+) -> BResult<()> {
+    let readers = bench_config.server.connections().div_ceil(2).max(1);
+    let writers = bench_config.server.connections().saturating_sub(readers);
+    let mut get_packets = vec_with_cap(bench_config.query_count())?;
+    let mut update_packets = vec_with_cap(bench_config.query_count())?;
+    keys.iter().for_each(|key| {
+        get_packets.push(
+            Query::from("get")
+                .arg(RawString::from(key.clone()))
+                .into_raw_query()
+                .into_boxed_slice(),
+        );
+        update_packets.push(
+            Query::from("update")
+                .arg(RawString::from(key.clone()))
+                .arg(RawString::from(new_value.to_owned()))
+                .into_raw_query()
+                .into_boxed_slice(),
+        );
+    });
+    let (get_packets, update_packets) = (Arc::new(get_packets), Arc::new(update_packets));
+    // every reader runs GETs for its own chunk of the keys
+    let chunk = get_packets.len().div_ceil(readers);
+    let mut latencies = vec_with_cap(bench_config.query_count() * bench_config.runs())?;
+    for _ in 0..bench_config.runs() {
+        let done = Arc::new(AtomicBool::new(false));
+        let writer_threads: Vec<_> = (0..writers)
+            .map(|_| {
+                let (bench_config, create_table) = (bench_config.clone(), create_table.to_owned());
+                let (packets, done) = (update_packets.clone(), done.clone());
+                thread::spawn(move || {
+                    let (mut con, mut buf) = init_connection_and_buf(
+                        bench_config.server.host(),
+                        bench_config.server.port(),
+                        create_table,
+                        validation::RESPCODE_OKAY.len(),
+                    );
+                    for packet in packets.iter().cycle() {
+                        if done.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        con.write_all(packet).unwrap();
+                        con.read_exact(&mut buf).unwrap();
+                        assert_eq!(buf, validation::RESPCODE_OKAY);
+                    }
+                    con.shutdown(Shutdown::Both).unwrap();
+                })
+            })
+            .collect();
+        let reader_threads: Vec<_> = (0..readers)
+            .map(|reader| {
+                let (bench_config, create_table) = (bench_config.clone(), create_table.to_owned());
+                let packets = get_packets.clone();
+                thread::spawn(move || {
+                    let (mut con, mut buf) = init_connection_and_buf(
+                        bench_config.server.host(),
+                        bench_config.server.port(),
+                        create_table,
+                        validation::calculate_response_size(bench_config.kvsize()),
+                    );
+                    let start = (reader * chunk).min(packets.len());
+                    let end = (start + chunk).min(packets.len());
+                    let mut times = Vec::with_capacity(end - start);
+                    for packet in &packets[start..end] {
+                        let now = Instant::now();
+                        con.write_all(packet).unwrap();
+                        con.read_exact(&mut buf).unwrap();
+                        times.push(now.elapsed().as_nanos() as u64);
+                    }
+                    con.shutdown(Shutdown::Both).unwrap();
+                    times
+                })
+            })
+            .collect();
+        for reader in reader_threads {
+            latencies.extend(reader.join().unwrap());
+        }
+        done.store(true, Ordering::Relaxed);
+        for writer in writer_threads {
+            writer.join().unwrap();
+        }
+    }
+    latencies.sort_unstable();
+    for (name, pct) in [
+        ("get-mixed-p50", 50_f64),
+        ("get-mixed-p99", 99_f64),
+        ("get-mixed-p99.9", 99.9),
+        ("get-mixed-max", 100_f64),
+    ] {
+        reports.push_latency(SingleReport::new(
+            name,
+            report::percentile(&latencies, pct) as f64,
+        ));
+    }
+    Ok(())
+}
@@ -244,6 +244,18 @@ pub fn run_bench(servercfg: &ServerConfig, bench_config: BenchmarkConfig) -> BRe
     binfo!("Benchmarking GET ...");
     benches::bench_get(&keys, &bench_config, &switch_table, &mut reports)?;
 
+    // bench get latencies under a mixed load
+    if bench_config.mixed() {
+        binfo!("Benchmarking GET latencies under a mixed load ...");
+        benches::bench_mixed(
+            &keys,
+            &new_updated_key,
+            &bench_config,
+            &switch_table,
+            &mut reports,
+        )?;
+    }
+
     // remove all test data
     binfo!("Finished benchmarks. Cleaning up ...");
     let r: Element = misc_connection.run_query(Query::from("drop model default.tmpbench force"))?;
@@ -254,7 +266,7 @@ pub fn run_bench(servercfg: &ServerConfig, bench_config: BenchmarkConfig) -> BRe
     if config::should_output_messages() {
         // normal output
         println!("===========RESULTS===========");
-        let (maxpad, reports) = reports.finish();
+        let (maxpad, reports, latencies) = reports.finish();
         for report in reports {
             let padding = " ".repeat(maxpad - report.name().len());
             println!(
@@ -264,6 +276,15 @@ pub fn run_bench(servercfg: &ServerConfig, bench_config: BenchmarkConfig) -> BRe
                 report.stat(),
             );
         }
+        for report in latencies {
+            let padding = " ".repeat(maxpad - report.name().len());
+            println!(
+                "{}{} {:.3}µs",
+                report.name().to_uppercase(),
+                padding,
+                report.stat() / 1000_f64,
+            );
+        }
         println!("=============================");
     } else {
         // JSON
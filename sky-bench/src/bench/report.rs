@@ -48,6 +48,8 @@ impl SingleReport {
 
 pub struct AggregateReport {
     names: Vec<SingleReport>,
+    /// the latencies (in nanoseconds), which are reported as they are
+    latencies: Vec<SingleReport>,
     query_count: usize,
 }
 
@@ -55,18 +57,23 @@ impl AggregateReport {
     pub fn new(query_count: usize) -> Self {
         Self {
             names: Vec::new(),
+            latencies: Vec::new(),
             query_count,
         }
     }
     pub fn push(&mut self, report: SingleReport) {
         self.names.push(report)
     }
+    pub fn push_latency(&mut self, report: SingleReport) {
+        self.latencies.push(report)
+    }
     pub(crate) fn into_json(self) -> String {
-        let (_, report) = self.finish();
+        let (_, mut report, latencies) = self.finish();
+        report.extend(latencies);
         serde_json::to_string(&report).unwrap()
     }
 
-    pub(crate) fn finish(self) -> (usize, Vec<SingleReport>) {
+    pub(crate) fn finish(self) -> (usize, Vec<SingleReport>, Vec<SingleReport>) {
         let mut maxpad = self.names[0].name.len();
         let mut reps = self.names;
         reps.iter_mut().for_each(|rep| {
@@ -77,6 +84,37 @@ impl AggregateReport {
                 maxpad = rep.name.len();
             }
         });
-        (maxpad, reps)
+        self.latencies.iter().for_each(|rep| {
+            if rep.name.len() > maxpad {
+                maxpad = rep.name.len();
+            }
+        });
+        (maxpad, reps, self.latencies)
+    }
+}
+
+/// Returns the latency below which `pct` percent of the sorted `latencies` fall (or 0 if there
+/// are no latencies)
+pub fn percentile(latencies: &[u64], pct: f64) -> u64 {
+    if latencies.is_empty() {
+        return 0;
+    }
+    let rank = ((pct / 100_f64) * latencies.len() as f64).ceil() as usize;
+    latencies[rank.saturating_sub(1).min(latencies.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::percentile;
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<u64> = (1..=1000).collect();
+        assert_eq!(percentile(&latencies, 50_f64), 500);
+        assert_eq!(percentile(&latencies, 99_f64), 990);
+        assert_eq!(percentile(&latencies, 99.9), 999);
+        assert_eq!(percentile(&latencies, 100_f64), 1000);
+        assert_eq!(percentile(&[42], 99.9), 42);
+        assert_eq!(percentile(&[], 99_f64), 0);
     }
 }
@@ -73,6 +73,14 @@ pub struct Cli {
     )]
     pub json: bool,
 
+    #[arg(
+        short = 'm',
+        long = "mixed",
+        help = "Also measures GET latencies while half of the clients run UPDATEs",
+        default_value_t = false
+    )]
+    pub mixed: bool,
+
     #[arg(long, help="Print help information", action=ArgAction::Help)]
     pub help: Option<bool>,
 }
@@ -95,6 +103,7 @@ mod tests {
         assert_eq!(cli.kvsize, 3);
         assert_eq!(cli.query_count, 100_000);
         assert!(!cli.json);
+        assert!(!cli.mixed);
     }
 
     #[test]
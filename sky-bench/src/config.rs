@@ -57,6 +57,7 @@ pub struct BenchmarkConfig {
     kvsize: usize,
     queries: usize,
     runs: usize,
+    mixed: bool,
 }
 
 impl BenchmarkConfig {
@@ -69,6 +70,9 @@ impl BenchmarkConfig {
     pub fn runs(&self) -> usize {
         self.runs
     }
+    pub fn mixed(&self) -> bool {
+        self.mixed
+    }
 }
 
 pub fn should_output_messages() -> bool {
@@ -87,6 +91,7 @@ impl From<(&ServerConfig, &Cli)> for BenchmarkConfig {
             queries: cli.query_count,
             kvsize: cli.kvsize,
             runs: cli.runs,
+            mixed: cli.mixed,
         }
     }
 }
@@ -43,7 +43,11 @@
 //!     - `__MYENTITY__` - `String` with entity
 //!
 
-use {proc_macro::TokenStream, quote::quote, syn::Lit};
+use {
+    proc_macro::TokenStream,
+    quote::quote,
+    syn::{parse::Parser, punctuated::Punctuated, Lit, Token},
+};
 
 mod dbtest_fn;
 mod dbtest_mod;
@@ -102,7 +106,8 @@ pub fn dbtest_func(args: TokenStream, item: TokenStream) -> TokenStream {
 
 #[proc_macro]
 /// Get a compile time respcode/respstring array. For example, if you pass: "Unknown action",
-/// it will return: `!14\nUnknown Action\n`
+/// it will return: `!14\nUnknown Action\n`. The string can be preceded by an error code: if you
+/// pass: 201, "container-not-found", it will return: `!201 container-not-found\n`
 pub fn compiled_eresp_array(tokens: TokenStream) -> TokenStream {
     _get_eresp_array(tokens, false)
 }
@@ -114,11 +119,26 @@ pub fn compiled_eresp_array_v1(tokens: TokenStream) -> TokenStream {
     _get_eresp_array(tokens, true)
 }
 
+/// Get the payload of a respcode/respstring. This is either just a string (`"Unknown action"`)
+/// or an error code followed by a string (`201, "container-not-found"`), in which case the
+/// payload is `201 container-not-found`
+fn get_eresp_payload(tokens: TokenStream) -> String {
+    let args = Punctuated::<Lit, Token![,]>::parse_terminated
+        .parse(tokens)
+        .expect("Expected a string literal, optionally preceded by an error code");
+    let mut args = args.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(Lit::Str(st)), None, None) => st.value(),
+        (Some(Lit::Int(code)), Some(Lit::Str(st)), None) => {
+            let code: u16 = code.base10_parse().expect("Expected an error code");
+            format!("{code} {}", st.value())
+        }
+        _ => panic!("Expected a string literal, optionally preceded by an error code"),
+    }
+}
+
 fn _get_eresp_array(tokens: TokenStream, sizeline: bool) -> TokenStream {
-    let payload_str = match syn::parse_macro_input!(tokens as Lit) {
-        Lit::Str(st) => st.value(),
-        _ => panic!("Expected a string literal"),
-    };
+    let payload_str = get_eresp_payload(tokens);
     let mut processed = quote! {
         b'!',
     };